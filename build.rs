@@ -0,0 +1,18 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    compile_protos();
+}
+
+// generated code lands in OUT_DIR, protoc comes vendored so nothing needs installing
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc should be available");
+    // build scripts are single threaded
+    unsafe { std::env::set_var("PROTOC", protoc) };
+
+    println!("cargo:rerun-if-changed=proto/analysis.proto");
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/analysis.proto"], &["proto"])
+        .expect("failed to compile proto/analysis.proto");
+}
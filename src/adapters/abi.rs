@@ -0,0 +1,65 @@
+use tiny_keccak::{Hasher, Keccak};
+
+// small ethereum helpers shared by the ens resolver and order signing
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    let mut out = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut out);
+    out
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// accepts an optional 0x prefix
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_start_matches("0x");
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+// a 20 byte address left padded to one abi word
+pub fn address_word(address: &str) -> Option<[u8; 32]> {
+    let bytes = from_hex(address)?;
+    if bytes.len() != 20 {
+        return None;
+    }
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&bytes);
+    Some(word)
+}
+
+pub fn u64_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+// a base 10 uint256 like a clob token id, None if it isn't one or overflows
+pub fn decimal_word(text: &str) -> Option<[u8; 32]> {
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut word = [0u8; 32];
+    for digit in text.chars() {
+        let mut carry = digit.to_digit(10)?;
+        for byte in word.iter_mut().rev() {
+            let value = *byte as u32 * 10 + carry;
+            *byte = (value & 0xff) as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(word)
+}
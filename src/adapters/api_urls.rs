@@ -0,0 +1,55 @@
+use crate::error::{HttpError, Result};
+use reqwest::Url;
+use serde::Deserialize;
+
+pub const GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
+pub const CLOB_API_URL: &str = "https://clob.polymarket.com";
+pub const DATA_API_URL: &str = "https://data-api.polymarket.com";
+
+// base urls of the polymarket apis, the [api] section of config.toml
+// pointed elsewhere they reach a proxy, a mirror or a mock server instead
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiUrls {
+    pub gamma: String,
+    pub clob: String,
+    pub data: String,
+}
+
+impl Default for ApiUrls {
+    fn default() -> Self {
+        Self {
+            gamma: GAMMA_API_URL.to_string(),
+            clob: CLOB_API_URL.to_string(),
+            data: DATA_API_URL.to_string(),
+        }
+    }
+}
+
+impl ApiUrls {
+    // checked once at startup so a typo fails before the first request instead of as a confusing http error
+    pub fn validated(self) -> Result<Self> {
+        Ok(Self {
+            gamma: validate("gamma", &self.gamma)?,
+            clob: validate("clob", &self.clob)?,
+            data: validate("data", &self.data)?,
+        })
+    }
+}
+
+// http or https with a host, paths and queries get appended so the url can't carry its own query
+// a trailing slash is dropped, every path joined on starts with one
+pub fn validate(api: &str, url: &str) -> Result<String> {
+    let invalid = |reason: &str| HttpError::InvalidConfig(format!("{} api url {}: {}", api, url, reason));
+    let parsed = Url::parse(url.trim()).map_err(|e| invalid(&e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(invalid("scheme must be http or https").into());
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(invalid("missing host").into());
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(invalid("base url can't have a query or fragment").into());
+    }
+    Ok(url.trim().trim_end_matches('/').to_string())
+}
@@ -0,0 +1,252 @@
+use crate::adapters::HttpClient;
+use crate::error::{DataError, HttpError, Result};
+use crate::standard_data::models::Transaction;
+use crate::paths;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const BLOCKS_FILE: &str = "blocks.json";
+// one sampled header per day of 2s blocks, interpolating between them is off by minutes at most
+const SAMPLE_SPACING: u64 = 43_200;
+// polygon's target block time, only used when there aren't two samples to interpolate between
+const SECONDS_PER_BLOCK: f64 = 2.0;
+// header requests in flight at once while filling in a range
+const RPC_CONCURRENCY: usize = 8;
+
+// polygon block number -> unix timestamp, sampled from block headers over json-rpc and cached in
+// ~/.polymarket-explorer/blocks.json, blocks between samples are interpolated
+pub struct BlockIndex {
+    http_client: HttpClient,
+    rpc: String,
+    cache_path: PathBuf,
+    samples: Mutex<BTreeMap<u64, i64>>,
+}
+
+impl BlockIndex {
+    // an unreadable cache is dropped, it only saves lookups
+    pub fn new(http_client: HttpClient, rpc: impl Into<String>) -> Self {
+        let cache_path = Self::default_path();
+        let samples = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        Self {
+            http_client,
+            rpc: rpc.into(),
+            cache_path,
+            samples: Mutex::new(samples),
+        }
+    }
+
+    // blocks.json in the platform cache dir
+    pub fn default_path() -> PathBuf {
+        paths::cache_file(BLOCKS_FILE)
+    }
+
+    // estimated time of a block, samples the range around it first if needed
+    pub async fn timestamp(&self, block: u64) -> Result<Option<i64>> {
+        self.ensure_range(block, block).await?;
+        Ok(interpolate(&self.samples()?, block))
+    }
+
+    // first block at or after a unix timestamp, what a "last n days" cutoff turns into
+    pub async fn block_at(&self, timestamp: i64) -> Result<Option<u64>> {
+        let (latest_block, latest_timestamp) = self.latest().await?;
+        if timestamp >= latest_timestamp {
+            return Ok(Some(latest_block));
+        }
+
+        // guess from the block time, then sample a day either side and refine
+        let guess = latest_block.saturating_sub(((latest_timestamp - timestamp) as f64 / SECONDS_PER_BLOCK) as u64);
+        self.ensure_range(guess.saturating_sub(SAMPLE_SPACING), guess + SAMPLE_SPACING).await?;
+        Ok(invert(&self.samples()?, timestamp))
+    }
+
+    // give every transaction without a timestamp an interpolated one
+    // api rows have no block number (0) but always carry their own timestamp, so they are left alone
+    pub async fn fill_timestamps(&self, transactions: &mut [Transaction]) -> Result<()> {
+        let untimed = transactions.iter().filter(|tx| tx.timestamp.is_none() && tx.block_number > 0);
+        let (Some(low), Some(high)) = (
+            untimed.clone().map(|tx| tx.block_number).min(),
+            untimed.map(|tx| tx.block_number).max(),
+        ) else {
+            return Ok(());
+        };
+
+        self.ensure_range(low, high).await?;
+        let samples = self.samples()?;
+        for tx in transactions.iter_mut().filter(|tx| tx.timestamp.is_none() && tx.block_number > 0) {
+            tx.timestamp = interpolate(&samples, tx.block_number);
+        }
+        Ok(())
+    }
+
+    // first block at or after each timestamp, the whole span is sampled once instead of per timestamp
+    pub async fn blocks_at(&self, timestamps: &[i64]) -> Result<Vec<Option<u64>>> {
+        let (Some(earliest), Some(latest)) = (timestamps.iter().min(), timestamps.iter().max()) else {
+            return Ok(Vec::new());
+        };
+        let (Some(low), Some(high)) = (self.block_at(*earliest).await?, self.block_at(*latest).await?) else {
+            return Ok(vec![None; timestamps.len()]);
+        };
+
+        self.ensure_range(low, high).await?;
+        let samples = self.samples()?;
+        Ok(timestamps.iter().map(|timestamp| invert(&samples, *timestamp)).collect())
+    }
+
+    // put every api row (block 0) on the chain at the first block at or after its timestamp
+    pub async fn fill_blocks(&self, transactions: &mut [Transaction]) -> Result<()> {
+        let unplaced: Vec<&mut Transaction> = transactions.iter_mut().filter(|tx| tx.block_number == 0 && tx.timestamp.is_some()).collect();
+        let timestamps: Vec<i64> = unplaced.iter().filter_map(|tx| tx.timestamp).collect();
+        let blocks = self.blocks_at(&timestamps).await?;
+        for (tx, block) in unplaced.into_iter().zip(blocks) {
+            if let Some(block) = block {
+                tx.block_number = block;
+            }
+        }
+        Ok(())
+    }
+
+    // sample every spacing boundary covering [low, high] that isn't cached yet, then save
+    async fn ensure_range(&self, low: u64, high: u64) -> Result<()> {
+        let (latest_block, _) = self.latest().await?;
+        let first = low / SAMPLE_SPACING * SAMPLE_SPACING;
+        let last = high.div_ceil(SAMPLE_SPACING) * SAMPLE_SPACING;
+
+        let missing: Vec<u64> = {
+            let samples = self.samples()?;
+            (first..=last)
+                .step_by(SAMPLE_SPACING as usize)
+                .filter(|block| *block > 0 && *block <= latest_block && !samples.contains_key(block))
+                .collect()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let fetched: Vec<(u64, Option<i64>)> = futures::stream::iter(missing)
+            .map(|block| async move { Ok::<_, crate::error::AppError>((block, self.block_timestamp(block).await?)) })
+            .buffer_unordered(RPC_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.extend(fetched.into_iter().filter_map(|(block, timestamp)| Some((block, timestamp?))));
+        }
+
+        // best effort, a read only home dir shouldn't break the analysis
+        let _ = self.save();
+        Ok(())
+    }
+
+    // the chain head, always fetched fresh and added to the samples
+    async fn latest(&self) -> Result<(u64, i64)> {
+        let head = self.rpc_call("eth_blockNumber", json!([])).await?;
+        let block = parse_quantity(&head).ok_or_else(|| rpc_error("eth_blockNumber", format!("bad block number {}", head)))?;
+        let timestamp = self.block_timestamp(block).await?
+            .ok_or_else(|| rpc_error("eth_getBlockByNumber", format!("no header for head block {}", block)))?;
+
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.insert(block, timestamp);
+        }
+        Ok((block, timestamp))
+    }
+
+    // None when the node doesn't have the block
+    async fn block_timestamp(&self, block: u64) -> Result<Option<i64>> {
+        let header = self.rpc_call("eth_getBlockByNumber", json!([format!("0x{:x}", block), false])).await?;
+        if header.is_null() {
+            return Ok(None);
+        }
+
+        let timestamp = header.get("timestamp")
+            .and_then(parse_quantity)
+            .ok_or_else(|| rpc_error("eth_getBlockByNumber", format!("block {} has no timestamp", block)))?;
+        Ok(Some(timestamp as i64))
+    }
+
+    async fn rpc_call(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut response: Value = self.http_client.post_json(&self.rpc, &body).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(rpc_error(method, error.to_string()).into());
+        }
+        response.get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| rpc_error(method, "response has no result".to_string()).into())
+    }
+
+    fn samples(&self) -> Result<BTreeMap<u64, i64>> {
+        self.samples.lock()
+            .map(|samples| samples.clone())
+            .map_err(|_| DataError::Corrupt("block index lock poisoned".to_string()).into())
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.cache_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let text = serde_json::to_string(&self.samples()?).map_err(|e| DataError::Corrupt(e.to_string()))?;
+        fs::write(&self.cache_path, text)?;
+        Ok(())
+    }
+}
+
+fn rpc_error(method: &str, message: String) -> HttpError {
+    HttpError::Rpc { method: method.to_string(), message }
+}
+
+// json-rpc quantities are 0x prefixed hex strings
+fn parse_quantity(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.trim_start_matches("0x"), 16).ok()
+}
+
+// straight line between the samples either side, past the ends the nearest pair's slope carries on
+fn interpolate(samples: &BTreeMap<u64, i64>, block: u64) -> Option<i64> {
+    if let Some(timestamp) = samples.get(&block) {
+        return Some(*timestamp);
+    }
+
+    let ((low_block, low_ts), (high_block, high_ts)) = bracket(samples, |sample, _| sample < block)?;
+    let seconds_per_block = (high_ts - low_ts) as f64 / (high_block - low_block) as f64;
+    Some((low_ts as f64 + (block as f64 - low_block as f64) * seconds_per_block).round() as i64)
+}
+
+// the inverse, first block at or after the timestamp
+fn invert(samples: &BTreeMap<u64, i64>, timestamp: i64) -> Option<u64> {
+    let ((low_block, low_ts), (high_block, high_ts)) = bracket(samples, |_, sample_ts| sample_ts < timestamp)?;
+    if high_ts == low_ts {
+        return Some(low_block);
+    }
+
+    let blocks_per_second = (high_block - low_block) as f64 / (high_ts - low_ts) as f64;
+    let block = low_block as f64 + (timestamp - low_ts) as f64 * blocks_per_second;
+    Some(block.ceil().max(0.0) as u64)
+}
+
+// the two samples around a point, `before` tells which samples sit below it
+// with a single sample the block time constant stands in for the second one
+fn bracket(samples: &BTreeMap<u64, i64>, before: impl Fn(u64, i64) -> bool) -> Option<((u64, i64), (u64, i64))> {
+    let points: Vec<(u64, i64)> = samples.iter().map(|(block, ts)| (*block, *ts)).collect();
+    match points.len() {
+        0 => None,
+        1 => {
+            let (block, ts) = points[0];
+            Some(((block, ts), (block + 1, ts + SECONDS_PER_BLOCK as i64)))
+        }
+        len => {
+            let split = points.iter().filter(|(block, ts)| before(*block, *ts)).count().clamp(1, len - 1);
+            Some((points[split - 1], points[split]))
+        }
+    }
+}
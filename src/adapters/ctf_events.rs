@@ -0,0 +1,259 @@
+use crate::adapters::HttpClient;
+use crate::adapters::abi::{from_hex, keccak256, to_hex};
+use crate::error::{HttpError, Result};
+use crate::standard_data::models::Collateral;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// gnosis conditional tokens, every polymarket share is minted, merged and redeemed here
+const CTF_CONTRACT: &str = "0x4d97dcd97ec945f40cf65f87097ace5ea0476045";
+// the exchanges split and merge to settle matched orders, those already show up as fills
+// neg risk markets are split by the adapter on the user's behalf and aren't covered
+const EXCHANGE_CONTRACTS: [&str; 3] = [
+    // ctf exchange
+    "0x4bfb41d5b3570defd03c39a9a4d8de6bd8b8982e",
+    // neg risk ctf exchange
+    "0xc5d563a36ae78145c45a50134d48a1215220f80a",
+    // neg risk adapter
+    "0xd91e80cf2e7be2e162c6513ced06f1dd0da35296",
+];
+const SPLIT_EVENT: &str = "PositionSplit(address,address,bytes32,bytes32,uint256[],uint256)";
+const MERGE_EVENT: &str = "PositionsMerge(address,address,bytes32,bytes32,uint256[],uint256)";
+const REDEEM_EVENT: &str = "PayoutRedemption(address,address,bytes32,bytes32,uint256[],uint256)";
+// blocks per eth_getLogs call, public polygon nodes refuse much wider ranges
+const LOG_CHUNK_BLOCKS: u64 = 10_000;
+const USDC_DECIMALS: f64 = 1_000_000.0;
+// a binary market's outcomes as index sets, yes is bit 0 and no bit 1
+const FULL_SET: [u64; 2] = [1, 2];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CtfOperation {
+    // usdc in, one yes and one no share out per dollar
+    Split,
+    // the reverse, a yes and a no share back into a dollar
+    Merge,
+    // shares of a resolved market cashed in for their payout
+    Redeem,
+}
+
+impl CtfOperation {
+    // the transaction action it's stored as
+    pub fn action(&self) -> &'static str {
+        match self {
+            CtfOperation::Split => "SPLIT",
+            CtfOperation::Merge => "MERGE",
+            CtfOperation::Redeem => "REDEEM",
+        }
+    }
+}
+
+// one split, merge or redemption by a wallet
+#[derive(Debug, Clone, Serialize)]
+pub struct CtfEvent {
+    pub operation: CtfOperation,
+    pub account: String,
+    pub condition_id: String,
+    // in the collateral, full sets for splits and merges, the payout for redemptions
+    pub amount: f64,
+    pub collateral: Collateral,
+    pub block: u64,
+    pub transaction_hash: String,
+    pub log_index: u32,
+}
+
+// reads splits, merges and redemptions from the ctf contract's logs over polygon json-rpc
+pub struct CtfEventReader {
+    http_client: HttpClient,
+    rpc: String,
+}
+
+impl CtfEventReader {
+    pub fn new(http_client: HttpClient, rpc: impl Into<String>) -> Self {
+        Self { http_client, rpc: rpc.into() }
+    }
+
+    pub async fn head_block(&self) -> Result<u64> {
+        let head = self.rpc_call("eth_blockNumber", json!([])).await?;
+        parse_quantity(&head).ok_or_else(|| rpc_error("eth_blockNumber", format!("bad block number {}", head)).into())
+    }
+
+    // a market's splits and merges of full sets in [low, high], its redemptions too when asked for
+    // redemptions don't index the condition id, so those are every market's, filtered here
+    pub async fn market_events(&self, condition_id: &str, low: u64, high: u64, redemptions: bool) -> Result<Vec<CtfEvent>> {
+        let condition = condition_id.to_lowercase();
+        let top_level = format!("0x{}", to_hex(&[0u8; 32]));
+
+        let mut events = Vec::new();
+        let mut from = low;
+        while from <= high {
+            let to = from.saturating_add(LOG_CHUNK_BLOCKS - 1).min(high);
+            let filter = json!({
+                "address": CTF_CONTRACT,
+                "fromBlock": format!("0x{:x}", from),
+                "toBlock": format!("0x{:x}", to),
+                "topics": [[topic(SPLIT_EVENT), topic(MERGE_EVENT)], null, top_level, condition],
+            });
+            for log in self.logs(filter).await? {
+                events.extend(parse_split_or_merge(&log)?);
+            }
+
+            if redemptions {
+                let filter = json!({
+                    "address": CTF_CONTRACT,
+                    "fromBlock": format!("0x{:x}", from),
+                    "toBlock": format!("0x{:x}", to),
+                    "topics": [topic(REDEEM_EVENT), null, null, top_level],
+                });
+                for log in self.logs(filter).await? {
+                    events.extend(parse_redemption(&log)?.filter(|event| event.condition_id == condition));
+                }
+            }
+            from = to + 1;
+        }
+
+        events.retain(|event| !EXCHANGE_CONTRACTS.contains(&event.account.as_str()));
+        events.sort_by_key(|event| (event.block, event.log_index));
+        Ok(events)
+    }
+
+    async fn logs(&self, filter: Value) -> Result<Vec<Value>> {
+        match self.rpc_call("eth_getLogs", json!([filter])).await? {
+            Value::Array(logs) => Ok(logs),
+            other => Err(rpc_error("eth_getLogs", format!("expected a list of logs, got {}", other)).into()),
+        }
+    }
+
+    async fn rpc_call(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut response: Value = self.http_client.post_json(&self.rpc, &body).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(rpc_error(method, error.to_string()).into());
+        }
+        response.get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| rpc_error(method, "response has no result".to_string()).into())
+    }
+}
+
+fn rpc_error(method: &str, message: String) -> HttpError {
+    HttpError::Rpc { method: method.to_string(), message }
+}
+
+fn topic(signature: &str) -> String {
+    format!("0x{}", to_hex(&keccak256(signature.as_bytes())))
+}
+
+fn parse_quantity(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.trim_start_matches("0x"), 16).ok()
+}
+
+// a log's topics, data words and position, None for anything malformed
+struct RawLog {
+    topics: Vec<Vec<u8>>,
+    words: Vec<Vec<u8>>,
+    block: u64,
+    transaction_hash: String,
+    log_index: u32,
+}
+
+impl RawLog {
+    fn parse(log: &Value) -> Option<Self> {
+        let topics = log.get("topics")?.as_array()?
+            .iter()
+            .map(|t| from_hex(t.as_str()?).filter(|t| t.len() == 32))
+            .collect::<Option<Vec<_>>>()?;
+        let data = from_hex(log.get("data")?.as_str()?)?;
+        if !data.len().is_multiple_of(32) {
+            return None;
+        }
+        Some(Self {
+            topics,
+            words: data.chunks(32).map(<[u8]>::to_vec).collect(),
+            block: parse_quantity(log.get("blockNumber")?)?,
+            transaction_hash: log.get("transactionHash")?.as_str()?.to_lowercase(),
+            log_index: parse_quantity(log.get("logIndex")?)? as u32,
+        })
+    }
+
+    fn address(&self, topic: usize) -> Option<String> {
+        Some(format!("0x{}", to_hex(&self.topics.get(topic)?[12..])))
+    }
+
+    // an address held in a data word rather than a topic
+    fn word_address(&self, word: usize) -> Option<String> {
+        Some(format!("0x{}", to_hex(&self.words.get(word)?[12..])))
+    }
+
+    // the uint256[] whose offset is in the given data word, entries past u64 don't occur in index sets
+    fn index_sets(&self, offset_word: usize) -> Option<Vec<u64>> {
+        let start = word_u64(self.words.get(offset_word)?)? as usize / 32;
+        let len = word_u64(self.words.get(start)?)? as usize;
+        (start + 1..start + 1 + len).map(|i| word_u64(self.words.get(i)?)).collect()
+    }
+
+    // collateral amount in a data word, both usdcs have 6 decimals and anything past 16 bytes would be more than exists
+    fn usdc(&self, word: usize) -> Option<f64> {
+        let raw = u128::from_be_bytes(self.words.get(word)?[16..].try_into().ok()?);
+        Some(raw as f64 / USDC_DECIMALS)
+    }
+}
+
+fn word_u64(word: &[u8]) -> Option<u64> {
+    if word[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(word[24..].try_into().ok()?))
+}
+
+// stakeholder, parent and condition in the topics, collateral, partition and amount in the data
+// only splits into or merges from the full yes/no set are positions that can be traded
+// collateral polymarket doesn't settle in makes someone else's condition, those are dropped
+fn parse_split_or_merge(log: &Value) -> Result<Option<CtfEvent>> {
+    let unreadable = || rpc_error("eth_getLogs", format!("unreadable split or merge log {}", log));
+    let raw = RawLog::parse(log).filter(|raw| raw.topics.len() == 4).ok_or_else(unreadable)?;
+    let operation = if to_hex(&raw.topics[0]) == topic(SPLIT_EVENT)[2..] { CtfOperation::Split } else { CtfOperation::Merge };
+    let partition = raw.index_sets(1).ok_or_else(unreadable)?;
+    let Some(collateral) = Collateral::from_address(&raw.word_address(0).ok_or_else(unreadable)?) else {
+        return Ok(None);
+    };
+    if partition != FULL_SET {
+        return Ok(None);
+    }
+
+    Ok(Some(CtfEvent {
+        operation,
+        account: raw.address(1).ok_or_else(unreadable)?,
+        condition_id: format!("0x{}", to_hex(&raw.topics[3])),
+        amount: raw.usdc(2).ok_or_else(unreadable)?,
+        collateral,
+        block: raw.block,
+        transaction_hash: raw.transaction_hash,
+        log_index: raw.log_index,
+    }))
+}
+
+// redeemer, collateral and parent in the topics, condition, index sets and payout in the data
+// a redemption that paid nothing only burned losing shares
+fn parse_redemption(log: &Value) -> Result<Option<CtfEvent>> {
+    let unreadable = || rpc_error("eth_getLogs", format!("unreadable redemption log {}", log));
+    let raw = RawLog::parse(log).filter(|raw| raw.topics.len() == 4 && raw.words.len() >= 3).ok_or_else(unreadable)?;
+    let payout = raw.usdc(2).ok_or_else(unreadable)?;
+    let Some(collateral) = Collateral::from_address(&raw.address(2).ok_or_else(unreadable)?) else {
+        return Ok(None);
+    };
+    if payout <= 0.0 {
+        return Ok(None);
+    }
+
+    Ok(Some(CtfEvent {
+        operation: CtfOperation::Redeem,
+        account: raw.address(1).ok_or_else(unreadable)?,
+        condition_id: format!("0x{}", to_hex(&raw.words[0])),
+        amount: payout,
+        collateral,
+        block: raw.block,
+        transaction_hash: raw.transaction_hash,
+        log_index: raw.log_index,
+    }))
+}
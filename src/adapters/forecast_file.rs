@@ -0,0 +1,108 @@
+use crate::adapters::HttpClient;
+use crate::error::{AppError, Result};
+use crate::standard_data::models::{ExternalForecast, Market};
+use crate::standard_data::providers::ExternalForecastProvider;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fs;
+use std::path::Path;
+
+// source name for rows without one
+const DEFAULT_FORECAST_SOURCE: &str = "model";
+
+// model outputs from a csv file or url, read once
+// header row with market and probability columns, source and updated_at are optional:
+//   market,probability,source,updated_at
+//   will-the-mock-candidate-win-the-primary,0.62,my model,2026-10-01
+// market is a condition id or market slug, probability 0 to 1 or a percentage like 62%
+pub struct ForecastFile {
+    forecasts: Vec<ExternalForecast>,
+}
+
+impl ForecastFile {
+    // http and https locations are fetched, anything else is a local path
+    pub async fn load(location: &str, http_client: &HttpClient) -> Result<Self> {
+        let text = match location.starts_with("http://") || location.starts_with("https://") {
+            true => http_client.get_text(location).await?,
+            false => fs::read_to_string(Path::new(location))?,
+        };
+        Ok(Self { forecasts: parse_forecasts(&text, location)? })
+    }
+}
+
+#[async_trait]
+impl ExternalForecastProvider for ForecastFile {
+    async fn get_forecasts(&self, market: &Market) -> Result<Vec<ExternalForecast>> {
+        Ok(self.forecasts
+            .iter()
+            .filter(|f| f.market.eq_ignore_ascii_case(&market.condition_id) || f.market.eq_ignore_ascii_case(&market.slug))
+            .cloned()
+            .collect())
+    }
+}
+
+// one csv line, double quoted fields may hold commas and "" for a quote
+fn fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields.into_iter().map(|field| field.trim().to_string()).collect()
+}
+
+fn parse_probability(raw: &str) -> Option<f64> {
+    let probability = match raw.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().ok()? / 100.0,
+        None => raw.parse().ok()?,
+    };
+    (0.0..=1.0).contains(&probability).then_some(probability)
+}
+
+fn parse_time(raw: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(raw) {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|time| time.and_utc())
+}
+
+// a bad row fails the whole file, a silently skipped forecast would look like a model with no view
+pub fn parse_forecasts(text: &str, location: &str) -> Result<Vec<ExternalForecast>> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((_, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = fields(header).into_iter().map(|name| name.to_lowercase()).collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (Some(market), Some(probability)) = (column("market"), column("probability")) else {
+        return Err(AppError::Parse(format!("{}: needs market and probability columns", location)));
+    };
+    let (source, updated_at) = (column("source"), column("updated_at"));
+
+    lines
+        .map(|(number, line)| {
+            let row = fields(line);
+            let cell = |index: Option<usize>| index.and_then(|i| row.get(i)).map(String::as_str).filter(|value| !value.is_empty());
+            let invalid = |what: &str| AppError::Parse(format!("{} line {}: {}", location, number + 1, what));
+
+            Ok(ExternalForecast {
+                source: cell(source).unwrap_or(DEFAULT_FORECAST_SOURCE).to_string(),
+                market: cell(Some(market)).ok_or_else(|| invalid("no market"))?.to_string(),
+                probability: cell(Some(probability)).and_then(parse_probability).ok_or_else(|| invalid("probability isn't between 0 and 1"))?,
+                updated_at: match cell(updated_at) {
+                    Some(raw) => Some(parse_time(raw).ok_or_else(|| invalid("updated_at isn't a date"))?),
+                    None => None,
+                },
+            })
+        })
+        .collect()
+}
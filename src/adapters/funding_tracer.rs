@@ -0,0 +1,193 @@
+use crate::adapters::HttpClient;
+use crate::adapters::abi::{address_word, from_hex, to_hex};
+use crate::error::{DataError, HttpError, Result};
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const FUNDING_FILE: &str = "funding.json";
+// bridged usdc.e, what polymarket settles in, and circle's native usdc
+const USDC_CONTRACTS: [&str; 2] = [
+    "0x2791bca1f2de4661ed88a30c99a7a9449aa84174",
+    "0x3c499c542cef5e3811e1192ce70d8cc03d5c3359",
+];
+// keccak256("Transfer(address,address,uint256)")
+const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+// blocks per eth_getLogs call, public polygon nodes refuse much wider ranges
+const LOG_CHUNK_BLOCKS: u64 = 10_000;
+const USDC_DECIMALS: f64 = 1_000_000.0;
+
+// one usdc transfer into a wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transfer {
+    pub from: String,
+    pub amount: f64,
+    pub block: u64,
+}
+
+// the usdc a wallet received in the last stretch before it started trading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingTrace {
+    pub first_activity_block: u64,
+    // how far back the scan went, a longer lookback later scans again
+    pub scanned_from: u64,
+    // transfers in the newest chunk that had any, oldest first, empty when nothing came in
+    pub transfers: Vec<Transfer>,
+}
+
+impl FundingTrace {
+    // the sender that moved the most usdc in
+    pub fn source(&self) -> Option<(&str, f64)> {
+        let mut totals: BTreeMap<&str, f64> = BTreeMap::new();
+        for transfer in &self.transfers {
+            *totals.entry(transfer.from.as_str()).or_default() += transfer.amount;
+        }
+        totals.into_iter().max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    // block of the first transfer from the source
+    pub fn funded_block(&self) -> Option<u64> {
+        let (source, _) = self.source()?;
+        self.transfers.iter().filter(|t| t.from == source).map(|t| t.block).min()
+    }
+}
+
+// finds where wallets got their usdc from over polygon json-rpc, cached in funding.json
+// scans back from a wallet's first trade a chunk at a time and stops at the first chunk with deposits
+pub struct FundingTracer {
+    http_client: HttpClient,
+    rpc: String,
+    cache_path: PathBuf,
+    cache: Mutex<BTreeMap<String, FundingTrace>>,
+}
+
+impl FundingTracer {
+    // an unreadable cache is dropped, it only saves lookups
+    pub fn new(http_client: HttpClient, rpc: impl Into<String>) -> Self {
+        let cache_path = Self::default_path();
+        let cache = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        Self {
+            http_client,
+            rpc: rpc.into(),
+            cache_path,
+            cache: Mutex::new(cache),
+        }
+    }
+
+    // funding.json in the platform cache dir
+    pub fn default_path() -> PathBuf {
+        paths::cache_file(FUNDING_FILE)
+    }
+
+    // traces for every (address, first activity block), wallets the node failed on are left out
+    pub async fn trace_many(&self, wallets: &[(String, u64)], lookback_blocks: u64) -> BTreeMap<String, FundingTrace> {
+        let lookups = wallets.iter().map(|(address, first_block)| async move {
+            let trace = self.trace(address, *first_block, lookback_blocks).await.ok()?;
+            Some((address.clone(), trace))
+        });
+        let traces = futures::future::join_all(lookups).await.into_iter().flatten().collect();
+
+        // best effort, a read only home dir shouldn't break the analysis
+        let _ = self.save();
+        traces
+    }
+
+    pub async fn trace(&self, address: &str, first_activity_block: u64, lookback_blocks: u64) -> Result<FundingTrace> {
+        let address = address.to_lowercase();
+        let scanned_from = first_activity_block.saturating_sub(lookback_blocks);
+
+        // a deposit found stays the answer, an empty scan only holds for as far back as it went
+        if let Some(cached) = self.cached(&address)
+            && cached.first_activity_block == first_activity_block
+            && (!cached.transfers.is_empty() || cached.scanned_from <= scanned_from)
+        {
+            return Ok(cached);
+        }
+
+        let mut transfers = Vec::new();
+        let mut high = first_activity_block;
+        while transfers.is_empty() && high > scanned_from {
+            let low = high.saturating_sub(LOG_CHUNK_BLOCKS - 1).max(scanned_from);
+            transfers = self.incoming(&address, low, high).await?;
+            high = low.saturating_sub(1);
+        }
+        transfers.sort_by_key(|t: &Transfer| t.block);
+
+        let trace = FundingTrace { first_activity_block, scanned_from, transfers };
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(address, trace.clone());
+        }
+        Ok(trace)
+    }
+
+    fn cached(&self, address: &str) -> Option<FundingTrace> {
+        self.cache.lock().ok()?.get(address).cloned()
+    }
+
+    // usdc transfer logs with the wallet as recipient in [low, high]
+    async fn incoming(&self, address: &str, low: u64, high: u64) -> Result<Vec<Transfer>> {
+        let recipient = address_word(address)
+            .ok_or_else(|| rpc_error(format!("{} is not an address", address)))?;
+        let filter = json!({
+            "address": USDC_CONTRACTS,
+            "fromBlock": format!("0x{:x}", low),
+            "toBlock": format!("0x{:x}", high),
+            "topics": [TRANSFER_TOPIC, null, format!("0x{}", to_hex(&recipient))],
+        });
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_getLogs", "params": [filter] });
+        let response: Value = self.http_client.post_json(&self.rpc, &body).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(rpc_error(error.to_string()).into());
+        }
+        let logs = response.get("result")
+            .and_then(Value::as_array)
+            .ok_or_else(|| rpc_error("response has no result".to_string()))?;
+        logs.iter()
+            .map(|log| parse_transfer(log).ok_or_else(|| rpc_error(format!("unreadable transfer log {}", log)).into()))
+            .collect()
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.cache_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let text = {
+            let cache = self.cache.lock().map_err(|_| DataError::Corrupt("funding cache lock poisoned".to_string()))?;
+            serde_json::to_string_pretty(&*cache).map_err(|e| DataError::Corrupt(e.to_string()))?
+        };
+        fs::write(&self.cache_path, text)?;
+        Ok(())
+    }
+}
+
+fn rpc_error(message: String) -> HttpError {
+    HttpError::Rpc { method: "eth_getLogs".to_string(), message }
+}
+
+// sender from the second topic, amount from the data word
+fn parse_transfer(log: &Value) -> Option<Transfer> {
+    let sender = from_hex(log.get("topics")?.get(1)?.as_str()?)?;
+    let data = from_hex(log.get("data")?.as_str()?)?;
+    let block = u64::from_str_radix(log.get("blockNumber")?.as_str()?.trim_start_matches("0x"), 16).ok()?;
+    if sender.len() != 32 || data.len() != 32 {
+        return None;
+    }
+
+    // anything past 16 bytes would be more usdc than exists
+    let raw = u128::from_be_bytes(data[16..].try_into().ok()?);
+    Some(Transfer {
+        from: format!("0x{}", to_hex(&sender[12..])),
+        amount: raw as f64 / USDC_DECIMALS,
+        block,
+    })
+}
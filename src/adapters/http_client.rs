@@ -1,44 +1,504 @@
-use anyhow::Result;
+use crate::adapters::raw_capture::{CapturedResponse, RawCapture};
+use crate::adapters::stats::RequestStats;
+use crate::cancel::Cancellation;
+use crate::error::{AppError, HttpError, Result};
+use serde::Serialize;
 use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// biggest body read before giving up, decompressed size, a gamma page of 500 markets is a few mb
+pub const DEFAULT_MAX_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
+// idle connections kept open between requests, long enough to span monitor's poll interval
+pub const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+// a full analysis runs this many requests to one host at once, more idle sockets than that just sit there
+const POOL_MAX_IDLE_PER_HOST: usize = 32;
+// os level probes so a connection a nat dropped while idle is noticed before a request is sent on it
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+// http/2 pings on idle connections, an unanswered one closes the connection instead of hanging a request
+const HTTP2_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+const HTTP2_KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+// GETs tried again after a timeout, a dropped connection, a 429 or a 5xx
+pub const DEFAULT_RETRIES: u32 = 2;
+// wait before the first retry, doubled for each one after
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+// what the server sent to revalidate a response later, etag and last-modified headers
+#[derive(Debug, Clone, Default)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+// result of a conditional GET
+pub enum Revalidated<T> {
+    Modified(T, Validators),
+    // 304, the copy the validators came with is still current
+    NotModified,
+}
+
+// cheap to clone, clones share the connection pool and the stats
+#[derive(Clone)]
 pub struct HttpClient {
     client: reqwest::Client,
+    timeout: Duration,
+    max_response_bytes: u64,
+    retries: u32,
+    stats: Arc<RequestStats>,
+    // --dump-raw writes every response here, --replay-raw answers from it instead of the network
+    capture: Option<Arc<RawCapture>>,
+    // requests in flight are dropped when the run is cancelled
+    cancellation: Option<Cancellation>,
+}
+
+// a response as the server sent it, before the status is checked or the body decoded
+struct Exchange {
+    status: reqwest::StatusCode,
+    validators: Validators,
+    body: String,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl HttpClient {
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-        }
+        Self::builder()
+            .build()
+            .expect("default http client should always build")
+    }
+
+    pub fn builder() -> HttpClientBuilder {
+        HttpClientBuilder::default()
+    }
+
+    // shared counters, clone the arc before handing the client to a source
+    pub fn stats(&self) -> Arc<RequestStats> {
+        Arc::clone(&self.stats)
     }
-    
-    // GET reuqest to url
+
+    // GET request to url, counted in the request stats that --stats prints
     pub async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
-        println!("sent GET request to URL: {}", url);
-        let response = self.client.get(url).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error = response.text().await?;
-            anyhow::bail!(
-            "HTTP Request failed: {} - {}",
-                status,
-                error
-            );
-        }
-        
-        let text = response.text().await?;
-
-        let data = serde_json::from_str::<T>(&text).map_err(|e| {
-            anyhow::anyhow!(
-                "Deserialization Error: {}\nExpected Type: {}\nRaw JSON: {}",
-                e,
-                std::any::type_name::<T>(),
+        self.send(self.client.get(url), url).await
+    }
+
+    // GET with If-None-Match / If-Modified-Since from an earlier response, a 304 counts as a cache hit
+    pub async fn get_revalidated<T: DeserializeOwned>(&self, url: &str, validators: Option<&Validators>) -> Result<Revalidated<T>> {
+        let mut request = self.client.get(url);
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let started = Instant::now();
+        let result = async {
+            let exchange = self.exchange(request, url).await?;
+            if exchange.status == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(None);
+            }
+            let validators = exchange.validators.clone();
+            Ok(Some((Self::success_body(exchange)?, validators)))
+        }
+        .await;
+
+        match result {
+            Ok(None) => {
+                self.stats.record_request(0, started.elapsed());
+                self.stats.record_cache_hit();
+                Ok(Revalidated::NotModified)
+            }
+            Ok(Some((text, validators))) => {
+                self.stats.record_request(text.len() as u64, started.elapsed());
+                Ok(Revalidated::Modified(Self::decode(text)?, validators))
+            }
+            Err(e) => {
+                self.stats.record_failure(started.elapsed());
+                Err(e)
+            }
+        }
+    }
+
+    // GET a body that isn't json, like a csv file
+    pub async fn get_text(&self, url: &str) -> Result<String> {
+        let started = Instant::now();
+        match self.fetch_text(self.client.get(url), url).await {
+            Ok(text) => {
+                self.stats.record_request(text.len() as u64, started.elapsed());
+                Ok(text)
+            }
+            Err(e) => {
+                self.stats.record_failure(started.elapsed());
+                Err(e)
+            }
+        }
+    }
+
+    // POST a json body, used for json-rpc endpoints
+    pub async fn post_json<B: Serialize + ?Sized, T: DeserializeOwned>(&self, url: &str, body: &B) -> Result<T> {
+        self.send(self.client.post(url).json(body), url).await
+    }
+
+    // POST an already serialized json body where only the status matters, webhooks answer with anything or nothing
+    pub async fn post_json_discard(&self, url: &str, headers: &[(&str, String)], body: String) -> Result<()> {
+        let mut request = self.client.post(url).header(reqwest::header::CONTENT_TYPE, "application/json").body(body);
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        let started = Instant::now();
+        match self.fetch_text(request, url).await {
+            Ok(text) => {
+                self.stats.record_request(text.len() as u64, started.elapsed());
+                Ok(())
+            }
+            Err(e) => {
+                self.stats.record_failure(started.elapsed());
+                Err(e)
+            }
+        }
+    }
+
+    // send an already serialized json body with extra headers, for apis that sign the exact bytes sent
+    pub async fn request_with_headers<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        headers: &[(&str, String)],
+        body: Option<String>,
+    ) -> Result<T> {
+        let mut request = self.client.request(method, url);
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        if let Some(body) = body {
+            request = request.header(reqwest::header::CONTENT_TYPE, "application/json").body(body);
+        }
+        self.send(request, url).await
+    }
+
+    // send, record stats and decode the body into T
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder, url: &str) -> Result<T> {
+        let started = Instant::now();
+        let text = match self.fetch_text(request, url).await {
+            Ok(text) => {
+                self.stats.record_request(text.len() as u64, started.elapsed());
                 text
-            )
+            }
+            Err(e) => {
+                self.stats.record_failure(started.elapsed());
+                return Err(e);
+            }
+        };
+
+        Self::decode(text)
+    }
+
+    fn decode<T: DeserializeOwned>(text: String) -> Result<T> {
+        let data = serde_json::from_str::<T>(&text).map_err(|error| {
+            HttpError::Deserialize {
+                error,
+                expected: std::any::type_name::<T>(),
+                raw: text,
+            }
         })?;
 
         Ok(data)
     }
-    
+
+    // send the request and read the body, any non 2xx is an error
+    async fn fetch_text(&self, request: reqwest::RequestBuilder, url: &str) -> Result<String> {
+        Self::success_body(self.exchange(request, url).await?)
+    }
+
+    fn success_body(exchange: Exchange) -> Result<String> {
+        if !exchange.status.is_success() {
+            return Err(HttpError::Status { status: exchange.status, body: exchange.body }.into());
+        }
+        Ok(exchange.body)
+    }
+
+    // one round trip, or its capture when replaying, the raw response is saved when capturing
+    async fn exchange(&self, request: reqwest::RequestBuilder, url: &str) -> Result<Exchange> {
+        let request = request.build()?;
+        let method = request.method().to_string();
+        let request_body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+
+        if let Some(capture) = self.capture.as_ref().filter(|c| c.is_replay()) {
+            let captured = capture.lookup(&method, url, request_body.as_deref())?;
+            return Ok(Exchange {
+                status: reqwest::StatusCode::from_u16(captured.status).unwrap_or(reqwest::StatusCode::OK),
+                validators: Validators::default(),
+                body: captured.body,
+            });
+        }
+
+        let started = Instant::now();
+        let mut request = request;
+        let mut attempt = 0;
+        let (status, validators, body) = loop {
+            // only GETs are sent again, a POST like an order may have gone through before the error
+            let next = match attempt < self.retries && request.method() == reqwest::Method::GET {
+                true => request.try_clone(),
+                false => None,
+            };
+            let outcome = self.cancellable(self.round_trip(request, url)).await;
+            let transient = match &outcome {
+                Ok((status, _, _)) => status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+                Err(AppError::Http(HttpError::Request(_) | HttpError::Timeout { .. })) => true,
+                Err(_) => false,
+            };
+            match next {
+                Some(next) if transient => {
+                    self.stats.record_retry();
+                    self.cancellable(async {
+                        tokio::time::sleep(RETRY_DELAY * 2u32.pow(attempt)).await;
+                        Ok(())
+                    })
+                    .await?;
+                    attempt += 1;
+                    request = next;
+                }
+                _ => break outcome?,
+            }
+        };
+
+        if let Some(capture) = &self.capture {
+            let captured = CapturedResponse {
+                method,
+                url: url.to_string(),
+                request_body,
+                status: status.as_u16(),
+                captured_at: chrono::Utc::now().timestamp(),
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                body,
+            };
+            // a dump that can't be written shouldn't fail the run
+            if let Err(e) = capture.save(&captured) {
+                eprintln!("could not save raw response to {}: {}", capture.dir().display(), e);
+            }
+            return Ok(Exchange { status, validators, body: captured.body });
+        }
+
+        Ok(Exchange { status, validators, body })
+    }
+
+    async fn round_trip(&self, request: reqwest::Request, url: &str) -> Result<(reqwest::StatusCode, Validators, String)> {
+        let response = self.client.execute(request).await.map_err(|e| {
+            if e.is_timeout() {
+                HttpError::Timeout { url: url.to_string(), seconds: self.timeout.as_secs() }
+            } else {
+                HttpError::Request(e)
+            }
+        })?;
+        let status = response.status();
+        let validators = Validators::from_headers(response.headers());
+        Ok((status, validators, self.read_body(response, url).await?))
+    }
+
+    async fn cancellable<T>(&self, work: impl Future<Output = Result<T>>) -> Result<T> {
+        match &self.cancellation {
+            Some(cancellation) => cancellation.run(work).await,
+            None => work.await,
+        }
+    }
+
+    // reads chunk by chunk and stops at the limit, a runaway or decompression bomb response never sits whole in memory
+    async fn read_body(&self, mut response: reqwest::Response, url: &str) -> Result<String> {
+        let too_large = || HttpError::TooLarge { url: url.to_string(), limit: self.max_response_bytes };
+        // compressed responses don't say their decoded length, those are only caught while reading
+        if response.content_length().is_some_and(|length| length > self.max_response_bytes) {
+            return Err(too_large().into());
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (body.len() + chunk.len()) as u64 > self.max_response_bytes {
+                return Err(too_large().into());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8(body).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+    }
+}
+
+// knobs for the underlying reqwest client
+pub struct HttpClientBuilder {
+    connect_timeout: Duration,
+    timeout: Duration,
+    proxy: Option<String>,
+    user_agent: String,
+    accept_invalid_certs: bool,
+    min_tls_version: Option<reqwest::tls::Version>,
+    pool_idle_timeout: Duration,
+    http1_only: bool,
+    max_response_bytes: u64,
+    retries: u32,
+    capture: Option<RawCapture>,
+    cancellation: Option<Cancellation>,
+}
+
+impl Default for HttpClientBuilder {
+    fn default() -> Self {
+        Self {
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: DEFAULT_TIMEOUT,
+            proxy: None,
+            user_agent: format!("polymarket-explorer/{}", env!("CARGO_PKG_VERSION")),
+            accept_invalid_certs: false,
+            min_tls_version: None,
+            pool_idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT,
+            http1_only: false,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            retries: DEFAULT_RETRIES,
+            capture: None,
+            cancellation: None,
+        }
+    }
+}
+
+impl HttpClientBuilder {
+    // time allowed to open the connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    // time allowed for the whole request including reading the body
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // http, https or socks5 proxy url
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    // only for debugging through intercepting proxies
+    pub fn accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    pub fn min_tls_version(mut self, version: reqwest::tls::Version) -> Self {
+        self.min_tls_version = Some(version);
+        self
+    }
+
+    // how long an unused connection stays in the pool, zero closes each one after its request
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    // http/2 is used wherever the server offers it, this is for proxies that mangle it
+    pub fn http1_only(mut self, http1_only: bool) -> Self {
+        self.http1_only = http1_only;
+        self
+    }
+
+    // bodies past this many bytes fail with HttpError::TooLarge instead of being read
+    pub fn max_response_bytes(mut self, limit: u64) -> Self {
+        self.max_response_bytes = limit;
+        self
+    }
+
+    // how many times a GET is tried again before its error is returned, 0 never retries
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    // record every raw response to a dir, or serve them back from one
+    pub fn raw_capture(mut self, capture: RawCapture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    // give up on requests in flight once the run is cancelled
+    pub fn cancellation(mut self, cancellation: Cancellation) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    pub fn build(self) -> Result<HttpClient> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.timeout)
+            .user_agent(self.user_agent)
+            .danger_accept_invalid_certs(self.accept_invalid_certs)
+            // every clone shares this pool, so one client keeps connections warm for every source
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(if self.pool_idle_timeout.is_zero() { 0 } else { POOL_MAX_IDLE_PER_HOST })
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .tcp_nodelay(true)
+            // gzip and deflate are asked for and decoded transparently, gamma's json shrinks several times over
+            .gzip(true)
+            .deflate(true);
+
+        // negotiated through alpn, many requests share one connection instead of each waiting for a free one
+        builder = match self.http1_only {
+            true => builder.http1_only(),
+            false => builder
+                .http2_adaptive_window(true)
+                .http2_keep_alive_interval(HTTP2_KEEPALIVE_INTERVAL)
+                .http2_keep_alive_timeout(HTTP2_KEEPALIVE_TIMEOUT)
+                .http2_keep_alive_while_idle(true),
+        };
+
+        if let Some(url) = &self.proxy {
+            let proxy = reqwest::Proxy::all(url)
+                .map_err(|e| HttpError::InvalidConfig(format!("proxy {}: {}", url, e)))?;
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(version) = self.min_tls_version {
+            builder = builder.min_tls_version(version);
+        }
+
+        let client = builder
+            .build()
+            .map_err(|e| HttpError::InvalidConfig(e.to_string()))?;
+
+        Ok(HttpClient {
+            client,
+            timeout: self.timeout,
+            max_response_bytes: self.max_response_bytes,
+            retries: self.retries,
+            stats: Arc::new(RequestStats::new()),
+            capture: self.capture.map(Arc::new),
+            cancellation: self.cancellation,
+        })
+    }
 }
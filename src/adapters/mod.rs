@@ -1,5 +1,36 @@
+pub mod abi;
+pub mod api_urls;
+pub mod block_index;
+pub mod ctf_events;
+pub mod forecast_file;
+pub mod funding_tracer;
 pub mod http_client;
+pub mod name_resolver;
+pub mod odds_api;
 pub mod parquet_reader;
+pub mod parquet_writer;
+pub mod raw_capture;
+pub mod script;
+pub mod spot_price;
+pub mod stats;
+#[cfg(feature = "plugins")]
+pub mod wasm_plugin;
+pub mod webhook;
 
-pub use http_client::HttpClient;
+pub use api_urls::ApiUrls;
+pub use block_index::BlockIndex;
+pub use ctf_events::{CtfEvent, CtfEventReader, CtfOperation};
+pub use forecast_file::ForecastFile;
+pub use funding_tracer::{FundingTrace, FundingTracer, Transfer};
+pub use http_client::{HttpClient, Revalidated, Validators};
+pub use name_resolver::{NameResolver, ResolvedName};
+pub use odds_api::OddsApiClient;
 pub use parquet_reader::ParquetReader;
+pub use parquet_writer::{Compaction, Compression, ParquetWriter};
+pub use raw_capture::{CapturedResponse, RawCapture};
+pub use script::ScriptAnalyzer;
+pub use spot_price::SpotPriceFeed;
+pub use stats::{RequestStats, RequestStatsSnapshot};
+#[cfg(feature = "plugins")]
+pub use wasm_plugin::WasmAnalyzer;
+pub use webhook::WebhookTarget;
@@ -0,0 +1,243 @@
+use crate::adapters::HttpClient;
+use crate::adapters::api_urls::GAMMA_API_URL;
+use crate::adapters::abi::{from_hex, keccak256, to_hex};
+use crate::error::{AppError, DataError, HttpError, Result};
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const NAMES_FILE: &str = "names.json";
+// usernames change rarely, look them up again after a week
+const CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+// ens registry, same address on mainnet since 2020
+const ENS_REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+// function selectors: resolver(bytes32), name(bytes32), addr(bytes32)
+const RESOLVER_SELECTOR: &str = "0178b8bf";
+const NAME_SELECTOR: &str = "691f3431";
+const ADDR_SELECTOR: &str = "3b3b57de";
+
+// what an address resolved to, cached with the time of the lookup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolvedName {
+    pub username: Option<String>,
+    pub ens: Option<String>,
+    pub resolved_at: i64,
+}
+
+impl ResolvedName {
+    // polymarket username first, it's what people know traders by
+    pub fn display_name(&self) -> Option<&str> {
+        self.username.as_deref().or(self.ens.as_deref())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileResponse {
+    name: Option<String>,
+}
+
+// maps wallet addresses to polymarket usernames and optionally ens names, cached in ~/.polymarket-explorer/names.json
+pub struct NameResolver {
+    http_client: HttpClient,
+    // profiles live on gamma
+    gamma_url: String,
+    ens_rpc: Option<String>,
+    cache_path: PathBuf,
+    cache: Mutex<BTreeMap<String, ResolvedName>>,
+}
+
+impl NameResolver {
+    // an unreadable cache is dropped, it only saves lookups
+    pub fn new(http_client: HttpClient) -> Self {
+        let cache_path = Self::default_path();
+        let cache = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        Self {
+            http_client,
+            gamma_url: GAMMA_API_URL.to_string(),
+            ens_rpc: None,
+            cache_path,
+            cache: Mutex::new(cache),
+        }
+    }
+
+    pub fn with_gamma_url(mut self, url: impl Into<String>) -> Self {
+        self.gamma_url = url.into();
+        self
+    }
+
+    // ens reverse lookups need an ethereum mainnet json-rpc endpoint
+    pub fn with_ens_rpc(mut self, url: impl Into<String>) -> Self {
+        self.ens_rpc = Some(url.into());
+        self
+    }
+
+    // names.json in the platform cache dir
+    pub fn default_path() -> PathBuf {
+        paths::cache_file(NAMES_FILE)
+    }
+
+    // address -> display name for every address that has one, failed lookups are skipped
+    pub async fn resolve_many(&self, addresses: &[String]) -> HashMap<String, String> {
+        let lookups = addresses.iter().map(|address| async move {
+            let resolved = self.resolve(address).await.ok()?;
+            Some((address.clone(), resolved.display_name()?.to_string()))
+        });
+        let names = futures::future::join_all(lookups).await.into_iter().flatten().collect();
+
+        // best effort, a read only home dir shouldn't break the output
+        let _ = self.save();
+        names
+    }
+
+    pub async fn resolve(&self, address: &str) -> Result<ResolvedName> {
+        let address = address.to_lowercase();
+        let now = chrono::Utc::now().timestamp();
+
+        if let Some(cached) = self.cached(&address)
+            && now - cached.resolved_at < CACHE_TTL_SECS
+        {
+            return Ok(cached);
+        }
+
+        let username = self.fetch_username(&address).await?;
+        let ens = match &self.ens_rpc {
+            Some(rpc) => self.reverse_ens(rpc, &address).await?,
+            None => None,
+        };
+
+        let resolved = ResolvedName { username, ens, resolved_at: now };
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(address, resolved.clone());
+        }
+        Ok(resolved)
+    }
+
+    fn cached(&self, address: &str) -> Option<ResolvedName> {
+        self.cache.lock().ok()?.get(address).cloned()
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.cache_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let text = {
+            let cache = self.cache.lock().map_err(|_| DataError::Corrupt("name cache lock poisoned".to_string()))?;
+            serde_json::to_string_pretty(&*cache).map_err(|e| DataError::Corrupt(e.to_string()))?
+        };
+        fs::write(&self.cache_path, text)?;
+        Ok(())
+    }
+
+    // wallets that never set up a profile are a 404, not an error
+    async fn fetch_username(&self, address: &str) -> Result<Option<String>> {
+        let url = format!("{}/public-profile?address={}", self.gamma_url, address);
+        match self.http_client.get::<ProfileResponse>(&url).await {
+            Ok(profile) => Ok(profile.name.filter(|name| !name.trim().is_empty())),
+            Err(AppError::Http(HttpError::Status { status, .. })) if status.as_u16() == 404 => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // primary name from the reverse registrar, only trusted if it resolves back to the same address
+    async fn reverse_ens(&self, rpc: &str, address: &str) -> Result<Option<String>> {
+        let reverse_node = namehash(&format!("{}.addr.reverse", address.trim_start_matches("0x")));
+        let Some(name) = self.call_resolver(rpc, &reverse_node, NAME_SELECTOR).await? else {
+            return Ok(None);
+        };
+        let Some(name) = decode_string(&name) else {
+            return Ok(None);
+        };
+
+        let Some(forward) = self.call_resolver(rpc, &namehash(&name), ADDR_SELECTOR).await? else {
+            return Ok(None);
+        };
+        let verified = decode_address(&forward).is_some_and(|resolved| resolved == address);
+
+        Ok(verified.then_some(name))
+    }
+
+    // look up the resolver of a node then call one of its bytes32 getters on it
+    async fn call_resolver(&self, rpc: &str, node: &[u8; 32], selector: &str) -> Result<Option<Vec<u8>>> {
+        let resolver = self.eth_call(rpc, ENS_REGISTRY, RESOLVER_SELECTOR, node).await?;
+        let Some(resolver) = decode_address(&resolver) else {
+            return Ok(None);
+        };
+
+        Ok(Some(self.eth_call(rpc, &resolver, selector, node).await?))
+    }
+
+    async fn eth_call(&self, rpc: &str, to: &str, selector: &str, node: &[u8; 32]) -> Result<Vec<u8>> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [{ "to": to, "data": format!("0x{}{}", selector, to_hex(node)) }, "latest"],
+        });
+        let response: Value = self.http_client.post_json(rpc, &body).await?;
+
+        let rpc_error = |message: String| HttpError::Rpc { method: "eth_call".to_string(), message };
+        if let Some(error) = response.get("error") {
+            return Err(rpc_error(error.to_string()).into());
+        }
+        let result = response
+            .get("result")
+            .and_then(Value::as_str)
+            .ok_or_else(|| rpc_error("response has no result".to_string()))?;
+
+        from_hex(result).ok_or_else(|| rpc_error(format!("result is not hex: {}", result)).into())
+    }
+}
+
+// ens namehash, keccak over the labels from the right
+fn namehash(name: &str) -> [u8; 32] {
+    let mut node = [0u8; 32];
+    if name.is_empty() {
+        return node;
+    }
+
+    for label in name.rsplit('.') {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(&node);
+        buf[32..].copy_from_slice(&keccak256(label.as_bytes()));
+        node = keccak256(&buf);
+    }
+    node
+}
+
+// an abi encoded address is the last 20 bytes of one word, all zero means unset
+fn decode_address(word: &[u8]) -> Option<String> {
+    let bytes = word.get(12..32)?;
+    if bytes.iter().all(|b| *b == 0) {
+        return None;
+    }
+    Some(format!("0x{}", to_hex(bytes)))
+}
+
+// abi encoded string: offset word, length word, then the bytes
+fn decode_string(data: &[u8]) -> Option<String> {
+    let offset = word_to_usize(data.get(0..32)?)?;
+    let length = word_to_usize(data.get(offset..offset + 32)?)?;
+    let bytes = data.get(offset + 32..offset + 32 + length)?;
+
+    let name = String::from_utf8(bytes.to_vec()).ok()?;
+    (!name.is_empty()).then_some(name)
+}
+
+fn word_to_usize(word: &[u8]) -> Option<usize> {
+    // anything that doesn't fit in the last 8 bytes is garbage for our purposes
+    if word[..24].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let tail: [u8; 8] = word[24..].try_into().ok()?;
+    usize::try_from(u64::from_be_bytes(tail)).ok()
+}
@@ -0,0 +1,113 @@
+use crate::adapters::HttpClient;
+use crate::error::Result;
+use crate::standard_data::models::{ExternalEvent, ExternalLine};
+use crate::standard_data::providers::ExternalOddsProvider;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+// the-odds-api v4, other odds apis serving the same shape work with --odds-api-url
+pub const ODDS_API_URL: &str = "https://api.the-odds-api.com/v4";
+// bookmaker regions asked for, comma separated
+pub const DEFAULT_ODDS_REGIONS: &str = "us,uk,eu";
+// head to head, the moneyline that lines up with polymarket's winner markets
+const ODDS_MARKET: &str = "h2h";
+
+#[derive(Debug, Deserialize)]
+struct OddsEvent {
+    id: String,
+    commence_time: Option<DateTime<Utc>>,
+    home_team: Option<String>,
+    away_team: Option<String>,
+    // outrights have no teams, only a title
+    sport_title: Option<String>,
+    #[serde(default)]
+    bookmakers: Vec<OddsBookmaker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OddsBookmaker {
+    title: String,
+    last_update: Option<DateTime<Utc>>,
+    #[serde(default)]
+    markets: Vec<OddsMarket>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OddsMarket {
+    key: String,
+    #[serde(default)]
+    outcomes: Vec<OddsOutcome>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OddsOutcome {
+    name: String,
+    price: f64,
+}
+
+// bookmaker lines from an odds api, decimal odds on the head to head market
+pub struct OddsApiClient {
+    http_client: HttpClient,
+    url: String,
+    api_key: String,
+    regions: String,
+}
+
+impl OddsApiClient {
+    pub fn new(http_client: HttpClient, api_key: impl Into<String>) -> Self {
+        Self {
+            http_client,
+            url: ODDS_API_URL.to_string(),
+            api_key: api_key.into(),
+            regions: DEFAULT_ODDS_REGIONS.to_string(),
+        }
+    }
+
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    pub fn with_regions(mut self, regions: impl Into<String>) -> Self {
+        self.regions = regions.into();
+        self
+    }
+}
+
+#[async_trait]
+impl ExternalOddsProvider for OddsApiClient {
+    async fn get_external_odds(&self, sport: &str) -> Result<Vec<ExternalEvent>> {
+        let url = format!(
+            "{}/sports/{}/odds?apiKey={}&regions={}&markets={}&oddsFormat=decimal",
+            self.url, sport, self.api_key, self.regions, ODDS_MARKET,
+        );
+        // not get, it prints the url and the key is in it
+        let events: Vec<OddsEvent> = self.http_client.request_with_headers(reqwest::Method::GET, &url, &[], None).await?;
+        Ok(events.into_iter().map(standardize_event).collect())
+    }
+}
+
+fn standardize_event(event: OddsEvent) -> ExternalEvent {
+    let title = match (&event.home_team, &event.away_team) {
+        (Some(home), Some(away)) => format!("{} vs {}", home, away),
+        _ => event.sport_title.clone().unwrap_or_else(|| event.id.clone()),
+    };
+    let lines = event.bookmakers
+        .iter()
+        .flat_map(|bookmaker| bookmaker.markets
+            .iter()
+            .filter(|market| market.key == ODDS_MARKET)
+            .flat_map(|market| market.outcomes.iter())
+            // a price under 1 is no bet at all
+            .filter(|outcome| outcome.price > 1.0)
+            .map(|outcome| ExternalLine {
+                bookmaker: bookmaker.title.clone(),
+                outcome: outcome.name.clone(),
+                decimal_odds: outcome.price,
+                updated_at: bookmaker.last_update,
+            }))
+        .collect();
+
+    ExternalEvent { id: event.id, title, starts_at: event.commence_time, lines }
+}
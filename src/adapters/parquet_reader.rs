@@ -1,6 +1,7 @@
-use anyhow::Result;
+use crate::error::{DataError, Result};
 use polars::prelude::*; // dataframe and lazyframe
-use std::path::PathBuf;
+use polars::io::HiveOptions;
+use std::path::{Path, PathBuf};
 
 pub struct ParquetReader {
     data_dir: PathBuf,
@@ -14,17 +15,38 @@ impl ParquetReader {
     }
 
     // read from parquet into lazyframe
+    // a table can be a single file or a hive partitioned dir like transactions/market_id=.../*.parquet
     pub fn read_lazy(&self, filename: &str) -> Result<LazyFrame> {
         // path for the table
         let path = self.data_dir.join(filename);
 
-        if !path.exists() {
-            anyhow::bail!("Parquet file not found {:?}", path);
+        if path.exists() {
+            let frame = LazyFrame::scan_parquet(path, Default::default())?;
+            return Ok(frame);
         }
 
-        let frame = LazyFrame::scan_parquet(path, Default::default())?;
+        let dir = self.partition_dir(filename);
+        if dir.is_dir() {
+            return Self::scan_hive(&dir);
+        }
 
-        Ok(frame)
+        Err(DataError::TableNotFound(path).into())
+    }
+
+    // read just the hive partition directory for column=value
+    // None when the table isn't partitioned or has no data for this key, callers filter a full scan instead
+    pub fn read_lazy_partition(&self, filename: &str, column: &str, value: &str) -> Result<Option<LazyFrame>> {
+        let partition = self.partition_dir(filename).join(format!("{}={}", column, value));
+
+        if !partition.is_dir() {
+            return Ok(None);
+        }
+
+        // the key only lives in the dir name so add it back as a column
+        let frame = LazyFrame::scan_parquet(partition.join("*.parquet"), Default::default())?
+            .with_column(lit(value).alias(column));
+
+        Ok(Some(frame))
     }
 
     // read parquet file into DataFrame
@@ -34,8 +56,41 @@ impl ParquetReader {
         Ok(lazy.collect()?)
     }
 
-    // check if file exists 
+    // check if file (or partitioned dir) exists
     pub fn exists(&self, filename: &str) -> bool {
-        self.data_dir.join(filename).exists()
+        self.data_dir.join(filename).exists() || self.partition_dir(filename).is_dir()
+    }
+
+    // stored as a hive dir rather than a single file
+    pub fn is_partitioned(&self, filename: &str) -> bool {
+        !self.data_dir.join(filename).exists() && self.partition_dir(filename).is_dir()
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    // full path of a table in the data dir
+    pub fn path(&self, filename: &str) -> PathBuf {
+        self.data_dir.join(filename)
+    }
+
+    // transactions.parquet -> data_dir/transactions
+    fn partition_dir(&self, filename: &str) -> PathBuf {
+        self.data_dir.join(filename.trim_end_matches(".parquet"))
+    }
+
+    // scan every file under a hive dir, partition columns come from the key=value dir names
+    fn scan_hive(dir: &Path) -> Result<LazyFrame> {
+        let args = ScanArgsParquet {
+            hive_options: HiveOptions {
+                enabled: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let frame = LazyFrame::scan_parquet(dir.join("**/*.parquet"), args)?;
+        Ok(frame)
     }
 }
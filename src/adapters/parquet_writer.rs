@@ -0,0 +1,282 @@
+use crate::error::Result;
+use polars::prelude::{
+    Column, DataFrame, DataType, ParquetCompression, ParquetReader as PolarsParquetReader,
+    ParquetWriter as PolarsParquetWriter, PolarsResult, Schema, SerReader, SortMultipleOptions,
+};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// codec used for every file the writer produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    Uncompressed,
+    Snappy,
+    #[default]
+    Zstd,
+    Lz4,
+}
+
+impl Compression {
+    fn codec(self) -> ParquetCompression {
+        match self {
+            Compression::Uncompressed => ParquetCompression::Uncompressed,
+            Compression::Snappy => ParquetCompression::Snappy,
+            Compression::Zstd => ParquetCompression::Zstd(None),
+            Compression::Lz4 => ParquetCompression::Lz4Raw,
+        }
+    }
+}
+
+// what compacting one table did
+#[derive(Debug, Clone, Default)]
+pub struct Compaction {
+    pub table: String,
+    // 0 for a single file table
+    pub partitions: usize,
+    pub files_before: usize,
+    pub files_after: usize,
+    pub rows: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    // temp files left behind by writes that never finished
+    pub stale_removed: usize,
+}
+
+// counterpart to ParquetReader, writes tables in the same layouts it reads
+pub struct ParquetWriter {
+    data_dir: PathBuf,
+    compression: Compression,
+}
+
+impl ParquetWriter {
+    pub fn new(data_dir: &str) -> Self {
+        Self {
+            data_dir: PathBuf::from(data_dir),
+            compression: Compression::default(),
+        }
+    }
+
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    // rewrite a table sorted by whichever of sort_by it has, a partitioned table ends up with one file per partition
+    // the new file is in place before the old ones are removed, so a crash leaves duplicates rather than holes
+    // not safe to run while something else is appending to the same table
+    pub fn compact(&self, filename: &str, sort_by: &[&str], row_group_size: usize) -> Result<Compaction> {
+        let mut compaction = Compaction {
+            table: filename.to_string(),
+            ..Default::default()
+        };
+
+        let path = self.data_dir.join(filename);
+        compaction.stale_removed += remove_stale(&self.data_dir, filename)?;
+        if path.is_file() {
+            compaction.files_before = 1;
+            compaction.bytes_before = fs::metadata(&path)?.len();
+            let mut df = sorted(read_files(std::slice::from_ref(&path))?, sort_by)?;
+            compaction.rows = df.height();
+            self.write_file(&path, &mut df, Some(row_group_size))?;
+            compaction.files_after = 1;
+            compaction.bytes_after = fs::metadata(&path)?.len();
+            return Ok(compaction);
+        }
+
+        let table_dir = self.data_dir.join(filename.trim_end_matches(".parquet"));
+        if !table_dir.is_dir() {
+            return Ok(compaction);
+        }
+
+        for entry in fs::read_dir(&table_dir)? {
+            let dir = entry?.path();
+            if !dir.is_dir() {
+                continue;
+            }
+            compaction.stale_removed += remove_stale(&dir, "")?;
+
+            let files = parquet_files(&dir)?;
+            if files.is_empty() {
+                // an emptied partition only slows down listing
+                fs::remove_dir(&dir)?;
+                continue;
+            }
+            compaction.partitions += 1;
+            compaction.files_before += files.len();
+            for file in &files {
+                compaction.bytes_before += fs::metadata(file)?.len();
+            }
+
+            let mut df = sorted(read_files(&files)?, sort_by)?;
+            compaction.rows += df.height();
+            let target = dir.join(format!("part-{}.parquet", unique_suffix()));
+            self.write_file(&target, &mut df, Some(row_group_size))?;
+            for file in &files {
+                fs::remove_file(file)?;
+            }
+            compaction.files_after += 1;
+            compaction.bytes_after += fs::metadata(&target)?.len();
+        }
+
+        Ok(compaction)
+    }
+
+    // replace a single file table, readers see either the old or the new file never half of one
+    pub fn write(&self, filename: &str, df: &mut DataFrame) -> Result<()> {
+        fs::create_dir_all(&self.data_dir)?;
+        self.write_file(&self.data_dir.join(filename), df, None)
+    }
+
+    // add rows to a hive partitioned table like transactions/market_id=.../part-*.parquet
+    // every call writes one new file per partition key, existing files are never touched
+    pub fn append_partitioned(&self, filename: &str, column: &str, df: &DataFrame) -> Result<usize> {
+        if df.height() == 0 {
+            return Ok(0);
+        }
+
+        let table_dir = self.data_dir.join(filename.trim_end_matches(".parquet"));
+        let part_name = format!("part-{}.parquet", unique_suffix());
+
+        let mut written = 0;
+        for part in df.partition_by([column], true)? {
+            let keys = part.column(column)?.cast(&DataType::String)?;
+            let Some(key) = keys.str()?.get(0).map(str::to_string) else {
+                continue;
+            };
+
+            // the key lives in the dir name, same as what the reader expects
+            let mut part = part.drop(column)?;
+            let dir = table_dir.join(format!("{}={}", column, key));
+            fs::create_dir_all(&dir)?;
+            self.write_file(&dir.join(&part_name), &mut part, None)?;
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    // swap one hive partition's files for a single file holding df, the new file lands before the old ones go
+    pub fn replace_partition(&self, filename: &str, column: &str, value: &str, df: &DataFrame) -> Result<()> {
+        let dir = self.data_dir
+            .join(filename.trim_end_matches(".parquet"))
+            .join(format!("{}={}", column, value));
+        fs::create_dir_all(&dir)?;
+        let old = parquet_files(&dir)?;
+
+        let mut part = df.drop(column)?;
+        self.write_file(&dir.join(format!("part-{}.parquet", unique_suffix())), &mut part, None)?;
+        for file in &old {
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+
+    // write next to the target then rename over it, rename is atomic on the same filesystem
+    // row groups are left at the polars default unless a size is given
+    fn write_file(&self, path: &Path, df: &mut DataFrame, row_group_size: Option<usize>) -> Result<()> {
+        let tmp = path.with_extension(format!("parquet.tmp-{}", std::process::id()));
+
+        let result = (|| -> Result<()> {
+            let file = File::create(&tmp)?;
+            PolarsParquetWriter::new(&file)
+                .with_compression(self.compression.codec())
+                .with_row_group_size(row_group_size)
+                .finish(df)?;
+            file.sync_all()?;
+            fs::rename(&tmp, path)?;
+            Ok(())
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    }
+}
+
+// unique enough for part files written by one process
+fn unique_suffix() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!("{}-{}", nanos, std::process::id())
+}
+
+// the parquet files directly in dir, oldest name first
+fn parquet_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "parquet") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// delete the .tmp-<pid> files an interrupted write_file left next to its target
+fn remove_stale(dir: &Path, prefix: &str) -> Result<usize> {
+    let mut removed = 0;
+    if !dir.is_dir() {
+        return Ok(0);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if path.is_file() && name.starts_with(prefix) && name.contains(".parquet.tmp-") {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+// stack files written at different times, columns an older file lacks come back null
+fn read_files(files: &[PathBuf]) -> Result<DataFrame> {
+    let frames = files
+        .iter()
+        .map(|file| Ok(PolarsParquetReader::new(File::open(file)?).finish()?))
+        .collect::<Result<Vec<DataFrame>>>()?;
+
+    let mut schema = Schema::default();
+    for frame in &frames {
+        for (name, dtype) in frame.schema().iter() {
+            if !schema.contains(name) {
+                schema.insert(name.clone(), dtype.clone());
+            }
+        }
+    }
+
+    let mut combined: Option<DataFrame> = None;
+    for frame in frames {
+        let columns = schema
+            .iter()
+            .map(|(name, dtype)| match frame.column(name) {
+                Ok(column) => column.cast(dtype),
+                Err(_) => Ok(Column::full_null(name.clone(), frame.height(), dtype)),
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let frame = DataFrame::new(columns)?;
+        match combined.as_mut() {
+            Some(combined) => {
+                combined.vstack_mut(&frame)?;
+            }
+            None => combined = Some(frame),
+        }
+    }
+
+    let mut combined = combined.unwrap_or_default();
+    combined.as_single_chunk();
+    Ok(combined)
+}
+
+fn sorted(df: DataFrame, sort_by: &[&str]) -> Result<DataFrame> {
+    let columns: Vec<&str> = sort_by.iter().copied().filter(|c| df.schema().contains(c)).collect();
+    if columns.is_empty() {
+        return Ok(df);
+    }
+    Ok(df.sort(columns, SortMultipleOptions::default().with_maintain_order(true))?)
+}
@@ -0,0 +1,125 @@
+use crate::adapters::abi::{keccak256, to_hex};
+use crate::error::{DataError, HttpError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// one http exchange as it went over the wire, written before any status check or decoding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedResponse {
+    pub method: String,
+    pub url: String,
+    // json-rpc and order bodies, None for plain GETs
+    #[serde(default)]
+    pub request_body: Option<String>,
+    pub status: u16,
+    // unix seconds
+    pub captured_at: i64,
+    pub elapsed_ms: u64,
+    pub body: String,
+}
+
+impl CapturedResponse {
+    // same request, same key, so a replay finds the response whatever order the run asks in
+    pub fn key(&self) -> String {
+        request_key(&self.method, &self.url, self.request_body.as_deref())
+    }
+}
+
+// what the run does with raw responses
+enum Mode {
+    // write each one to its own file, numbered in the order they came back
+    Record { next: AtomicUsize },
+    // answer from the files instead of the network, repeats of a request get its captures in order
+    // and the last one again once they run out
+    Replay { responses: Mutex<HashMap<String, (Vec<CapturedResponse>, usize)>> },
+}
+
+// --dump-raw and --replay-raw, one directory of json files shared by both
+pub struct RawCapture {
+    dir: PathBuf,
+    mode: Mode,
+}
+
+impl RawCapture {
+    // numbering carries on after what's already there, so several runs can share a dir
+    pub fn record(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let existing = captures_in(&dir)?.len();
+        Ok(Self { dir, mode: Mode::Record { next: AtomicUsize::new(existing) } })
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let mut responses: HashMap<String, (Vec<CapturedResponse>, usize)> = HashMap::new();
+        for path in captures_in(&dir)? {
+            let text = fs::read_to_string(&path)?;
+            let captured: CapturedResponse = serde_json::from_str(&text)
+                .map_err(|e| DataError::Corrupt(format!("{}: {}", path.display(), e)))?;
+            responses.entry(captured.key()).or_default().0.push(captured);
+        }
+        Ok(Self { dir, mode: Mode::Replay { responses: Mutex::new(responses) } })
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay { .. })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // the next captured response for a request, an error when the dump never saw it
+    pub fn lookup(&self, method: &str, url: &str, request_body: Option<&str>) -> Result<CapturedResponse> {
+        let not_captured = || HttpError::NotCaptured { method: method.to_string(), url: url.to_string() };
+        let Mode::Replay { responses } = &self.mode else {
+            return Err(not_captured().into());
+        };
+
+        let mut responses = responses.lock().map_err(|_| DataError::Corrupt("raw capture lock poisoned".to_string()))?;
+        let (captures, served) = responses.get_mut(&request_key(method, url, request_body)).ok_or_else(not_captured)?;
+        let captured = captures[(*served).min(captures.len() - 1)].clone();
+        *served += 1;
+        Ok(captured)
+    }
+
+    // a 304 only makes sense next to the copy it revalidated, the 200 before it is what replay needs
+    pub fn save(&self, captured: &CapturedResponse) -> Result<()> {
+        let Mode::Record { next } = &self.mode else {
+            return Ok(());
+        };
+        if captured.status == 304 {
+            return Ok(());
+        }
+
+        let host = reqwest::Url::parse(&captured.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| "unknown".to_string());
+        let name = format!("{:06}-{}-{}.json", next.fetch_add(1, Ordering::SeqCst), host, captured.key());
+
+        let text = serde_json::to_string_pretty(captured).map_err(|e| DataError::Corrupt(e.to_string()))?;
+        fs::write(self.dir.join(name), text)?;
+        Ok(())
+    }
+}
+
+// method, url and body hashed, first 8 bytes as hex
+fn request_key(method: &str, url: &str, request_body: Option<&str>) -> String {
+    let material = format!("{} {}\n{}", method.to_uppercase(), url, request_body.unwrap_or_default());
+    to_hex(&keccak256(material.as_bytes())[..8])
+}
+
+// capture files in name order, which is the order they were recorded in
+fn captures_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
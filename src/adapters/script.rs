@@ -0,0 +1,54 @@
+use crate::analysis::{Analyzer, AnalyzerInput};
+use anyhow::{anyhow, Context, Result};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde_json::Value;
+use std::path::Path;
+
+// a script that never finishes fails after this many operations instead of hanging the run
+const MAX_SCRIPT_OPERATIONS: u64 = 50_000_000;
+
+// a rhai script run as an analyzer, for a quick metric without building a plugin
+// the script sees market, positions, traders and transactions as maps and arrays of the json fields,
+// whatever its last expression evaluates to is its section, a map is shown key by key
+//   let smart = traders.filter(|t| t.accuracy > 0.6).len();
+//   #{ smart_holders: smart, trades: transactions.len() }
+pub struct ScriptAnalyzer {
+    name: String,
+    engine: Engine,
+    ast: AST,
+}
+
+impl ScriptAnalyzer {
+    // the script is named after its file, my_metric.rhai reports as my_metric
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let source = std::fs::read_to_string(path).with_context(|| format!("could not read script {}", path.display()))?;
+        Self::from_source(&name, &source).with_context(|| format!("could not compile script {}", path.display()))
+    }
+
+    pub fn from_source(name: &str, source: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        // stdout may be carrying json or arrow, a script's prints go to stderr
+        engine.on_print(|text| eprintln!("{}", text));
+        engine.on_debug(|text, _, position| eprintln!("{} {}", position, text));
+        let ast = engine.compile(source)?;
+        Ok(Self { name: name.to_string(), engine, ast })
+    }
+}
+
+impl Analyzer for ScriptAnalyzer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn analyze(&self, input: &AnalyzerInput<'_>) -> Result<Value> {
+        let mut scope = Scope::new();
+        scope.push_constant("market", rhai::serde::to_dynamic(input.market)?);
+        scope.push_constant("positions", rhai::serde::to_dynamic(input.positions)?);
+        scope.push_constant("traders", rhai::serde::to_dynamic(input.traders)?);
+        scope.push_constant("transactions", rhai::serde::to_dynamic(input.transactions)?);
+        let output: Dynamic = self.engine.eval_ast_with_scope(&mut scope, &self.ast).map_err(|e| anyhow!("{}", e))?;
+        Ok(rhai::serde::from_dynamic(&output)?)
+    }
+}
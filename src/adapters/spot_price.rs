@@ -0,0 +1,48 @@
+use crate::adapters::HttpClient;
+use crate::error::{AppError, Result};
+use serde::Deserialize;
+
+// coinbase exchange's public market data, no key needed
+pub const COINBASE_API_URL: &str = "https://api.exchange.coinbase.com";
+// coinbase serves at most 300 candles a request
+const MAX_CANDLES: u32 = 300;
+const DAY_SECS: u32 = 24 * 60 * 60;
+
+#[derive(Debug, Deserialize)]
+struct TickerResponse {
+    price: String,
+}
+
+// spot prices and daily closes of crypto assets against usd
+pub struct SpotPriceFeed {
+    http_client: HttpClient,
+    url: String,
+}
+
+impl SpotPriceFeed {
+    pub fn new(http_client: HttpClient) -> Self {
+        Self { http_client, url: COINBASE_API_URL.to_string() }
+    }
+
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    // last trade price of an asset like "BTC" in usd
+    pub async fn spot(&self, asset: &str) -> Result<f64> {
+        let url = format!("{}/products/{}-USD/ticker", self.url, asset);
+        let ticker: TickerResponse = self.http_client.get(&url).await?;
+        ticker.price.parse().map_err(|_| AppError::Parse(format!("{} spot price {:?}", asset, ticker.price)))
+    }
+
+    // closes of the last days whole days, oldest first
+    pub async fn daily_closes(&self, asset: &str, days: u32) -> Result<Vec<f64>> {
+        let url = format!("{}/products/{}-USD/candles?granularity={}", self.url, asset, DAY_SECS);
+        // rows are [time, low, high, open, close, volume], newest first
+        let candles: Vec<[f64; 6]> = self.http_client.get(&url).await?;
+        let mut closes: Vec<f64> = candles.iter().take(days.min(MAX_CANDLES) as usize).map(|candle| candle[4]).collect();
+        closes.reverse();
+        Ok(closes)
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// counters for every request an adapter makes during one run
+#[derive(Debug, Default)]
+pub struct RequestStats {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    // response bodies as read, after gzip or deflate was undone, not what crossed the wire
+    body_bytes: AtomicU64,
+    cache_hits: AtomicU64,
+    retries: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+// point in time copy for printing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestStatsSnapshot {
+    pub requests: u64,
+    pub failures: u64,
+    pub body_bytes: u64,
+    pub cache_hits: u64,
+    pub retries: u64,
+    pub total_latency: Duration,
+}
+
+impl RequestStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_request(&self, body_bytes: u64, latency: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.body_bytes.fetch_add(body_bytes, Ordering::Relaxed);
+        self.latency_micros.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self, latency: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        self.latency_micros.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> RequestStatsSnapshot {
+        RequestStatsSnapshot {
+            requests: self.requests.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            body_bytes: self.body_bytes.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            total_latency: Duration::from_micros(self.latency_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl RequestStatsSnapshot {
+    pub fn average_latency(&self) -> Duration {
+        if self.requests == 0 {
+            Duration::ZERO
+        } else {
+            self.total_latency / self.requests as u32
+        }
+    }
+}
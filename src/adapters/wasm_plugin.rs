@@ -0,0 +1,97 @@
+use crate::analysis::{Analyzer, AnalyzerInput};
+use crate::paths;
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+// a plugin gets this much linear memory and this much fuel per market, a runaway one fails instead of hanging the run
+const PLUGIN_MEMORY: usize = 64 << 20;
+const PLUGIN_FUEL: u64 = 5_000_000_000;
+
+// a wasm module run as an analyzer, built once and instantiated fresh for every market
+// the plugin abi, all in the module's own exports:
+//   memory                           its linear memory
+//   alloc(len: i32) -> i32           room for the input json
+//   analyze(ptr: i32, len: i32) -> i64   reads the AnalyzerInput json, returns ptr << 32 | len of its output json
+// nothing is imported, a plugin has no files, network or clock, only the data it's handed
+pub struct WasmAnalyzer {
+    name: String,
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmAnalyzer {
+    // the plugin is named after its file, my_metric.wasm reports as my_metric
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let bytes = fs::read(path).with_context(|| format!("could not read plugin {}", path.display()))?;
+        Self::from_bytes(&name, &bytes).with_context(|| format!("could not load plugin {}", path.display()))
+    }
+
+    // wasm or its text format
+    pub fn from_bytes(name: &str, bytes: &[u8]) -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, bytes)?;
+        if let Some(import) = module.imports().next() {
+            bail!("plugins can't import anything, {} imports {}::{}", name, import.module(), import.name());
+        }
+        Ok(Self { name: name.to_string(), engine, module })
+    }
+
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let limits = StoreLimitsBuilder::new().memory_size(PLUGIN_MEMORY).build();
+        let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(PLUGIN_FUEL)?;
+
+        let instance = Instance::new(&mut store, &self.module, &[])?;
+        let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| anyhow!("no exported memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let analyze = instance.get_typed_func::<(i32, i32), i64>(&mut store, "analyze")?;
+
+        let len = i32::try_from(input.len()).context("input too large for a plugin")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, input)?;
+        let packed = analyze.call(&mut store, (ptr, len))? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let output = memory
+            .data(&store)
+            .get(out_ptr..out_ptr + out_len)
+            .ok_or_else(|| anyhow!("output at {}..{} is outside the plugin's memory", out_ptr, out_ptr + out_len))?;
+        Ok(output.to_vec())
+    }
+}
+
+impl Analyzer for WasmAnalyzer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn analyze(&self, input: &AnalyzerInput<'_>) -> Result<Value> {
+        let output = self.run(&serde_json::to_vec(input)?)?;
+        serde_json::from_slice(&output).context("output isn't json")
+    }
+}
+
+// plugins in the data dir unless --plugins names another
+pub fn default_plugin_dir() -> PathBuf {
+    paths::data_file("plugins")
+}
+
+// every *.wasm in the dir in name order, a missing dir has none
+// one that fails to load stops the run, a broken plugin shouldn't go unnoticed
+pub fn load_plugins(dir: &Path) -> Result<Vec<WasmAnalyzer>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.retain(|path| path.extension().is_some_and(|e| e == "wasm"));
+    paths.sort();
+    paths.iter().map(|path| WasmAnalyzer::load(path)).collect()
+}
@@ -0,0 +1,95 @@
+use crate::adapters::abi::to_hex;
+use crate::adapters::HttpClient;
+use crate::clock;
+use crate::error::{AppError, HttpError, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+
+// tries after the first failed delivery, a receiver restarting shouldn't lose the report
+pub const DEFAULT_WEBHOOK_RETRIES: u32 = 3;
+// wait before the first retry, doubled for each one after up to the cap
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DOUBLINGS: u32 = 6;
+// unix seconds of the delivery, signed along with the body
+pub const TIMESTAMP_HEADER: &str = "X-Explorer-Timestamp";
+// "sha256=<hex>", only sent when the target has a secret
+pub const SIGNATURE_HEADER: &str = "X-Explorer-Signature";
+
+fn default_retries() -> u32 {
+    DEFAULT_WEBHOOK_RETRIES
+}
+
+// a url reports are POSTed to, --webhook or a [[webhooks]] entry of config.toml:
+//   [[webhooks]]
+//   url = "https://example.com/hooks/polymarket"
+//   secret = "shared secret"
+//   retries = 5
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookTarget {
+    pub url: String,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+impl WebhookTarget {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), secret: None, retries: DEFAULT_WEBHOOK_RETRIES }
+    }
+
+    pub fn with_secret(mut self, secret: Option<String>) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+}
+
+// hmac-sha256 of "<timestamp>.<body>", the timestamp in it lets receivers turn away replayed deliveries
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac takes keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", to_hex(&mac.finalize().into_bytes()))
+}
+
+// network errors, rate limits and server errors may pass, any other status means the receiver turned the body down
+fn retryable(error: &AppError) -> bool {
+    match error {
+        AppError::Http(HttpError::Request(_) | HttpError::Timeout { .. }) => true,
+        AppError::Http(HttpError::Status { status, .. }) => status.is_server_error() || status.as_u16() == 429,
+        _ => false,
+    }
+}
+
+// POST the body, retried with backoff up to the target's retries
+pub async fn deliver<B: Serialize + ?Sized>(client: &HttpClient, target: &WebhookTarget, body: &B) -> Result<()> {
+    // serialized once, the signature covers exactly the bytes sent
+    let body = serde_json::to_string(body).map_err(|e| AppError::Parse(e.to_string()))?;
+    let mut attempt = 0;
+    loop {
+        // signed again each try so a retry isn't mistaken for a replay
+        let timestamp = clock::now().timestamp();
+        let mut headers = vec![(TIMESTAMP_HEADER, timestamp.to_string())];
+        if let Some(secret) = &target.secret {
+            headers.push((SIGNATURE_HEADER, sign(secret, timestamp, &body)));
+        }
+
+        match client.post_json_discard(&target.url, &headers, body.clone()).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < target.retries && retryable(&e) => {
+                eprintln!("webhook {} failed, retrying: {}", target.url, e);
+                client.stats().record_retry();
+                tokio::time::sleep(RETRY_DELAY * 2u32.pow(attempt.min(MAX_RETRY_DOUBLINGS))).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
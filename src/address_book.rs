@@ -0,0 +1,123 @@
+use crate::error::Result;
+use crate::state_db::StateDb;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// user assigned labels for wallet addresses, "known sharp", "insider-suspect", ens names, ...
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddressBook {
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    // wallets one person controls, group name to its addresses, an address is in at most one group
+    #[serde(default)]
+    pub groups: BTreeMap<String, Vec<String>>,
+    // looked up names (usernames, ens), never saved, the resolver keeps its own cache
+    #[serde(skip)]
+    resolved: HashMap<String, String>,
+}
+
+impl AddressBook {
+    // from the state db, empty until something is labeled
+    pub fn load() -> Result<Self> {
+        StateDb::open()?.address_book()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        StateDb::open()?.save_address_book(self)
+    }
+
+    // returns the label it replaced, if any
+    pub fn set(&mut self, address: &str, label: &str) -> Option<String> {
+        self.labels.insert(address.to_lowercase(), label.trim().to_string())
+    }
+
+    pub fn remove(&mut self, address: &str) -> Option<String> {
+        self.labels.remove(&address.to_lowercase())
+    }
+
+    pub fn label(&self, address: &str) -> Option<&str> {
+        self.labels.get(&address.to_lowercase()).map(String::as_str)
+    }
+
+    // add addresses to a group, creating it, addresses already in another group move over
+    // returns how many addresses were moved
+    pub fn group(&mut self, name: &str, addresses: &[String]) -> usize {
+        let addresses: Vec<String> = addresses.iter().map(|a| a.to_lowercase()).collect();
+        let mut moved = 0;
+        for (_, members) in self.groups.iter_mut().filter(|(group, _)| group.as_str() != name.trim()) {
+            let before = members.len();
+            members.retain(|m| !addresses.contains(m));
+            moved += before - members.len();
+        }
+        self.groups.retain(|_, members| !members.is_empty());
+
+        let members = self.groups.entry(name.trim().to_string()).or_default();
+        for address in addresses {
+            if !members.contains(&address) {
+                members.push(address);
+            }
+        }
+        moved
+    }
+
+    // take an address out of its group, the group goes when it's empty, returns the group's name
+    pub fn ungroup(&mut self, address: &str) -> Option<String> {
+        let address = address.to_lowercase();
+        let name = self.group_of(&address)?.to_string();
+        if let Some(members) = self.groups.get_mut(&name) {
+            members.retain(|m| *m != address);
+            if members.is_empty() {
+                self.groups.remove(&name);
+            }
+        }
+        Some(name)
+    }
+
+    pub fn group_of(&self, address: &str) -> Option<&str> {
+        let address = address.to_lowercase();
+        self.groups
+            .iter()
+            .find(|(_, members)| members.contains(&address))
+            .map(|(name, _)| name.as_str())
+    }
+
+    // every address behind a wallet or group name, a lone wallet is just itself
+    pub fn members(&self, wallet: &str) -> Vec<String> {
+        if let Some(members) = self.groups.get(wallet.trim()) {
+            return members.clone();
+        }
+        match self.group_of(wallet) {
+            Some(name) => self.groups[name].clone(),
+            None => vec![wallet.to_lowercase()],
+        }
+    }
+
+    // lowercase address to group name, for treating a group as one trader in the analysis
+    pub fn wallet_groups(&self) -> HashMap<String, String> {
+        self.groups
+            .iter()
+            .flat_map(|(name, members)| members.iter().map(move |m| (m.clone(), name.clone())))
+            .collect()
+    }
+
+    // names from the resolver, user labels still win
+    pub fn add_resolved(&mut self, names: HashMap<String, String>) {
+        for (address, name) in names {
+            self.resolved.insert(address.to_lowercase(), name);
+        }
+    }
+
+    // how an address is shown in every output, the label first when there is one
+    pub fn display(&self, address: &str) -> String {
+        let address_key = address.to_lowercase();
+        let name = self.label(address).or_else(|| self.resolved.get(&address_key).map(String::as_str));
+        let shown = match name {
+            Some(label) => format!("{} ({})", label, address),
+            None => address.to_string(),
+        };
+        match self.group_of(address) {
+            Some(group) => format!("{} [{}]", shown, group),
+            None => shown,
+        }
+    }
+}
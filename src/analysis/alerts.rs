@@ -0,0 +1,99 @@
+use crate::analysis::MarketSummary;
+use serde::Serialize;
+use std::collections::HashMap;
+use schemars::JsonSchema;
+
+// yes price change between two polls worth shouting about
+pub const PRICE_MOVE_ALERT: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    PriceMove,
+    SmartMoneyFlip,
+    NewWhale,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::PriceMove => "price_move",
+            AlertKind::SmartMoneyFlip => "smart_money_flip",
+            AlertKind::NewWhale => "new_whale",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Alert {
+    pub slug: String,
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+// remembers the last poll of every market so the next one can be compared to it
+#[derive(Debug, Default)]
+pub struct AlertTracker {
+    last: HashMap<String, MarketSummary>,
+}
+
+impl AlertTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // polls saved by an earlier or another run replace what this one remembers
+    pub fn restore(&mut self, baselines: HashMap<String, MarketSummary>) {
+        self.last.extend(baselines);
+    }
+
+    // the first poll of a market only sets the baseline
+    pub fn update(&mut self, summaries: &[MarketSummary]) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for summary in summaries {
+            if let Some(previous) = self.last.get(&summary.slug) {
+                alerts.extend(detect_alerts(previous, summary));
+            }
+            self.last.insert(summary.slug.clone(), summary.clone());
+        }
+        alerts
+    }
+}
+
+// what changed between two polls of the same market
+pub fn detect_alerts(previous: &MarketSummary, current: &MarketSummary) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    let mut push = |kind, message: String| {
+        alerts.push(Alert { slug: current.slug.clone(), kind, message });
+    };
+
+    let price_move = current.yes_price - previous.yes_price;
+    if price_move.abs() >= PRICE_MOVE_ALERT {
+        push(AlertKind::PriceMove, format!(
+            "YES moved {:+.3} to {:.3}",
+            price_move,
+            current.yes_price,
+        ));
+    }
+
+    if let (Some(before), Some(after)) = (previous.smart_lean, current.smart_lean)
+        && (before >= 0.5) != (after >= 0.5)
+    {
+        let side = if after >= 0.5 { "YES" } else { "NO" };
+        push(AlertKind::SmartMoneyFlip, format!(
+            "smart money now leans {} ({:.1}% on YES)",
+            side,
+            after * 100.0,
+        ));
+    }
+
+    if current.whale_count > previous.whale_count {
+        push(AlertKind::NewWhale, format!(
+            "whales went from {} to {}",
+            previous.whale_count,
+            current.whale_count,
+        ));
+    }
+
+    alerts
+}
@@ -0,0 +1,22 @@
+use crate::standard_data::models::{Market, Position, Trader, Transaction};
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+// what analyze already fetched for a market, handed to every extra analyzer as is
+// positions and traders are empty without a local db, transactions without a trade log
+#[derive(Debug, Serialize)]
+pub struct AnalyzerInput<'a> {
+    pub market: &'a Market,
+    pub positions: &'a [Position],
+    pub traders: &'a [Trader],
+    pub transactions: &'a [Transaction],
+}
+
+// an analysis that isn't built in, e.g. a wasm plugin, its output goes into the report as its own section
+// the output is any json, objects are shown key by key
+pub trait Analyzer: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn analyze(&self, input: &AnalyzerInput<'_>) -> Result<Value>;
+}
@@ -0,0 +1,87 @@
+use crate::standard_data::models::{Position, Trader, Transaction};
+use serde::Serialize;
+use std::collections::HashSet;
+
+// one kind of bad row and how many of them were found
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditCheck {
+    pub table: &'static str,
+    pub name: &'static str,
+    pub rows: usize,
+    // a few offending rows so they can be looked up
+    pub examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditReport {
+    // rows scanned per table
+    pub tables: Vec<(&'static str, usize)>,
+    pub checks: Vec<AuditCheck>,
+}
+
+impl AuditReport {
+    pub fn problems(&self) -> usize {
+        self.checks.iter().map(|c| c.rows).sum()
+    }
+}
+
+// scan the local tables for rows no analysis should trust
+pub fn audit(positions: &[Position], transactions: &[Transaction], traders: &[Trader], examples: usize) -> AuditReport {
+    let outside_unit = |price: f64| !(0.0..=1.0).contains(&price);
+    let traded: HashSet<(&str, &str)> = transactions
+        .iter()
+        .map(|tx| (tx.trader_address.as_str(), tx.market_id.as_str()))
+        .collect();
+
+    let position_label = |p: &&Position| format!("{} {} {}", p.trader_address, p.market_id, p.side);
+    let transaction_label = |tx: &&Transaction| format!("{} {}", tx.transaction_hash, tx.trader_address);
+    let trader_label = |t: &&Trader| t.trader_address.clone();
+
+    let checks = vec![
+        check("positions", "negative shares", positions.iter().filter(|p| p.shares_held < 0.0), position_label, examples),
+        check("positions", "entry price outside [0, 1]", positions.iter().filter(|p| outside_unit(p.avg_entry_price)), position_label, examples),
+        check(
+            "positions",
+            "no transactions for the trader in the market",
+            positions.iter().filter(|p| !traded.contains(&(p.trader_address.as_str(), p.market_id.as_str()))),
+            position_label,
+            examples,
+        ),
+        check("transactions", "negative shares", transactions.iter().filter(|tx| tx.shares < 0.0), transaction_label, examples),
+        check(
+            "transactions",
+            "fill price outside [0, 1]",
+            transactions.iter().filter(|tx| tx.shares > 0.0 && outside_unit(tx.usdc_amount / tx.shares)),
+            transaction_label,
+            examples,
+        ),
+        check(
+            "traders",
+            "more wins than resolved markets",
+            traders.iter().filter(|t| t.total_wins > t.total_markets_resolved),
+            trader_label,
+            examples,
+        ),
+    ];
+
+    AuditReport {
+        tables: vec![("positions", positions.len()), ("transactions", transactions.len()), ("traders", traders.len())],
+        checks,
+    }
+}
+
+fn check<'a, T: 'a>(
+    table: &'static str,
+    name: &'static str,
+    rows: impl Iterator<Item = &'a T>,
+    label: impl Fn(&&'a T) -> String,
+    examples: usize,
+) -> AuditCheck {
+    let rows: Vec<&T> = rows.collect();
+    AuditCheck {
+        table,
+        name,
+        rows: rows.len(),
+        examples: rows.iter().take(examples).map(&label).collect(),
+    }
+}
@@ -0,0 +1,324 @@
+use crate::analysis::smart_money::SmartMoney;
+use crate::ingest::{fold_transactions, traders_from_outcomes, Ledgers, Outcomes};
+use crate::standard_data::models::{MarketResolution, Transaction};
+use std::collections::{HashMap, HashSet};
+
+// polygon makes a block roughly every 2 seconds
+pub const BLOCKS_PER_DAY: u64 = 43_200;
+
+// strategy knobs for following smart money
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    // traders whose entries get copied
+    pub smart_money: SmartMoney,
+    // how long after the smart trader's entry we are still willing to copy it
+    pub max_entry_delay_days: f64,
+    // usdc put into every copied trade
+    pub stake: f64,
+}
+
+// one simulated copy trade
+#[derive(Debug, Clone)]
+pub struct BacktestTrade {
+    pub market_id: String,
+    pub side: String,
+    pub followed_trader: String,
+    pub signal_block: u64,
+    pub entry_block: u64,
+    pub entry_price: f64,
+    pub stake: f64,
+    pub payout: f64,
+    pub resolution_block: u64,
+}
+
+impl BacktestTrade {
+    pub fn pnl(&self) -> f64 {
+        self.payout - self.stake
+    }
+
+    pub fn won(&self) -> bool {
+        self.payout > 0.0
+    }
+}
+
+// results of a backtest run
+#[derive(Debug, Clone)]
+pub struct BacktestReport {
+    pub config: BacktestConfig,
+    // traders who met the bar at one of their buys
+    pub smart_traders: usize,
+    // market sides a smart trader bought into, each counted once
+    pub signals: usize,
+    // signals with no trade on the same token inside the entry window, counted once however often they repeat
+    pub missed_signals: usize,
+    // copied trades on markets that have not resolved yet
+    pub open_trades: usize,
+    // resolved copied trades ordered by resolution block
+    pub trades: Vec<BacktestTrade>,
+    pub total_staked: f64,
+    pub total_returned: f64,
+    pub roi: f64,
+    pub hit_rate: f64,
+    // biggest peak to trough drop of cumulative pnl in usdc
+    pub max_drawdown: f64,
+}
+
+// replay history and copy the first entry of a smart trader into each market side
+// who counts as smart is decided at each buy from what was known then, the trader's earlier trades
+// and the markets resolved before that block, so later outcomes can't pick the traders to follow
+pub fn run_backtest(
+    config: &BacktestConfig,
+    transactions: &[Transaction],
+    resolutions: &[MarketResolution],
+) -> BacktestReport {
+    let resolved: HashMap<&str, &MarketResolution> = resolutions
+        .iter()
+        .map(|r| (r.condition_id.as_str(), r))
+        .collect();
+
+    // replay in chain order even if the provider didn't sort, ties in a block by time then log index
+    let mut ordered: Vec<&Transaction> = transactions.iter().collect();
+    ordered.sort_by_key(|tx| (tx.block_number, tx.timestamp, tx.log_index));
+
+    // resolutions become known as the replay passes their block
+    let mut by_block: Vec<MarketResolution> = resolutions.to_vec();
+    by_block.sort_by_key(|r| r.resolution_block);
+
+    // fills available per token, used to find the price we could have copied at
+    let mut fills: HashMap<&str, Vec<(u64, f64)>> = HashMap::new();
+    for tx in &ordered {
+        if tx.shares > 0.0 {
+            fills
+                .entry(tx.token_id.as_str())
+                .or_default()
+                .push((tx.block_number, tx.usdc_amount / tx.shares));
+        }
+    }
+
+    let delay_blocks = (config.max_entry_delay_days * BLOCKS_PER_DAY as f64) as u64;
+
+    let mut ledgers = Ledgers::new();
+    let mut known = 0;
+    // outcomes of the markets resolved so far, grown as the replay passes each resolution
+    let mut outcomes = Outcomes::new();
+    let mut smart: HashSet<&str> = HashSet::new();
+
+    let mut signalled: HashSet<(&str, &str)> = HashSet::new();
+    let mut followed: HashSet<(&str, &str)> = HashSet::new();
+    let mut open_trades = 0;
+    let mut trades = Vec::new();
+
+    for tx in &ordered {
+        let before = by_block[known..].partition_point(|r| r.resolution_block < tx.block_number);
+        for resolution in &by_block[known..known + before] {
+            outcomes.insert(resolution.condition_id.as_str(), (resolution.outcome.as_str(), resolution.resolution_block));
+        }
+        known += before;
+
+        let key = (tx.market_id.as_str(), tx.side.as_str());
+        let candidate = tx.action.eq_ignore_ascii_case("BUY") && !followed.contains(&key);
+        // every trade changes the trader's own record, so a verdict is only good for the trade it was made at
+        let is_smart = candidate && smart_as_of(&config.smart_money, &ledgers, &tx.trader_address, &outcomes);
+
+        // the trade itself only counts toward the trader's record after the signal it gives
+        fold_transactions(&mut ledgers, std::slice::from_ref(*tx));
+
+        if !is_smart {
+            continue;
+        }
+        smart.insert(tx.trader_address.as_str());
+        // a side that keeps getting signals without a fill is still one signal
+        signalled.insert(key);
+
+        // we only see the smart trade once it's on chain so copy the next fill after it
+        let entry = fills.get(tx.token_id.as_str()).and_then(|token_fills| {
+            let start = token_fills.partition_point(|(block, _)| *block <= tx.block_number);
+            token_fills[start..]
+                .iter()
+                .take_while(|(block, _)| *block <= tx.block_number + delay_blocks)
+                .find(|(_, price)| *price > 0.0 && *price < 1.0)
+        });
+
+        let Some(&(entry_block, entry_price)) = entry else {
+            continue;
+        };
+        followed.insert(key);
+
+        let Some(resolution) = resolved.get(tx.market_id.as_str()) else {
+            open_trades += 1;
+            continue;
+        };
+
+        let shares = config.stake / entry_price;
+        let payout = if resolution.outcome.eq_ignore_ascii_case(&tx.side) {
+            shares
+        } else {
+            0.0
+        };
+
+        trades.push(BacktestTrade {
+            market_id: tx.market_id.clone(),
+            side: tx.side.clone(),
+            followed_trader: tx.trader_address.clone(),
+            signal_block: tx.block_number,
+            entry_block,
+            entry_price,
+            stake: config.stake,
+            payout,
+            resolution_block: resolution.resolution_block,
+        });
+    }
+    let signals = signalled.len();
+    let missed_signals = signals - followed.len();
+
+    trades.sort_by_key(|t| t.resolution_block);
+
+    let total_staked: f64 = trades.iter().map(|t| t.stake).sum();
+    let total_returned: f64 = trades.iter().map(|t| t.payout).sum();
+    let wins = trades.iter().filter(|t| t.won()).count();
+
+    BacktestReport {
+        config: config.clone(),
+        smart_traders: smart.len(),
+        signals,
+        missed_signals,
+        open_trades,
+        total_staked,
+        total_returned,
+        roi: ratio(total_returned - total_staked, total_staked),
+        hit_rate: ratio(wins as f64, trades.len() as f64),
+        max_drawdown: max_drawdown(&trades),
+        trades,
+    }
+}
+
+// whether the trader met the smart money bar with only their ledgers so far and the markets resolved so far
+fn smart_as_of(smart_money: &SmartMoney, ledgers: &Ledgers, trader: &str, known: &Outcomes) -> bool {
+    let own = ledgers
+        .range((trader.to_string(), String::new())..)
+        .take_while(|((address, _), _)| address == trader)
+        .map(|(_, ledger)| ledger);
+    traders_from_outcomes(own, known).first().is_some_and(|stats| smart_money.includes(stats))
+}
+
+// largest drop from a running peak of cumulative pnl
+fn max_drawdown(trades: &[BacktestTrade]) -> f64 {
+    let mut cumulative = 0.0;
+    let mut peak = 0.0_f64;
+    let mut drawdown = 0.0_f64;
+
+    for trade in trades {
+        cumulative += trade.pnl();
+        peak = peak.max(cumulative);
+        drawdown = drawdown.max(peak - cumulative);
+    }
+
+    drawdown
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_data::models::Collateral;
+
+    const SMART: &str = "0xsmart";
+    const CROWD: &str = "0xcrowd";
+
+    fn buy(trader: &str, market: &str, block: u64, price: f64) -> Transaction {
+        Transaction {
+            block_number: block,
+            transaction_hash: format!("0x{}{}", market, block),
+            log_index: Some(0),
+            trader_address: trader.to_string(),
+            token_id: format!("{}-yes", market),
+            side: "YES".to_string(),
+            action: "BUY".to_string(),
+            shares: 10.0,
+            usdc_amount: 10.0 * price,
+            market_id: market.to_string(),
+            timestamp: Some(block as i64 * 2),
+            collateral: Collateral::default(),
+        }
+    }
+
+    fn resolution(market: &str, block: u64) -> MarketResolution {
+        MarketResolution {
+            condition_id: market.to_string(),
+            outcome: "YES".to_string(),
+            resolution_block: block,
+            yes_token_id: format!("{}-yes", market),
+            no_token_id: format!("{}-no", market),
+        }
+    }
+
+    fn config() -> BacktestConfig {
+        BacktestConfig { smart_money: SmartMoney::default(), max_entry_delay_days: 1.0, stake: 100.0 }
+    }
+
+    // five winning markets for the smart wallet, all resolving at track_record_block, then its buy into "next"
+    // with a crowd fill right after to copy
+    fn history(track_record_block: u64) -> (Vec<Transaction>, Vec<MarketResolution>) {
+        let mut transactions: Vec<Transaction> = (1..=5).map(|i| buy(SMART, &format!("won{}", i), i * 10, 0.5)).collect();
+        transactions.push(buy(SMART, "next", 100, 0.5));
+        transactions.push(buy(CROWD, "next", 101, 0.4));
+        let mut resolutions: Vec<MarketResolution> = (1..=5).map(|i| resolution(&format!("won{}", i), track_record_block)).collect();
+        resolutions.push(resolution("next", 500));
+        (transactions, resolutions)
+    }
+
+    #[test]
+    fn traders_are_judged_on_markets_resolved_before_the_signal() {
+        let (transactions, resolutions) = history(90);
+        let report = run_backtest(&config(), &transactions, &resolutions);
+        assert_eq!(report.smart_traders, 1);
+        assert_eq!(report.signals, 1);
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.trades[0].followed_trader, SMART);
+        assert_eq!(report.trades[0].entry_block, 101);
+    }
+
+    #[test]
+    fn a_record_made_after_the_signal_is_no_reason_to_follow() {
+        // the same wins, but they only resolve after the buy into "next"
+        let (transactions, resolutions) = history(200);
+        let report = run_backtest(&config(), &transactions, &resolutions);
+        assert_eq!(report.smart_traders, 0);
+        assert_eq!(report.signals, 0);
+        assert!(report.trades.is_empty());
+    }
+
+    #[test]
+    fn a_signal_that_keeps_missing_is_counted_once() {
+        let (mut transactions, resolutions) = history(90);
+        transactions.retain(|tx| tx.trader_address != CROWD);
+        transactions.push(buy(SMART, "next", 110, 0.5));
+        transactions.push(buy(SMART, "next", 120, 0.5));
+        // no window, so the wallet's own repeats aren't fills to copy either
+        let config = BacktestConfig { max_entry_delay_days: 0.0, ..config() };
+        let report = run_backtest(&config, &transactions, &resolutions);
+        assert_eq!(report.signals, 1);
+        assert_eq!(report.missed_signals, 1);
+    }
+
+    #[test]
+    fn trades_in_one_block_replay_in_time_and_log_order() {
+        let (mut transactions, resolutions) = history(90);
+        // two crowd fills in the smart buy's next block, the earlier one by timestamp is the copy price
+        let mut later = buy(CROWD, "next", 101, 0.6);
+        later.timestamp = Some(203);
+        transactions.insert(0, later);
+        let forward = run_backtest(&config(), &transactions, &resolutions);
+        transactions.reverse();
+        let backward = run_backtest(&config(), &transactions, &resolutions);
+        assert_eq!(forward.trades[0].entry_price, 0.4);
+        assert_eq!(backward.trades[0].entry_price, 0.4);
+    }
+}
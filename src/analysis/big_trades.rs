@@ -0,0 +1,83 @@
+use crate::standard_data::models::Transaction;
+use std::collections::{HashMap, HashSet};
+
+// what a trade did to the trader's holding of that outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionChange {
+    Open,
+    Add,
+    Reduce,
+    Close,
+}
+
+impl PositionChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PositionChange::Open => "open",
+            PositionChange::Add => "add",
+            PositionChange::Reduce => "reduce",
+            PositionChange::Close => "close",
+        }
+    }
+
+    // from the holding before and after, a sell with nothing held still counts as a close
+    pub fn between(before: f64, after: f64) -> Self {
+        if after > before {
+            if before > 0.0 { PositionChange::Add } else { PositionChange::Open }
+        } else if after <= 0.0 {
+            PositionChange::Close
+        } else {
+            PositionChange::Reduce
+        }
+    }
+}
+
+// one fill, unique across dumps that carry a log index and those that don't
+pub fn fill_key(tx: &Transaction) -> (String, Option<u32>, String, String) {
+    (tx.transaction_hash.clone(), tx.log_index, tx.trader_address.clone(), tx.token_id.clone())
+}
+
+// holding of the traded outcome once the fill lands
+// a sell past what was bought means the buys predate the history, floor at zero
+pub fn shares_after(before: f64, tx: &Transaction) -> f64 {
+    if tx.removes_shares() {
+        (before - tx.shares).max(0.0)
+    } else {
+        before + tx.shares
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BigTrade {
+    pub transaction: Transaction,
+    pub change: PositionChange,
+    // shares of the outcome held after the trade, as far as the history shows
+    pub shares_after: f64,
+}
+
+// trades in `recent` of at least min_usdc, largest first
+// holdings are replayed over the full history so positions opened before the window are known
+pub fn big_trades(history: &[Transaction], recent: &[Transaction], min_usdc: f64) -> Vec<BigTrade> {
+    let wanted: HashSet<_> = recent.iter().filter(|tx| tx.is_fill() && tx.usdc_amount >= min_usdc).map(fill_key).collect();
+
+    let mut ordered: Vec<&Transaction> = history.iter().collect();
+    ordered.sort_by_key(|tx| tx.block_number);
+
+    let mut holdings: HashMap<(&str, &str), f64> = HashMap::new();
+    let mut trades = Vec::new();
+    for tx in ordered {
+        let held = holdings.entry((tx.trader_address.as_str(), tx.token_id.as_str())).or_default();
+        let before = *held;
+        *held = shares_after(before, tx);
+
+        if !wanted.contains(&fill_key(tx)) {
+            continue;
+        }
+
+        let change = PositionChange::between(before, *held);
+        trades.push(BigTrade { transaction: tx.clone(), change, shares_after: *held });
+    }
+
+    trades.sort_by(|a, b| b.transaction.usdc_amount.total_cmp(&a.transaction.usdc_amount));
+    trades
+}
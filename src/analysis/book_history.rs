@@ -0,0 +1,76 @@
+use crate::standard_data::models::{BookLevel, OrderBook};
+use serde::{Deserialize, Serialize};
+
+// levels kept per side of an archived book, well past the widest depth distance on any liquid market
+pub const ARCHIVED_LEVELS: usize = 50;
+// distances from the mid that depth is summed within
+pub const DEPTH_DISTANCES: [f64; 4] = [0.01, 0.02, 0.05, 0.10];
+
+// one archived book of a market's YES token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookSnapshot {
+    pub timestamp: i64,
+    pub book: OrderBook,
+}
+
+impl BookSnapshot {
+    // only the best levels, the deep tail is rarely touched and would grow the archive fastest
+    pub fn new(timestamp: i64, mut book: OrderBook) -> Self {
+        book.bids.truncate(ARCHIVED_LEVELS);
+        book.asks.truncate(ARCHIVED_LEVELS);
+        Self { timestamp, book }
+    }
+}
+
+// the top of one snapshot and what rested near the mid
+#[derive(Debug, Clone, Serialize)]
+pub struct BookPoint {
+    pub timestamp: i64,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    // None unless both sides have orders
+    pub spread: Option<f64>,
+    // usd resting within each distance of the mid, same order as the distances
+    pub bid_depth: Vec<f64>,
+    pub ask_depth: Vec<f64>,
+}
+
+impl BookPoint {
+    // both sides within the distance at index i
+    pub fn depth(&self, i: usize) -> f64 {
+        self.bid_depth[i] + self.ask_depth[i]
+    }
+}
+
+// one point per snapshot, oldest first
+// the mid is between the best bid and ask, or whichever side exists, an empty book has no depth anywhere
+pub fn book_history(snapshots: &[BookSnapshot], distances: &[f64]) -> Vec<BookPoint> {
+    snapshots.iter().map(|snapshot| {
+        let best_bid = snapshot.book.bids.iter().map(|level| level.price).reduce(f64::max);
+        let best_ask = snapshot.book.asks.iter().map(|level| level.price).reduce(f64::min);
+        let mid = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            (bid, ask) => bid.or(ask),
+        };
+
+        // a hair of slack so a level exactly a cent away isn't lost to float error
+        let within = |levels: &[BookLevel], distance: f64| -> f64 {
+            let Some(mid) = mid else {
+                return 0.0;
+            };
+            levels.iter()
+                .filter(|level| (level.price - mid).abs() <= distance + 1e-9)
+                .map(|level| level.price * level.size)
+                .sum()
+        };
+
+        BookPoint {
+            timestamp: snapshot.timestamp,
+            best_bid,
+            best_ask,
+            spread: best_bid.zip(best_ask).map(|(bid, ask)| ask - bid),
+            bid_depth: distances.iter().map(|d| within(&snapshot.book.bids, *d)).collect(),
+            ask_depth: distances.iter().map(|d| within(&snapshot.book.asks, *d)).collect(),
+        }
+    }).collect()
+}
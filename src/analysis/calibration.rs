@@ -0,0 +1,311 @@
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::analysis::probability_model::{self, ProbabilityModel, SmartHolding};
+use crate::analysis::smart_money::SmartMoney;
+use crate::standard_data::models::{MarketResolution, Trader, TraderSnapshot, Transaction};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// knobs for scoring the smart money probability against resolved markets
+#[derive(Debug, Clone)]
+pub struct CalibrationConfig {
+    pub smart_money: SmartMoney,
+    // the prediction is rebuilt from trades this long before resolution
+    pub days_before: f64,
+    // equal width probability buckets for the calibration curve
+    pub buckets: usize,
+}
+
+// predictions that fell in one probability bucket
+#[derive(Debug, Clone)]
+pub struct CalibrationBucket {
+    pub low: f64,
+    pub high: f64,
+    pub count: usize,
+    pub mean_predicted: f64,
+    // share of these markets that resolved YES
+    pub observed: f64,
+}
+
+// how one predictor did across every scored market
+#[derive(Debug, Clone)]
+pub struct PredictorScore {
+    // model name, or "market" for the last traded price
+    pub name: &'static str,
+    pub predictions: usize,
+    // mean squared error of the YES probability, lower is better, 0.25 is a coin flip
+    pub brier: f64,
+    pub buckets: Vec<CalibrationBucket>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CalibrationReport {
+    pub config: CalibrationConfig,
+    // smart holders behind the scored predictions
+    pub smart_traders: usize,
+    // stored trader stats snapshots to judge traders by
+    pub snapshots: usize,
+    pub resolved_markets: usize,
+    // resolved markets whose cutoff came before any snapshot
+    pub before_first_snapshot: usize,
+    // resolved markets with at least one smart holder at the cutoff
+    pub scored_markets: usize,
+    // every model, then the market price as a baseline
+    pub scores: Vec<PredictorScore>,
+}
+
+// open cost per trader and side, sells take out cost at the running average
+#[derive(Debug, Default)]
+pub struct SideBook {
+    pub shares: f64,
+    pub cost: f64,
+}
+
+impl SideBook {
+    pub fn apply(&mut self, tx: &Transaction) {
+        if tx.removes_shares() {
+            if self.shares > 0.0 {
+                let sold = tx.shares.min(self.shares);
+                self.cost -= self.cost * sold / self.shares;
+                self.shares -= sold;
+            }
+        } else {
+            self.shares += tx.shares;
+            self.cost += tx.usdc_amount;
+        }
+    }
+}
+
+// rebuild what the tool would have said before each market resolved and score it against the outcome
+// smart money at a cutoff is whoever met the bar in the last stored snapshot before it, markets resolving
+// before the first snapshot can't be scored without looking ahead and are left out
+pub fn run_calibration(
+    config: &CalibrationConfig,
+    snapshots: &[TraderSnapshot],
+    transactions: &[Transaction],
+    resolutions: &[MarketResolution],
+) -> CalibrationReport {
+    let mut snapshots: Vec<&TraderSnapshot> = snapshots.iter().collect();
+    snapshots.sort_by_key(|snapshot| snapshot.block);
+    // each snapshot's smart traders, worked out the first time a cutoff lands on it
+    let mut smart_by_snapshot: HashMap<usize, HashMap<&str, &Trader>> = HashMap::new();
+
+    let mut by_market: HashMap<&str, Vec<&Transaction>> = HashMap::new();
+    for tx in transactions {
+        by_market.entry(tx.market_id.as_str()).or_default().push(tx);
+    }
+
+    let lead_blocks = (config.days_before * BLOCKS_PER_DAY as f64) as u64;
+
+    let mut smart_traders: HashSet<&str> = HashSet::new();
+    let mut before_first_snapshot = 0;
+    // (model predictions in ProbabilityModel::ALL order, market price, resolved yes)
+    let mut scored: Vec<([Option<f64>; 3], f64, bool)> = Vec::new();
+    for resolution in resolutions {
+        let Some(market_txs) = by_market.get_mut(resolution.condition_id.as_str()) else {
+            continue;
+        };
+        market_txs.sort_by_key(|tx| (tx.block_number, tx.timestamp, tx.log_index));
+
+        let cutoff = resolution.resolution_block.saturating_sub(lead_blocks);
+        let in_effect = snapshots.partition_point(|snapshot| snapshot.block < cutoff);
+        let Some(index) = in_effect.checked_sub(1) else {
+            before_first_snapshot += 1;
+            continue;
+        };
+        let smart = smart_by_snapshot.entry(index).or_insert_with(|| {
+            snapshots[index]
+                .traders
+                .iter()
+                .filter(|t| config.smart_money.includes(t))
+                .map(|t| (t.trader_address.as_str(), t))
+                .collect()
+        });
+
+        let before: Vec<&Transaction> = market_txs.iter().copied().take_while(|tx| tx.block_number < cutoff).collect();
+
+        let mut books: HashMap<(&str, bool), SideBook> = HashMap::new();
+        for tx in before.iter().filter(|tx| smart.contains_key(tx.trader_address.as_str())) {
+            let yes = tx.side.eq_ignore_ascii_case("YES");
+            books.entry((tx.trader_address.as_str(), yes)).or_default().apply(tx);
+        }
+        if books.is_empty() {
+            continue;
+        }
+
+        let mut holdings: BTreeMap<&str, SmartHolding> = BTreeMap::new();
+        for ((address, yes), book) in &books {
+            smart_traders.insert(*address);
+            let holding = holdings.entry(*address).or_insert(SmartHolding {
+                yes_capital: 0.0,
+                no_capital: 0.0,
+                adjusted_accuracy: smart[*address].adjusted_accuracy,
+            });
+            if *yes {
+                holding.yes_capital += book.cost.max(0.0);
+            } else {
+                holding.no_capital += book.cost.max(0.0);
+            }
+        }
+        let holdings: Vec<SmartHolding> = holdings.into_values().collect();
+
+        let price = last_yes_price(&before).unwrap_or(0.5);
+        let models = probability_model::estimate_all(price, &holdings);
+        let predictions = ProbabilityModel::ALL.map(|model| models.get(model));
+        if predictions.iter().all(Option::is_none) {
+            continue;
+        }
+
+        scored.push((predictions, price, resolution.outcome.eq_ignore_ascii_case("YES")));
+    }
+
+    let mut scores: Vec<PredictorScore> = ProbabilityModel::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, model)| {
+            let pairs: Vec<(f64, bool)> = scored.iter().filter_map(|(p, _, yes)| p[i].map(|p| (p, *yes))).collect();
+            score(model.as_str(), &pairs, config.buckets)
+        })
+        .collect();
+    let market: Vec<(f64, bool)> = scored.iter().map(|(_, price, yes)| (*price, *yes)).collect();
+    scores.push(score("market", &market, config.buckets));
+
+    CalibrationReport {
+        config: config.clone(),
+        smart_traders: smart_traders.len(),
+        snapshots: snapshots.len(),
+        resolved_markets: resolutions.len(),
+        before_first_snapshot,
+        scored_markets: scored.len(),
+        scores,
+    }
+}
+
+// YES price implied by the last fill, a NO fill at p is a YES price of 1 - p
+pub fn last_yes_price(transactions: &[&Transaction]) -> Option<f64> {
+    transactions.iter().rev().find(|tx| tx.shares > 0.0).map(|tx| {
+        let price = tx.usdc_amount / tx.shares;
+        if tx.side.eq_ignore_ascii_case("YES") { price } else { 1.0 - price }
+    })
+}
+
+fn score(name: &'static str, pairs: &[(f64, bool)], buckets: usize) -> PredictorScore {
+    let buckets = buckets.max(1);
+    let outcome = |yes: bool| if yes { 1.0 } else { 0.0 };
+
+    let brier = if pairs.is_empty() {
+        0.0
+    } else {
+        pairs.iter().map(|(p, yes)| (p - outcome(*yes)).powi(2)).sum::<f64>() / pairs.len() as f64
+    };
+
+    let mut sums = vec![(0usize, 0.0, 0.0); buckets];
+    for (p, yes) in pairs {
+        let index = ((p * buckets as f64) as usize).min(buckets - 1);
+        let (count, predicted, observed) = &mut sums[index];
+        *count += 1;
+        *predicted += p;
+        *observed += outcome(*yes);
+    }
+
+    let width = 1.0 / buckets as f64;
+    let buckets = sums
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (count, _, _))| *count > 0)
+        .map(|(i, (count, predicted, observed))| CalibrationBucket {
+            low: i as f64 * width,
+            high: (i + 1) as f64 * width,
+            count,
+            mean_predicted: predicted / count as f64,
+            observed: observed / count as f64,
+        })
+        .collect();
+
+    PredictorScore { name, predictions: pairs.len(), brier, buckets }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_data::models::Collateral;
+
+    const WALLET: &str = "0xwallet";
+
+    fn trader(accuracy: f64) -> Trader {
+        Trader {
+            trader_address: WALLET.to_string(),
+            total_markets_entered: 10,
+            total_markets_resolved: 10,
+            total_wins: (accuracy * 10.0) as u32,
+            accuracy,
+            adjusted_accuracy: accuracy,
+            total_invested: 100.0,
+            total_returned: 100.0,
+            roi: 0.0,
+            first_activity_block: Some(1),
+            longest_win_streak: None,
+            longest_loss_streak: None,
+            max_drawdown: None,
+            return_volatility: None,
+            rewards: None,
+        }
+    }
+
+    fn buy_yes(market: &str, block: u64) -> Transaction {
+        Transaction {
+            block_number: block,
+            transaction_hash: format!("0x{}", block),
+            log_index: Some(0),
+            trader_address: WALLET.to_string(),
+            token_id: format!("{}-yes", market),
+            side: "YES".to_string(),
+            action: "BUY".to_string(),
+            shares: 100.0,
+            usdc_amount: 60.0,
+            market_id: market.to_string(),
+            timestamp: None,
+            collateral: Collateral::default(),
+        }
+    }
+
+    fn resolved(market: &str, block: u64) -> MarketResolution {
+        MarketResolution {
+            condition_id: market.to_string(),
+            outcome: "YES".to_string(),
+            resolution_block: block,
+            yes_token_id: format!("{}-yes", market),
+            no_token_id: format!("{}-no", market),
+        }
+    }
+
+    fn config() -> CalibrationConfig {
+        CalibrationConfig { smart_money: SmartMoney::default(), days_before: 0.0, buckets: 10 }
+    }
+
+    // the wallet only became smart money in the snapshot after the cutoff, so it isn't counted
+    #[test]
+    fn traders_are_judged_by_the_snapshot_in_effect_at_the_cutoff() {
+        let snapshots = [
+            TraderSnapshot { block: 100, traders: vec![trader(0.4)] },
+            TraderSnapshot { block: 300, traders: vec![trader(0.9)] },
+        ];
+        let transactions = [buy_yes("early", 150), buy_yes("late", 350)];
+        let resolutions = [resolved("early", 200), resolved("late", 400)];
+        let report = run_calibration(&config(), &snapshots, &transactions, &resolutions);
+        assert_eq!(report.snapshots, 2);
+        assert_eq!(report.scored_markets, 1);
+        assert_eq!(report.smart_traders, 1);
+        assert_eq!(report.scores.last().unwrap().predictions, 1);
+        assert!((report.scores.last().unwrap().buckets[0].mean_predicted - 0.6).abs() < 1e-9);
+    }
+
+    // without a snapshot from before the cutoff there's nothing to judge traders by that doesn't look ahead
+    #[test]
+    fn markets_closing_before_the_first_snapshot_are_left_out() {
+        let snapshots = [TraderSnapshot { block: 500, traders: vec![trader(0.9)] }];
+        let transactions = [buy_yes("early", 150)];
+        let resolutions = [resolved("early", 200)];
+        let report = run_calibration(&config(), &snapshots, &transactions, &resolutions);
+        assert_eq!(report.before_first_snapshot, 1);
+        assert_eq!(report.scored_markets, 0);
+    }
+}
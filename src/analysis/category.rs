@@ -0,0 +1,140 @@
+use crate::ingest;
+use crate::standard_data::models::{MarketResolution, MarketTag, Trader, TraderCategoryStats, Transaction};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// bucket for markets that came back from gamma without any tag
+pub const UNTAGGED: &str = "untagged";
+
+// one wallet's activity in a single category
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryExposure {
+    pub category: String,
+    pub markets: usize,
+    pub resolved: u32,
+    pub wins: u32,
+    // usdc put into resolved markets and what came back
+    pub invested: f64,
+    pub returned: f64,
+    // buys minus sells in markets that haven't resolved
+    pub open_cost: f64,
+}
+
+impl CategoryExposure {
+    pub fn accuracy(&self) -> f64 {
+        if self.resolved == 0 { 0.0 } else { self.wins as f64 / self.resolved as f64 }
+    }
+
+    pub fn roi(&self) -> f64 {
+        if self.invested > 0.0 { (self.returned - self.invested) / self.invested } else { 0.0 }
+    }
+}
+
+// tags of every tagged market
+pub fn tags_by_market(tags: &[MarketTag]) -> HashMap<&str, Vec<&str>> {
+    let mut by_market: HashMap<&str, Vec<&str>> = HashMap::new();
+    for tag in tags {
+        by_market.entry(tag.condition_id.as_str()).or_default().push(tag.tag.as_str());
+    }
+    by_market
+}
+
+// condition ids carrying the tag, matched case insensitively
+pub fn markets_in_category<'a>(tags: &'a [MarketTag], category: &str) -> HashSet<&'a str> {
+    tags.iter()
+        .filter(|t| t.tag.eq_ignore_ascii_case(category))
+        .map(|t| t.condition_id.as_str())
+        .collect()
+}
+
+// weight holders by their record in the market's categories instead of their overall one
+// with several tags the one a trader has the most resolved markets in wins, traders without any keep theirs
+// returns how many traders were switched
+pub fn apply_category_skill(traders: &mut [Trader], stats: &[TraderCategoryStats]) -> usize {
+    let mut best: HashMap<&str, &TraderCategoryStats> = HashMap::new();
+    for stat in stats.iter().filter(|s| s.total_markets_resolved > 0) {
+        best.entry(stat.trader_address.as_str())
+            .and_modify(|current| {
+                if stat.total_markets_resolved > current.total_markets_resolved {
+                    *current = stat;
+                }
+            })
+            .or_insert(stat);
+    }
+
+    let mut switched = 0;
+    for trader in traders.iter_mut() {
+        if let Some(stat) = best.get(trader.trader_address.as_str()) {
+            trader.adjusted_accuracy = stat.adjusted_accuracy;
+            switched += 1;
+        }
+    }
+    switched
+}
+
+// trader stats recomputed over only the given markets, all markets when None
+// best accuracy lower bound first, roi breaks ties
+pub fn leaderboard(
+    transactions: &[Transaction],
+    resolutions: &[MarketResolution],
+    markets: Option<&HashSet<&str>>,
+    min_resolved: u32,
+) -> Vec<Trader> {
+    let in_scope = |market_id: &str| markets.is_none_or(|m| m.contains(market_id));
+
+    let transactions: Vec<Transaction> = transactions.iter().filter(|tx| in_scope(&tx.market_id)).cloned().collect();
+    let resolutions: Vec<MarketResolution> = resolutions.iter().filter(|r| in_scope(&r.condition_id)).cloned().collect();
+
+    let mut traders: Vec<Trader> = ingest::compute_trader_stats(&transactions, &resolutions)
+        .into_iter()
+        .filter(|t| t.total_markets_resolved >= min_resolved)
+        .collect();
+    traders.sort_by(|a, b| b.adjusted_accuracy.total_cmp(&a.adjusted_accuracy).then(b.roi.total_cmp(&a.roi)));
+    traders
+}
+
+// one wallet's markets grouped by tag, a market with several tags counts in each of them
+pub fn portfolio(
+    trader_address: &str,
+    transactions: &[Transaction],
+    resolutions: &[MarketResolution],
+    tags: &[MarketTag],
+) -> Vec<CategoryExposure> {
+    let tagged = tags_by_market(tags);
+    let resolved: HashSet<&str> = resolutions.iter().map(|r| r.condition_id.as_str()).collect();
+
+    let mut by_category: BTreeMap<&str, Vec<Transaction>> = BTreeMap::new();
+    for tx in transactions.iter().filter(|tx| tx.trader_address.eq_ignore_ascii_case(trader_address)) {
+        let categories = tagged.get(tx.market_id.as_str()).map_or(&[UNTAGGED][..], |t| t.as_slice());
+        for category in categories {
+            by_category.entry(category).or_default().push(tx.clone());
+        }
+    }
+
+    let mut exposures: Vec<CategoryExposure> = by_category
+        .into_iter()
+        .map(|(category, txs)| {
+            // a single wallet comes back as at most one trader
+            let stats = ingest::compute_trader_stats(&txs, resolutions).into_iter().next();
+
+            let mut open: BTreeMap<&str, f64> = BTreeMap::new();
+            for tx in txs.iter().filter(|tx| !resolved.contains(tx.market_id.as_str())) {
+                let signed = if tx.removes_shares() { -tx.usdc_amount } else { tx.usdc_amount };
+                *open.entry(tx.market_id.as_str()).or_default() += signed;
+            }
+
+            CategoryExposure {
+                category: category.to_string(),
+                markets: txs.iter().map(|tx| tx.market_id.as_str()).collect::<HashSet<_>>().len(),
+                resolved: stats.as_ref().map_or(0, |s| s.total_markets_resolved),
+                wins: stats.as_ref().map_or(0, |s| s.total_wins),
+                invested: stats.as_ref().map_or(0.0, |s| s.total_invested),
+                returned: stats.as_ref().map_or(0.0, |s| s.total_returned),
+                open_cost: open.values().fold(0.0, |total, cost| total + cost.max(0.0)),
+            }
+        })
+        .collect();
+
+    exposures.sort_by(|a, b| (b.invested + b.open_cost).total_cmp(&(a.invested + a.open_cost)));
+    exposures
+}
@@ -0,0 +1,126 @@
+use crate::standard_data::models::Comment;
+use serde::Serialize;
+use std::collections::HashSet;
+
+// latest comments read per event, a busy event has thousands and the recent ones say what the crowd thinks now
+pub const CHATTER_COMMENT_LIMIT: usize = 500;
+// crowd yes share and yes price further apart than this are called a disagreement
+pub const CHATTER_GAP: f64 = 0.15;
+
+// plain word lists, crude but the same every run and easy to read off a comment
+const POSITIVE_WORDS: &[&str] = &[
+    "good", "great", "love", "easy", "free", "win", "winning", "confident", "lock", "locked", "bullish", "obviously",
+    "undervalued", "cheap", "profit", "thanks", "nice", "solid", "strong", "moon",
+];
+const NEGATIVE_WORDS: &[&str] = &[
+    "bad", "terrible", "hate", "scam", "rigged", "lose", "losing", "lost", "dump", "bearish", "overvalued", "crazy",
+    "stupid", "worst", "fraud", "manipulated", "manipulation", "rekt", "weak", "doubt",
+];
+// phrases that back one outcome, matched on whole words
+const YES_PHRASES: &[&str] = &[
+    "buy yes", "bought yes", "buying yes", "long yes", "yes is free", "easy yes", "yes easily", "going yes",
+    "will happen", "definitely yes", "yes for sure", "all in yes",
+];
+const NO_PHRASES: &[&str] = &[
+    "buy no", "bought no", "buying no", "long no", "no is free", "easy no", "no way", "not happening", "won't happen",
+    "will not happen", "never happening", "definitely no", "all in no",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lean {
+    Yes,
+    No,
+}
+
+// how much an event is talked about and in what mood, next to what the money does
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Chatter {
+    // comments read, at most CHATTER_COMMENT_LIMIT of the latest
+    pub comments: usize,
+    pub commenters: usize,
+    pub last_24h: usize,
+    pub last_7d: usize,
+    // comments a day from the oldest one read until now
+    pub per_day: Option<f64>,
+    pub positive: usize,
+    pub negative: usize,
+    // positive minus negative over the comments with any tone, -1 to 1
+    pub tone: Option<f64>,
+    pub yes_leaning: usize,
+    pub no_leaning: usize,
+    // share of the comments backing an outcome that back YES
+    pub crowd_yes: Option<f64>,
+    pub most_liked: Option<Comment>,
+}
+
+// a comment's words, lowercased, apostrophes kept so "won't" stays one word
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace('\u{2019}', "'")
+        .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// positive minus negative words, only the sign is used
+pub fn tone_of(text: &str) -> i32 {
+    words(text).iter().map(|word| {
+        let word = word.as_str();
+        match (POSITIVE_WORDS.contains(&word), NEGATIVE_WORDS.contains(&word)) {
+            (true, _) => 1,
+            (_, true) => -1,
+            _ => 0,
+        }
+    }).sum()
+}
+
+// the outcome a comment backs, None when it backs neither or both
+pub fn lean_of(text: &str) -> Option<Lean> {
+    let padded = format!(" {} ", words(text).join(" "));
+    let backs = |phrases: &[&str]| phrases.iter().any(|phrase| padded.contains(&format!(" {} ", phrase)));
+    match (backs(YES_PHRASES), backs(NO_PHRASES)) {
+        (true, false) => Some(Lean::Yes),
+        (false, true) => Some(Lean::No),
+        _ => None,
+    }
+}
+
+pub fn chatter(comments: &[Comment], now: i64) -> Chatter {
+    let mut chatter = Chatter { comments: comments.len(), ..Chatter::default() };
+    if comments.is_empty() {
+        return chatter;
+    }
+
+    chatter.commenters = comments.iter()
+        .map(|c| c.author_address.as_str())
+        .filter(|address| !address.is_empty())
+        .collect::<HashSet<_>>()
+        .len();
+    chatter.last_24h = comments.iter().filter(|c| now - c.created_at <= 24 * 60 * 60).count();
+    chatter.last_7d = comments.iter().filter(|c| now - c.created_at <= 7 * 24 * 60 * 60).count();
+    // less than a day of history would blow the rate up
+    let oldest = comments.iter().map(|c| c.created_at).min().unwrap_or(now);
+    let days = (now - oldest) as f64 / (24.0 * 60.0 * 60.0);
+    chatter.per_day = (days >= 1.0).then(|| comments.len() as f64 / days);
+
+    for comment in comments {
+        match tone_of(&comment.body) {
+            tone if tone > 0 => chatter.positive += 1,
+            tone if tone < 0 => chatter.negative += 1,
+            _ => {}
+        }
+        match lean_of(&comment.body) {
+            Some(Lean::Yes) => chatter.yes_leaning += 1,
+            Some(Lean::No) => chatter.no_leaning += 1,
+            None => {}
+        }
+    }
+    let toned = chatter.positive + chatter.negative;
+    chatter.tone = (toned > 0).then(|| (chatter.positive as f64 - chatter.negative as f64) / toned as f64);
+    let leaning = chatter.yes_leaning + chatter.no_leaning;
+    chatter.crowd_yes = (leaning > 0).then(|| chatter.yes_leaning as f64 / leaning as f64);
+    chatter.most_liked = comments.iter().filter(|c| c.likes > 0).max_by_key(|c| c.likes).cloned();
+    chatter
+}
@@ -0,0 +1,54 @@
+use crate::analysis::implied_return;
+use crate::analysis::smart_money::{SmartMoney, WalletGroups};
+use crate::standard_data::models::{Market, Position, Trader};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// one row of the closing soon report
+#[derive(Debug, Clone, Serialize)]
+pub struct ClosingMarket {
+    pub slug: String,
+    pub condition_id: String,
+    pub question: String,
+    pub end_date: DateTime<Utc>,
+    pub yes_price: f64,
+    // smart money YES probability, None when no smart trader holds it or positions weren't checked
+    pub smart_probability: Option<f64>,
+    pub smart_traders: usize,
+}
+
+impl ClosingMarket {
+    pub fn from_market(market: &Market) -> Option<Self> {
+        Some(Self {
+            slug: market.slug.clone(),
+            condition_id: market.condition_id.clone(),
+            question: market.question.clone(),
+            end_date: market.end_date?,
+            yes_price: market.last_trade_price,
+            smart_probability: None,
+            smart_traders: 0,
+        })
+    }
+
+    // smart money probability minus the market price, positive means smart money thinks YES is cheap
+    pub fn divergence(&self) -> Option<f64> {
+        self.smart_probability.map(|p| p - self.yes_price)
+    }
+
+    pub fn add_smart_money(&mut self, positions: &[Position], traders: &[Trader], smart_money: &SmartMoney, groups: &WalletGroups) {
+        if let Some((lean, holders)) = implied_return::smart_lean(self.yes_price, positions, traders, smart_money, groups) {
+            self.smart_probability = Some(lean);
+            self.smart_traders = holders;
+        }
+    }
+}
+
+// biggest disagreement first, markets without a smart money read go last by end date
+pub fn rank_by_divergence(markets: &mut [ClosingMarket]) {
+    markets.sort_by(|a, b| match (a.divergence(), b.divergence()) {
+        (Some(x), Some(y)) => y.abs().total_cmp(&x.abs()),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.end_date.cmp(&b.end_date),
+    });
+}
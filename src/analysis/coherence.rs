@@ -0,0 +1,87 @@
+use crate::standard_data::models::{Market, MarketGroup};
+use serde::Serialize;
+use schemars::JsonSchema;
+
+// mispricing vs the normalized price before a market is called rich or cheap
+pub const RICH_CHEAP_THRESHOLD: f64 = 0.02;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct MarketFairValue {
+    pub question: String,
+    pub yes_price: f64,
+    // price scaled so the group sums to one
+    pub fair_price: f64,
+}
+
+impl MarketFairValue {
+    // positive means the market trades above its share of the group
+    pub fn mispricing(&self) -> f64 {
+        self.yes_price - self.fair_price
+    }
+}
+
+// price checks for a group where exactly one market should resolve YES
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GroupCoherence {
+    pub yes_price_sum: f64,
+    // cost of buying one YES share in every market, pays exactly $1
+    pub yes_ask_sum: f64,
+    // proceeds from selling one YES share in every market, costs exactly $1 at resolution
+    pub yes_bid_sum: f64,
+    // cost of one NO share in every market, pays n - 1
+    pub no_ask_sum: f64,
+    pub markets: Vec<MarketFairValue>,
+}
+
+impl GroupCoherence {
+    // guaranteed profit per set from buying every YES, if any
+    pub fn buy_all_yes_edge(&self) -> Option<f64> {
+        let edge = 1.0 - self.yes_ask_sum;
+        (edge > 0.0).then_some(edge)
+    }
+
+    // guaranteed profit per set from buying every NO, if any
+    pub fn buy_all_no_edge(&self) -> Option<f64> {
+        let payout = self.markets.len().saturating_sub(1) as f64;
+        let edge = payout - self.no_ask_sum;
+        (edge > 0.0).then_some(edge)
+    }
+}
+
+// None for single market groups, there is nothing to be coherent with
+pub fn check_group(group: &MarketGroup) -> Option<GroupCoherence> {
+    // settled markets no longer trade
+    let markets: Vec<&Market> = group.markets.iter().filter(|m| !m.closed).collect();
+    if markets.len() < 2 {
+        return None;
+    }
+
+    let prices: Vec<f64> = markets.iter().map(|m| yes_price(m)).collect();
+    let yes_price_sum: f64 = prices.iter().sum();
+
+    let fair_values = markets
+        .iter()
+        .zip(&prices)
+        .map(|(market, price)| MarketFairValue {
+            question: market.question.clone(),
+            yes_price: *price,
+            fair_price: if yes_price_sum > 0.0 { price / yes_price_sum } else { 0.0 },
+        })
+        .collect();
+
+    Some(GroupCoherence {
+        yes_price_sum,
+        yes_ask_sum: markets.iter().map(|m| m.ask_price).sum(),
+        yes_bid_sum: markets.iter().map(|m| m.bid_price).sum(),
+        // no is bought at one minus the yes bid
+        no_ask_sum: markets.iter().map(|m| 1.0 - m.bid_price).sum(),
+        markets: fair_values,
+    })
+}
+
+fn yes_price(market: &Market) -> f64 {
+    market.outcome_prices
+        .first()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(market.last_trade_price)
+}
@@ -0,0 +1,68 @@
+use crate::standard_data::models::{Collateral, Position, Transaction};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// the [collateral] section of config.toml, usd per unit of each token
+// both track the dollar, a depeg is when one is worth changing
+// stored trader stats keep the rates of the rebuild that made them, a change needs `stats rebuild --full`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct CollateralRates {
+    pub usdc_e: f64,
+    pub usdc: f64,
+}
+
+impl Default for CollateralRates {
+    fn default() -> Self {
+        Self { usdc_e: 1.0, usdc: 1.0 }
+    }
+}
+
+impl CollateralRates {
+    pub fn usd(&self, collateral: Collateral) -> f64 {
+        match collateral {
+            Collateral::UsdcE => self.usdc_e,
+            Collateral::Usdc => self.usdc,
+        }
+    }
+}
+
+// trades and positions settled in each collateral
+#[derive(Debug, Clone, Copy, Default, Serialize, JsonSchema)]
+pub struct CollateralMix {
+    pub usdc_e: usize,
+    pub usdc: usize,
+}
+
+impl CollateralMix {
+    pub fn of(transactions: &[Transaction], positions: &[Position]) -> Self {
+        let mut mix = Self::default();
+        let collaterals = transactions.iter().map(|tx| tx.collateral).chain(positions.iter().map(|p| p.collateral));
+        for collateral in collaterals {
+            match collateral {
+                Collateral::UsdcE => mix.usdc_e += 1,
+                Collateral::Usdc => mix.usdc += 1,
+            }
+        }
+        mix
+    }
+
+    // totals over these add up two tokens, they only mean something at the configured rates
+    pub fn is_mixed(&self) -> bool {
+        self.usdc_e > 0 && self.usdc > 0
+    }
+}
+
+// usdc amounts converted to usd, the collateral stays on each row to say what it was paid in
+pub fn transactions_to_usd(transactions: &mut [Transaction], rates: CollateralRates) {
+    for tx in transactions {
+        tx.usdc_amount *= rates.usd(tx.collateral);
+    }
+}
+
+// entry prices converted to usd, shares are the same either way
+pub fn positions_to_usd(positions: &mut [Position], rates: CollateralRates) {
+    for position in positions {
+        position.avg_entry_price *= rates.usd(position.collateral);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::analysis::implied_return;
+use crate::analysis::smart_money::{SmartMoney, WalletGroups};
+use crate::standard_data::models::{Market, Position, Trader};
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+// wallets counted as whales when measuring concentration
+pub const WHALE_TOP_N: usize = 10;
+// position size that makes a single wallet a whale
+pub const WHALE_MIN_CAPITAL: f64 = 10_000.0;
+
+// one row of the compare table
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MarketSummary {
+    pub slug: String,
+    pub question: String,
+    pub yes_price: f64,
+    pub spread: f64,
+    pub liquidity: f64,
+    // smart money YES probability under the configured model
+    pub smart_lean: Option<f64>,
+    // share of position capital held by the top WHALE_TOP_N wallets
+    pub whale_share: f64,
+    // wallets holding at least WHALE_MIN_CAPITAL
+    pub whale_count: usize,
+    pub volume_24h: f64,
+    // last day's volume against the weekly daily average, above 1 means picking up
+    pub volume_trend: Option<f64>,
+}
+
+pub fn summarize_market(
+    slug: &str,
+    market: &Market,
+    positions: &[Position],
+    traders: &[Trader],
+    smart_money: &SmartMoney,
+    groups: &WalletGroups,
+) -> MarketSummary {
+    let mut capital: HashMap<&str, f64> = HashMap::new();
+    for position in positions {
+        *capital.entry(position.trader_address.as_str()).or_default() += position.shares_held * position.avg_entry_price;
+    }
+
+    let mut holdings: Vec<f64> = capital.into_values().collect();
+    holdings.sort_by(|a, b| b.total_cmp(a));
+    let total: f64 = holdings.iter().sum();
+    let whales: f64 = holdings.iter().take(WHALE_TOP_N).sum();
+
+    let daily_average = market.volume_1w / 7.0;
+
+    MarketSummary {
+        slug: slug.to_string(),
+        question: market.question.clone(),
+        yes_price: market.last_trade_price,
+        spread: market.ask_price - market.bid_price,
+        liquidity: market.liquidity,
+        smart_lean: implied_return::smart_lean(market.last_trade_price, positions, traders, smart_money, groups).map(|(lean, _)| lean),
+        whale_share: if total > 0.0 { whales / total } else { 0.0 },
+        whale_count: holdings.iter().filter(|capital| **capital >= WHALE_MIN_CAPITAL).count(),
+        volume_24h: market.volume_24h,
+        volume_trend: (daily_average > 0.0).then(|| market.volume_24h / daily_average),
+    }
+}
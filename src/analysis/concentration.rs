@@ -0,0 +1,79 @@
+use crate::standard_data::models::Position;
+use std::collections::HashMap;
+use serde::Serialize;
+use schemars::JsonSchema;
+
+// holders counted in the top share
+pub const CONCENTRATION_TOP_N: usize = 5;
+// herfindahl index above this reads as one or two wallets carrying the side
+pub const CONCENTRATED_HHI: f64 = 0.25;
+
+// how evenly one side's capital is spread over its holders
+#[derive(Debug, Clone, Copy, Default, Serialize, JsonSchema)]
+pub struct SideConcentration {
+    pub holders: usize,
+    pub capital: f64,
+    // sum of squared capital shares, 1 / holders when even up to 1 for a single wallet
+    pub hhi: f64,
+    // share of the side's capital held by the top CONCENTRATION_TOP_N wallets
+    pub top_share: f64,
+    // 0 when every holder has the same stake, towards 1 when one wallet has it all
+    pub gini: f64,
+}
+
+impl SideConcentration {
+    pub fn concentrated(&self) -> bool {
+        self.hhi > CONCENTRATED_HHI
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, JsonSchema)]
+pub struct Concentration {
+    pub yes: SideConcentration,
+    pub no: SideConcentration,
+}
+
+// capital per wallet on each side, a wallet with several positions on a side counts once
+pub fn concentration(positions: &[Position]) -> Concentration {
+    let mut yes: HashMap<&str, f64> = HashMap::new();
+    let mut no: HashMap<&str, f64> = HashMap::new();
+    for position in positions {
+        let side = if position.side.eq_ignore_ascii_case("YES") { &mut yes } else { &mut no };
+        *side.entry(position.trader_address.as_str()).or_default() += position.shares_held * position.avg_entry_price;
+    }
+
+    Concentration {
+        yes: side_concentration(yes.into_values().collect()),
+        no: side_concentration(no.into_values().collect()),
+    }
+}
+
+fn side_concentration(mut holdings: Vec<f64>) -> SideConcentration {
+    holdings.retain(|capital| *capital > 0.0);
+    let capital: f64 = holdings.iter().sum();
+    if capital <= 0.0 {
+        return SideConcentration::default();
+    }
+
+    // largest first for the top share, the gini sum below wants them ascending
+    holdings.sort_by(|a, b| b.total_cmp(a));
+    let top: f64 = holdings.iter().take(CONCENTRATION_TOP_N).sum();
+    let hhi = holdings.iter().map(|h| (h / capital).powi(2)).sum();
+
+    let n = holdings.len() as f64;
+    let ranked: f64 = holdings
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, h)| (i + 1) as f64 * h)
+        .sum();
+    let gini = (2.0 * ranked) / (n * capital) - (n + 1.0) / n;
+
+    SideConcentration {
+        holders: holdings.len(),
+        capital,
+        hhi,
+        top_share: top / capital,
+        gini: gini.max(0.0),
+    }
+}
@@ -0,0 +1,204 @@
+use crate::analysis::expiry;
+use crate::standard_data::models::Market;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// daily closes the realized volatility is measured over
+pub const CRYPTO_VOL_DAYS: u32 = 30;
+// crypto trades every day of the year
+const DAYS_PER_YEAR: f64 = 365.0;
+
+// how questions name an asset, and its ticker on the spot feed
+const ASSETS: &[(&[&str], &str)] = &[
+    (&["bitcoin", "btc"], "BTC"),
+    (&["ethereum", "eth", "ether"], "ETH"),
+    (&["solana", "sol"], "SOL"),
+    (&["xrp", "ripple"], "XRP"),
+    (&["dogecoin", "doge"], "DOGE"),
+    (&["cardano", "ada"], "ADA"),
+    (&["litecoin", "ltc"], "LTC"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrikeKind {
+    // settles on the price at the end date
+    Above,
+    Below,
+    // any touch before the end date counts
+    Reach,
+    Dip,
+}
+
+impl StrikeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StrikeKind::Above => "above",
+            StrikeKind::Below => "below",
+            StrikeKind::Reach => "reach",
+            StrikeKind::Dip => "dip to",
+        }
+    }
+}
+
+// the phrase before the strike, earliest one in the question wins so "dip below" isn't read as "below"
+const KIND_PHRASES: &[(&str, StrikeKind)] = &[
+    ("above", StrikeKind::Above),
+    ("over", StrikeKind::Above),
+    ("higher than", StrikeKind::Above),
+    ("greater than", StrikeKind::Above),
+    ("below", StrikeKind::Below),
+    ("under", StrikeKind::Below),
+    ("lower than", StrikeKind::Below),
+    ("less than", StrikeKind::Below),
+    ("reach", StrikeKind::Reach),
+    ("hit", StrikeKind::Reach),
+    ("touch", StrikeKind::Reach),
+    ("dip to", StrikeKind::Dip),
+    ("dip below", StrikeKind::Dip),
+    ("fall to", StrikeKind::Dip),
+    ("drop to", StrikeKind::Dip),
+];
+
+// what a crypto price market asks, e.g. "Will Bitcoin be above $120,000 on October 31?"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CryptoQuestion {
+    pub asset: &'static str,
+    pub kind: StrikeKind,
+    pub strike: f64,
+}
+
+// "$120,000", "120k" or "1.5m", the question's trailing punctuation already gone
+fn amount(token: &str) -> Option<f64> {
+    let token = token.trim_start_matches('$').replace(',', "");
+    let (number, scale) = match token.chars().last()? {
+        'k' => (&token[..token.len() - 1], 1_000.0),
+        'm' => (&token[..token.len() - 1], 1_000_000.0),
+        _ => (token.as_str(), 1.0),
+    };
+    let value: f64 = number.parse().ok()?;
+    (value > 0.0).then_some(value * scale)
+}
+
+// None for anything that isn't a price level of a known asset, up or down and range markets included
+pub fn parse_question(question: &str) -> Option<CryptoQuestion> {
+    let lower = question.to_lowercase();
+    let tokens: Vec<&str> = lower
+        .split_whitespace()
+        .map(|token| token.trim_matches(|c: char| matches!(c, '?' | '.' | ',' | '!' | '(' | ')')))
+        .collect();
+
+    let asset = tokens.iter().find_map(|token| {
+        ASSETS.iter().find(|(names, _)| names.contains(token)).map(|(_, ticker)| *ticker)
+    })?;
+
+    let (end, kind) = (0..tokens.len()).find_map(|i| {
+        KIND_PHRASES.iter().find_map(|(phrase, kind)| {
+            let words: Vec<&str> = phrase.split(' ').collect();
+            tokens[i..].starts_with(&words).then_some((i + words.len(), *kind))
+        })
+    })?;
+    let strike = tokens[end..].iter().find_map(|token| amount(token))?;
+
+    Some(CryptoQuestion { asset, kind, strike })
+}
+
+// annualized standard deviation of daily log returns, None under three closes
+pub fn realized_volatility(closes: &[f64]) -> Option<f64> {
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0 && pair[1] > 0.0)
+        .map(|pair| (pair[1] / pair[0]).ln())
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some((variance * DAYS_PER_YEAR).sqrt())
+}
+
+// abramowitz and stegun 7.1.26, good to about 1e-7
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    match x >= 0.0 {
+        true => 0.5 * (1.0 + erf),
+        false => 0.5 * (1.0 - erf),
+    }
+}
+
+// chance YES pays with the price lognormal and driftless, like a binary option with no rates
+// end date settles on the closing side of the strike, touch markets use the reflection principle
+pub fn fair_probability(kind: StrikeKind, spot: f64, strike: f64, volatility: f64, years: f64) -> f64 {
+    let spread = volatility * years.max(0.0).sqrt();
+    let above = spot > strike;
+    if spread <= 0.0 {
+        return match kind {
+            StrikeKind::Above | StrikeKind::Reach => if above { 1.0 } else { 0.0 },
+            StrikeKind::Below | StrikeKind::Dip => if above { 0.0 } else { 1.0 },
+        };
+    }
+
+    let d2 = ((spot / strike).ln() - spread * spread / 2.0) / spread;
+    match kind {
+        StrikeKind::Above => normal_cdf(d2),
+        StrikeKind::Below => 1.0 - normal_cdf(d2),
+        StrikeKind::Reach if spot >= strike => 1.0,
+        StrikeKind::Reach => (2.0 * (1.0 - normal_cdf((strike / spot).ln() / spread))).min(1.0),
+        StrikeKind::Dip if spot <= strike => 1.0,
+        StrikeKind::Dip => (2.0 * (1.0 - normal_cdf((spot / strike).ln() / spread))).min(1.0),
+    }
+}
+
+// one crypto market held against its option-like fair value
+#[derive(Debug, Clone, Serialize)]
+pub struct CryptoFairValue {
+    pub question: String,
+    pub asset: &'static str,
+    pub kind: StrikeKind,
+    pub strike: f64,
+    pub spot: f64,
+    // spot over strike minus one, negative while spot is under the strike
+    pub distance: f64,
+    pub days_left: f64,
+    pub volatility: f64,
+    // log distance to the strike in standard deviations of the move expected by the end date
+    pub sigmas: Option<f64>,
+    pub fair_yes: f64,
+    pub market_yes: f64,
+    // positive when the market's YES is cheaper than the fair value
+    pub edge: f64,
+    // change in fair value over one more day with spot unchanged, the time decay
+    pub decay_per_day: f64,
+}
+
+// None without an end date or a YES price
+pub fn fair_value(market: &Market, question: &CryptoQuestion, spot: f64, volatility: f64, now: DateTime<Utc>) -> Option<CryptoFairValue> {
+    let market_yes: f64 = market.outcome_prices.first()?.parse().ok()?;
+    let left = expiry::time_to_expiry(market, now)?;
+    let days_left = (left.num_seconds() as f64 / 86_400.0).max(0.0);
+
+    let years = days_left / DAYS_PER_YEAR;
+    let fair_yes = fair_probability(question.kind, spot, question.strike, volatility, years);
+    let tomorrow = fair_probability(question.kind, spot, question.strike, volatility, (days_left - 1.0).max(0.0) / DAYS_PER_YEAR);
+    let spread = volatility * years.sqrt();
+
+    Some(CryptoFairValue {
+        question: market.question.clone(),
+        asset: question.asset,
+        kind: question.kind,
+        strike: question.strike,
+        spot,
+        distance: spot / question.strike - 1.0,
+        days_left,
+        volatility,
+        sigmas: (spread > 0.0).then(|| (spot / question.strike).ln() / spread),
+        fair_yes,
+        market_yes,
+        edge: fair_yes - market_yes,
+        decay_per_day: tomorrow - fair_yes,
+    })
+}
@@ -0,0 +1,26 @@
+use crate::standard_data::models::Market;
+use chrono::{DateTime, TimeDelta, Utc};
+
+// markets closer than this to resolving get flagged
+pub const RESOLVES_SOON_HOURS: i64 = 48;
+
+// negative once the end date has passed but the market hasn't settled yet
+pub fn time_to_expiry(market: &Market, now: DateTime<Utc>) -> Option<TimeDelta> {
+    market.end_date.map(|end| end - now)
+}
+
+pub fn resolves_soon(market: &Market, now: DateTime<Utc>) -> bool {
+    time_to_expiry(market, now)
+        .is_some_and(|left| left <= TimeDelta::hours(RESOLVES_SOON_HOURS))
+}
+
+// compound a hold to expiry return over a year
+// None when the horizon is too short for the number to mean anything
+pub fn annualize(simple_return: f64, time_left: TimeDelta) -> Option<f64> {
+    let days = time_left.num_seconds() as f64 / 86_400.0;
+    if days < 1.0 || simple_return <= -1.0 {
+        return None;
+    }
+
+    Some((1.0 + simple_return).powf(365.0 / days) - 1.0)
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+// trading and settlement costs, read from the [fees] section of config.toml
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeeModel {
+    // charged on resting orders that get filled, basis points of notional
+    pub maker_bps: f64,
+    // charged on orders that take liquidity from the book
+    pub taker_bps: f64,
+    // usdc of gas to redeem one side of a resolved market on chain
+    pub redemption_gas: f64,
+}
+
+impl Default for FeeModel {
+    // polymarket charges no trading fee on most markets, gas on polygon is a few cents
+    fn default() -> Self {
+        Self {
+            maker_bps: 0.0,
+            taker_bps: 0.0,
+            redemption_gas: 0.05,
+        }
+    }
+}
+
+impl FeeModel {
+    pub fn maker_fee(&self, notional: f64) -> f64 {
+        notional * self.maker_bps / 10_000.0
+    }
+
+    pub fn taker_fee(&self, notional: f64) -> f64 {
+        notional * self.taker_bps / 10_000.0
+    }
+
+    // what one share really costs when bought by taking the ask
+    pub fn buy_price(&self, price: f64) -> f64 {
+        price + self.taker_fee(price)
+    }
+
+    // what one share really brings in when sold into the bid
+    pub fn sell_price(&self, price: f64) -> f64 {
+        price - self.taker_fee(price)
+    }
+
+    // share of bankroll to stake on a side paying 1 at this probability, after the taker fee
+    // zero when there is no edge left
+    pub fn kelly_fraction(&self, probability: f64, price: f64) -> f64 {
+        let cost = self.buy_price(price);
+        if cost <= 0.0 || cost >= 1.0 {
+            return 0.0;
+        }
+        ((probability - cost) / (1.0 - cost)).max(0.0)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::standard_data::models::Position;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// holders traced when no --top is given
+pub const FUNDING_TOP_N: usize = 20;
+// usdc.e minted by the pos bridge comes from the zero address
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+// funded this close to the first trade reads as a wallet made for the bet
+pub const FRESH_FUNDING_BLOCKS: u64 = BLOCKS_PER_DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundingKind {
+    // minted on polygon by the bridge, came straight from another chain
+    Bridge,
+    // a sender labeled as an exchange in the address book
+    Exchange,
+    Wallet,
+}
+
+impl FundingKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FundingKind::Bridge => "bridge",
+            FundingKind::Exchange => "exchange",
+            FundingKind::Wallet => "wallet",
+        }
+    }
+}
+
+// where a wallet's usdc came from before it started trading
+#[derive(Debug, Clone)]
+pub struct FundingSource {
+    pub funder: String,
+    pub amount: f64,
+    pub funded_block: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct HolderFunding {
+    pub address: String,
+    pub capital: f64,
+    pub first_activity_block: Option<u64>,
+    // None when the wallet wasn't traced or nothing came in over the lookback
+    pub source: Option<FundingSource>,
+    pub kind: Option<FundingKind>,
+}
+
+impl HolderFunding {
+    // blocks from the deposit to the first trade
+    pub fn lead_blocks(&self) -> Option<u64> {
+        Some(self.first_activity_block?.saturating_sub(self.source.as_ref()?.funded_block))
+    }
+
+    // bridged or withdrawn from an exchange right before trading
+    pub fn fresh(&self) -> bool {
+        matches!(self.kind, Some(FundingKind::Bridge | FundingKind::Exchange))
+            && self.lead_blocks().is_some_and(|blocks| blocks <= FRESH_FUNDING_BLOCKS)
+    }
+}
+
+// top holders paid from the same wallet
+#[derive(Debug, Clone)]
+pub struct FundingCluster {
+    pub funder: String,
+    pub holders: Vec<String>,
+    pub capital: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct FundingReport {
+    // largest position first
+    pub holders: Vec<HolderFunding>,
+    // most capital first
+    pub clusters: Vec<FundingCluster>,
+}
+
+// capital per wallet over both sides, largest first
+pub fn top_holders(positions: &[Position], limit: usize) -> Vec<(String, f64)> {
+    let mut capital: HashMap<&str, f64> = HashMap::new();
+    for position in positions {
+        *capital.entry(position.trader_address.as_str()).or_default() += position.shares_held * position.avg_entry_price;
+    }
+
+    let mut holders: Vec<(String, f64)> = capital
+        .into_iter()
+        .filter(|(_, capital)| *capital > 0.0)
+        .map(|(address, capital)| (address.to_string(), capital))
+        .collect();
+    holders.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    holders.truncate(limit);
+    holders
+}
+
+// classify each holder's funding and group holders sharing a funding wallet
+// bridges and exchanges fund everyone, so only plain wallets make a cluster
+pub fn funding_report(
+    holders: &[(String, f64)],
+    first_blocks: &HashMap<String, u64>,
+    sources: &HashMap<String, FundingSource>,
+    exchanges: &HashSet<String>,
+) -> FundingReport {
+    let holders: Vec<HolderFunding> = holders
+        .iter()
+        .map(|(address, capital)| {
+            let source = sources.get(address).cloned();
+            let kind = source.as_ref().map(|s| {
+                let funder = s.funder.to_lowercase();
+                if funder == ZERO_ADDRESS {
+                    FundingKind::Bridge
+                } else if exchanges.contains(&funder) {
+                    FundingKind::Exchange
+                } else {
+                    FundingKind::Wallet
+                }
+            });
+            HolderFunding {
+                address: address.clone(),
+                capital: *capital,
+                first_activity_block: first_blocks.get(address).copied(),
+                source,
+                kind,
+            }
+        })
+        .collect();
+
+    // btree keeps clusters of equal capital in funder order
+    let mut funded: BTreeMap<String, FundingCluster> = BTreeMap::new();
+    for holder in holders.iter().filter(|h| h.kind == Some(FundingKind::Wallet)) {
+        let Some(source) = &holder.source else {
+            continue;
+        };
+        let funder = source.funder.to_lowercase();
+        let cluster = funded.entry(funder.clone()).or_insert(FundingCluster { funder, holders: Vec::new(), capital: 0.0 });
+        cluster.holders.push(holder.address.clone());
+        cluster.capital += holder.capital;
+    }
+
+    let mut clusters: Vec<FundingCluster> = funded.into_values().filter(|c| c.holders.len() > 1).collect();
+    clusters.sort_by(|a, b| b.capital.total_cmp(&a.capital));
+
+    FundingReport { holders, clusters }
+}
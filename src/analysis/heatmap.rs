@@ -0,0 +1,109 @@
+use crate::standard_data::models::Transaction;
+use std::collections::HashSet;
+
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+// usdc traded per (weekday, hour) bucket on the caller's clock, one grid per side
+#[derive(Debug, Clone, Default)]
+pub struct TradeHeatmap {
+    pub yes: [[f64; 24]; 7],
+    pub no: [[f64; 24]; 7],
+    pub trades: usize,
+    // trades whose time was interpolated from the block number
+    pub estimated: usize,
+    // trades we couldn't place at all
+    pub untimed: usize,
+}
+
+impl TradeHeatmap {
+    // largest bucket across both sides so the grids share a scale
+    pub fn peak(&self) -> f64 {
+        self.yes
+            .iter()
+            .chain(self.no.iter())
+            .flatten()
+            .copied()
+            .fold(0.0, f64::max)
+    }
+}
+
+// only count trades from `traders` when given, e.g. the smart money set
+// wall_clock turns unix seconds into the same seconds shifted to the display zone, identity for utc
+pub fn build_heatmap(
+    transactions: &[Transaction],
+    traders: Option<&HashSet<String>>,
+    wall_clock: impl Fn(i64) -> i64,
+) -> TradeHeatmap {
+    let clock = BlockClock::fit(transactions);
+    let mut heatmap = TradeHeatmap::default();
+
+    for tx in transactions.iter().filter(|tx| tx.is_fill()) {
+        if let Some(traders) = traders
+            && !traders.contains(&tx.trader_address)
+        {
+            continue;
+        }
+
+        let timestamp = match (tx.timestamp, &clock) {
+            (Some(timestamp), _) => timestamp,
+            (None, Some(clock)) => {
+                heatmap.estimated += 1;
+                clock.estimate(tx.block_number)
+            }
+            (None, None) => {
+                heatmap.untimed += 1;
+                continue;
+            }
+        };
+
+        let (weekday, hour) = bucket(wall_clock(timestamp));
+        let grid = if tx.side == "YES" { &mut heatmap.yes } else { &mut heatmap.no };
+        grid[weekday][hour] += tx.usdc_amount;
+        heatmap.trades += 1;
+    }
+
+    heatmap
+}
+
+// (weekday with monday = 0, hour) of a timestamp read as utc
+fn bucket(timestamp: i64) -> (usize, usize) {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+    // 1970-01-01 was a thursday
+    let weekday = (days + 3).rem_euclid(7) as usize;
+    (weekday, (seconds / 3_600) as usize)
+}
+
+// straight line between the earliest and latest timed blocks
+struct BlockClock {
+    block: f64,
+    timestamp: f64,
+    seconds_per_block: f64,
+}
+
+impl BlockClock {
+    fn fit(transactions: &[Transaction]) -> Option<Self> {
+        let mut timed = transactions
+            .iter()
+            .filter_map(|tx| tx.timestamp.map(|ts| (tx.block_number, ts)));
+
+        let first = timed.next()?;
+        let (low, high) = timed.fold((first, first), |(low, high), point| {
+            (if point.0 < low.0 { point } else { low }, if point.0 > high.0 { point } else { high })
+        });
+
+        if high.0 == low.0 {
+            return None;
+        }
+
+        Some(Self {
+            block: low.0 as f64,
+            timestamp: low.1 as f64,
+            seconds_per_block: (high.1 - low.1) as f64 / (high.0 - low.0) as f64,
+        })
+    }
+
+    fn estimate(&self, block: u64) -> i64 {
+        (self.timestamp + (block as f64 - self.block) * self.seconds_per_block).round() as i64
+    }
+}
@@ -0,0 +1,82 @@
+use crate::analysis::expiry;
+use crate::analysis::fees::FeeModel;
+use crate::analysis::probability_model::{self, ModelEstimates};
+use crate::analysis::smart_money::{SmartMoney, WalletGroups};
+use crate::standard_data::models::{Market, Position, Trader};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use schemars::JsonSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct SideReturn {
+    pub ask: f64,
+    // payoff expected at the smart money probability, relative to the ask plus the taker fee
+    pub expected_return: f64,
+    pub annualized: Option<f64>,
+    // kelly stake as a share of bankroll at the smart money probability
+    pub kelly: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct ImpliedReturns {
+    // YES probability under the configured model
+    pub smart_probability: f64,
+    pub smart_traders: usize,
+    // every model side by side
+    pub models: ModelEstimates,
+    pub yes: Option<SideReturn>,
+    pub no: Option<SideReturn>,
+}
+
+// smart money YES probability under the configured model and how many smart holders there are
+// yes_price is the prior for the bayes model
+pub fn smart_lean(
+    yes_price: f64,
+    positions: &[Position],
+    traders: &[Trader],
+    smart_money: &SmartMoney,
+    groups: &WalletGroups,
+) -> Option<(f64, usize)> {
+    let holdings = probability_model::smart_holdings(positions, traders, smart_money, groups);
+    let probability = probability_model::estimate(smart_money.model, yes_price, &holdings)?;
+    Some((probability, holdings.len()))
+}
+
+// price the market at what smart holders are betting and compare to the current asks
+// None when no smart trader holds a position
+pub fn implied_returns(
+    market: &Market,
+    positions: &[Position],
+    traders: &[Trader],
+    smart_money: &SmartMoney,
+    groups: &WalletGroups,
+    fees: &FeeModel,
+    now: DateTime<Utc>,
+) -> Option<ImpliedReturns> {
+    let holdings = probability_model::smart_holdings(positions, traders, smart_money, groups);
+    let models = probability_model::estimate_all(market.last_trade_price, &holdings);
+    let probability = models.get(smart_money.model)?;
+
+    let time_left = expiry::time_to_expiry(market, now);
+    let side = |ask: f64, probability: f64| {
+        if ask <= 0.0 || ask >= 1.0 {
+            return None;
+        }
+        let expected_return = probability / fees.buy_price(ask) - 1.0;
+        Some(SideReturn {
+            ask,
+            expected_return,
+            annualized: time_left.and_then(|left| expiry::annualize(expected_return, left)),
+            kelly: fees.kelly_fraction(probability, ask),
+        })
+    };
+
+    Some(ImpliedReturns {
+        smart_probability: probability,
+        smart_traders: holdings.len(),
+        models,
+        yes: side(market.ask_price, probability),
+        // no is bought at one minus the yes bid
+        no: side(1.0 - market.bid_price, 1.0 - probability),
+    })
+}
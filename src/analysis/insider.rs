@@ -0,0 +1,215 @@
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::analysis::wallet_age::FRESH_MAX_MARKETS;
+use crate::standard_data::models::{Market, Position, Trader, Transaction};
+use std::collections::HashMap;
+
+// score at or above which a holder is flagged
+pub const INSIDER_MIN_SCORE: f64 = 0.6;
+// wallet age past which the age part of the score is gone
+pub const INSIDER_AGE_DAYS: f64 = 30.0;
+// entries this close to the scheduled end count as late, the closer the more
+pub const INSIDER_LATE_DAYS: f64 = 7.0;
+// lifetime volume at or under the floor is fully obscure, at or over the ceiling not at all, log scale in between
+pub const OBSCURE_VOLUME_FLOOR: f64 = 10_000.0;
+pub const OBSCURE_VOLUME_CEILING: f64 = 1_000_000.0;
+
+// how much each part counts towards the score, they add up to 1
+const AGE_WEIGHT: f64 = 0.25;
+const SIZE_WEIGHT: f64 = 0.25;
+const ONE_SIDED_WEIGHT: f64 = 0.15;
+const OBSCURITY_WEIGHT: f64 = 0.15;
+const TIMING_WEIGHT: f64 = 0.2;
+// a part at or above this is named in the explanation
+const REASON_THRESHOLD: f64 = 0.5;
+
+// why a holder scored high, one per part that stood out
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsiderReason {
+    // no stats row, nothing is known about the wallet
+    NoHistory,
+    FewMarkets(u32),
+    // days from the wallet's first trade anywhere to its entry here
+    YoungWallet(f64),
+    // share of the other holders with a smaller position
+    LargePosition(f64),
+    // share of the wallet's capital on its main side
+    OneSided(f64),
+    // the market's lifetime volume
+    ObscureMarket(f64),
+    // days from the first buy to the scheduled end
+    LateEntry(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct InsiderSignal {
+    pub trader_address: String,
+    // the side most of the capital is on
+    pub side: String,
+    pub capital: f64,
+    // 0 to 1
+    pub score: f64,
+    pub reasons: Vec<InsiderReason>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InsiderReport {
+    pub volume: f64,
+    // 0 for a busy market, 1 for a niche one
+    pub obscurity: f64,
+    pub holders: usize,
+    // holders without a timed buy can't be scored on timing
+    pub untimed: usize,
+    // every holder, highest score first
+    pub signals: Vec<InsiderSignal>,
+}
+
+impl InsiderReport {
+    pub fn flagged(&self, min_score: f64) -> impl Iterator<Item = &InsiderSignal> {
+        self.signals.iter().filter(move |s| s.score >= min_score)
+    }
+}
+
+// per wallet sums over its positions in the market
+#[derive(Default)]
+struct Holding {
+    yes: f64,
+    no: f64,
+    first_entry_block: Option<u64>,
+}
+
+// score each holder on wallet age, position size, one sidedness, market obscurity and how close to the
+// scheduled end they bought, the parts are weighted into a single 0 to 1 score
+pub fn insider_signals(market: &Market, positions: &[Position], traders: &[Trader], transactions: &[Transaction]) -> InsiderReport {
+    let by_address: HashMap<&str, &Trader> = traders.iter().map(|t| (t.trader_address.as_str(), t)).collect();
+
+    let mut holdings: HashMap<&str, Holding> = HashMap::new();
+    for position in positions {
+        let holding = holdings.entry(position.trader_address.as_str()).or_default();
+        let capital = position.shares_held * position.avg_entry_price;
+        if position.side.eq_ignore_ascii_case("YES") {
+            holding.yes += capital;
+        } else {
+            holding.no += capital;
+        }
+        if let Some(block) = position.first_entry_block {
+            holding.first_entry_block = Some(holding.first_entry_block.map_or(block, |b| b.min(block)));
+        }
+    }
+    holdings.retain(|_, h| h.yes + h.no > 0.0);
+
+    // first timed buy or split per wallet, the trade log carries times the positions table doesn't
+    let mut first_buy: HashMap<&str, i64> = HashMap::new();
+    for tx in transactions.iter().filter(|tx| !tx.removes_shares()) {
+        if let Some(timestamp) = tx.timestamp {
+            let first = first_buy.entry(tx.trader_address.as_str()).or_insert(timestamp);
+            *first = (*first).min(timestamp);
+        }
+    }
+
+    let mut sizes: Vec<f64> = holdings.values().map(|h| h.yes + h.no).collect();
+    sizes.sort_by(f64::total_cmp);
+    let obscurity = obscurity(market.volume);
+    let end = market.end_date.map(|end| end.timestamp());
+
+    let mut untimed = 0;
+    let mut signals: Vec<InsiderSignal> = holdings
+        .iter()
+        .map(|(address, holding)| {
+            let capital = holding.yes + holding.no;
+            let mut reasons = Vec::new();
+
+            let age = match by_address.get(address) {
+                None => {
+                    reasons.push(InsiderReason::NoHistory);
+                    1.0
+                }
+                Some(trader) if trader.total_markets_entered <= FRESH_MAX_MARKETS => {
+                    reasons.push(InsiderReason::FewMarkets(trader.total_markets_entered));
+                    1.0
+                }
+                Some(trader) => match (trader.first_activity_block, holding.first_entry_block) {
+                    (Some(first_seen), Some(entry)) => {
+                        let days = entry.saturating_sub(first_seen) as f64 / BLOCKS_PER_DAY as f64;
+                        let part = (1.0 - days / INSIDER_AGE_DAYS).clamp(0.0, 1.0);
+                        if part >= REASON_THRESHOLD {
+                            reasons.push(InsiderReason::YoungWallet(days));
+                        }
+                        part
+                    }
+                    _ => 0.0,
+                },
+            };
+
+            // share of holders strictly smaller
+            let size = if sizes.len() > 1 {
+                sizes.partition_point(|s| *s < capital) as f64 / (sizes.len() - 1) as f64
+            } else {
+                0.0
+            };
+            if size >= REASON_THRESHOLD {
+                reasons.push(InsiderReason::LargePosition(size));
+            }
+
+            let main_share = holding.yes.max(holding.no) / capital;
+            let one_sided = (main_share - 0.5) * 2.0;
+            if one_sided >= REASON_THRESHOLD {
+                reasons.push(InsiderReason::OneSided(main_share));
+            }
+
+            if obscurity >= REASON_THRESHOLD {
+                reasons.push(InsiderReason::ObscureMarket(market.volume));
+            }
+
+            let timing = match (first_buy.get(address), end) {
+                (Some(bought), Some(end)) => {
+                    let days = (end - bought) as f64 / 86_400.0;
+                    let part = if days < 0.0 { 0.0 } else { (1.0 - days / INSIDER_LATE_DAYS).clamp(0.0, 1.0) };
+                    if part >= REASON_THRESHOLD {
+                        reasons.push(InsiderReason::LateEntry(days));
+                    }
+                    part
+                }
+                (None, _) => {
+                    untimed += 1;
+                    0.0
+                }
+                _ => 0.0,
+            };
+
+            InsiderSignal {
+                trader_address: address.to_string(),
+                side: if holding.yes >= holding.no { "YES" } else { "NO" }.to_string(),
+                capital,
+                score: AGE_WEIGHT * age
+                    + SIZE_WEIGHT * size
+                    + ONE_SIDED_WEIGHT * one_sided
+                    + OBSCURITY_WEIGHT * obscurity
+                    + TIMING_WEIGHT * timing,
+                reasons,
+            }
+        })
+        .collect();
+    signals.sort_by(|a, b| {
+        b.score.total_cmp(&a.score)
+            .then_with(|| b.capital.total_cmp(&a.capital))
+            .then_with(|| a.trader_address.cmp(&b.trader_address))
+    });
+
+    InsiderReport {
+        volume: market.volume,
+        obscurity,
+        holders: holdings.len(),
+        untimed,
+        signals,
+    }
+}
+
+// where the volume sits between the floor and the ceiling on a log scale, inverted
+fn obscurity(volume: f64) -> f64 {
+    if volume <= OBSCURE_VOLUME_FLOOR {
+        return 1.0;
+    }
+    let span = OBSCURE_VOLUME_CEILING.log10() - OBSCURE_VOLUME_FLOOR.log10();
+    ((OBSCURE_VOLUME_CEILING.log10() - volume.log10()) / span).clamp(0.0, 1.0)
+}
+
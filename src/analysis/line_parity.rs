@@ -0,0 +1,176 @@
+use crate::standard_data::models::{ExternalEvent, Market, MarketGroup};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+// polymarket price and bookmaker consensus further apart than this get flagged
+pub const DEFAULT_PARITY_THRESHOLD: f64 = 0.05;
+
+// one outcome priced on polymarket and by the bookmakers
+#[derive(Debug, Clone, Serialize)]
+pub struct ParityLine {
+    // polymarket's name for it, an outcome or a market's question
+    pub outcome: String,
+    // the bookmakers' name for it
+    pub external_outcome: String,
+    pub polymarket_price: f64,
+    // median of the bookmakers' probabilities with their margin taken out
+    pub consensus: f64,
+    // best decimal odds on offer and who has them
+    pub best_odds: f64,
+    pub best_bookmaker: String,
+    pub bookmakers: usize,
+    pub flagged: bool,
+}
+
+impl ParityLine {
+    // positive means polymarket prices the outcome higher than the books
+    pub fn gap(&self) -> f64 {
+        self.polymarket_price - self.consensus
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParityReport {
+    pub event: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub threshold: f64,
+    // bookmakers' mean margin, how far their implied probabilities sum over one
+    pub overround: Option<f64>,
+    pub lines: Vec<ParityLine>,
+}
+
+impl ParityReport {
+    pub fn flagged(&self) -> usize {
+        self.lines.iter().filter(|line| line.flagged).count()
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// how many of the outcome's words a polymarket name has, 0 unless it has the last one, the nickname or surname
+fn fit(name: &str, outcome: &str) -> usize {
+    let name = words(name);
+    let outcome = words(outcome);
+    match outcome.last() {
+        Some(last) if name.contains(last) => outcome.iter().filter(|word| name.contains(word)).count(),
+        _ => 0,
+    }
+}
+
+fn is_yes_no(market: &Market) -> bool {
+    market.outcomes.len() == 2
+        && market.outcomes[0].eq_ignore_ascii_case("yes")
+        && market.outcomes[1].eq_ignore_ascii_case("no")
+}
+
+// what polymarket prices by name
+// a lone market with named outcomes is a moneyline, otherwise each open market's YES
+pub fn polymarket_sides(group: &MarketGroup) -> Vec<(String, f64)> {
+    let open: Vec<&Market> = group.markets.iter().filter(|m| !m.closed).collect();
+    if let [market] = open[..]
+        && !is_yes_no(market)
+    {
+        return market.outcomes
+            .iter()
+            .zip(&market.outcome_prices)
+            .filter_map(|(outcome, price)| Some((outcome.clone(), price.parse().ok()?)))
+            .collect();
+    }
+    open.iter()
+        .filter_map(|m| Some((m.question.clone(), m.outcome_prices.first()?.parse().ok()?)))
+        .collect()
+}
+
+// side index per bookmaker outcome name, best fits first, each side and outcome used once
+fn pair(sides: &[(String, f64)], event: &ExternalEvent) -> Vec<(usize, String)> {
+    let outcomes: HashSet<&str> = event.lines.iter().map(|line| line.outcome.as_str()).collect();
+    let mut candidates: Vec<(usize, usize, &str)> = outcomes
+        .iter()
+        .flat_map(|outcome| sides.iter().enumerate().map(move |(i, (name, _))| (fit(name, outcome), i, *outcome)))
+        .filter(|(fit, _, _)| *fit > 0)
+        .collect();
+    candidates.sort_by_key(|(fit, i, outcome)| (Reverse(*fit), *i, *outcome));
+
+    let mut used_sides = HashSet::new();
+    let mut used_outcomes = HashSet::new();
+    let mut pairs = Vec::new();
+    for (_, side, outcome) in candidates {
+        if used_sides.contains(&side) || used_outcomes.contains(outcome) {
+            continue;
+        }
+        used_sides.insert(side);
+        used_outcomes.insert(outcome);
+        pairs.push((side, outcome.to_string()));
+    }
+    pairs.sort();
+    pairs
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[mid - 1] + values[mid]) / 2.0,
+        _ => values[mid],
+    }
+}
+
+// the bookmakers' event that matches the most of polymarket's names, None when none matches any
+pub fn compare(group: &MarketGroup, events: &[ExternalEvent], threshold: f64) -> Option<ParityReport> {
+    let sides = polymarket_sides(group);
+    // most names matched, then the soonest to start
+    let (event, pairs) = events
+        .iter()
+        .map(|event| (event, pair(&sides, event)))
+        .filter(|(_, pairs)| !pairs.is_empty())
+        .min_by_key(|(event, pairs)| (Reverse(pairs.len()), event.starts_at))?;
+
+    // each bookmaker's implied probabilities scaled to sum to one, which takes its margin out
+    // a bookmaker quoting a single outcome has nothing to scale against
+    let mut books: BTreeMap<&str, BTreeMap<&str, f64>> = BTreeMap::new();
+    for line in &event.lines {
+        books.entry(line.bookmaker.as_str()).or_default().insert(line.outcome.as_str(), 1.0 / line.decimal_odds);
+    }
+    books.retain(|_, implied| implied.len() >= 2);
+    let overround = (!books.is_empty())
+        .then(|| books.values().map(|implied| implied.values().sum::<f64>() - 1.0).sum::<f64>() / books.len() as f64);
+
+    let lines = pairs
+        .into_iter()
+        .filter_map(|(side, outcome)| {
+            let fair: Vec<f64> = books
+                .values()
+                .filter_map(|implied| Some(implied.get(outcome.as_str())? / implied.values().sum::<f64>()))
+                .collect();
+            if fair.is_empty() {
+                return None;
+            }
+            let best = event.lines
+                .iter()
+                .filter(|line| line.outcome == outcome)
+                .max_by(|a, b| a.decimal_odds.total_cmp(&b.decimal_odds))?;
+            let (name, price) = &sides[side];
+            let consensus = median(fair.clone());
+            Some(ParityLine {
+                outcome: name.clone(),
+                external_outcome: outcome,
+                polymarket_price: *price,
+                consensus,
+                best_odds: best.decimal_odds,
+                best_bookmaker: best.bookmaker.clone(),
+                bookmakers: fair.len(),
+                flagged: (price - consensus).abs() > threshold,
+            })
+        })
+        .collect();
+
+    Some(ParityReport { event: event.title.clone(), starts_at: event.starts_at, threshold, overround, lines })
+}
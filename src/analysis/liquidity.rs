@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+// relative liquidity change from the last shift that counts as liquidity entering or leaving
+pub const LIQUIDITY_SHIFT: f64 = 0.2;
+// how long after a shift the price is read again
+pub const PRICE_LOOKAHEAD_SECS: i64 = 3_600;
+
+// one monitor poll of a market
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LiquidityPoint {
+    pub timestamp: i64,
+    pub liquidity: f64,
+    pub spread: f64,
+    pub yes_price: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiquidityMove {
+    Entered,
+    Fled,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidityShift {
+    pub kind: LiquidityMove,
+    pub from: LiquidityPoint,
+    pub to: LiquidityPoint,
+    // yes price at the first poll PRICE_LOOKAHEAD_SECS after the shift, None when monitor hasn't got there yet
+    pub price_after: Option<f64>,
+}
+
+impl LiquidityShift {
+    pub fn change(&self) -> f64 {
+        (self.to.liquidity - self.from.liquidity) / self.from.liquidity
+    }
+}
+
+// walk the polls oldest first, a shift is where liquidity has moved threshold or more from the
+// last shift, so a slow drain shows up as well as a sudden pull
+pub fn liquidity_shifts(points: &[LiquidityPoint], threshold: f64) -> Vec<LiquidityShift> {
+    let mut shifts = Vec::new();
+    let Some(mut anchor) = points.first().copied() else {
+        return shifts;
+    };
+
+    for (i, point) in points.iter().enumerate().skip(1) {
+        // an empty book has no relative change, wait for liquidity to show up
+        if anchor.liquidity <= 0.0 {
+            anchor = *point;
+            continue;
+        }
+
+        let change = (point.liquidity - anchor.liquidity) / anchor.liquidity;
+        if change.abs() < threshold {
+            continue;
+        }
+
+        let price_after = points[i..]
+            .iter()
+            .find(|later| later.timestamp >= point.timestamp + PRICE_LOOKAHEAD_SECS)
+            .map(|later| later.yes_price);
+        shifts.push(LiquidityShift {
+            kind: if change > 0.0 { LiquidityMove::Entered } else { LiquidityMove::Fled },
+            from: anchor,
+            to: *point,
+            price_after,
+        });
+        anchor = *point;
+    }
+
+    shifts
+}
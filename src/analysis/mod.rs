@@ -0,0 +1,78 @@
+pub mod alerts;
+pub mod analyzer;
+pub mod audit;
+pub mod backtest;
+pub mod big_trades;
+pub mod book_history;
+pub mod calibration;
+pub mod category;
+pub mod chatter;
+pub mod collateral;
+pub mod closing_soon;
+pub mod coherence;
+pub mod compare;
+pub mod concentration;
+pub mod crypto;
+pub mod expiry;
+pub mod fees;
+pub mod funding;
+pub mod heatmap;
+pub mod implied_return;
+pub mod insider;
+pub mod line_parity;
+pub mod liquidity;
+pub mod movers;
+pub mod new_markets;
+pub mod open_interest;
+pub mod order_flow;
+pub mod order_plan;
+pub mod pnl;
+pub mod postmortem;
+pub mod position_diff;
+pub mod probability_model;
+pub mod rewards;
+pub mod smart_money;
+pub mod tape;
+pub mod trader_history;
+pub mod trader_watch;
+pub mod vwap;
+pub mod wallet_age;
+
+pub use alerts::{Alert, AlertTracker};
+pub use analyzer::{Analyzer, AnalyzerInput};
+pub use audit::AuditReport;
+pub use backtest::{BacktestConfig, BacktestReport};
+pub use big_trades::BigTrade;
+pub use book_history::BookPoint;
+pub use calibration::{CalibrationConfig, CalibrationReport};
+pub use category::CategoryExposure;
+pub use chatter::Chatter;
+pub use closing_soon::ClosingMarket;
+pub use collateral::{CollateralMix, CollateralRates};
+pub use coherence::GroupCoherence;
+pub use compare::MarketSummary;
+pub use concentration::Concentration;
+pub use crypto::CryptoFairValue;
+pub use fees::FeeModel;
+pub use funding::FundingReport;
+pub use heatmap::TradeHeatmap;
+pub use implied_return::ImpliedReturns;
+pub use insider::InsiderReport;
+pub use line_parity::ParityReport;
+pub use liquidity::LiquidityShift;
+pub use movers::Mover;
+pub use new_markets::NewMarket;
+pub use open_interest::OpenInterest;
+pub use order_flow::OrderFlowReport;
+pub use order_plan::{OrderPlan, OrderRequest, Outcome};
+pub use pnl::{CostBasis, TraderPnl};
+pub use position_diff::PositionDelta;
+pub use postmortem::Postmortem;
+pub use probability_model::{ModelEstimates, ProbabilityModel};
+pub use rewards::RewardSettings;
+pub use smart_money::SmartMoney;
+pub use tape::Tape;
+pub use trader_history::MarketRecord;
+pub use trader_watch::TraderActivity;
+pub use vwap::VwapReport;
+pub use wallet_age::WalletAgeBreakdown;
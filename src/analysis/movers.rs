@@ -0,0 +1,56 @@
+use crate::analysis::vwap;
+use crate::standard_data::models::{Market, Transaction};
+use serde::Serialize;
+
+// window the momentum read is taken over, matches the 24h change
+pub const MOVER_VWAP_HOURS: u32 = 24;
+
+// one row of the movers table
+#[derive(Debug, Clone, Serialize)]
+pub struct Mover {
+    pub slug: String,
+    pub condition_id: String,
+    pub question: String,
+    pub yes_price: f64,
+    pub change_24h: f64,
+    pub volume_24h: f64,
+    pub liquidity: f64,
+    // yes price relative to the yes vwap over the last day, None until trades are checked
+    pub vwap_deviation: Option<f64>,
+}
+
+impl Mover {
+    // above the vwap means the move still has buyers paying up, below means it's fading
+    pub fn add_vwap(&mut self, transactions: &[Transaction]) {
+        let report = vwap::vwap_windows(transactions, &[MOVER_VWAP_HOURS]);
+        self.vwap_deviation = report.windows
+            .first()
+            .and_then(|window| window.yes)
+            .and_then(|yes| vwap::deviation(self.yes_price, yes.vwap));
+    }
+}
+
+// biggest absolute 24h yes price moves, markets without a change or under the volume floor are left out
+pub fn top_movers(markets: &[Market], min_volume_24h: f64, limit: usize) -> Vec<Mover> {
+    let mut movers: Vec<Mover> = markets
+        .iter()
+        .filter(|m| m.volume_24h >= min_volume_24h)
+        .filter_map(|m| {
+            let change_24h = m.price_change_24h.filter(|change| *change != 0.0)?;
+            Some(Mover {
+                slug: m.slug.clone(),
+                condition_id: m.condition_id.clone(),
+                question: m.question.clone(),
+                yes_price: m.last_trade_price,
+                change_24h,
+                volume_24h: m.volume_24h,
+                liquidity: m.liquidity,
+                vwap_deviation: None,
+            })
+        })
+        .collect();
+
+    movers.sort_by(|a, b| b.change_24h.abs().total_cmp(&a.change_24h.abs()));
+    movers.truncate(limit);
+    movers
+}
@@ -0,0 +1,58 @@
+use crate::standard_data::models::{Market, Position};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// one row of the new markets feed
+#[derive(Debug, Clone, Serialize)]
+pub struct NewMarket {
+    pub slug: String,
+    pub condition_id: String,
+    pub question: String,
+    pub created_at: DateTime<Utc>,
+    pub yes_price: f64,
+    pub volume: f64,
+    // wallets already holding at least the large position threshold, None when positions weren't checked
+    pub large_holders: Option<usize>,
+    pub largest_position: Option<f64>,
+}
+
+impl NewMarket {
+    pub fn accumulating(&self) -> bool {
+        self.large_holders.is_some_and(|count| count > 0)
+    }
+}
+
+// markets listed within the window, newest first
+pub fn recent_markets(markets: &[Market], window: TimeDelta, now: DateTime<Utc>) -> Vec<NewMarket> {
+    let mut recent: Vec<NewMarket> = markets
+        .iter()
+        .filter_map(|m| {
+            let created_at = m.created_at.filter(|created| now - *created <= window)?;
+            Some(NewMarket {
+                slug: m.slug.clone(),
+                condition_id: m.condition_id.clone(),
+                question: m.question.clone(),
+                created_at,
+                yes_price: m.last_trade_price,
+                volume: m.volume,
+                large_holders: None,
+                largest_position: None,
+            })
+        })
+        .collect();
+
+    recent.sort_by_key(|m| std::cmp::Reverse(m.created_at));
+    recent
+}
+
+// count wallets whose combined cost basis in the market reaches min_capital
+pub fn flag_accumulation(market: &mut NewMarket, positions: &[Position], min_capital: f64) {
+    let mut capital: HashMap<&str, f64> = HashMap::new();
+    for position in positions {
+        *capital.entry(position.trader_address.as_str()).or_default() += position.shares_held * position.avg_entry_price;
+    }
+
+    market.large_holders = Some(capital.values().filter(|c| **c >= min_capital).count());
+    market.largest_position = Some(capital.values().copied().fold(0.0, f64::max));
+}
@@ -0,0 +1,144 @@
+use crate::standard_data::models::{Position, Transaction};
+use serde::Serialize;
+use schemars::JsonSchema;
+use std::collections::HashSet;
+
+// a day per point, a week back from the latest trade
+pub const OI_WINDOW_HOURS: u32 = 24;
+pub const OI_WINDOWS: usize = 7;
+// smart flow this one sided, with open interest rising, is a signal
+pub const OI_SMART_THRESHOLD: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplySource {
+    // summed over the stored positions
+    Positions,
+    // netted over the whole trade log, only right when the log goes back to the market's first trade
+    Transactions,
+}
+
+// outstanding shares of each outcome at the end of a window
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct SupplyPoint {
+    // unix seconds, exclusive
+    pub end: i64,
+    pub yes_shares: f64,
+    pub no_shares: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenInterestSignal {
+    // open interest growing with smart money buying into one side
+    BuildingYes,
+    BuildingNo,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct OpenInterest {
+    pub yes_shares: f64,
+    pub no_shares: f64,
+    pub source: SupplySource,
+    pub window_hours: u32,
+    // oldest first, from the trade log, empty without timed trades
+    pub history: Vec<SupplyPoint>,
+    // change in yes plus no shares per window, least squares over the history
+    pub trend: Option<f64>,
+    // smart traders' net usdc into YES minus into NO over everything they traded in the history, -1 to 1
+    // None when no smart trader traded in it
+    pub smart_pressure: Option<f64>,
+    pub signal: OpenInterestSignal,
+    pub untimed: usize,
+}
+
+impl OpenInterest {
+    // every complete set pays out $1, a yes and a no share make one
+    pub fn usdc(&self) -> f64 {
+        (self.yes_shares + self.no_shares) / 2.0
+    }
+}
+
+// shares a trade adds to the outcome's supply as seen from one wallet, buys mint or take over shares and sells give them up
+// a trade between two wallets nets out, two buyers on opposite outcomes mint a set, so does a split, merges and redemptions burn
+fn signed_shares(tx: &Transaction) -> f64 {
+    if tx.removes_shares() { -tx.shares } else { tx.shares }
+}
+
+// open interest now and over the last `count` windows of `window_hours`
+// positions give the current supply when there are any, the history always comes from the trade log
+pub fn open_interest(
+    positions: Option<&[Position]>,
+    transactions: &[Transaction],
+    smart: &HashSet<&str>,
+    window_hours: u32,
+    count: usize,
+) -> OpenInterest {
+    let untimed = transactions.iter().filter(|tx| tx.timestamp.is_none()).count();
+    let as_of = transactions.iter().filter_map(|tx| tx.timestamp).max();
+    let width = window_hours.max(1) as i64 * 3_600;
+    let start = as_of.map(|end| end + 1 - count as i64 * width);
+
+    let mut history: Vec<SupplyPoint> = match as_of {
+        Some(end) => (0..count as i64)
+            .rev()
+            .map(|back| SupplyPoint { end: end + 1 - back * width, yes_shares: 0.0, no_shares: 0.0 })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let (mut yes_total, mut no_total) = (0.0, 0.0);
+    let (mut smart_yes, mut smart_no, mut smart_gross) = (0.0, 0.0, 0.0);
+    for tx in transactions {
+        let yes = tx.side.eq_ignore_ascii_case("YES");
+        let shares = signed_shares(tx);
+        if yes { yes_total += shares } else { no_total += shares }
+
+        let Some(timestamp) = tx.timestamp else {
+            continue;
+        };
+        // every window ending after the trade includes it
+        for point in history.iter_mut().filter(|p| timestamp < p.end) {
+            if yes { point.yes_shares += shares } else { point.no_shares += shares }
+        }
+
+        if tx.is_fill() && start.is_some_and(|start| timestamp >= start) && smart.contains(tx.trader_address.as_str()) {
+            let usdc = if tx.removes_shares() { -tx.usdc_amount } else { tx.usdc_amount };
+            if yes { smart_yes += usdc } else { smart_no += usdc }
+            smart_gross += tx.usdc_amount;
+        }
+    }
+
+    let (yes_shares, no_shares, source) = match positions.filter(|p| !p.is_empty()) {
+        Some(positions) => {
+            let held = |side: &str| positions.iter().filter(|p| p.side.eq_ignore_ascii_case(side)).map(|p| p.shares_held).sum::<f64>();
+            (held("YES"), held("NO"), SupplySource::Positions)
+        }
+        None => (yes_total.max(0.0), no_total.max(0.0), SupplySource::Transactions),
+    };
+
+    let trend = trend(&history);
+    let smart_pressure = (smart_gross > 0.0).then(|| (smart_yes - smart_no) / smart_gross);
+    let signal = match (trend, smart_pressure) {
+        (Some(t), Some(p)) if t > 0.0 && p > OI_SMART_THRESHOLD => OpenInterestSignal::BuildingYes,
+        (Some(t), Some(p)) if t > 0.0 && p < -OI_SMART_THRESHOLD => OpenInterestSignal::BuildingNo,
+        _ => OpenInterestSignal::Neutral,
+    };
+
+    OpenInterest { yes_shares, no_shares, source, window_hours, history, trend, smart_pressure, signal, untimed }
+}
+
+// slope of total shares against the window index, None with fewer than two windows
+fn trend(history: &[SupplyPoint]) -> Option<f64> {
+    if history.len() < 2 {
+        return None;
+    }
+
+    let n = history.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = history.iter().map(|p| p.yes_shares + p.no_shares).sum::<f64>() / n;
+    let covariance: f64 = history.iter().enumerate().map(|(i, p)| (i as f64 - mean_x) * (p.yes_shares + p.no_shares - mean_y)).sum();
+    let variance: f64 = (0..history.len()).map(|i| (i as f64 - mean_x).powi(2)).sum();
+    Some(covariance / variance)
+}
@@ -0,0 +1,126 @@
+use crate::standard_data::models::Transaction;
+use serde::Serialize;
+use schemars::JsonSchema;
+
+// hours per window and how many windows back from the latest trade
+pub const DEFAULT_OFI_WINDOW_HOURS: u32 = 1;
+pub const OFI_WINDOWS: usize = 6;
+// yes pressure in the latest window past this, with the trend not against it, is a signal
+pub const OFI_SIGNAL_THRESHOLD: f64 = 0.2;
+
+// usdc bought and sold on one outcome
+#[derive(Debug, Clone, Copy, Default, Serialize, JsonSchema)]
+pub struct SideFlow {
+    pub buy: f64,
+    pub sell: f64,
+}
+
+impl SideFlow {
+    pub fn net(&self) -> f64 {
+        self.buy - self.sell
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct FlowWindow {
+    // unix seconds, start inclusive
+    pub start: i64,
+    pub end: i64,
+    pub yes: SideFlow,
+    pub no: SideFlow,
+}
+
+impl FlowWindow {
+    // net flow into YES minus net flow into NO over everything traded, -1 all towards NO, 1 all towards YES
+    // buying NO and selling YES both push the YES price down so they count the same way
+    pub fn yes_pressure(&self) -> Option<f64> {
+        let total = self.yes.buy + self.yes.sell + self.no.buy + self.no.sell;
+        (total > 0.0).then(|| (self.yes.net() - self.no.net()) / total)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowSignal {
+    BuyingYes,
+    BuyingNo,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct OrderFlowReport {
+    pub window_hours: u32,
+    // windows end at the latest timed trade, same as vwap
+    pub as_of: Option<i64>,
+    // oldest first
+    pub windows: Vec<FlowWindow>,
+    // change in yes pressure per window, least squares over the windows that traded
+    pub trend: Option<f64>,
+    pub signal: FlowSignal,
+    pub untimed: usize,
+}
+
+// rolling order flow imbalance over `count` back to back windows of `window_hours`
+pub fn order_flow(transactions: &[Transaction], window_hours: u32, count: usize) -> OrderFlowReport {
+    let untimed = transactions.iter().filter(|tx| tx.timestamp.is_none()).count();
+    let as_of = transactions.iter().filter_map(|tx| tx.timestamp).max();
+    let width = window_hours.max(1) as i64 * 3_600;
+
+    let mut windows: Vec<FlowWindow> = match as_of {
+        Some(end) => (0..count as i64)
+            .rev()
+            .map(|back| FlowWindow {
+                // the latest trade sits inside the newest window
+                start: end + 1 - (back + 1) * width,
+                end: end + 1 - back * width,
+                yes: SideFlow::default(),
+                no: SideFlow::default(),
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    for tx in transactions.iter().filter(|tx| tx.is_fill()) {
+        let Some(timestamp) = tx.timestamp else {
+            continue;
+        };
+        let Some(window) = windows.iter_mut().find(|w| timestamp >= w.start && timestamp < w.end) else {
+            continue;
+        };
+        let side = if tx.side.eq_ignore_ascii_case("YES") { &mut window.yes } else { &mut window.no };
+        if tx.action.eq_ignore_ascii_case("SELL") {
+            side.sell += tx.usdc_amount;
+        } else {
+            side.buy += tx.usdc_amount;
+        }
+    }
+
+    let trend = trend(&windows);
+    let latest = windows.last().and_then(FlowWindow::yes_pressure);
+    let signal = match (latest, trend) {
+        (Some(p), t) if p > OFI_SIGNAL_THRESHOLD && t.is_none_or(|t| t >= 0.0) => FlowSignal::BuyingYes,
+        (Some(p), t) if p < -OFI_SIGNAL_THRESHOLD && t.is_none_or(|t| t <= 0.0) => FlowSignal::BuyingNo,
+        _ => FlowSignal::Neutral,
+    };
+
+    OrderFlowReport { window_hours, as_of, windows, trend, signal, untimed }
+}
+
+// slope of yes pressure against the window index, None with fewer than two windows that traded
+fn trend(windows: &[FlowWindow]) -> Option<f64> {
+    let points: Vec<(f64, f64)> = windows
+        .iter()
+        .enumerate()
+        .filter_map(|(i, w)| w.yes_pressure().map(|p| (i as f64, p)))
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    Some(covariance / variance)
+}
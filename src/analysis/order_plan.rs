@@ -0,0 +1,117 @@
+use crate::analysis::fees::FeeModel;
+use crate::standard_data::models::{Market, OrderBook};
+use serde::{Deserialize, Serialize};
+
+// outcome token an order buys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Yes,
+    No,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Yes => "YES",
+            Outcome::No => "NO",
+        }
+    }
+
+    pub fn token_id<'a>(&self, market: &'a Market) -> &'a str {
+        match self {
+            Outcome::Yes => &market.yes_token_id,
+            Outcome::No => &market.no_token_id,
+        }
+    }
+
+    // yes probability turned into this outcome's probability
+    pub fn probability(&self, yes_probability: f64) -> f64 {
+        match self {
+            Outcome::Yes => yes_probability,
+            Outcome::No => 1.0 - yes_probability,
+        }
+    }
+}
+
+// a buy limit order as the user would place it
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OrderRequest {
+    pub outcome: Outcome,
+    pub shares: f64,
+    pub limit_price: f64,
+}
+
+// what the order would do against the current book, nothing is sent anywhere
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderPlan {
+    pub request: OrderRequest,
+    pub best_ask: Option<f64>,
+    pub filled_shares: f64,
+    // book price per filled share, before fees
+    pub avg_price: Option<f64>,
+    pub fees: f64,
+    // usdc spent including fees
+    pub total_cost: f64,
+    // avg price minus the best ask, what walking the book costs per share
+    pub slippage: Option<f64>,
+    pub levels_used: usize,
+    // the outcome's probability according to smart money
+    pub model_probability: Option<f64>,
+    pub kelly: Option<f64>,
+}
+
+impl OrderPlan {
+    // left resting on the book at the limit price
+    pub fn unfilled_shares(&self) -> f64 {
+        self.request.shares - self.filled_shares
+    }
+
+    // model probability minus the all in price per share
+    pub fn edge(&self) -> Option<f64> {
+        let probability = self.model_probability?;
+        (self.filled_shares > 0.0).then(|| probability - self.total_cost / self.filled_shares)
+    }
+
+    // payoff expected at the model probability for the filled part
+    pub fn expected_profit(&self) -> Option<f64> {
+        let probability = self.model_probability?;
+        Some(probability * self.filled_shares - self.total_cost)
+    }
+}
+
+// walk the asks up to the limit price, every fill pays the taker fee
+pub fn plan_buy(book: &OrderBook, request: OrderRequest, fees: &FeeModel, model_probability: Option<f64>) -> OrderPlan {
+    let mut remaining = request.shares;
+    let mut filled_shares = 0.0;
+    let mut notional = 0.0;
+    let mut levels_used = 0;
+
+    for level in book.asks.iter().take_while(|level| level.price <= request.limit_price) {
+        if remaining <= 0.0 {
+            break;
+        }
+        let take = level.size.min(remaining);
+        filled_shares += take;
+        notional += take * level.price;
+        remaining -= take;
+        levels_used += 1;
+    }
+
+    let best_ask = book.asks.first().map(|level| level.price);
+    let avg_price = (filled_shares > 0.0).then(|| notional / filled_shares);
+    let fee = fees.taker_fee(notional);
+
+    OrderPlan {
+        request,
+        best_ask,
+        filled_shares,
+        avg_price,
+        fees: fee,
+        total_cost: notional + fee,
+        slippage: avg_price.zip(best_ask).map(|(avg, best)| avg - best),
+        levels_used,
+        model_probability,
+        kelly: model_probability.zip(avg_price).map(|(p, price)| fees.kelly_fraction(p, price)),
+    }
+}
@@ -0,0 +1,288 @@
+use crate::analysis::fees::FeeModel;
+use crate::standard_data::models::{Market, Transaction};
+use std::collections::{BTreeMap, VecDeque};
+use serde::Serialize;
+use schemars::JsonSchema;
+
+// how sells are matched against earlier buys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, JsonSchema, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CostBasis {
+    // oldest lots are sold first
+    #[default]
+    Fifo,
+    // every sell uses the running average entry price
+    #[value(name = "avg")]
+    #[serde(rename = "avg")]
+    Average,
+    // newest lots are sold first
+    Lifo,
+}
+
+// one trader's pnl in a single market
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct TraderPnl {
+    pub trader_address: String,
+    pub realized: f64,
+    pub unrealized: f64,
+    pub open_yes_shares: f64,
+    pub open_no_shares: f64,
+    // cost of the shares still held
+    pub open_cost: f64,
+    // shares sold that we never saw bought, e.g. acquired before the log starts
+    pub unmatched_shares: f64,
+    // trading fees paid plus gas still needed to redeem open shares, already taken out of the pnl
+    pub fees: f64,
+    // liquidity rewards paid for this market, never part of realized or unrealized
+    pub rewards: f64,
+}
+
+impl TraderPnl {
+    pub fn total(&self) -> f64 {
+        self.realized + self.unrealized
+    }
+
+    pub fn total_with_rewards(&self) -> f64 {
+        self.total() + self.rewards
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Lot {
+    shares: f64,
+    price: f64,
+}
+
+// open lots for one side of one trader
+#[derive(Debug, Default)]
+struct Book {
+    lots: VecDeque<Lot>,
+}
+
+impl Book {
+    fn shares(&self) -> f64 {
+        self.lots.iter().map(|l| l.shares).sum()
+    }
+
+    fn cost(&self) -> f64 {
+        self.lots.iter().map(|l| l.shares * l.price).sum()
+    }
+
+    fn buy(&mut self, method: CostBasis, shares: f64, price: f64) {
+        match method {
+            CostBasis::Fifo | CostBasis::Lifo => self.lots.push_back(Lot { shares, price }),
+            // keep a single lot at the blended price
+            CostBasis::Average => {
+                let held = self.shares();
+                let cost = self.cost() + shares * price;
+                self.lots.clear();
+                self.lots.push_back(Lot { shares: held + shares, price: cost / (held + shares) });
+            }
+        }
+    }
+
+    // remove shares and return (matched shares, their cost)
+    fn sell(&mut self, method: CostBasis, mut shares: f64) -> (f64, f64) {
+        let mut matched = 0.0;
+        let mut cost = 0.0;
+
+        while shares > 0.0 {
+            let lot = match method {
+                CostBasis::Lifo => self.lots.back_mut(),
+                CostBasis::Fifo | CostBasis::Average => self.lots.front_mut(),
+            };
+            let Some(lot) = lot else {
+                break;
+            };
+
+            let take = lot.shares.min(shares);
+            matched += take;
+            cost += take * lot.price;
+            lot.shares -= take;
+            shares -= take;
+
+            if lot.shares <= 0.0 {
+                match method {
+                    CostBasis::Lifo => self.lots.pop_back(),
+                    CostBasis::Fifo | CostBasis::Average => self.lots.pop_front(),
+                };
+            }
+        }
+
+        (matched, cost)
+    }
+}
+
+// current yes/no prices to mark open shares at
+pub fn outcome_marks(market: &Market) -> (f64, f64) {
+    let price = |i: usize| market.outcome_prices.get(i).and_then(|p| p.parse::<f64>().ok());
+    let yes = price(0).unwrap_or(market.last_trade_price);
+    let no = price(1).unwrap_or(1.0 - yes);
+    (yes, no)
+}
+
+// replay a market's trades per trader, open shares are marked at the given prices
+// transactions should be ordered by block
+pub fn reconstruct_pnl(
+    transactions: &[Transaction],
+    method: CostBasis,
+    yes_mark: f64,
+    no_mark: f64,
+    fees: &FeeModel,
+) -> Vec<TraderPnl> {
+    let mut books: BTreeMap<&str, (Book, Book, TraderPnl)> = BTreeMap::new();
+
+    for tx in transactions {
+        if tx.shares <= 0.0 {
+            continue;
+        }
+
+        let (yes, no, pnl) = books.entry(tx.trader_address.as_str()).or_default();
+        let book = if tx.side.eq_ignore_ascii_case("YES") { yes } else { no };
+        let price = tx.usdc_amount / tx.shares;
+
+        // the log doesn't say who took liquidity, charge every fill as taker so pnl errs low
+        // splits, merges and redemptions go through the ctf contract and pay no fee
+        let fees = if tx.is_fill() { *fees } else { FeeModel { taker_bps: 0.0, ..*fees } };
+        pnl.fees += fees.taker_fee(tx.usdc_amount);
+        if tx.removes_shares() {
+            let (matched, cost) = book.sell(method, tx.shares);
+            pnl.realized += matched * fees.sell_price(price) - cost;
+            pnl.unmatched_shares += tx.shares - matched;
+        } else {
+            book.buy(method, tx.shares, fees.buy_price(price));
+        }
+    }
+
+    let mut results: Vec<TraderPnl> = books
+        .into_iter()
+        .map(|(address, (yes, no, mut pnl))| {
+            pnl.trader_address = address.to_string();
+            pnl.open_yes_shares = yes.shares();
+            pnl.open_no_shares = no.shares();
+            pnl.open_cost = yes.cost() + no.cost();
+            pnl.unrealized = pnl.open_yes_shares * yes_mark + pnl.open_no_shares * no_mark - pnl.open_cost;
+
+            // every side still worth something has to be redeemed on chain eventually
+            let redemptions = [(pnl.open_yes_shares, yes_mark), (pnl.open_no_shares, no_mark)]
+                .iter()
+                .filter(|(shares, mark)| *shares > 0.0 && *mark > 0.0)
+                .count();
+            let gas = redemptions as f64 * fees.redemption_gas;
+            pnl.unrealized -= gas;
+            pnl.fees += gas;
+            pnl
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.total().total_cmp(&a.total()));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_data::models::Collateral;
+
+    // 1% taker fee on every fill and 0.05 of gas per side left to redeem
+    const FEES: FeeModel = FeeModel { maker_bps: 0.0, taker_bps: 100.0, redemption_gas: 0.05 };
+
+    fn trade(trader: &str, side: &str, action: &str, block: u64, shares: f64, price: f64) -> Transaction {
+        Transaction {
+            block_number: block,
+            transaction_hash: format!("0x{}{}", trader, block),
+            log_index: Some(0),
+            trader_address: trader.to_string(),
+            token_id: format!("m-{}", side.to_lowercase()),
+            side: side.to_string(),
+            action: action.to_string(),
+            shares,
+            usdc_amount: shares * price,
+            market_id: "m".to_string(),
+            timestamp: Some(block as i64 * 2),
+            collateral: Collateral::default(),
+        }
+    }
+
+    fn of<'a>(results: &'a [TraderPnl], trader: &str) -> &'a TraderPnl {
+        results.iter().find(|pnl| pnl.trader_address == trader).unwrap()
+    }
+
+    fn assert_close(got: f64, want: f64) {
+        assert!((got - want).abs() < 1e-9, "got {}, want {}", got, want);
+    }
+
+    #[test]
+    fn a_partial_close_realizes_the_oldest_lots_after_fees() {
+        let transactions = [
+            trade("0xa", "YES", "BUY", 1, 10.0, 0.40),
+            trade("0xa", "YES", "BUY", 2, 10.0, 0.60),
+            trade("0xa", "YES", "SELL", 3, 15.0, 0.70),
+        ];
+        let results = reconstruct_pnl(&transactions, CostBasis::Fifo, 0.8, 0.2, &FEES);
+        let pnl = of(&results, "0xa");
+
+        // lots cost 0.404 and 0.606 a share with the fee, the sell brings in 0.693
+        // 15 * 0.693 - (10 * 0.404 + 5 * 0.606)
+        assert_close(pnl.realized, 3.325);
+        assert_close(pnl.open_yes_shares, 5.0);
+        assert_close(pnl.open_cost, 3.03);
+        // 5 * 0.8 - 3.03, less the gas to redeem the yes side
+        assert_close(pnl.unrealized, 0.92);
+        assert_close(pnl.fees, 0.04 + 0.06 + 0.105 + 0.05);
+        assert_eq!(pnl.unmatched_shares, 0.0);
+    }
+
+    #[test]
+    fn selling_more_than_was_bought_only_realizes_the_matched_shares() {
+        let transactions = [
+            trade("0xb", "NO", "BUY", 1, 10.0, 0.30),
+            trade("0xb", "NO", "SELL", 2, 14.0, 0.50),
+        ];
+        let results = reconstruct_pnl(&transactions, CostBasis::Fifo, 0.5, 0.5, &FEES);
+        let pnl = of(&results, "0xb");
+
+        // 10 * 0.495 - 10 * 0.303, the 4 shares bought before the log are left out
+        assert_close(pnl.realized, 1.92);
+        assert_close(pnl.unmatched_shares, 4.0);
+        assert_eq!(pnl.open_no_shares, 0.0);
+        // nothing left to redeem, so no gas either
+        assert_eq!(pnl.unrealized, 0.0);
+        assert_close(pnl.fees, 0.03 + 0.07);
+    }
+
+    #[test]
+    fn redemptions_pay_no_trading_fee() {
+        let transactions = [
+            trade("0xc", "YES", "BUY", 1, 10.0, 0.50),
+            trade("0xc", "YES", "REDEEM", 2, 10.0, 1.0),
+        ];
+        let results = reconstruct_pnl(&transactions, CostBasis::Fifo, 1.0, 0.0, &FEES);
+        let pnl = of(&results, "0xc");
+
+        // 10 * 1.0 - 10 * 0.505
+        assert_close(pnl.realized, 4.95);
+        assert_close(pnl.fees, 0.05);
+        assert_eq!(pnl.unrealized, 0.0);
+    }
+
+    #[test]
+    fn cost_basis_methods_realize_different_lots() {
+        let transactions = [
+            trade("0xd", "YES", "BUY", 1, 10.0, 0.40),
+            trade("0xd", "YES", "BUY", 2, 10.0, 0.60),
+            trade("0xd", "YES", "SELL", 3, 10.0, 0.70),
+        ];
+        let fees = FeeModel { redemption_gas: 0.0, ..FeeModel::default() };
+
+        // the sell brings in 7, against the 0.40 lot, the 0.50 average or the 0.60 lot
+        // the rest is marked at 0.5, so the total is the same whichever lot was sold
+        for (method, realized, unrealized) in [(CostBasis::Fifo, 3.0, -1.0), (CostBasis::Average, 2.0, 0.0), (CostBasis::Lifo, 1.0, 1.0)] {
+            let results = reconstruct_pnl(&transactions, method, 0.5, 0.5, &fees);
+            let pnl = of(&results, "0xd");
+            assert_close(pnl.realized, realized);
+            assert_close(pnl.unrealized, unrealized);
+            assert_close(pnl.open_yes_shares, 10.0);
+        }
+    }
+}
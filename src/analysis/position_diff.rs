@@ -0,0 +1,54 @@
+use crate::analysis::big_trades::{self, PositionChange};
+use crate::standard_data::models::Transaction;
+use std::collections::{BTreeMap, HashSet};
+
+// one trader's holding of one outcome at the start and end of the window
+#[derive(Debug, Clone)]
+pub struct PositionDelta {
+    pub trader_address: String,
+    pub side: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl PositionDelta {
+    pub fn shares(&self) -> f64 {
+        self.after - self.before
+    }
+
+    pub fn change(&self) -> PositionChange {
+        PositionChange::between(self.before, self.after)
+    }
+}
+
+// net position moves over the fills in `recent`, biggest moves first
+// the before side is the full history replayed without those fills, so no stored snapshot is needed
+pub fn position_changes(history: &[Transaction], recent: &[Transaction], min_shares: f64) -> Vec<PositionDelta> {
+    let window: HashSet<_> = recent.iter().map(big_trades::fill_key).collect();
+
+    let mut ordered: Vec<&Transaction> = history.iter().collect();
+    ordered.sort_by_key(|tx| tx.block_number);
+
+    // btree keeps ties in address order from run to run
+    let mut holdings: BTreeMap<(&str, &str), (f64, f64)> = BTreeMap::new();
+    for tx in ordered {
+        let (before, after) = holdings.entry((tx.trader_address.as_str(), tx.side.as_str())).or_default();
+        if !window.contains(&big_trades::fill_key(tx)) {
+            *before = big_trades::shares_after(*before, tx);
+        }
+        *after = big_trades::shares_after(*after, tx);
+    }
+
+    let mut deltas: Vec<PositionDelta> = holdings
+        .into_iter()
+        .map(|((trader, side), (before, after))| PositionDelta {
+            trader_address: trader.to_string(),
+            side: side.to_string(),
+            before,
+            after,
+        })
+        .filter(|delta| delta.shares().abs() >= min_shares.max(f64::EPSILON))
+        .collect();
+    deltas.sort_by(|a, b| b.shares().abs().total_cmp(&a.shares().abs()));
+    deltas
+}
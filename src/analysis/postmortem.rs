@@ -0,0 +1,199 @@
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::analysis::big_trades;
+use crate::analysis::calibration::{self, SideBook};
+use crate::analysis::probability_model::{self, ProbabilityModel, SmartHolding};
+use crate::standard_data::models::{Trader, Transaction};
+use std::collections::{BTreeMap, HashMap};
+
+// points the smart money signal is rebuilt at between the first trade and resolution
+pub const POSTMORTEM_CHECKPOINTS: usize = 8;
+
+// traders grouped by whether they count as smart money and whether they came out ahead
+#[derive(Debug, Clone, Default)]
+pub struct Cohort {
+    pub name: &'static str,
+    pub traders: usize,
+    pub bought: f64,
+    // average price paid per share of the outcome that won and the one that lost
+    pub winning_entry: Option<f64>,
+    pub losing_entry: Option<f64>,
+    // winning shares still held at resolution, paid out at $1 each
+    pub payout: f64,
+    // usdc taken out by selling before resolution
+    pub proceeds: f64,
+}
+
+impl Cohort {
+    pub fn net(&self) -> f64 {
+        self.payout + self.proceeds - self.bought
+    }
+}
+
+// what smart money said at one point before resolution
+#[derive(Debug, Clone)]
+pub struct SignalPoint {
+    pub block: u64,
+    pub days_before: f64,
+    pub yes_price: Option<f64>,
+    pub smart_holders: usize,
+    // YES probability under the configured model
+    pub smart_yes: Option<f64>,
+}
+
+impl SignalPoint {
+    // smart money leaned towards the outcome that won
+    pub fn right(&self, yes_won: bool) -> Option<bool> {
+        self.smart_yes.filter(|p| *p != 0.5).map(|p| (p > 0.5) == yes_won)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Postmortem {
+    pub outcome: String,
+    pub resolution_block: u64,
+    pub trades: usize,
+    // smart winners, smart losers, other winners, other losers
+    pub cohorts: Vec<Cohort>,
+    // oldest first
+    pub signal: Vec<SignalPoint>,
+}
+
+impl Postmortem {
+    pub fn yes_won(&self) -> bool {
+        self.outcome.eq_ignore_ascii_case("YES")
+    }
+}
+
+// one trader's dealings in the market
+#[derive(Default)]
+struct Ledger {
+    // indexed yes then no
+    bought_shares: [f64; 2],
+    bought_usdc: [f64; 2],
+    held: [f64; 2],
+    proceeds: f64,
+}
+
+// who won and lost a resolved market, what they paid and how the smart money read moved on the way
+pub fn postmortem(
+    transactions: &[Transaction],
+    outcome: &str,
+    resolution_block: u64,
+    smart: &HashMap<String, Trader>,
+    model: ProbabilityModel,
+    checkpoints: usize,
+) -> Postmortem {
+    let mut ordered: Vec<&Transaction> = transactions.iter().collect();
+    ordered.sort_by_key(|tx| tx.block_number);
+    let side = |tx: &Transaction| if tx.side.eq_ignore_ascii_case("YES") { 0 } else { 1 };
+    let winning = if outcome.eq_ignore_ascii_case("YES") { 0 } else { 1 };
+
+    let mut ledgers: BTreeMap<&str, Ledger> = BTreeMap::new();
+    for tx in &ordered {
+        let ledger = ledgers.entry(tx.trader_address.as_str()).or_default();
+        let i = side(tx);
+        if tx.removes_shares() {
+            ledger.proceeds += tx.usdc_amount;
+        } else {
+            ledger.bought_shares[i] += tx.shares;
+            ledger.bought_usdc[i] += tx.usdc_amount;
+        }
+        ledger.held[i] = big_trades::shares_after(ledger.held[i], tx);
+    }
+
+    let mut cohorts: Vec<Cohort> = ["Smart winners", "Smart losers", "Other winners", "Other losers"]
+        .into_iter()
+        .map(|name| Cohort { name, ..Cohort::default() })
+        .collect();
+    // shares and usdc behind each entry price, per cohort
+    let mut entries = vec![[(0.0, 0.0); 2]; cohorts.len()];
+    for (address, ledger) in &ledgers {
+        let payout = ledger.held[winning];
+        let won = payout + ledger.proceeds > ledger.bought_usdc.iter().sum::<f64>();
+        let index = match (smart.contains_key(*address), won) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        };
+
+        let cohort = &mut cohorts[index];
+        cohort.traders += 1;
+        cohort.bought += ledger.bought_usdc.iter().sum::<f64>();
+        cohort.payout += payout;
+        cohort.proceeds += ledger.proceeds;
+        for (slot, i) in [(0, winning), (1, 1 - winning)] {
+            entries[index][slot].0 += ledger.bought_shares[i];
+            entries[index][slot].1 += ledger.bought_usdc[i];
+        }
+    }
+    for (cohort, [won, lost]) in cohorts.iter_mut().zip(entries) {
+        let average = |(shares, usdc): (f64, f64)| (shares > 0.0).then(|| usdc / shares);
+        cohort.winning_entry = average(won);
+        cohort.losing_entry = average(lost);
+    }
+
+    let signal = match ordered.first() {
+        Some(first) => signal_points(&ordered, first.block_number, resolution_block, smart, model, checkpoints),
+        None => Vec::new(),
+    };
+
+    Postmortem {
+        outcome: outcome.to_string(),
+        resolution_block,
+        trades: transactions.len(),
+        cohorts,
+        signal,
+    }
+}
+
+// the smart money probability rebuilt from trades up to evenly spaced blocks, the last one at resolution
+fn signal_points(
+    ordered: &[&Transaction],
+    first_block: u64,
+    resolution_block: u64,
+    smart: &HashMap<String, Trader>,
+    model: ProbabilityModel,
+    checkpoints: usize,
+) -> Vec<SignalPoint> {
+    let checkpoints = checkpoints.max(1);
+    let span = resolution_block.saturating_sub(first_block);
+
+    (1..=checkpoints)
+        .map(|step| {
+            let block = first_block + span * step as u64 / checkpoints as u64;
+            let before: Vec<&Transaction> = ordered.iter().copied().take_while(|tx| tx.block_number <= block).collect();
+
+            // same open cost books calibration scores
+            let mut books: HashMap<(&str, bool), SideBook> = HashMap::new();
+            for tx in before.iter().filter(|tx| smart.contains_key(&tx.trader_address)) {
+                let yes = tx.side.eq_ignore_ascii_case("YES");
+                books.entry((tx.trader_address.as_str(), yes)).or_default().apply(tx);
+            }
+
+            let mut holdings: BTreeMap<&str, SmartHolding> = BTreeMap::new();
+            for ((address, yes), book) in &books {
+                let holding = holdings.entry(*address).or_insert(SmartHolding {
+                    yes_capital: 0.0,
+                    no_capital: 0.0,
+                    adjusted_accuracy: smart[*address].adjusted_accuracy,
+                });
+                if *yes {
+                    holding.yes_capital += book.cost.max(0.0);
+                } else {
+                    holding.no_capital += book.cost.max(0.0);
+                }
+            }
+            let holdings: Vec<SmartHolding> = holdings.into_values().filter(|h| h.yes_capital + h.no_capital > 0.0).collect();
+
+            let yes_price = calibration::last_yes_price(&before);
+            SignalPoint {
+                block,
+                days_before: resolution_block.saturating_sub(block) as f64 / BLOCKS_PER_DAY as f64,
+                yes_price,
+                smart_holders: holdings.len(),
+                smart_yes: probability_model::estimate(model, yes_price.unwrap_or(0.5), &holdings),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+use crate::analysis::smart_money::{SmartMoney, WalletGroups};
+use crate::ingest;
+use crate::standard_data::models::{Position, Trader};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use schemars::JsonSchema;
+
+// a $1000 net stake counts as half of one full observation in the bayes model
+pub const BAYES_HALF_STAKE: f64 = 1_000.0;
+
+// how smart holders' positions are turned into a YES probability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbabilityModel {
+    // share of smart capital on YES
+    Simple,
+    // capital scaled by each holder's accuracy lower bound
+    #[default]
+    Weighted,
+    // the market price updated by every holder as independent evidence
+    Bayes,
+}
+
+impl ProbabilityModel {
+    pub const ALL: [ProbabilityModel; 3] = [ProbabilityModel::Simple, ProbabilityModel::Weighted, ProbabilityModel::Bayes];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProbabilityModel::Simple => "simple",
+            ProbabilityModel::Weighted => "weighted",
+            ProbabilityModel::Bayes => "bayes",
+        }
+    }
+}
+
+// one smart trader's capital in a market
+#[derive(Debug, Clone, Copy)]
+pub struct SmartHolding {
+    pub yes_capital: f64,
+    pub no_capital: f64,
+    pub adjusted_accuracy: f64,
+}
+
+// every model's YES probability for the same holders
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct ModelEstimates {
+    pub simple: Option<f64>,
+    pub weighted: Option<f64>,
+    pub bayes: Option<f64>,
+}
+
+impl ModelEstimates {
+    pub fn get(&self, model: ProbabilityModel) -> Option<f64> {
+        match model {
+            ProbabilityModel::Simple => self.simple,
+            ProbabilityModel::Weighted => self.weighted,
+            ProbabilityModel::Bayes => self.bayes,
+        }
+    }
+}
+
+// capital per smart trader or wallet group holding the market, traders outside the definition are dropped
+pub fn smart_holdings(positions: &[Position], traders: &[Trader], smart_money: &SmartMoney, groups: &WalletGroups) -> Vec<SmartHolding> {
+    let entity = |address: &str| groups.get(&address.to_lowercase()).cloned().unwrap_or_else(|| address.to_string());
+
+    // a group's members are pooled before the definition is applied, so it counts as one smart trader or none
+    let mut members: HashMap<String, Vec<&Trader>> = HashMap::new();
+    for trader in traders {
+        members.entry(entity(&trader.trader_address)).or_default().push(trader);
+    }
+    let accuracy: HashMap<String, f64> = members
+        .into_iter()
+        .filter_map(|(key, traders)| ingest::combine_traders(&key, &traders))
+        .filter(|t| smart_money.includes(t))
+        .map(|t| (t.trader_address, t.adjusted_accuracy))
+        .collect();
+
+    let mut holdings: BTreeMap<String, SmartHolding> = BTreeMap::new();
+    for position in positions {
+        let key = entity(&position.trader_address);
+        let Some(&adjusted_accuracy) = accuracy.get(&key) else {
+            continue;
+        };
+        let holding = holdings.entry(key).or_insert(SmartHolding {
+            yes_capital: 0.0,
+            no_capital: 0.0,
+            adjusted_accuracy,
+        });
+        let capital = position.shares_held * position.avg_entry_price;
+        if position.side.eq_ignore_ascii_case("YES") {
+            holding.yes_capital += capital;
+        } else {
+            holding.no_capital += capital;
+        }
+    }
+
+    holdings.into_values().collect()
+}
+
+// YES probability under one model, None when the holders have no capital in the market
+// yes_price is only used as the bayes prior
+pub fn estimate(model: ProbabilityModel, yes_price: f64, holdings: &[SmartHolding]) -> Option<f64> {
+    match model {
+        ProbabilityModel::Simple => capital_share(holdings, |_| 1.0),
+        ProbabilityModel::Weighted => capital_share(holdings, |h| h.adjusted_accuracy),
+        ProbabilityModel::Bayes => bayes(yes_price, holdings),
+    }
+}
+
+pub fn estimate_all(yes_price: f64, holdings: &[SmartHolding]) -> ModelEstimates {
+    ModelEstimates {
+        simple: estimate(ProbabilityModel::Simple, yes_price, holdings),
+        weighted: estimate(ProbabilityModel::Weighted, yes_price, holdings),
+        bayes: estimate(ProbabilityModel::Bayes, yes_price, holdings),
+    }
+}
+
+fn capital_share(holdings: &[SmartHolding], weight: impl Fn(&SmartHolding) -> f64) -> Option<f64> {
+    let yes: f64 = holdings.iter().map(|h| h.yes_capital * weight(h)).sum();
+    let no: f64 = holdings.iter().map(|h| h.no_capital * weight(h)).sum();
+
+    if yes + no <= 0.0 {
+        return None;
+    }
+    Some(yes / (yes + no))
+}
+
+// start at the market price in log odds, every holder's net side adds the log odds of their record
+// scaled by how much they put in, holders are treated as independent so a crowd gets confident fast
+fn bayes(yes_price: f64, holdings: &[SmartHolding]) -> Option<f64> {
+    if holdings.iter().all(|h| h.yes_capital + h.no_capital <= 0.0) {
+        return None;
+    }
+
+    let mut log_odds = logit(yes_price.clamp(0.01, 0.99));
+    for holding in holdings {
+        let net = holding.yes_capital - holding.no_capital;
+        let stake = net.abs();
+        if stake <= 0.0 {
+            continue;
+        }
+        // a record no better than a coin flip says nothing
+        let accuracy = holding.adjusted_accuracy.clamp(0.5, 0.99);
+        let weight = stake / (stake + BAYES_HALF_STAKE);
+        log_odds += net.signum() * weight * logit(accuracy);
+    }
+
+    Some(1.0 / (1.0 + (-log_odds).exp()))
+}
+
+fn logit(p: f64) -> f64 {
+    (p / (1.0 - p)).ln()
+}
@@ -0,0 +1,52 @@
+use crate::analysis::pnl::TraderPnl;
+use crate::standard_data::models::{RewardPayout, Trader};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// the [rewards] section of config.toml
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct RewardSettings {
+    // count liquidity rewards as returns in trader roi and market pnl rankings
+    // output shows the figure with and without them either way
+    pub include: bool,
+}
+
+// reward usdc per wallet, only payouts in the market when one is given
+pub fn wallet_totals(rewards: &[RewardPayout], market_id: Option<&str>) -> HashMap<String, f64> {
+    let mut totals: HashMap<String, f64> = HashMap::new();
+    for reward in rewards {
+        if market_id.is_some_and(|id| reward.market_id.as_deref().is_none_or(|paid_for| !paid_for.eq_ignore_ascii_case(id))) {
+            continue;
+        }
+        *totals.entry(reward.trader_address.to_lowercase()).or_default() += reward.usdc_amount;
+    }
+    totals
+}
+
+// traders paid anything get their total, roi counts it when the settings say so
+pub fn apply_to_traders(traders: &mut [Trader], rewards: &[RewardPayout], settings: RewardSettings) {
+    let totals = wallet_totals(rewards, None);
+    for trader in traders {
+        let Some(&total) = totals.get(&trader.trader_address.to_lowercase()) else {
+            continue;
+        };
+        trader.rewards = Some(total);
+        if settings.include {
+            trader.roi = trader.roi_with_rewards();
+        }
+    }
+}
+
+// rewards earned in this market per trader, ranked by total with them when the settings count them
+// payouts that don't name a market only show up in trader roi
+pub fn apply_to_pnl(pnls: &mut [TraderPnl], rewards: &[RewardPayout], market_id: &str, settings: RewardSettings) {
+    let totals = wallet_totals(rewards, Some(market_id));
+    for pnl in pnls.iter_mut() {
+        pnl.rewards = totals.get(&pnl.trader_address.to_lowercase()).copied().unwrap_or_default();
+    }
+    if settings.include {
+        pnls.sort_by(|a, b| b.total_with_rewards().total_cmp(&a.total_with_rewards()));
+    }
+}
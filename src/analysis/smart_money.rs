@@ -0,0 +1,83 @@
+use crate::analysis::probability_model::ProbabilityModel;
+use crate::standard_data::models::Trader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use schemars::JsonSchema;
+
+// lowercase address to the wallet group it's in, a group is weighted as one trader
+pub type WalletGroups = HashMap<String, String>;
+
+// who counts as smart money, read from the [smart_money] section of config.toml
+// the --smart-* flags override single fields for one run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmartMoney {
+    pub min_accuracy: f64,
+    pub min_resolved: u32,
+    // lifetime roi floor, None takes any roi
+    pub min_roi: Option<f64>,
+    // usdc put into markets over the wallet's lifetime
+    pub min_invested: f64,
+    // risk caps, None takes anyone, traders without the stats are left out once one is set
+    pub max_drawdown: Option<f64>,
+    pub max_loss_streak: Option<u32>,
+    pub max_volatility: Option<f64>,
+    // how their positions become a YES probability
+    pub model: ProbabilityModel,
+}
+
+impl Default for SmartMoney {
+    fn default() -> Self {
+        Self {
+            min_accuracy: 0.65,
+            min_resolved: 5,
+            min_roi: None,
+            min_invested: 0.0,
+            max_drawdown: None,
+            max_loss_streak: None,
+            max_volatility: None,
+            model: ProbabilityModel::default(),
+        }
+    }
+}
+
+impl SmartMoney {
+    pub fn includes(&self, trader: &Trader) -> bool {
+        trader.accuracy >= self.min_accuracy
+            && trader.total_markets_resolved >= self.min_resolved
+            && self.min_roi.is_none_or(|roi| trader.roi >= roi)
+            && trader.total_invested >= self.min_invested
+            && at_most(trader.max_drawdown, self.max_drawdown)
+            && at_most(trader.longest_loss_streak, self.max_loss_streak)
+            && at_most(trader.return_volatility, self.max_volatility)
+    }
+
+    // one line for report headers, e.g. "accuracy >= 65% and 5+ resolved markets, roi >= 10%"
+    pub fn describe(&self) -> String {
+        let mut text = format!("accuracy >= {:.0}% and {}+ resolved markets", self.min_accuracy * 100.0, self.min_resolved);
+        if let Some(roi) = self.min_roi {
+            text.push_str(&format!(", roi >= {:.0}%", roi * 100.0));
+        }
+        if self.min_invested > 0.0 {
+            text.push_str(&format!(", ${:.0}+ invested", self.min_invested));
+        }
+        if let Some(drawdown) = self.max_drawdown {
+            text.push_str(&format!(", drawdown <= ${:.0}", drawdown));
+        }
+        if let Some(streak) = self.max_loss_streak {
+            text.push_str(&format!(", losing streak <= {}", streak));
+        }
+        if let Some(volatility) = self.max_volatility {
+            text.push_str(&format!(", roi volatility <= {:.2}", volatility));
+        }
+        text
+    }
+}
+
+fn at_most<T: PartialOrd>(value: Option<T>, cap: Option<T>) -> bool {
+    match (value, cap) {
+        (_, None) => true,
+        (Some(value), Some(cap)) => value <= cap,
+        (None, Some(_)) => false,
+    }
+}
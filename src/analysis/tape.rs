@@ -0,0 +1,76 @@
+use crate::standard_data::models::Transaction;
+use serde::Serialize;
+use std::collections::HashSet;
+
+// longest pause between two replayed trades however quiet the market went, a quiet weekend would stall it for hours
+pub const MAX_REPLAY_PAUSE_SECS: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotableEntry {
+    // a smart money wallet's first buy in the market
+    Smart,
+    // anyone else's first buy of at least the size asked for
+    Large,
+}
+
+// one fill as the tape shows it, with the running totals up to and including it
+#[derive(Debug, Clone, Serialize)]
+pub struct TapePrint {
+    pub transaction: Transaction,
+    // paid per share of its own outcome
+    pub price: f64,
+    // the YES price it implies, a NO fill at p is YES at 1 - p
+    pub yes_price: f64,
+    // usdc bought into each outcome so far
+    pub yes_volume: f64,
+    pub no_volume: f64,
+    pub notable: Option<NotableEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Tape {
+    // oldest first
+    pub prints: Vec<TapePrint>,
+    // fills without a timestamp, they can't be placed on the tape
+    pub untimed: usize,
+}
+
+// the market's fills in time order, splits, merges and redemptions don't trade at a price and are left out
+// a wallet's entry is its first buy in the market, earlier ctf operations don't count
+pub fn tape(transactions: &[Transaction], smart: &HashSet<&str>, large_usdc: f64) -> Tape {
+    let mut fills: Vec<&Transaction> = transactions.iter().filter(|tx| tx.is_fill() && tx.shares > 0.0).collect();
+    let untimed = fills.iter().filter(|tx| tx.timestamp.is_none()).count();
+    fills.retain(|tx| tx.timestamp.is_some());
+    fills.sort_by_key(|tx| (tx.timestamp, tx.block_number, tx.log_index));
+
+    let mut entered: HashSet<&str> = HashSet::new();
+    let (mut yes_volume, mut no_volume) = (0.0, 0.0);
+    let mut prints = Vec::with_capacity(fills.len());
+    for tx in fills {
+        let yes = tx.side.eq_ignore_ascii_case("YES");
+        let price = tx.usdc_amount / tx.shares;
+        let buy = !tx.removes_shares();
+        if buy {
+            if yes { yes_volume += tx.usdc_amount } else { no_volume += tx.usdc_amount }
+        }
+
+        let first_buy = buy && entered.insert(tx.trader_address.as_str());
+        let notable = match first_buy {
+            true if smart.contains(tx.trader_address.as_str()) => Some(NotableEntry::Smart),
+            true if tx.usdc_amount >= large_usdc => Some(NotableEntry::Large),
+            _ => None,
+        };
+
+        prints.push(TapePrint {
+            transaction: tx.clone(),
+            price,
+            yes_price: if yes { price } else { 1.0 - price },
+            yes_volume,
+            no_volume,
+            notable,
+        });
+    }
+
+    Tape { prints, untimed }
+}
@@ -0,0 +1,102 @@
+use crate::analysis::category::{self, UNTAGGED};
+use crate::standard_data::models::{MarketResolution, MarketTag, Transaction};
+use std::collections::HashMap;
+
+// one market a wallet traded, from its first fill to the resolution
+#[derive(Debug, Clone)]
+pub struct MarketRecord {
+    pub market_id: String,
+    pub first_block: u64,
+    pub first_timestamp: Option<i64>,
+    pub categories: Vec<String>,
+    // the outcome most usdc went into
+    pub side: String,
+    // average price paid on that side
+    pub entry_price: Option<f64>,
+    // average price sold at on that side, None when nothing was sold
+    pub exit_price: Option<f64>,
+    pub invested: f64,
+    pub outcome: Option<String>,
+    // what came back minus what went in, resolved markets only
+    pub pnl: Option<f64>,
+}
+
+// per side sums for one market
+#[derive(Default)]
+struct SideTotals {
+    bought_shares: f64,
+    bought_usdc: f64,
+    sold_shares: f64,
+    sold_usdc: f64,
+}
+
+impl SideTotals {
+    fn net_shares(&self) -> f64 {
+        self.bought_shares - self.sold_shares
+    }
+}
+
+// every market the wallet traded, oldest first, pnl counted the way trader stats count it
+pub fn market_history(
+    trader_address: &str,
+    transactions: &[Transaction],
+    resolutions: &[MarketResolution],
+    tags: &[MarketTag],
+) -> Vec<MarketRecord> {
+    let outcomes: HashMap<&str, &str> = resolutions.iter().map(|r| (r.condition_id.as_str(), r.outcome.as_str())).collect();
+    let tagged = category::tags_by_market(tags);
+
+    // (first block, first timestamp, yes, no) per market
+    let mut markets: HashMap<&str, (u64, Option<i64>, SideTotals, SideTotals)> = HashMap::new();
+    for tx in transactions.iter().filter(|tx| tx.trader_address.eq_ignore_ascii_case(trader_address)) {
+        let (first_block, first_timestamp, yes, no) = markets
+            .entry(tx.market_id.as_str())
+            .or_insert((tx.block_number, tx.timestamp, SideTotals::default(), SideTotals::default()));
+        if tx.block_number < *first_block {
+            *first_block = tx.block_number;
+            *first_timestamp = tx.timestamp;
+        }
+
+        let totals = if tx.side.eq_ignore_ascii_case("YES") { yes } else { no };
+        if tx.removes_shares() {
+            totals.sold_shares += tx.shares;
+            totals.sold_usdc += tx.usdc_amount;
+        } else {
+            totals.bought_shares += tx.shares;
+            totals.bought_usdc += tx.usdc_amount;
+        }
+    }
+
+    let mut history: Vec<MarketRecord> = markets
+        .into_iter()
+        .map(|(market_id, (first_block, first_timestamp, yes, no))| {
+            let (side, main) = if yes.bought_usdc >= no.bought_usdc { ("YES", &yes) } else { ("NO", &no) };
+            let average = |usdc: f64, shares: f64| (shares > 0.0).then(|| usdc / shares);
+
+            let invested = yes.bought_usdc + no.bought_usdc;
+            let outcome = outcomes.get(market_id).copied();
+            let pnl = outcome.map(|outcome| {
+                let winning = if outcome.eq_ignore_ascii_case("YES") { &yes } else { &no };
+                yes.sold_usdc + no.sold_usdc + winning.net_shares().max(0.0) - invested
+            });
+
+            MarketRecord {
+                market_id: market_id.to_string(),
+                first_block,
+                first_timestamp,
+                categories: tagged
+                    .get(market_id)
+                    .map_or_else(|| vec![UNTAGGED.to_string()], |t| t.iter().map(|t| t.to_string()).collect()),
+                side: side.to_string(),
+                entry_price: average(main.bought_usdc, main.bought_shares),
+                exit_price: average(main.sold_usdc, main.sold_shares),
+                invested,
+                outcome: outcome.map(str::to_string),
+                pnl,
+            }
+        })
+        .collect();
+
+    history.sort_by(|a, b| (a.first_block, &a.market_id).cmp(&(b.first_block, &b.market_id)));
+    history
+}
@@ -0,0 +1,67 @@
+use crate::analysis::big_trades::{self, PositionChange};
+use crate::standard_data::models::Transaction;
+use std::collections::{HashMap, HashSet};
+
+// history read before the first poll to learn what the wallets already hold, nothing in it is reported
+pub const TRADER_WATCH_LOOKBACK_DAYS: i64 = 30;
+// market pages on polymarket.com, by market slug
+pub const POLYMARKET_MARKET_URL: &str = "https://polymarket.com/market";
+
+// a watched wallet going into or out of one outcome of a market
+#[derive(Debug, Clone)]
+pub struct TraderActivity {
+    // open or close, adds and trims aren't reported
+    pub change: PositionChange,
+    pub transaction: Transaction,
+    // shares of the outcome held after it, as far as the history shows
+    pub shares_after: f64,
+}
+
+// what each watched wallet holds per outcome, built from the transactions seen so far
+#[derive(Debug, Default)]
+pub struct TraderWatch {
+    holdings: HashMap<(String, String), f64>,
+    // the data api's cutoff is inclusive, the last second of one poll comes back in the next
+    seen: HashSet<(String, Option<u32>, String, String)>,
+    // newest timestamp applied, the next poll asks from here
+    cursor: i64,
+}
+
+impl TraderWatch {
+    pub fn new(since: i64) -> Self {
+        Self { cursor: since, ..Self::default() }
+    }
+
+    pub fn cursor(&self) -> i64 {
+        self.cursor
+    }
+
+    // applies transactions not seen before oldest first, returning every position they opened or closed
+    // like big trades a sell with nothing held is a close, the buys predate the history
+    pub fn observe(&mut self, transactions: &[Transaction]) -> Vec<TraderActivity> {
+        let mut ordered: Vec<&Transaction> = transactions.iter().filter(|tx| tx.timestamp.is_some()).collect();
+        ordered.sort_by_key(|tx| (tx.timestamp, tx.block_number, tx.log_index));
+
+        let mut activity = Vec::new();
+        for tx in ordered {
+            if !self.seen.insert(big_trades::fill_key(tx)) {
+                continue;
+            }
+            self.cursor = self.cursor.max(tx.timestamp.unwrap_or_default());
+
+            let held = self.holdings.entry((tx.trader_address.clone(), tx.token_id.clone())).or_default();
+            let before = *held;
+            *held = big_trades::shares_after(before, tx);
+
+            let change = PositionChange::between(before, *held);
+            if matches!(change, PositionChange::Open | PositionChange::Close) {
+                activity.push(TraderActivity { change, transaction: tx.clone(), shares_after: *held });
+            }
+        }
+        activity
+    }
+}
+
+pub fn market_url(market_slug: &str) -> String {
+    format!("{}/{}", POLYMARKET_MARKET_URL, market_slug)
+}
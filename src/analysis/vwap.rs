@@ -0,0 +1,74 @@
+use crate::standard_data::models::Transaction;
+use serde::Serialize;
+use schemars::JsonSchema;
+
+// hours looked back by default, last hour, day and week
+pub const DEFAULT_VWAP_WINDOWS: [u32; 3] = [1, 24, 168];
+
+// volume weighted price of one outcome over a window
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct SideVwap {
+    pub vwap: f64,
+    pub shares: f64,
+    pub trades: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct VwapWindow {
+    pub hours: u32,
+    pub yes: Option<SideVwap>,
+    pub no: Option<SideVwap>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct VwapReport {
+    // windows end here, the latest trade rather than now so older dumps still get numbers
+    pub as_of: Option<i64>,
+    pub windows: Vec<VwapWindow>,
+    // trades without a timestamp can't be placed in a window
+    pub untimed: usize,
+}
+
+// vwap per side for every window, each counting back `hours` from the latest timed trade
+pub fn vwap_windows(transactions: &[Transaction], windows: &[u32]) -> VwapReport {
+    let untimed = transactions.iter().filter(|tx| tx.timestamp.is_none()).count();
+    let as_of = transactions.iter().filter_map(|tx| tx.timestamp).max();
+
+    let windows = windows
+        .iter()
+        .map(|&hours| {
+            let since = as_of.map_or(i64::MAX, |end| end - hours as i64 * 3_600);
+            let in_window: Vec<&Transaction> = transactions
+                .iter()
+                .filter(|tx| tx.timestamp.is_some_and(|ts| ts >= since))
+                .collect();
+
+            VwapWindow {
+                hours,
+                yes: side_vwap(&in_window, "YES"),
+                no: side_vwap(&in_window, "NO"),
+            }
+        })
+        .collect();
+
+    VwapReport { as_of, windows, untimed }
+}
+
+// how far the price sits from the vwap, relative to the vwap, positive means above
+pub fn deviation(price: f64, vwap: f64) -> Option<f64> {
+    (vwap > 0.0).then(|| price / vwap - 1.0)
+}
+
+// None when the side didn't trade in the window
+fn side_vwap(transactions: &[&Transaction], side: &str) -> Option<SideVwap> {
+    let mut notional = 0.0;
+    let mut shares = 0.0;
+    let mut trades = 0;
+    for tx in transactions.iter().filter(|tx| tx.is_fill() && tx.side.eq_ignore_ascii_case(side) && tx.shares > 0.0) {
+        notional += tx.usdc_amount;
+        shares += tx.shares;
+        trades += 1;
+    }
+
+    (shares > 0.0).then(|| SideVwap { vwap: notional / shares, shares, trades })
+}
@@ -0,0 +1,76 @@
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::standard_data::models::{Position, Trader};
+use std::collections::HashMap;
+use serde::Serialize;
+use schemars::JsonSchema;
+
+// a wallet is fresh if it has barely traded or only started shortly before entering this market
+pub const FRESH_MAX_MARKETS: u32 = 3;
+pub const FRESH_MAX_AGE_DAYS: u64 = 7;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, JsonSchema)]
+pub struct CapitalSplit {
+    pub fresh_wallets: usize,
+    pub veteran_wallets: usize,
+    pub fresh_capital: f64,
+    pub veteran_capital: f64,
+}
+
+impl CapitalSplit {
+    pub fn fresh_share(&self) -> f64 {
+        let total = self.fresh_capital + self.veteran_capital;
+        if total > 0.0 { self.fresh_capital / total } else { 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, JsonSchema)]
+pub struct WalletAgeBreakdown {
+    pub yes: CapitalSplit,
+    pub no: CapitalSplit,
+}
+
+// split each side's cost basis between fresh and veteran wallets
+pub fn wallet_age_breakdown(positions: &[Position], traders: &[Trader]) -> WalletAgeBreakdown {
+    let by_address: HashMap<&str, &Trader> = traders
+        .iter()
+        .map(|t| (t.trader_address.as_str(), t))
+        .collect();
+
+    let mut breakdown = WalletAgeBreakdown::default();
+    for position in positions {
+        let capital = position.shares_held * position.avg_entry_price;
+        let fresh = is_fresh(position, by_address.get(position.trader_address.as_str()).copied());
+
+        let split = if position.side.eq_ignore_ascii_case("YES") {
+            &mut breakdown.yes
+        } else {
+            &mut breakdown.no
+        };
+
+        if fresh {
+            split.fresh_wallets += 1;
+            split.fresh_capital += capital;
+        } else {
+            split.veteran_wallets += 1;
+            split.veteran_capital += capital;
+        }
+    }
+
+    breakdown
+}
+
+// wallets with no stats at all have no history we know of, so they count as fresh
+fn is_fresh(position: &Position, trader: Option<&Trader>) -> bool {
+    let Some(trader) = trader else {
+        return true;
+    };
+
+    if trader.total_markets_entered <= FRESH_MAX_MARKETS {
+        return true;
+    }
+
+    match (trader.first_activity_block, position.first_entry_block) {
+        (Some(first_seen), Some(entry)) => entry.saturating_sub(first_seen) < FRESH_MAX_AGE_DAYS * BLOCKS_PER_DAY,
+        _ => false,
+    }
+}
@@ -0,0 +1,82 @@
+use crate::error::{CancelError, Result};
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+// how long a cancelled run gets to notice and flush what it has before it's dropped
+pub const CANCEL_GRACE: Duration = Duration::from_secs(2);
+
+// stops a whole run on ctrl-c or --timeout, clones share the token
+// the http client and the local db each hold one and refuse new work once it fires
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation {
+    token: CancellationToken,
+    // the first reason wins, a ctrl-c after the timeout is still a timeout
+    reason: Arc<OnceLock<CancelError>>,
+}
+
+impl Cancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self, reason: CancelError) {
+        let _ = self.reason.set(reason);
+        self.token.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    // Err with the reason once cancelled, for checks before starting something that can't be interrupted
+    pub fn check(&self) -> Result<()> {
+        match self.reason.get() {
+            Some(reason) if self.is_cancelled() => Err((*reason).into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn cancelled(&self) -> CancelError {
+        self.token.cancelled().await;
+        self.reason.get().copied().unwrap_or(CancelError::Interrupted)
+    }
+
+    // cancelled and the grace period is over, whatever is still running gets dropped
+    pub async fn abandoned(&self) -> CancelError {
+        let reason = self.cancelled().await;
+        tokio::time::sleep(CANCEL_GRACE).await;
+        reason
+    }
+
+    // work until it finishes or the run is cancelled, dropping the future cancels what it had in flight
+    pub async fn run<T>(&self, work: impl Future<Output = Result<T>>) -> Result<T> {
+        tokio::select! {
+            biased;
+            reason = self.cancelled() => Err(reason.into()),
+            result = work => result,
+        }
+    }
+
+    // cancel on the first ctrl-c or once the timeout runs out, a second ctrl-c exits right away
+    pub fn watch(&self, timeout: Option<Duration>) {
+        let cancellation = self.clone();
+        tokio::spawn(async move {
+            let deadline = async {
+                match timeout {
+                    Some(timeout) => tokio::time::sleep(timeout).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => cancellation.cancel(CancelError::Interrupted),
+                _ = deadline => cancellation.cancel(CancelError::TimedOut { seconds: timeout.unwrap_or_default().as_secs() }),
+            }
+
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        });
+    }
+}
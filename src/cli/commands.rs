@@ -1,4 +1,24 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::net::SocketAddr;
+use crate::adapters::ApiUrls;
+use crate::adapters::http_client::{DEFAULT_MAX_RESPONSE_BYTES, DEFAULT_POOL_IDLE_TIMEOUT, DEFAULT_RETRIES};
+use crate::adapters::odds_api::{DEFAULT_ODDS_REGIONS, ODDS_API_URL};
+use crate::analysis::{CostBasis, Outcome, ProbabilityModel, SmartMoney};
+use crate::analysis::compare::WHALE_MIN_CAPITAL;
+use crate::analysis::crypto::CRYPTO_VOL_DAYS;
+use crate::analysis::funding::FUNDING_TOP_N;
+use crate::analysis::insider::INSIDER_MIN_SCORE;
+use crate::analysis::line_parity::DEFAULT_PARITY_THRESHOLD;
+use crate::analysis::liquidity::LIQUIDITY_SHIFT;
+use crate::analysis::order_flow::DEFAULT_OFI_WINDOW_HOURS;
+use crate::analysis::postmortem::POSTMORTEM_CHECKPOINTS;
+use crate::analysis::vwap::DEFAULT_VWAP_WINDOWS;
+use crate::data_sources::QueryBackend;
+use crate::paper::DEFAULT_PAPER_CASH;
+use crate::cli::format::{DisplayTz, PriceFormat};
+use crate::cli::output::{check_report_version, REPORT_VERSION};
+use crate::paths;
+use crate::workers::DEFAULT_CONCURRENCY;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -6,9 +26,1006 @@ use clap::Parser;
     version = "0.1.0",
     about = "explore more into polymarket"
 )]
+pub struct Cli {
+    // where market, trader and position data comes from
+    #[arg(long, value_enum, default_value_t = Source::Live, global = true)]
+    pub source: Source,
 
-pub struct CLI {
-    // gets slug
-    #[arg(short, long)]
-    pub market_slug: String,
+    // text for people, json for scripts (errors go to stderr as json, analyze prints its sections as json lines)
+    // arrow streams analyze's result tables as arrow ipc, the same tables --export writes
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    pub output: OutputFormat,
+
+    // processed parquet dump used by the live source, defaults to processed_data in the platform data dir
+    #[arg(long, env = "POLYMARKET_EXPLORER_DATA", default_value = paths::default_data_dir(), global = true)]
+    pub data_dir: String,
+
+    // engine for heavy local db aggregations
+    #[arg(long, value_enum, default_value_t = QueryBackend::Polars, global = true)]
+    pub backend: QueryBackend,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    #[command(flatten)]
+    pub api: ApiUrlArgs,
+
+    #[command(flatten)]
+    pub odds: OddsArgs,
+
+    #[command(flatten)]
+    pub smart_money: SmartMoneyArgs,
+
+    // show polymarket usernames instead of raw addresses, live source only
+    #[arg(long, global = true)]
+    pub resolve_names: bool,
+
+    // ethereum mainnet json-rpc url for ens names, implies --resolve-names
+    #[arg(long, env = "POLYMARKET_EXPLORER_ENS_RPC", global = true)]
+    pub ens_rpc: Option<String>,
+
+    // polygon json-rpc url, dates transactions the local dump only has block numbers for
+    #[arg(long, env = "POLYMARKET_EXPLORER_POLYGON_RPC", global = true)]
+    pub polygon_rpc: Option<String>,
+
+    // dollar amounts as $1.23M instead of $1,234,567.89
+    #[arg(long, global = true)]
+    pub compact_numbers: bool,
+
+    // share prices as 0.67, 67¢ or 67%
+    #[arg(long, value_enum, default_value_t = PriceFormat::Decimal, global = true)]
+    pub price_format: PriceFormat,
+
+    // zone for dates and times, local, a name like Europe/Berlin, UTC or an offset like +05:30
+    #[arg(long, env = "POLYMARKET_EXPLORER_TZ", default_value = "local", global = true)]
+    pub tz: DisplayTz,
+
+    // number separators of a locale like de_DE, taken from LC_ALL, LC_NUMERIC or LANG when not given
+    #[arg(long, env = "POLYMARKET_EXPLORER_LOCALE", global = true)]
+    pub locale: Option<String>,
+
+    // print api call statistics when the run finishes
+    #[arg(long, global = true)]
+    pub stats: bool,
+
+    // same input, same output: the clock is pinned to 2026-01-01 utc and times print in utc, for golden file tests
+    #[arg(long, global = true)]
+    pub deterministic: bool,
+
+    // seed for the mock source's generated data, implies --deterministic
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    // give up on the whole command after this many seconds, like ctrl-c whatever finished is still reported
+    #[arg(long, value_name = "SECONDS", global = true)]
+    pub timeout: Option<u64>,
+
+    // markets analyzed or fetched at once by analyze --all-markets, compare and monitor
+    #[arg(long, default_value_t = DEFAULT_CONCURRENCY, value_parser = concurrency, global = true)]
+    pub concurrency: usize,
+
+    #[command(subcommand)]
+    pub command: CliCommand,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    // gamma api plus the local parquet db
+    Live,
+    // generated offline data, same every run
+    Mock,
+}
+
+// settings for the http client used by the api sources
+#[derive(Args, Debug, Clone)]
+pub struct HttpArgs {
+    // seconds allowed to connect
+    #[arg(long, default_value_t = 10, global = true)]
+    pub connect_timeout: u64,
+
+    // seconds allowed for a whole request
+    #[arg(long, default_value_t = 30, global = true)]
+    pub request_timeout: u64,
+
+    // seconds an idle connection is kept for the next request, 0 opens a new one every time
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_POOL_IDLE_TIMEOUT.as_secs(), global = true)]
+    pub pool_idle_timeout: u64,
+
+    // give up on a response bigger than this many megabytes once decompressed
+    #[arg(long, value_name = "MB", default_value_t = DEFAULT_MAX_RESPONSE_BYTES / (1024 * 1024), global = true)]
+    pub max_response_mb: u64,
+
+    // times a GET is tried again after a timeout, a dropped connection, a 429 or a 5xx
+    #[arg(long, default_value_t = DEFAULT_RETRIES, global = true)]
+    pub retries: u32,
+
+    // never negotiate http/2, for proxies that break it
+    #[arg(long, global = true)]
+    pub http1_only: bool,
+
+    // route requests through a proxy (http, https or socks5 url)
+    #[arg(long, global = true)]
+    pub proxy: Option<String>,
+
+    // override the User-Agent header
+    #[arg(long, global = true)]
+    pub user_agent: Option<String>,
+
+    // skip tls certificate checks, only for debugging proxies
+    #[arg(long, global = true)]
+    pub insecure: bool,
+
+    // refuse tls versions older than this
+    #[arg(long, value_enum, global = true)]
+    pub min_tls: Option<TlsVersion>,
+
+    // save every raw api response of the run to this dir, request bodies included
+    #[arg(long, value_name = "DIR", global = true, conflicts_with = "replay_raw")]
+    pub dump_raw: Option<String>,
+
+    // answer api requests from a --dump-raw dir instead of the network
+    #[arg(long, value_name = "DIR", global = true)]
+    pub replay_raw: Option<String>,
+}
+
+// overrides for the [api] section of config.toml, for proxies, mirrors or a mock server
+#[derive(Args, Debug, Clone)]
+pub struct ApiUrlArgs {
+    // gamma api base url, markets, events and profiles
+    #[arg(long, env = "POLYMARKET_EXPLORER_GAMMA_URL", global = true)]
+    pub gamma_url: Option<String>,
+
+    // clob base url, order books and trading
+    #[arg(long, env = "POLYMARKET_EXPLORER_CLOB_URL", global = true)]
+    pub clob_url: Option<String>,
+
+    // data api base url, trades and wallet activity
+    #[arg(long, env = "POLYMARKET_EXPLORER_DATA_URL", global = true)]
+    pub data_url: Option<String>,
+}
+
+impl ApiUrlArgs {
+    // flags and env vars win over the config file, validate the result before use
+    pub fn apply(&self, base: ApiUrls) -> ApiUrls {
+        ApiUrls {
+            gamma: self.gamma_url.clone().unwrap_or(base.gamma),
+            clob: self.clob_url.clone().unwrap_or(base.clob),
+            data: self.data_url.clone().unwrap_or(base.data),
+        }
+    }
+}
+
+// the bookmaker odds api parity reads, off unless a key is given
+#[derive(Args, Debug, Clone)]
+pub struct OddsArgs {
+    // odds api key, the value isn't shown in --help
+    #[arg(long, env = "POLYMARKET_EXPLORER_ODDS_API_KEY", hide_env_values = true, global = true)]
+    pub odds_api_key: Option<String>,
+
+    // odds api base url, anything serving the-odds-api v4 shape
+    #[arg(long, env = "POLYMARKET_EXPLORER_ODDS_API_URL", default_value = ODDS_API_URL, global = true)]
+    pub odds_api_url: String,
+
+    // bookmaker regions asked for, comma separated
+    #[arg(long, default_value = DEFAULT_ODDS_REGIONS, global = true)]
+    pub odds_regions: String,
+}
+
+// overrides for the [smart_money] section of config.toml
+#[derive(Args, Debug, Clone)]
+pub struct SmartMoneyArgs {
+    // lowest share of resolved markets won, 0 to 1
+    #[arg(long, global = true)]
+    pub smart_min_accuracy: Option<f64>,
+
+    // fewest resolved markets before a trader's accuracy counts
+    #[arg(long, global = true)]
+    pub smart_min_resolved: Option<u32>,
+
+    // lowest lifetime roi, e.g. 0.1 for 10%
+    #[arg(long, global = true)]
+    pub smart_min_roi: Option<f64>,
+
+    // least usdc invested over the wallet's lifetime
+    #[arg(long, global = true)]
+    pub smart_min_invested: Option<f64>,
+
+    // most usdc a trader's cumulative pnl may have fallen from its peak
+    #[arg(long, global = true)]
+    pub smart_max_drawdown: Option<f64>,
+
+    // longest run of lost markets allowed
+    #[arg(long, global = true)]
+    pub smart_max_loss_streak: Option<u32>,
+
+    // highest standard deviation of per market roi, e.g. 0.8
+    #[arg(long, global = true)]
+    pub smart_max_volatility: Option<f64>,
+
+    // how smart positions become a probability, every model is still shown next to it
+    #[arg(long, global = true, value_enum)]
+    pub model: Option<ProbabilityModel>,
+}
+
+impl SmartMoneyArgs {
+    // flags win over the config file field by field
+    pub fn apply(&self, base: SmartMoney) -> SmartMoney {
+        SmartMoney {
+            min_accuracy: self.smart_min_accuracy.unwrap_or(base.min_accuracy),
+            min_resolved: self.smart_min_resolved.unwrap_or(base.min_resolved),
+            min_roi: self.smart_min_roi.or(base.min_roi),
+            min_invested: self.smart_min_invested.unwrap_or(base.min_invested),
+            max_drawdown: self.smart_max_drawdown.or(base.max_drawdown),
+            max_loss_streak: self.smart_max_loss_streak.or(base.max_loss_streak),
+            max_volatility: self.smart_max_volatility.or(base.max_volatility),
+            model: self.model.unwrap_or(base.model),
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsVersion {
+    #[value(name = "1.2")]
+    Tls12,
+    #[value(name = "1.3")]
+    Tls13,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Arrow,
+}
+
+impl OutputFormat {
+    // json and arrow are read by programs, anything else printed on stdout would break them
+    pub fn is_machine_readable(self) -> bool {
+        self != OutputFormat::Text
+    }
+}
+
+// json documents `schema` describes
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaTarget {
+    // body of the serve command's /markets/{slug}/analysis
+    Report,
+    // monitor and grpc alert events
+    Alert,
+    // per market snapshot sent every monitor and grpc poll
+    Snapshot,
+    // error on stderr with --output json
+    Error,
+    // every one of the above in a single object keyed by name
+    All,
+}
+
+// what the cli was asked to run, local bookkeeping never opens config.toml or a source
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    #[command(flatten)]
+    Source(Command),
+
+    #[command(flatten)]
+    Local(LocalCommand),
+}
+
+// commands run against a market source and a db source
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(about = "analyze a market group by its slug")]
+    Analyze {
+        // gets slug
+        #[arg(short, long)]
+        market_slug: String,
+
+        // how sells are matched to buys when rebuilding trader pnl
+        #[arg(long, value_enum, default_value_t = CostBasis::Fifo)]
+        cost_basis: CostBasis,
+
+        // hours each vwap looks back, comma separated
+        #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_VWAP_WINDOWS)]
+        vwap_windows: Vec<u32>,
+
+        // hours in each order flow window, the last few windows are compared
+        #[arg(long, default_value_t = DEFAULT_OFI_WINDOW_HOURS)]
+        ofi_window: u32,
+
+        // also write the market, positions, traders and metrics as tables, e.g. --export parquet ./run
+        #[arg(long, num_args = 2, value_names = ["FORMAT", "DIR"])]
+        export: Option<Vec<String>>,
+
+        // stop at the first failed fetch instead of marking the sections built on it as failed
+        #[arg(long)]
+        fail_fast: bool,
+
+        // analyze every market of the group instead of only the first, --concurrency at a time
+        #[arg(long)]
+        all_markets: bool,
+
+        // also write the report as a standalone html page
+        #[arg(long, value_name = "FILE")]
+        html: Option<String>,
+
+        // also POST every section as json to this url once the analysis is done, on top of [[webhooks]] in config.toml
+        #[arg(long, value_name = "URL")]
+        webhook: Option<String>,
+
+        // sign the --webhook body with hmac-sha256 using this secret
+        #[arg(long, env = "POLYMARKET_EXPLORER_WEBHOOK_SECRET", hide_env_values = true, requires = "webhook")]
+        webhook_secret: Option<String>,
+
+        // run the *.wasm analyzer plugins in this dir instead of the plugins dir (builds with the plugins feature)
+        #[arg(long, value_name = "DIR")]
+        plugins: Option<String>,
+
+        // also run this rhai script over the market's data and add what it returns as a section, repeatable
+        #[arg(long = "script", value_name = "FILE")]
+        scripts: Vec<String>,
+
+        // also read the event's latest comments for a crowd chatter section
+        #[arg(long)]
+        chatter: bool,
+
+        // outside model probabilities to set against the price and smart money, a csv file or url
+        #[arg(long, value_name = "FILE_OR_URL")]
+        forecasts: Option<String>,
+    },
+
+    #[command(about = "analyze several market groups side by side")]
+    Compare {
+        // event slugs to compare, defaults to the watchlist
+        market_slugs: Vec<String>,
+    },
+
+    #[command(about = "open markets with the biggest yes price moves over the last day")]
+    Movers {
+        // skip markets that traded less than this in the last 24h (USDC)
+        #[arg(long, default_value_t = 10_000.0)]
+        min_volume: f64,
+
+        // skip markets with less liquidity on the book (USDC)
+        #[arg(long, default_value_t = 1_000.0)]
+        min_liquidity: f64,
+
+        // rows to print
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        // also list markets gamma has archived
+        #[arg(long)]
+        include_archived: bool,
+    },
+
+    #[command(about = "markets listed in the last hours, flagging early large positions")]
+    NewMarkets {
+        // how far back to look
+        #[arg(long, default_value_t = 24)]
+        hours: u32,
+
+        // cost basis that counts as a large early position (USDC)
+        #[arg(long, default_value_t = WHALE_MIN_CAPITAL)]
+        min_position: f64,
+
+        // rows to print
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        // also list markets gamma has archived
+        #[arg(long)]
+        include_archived: bool,
+    },
+
+    #[command(about = "markets resolving soon, ranked by how far the price is from smart money")]
+    ClosingSoon {
+        // only markets scheduled to resolve within this many hours
+        #[arg(long, default_value_t = 72)]
+        hours: u32,
+
+        // rows to print
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        // also list markets gamma has archived
+        #[arg(long)]
+        include_archived: bool,
+    },
+
+    #[command(about = "dry run a buy order against the live book, nothing is placed")]
+    PlanOrder {
+        #[arg(long)]
+        market_slug: String,
+
+        // outcome token to buy
+        #[arg(long, value_enum)]
+        side: Outcome,
+
+        // shares to buy
+        #[arg(long)]
+        size: f64,
+
+        // highest price per share to pay
+        #[arg(long)]
+        limit_price: f64,
+    },
+
+    #[command(about = "simulated trading against the live book, fills go to a local ledger")]
+    Paper {
+        #[command(subcommand)]
+        action: PaperAction,
+    },
+
+    #[cfg(feature = "trading")]
+    #[command(about = "place or cancel real orders on the clob, credentials come from POLYMARKET_* env vars")]
+    Trade {
+        #[command(subcommand)]
+        action: TradeAction,
+
+        // orders spend real money, nothing is sent without this
+        #[arg(long = "i-understand-the-risks", global = true)]
+        i_understand_the_risks: bool,
+    },
+
+    #[command(about = "poll markets on an interval and print their key numbers")]
+    Monitor {
+        // event slugs to watch, defaults to the watchlist
+        market_slugs: Vec<String>,
+
+        // seconds between polls
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+
+        // stop after this many polls, runs until interrupted by default
+        #[arg(long)]
+        count: Option<u32>,
+
+        // also serve prometheus metrics here, e.g. 127.0.0.1:9898
+        #[arg(long)]
+        metrics_addr: Option<SocketAddr>,
+
+        // archive each market's order book every this many polls, for book-history, off by default
+        #[arg(long, value_name = "POLLS")]
+        archive_books: Option<u32>,
+
+        // watch the watchlisted traders instead, reporting every position they open or close in any market
+        #[arg(long, conflicts_with_all = ["market_slugs", "metrics_addr", "archive_books"])]
+        traders: bool,
+    },
+
+    #[command(about = "replay local history following smart money and report hypothetical returns")]
+    Backtest {
+        // follow traders at or above this accuracy, defaults to the smart money definition
+        #[arg(long)]
+        min_accuracy: Option<f64>,
+
+        // ignore traders with fewer resolved markets than this, defaults to the smart money definition
+        #[arg(long)]
+        min_resolved_markets: Option<u32>,
+
+        // copy an entry only if we can get filled within this many days
+        #[arg(long, default_value_t = 1.0)]
+        max_entry_delay_days: f64,
+
+        // usdc staked on every copied trade
+        #[arg(long, default_value_t = 100.0)]
+        stake: f64,
+    },
+
+    #[command(about = "score past smart money probabilities against how markets resolved")]
+    Calibration {
+        // rebuild each prediction from trades this many days before resolution
+        #[arg(long, default_value_t = 1.0)]
+        days_before: f64,
+
+        // probability buckets in the calibration curve
+        #[arg(long, default_value_t = 10)]
+        buckets: usize,
+    },
+
+    #[command(about = "rank traders by the lower bound on their accuracy, optionally within one category")]
+    Leaderboard {
+        // only count markets with this tag, e.g. politics
+        #[arg(long)]
+        category: Option<String>,
+
+        // traders to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    #[command(about = "one wallet's markets and results grouped by category")]
+    Portfolio {
+        // wallet address or wallet group name, grouped wallets are read together
+        #[arg(long)]
+        trader: String,
+    },
+
+    #[command(about = "show when trades happen on a market by weekday and hour in the --tz zone")]
+    Heatmap {
+        // gets slug
+        #[arg(short, long)]
+        market_slug: String,
+
+        // how far back to look
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+
+        // only count smart money trades
+        #[arg(long)]
+        smart_only: bool,
+
+        // implies --smart-only, overrides the smart money accuracy floor
+        #[arg(long)]
+        min_accuracy: Option<f64>,
+
+        // implies --smart-only, overrides the smart money resolved markets floor
+        #[arg(long)]
+        min_resolved_markets: Option<u32>,
+    },
+
+    #[command(about = "list a market's largest recent trades with each trader's record and what the trade did to their position")]
+    BigTrades {
+        // event slug
+        market_slug: String,
+
+        // smallest trade shown, in usdc
+        #[arg(long, default_value_t = 1_000.0)]
+        min_usdc: f64,
+
+        // how far back to look
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+
+        // most trades listed
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+
+    #[command(about = "replay a market's trade tape from the local db as it happened")]
+    Replay {
+        // event slug
+        market_slug: String,
+
+        // how much faster than real time, 10x or 10, pauses never run past a couple of seconds
+        #[arg(long, default_value = "10x", value_parser = replay_speed)]
+        speed: f64,
+
+        // first buys of at least this much usdc are called out, smart money entries always are
+        #[arg(long, default_value_t = 1_000.0)]
+        min_usdc: f64,
+    },
+
+    #[command(about = "flag holders that look like insiders, new wallets with large one sided late positions in niche markets")]
+    Insiders {
+        // event slug
+        market_slug: String,
+
+        // lowest score flagged, 0 to 1
+        #[arg(long, default_value_t = INSIDER_MIN_SCORE)]
+        min_score: f64,
+
+        // most holders listed
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    #[command(about = "compare a market group's prices with bookmaker odds on the same event, needs --odds-api-key")]
+    Parity {
+        // event slug
+        market_slug: String,
+
+        // the odds api's sport or competition key, e.g. basketball_nba
+        #[arg(long)]
+        sport: String,
+
+        // flag outcomes where polymarket and the bookmakers' consensus differ by more than this
+        #[arg(long, default_value_t = DEFAULT_PARITY_THRESHOLD)]
+        threshold: f64,
+    },
+
+    #[command(about = "price a crypto event's strike markets like options off the spot price and realized volatility")]
+    Crypto {
+        // event slug, e.g. a "bitcoin above ___ on ..." group
+        market_slug: String,
+
+        // days of daily closes the realized volatility is measured over
+        #[arg(long, default_value_t = CRYPTO_VOL_DAYS)]
+        vol_days: u32,
+
+        // annualized volatility to use instead of the realized one, e.g. 0.55
+        #[arg(long)]
+        volatility: Option<f64>,
+    },
+
+    #[command(about = "trace where a market's top holders got their usdc to flag wallets funded together, needs --polygon-rpc")]
+    Funding {
+        // event slug
+        market_slug: String,
+
+        // largest holders traced
+        #[arg(long, default_value_t = FUNDING_TOP_N)]
+        top: usize,
+
+        // how far before each holder's first trade to look for deposits
+        #[arg(long, default_value_t = 7)]
+        lookback_days: u32,
+    },
+
+    #[command(about = "say which traders moved their positions in a market over the last days and how")]
+    PositionChanges {
+        // event slug
+        market_slug: String,
+
+        // how far back the before side is taken
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+
+        // smallest net move in shares shown
+        #[arg(long, default_value_t = 100.0)]
+        min_shares: f64,
+
+        // most traders listed
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    #[command(about = "break down a resolved market: who won and lost, their entry prices, payouts and how smart money read it")]
+    Postmortem {
+        // event slug
+        market_slug: String,
+
+        // points the smart money signal is rebuilt at before resolution
+        #[arg(long, default_value_t = POSTMORTEM_CHECKPOINTS)]
+        checkpoints: usize,
+    },
+
+    #[command(about = "serve the analysis as a json rest api")]
+    Serve {
+        // address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: SocketAddr,
+
+        // report format version served when a request doesn't ask for one
+        #[arg(long, default_value_t = REPORT_VERSION, value_parser = report_version)]
+        report_version: u32,
+    },
+
+    #[cfg(feature = "grpc")]
+    #[command(about = "poll markets and stream snapshots and alerts over grpc")]
+    Grpc {
+        // event slugs to poll, defaults to the watchlist
+        market_slugs: Vec<String>,
+
+        // address to listen on
+        #[arg(long, default_value = "127.0.0.1:50051")]
+        addr: SocketAddr,
+
+        // seconds between polls
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+
+    #[command(about = "scan the local db for rows that would skew the analysis")]
+    AuditDb {
+        // offending rows listed per check
+        #[arg(long, default_value_t = 5)]
+        examples: usize,
+    },
+
+    #[command(about = "merge the local db's part files into one sorted file per partition")]
+    Compact {
+        // rows per parquet row group in the rewritten files
+        #[arg(long, default_value_t = 131_072)]
+        row_group_size: usize,
+    },
+
+    #[command(about = "pull data from polymarket into the local db")]
+    Ingest {
+        // ignore the saved checkpoints and pull everything again
+        #[arg(long, global = true)]
+        from_scratch: bool,
+
+        #[command(subcommand)]
+        target: IngestTarget,
+    },
+
+    #[command(about = "recompute derived tables of the local db")]
+    Stats {
+        #[command(subcommand)]
+        action: StatsAction,
+    },
+}
+
+// commands that only read or write local files
+#[derive(Subcommand, Debug)]
+pub enum LocalCommand {
+    #[command(about = "show when liquidity entered or left a market, from what monitor recorded")]
+    LiquidityHistory {
+        // event slug, as monitor was given it
+        market_slug: String,
+
+        // relative liquidity change that counts as a move
+        #[arg(long, default_value_t = LIQUIDITY_SHIFT)]
+        threshold: f64,
+
+        // most moves listed, newest first
+        #[arg(long, default_value_t = 30)]
+        limit: usize,
+    },
+
+    #[command(about = "show spread and depth over time from the order books monitor archived")]
+    BookHistory {
+        // event slug, as monitor was given it
+        market_slug: String,
+
+        // most snapshots listed, newest first
+        #[arg(long, default_value_t = 30)]
+        limit: usize,
+    },
+
+    #[command(about = "manage watched markets and traders")]
+    Watchlist {
+        #[command(subcommand)]
+        action: WatchlistAction,
+    },
+
+    #[command(about = "label wallet addresses, labels are shown wherever the address is printed")]
+    Label {
+        #[command(subcommand)]
+        action: LabelAction,
+    },
+
+    #[command(about = "print a shell completion script, watched slugs are offered for market slug arguments")]
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    #[command(about = "print the json schema of a json output, for validating it or generating clients")]
+    Schema {
+        #[arg(value_enum)]
+        target: SchemaTarget,
+
+        // describe this version of the report
+        #[arg(long, default_value_t = REPORT_VERSION, value_parser = report_version)]
+        report_version: u32,
+    },
+}
+
+impl CliCommand {
+    pub fn streams_arrow(&self) -> bool {
+        matches!(self, CliCommand::Source(command) if command.streams_arrow())
+    }
+}
+
+impl Command {
+    // analyze is the only command with result tables, --output arrow means nothing to the rest
+    pub fn streams_arrow(&self) -> bool {
+        matches!(self, Command::Analyze { .. })
+    }
+
+    // commands that only need gamma, or still print something useful from it, when the local db is missing
+    pub fn runs_without_local_db(&self) -> bool {
+        matches!(self, Command::Analyze { .. } | Command::Movers { .. } | Command::NewMarkets { .. } | Command::ClosingSoon { .. } | Command::PlanOrder { .. } | Command::Paper { .. } | Command::Parity { .. } | Command::Crypto { .. })
+            || matches!(self, Command::Ingest { target: IngestTarget::Trades { .. } | IngestTarget::Rewards { .. } | IngestTarget::Ctf { .. }, .. })
+            // rebuilding is how a missing traders table gets made
+            || matches!(self, Command::Stats { action: StatsAction::Rebuild { .. } })
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WatchlistAction {
+    #[command(about = "watch market slugs and trader addresses")]
+    Add {
+        // market slug to watch, repeatable
+        #[arg(long = "market")]
+        markets: Vec<String>,
+
+        // trader address to watch, repeatable
+        #[arg(long = "trader")]
+        traders: Vec<String>,
+    },
+
+    #[command(about = "stop watching market slugs and trader addresses")]
+    Remove {
+        #[arg(long = "market")]
+        markets: Vec<String>,
+
+        #[arg(long = "trader")]
+        traders: Vec<String>,
+    },
+
+    #[command(about = "show everything being watched")]
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PaperAction {
+    #[command(about = "buy at the current asks up to the limit price, whatever doesn't fill is dropped")]
+    Buy {
+        #[arg(long)]
+        market_slug: String,
+
+        // outcome token to buy
+        #[arg(long, value_enum)]
+        side: Outcome,
+
+        // shares to buy
+        #[arg(long)]
+        size: f64,
+
+        // highest price per share to pay
+        #[arg(long)]
+        limit_price: f64,
+    },
+
+    #[command(about = "sell held shares into the current bids down to the min price")]
+    Sell {
+        #[arg(long)]
+        market_slug: String,
+
+        // outcome token to sell
+        #[arg(long, value_enum)]
+        side: Outcome,
+
+        // shares to sell, at most what the paper account holds
+        #[arg(long)]
+        size: f64,
+
+        // lowest price per share to accept
+        #[arg(long, default_value_t = 0.01)]
+        min_price: f64,
+    },
+
+    #[command(about = "value the paper portfolio at current prices and record a snapshot")]
+    Status,
+
+    #[command(about = "wipe the paper account and start over")]
+    Reset {
+        // usdc to start with
+        #[arg(long, default_value_t = DEFAULT_PAPER_CASH)]
+        cash: f64,
+    },
+}
+
+#[cfg(feature = "trading")]
+#[derive(Subcommand, Debug)]
+pub enum TradeAction {
+    #[command(about = "buy shares with a good till cancelled limit order, shows the dry run plan first")]
+    Place {
+        #[arg(long)]
+        market_slug: String,
+
+        // outcome token to buy
+        #[arg(long, value_enum)]
+        side: Outcome,
+
+        // shares to buy
+        #[arg(long)]
+        size: f64,
+
+        // highest price per share to pay
+        #[arg(long)]
+        limit_price: f64,
+    },
+
+    #[command(about = "cancel an open order")]
+    Cancel {
+        #[arg(long)]
+        order_id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LabelAction {
+    #[command(about = "label an address, replacing any label it already has")]
+    Set {
+        #[arg(long)]
+        address: String,
+
+        // free text, e.g. "known sharp" or an ens name
+        #[arg(long)]
+        label: String,
+    },
+
+    #[command(about = "forget the label of an address")]
+    Remove {
+        #[arg(long)]
+        address: String,
+    },
+
+    #[command(about = "group wallets one person controls so they're read as a single trader")]
+    Group {
+        #[arg(long)]
+        name: String,
+
+        // repeat for each wallet, wallets already in another group move to this one
+        #[arg(long, required = true)]
+        address: Vec<String>,
+    },
+
+    #[command(about = "take an address out of its wallet group")]
+    Ungroup {
+        #[arg(long)]
+        address: String,
+    },
+
+    #[command(about = "show every labeled address and wallet group")]
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IngestTarget {
+    #[command(about = "backfill market resolutions from gamma, placed at their close with --polygon-rpc, and recompute trader stats")]
+    Resolutions {
+        // condition ids sent per gamma request
+        #[arg(long, default_value_t = 50)]
+        batch_size: usize,
+    },
+
+    #[command(about = "fetch gamma tags for every market in the local transactions")]
+    Tags {
+        // condition ids sent per gamma request
+        #[arg(long, default_value_t = 50)]
+        batch_size: usize,
+    },
+
+    #[command(about = "pull recent trades from the data api into the local transactions table")]
+    Trades {
+        // event slugs to pull, defaults to the watchlist
+        market_slugs: Vec<String>,
+
+        // how far back to pull
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+
+    #[command(about = "pull liquidity reward payouts per wallet from the data api into the local rewards table")]
+    Rewards {
+        // wallets to pull, defaults to the traders passing the smart money filter
+        addresses: Vec<String>,
+    },
+
+    #[command(about = "read splits, merges and redemptions from the chain into the local transactions table")]
+    Ctf {
+        // event slugs to scan, defaults to the watchlist
+        market_slugs: Vec<String>,
+
+        // how far back to scan markets not scanned before
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatsAction {
+    #[command(about = "recompute traders.parquet from the local transactions and resolutions alone")]
+    Rebuild {
+        // fold every transaction again instead of only the ones since the last rebuild
+        #[arg(long)]
+        full: bool,
+    },
+}
+
+// --report-version, only versions the output module can still produce
+fn report_version(text: &str) -> Result<u32, String> {
+    text.parse::<u32>()
+        .map_err(|_| format!("'{}' is not a report version", text))
+        .and_then(check_report_version)
+}
+
+// --speed, a positive multiple with or without the x
+fn replay_speed(text: &str) -> Result<f64, String> {
+    match text.trim_end_matches(['x', 'X']).parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("'{}' is not a speed like 10x", text)),
+    }
+}
+
+// --concurrency, at least one market at a time
+fn concurrency(text: &str) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(0) | Err(_) => Err(format!("'{}' is not a positive number", text)),
+        Ok(limit) => Ok(limit),
+    }
 }
@@ -0,0 +1,364 @@
+use crate::adapters::{webhook, HttpClient, WebhookTarget};
+use crate::address_book::AddressBook;
+use crate::analysis::{Chatter, CollateralMix, CollateralRates, Concentration, CostBasis, FeeModel, GroupCoherence, ImpliedReturns, OpenInterest, OrderFlowReport, RewardSettings, SmartMoney, TraderPnl, VwapReport, WalletAgeBreakdown};
+use crate::cli::export::AnalysisExport;
+use crate::cli::output;
+use crate::clock;
+use crate::error::AppError;
+use crate::standard_data::models::{ExternalForecast, Market, MarketGroup, Position, Trader};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::PathBuf;
+
+// why a section is missing from the report, the run went on without it
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl From<&AppError> for SectionError {
+    fn from(e: &AppError) -> Self {
+        Self { code: e.code(), message: e.to_string() }
+    }
+}
+
+// what analyze produces, in the order it's emitted
+// analysis only emits these, how they're shown is up to whoever subscribed
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AnalysisEvent<'a> {
+    Started {
+        slug: &'a str,
+        smart_money: &'a SmartMoney,
+    },
+    Group {
+        group: &'a MarketGroup,
+    },
+    Coherence {
+        coherence: &'a GroupCoherence,
+    },
+    // only with --chatter, yes_price is the primary market's to hold the crowd against
+    Chatter {
+        chatter: &'a Chatter,
+        yes_price: Option<f64>,
+    },
+    // number counts from 1, total is how many markets this run analyzes
+    Market {
+        market: &'a Market,
+        number: usize,
+        total: usize,
+        // None when the local db has neither positions nor transactions
+        open_interest: Option<&'a OpenInterest>,
+    },
+    // the market's rows settled in more than one collateral, usd figures converted them at these rates
+    MixedCollateral {
+        mix: &'a CollateralMix,
+        rates: CollateralRates,
+    },
+    Positions {
+        count: usize,
+    },
+    Traders {
+        count: usize,
+        // traders switched to their record in the market's tags, None for untagged markets
+        weighted: Option<usize>,
+        tags: &'a [String],
+    },
+    Samples {
+        position: Option<&'a Position>,
+        trader: Option<&'a Trader>,
+        // labels and resolved names for display, not part of the data
+        #[serde(skip)]
+        book: &'a AddressBook,
+    },
+    WalletAge {
+        breakdown: &'a WalletAgeBreakdown,
+    },
+    Concentration {
+        concentration: &'a Concentration,
+    },
+    ImpliedReturns {
+        // None when no smart trader holds the market
+        implied: Option<&'a ImpliedReturns>,
+        smart_money: &'a SmartMoney,
+        fees: &'a FeeModel,
+    },
+    // only with --forecasts, smart_money is None when no smart trader holds the market
+    Forecasts {
+        market_price: Option<f64>,
+        smart_money: Option<f64>,
+        forecasts: &'a [ExternalForecast],
+    },
+    Pnl {
+        pnls: &'a [TraderPnl],
+        cost_basis: CostBasis,
+        // whether the ranking counts liquidity rewards, each pnl carries them either way
+        rewards: RewardSettings,
+        yes_mark: f64,
+        no_mark: f64,
+        #[serde(skip)]
+        book: &'a AddressBook,
+    },
+    Vwap {
+        report: &'a VwapReport,
+        yes_mark: f64,
+        no_mark: f64,
+    },
+    OrderFlow {
+        report: &'a OrderFlowReport,
+    },
+    // a plugin's section, whatever json it returned
+    Analyzer {
+        name: &'a str,
+        output: &'a Value,
+    },
+    // a section the data at hand can't answer
+    Unavailable {
+        section: &'a str,
+        reason: &'a str,
+    },
+    // a section whose data couldn't be fetched, without --fail-fast the rest of the report still runs
+    Failed {
+        section: &'a str,
+        error: &'a SectionError,
+    },
+    Exported {
+        files: &'a [PathBuf],
+    },
+    NoMarkets,
+    // the run stopped early, everything emitted before this is all there is
+    Incomplete {
+        reason: &'a str,
+    },
+}
+
+// an output for analysis events, events are borrowed so anything kept past emit has to be copied out
+#[async_trait]
+pub trait AnalysisSink: Send {
+    fn emit(&mut self, event: &AnalysisEvent<'_>);
+
+    // a market's result tables once its sections are in, only sinks that ship data rather than a report look at them
+    fn tables(&mut self, _condition_id: &str, _tables: &AnalysisExport<'_>) {}
+
+    // after the last event, sinks that deliver everything at once do it here
+    async fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// fans every event out to the subscribed sinks in subscription order
+#[derive(Default)]
+pub struct AnalysisBus {
+    sinks: Vec<Box<dyn AnalysisSink>>,
+}
+
+impl AnalysisBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(mut self, sink: impl AnalysisSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    pub fn emit(&mut self, event: AnalysisEvent<'_>) {
+        for sink in &mut self.sinks {
+            sink.emit(&event);
+        }
+    }
+
+    pub fn tables(&mut self, condition_id: &str, tables: &AnalysisExport<'_>) {
+        for sink in &mut self.sinks {
+            sink.tables(condition_id, tables);
+        }
+    }
+
+    // every sink gets finished even when an earlier one failed, the first error is returned
+    pub async fn finish(mut self) -> Result<()> {
+        let mut result = Ok(());
+        for sink in &mut self.sinks {
+            let finished = sink.finish().await;
+            if result.is_ok() {
+                result = finished;
+            }
+        }
+        result
+    }
+}
+
+// the usual text report on stdout
+pub struct TerminalSink;
+
+#[async_trait]
+impl AnalysisSink for TerminalSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        match event {
+            AnalysisEvent::Started { slug, smart_money } => {
+                output::print_header(&format!("Fetching market: {}", slug));
+                output::print_smart_money(smart_money);
+            }
+            AnalysisEvent::Group { group } => {
+                output::print_market_group_info(group);
+                output::print_expiry_overview(group);
+            }
+            AnalysisEvent::Coherence { coherence } => output::print_group_coherence(coherence),
+            AnalysisEvent::Chatter { chatter, yes_price } => output::print_chatter(chatter, *yes_price),
+            AnalysisEvent::Market { market, number, total, open_interest } => {
+                match total {
+                    1 => output::print_header("ANALYZING PRIMARY MARKET"),
+                    _ => output::print_header(&format!("ANALYZING MARKET {} OF {}", number, total)),
+                }
+                output::print_market_info(market, *open_interest);
+            }
+            AnalysisEvent::MixedCollateral { mix, rates } => output::print_mixed_collateral(mix, rates),
+            AnalysisEvent::Positions { count } => output::print_position_count(*count),
+            AnalysisEvent::Traders { count, weighted, tags } => output::print_trader_count(*count, *weighted, tags),
+            AnalysisEvent::Samples { position, trader, book } => output::print_samples(*position, *trader, book),
+            AnalysisEvent::WalletAge { breakdown } => output::print_wallet_age_breakdown(breakdown),
+            AnalysisEvent::Concentration { concentration } => output::print_concentration(concentration),
+            AnalysisEvent::ImpliedReturns { implied, smart_money, fees } => output::print_implied_returns(*implied, smart_money, fees),
+            AnalysisEvent::Forecasts { market_price, smart_money, forecasts } => output::print_forecasts(*market_price, *smart_money, forecasts),
+            AnalysisEvent::Pnl { pnls, cost_basis, rewards, yes_mark, no_mark, book } => output::print_trader_pnl(pnls, *cost_basis, *rewards, *yes_mark, *no_mark, book),
+            AnalysisEvent::Vwap { report, yes_mark, no_mark } => output::print_vwap(report, *yes_mark, *no_mark),
+            AnalysisEvent::OrderFlow { report } => output::print_order_flow(report),
+            AnalysisEvent::Analyzer { name, output } => output::print_analyzer(name, output),
+            AnalysisEvent::Unavailable { section, reason } => output::print_unavailable(section, reason),
+            AnalysisEvent::Failed { section, error } => output::print_failed_section(section, &error.message),
+            AnalysisEvent::Exported { files } => output::print_export(files),
+            AnalysisEvent::NoMarkets => println!("  No markets found in this group\n"),
+            AnalysisEvent::Incomplete { reason } => output::print_incomplete(reason),
+        }
+    }
+}
+
+// one json object per event on stdout, for --output json
+pub struct JsonSink;
+
+#[async_trait]
+impl AnalysisSink for JsonSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        println!("{}", serde_json::to_string(event).unwrap_or_default());
+    }
+}
+
+// every market's tables as arrow ipc streams, for --output arrow, the report itself isn't written
+// the first failed write is kept for finish, the rest of the run still goes on
+pub struct ArrowSink {
+    out: Box<dyn Write + Send>,
+    error: Option<anyhow::Error>,
+}
+
+impl ArrowSink {
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Self { out: Box::new(out), error: None }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(std::io::stdout())
+    }
+}
+
+#[async_trait]
+impl AnalysisSink for ArrowSink {
+    fn emit(&mut self, _event: &AnalysisEvent<'_>) {}
+
+    fn tables(&mut self, condition_id: &str, tables: &AnalysisExport<'_>) {
+        if self.error.is_none() {
+            self.error = tables.stream(&mut self.out, condition_id).err();
+        }
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e.context("arrow output failed")),
+            None => Ok(()),
+        }
+    }
+}
+
+// standalone html page written once the analysis is done, a section per event
+pub struct HtmlSink {
+    path: PathBuf,
+    title: String,
+    sections: Vec<Value>,
+}
+
+impl HtmlSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), title: "analysis".to_string(), sections: Vec::new() }
+    }
+
+    fn render(&self) -> String {
+        let mut body = String::new();
+        for section in &self.sections {
+            let name = section["event"].as_str().unwrap_or_default().replace('_', " ");
+            let data = serde_json::to_string_pretty(section).unwrap_or_default();
+            body.push_str(&format!("<section>\n<h2>{}</h2>\n<pre>{}</pre>\n</section>\n", escape_html(&name), escape_html(&data)));
+        }
+        format!(
+            "<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>body {{ font-family: sans-serif; margin: 2em; }} h2 {{ text-transform: capitalize; }} pre {{ background: #f4f4f4; padding: 1em; overflow-x: auto; }}</style>\n\
+             </head>\n<body>\n<h1>{title}</h1>\n<p>Generated {generated}</p>\n{body}</body>\n</html>\n",
+            title = escape_html(&self.title),
+            generated = clock::now().to_rfc3339(),
+            body = body,
+        )
+    }
+}
+
+#[async_trait]
+impl AnalysisSink for HtmlSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        if let AnalysisEvent::Started { slug, .. } = event {
+            self.title = format!("Polymarket analysis: {}", slug);
+        }
+        self.sections.push(json!(event));
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        std::fs::write(&self.path, self.render()).with_context(|| format!("could not write {}", self.path.display()))?;
+        eprintln!("  Wrote {}", self.path.display());
+        Ok(())
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// every event in one POST once the analysis is done, {"slug": ..., "events": [...]}, signed and retried per the target
+pub struct WebhookSink {
+    client: HttpClient,
+    target: WebhookTarget,
+    slug: String,
+    events: Vec<Value>,
+}
+
+impl WebhookSink {
+    pub fn new(client: HttpClient, target: WebhookTarget) -> Self {
+        Self { client, target, slug: String::new(), events: Vec::new() }
+    }
+}
+
+#[async_trait]
+impl AnalysisSink for WebhookSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        if let AnalysisEvent::Started { slug, .. } = event {
+            self.slug = slug.to_string();
+        }
+        self.events.push(json!(event));
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        let body = json!({ "slug": self.slug, "generated_at": clock::now().timestamp(), "events": self.events });
+        webhook::deliver(&self.client, &self.target, &body)
+            .await
+            .with_context(|| format!("webhook {} failed", self.target.url))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+use crate::adapters::ParquetWriter;
+use crate::analysis::concentration::{Concentration, SideConcentration};
+use crate::analysis::implied_return::{ImpliedReturns, SideReturn};
+use crate::analysis::pnl::TraderPnl;
+use crate::analysis::vwap::{SideVwap, VwapReport};
+use crate::standard_data::models::{Market, Position, Trader};
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use polars::prelude::*;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// file format for --export
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    // arrow ipc, memory maps straight into polars or pyarrow
+    Arrow,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Arrow => "arrow",
+        }
+    }
+}
+
+// where and how an analyze run writes its tables
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub format: ExportFormat,
+    pub dir: PathBuf,
+}
+
+impl Export {
+    // from the two values of --export <FORMAT> <DIR>
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let [format, dir] = args else {
+            bail!("--export takes a format and a directory");
+        };
+        let Ok(format) = ExportFormat::from_str(format, true) else {
+            bail!("unknown export format '{}', use parquet or arrow", format);
+        };
+        Ok(Self { format, dir: PathBuf::from(dir) })
+    }
+}
+
+// everything analyze computed for one market, filled in as the sections run
+#[derive(Debug, Default)]
+pub struct AnalysisExport<'a> {
+    pub markets: &'a [Market],
+    pub positions: Vec<Position>,
+    pub traders: Vec<Trader>,
+    pub pnls: Vec<TraderPnl>,
+    pub vwap: Option<VwapReport>,
+    pub implied: Option<ImpliedReturns>,
+    pub concentration: Option<Concentration>,
+}
+
+impl AnalysisExport<'_> {
+    // every table by name, sections without data still get theirs, just empty
+    fn frames(&self, condition_id: &str) -> Result<[(&'static str, DataFrame); 6]> {
+        Ok([
+            ("markets", markets_frame(self.markets)?),
+            ("positions", positions_frame(&self.positions)?),
+            ("traders", traders_frame(&self.traders)?),
+            ("trader_pnl", pnl_frame(&self.pnls)?),
+            ("vwap", vwap_frame(self.vwap.as_ref())?),
+            ("metrics", metrics_frame(condition_id, self.implied.as_ref(), self.concentration.as_ref())?),
+        ])
+    }
+
+    // one file per table, returns the paths written
+    pub fn write(&self, export: &Export, condition_id: &str) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(&export.dir)?;
+        let mut written = Vec::new();
+        for (name, mut df) in self.frames(condition_id)? {
+            let filename = format!("{}.{}", name, export.format.extension());
+            match export.format {
+                ExportFormat::Parquet => ParquetWriter::new(&export.dir.to_string_lossy()).write(&filename, &mut df)?,
+                ExportFormat::Arrow => write_ipc(&export.dir.join(&filename), &mut df)?,
+            }
+            written.push(export.dir.join(filename));
+        }
+        Ok(written)
+    }
+
+    // each table as its own arrow ipc stream, back to back, readers open streams until the input ends
+    // the schema metadata says which table and market a stream is
+    pub fn stream(&self, writer: &mut dyn Write, condition_id: &str) -> Result<()> {
+        for (name, mut df) in self.frames(condition_id)? {
+            let mut stream = IpcStreamWriter::new(&mut *writer);
+            stream.set_custom_schema_metadata(Arc::new(
+                [("table".into(), name.into()), ("condition_id".into(), condition_id.into())].into_iter().collect(),
+            ));
+            stream.finish(&mut df)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn write_ipc(path: &Path, df: &mut DataFrame) -> Result<()> {
+    let file = File::create(path)?;
+    IpcWriter::new(file).finish(df)?;
+    Ok(())
+}
+
+fn markets_frame(markets: &[Market]) -> Result<DataFrame> {
+    let timestamp = |date: Option<chrono::DateTime<chrono::Utc>>| date.map(|d| d.timestamp());
+
+    let df = df!(
+        "condition_id" => markets.iter().map(|m| m.condition_id.as_str()).collect::<Vec<_>>(),
+        "slug" => markets.iter().map(|m| m.slug.as_str()).collect::<Vec<_>>(),
+        "question" => markets.iter().map(|m| m.question.as_str()).collect::<Vec<_>>(),
+        "yes_token_id" => markets.iter().map(|m| m.yes_token_id.as_str()).collect::<Vec<_>>(),
+        "no_token_id" => markets.iter().map(|m| m.no_token_id.as_str()).collect::<Vec<_>>(),
+        "active" => markets.iter().map(|m| m.active).collect::<Vec<_>>(),
+        "closed" => markets.iter().map(|m| m.closed).collect::<Vec<_>>(),
+        "volume" => markets.iter().map(|m| m.volume).collect::<Vec<_>>(),
+        "volume_24h" => markets.iter().map(|m| m.volume_24h).collect::<Vec<_>>(),
+        "volume_1w" => markets.iter().map(|m| m.volume_1w).collect::<Vec<_>>(),
+        "volume_1m" => markets.iter().map(|m| m.volume_1m).collect::<Vec<_>>(),
+        "liquidity" => markets.iter().map(|m| m.liquidity).collect::<Vec<_>>(),
+        "last_trade_price" => markets.iter().map(|m| m.last_trade_price).collect::<Vec<_>>(),
+        "bid_price" => markets.iter().map(|m| m.bid_price).collect::<Vec<_>>(),
+        "ask_price" => markets.iter().map(|m| m.ask_price).collect::<Vec<_>>(),
+        "price_change_24h" => markets.iter().map(|m| m.price_change_24h).collect::<Vec<_>>(),
+        // unix seconds
+        "created_at" => markets.iter().map(|m| timestamp(m.created_at)).collect::<Vec<_>>(),
+        "end_date" => markets.iter().map(|m| timestamp(m.end_date)).collect::<Vec<_>>(),
+        "tags" => markets.iter().map(|m| m.tags.join(",")).collect::<Vec<_>>(),
+    )?;
+
+    Ok(df)
+}
+
+fn positions_frame(positions: &[Position]) -> Result<DataFrame> {
+    let df = df!(
+        "trader_address" => positions.iter().map(|p| p.trader_address.as_str()).collect::<Vec<_>>(),
+        "token_id" => positions.iter().map(|p| p.token_id.as_str()).collect::<Vec<_>>(),
+        "market_id" => positions.iter().map(|p| p.market_id.as_str()).collect::<Vec<_>>(),
+        "side" => positions.iter().map(|p| p.side.as_str()).collect::<Vec<_>>(),
+        "shares_held" => positions.iter().map(|p| p.shares_held).collect::<Vec<_>>(),
+        "avg_entry_price" => positions.iter().map(|p| p.avg_entry_price).collect::<Vec<_>>(),
+        "first_entry_block" => positions.iter().map(|p| p.first_entry_block).collect::<Vec<_>>(),
+    )?;
+
+    Ok(df)
+}
+
+// adjusted_accuracy is the one the models weighted by, category record included
+fn traders_frame(traders: &[Trader]) -> Result<DataFrame> {
+    let df = df!(
+        "trader_address" => traders.iter().map(|t| t.trader_address.as_str()).collect::<Vec<_>>(),
+        "total_markets_entered" => traders.iter().map(|t| t.total_markets_entered).collect::<Vec<_>>(),
+        "total_markets_resolved" => traders.iter().map(|t| t.total_markets_resolved).collect::<Vec<_>>(),
+        "total_wins" => traders.iter().map(|t| t.total_wins).collect::<Vec<_>>(),
+        "accuracy" => traders.iter().map(|t| t.accuracy).collect::<Vec<_>>(),
+        "adjusted_accuracy" => traders.iter().map(|t| t.adjusted_accuracy).collect::<Vec<_>>(),
+        "total_invested" => traders.iter().map(|t| t.total_invested).collect::<Vec<_>>(),
+        "total_returned" => traders.iter().map(|t| t.total_returned).collect::<Vec<_>>(),
+        "roi" => traders.iter().map(|t| t.roi).collect::<Vec<_>>(),
+        "first_activity_block" => traders.iter().map(|t| t.first_activity_block).collect::<Vec<_>>(),
+        "longest_win_streak" => traders.iter().map(|t| t.longest_win_streak).collect::<Vec<_>>(),
+        "longest_loss_streak" => traders.iter().map(|t| t.longest_loss_streak).collect::<Vec<_>>(),
+        "max_drawdown" => traders.iter().map(|t| t.max_drawdown).collect::<Vec<_>>(),
+        "return_volatility" => traders.iter().map(|t| t.return_volatility).collect::<Vec<_>>(),
+    )?;
+
+    Ok(df)
+}
+
+fn pnl_frame(pnls: &[TraderPnl]) -> Result<DataFrame> {
+    let df = df!(
+        "trader_address" => pnls.iter().map(|p| p.trader_address.as_str()).collect::<Vec<_>>(),
+        "realized" => pnls.iter().map(|p| p.realized).collect::<Vec<_>>(),
+        "unrealized" => pnls.iter().map(|p| p.unrealized).collect::<Vec<_>>(),
+        "total" => pnls.iter().map(|p| p.total()).collect::<Vec<_>>(),
+        "open_yes_shares" => pnls.iter().map(|p| p.open_yes_shares).collect::<Vec<_>>(),
+        "open_no_shares" => pnls.iter().map(|p| p.open_no_shares).collect::<Vec<_>>(),
+        "open_cost" => pnls.iter().map(|p| p.open_cost).collect::<Vec<_>>(),
+        "unmatched_shares" => pnls.iter().map(|p| p.unmatched_shares).collect::<Vec<_>>(),
+        "fees" => pnls.iter().map(|p| p.fees).collect::<Vec<_>>(),
+    )?;
+
+    Ok(df)
+}
+
+// one row per window, sides without trades are null
+fn vwap_frame(report: Option<&VwapReport>) -> Result<DataFrame> {
+    let windows = report.map_or(&[][..], |r| r.windows.as_slice());
+    let side = |side: Option<SideVwap>| side.map(|s| s.vwap);
+    let shares = |side: Option<SideVwap>| side.map(|s| s.shares);
+
+    let df = df!(
+        "hours" => windows.iter().map(|w| w.hours).collect::<Vec<_>>(),
+        "yes_vwap" => windows.iter().map(|w| side(w.yes)).collect::<Vec<_>>(),
+        "yes_shares" => windows.iter().map(|w| shares(w.yes)).collect::<Vec<_>>(),
+        "no_vwap" => windows.iter().map(|w| side(w.no)).collect::<Vec<_>>(),
+        "no_shares" => windows.iter().map(|w| shares(w.no)).collect::<Vec<_>>(),
+    )?;
+
+    Ok(df)
+}
+
+// single row of market level numbers, null where the section had no data
+fn metrics_frame(condition_id: &str, implied: Option<&ImpliedReturns>, concentration: Option<&Concentration>) -> Result<DataFrame> {
+    let model = |get: fn(&ImpliedReturns) -> Option<f64>| implied.and_then(get);
+    let side_return = |side: fn(&ImpliedReturns) -> Option<SideReturn>, field: fn(SideReturn) -> f64| {
+        implied.and_then(side).map(field)
+    };
+    let side_concentration = |side: fn(&Concentration) -> SideConcentration, field: fn(SideConcentration) -> f64| {
+        concentration.map(|c| field(side(c)))
+    };
+
+    let df = df!(
+        "condition_id" => [condition_id],
+        "smart_traders" => [implied.map(|i| i.smart_traders as u32)],
+        "smart_probability" => [implied.map(|i| i.smart_probability)],
+        "simple_probability" => [model(|i| i.models.simple)],
+        "weighted_probability" => [model(|i| i.models.weighted)],
+        "bayes_probability" => [model(|i| i.models.bayes)],
+        "yes_expected_return" => [side_return(|i| i.yes, |s| s.expected_return)],
+        "yes_kelly" => [side_return(|i| i.yes, |s| s.kelly)],
+        "no_expected_return" => [side_return(|i| i.no, |s| s.expected_return)],
+        "no_kelly" => [side_return(|i| i.no, |s| s.kelly)],
+        "yes_capital" => [side_concentration(|c| c.yes, |s| s.capital)],
+        "yes_hhi" => [side_concentration(|c| c.yes, |s| s.hhi)],
+        "yes_gini" => [side_concentration(|c| c.yes, |s| s.gini)],
+        "no_capital" => [side_concentration(|c| c.no, |s| s.capital)],
+        "no_hhi" => [side_concentration(|c| c.no, |s| s.hhi)],
+        "no_gini" => [side_concentration(|c| c.no, |s| s.gini)],
+    )?;
+
+    Ok(df)
+}
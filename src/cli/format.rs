@@ -0,0 +1,256 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// separators for one locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    pub group: char,
+    pub decimal: char,
+}
+
+impl NumberLocale {
+    pub const EN: NumberLocale = NumberLocale { group: ',', decimal: '.' };
+
+    // from a tag like en_US, de-DE or fr_FR.UTF-8, languages not listed are written the english way
+    pub fn parse(tag: &str) -> Self {
+        let tag = tag.split('.').next().unwrap_or_default().replace('_', "-").to_ascii_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+
+        match (language, tag.as_str()) {
+            (_, "de-ch" | "it-ch") => NumberLocale { group: '\'', decimal: '.' },
+            ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el", _) => NumberLocale { group: '.', decimal: ',' },
+            ("fr" | "ru" | "pl" | "sv" | "cs" | "fi" | "nb" | "uk" | "hu" | "sk", _) => NumberLocale { group: ' ', decimal: ',' },
+            _ => Self::EN,
+        }
+    }
+
+    // LC_ALL, then LC_NUMERIC, then LANG, the way libc picks it
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map_or(Self::EN, |value| Self::parse(&value))
+    }
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self::EN
+    }
+}
+
+// how share prices are written, communities read the same 0.67 differently
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceFormat {
+    // 0.67
+    #[default]
+    Decimal,
+    // 67¢, what a share costs
+    Cents,
+    // 67%, the implied probability
+    Prob,
+}
+
+// zone times are shown in, --tz takes local, an iana name like Europe/Berlin or an offset like +05:30
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayTz {
+    #[default]
+    Local,
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl FromStr for DisplayTz {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.eq_ignore_ascii_case("local") {
+            return Ok(DisplayTz::Local);
+        }
+        if let Ok(tz) = text.parse::<Tz>() {
+            return Ok(DisplayTz::Named(tz));
+        }
+        if let Ok(offset) = text.parse::<FixedOffset>() {
+            return Ok(DisplayTz::Fixed(offset));
+        }
+        Err(format!("unknown time zone '{}', use local, a name like America/New_York or an offset like +02:00", text))
+    }
+}
+
+impl DisplayTz {
+    // wall clock time in this zone
+    pub fn naive(&self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            DisplayTz::Local => time.with_timezone(&Local).naive_local(),
+            DisplayTz::Named(tz) => time.with_timezone(tz).naive_local(),
+            DisplayTz::Fixed(offset) => time.with_timezone(offset).naive_local(),
+        }
+    }
+
+    // short name for headers, abbreviations change with dst so the offset is used for local
+    pub fn label(&self) -> String {
+        match self {
+            DisplayTz::Local => format!("local {}", Local::now().format("%:z")),
+            DisplayTz::Named(tz) => tz.name().to_string(),
+            DisplayTz::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    fn format(&self, time: DateTime<Utc>, pattern: &str) -> String {
+        match self {
+            DisplayTz::Local => time.with_timezone(&Local).format(pattern).to_string(),
+            DisplayTz::Named(tz) => time.with_timezone(tz).format(pattern).to_string(),
+            DisplayTz::Fixed(offset) => time.with_timezone(offset).format(pattern).to_string(),
+        }
+    }
+}
+
+// how dollar amounts, prices and times are written in text output, json and exported files keep raw values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DisplayFormat {
+    // $1.23M instead of $1,234,567.89
+    pub compact: bool,
+    pub locale: NumberLocale,
+    pub price: PriceFormat,
+    pub tz: DisplayTz,
+}
+
+static FORMAT: OnceLock<DisplayFormat> = OnceLock::new();
+
+// set once from the flags before anything prints, later calls are ignored
+pub fn init(format: DisplayFormat) {
+    let _ = FORMAT.set(format);
+}
+
+pub fn current() -> DisplayFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+impl DisplayFormat {
+    // 2026-03-01 14:05 CET, or +01:00 when the zone has no abbreviation
+    pub fn datetime(&self, time: DateTime<Utc>) -> String {
+        let zone = match self.tz {
+            DisplayTz::Named(_) => "%Z",
+            _ => "%:z",
+        };
+        self.tz.format(time, &format!("%Y-%m-%d %H:%M {}", zone))
+    }
+
+    // 14:05:09, for lines printed as they happen
+    pub fn clock(&self, time: DateTime<Utc>) -> String {
+        self.tz.format(time, "%H:%M:%S")
+    }
+
+    // $1,234,567.89, or $1.23M when compact
+    pub fn usd(&self, value: f64) -> String {
+        let sign = if value < 0.0 { "-" } else { "" };
+        format!("{}${}", sign, self.magnitude(value.abs()))
+    }
+
+    // always signed, for pnl
+    pub fn usd_signed(&self, value: f64) -> String {
+        let sign = if value < 0.0 { "-" } else { "+" };
+        format!("{}${}", sign, self.magnitude(value.abs()))
+    }
+
+    // places are for the decimal form, cents and prob move two of them in front of the point
+    // and drop trailing zeros so 0.670 reads 67¢
+    pub fn price(&self, value: f64, places: usize) -> String {
+        match self.price {
+            PriceFormat::Decimal => self.number(value, places),
+            PriceFormat::Cents => format!("{}¢", self.percent_points(value, places)),
+            PriceFormat::Prob => format!("{}%", self.percent_points(value, places)),
+        }
+    }
+
+    // a change or gap between prices, always signed
+    pub fn price_signed(&self, value: f64, places: usize) -> String {
+        let sign = if value < 0.0 { "-" } else { "+" };
+        format!("{}{}", sign, self.price(value.abs(), places))
+    }
+
+    fn percent_points(&self, value: f64, places: usize) -> String {
+        let text = self.number(value * 100.0, places.saturating_sub(2));
+        match text.split_once(self.locale.decimal) {
+            Some((whole, fraction)) => match fraction.trim_end_matches('0') {
+                "" => whole.to_string(),
+                fraction => format!("{}{}{}", whole, self.locale.decimal, fraction),
+            },
+            None => text,
+        }
+    }
+
+    // digits grouped with the locale's separators
+    pub fn number(&self, value: f64, places: usize) -> String {
+        let text = format!("{:.*}", places, value.abs());
+        let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i).is_multiple_of(3) {
+                grouped.push(self.locale.group);
+            }
+            grouped.push(digit);
+        }
+
+        let sign = if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+        if fraction.is_empty() {
+            format!("{}{}", sign, grouped)
+        } else {
+            format!("{}{}{}{}", sign, grouped, self.locale.decimal, fraction)
+        }
+    }
+
+    fn magnitude(&self, value: f64) -> String {
+        if !self.compact || value < 1_000.0 {
+            return self.number(value, 2);
+        }
+
+        // picked on the rounded value so 999,999 comes out as 1.00M rather than 1,000.00K
+        const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+        let (scale, unit) = UNITS
+            .iter()
+            .find(|(scale, _)| (value / scale * 100.0).round() >= 100.0)
+            .copied()
+            .unwrap_or((1e3, "K"));
+        format!("{}{}", self.number(value / scale, 2), unit)
+    }
+}
+
+pub fn usd(value: f64) -> String {
+    current().usd(value)
+}
+
+pub fn usd_signed(value: f64) -> String {
+    current().usd_signed(value)
+}
+
+pub fn price(value: f64, places: usize) -> String {
+    current().price(value, places)
+}
+
+pub fn price_signed(value: f64, places: usize) -> String {
+    current().price_signed(value, places)
+}
+
+pub fn datetime(time: DateTime<Utc>) -> String {
+    current().datetime(time)
+}
+
+// unix seconds, empty when out of range
+pub fn timestamp(seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0).map(datetime).unwrap_or_default()
+}
+
+pub fn clock(time: DateTime<Utc>) -> String {
+    current().clock(time)
+}
+
+// unix seconds shifted so utc arithmetic on them gives the wall clock in the display zone
+pub fn wall_clock_seconds(seconds: i64) -> i64 {
+    DateTime::from_timestamp(seconds, 0).map_or(seconds, |time| current().tz.naive(time).and_utc().timestamp())
+}
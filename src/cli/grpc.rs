@@ -0,0 +1,92 @@
+use crate::analysis::{Alert, MarketSummary};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("polymarket_explorer.analysis");
+}
+
+use proto::analysis_event::Event;
+use proto::analysis_stream_server::{AnalysisStream, AnalysisStreamServer};
+use proto::{AnalysisEvent, WatchRequest};
+
+// events buffered per subscriber before slow clients start missing some
+const EVENT_BUFFER: usize = 256;
+
+// fans every poll out to all connected clients
+pub struct AnalysisService {
+    events: broadcast::Sender<AnalysisEvent>,
+}
+
+#[tonic::async_trait]
+impl AnalysisStream for AnalysisService {
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<AnalysisEvent, Status>> + Send>>;
+
+    async fn watch(&self, request: Request<WatchRequest>) -> Result<Response<Self::WatchStream>, Status> {
+        let slugs: HashSet<String> = request.into_inner().market_slugs.into_iter().collect();
+
+        // lagged receivers just skip what they missed
+        let stream = BroadcastStream::new(self.events.subscribe()).filter_map(move |event| match event {
+            Ok(event) if slugs.is_empty() || slugs.contains(event_slug(&event)) => Some(Ok(event)),
+            _ => None,
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+// start the grpc server in the background, send events through the returned sender
+pub fn spawn_server(addr: SocketAddr) -> (broadcast::Sender<AnalysisEvent>, JoinHandle<Result<(), tonic::transport::Error>>) {
+    let (events, _) = broadcast::channel(EVENT_BUFFER);
+    let service = AnalysisService { events: events.clone() };
+
+    let server = tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(AnalysisStreamServer::new(service))
+            .serve(addr),
+    );
+
+    (events, server)
+}
+
+pub fn snapshot_event(summary: &MarketSummary, timestamp: i64) -> AnalysisEvent {
+    AnalysisEvent {
+        event: Some(Event::Snapshot(proto::Snapshot {
+            slug: summary.slug.clone(),
+            question: summary.question.clone(),
+            yes_price: summary.yes_price,
+            spread: summary.spread,
+            smart_lean: summary.smart_lean,
+            whale_share: summary.whale_share,
+            whale_count: summary.whale_count as u32,
+            volume_24h: summary.volume_24h,
+            timestamp,
+            liquidity: summary.liquidity,
+        })),
+    }
+}
+
+pub fn alert_event(alert: &Alert, timestamp: i64) -> AnalysisEvent {
+    AnalysisEvent {
+        event: Some(Event::Alert(proto::Alert {
+            slug: alert.slug.clone(),
+            kind: alert.kind.as_str().to_string(),
+            message: alert.message.clone(),
+            timestamp,
+        })),
+    }
+}
+
+fn event_slug(event: &AnalysisEvent) -> &str {
+    match &event.event {
+        Some(Event::Snapshot(snapshot)) => &snapshot.slug,
+        Some(Event::Alert(alert)) => &alert.slug,
+        None => "",
+    }
+}
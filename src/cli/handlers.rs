@@ -1,70 +1,3094 @@
+use crate::cli::metrics::{self, MetricsState};
+use crate::cli::server;
+use crate::cli::format;
 use crate::cli::output;
+use crate::cli::events::{AnalysisBus, ArrowSink, AnalysisEvent, HtmlSink, JsonSink, SectionError, TerminalSink, WebhookSink};
+use crate::cli::export::{AnalysisExport, Export};
+use crate::clock;
+use crate::error::AppError;
+use crate::workers;
+use crate::cli::commands::{Cli, Command, IngestTarget, LabelAction, LocalCommand, OutputFormat, PaperAction, SchemaTarget, StatsAction, WatchlistAction};
+use crate::analysis::backtest::{self, BacktestConfig};
+use crate::analysis::big_trades;
+use crate::analysis::book_history::{self, BookSnapshot, DEPTH_DISTANCES};
+use crate::analysis::calibration::{self, CalibrationConfig};
+use crate::analysis::category;
+use crate::analysis::chatter::{self, CHATTER_COMMENT_LIMIT};
+use crate::analysis::funding::{self, FundingSource};
+use crate::analysis::alerts::AlertTracker;
+use crate::analysis::analyzer::{Analyzer, AnalyzerInput};
+use crate::analysis::audit;
+use crate::analysis::compare::{self, MarketSummary};
+use crate::analysis::crypto::{self, CryptoFairValue};
+use crate::analysis::{Alert, Concentration, FeeModel, ImpliedReturns, OpenInterest, OrderFlowReport, TraderPnl, VwapReport, WalletAgeBreakdown};
+use serde_json::{json, Value};
+use crate::analysis::closing_soon::{self, ClosingMarket};
+use crate::analysis::collateral::{CollateralMix, CollateralRates};
+use crate::analysis::coherence;
+use crate::analysis::concentration;
+use crate::analysis::heatmap;
+use crate::analysis::implied_return;
+use crate::analysis::insider;
+use crate::analysis::line_parity;
+use crate::analysis::liquidity::{self, LiquidityPoint};
+use crate::analysis::movers::{self, MOVER_VWAP_HOURS};
+use crate::analysis::new_markets;
+use crate::analysis::order_plan::{self, OrderRequest};
+use crate::analysis::pnl::{self, CostBasis};
+use crate::analysis::rewards::{self, RewardSettings};
+use crate::analysis::position_diff;
+use crate::analysis::postmortem;
+use crate::analysis::smart_money::{SmartMoney, WalletGroups};
+use crate::analysis::tape::{self, MAX_REPLAY_PAUSE_SECS};
+use crate::analysis::trader_history;
+use crate::analysis::trader_watch::{TraderActivity, TraderWatch, TRADER_WATCH_LOOKBACK_DAYS};
+use crate::analysis::open_interest;
+use crate::analysis::order_flow;
+use crate::analysis::vwap;
+use crate::analysis::wallet_age;
+use crate::data_sources::Capabilities;
+use crate::ingest::{self, checkpoint, resolutions, Checkpoints};
 use anyhow::Result;
-use crate::standard_data::providers::{MarketMetadataProvider, TraderStatsProvider, PositionProvider};
+use crate::standard_data::models::{ExternalForecast, Market, MarketGroup, MarketResolution, MarketTag, Position, Trader, TraderSnapshot, Transaction};
+use crate::standard_data::providers::{CommentProvider, ExternalForecastProvider, ExternalOddsProvider, MarketFilter, MarketMetadataProvider, MarketOrder, OrderBookProvider, TraderStatsProvider, PositionProvider, TransactionProvider, ResolutionProvider, RewardProvider, TagProvider, DataStore};
+use crate::watchlist::Watchlist;
+use crate::state_db::StateDb;
+use crate::address_book::AddressBook;
+use crate::paper::{self, FillSide, PaperFill, PaperLedger, PaperSnapshot};
+use crate::config::Config;
+use crate::adapters::{CtfEventReader, ForecastFile, FundingTracer, HttpClient, NameResolver, ScriptAnalyzer, SpotPriceFeed, WebhookTarget};
+use anyhow::bail;
+use clap::CommandFactory;
+use futures::StreamExt;
+use clap::builder::PossibleValuesParser;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::pin::pin;
+use std::time::Duration;
 
-// print the results from the market, takes in a marketprovider
-pub async fn handle_analyze<M, T, P>(
+// markets pulled per scan before ranking them locally, one gamma page
+const MARKET_POOL: usize = 500;
+
+// condition ids per gamma lookup when naming a wallet's markets
+const HISTORY_LOOKUP_BATCH: usize = 50;
+
+// run a parsed command against a market source and a db source
+#[allow(clippy::too_many_arguments)]
+pub async fn dispatch<M, D>(
+    command: Command,
+    output_format: OutputFormat,
+    market_provider: &M,
+    db: &D,
+    capabilities: &Capabilities,
+    names: Option<&NameResolver>,
+    funding: Option<&FundingTracer>,
+    ctf: Option<&CtfEventReader>,
+    odds: Option<&dyn ExternalOddsProvider>,
+    http_client: &HttpClient,
+    config: &Config,
+) -> Result<()>
+where
+    M: MarketMetadataProvider + OrderBookProvider + TransactionProvider + RewardProvider + CommentProvider,
+    D: TraderStatsProvider + PositionProvider + TransactionProvider + ResolutionProvider + TagProvider + RewardProvider + DataStore,
+{
+    let smart_money = &config.smart_money;
+    match command {
+        Command::Analyze { market_slug, all_markets, cost_basis, vwap_windows, ofi_window, fail_fast, export, html, webhook, webhook_secret, plugins, scripts, chatter, forecasts } => {
+            let webhook = webhook.map(|url| WebhookTarget::new(url).with_secret(webhook_secret));
+            // read once, every market of the group is looked up in it
+            let forecasts = match forecasts {
+                Some(location) => Some(ForecastFile::load(&location, http_client).await?),
+                None => None,
+            };
+            handle_analyze(
+                    &market_slug,
+                    all_markets,
+                    cost_basis,
+                    &vwap_windows,
+                    ofi_window,
+                    fail_fast,
+                    chatter,
+                    forecasts.as_ref().map(|file| file as &dyn ExternalForecastProvider),
+                    export.map(|args| Export::from_args(&args)).transpose()?.as_ref(),
+                    capabilities,
+                    names,
+                    config,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+                    db, // transaction provider
+                    &load_analyzers(plugins, &scripts)?,
+                    analysis_bus(output_format, html, webhook, &config.webhooks, http_client),
+            ).await
+        }
+        Command::Compare { market_slugs } => {
+            handle_compare(
+                    &slugs_or_watchlist(market_slugs)?,
+                    smart_money,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+            ).await
+        }
+        Command::Movers { min_volume, min_liquidity, limit, include_archived } => {
+            handle_movers(
+                    min_volume,
+                    min_liquidity,
+                    limit,
+                    include_archived,
+                    market_provider,
+                    market_provider, // transaction provider
+            ).await
+        }
+        Command::NewMarkets { hours, min_position, limit, include_archived } => {
+            handle_new_markets(
+                    chrono::TimeDelta::hours(hours as i64),
+                    min_position,
+                    limit,
+                    include_archived,
+                    capabilities,
+                    market_provider,
+                    db, // position provider
+            ).await
+        }
+        Command::ClosingSoon { hours, limit, include_archived } => {
+            handle_closing_soon(
+                    chrono::TimeDelta::hours(hours as i64),
+                    limit,
+                    include_archived,
+                    smart_money,
+                    capabilities,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+            ).await
+        }
+        Command::PlanOrder { market_slug, side, size, limit_price } => {
+            handle_plan_order(
+                    &market_slug,
+                    OrderRequest { outcome: side, shares: size, limit_price },
+                    config,
+                    capabilities,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+            ).await
+        }
+        Command::Monitor { traders: true, interval, count, .. } => {
+            handle_monitor_traders(
+                    &watched_traders()?,
+                    Duration::from_secs(interval.max(1)),
+                    count,
+                    smart_money,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+            ).await
+        }
+        Command::Monitor { market_slugs, interval, count, metrics_addr, archive_books, .. } => {
+            handle_monitor(
+                    &slugs_or_watchlist(market_slugs)?,
+                    Duration::from_secs(interval.max(1)),
+                    count,
+                    metrics_addr,
+                    archive_books,
+                    smart_money,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+            ).await
+        }
+        Command::Backtest { min_accuracy, min_resolved_markets, max_entry_delay_days, stake } => {
+            let config = BacktestConfig {
+                smart_money: SmartMoney {
+                    min_accuracy: min_accuracy.unwrap_or(smart_money.min_accuracy),
+                    min_resolved: min_resolved_markets.unwrap_or(smart_money.min_resolved),
+                    ..*smart_money
+                },
+                max_entry_delay_days,
+                stake,
+            };
+
+            handle_backtest(
+                    &config,
+                    db, // transaction provider
+                    db, // resolution provider
+            ).await
+        }
+        Command::Calibration { days_before, buckets } => {
+            let config = CalibrationConfig {
+                smart_money: *smart_money,
+                days_before,
+                buckets,
+            };
+            handle_calibration(
+                    &config,
+                    db, // trader stats provider
+                    db, // transaction provider
+                    db, // resolution provider
+            ).await
+        }
+        Command::Leaderboard { category, limit } => {
+            handle_leaderboard(
+                    category.as_deref(),
+                    limit,
+                    config,
+                    capabilities,
+                    names,
+                    db, // transaction provider
+                    db, // resolution provider
+                    db, // tag provider
+                    db, // reward provider
+            ).await
+        }
+        Command::Portfolio { trader } => {
+            handle_portfolio(
+                    &trader,
+                    capabilities,
+                    market_provider,
+                    db, // transaction provider
+                    db, // resolution provider
+                    db, // tag provider
+            ).await
+        }
+        Command::Heatmap { market_slug, days, smart_only, min_accuracy, min_resolved_markets } => {
+            let smart_filter = (smart_only || min_accuracy.is_some() || min_resolved_markets.is_some()).then(|| SmartMoney {
+                min_accuracy: min_accuracy.unwrap_or(smart_money.min_accuracy),
+                min_resolved: min_resolved_markets.unwrap_or(smart_money.min_resolved),
+                ..*smart_money
+            });
+
+            handle_heatmap(
+                    &market_slug,
+                    days,
+                    smart_filter.as_ref(),
+                    market_provider,
+                    db, // trader stats provider
+                    db, // transaction provider
+            ).await
+        }
+        Command::BigTrades { market_slug, min_usdc, days, limit } => {
+            handle_big_trades(
+                    &market_slug,
+                    min_usdc,
+                    days,
+                    limit,
+                    smart_money,
+                    names,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // transaction provider
+            ).await
+        }
+        Command::Replay { market_slug, speed, min_usdc } => {
+            handle_replay(
+                    &market_slug,
+                    speed,
+                    min_usdc,
+                    capabilities,
+                    smart_money,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // transaction provider
+            ).await
+        }
+        Command::Insiders { market_slug, min_score, limit } => {
+            handle_insiders(
+                    &market_slug,
+                    min_score,
+                    limit,
+                    names,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+                    db, // transaction provider
+            ).await
+        }
+        Command::Parity { market_slug, sport, threshold } => {
+            handle_parity(
+                    &market_slug,
+                    &sport,
+                    threshold,
+                    odds,
+                    market_provider,
+            ).await
+        }
+        Command::Crypto { market_slug, vol_days, volatility } => {
+            handle_crypto(
+                    &market_slug,
+                    vol_days,
+                    volatility,
+                    &SpotPriceFeed::new(http_client.clone()),
+                    market_provider,
+            ).await
+        }
+        Command::Funding { market_slug, top, lookback_days } => {
+            handle_funding(
+                    &market_slug,
+                    top,
+                    lookback_days,
+                    funding,
+                    names,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+            ).await
+        }
+        Command::PositionChanges { market_slug, days, min_shares, limit } => {
+            handle_position_changes(
+                    &market_slug,
+                    days,
+                    min_shares,
+                    limit,
+                    names,
+                    market_provider,
+                    db, // transaction provider
+            ).await
+        }
+        Command::Postmortem { market_slug, checkpoints } => {
+            handle_postmortem(
+                    &market_slug,
+                    checkpoints,
+                    smart_money,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // transaction provider
+                    db, // resolution provider
+            ).await
+        }
+        #[cfg(feature = "grpc")]
+        Command::Grpc { market_slugs, addr, interval } => {
+            handle_grpc(
+                    &slugs_or_watchlist(market_slugs)?,
+                    addr,
+                    Duration::from_secs(interval.max(1)),
+                    smart_money,
+                    market_provider,
+                    db, // trader stats provider
+                    db, // position provider
+            ).await
+        }
+        Command::Serve { addr, report_version } => handle_serve(addr, report_version, config, market_provider, db).await,
+        // live orders go through main with the http client, never against mock data
+        Command::Paper { action } => handle_paper(action, &config.fees, market_provider).await,
+        #[cfg(feature = "trading")]
+        Command::Trade { .. } => bail!("trade only runs against --source live"),
+        Command::AuditDb { examples } => {
+            handle_audit_db(
+                    examples,
+                    db, // trader stats provider
+                    db, // position provider
+                    db, // transaction provider
+            ).await
+        }
+        Command::Compact { row_group_size } => handle_compact(row_group_size, db).await,
+        Command::Stats { action: StatsAction::Rebuild { full } } => {
+            handle_stats_rebuild(
+                    full,
+                    capabilities,
+                    &config.collateral,
+                    db, // transaction provider
+                    db, // resolution provider
+                    db, // tag provider
+                    db, // data store
+            ).await
+        }
+        Command::Ingest { from_scratch, target: IngestTarget::Resolutions { batch_size } } => {
+            handle_ingest_resolutions(
+                    batch_size,
+                    from_scratch,
+                    capabilities,
+                    &config.collateral,
+                    market_provider,
+                    db, // transaction provider
+                    db, // resolution provider
+                    db, // tag provider
+                    db, // data store
+            ).await
+        }
+        Command::Ingest { from_scratch, target: IngestTarget::Tags { batch_size } } => {
+            handle_ingest_tags(
+                    batch_size,
+                    from_scratch,
+                    capabilities,
+                    market_provider,
+                    db, // transaction provider
+                    db, // resolution provider
+                    db, // tag provider
+                    db, // data store
+            ).await
+        }
+        Command::Ingest { from_scratch, target: IngestTarget::Trades { market_slugs, days } } => {
+            handle_ingest_trades(
+                    &slugs_or_watchlist(market_slugs)?,
+                    days,
+                    from_scratch,
+                    market_provider,
+                    market_provider, // remote transaction provider
+                    db, // local transaction provider
+                    db, // data store
+            ).await
+        }
+        Command::Ingest { from_scratch, target: IngestTarget::Rewards { addresses } } => {
+            handle_ingest_rewards(
+                    &addresses,
+                    from_scratch,
+                    smart_money,
+                    market_provider, // remote reward provider
+                    db, // trader stats provider
+                    db, // data store
+            ).await
+        }
+        Command::Ingest { from_scratch, target: IngestTarget::Ctf { market_slugs, days } } => {
+            handle_ingest_ctf(
+                    &slugs_or_watchlist(market_slugs)?,
+                    days,
+                    from_scratch,
+                    market_provider,
+                    ctf,
+                    db, // local transaction provider
+                    db, // data store
+            ).await
+        }
+    }
+}
+
+// run a command that only touches local files, no config or source is opened for it
+pub fn dispatch_local(command: LocalCommand) -> Result<()> {
+    match command {
+        LocalCommand::Watchlist { action } => handle_watchlist(action),
+        LocalCommand::Label { action } => handle_label(action),
+        LocalCommand::Completions { shell } => handle_completions(shell),
+        LocalCommand::Schema { target, report_version } => handle_schema(target, report_version),
+        LocalCommand::LiquidityHistory { market_slug, threshold, limit } => handle_liquidity_history(&market_slug, threshold, limit),
+        LocalCommand::BookHistory { market_slug, limit } => handle_book_history(&market_slug, limit),
+    }
+}
+
+// analyze the first market of a group, or all of them, every result goes out as an event on the bus
+// the bus decides where it ends up, terminal, json, html or a webhook
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_analyze<M, T, P, X>(
     market_slug: &str,
+    all_markets: bool,
+    cost_basis: CostBasis,
+    vwap_windows: &[u32],
+    ofi_window: u32,
+    fail_fast: bool,
+    chatter: bool,
+    forecasts: Option<&dyn ExternalForecastProvider>,
+    export: Option<&Export>,
+    capabilities: &Capabilities,
+    names: Option<&NameResolver>,
+    config: &Config,
     market_provider: &M,
     trader_provider: &T,
     position_provider: &P,
+    transaction_provider: &X,
+    analyzers: &[Box<dyn Analyzer>],
+    mut bus: AnalysisBus,
 ) -> Result<()> 
 where   
-    M: MarketMetadataProvider,
-    T: TraderStatsProvider,
+    M: MarketMetadataProvider + CommentProvider,
+    T: TraderStatsProvider + RewardProvider,
+    P: PositionProvider,
+    X: TransactionProvider,
+{
+    let result = analyze_into(
+        &mut bus,
+        market_slug,
+        all_markets,
+        cost_basis,
+        vwap_windows,
+        ofi_window,
+        fail_fast,
+        chatter,
+        forecasts,
+        export,
+        capabilities,
+        names,
+        config,
+        market_provider,
+        trader_provider,
+        position_provider,
+        transaction_provider,
+        analyzers,
+    )
+    .await;
+
+    // sections that ran before an error, ctrl-c or --timeout still reach every sink
+    if let Err(e) = &result {
+        bus.emit(AnalysisEvent::Incomplete { reason: &e.to_string() });
+    }
+    let finished = bus.finish().await;
+    result.and(finished)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn analyze_into<M, T, P, X>(
+    bus: &mut AnalysisBus,
+    market_slug: &str,
+    all_markets: bool,
+    cost_basis: CostBasis,
+    vwap_windows: &[u32],
+    ofi_window: u32,
+    fail_fast: bool,
+    chatter: bool,
+    forecasts: Option<&dyn ExternalForecastProvider>,
+    export: Option<&Export>,
+    capabilities: &Capabilities,
+    names: Option<&NameResolver>,
+    config: &Config,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+    transaction_provider: &X,
+    analyzers: &[Box<dyn Analyzer>],
+) -> Result<()> 
+where   
+    M: MarketMetadataProvider + CommentProvider,
+    T: TraderStatsProvider + RewardProvider,
     P: PositionProvider,
+    X: TransactionProvider,
 {
+    let smart_money = &config.smart_money;
     // get market info
-    output::print_header(&format!("Fetching market: {}", market_slug));
+    bus.emit(AnalysisEvent::Started { slug: market_slug, smart_money });
     let market_group = market_provider.get_market_group(market_slug).await?;
     
     // display market info
-    output::print_market_group_info(&market_group);
+    bus.emit(AnalysisEvent::Group { group: &market_group });
+    if let Some(coherence) = coherence::check_group(&market_group) {
+        bus.emit(AnalysisEvent::Coherence { coherence: &coherence });
+    }
+    // what the event's comments say, set against the primary market's price
+    if chatter {
+        let comments = market_provider.get_event_comments(market_slug, CHATTER_COMMENT_LIMIT).await;
+        match isolate(comments.map(|comments| chatter::chatter(&comments, clock::now().timestamp())), fail_fast)? {
+            Ok(chatter) => {
+                let yes_price = market_group.markets.first().and_then(|market| market.outcome_prices.first()?.parse().ok());
+                bus.emit(AnalysisEvent::Chatter { chatter: &chatter, yes_price });
+            }
+            Err(error) => bus.emit(AnalysisEvent::Failed { section: "CROWD CHATTER", error: &error }),
+        }
+    }
     
-    // TODO: change here for deciding what market to analyse right now just first
-    if let Some(first_market) = market_group.markets.first() {
-        output::print_header("ANALYZING PRIMARY MARKET");
-        output::print_market_info(first_market);
+    // the first market is the primary one, the rest only with --all-markets
+    let markets = match all_markets {
+        true => &market_group.markets[..],
+        false => &market_group.markets[..market_group.markets.len().min(1)],
+    };
+    if markets.is_empty() {
+        bus.emit(AnalysisEvent::NoMarkets);
+        return Ok(());
+    }
 
-        let condition_id = &first_market.condition_id;
+    let mut book = AddressBook::load()?;
+    let groups = book.wallet_groups();
 
-        // get positions
-        output::print_header("FETCHING POSITION DATA");
-        let positions = position_provider.get_positions(condition_id).await?;
-        println!("  Found {} positions for this market", positions.len());
+    // --concurrency markets at a time, each one is shown as soon as it and the ones before it are in
+    let mut analyses = pin!(workers::ordered(markets.iter().map(|market| analyze_market(
+        market,
+        cost_basis,
+        vwap_windows,
+        ofi_window,
+        fail_fast,
+        forecasts,
+        capabilities,
+        smart_money,
+        &groups,
+        &config.fees,
+        config.rewards,
+        trader_provider,
+        position_provider,
+        transaction_provider,
+    ))));
 
-        let trader_addresses: Vec<String> = positions
-            .iter()
-            .map(|p| p.trader_address.clone())
-            .collect();
-        
-        output::print_header("TRADER STATS");
-        let traders = trader_provider.get_traders_by_addresses(&trader_addresses).await?;
-        println!("  Found {} traders", traders.len());
-
-        println!("Sample data: ");
-        if let Some(first_position) = positions.first() {
-            println!("\n  Sample position:");
-            println!("    Trader: {}", first_position.trader_address);
-            println!("    Side: {}", first_position.side);
-            println!("    Shares: {}", first_position.shares_held);
-            println!("    Avg Price: ${:.4}", first_position.avg_entry_price);
-        }
-        
-        if let Some(first_trader) = traders.first() {
-            println!("\n  Sample trader:");
-            println!("    Address: {}", first_trader.trader_address);
-            println!("    Accuracy: {:.1}%", first_trader.accuracy * 100.0);
-            println!("    ROI: {:.1}%", first_trader.roi * 100.0);
-            println!("    Markets: {}", first_trader.total_markets_resolved);
-        }
-
-        // TODO: Fetch positions and calculate statistics
-        output::print_header("ANALYSIS");
-        println!("something will be here soon");
-    } else {
-        println!("  No markets found in this group\n");
+    for (number, market) in markets.iter().enumerate() {
+        let Some(analysis) = analyses.next().await else {
+            break;
+        };
+        let analysis = analysis?;
+        bus.emit(AnalysisEvent::Market { market, number: number + 1, total: markets.len(), open_interest: analysis.open_interest.as_ref() });
+        if analysis.collateral.is_mixed() {
+            bus.emit(AnalysisEvent::MixedCollateral { mix: &analysis.collateral, rates: config.collateral });
+        }
+        let mut tables = AnalysisExport { markets: &market_group.markets, ..Default::default() };
+        let mut transactions = Vec::new();
+
+        match analysis.holders {
+            Some(Ok(holders)) => {
+                bus.emit(AnalysisEvent::Positions { count: holders.positions.len() });
+                let traders = holders.traders.as_ref().map(|stats| &stats.traders[..]).unwrap_or_default();
+                match &holders.traders {
+                    Ok(stats) => {
+                        bus.emit(AnalysisEvent::Traders { count: stats.traders.len(), weighted: stats.weighted.as_ref().ok().copied().flatten(), tags: &market.tags });
+                        if let Err(error) = &stats.weighted {
+                            bus.emit(AnalysisEvent::Failed { section: "CATEGORY SKILL", error });
+                        }
+                    }
+                    Err(error) => bus.emit(AnalysisEvent::Failed { section: "TRADER STATS", error }),
+                }
+
+                let samples: Vec<String> = holders.positions.first().map(|p| p.trader_address.clone())
+                    .into_iter()
+                    .chain(traders.first().map(|t| t.trader_address.clone()))
+                    .collect();
+                resolve_names(&mut book, names, &samples).await;
+                bus.emit(AnalysisEvent::Samples { position: holders.positions.first(), trader: traders.first(), book: &book });
+
+                // TODO: more statistics on the positions
+                match &holders.traders {
+                    Ok(stats) => bus.emit(AnalysisEvent::WalletAge { breakdown: &stats.wallet_age }),
+                    Err(error) => bus.emit(AnalysisEvent::Failed { section: "WALLET AGE", error }),
+                }
+                bus.emit(AnalysisEvent::Concentration { concentration: &holders.concentration });
+                match &holders.traders {
+                    Ok(stats) => bus.emit(AnalysisEvent::ImpliedReturns { implied: stats.implied.as_ref(), smart_money, fees: &config.fees }),
+                    Err(error) => bus.emit(AnalysisEvent::Failed { section: "IMPLIED RETURNS", error }),
+                }
+
+                tables.concentration = Some(holders.concentration);
+                tables.positions = holders.positions;
+                if let Ok(stats) = holders.traders {
+                    tables.implied = stats.implied;
+                    tables.traders = stats.traders;
+                }
+            }
+            Some(Err(error)) => {
+                for section in ["POSITION DATA", "WALLET AGE", "HOLDER CONCENTRATION", "IMPLIED RETURNS"] {
+                    bus.emit(AnalysisEvent::Failed { section, error: &error });
+                }
+            }
+            None => {
+                for section in ["POSITION DATA", "WALLET AGE", "HOLDER CONCENTRATION", "IMPLIED RETURNS"] {
+                    bus.emit(AnalysisEvent::Unavailable { section, reason: "no positions or trader stats in the local db" });
+                }
+            }
+        }
+
+        // the market, smart money and every outside model in one table
+        match &analysis.forecasts {
+            Some(Ok(forecasts)) => bus.emit(AnalysisEvent::Forecasts {
+                market_price: market.outcome_prices.first().and_then(|price| price.parse().ok()),
+                smart_money: tables.implied.as_ref().map(|implied| implied.smart_probability),
+                forecasts,
+            }),
+            Some(Err(error)) => bus.emit(AnalysisEvent::Failed { section: "MODEL VS MARKET", error }),
+            None => {}
+        }
+
+        match analysis.trades {
+            Some(Ok(trades)) => {
+                let top: Vec<String> = trades.pnls.iter().take(output::PNL_TOP_TRADERS).map(|p| p.trader_address.clone()).collect();
+                resolve_names(&mut book, names, &top).await;
+                bus.emit(AnalysisEvent::Pnl { pnls: &trades.pnls, cost_basis, rewards: config.rewards, yes_mark: trades.yes_mark, no_mark: trades.no_mark, book: &book });
+                bus.emit(AnalysisEvent::Vwap { report: &trades.vwap, yes_mark: trades.yes_mark, no_mark: trades.no_mark });
+                bus.emit(AnalysisEvent::OrderFlow { report: &trades.flow });
+
+                tables.pnls = trades.pnls;
+                tables.vwap = Some(trades.vwap);
+                transactions = trades.transactions;
+            }
+            Some(Err(error)) => {
+                for section in ["TRADER PNL", "VWAP", "ORDER FLOW"] {
+                    bus.emit(AnalysisEvent::Failed { section, error: &error });
+                }
+            }
+            None => {
+                for section in ["TRADER PNL", "VWAP", "ORDER FLOW"] {
+                    bus.emit(AnalysisEvent::Unavailable { section, reason: "no transactions in the local db" });
+                }
+            }
+        }
+
+        // plugins see the same data the built in sections did, one failing doesn't stop the others
+        let input = AnalyzerInput { market, positions: &tables.positions, traders: &tables.traders, transactions: &transactions };
+        for analyzer in analyzers {
+            match analyzer.analyze(&input) {
+                Ok(output) => bus.emit(AnalysisEvent::Analyzer { name: analyzer.name(), output: &output }),
+                Err(e) => bus.emit(AnalysisEvent::Unavailable { section: analyzer.name(), reason: &format!("{:#}", e) }),
+            }
+        }
+
+        bus.tables(&market.condition_id, &tables);
+        // sections without data still get their table, just empty
+        // with --all-markets every market gets its own subdirectory
+        if let Some(export) = export {
+            let written = match all_markets {
+                true => tables.write(&Export { format: export.format, dir: export.dir.join(&market.slug) }, &market.condition_id)?,
+                false => tables.write(export, &market.condition_id)?,
+            };
+            bus.emit(AnalysisEvent::Exported { files: &written });
+        }
     }
     
     Ok(())
 }
+
+// a section's result, or why it failed when the run isn't --fail-fast
+type Section<T> = std::result::Result<T, SectionError>;
+
+// keep the error for the report, unless the run should stop on it
+fn isolate<T>(result: crate::error::Result<T>, fail_fast: bool) -> Result<Section<T>> {
+    match result {
+        Ok(value) => Ok(Ok(value)),
+        // a cancelled run stops either way
+        Err(e) if fail_fast || matches!(e, AppError::Cancelled(_)) => Err(e.into()),
+        Err(e) => Ok(Err(SectionError::from(&e))),
+    }
+}
+
+// holder sections of one market, from its positions
+struct HolderSections {
+    positions: Vec<Position>,
+    concentration: Concentration,
+    // the rest also needs the holders' trader stats
+    traders: Section<TraderSections>,
+}
+
+struct TraderSections {
+    traders: Vec<Trader>,
+    // traders switched to their record in the market's tags, None for untagged markets
+    // when the category stats fail everyone keeps their lifetime record
+    weighted: Section<Option<usize>>,
+    wallet_age: WalletAgeBreakdown,
+    implied: Option<ImpliedReturns>,
+}
+
+// trade sections of one market, from its transaction log
+struct TradeSections {
+    pnls: Vec<TraderPnl>,
+    yes_mark: f64,
+    no_mark: f64,
+    vwap: VwapReport,
+    flow: OrderFlowReport,
+    // kept for plugins and scripts
+    transactions: Vec<Transaction>,
+}
+
+// everything analyze computes for one market, None for what the local db can't answer
+struct MarketAnalysis {
+    holders: Option<Section<HolderSections>>,
+    // None when neither positions nor transactions loaded
+    open_interest: Option<OpenInterest>,
+    // rows per collateral over whatever loaded of the positions and trades
+    collateral: CollateralMix,
+    trades: Option<Section<TradeSections>>,
+    // only with --forecasts
+    forecasts: Option<Section<Vec<ExternalForecast>>>,
+}
+
+// fetch and compute, nothing is shown yet so several markets can run at once
+// a failed fetch only fails the sections built on it unless fail_fast
+#[allow(clippy::too_many_arguments)]
+async fn analyze_market<T, P, X>(
+    market: &Market,
+    cost_basis: CostBasis,
+    vwap_windows: &[u32],
+    ofi_window: u32,
+    fail_fast: bool,
+    forecasts: Option<&dyn ExternalForecastProvider>,
+    capabilities: &Capabilities,
+    smart_money: &SmartMoney,
+    groups: &WalletGroups,
+    fees: &FeeModel,
+    reward_settings: RewardSettings,
+    trader_provider: &T,
+    position_provider: &P,
+    transaction_provider: &X,
+) -> Result<MarketAnalysis>
+where
+    T: TraderStatsProvider + RewardProvider,
+    P: PositionProvider,
+    X: TransactionProvider,
+{
+    let condition_id = &market.condition_id;
+
+    // positions and trader stats come from the local db, skip them when it's missing
+    let holders = match capabilities.holders() {
+        true => Some(match isolate(position_provider.get_positions(condition_id).await, fail_fast)? {
+            Ok(positions) => {
+                let trader_addresses: Vec<String> = positions
+                    .iter()
+                    .map(|p| p.trader_address.clone())
+                    .collect();
+
+                let traders = match isolate(trader_provider.get_traders_by_addresses(&trader_addresses).await, fail_fast)? {
+                    Ok(mut traders) => {
+                        let weighted = match market.tags.is_empty() {
+                            true => Ok(None),
+                            false => isolate(trader_provider.get_category_stats(&trader_addresses, &market.tags).await, fail_fast)?
+                                .map(|stats| Some(category::apply_category_skill(&mut traders, &stats))),
+                        };
+
+                        Ok(TraderSections {
+                            wallet_age: wallet_age::wallet_age_breakdown(&positions, &traders),
+                            implied: implied_return::implied_returns(market, &positions, &traders, smart_money, groups, fees, clock::now()),
+                            traders,
+                            weighted,
+                        })
+                    }
+                    Err(error) => Err(error),
+                };
+
+                Ok(HolderSections {
+                    concentration: concentration::concentration(&positions),
+                    positions,
+                    traders,
+                })
+            }
+            Err(error) => Err(error),
+        }),
+        false => None,
+    };
+
+    let transactions = match capabilities.transactions {
+        true => Some(isolate(transaction_provider.get_market_transactions(condition_id).await, fail_fast)?),
+        false => None,
+    };
+
+    // supply from the positions when they loaded, its trend from the trade log
+    // smart flow only counts smart traders still holding, they're the ones with stats loaded
+    let held = holders.as_ref().and_then(|h| h.as_ref().ok());
+    let logged = transactions.as_ref().and_then(|t| t.as_ref().ok());
+    let smart: HashSet<&str> = held
+        .and_then(|h| h.traders.as_ref().ok())
+        .map(|stats| stats.traders.iter().filter(|t| smart_money.includes(t)).map(|t| t.trader_address.as_str()).collect())
+        .unwrap_or_default();
+    let open_interest = (held.is_some() || logged.is_some()).then(|| open_interest::open_interest(
+        held.map(|h| &h.positions[..]),
+        logged.map_or(&[][..], |t| &t[..]),
+        &smart,
+        open_interest::OI_WINDOW_HOURS,
+        open_interest::OI_WINDOWS,
+    ));
+    let collateral = CollateralMix::of(logged.map_or(&[][..], |t| &t[..]), held.map_or(&[][..], |h| &h.positions[..]));
+
+    // rewards only add to the pnl, a failed read leaves them out instead of failing the section
+    let rewards = match logged {
+        Some(logged) => {
+            let wallets: Vec<String> = logged.iter().map(|tx| tx.trader_address.clone()).collect::<HashSet<_>>().into_iter().collect();
+            isolate(trader_provider.get_rewards(&wallets, None).await, fail_fast)?.unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    // rebuild pnl from the trade log instead of the lifetime aggregates
+    let trades = transactions.map(|transactions| transactions.map(|transactions| {
+        let (yes_mark, no_mark) = pnl::outcome_marks(market);
+        let mut pnls = pnl::reconstruct_pnl(&transactions, cost_basis, yes_mark, no_mark, fees);
+        rewards::apply_to_pnl(&mut pnls, &rewards, condition_id, reward_settings);
+        TradeSections {
+            pnls,
+            yes_mark,
+            no_mark,
+            vwap: vwap::vwap_windows(&transactions, vwap_windows),
+            flow: order_flow::order_flow(&transactions, ofi_window, order_flow::OFI_WINDOWS),
+            transactions,
+        }
+    }));
+
+    let forecasts = match forecasts {
+        Some(provider) => Some(isolate(provider.get_forecasts(market).await, fail_fast)?),
+        None => None,
+    };
+
+    Ok(MarketAnalysis { holders, open_interest, collateral, trades, forecasts })
+}
+
+// run the analysis for every slug at once and print them side by side
+pub async fn handle_movers<M, X>(
+    min_volume: f64,
+    min_liquidity: f64,
+    limit: usize,
+    include_archived: bool,
+    market_provider: &M,
+    transaction_provider: &X,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    X: TransactionProvider,
+{
+    output::print_header("Fetching active markets");
+
+    // the api can't sort by absolute change, pull a wide pool by volume and rank it here
+    let filter = MarketFilter {
+        order: MarketOrder::Volume24h,
+        min_liquidity: Some(min_liquidity),
+        ends_before: None,
+        include_archived,
+        limit: MARKET_POOL,
+    };
+    let markets = market_provider.get_active_markets(&filter).await?;
+    println!("  Scanned {} markets", markets.len());
+
+    let mut movers = movers::top_movers(&markets, min_volume, limit);
+
+    // only the rows that made the cut, one trades request each
+    let trades = futures::future::try_join_all(movers.iter().map(|mover| {
+        transaction_provider.get_recent_transactions(&mover.condition_id, MOVER_VWAP_HOURS.div_ceil(24))
+    }))
+    .await?;
+    for (mover, transactions) in movers.iter_mut().zip(&trades) {
+        mover.add_vwap(transactions);
+    }
+
+    output::print_movers(&movers, min_volume, min_liquidity);
+
+    Ok(())
+}
+
+pub async fn handle_new_markets<M, P>(
+    window: chrono::TimeDelta,
+    min_position: f64,
+    limit: usize,
+    include_archived: bool,
+    capabilities: &Capabilities,
+    market_provider: &M,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    P: PositionProvider,
+{
+    output::print_header("Fetching newest markets");
+
+    let filter = MarketFilter {
+        order: MarketOrder::Newest,
+        min_liquidity: None,
+        ends_before: None,
+        include_archived,
+        limit: MARKET_POOL,
+    };
+    let markets = market_provider.get_active_markets(&filter).await?;
+
+    let mut recent = new_markets::recent_markets(&markets, window, clock::now());
+    recent.truncate(limit);
+
+    // positions only exist when the local db covers these markets
+    if capabilities.positions {
+        let positions = futures::future::try_join_all(recent.iter().map(|market| {
+            position_provider.get_positions(&market.condition_id)
+        }))
+        .await?;
+
+        for (market, positions) in recent.iter_mut().zip(&positions) {
+            new_markets::flag_accumulation(market, positions, min_position);
+        }
+    }
+
+    output::print_new_markets(&recent, window, min_position);
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_closing_soon<M, T, P>(
+    window: chrono::TimeDelta,
+    limit: usize,
+    include_archived: bool,
+    smart_money: &SmartMoney,
+    capabilities: &Capabilities,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    output::print_header("Fetching markets closing soon");
+    output::print_smart_money(smart_money);
+
+    let now = clock::now();
+    let filter = MarketFilter {
+        order: MarketOrder::EndingSoonest,
+        min_liquidity: None,
+        ends_before: Some(now + window),
+        include_archived,
+        limit: MARKET_POOL,
+    };
+    let markets = market_provider.get_active_markets(&filter).await?;
+
+    let mut closing: Vec<ClosingMarket> = markets
+        .iter()
+        .filter(|m| m.end_date.is_some_and(|end| end > now && end <= now + window))
+        .filter_map(ClosingMarket::from_market)
+        .collect();
+    println!("  {} markets resolve in the window", closing.len());
+
+    // smart money needs positions and trader stats from the local db
+    if capabilities.holders() {
+        let markets = &markets;
+        let holders = futures::future::try_join_all(closing.iter().map(|closing_market| async move {
+            let positions = position_provider.get_positions(&closing_market.condition_id).await?;
+            let addresses: Vec<String> = positions.iter().map(|p| p.trader_address.clone()).collect();
+            let tags = markets
+                .iter()
+                .find(|m| m.condition_id == closing_market.condition_id)
+                .map_or(&[][..], |m| m.tags.as_slice());
+            let traders = market_traders(trader_provider, &addresses, tags).await?;
+            Ok::<_, crate::error::AppError>((positions, traders))
+        }))
+        .await?;
+
+        let groups = AddressBook::load()?.wallet_groups();
+        for (market, (positions, traders)) in closing.iter_mut().zip(&holders) {
+            market.add_smart_money(positions, traders, smart_money, &groups);
+        }
+    }
+
+    closing_soon::rank_by_divergence(&mut closing);
+    closing.truncate(limit);
+    output::print_closing_soon(&closing, window, capabilities.holders());
+
+    Ok(())
+}
+
+pub async fn handle_plan_order<M, T, P>(
+    market_slug: &str,
+    request: OrderRequest,
+    config: &Config,
+    capabilities: &Capabilities,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider + OrderBookProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    check_order_request(&request)?;
+
+    output::print_header(&format!("Planning order: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+    let Some(market) = market_group.markets.first() else {
+        bail!("no markets found in {}", market_slug);
+    };
+
+    let book = market_provider.get_order_book(request.outcome.token_id(market)).await?;
+
+    // model probability is the smart money lean, only there with a local db
+    let model_probability = if capabilities.holders() {
+        let positions = position_provider.get_positions(&market.condition_id).await?;
+        let addresses: Vec<String> = positions.iter().map(|p| p.trader_address.clone()).collect();
+        let traders = market_traders(trader_provider, &addresses, &market.tags).await?;
+        let groups = AddressBook::load()?.wallet_groups();
+        implied_return::smart_lean(market.last_trade_price, &positions, &traders, &config.smart_money, &groups).map(|(lean, _)| request.outcome.probability(lean))
+    } else {
+        None
+    };
+
+    let plan = order_plan::plan_buy(&book, request, &config.fees, model_probability);
+    output::print_order_plan(market, &plan, &config.fees);
+
+    Ok(())
+}
+
+// --size and --limit-price checks shared by everything that builds an order
+fn check_order_request(request: &OrderRequest) -> Result<()> {
+    if request.shares <= 0.0 {
+        bail!("--size must be a positive number of shares");
+    }
+    if request.limit_price <= 0.0 || request.limit_price >= 1.0 {
+        bail!("--limit-price must be between 0 and 1");
+    }
+    Ok(())
+}
+
+// simulated fills against the live book, kept in the paper ledger
+pub async fn handle_paper<M>(action: PaperAction, fees: &FeeModel, market_provider: &M) -> Result<()>
+where
+    M: MarketMetadataProvider + OrderBookProvider,
+{
+    let mut ledger = PaperLedger::load()?;
+
+    match action {
+        PaperAction::Buy { market_slug, side, size, limit_price } => {
+            let request = OrderRequest { outcome: side, shares: size, limit_price };
+            check_order_request(&request)?;
+
+            output::print_header(&format!("Paper buy: {}", market_slug));
+            let market_group = market_provider.get_market_group(&market_slug).await?;
+            let Some(market) = market_group.markets.first() else {
+                bail!("no markets found in {}", market_slug);
+            };
+
+            let book = market_provider.get_order_book(request.outcome.token_id(market)).await?;
+            let plan = order_plan::plan_buy(&book, request, fees, None);
+            output::print_order_plan(market, &plan, fees);
+
+            let Some(avg_price) = plan.avg_price else {
+                bail!("nothing on the book at or below ${:.3}, no paper fill", limit_price);
+            };
+            if plan.total_cost > ledger.cash() {
+                bail!("paper fill costs ${:.2} but the account only has ${:.2}", plan.total_cost, ledger.cash());
+            }
+
+            let fill = PaperFill {
+                timestamp: clock::now().timestamp(),
+                market_slug,
+                condition_id: market.condition_id.clone(),
+                question: market.question.clone(),
+                outcome: side,
+                side: FillSide::Buy,
+                shares: plan.filled_shares,
+                price: avg_price,
+                fees: plan.fees,
+            };
+            ledger.fills.push(fill.clone());
+            ledger.save()?;
+            output::print_paper_fill(&fill, ledger.cash());
+        }
+        PaperAction::Sell { market_slug, side, size, min_price } => {
+            if size <= 0.0 {
+                bail!("--size must be a positive number of shares");
+            }
+
+            output::print_header(&format!("Paper sell: {}", market_slug));
+            let market_group = market_provider.get_market_group(&market_slug).await?;
+            let Some(market) = market_group.markets.first() else {
+                bail!("no markets found in {}", market_slug);
+            };
+
+            let held = ledger.shares_held(&market.condition_id, side);
+            if size > held {
+                bail!("paper account holds {:.2} {} shares, can't sell {:.2}", held, side.as_str(), size);
+            }
+
+            let book = market_provider.get_order_book(side.token_id(market)).await?;
+            let (sold, avg_price, fees) = paper::simulate_sell(&book, size, min_price, fees);
+            let Some(avg_price) = avg_price else {
+                bail!("no bids at or above ${:.3}, no paper fill", min_price);
+            };
+
+            let fill = PaperFill {
+                timestamp: clock::now().timestamp(),
+                market_slug,
+                condition_id: market.condition_id.clone(),
+                question: market.question.clone(),
+                outcome: side,
+                side: FillSide::Sell,
+                shares: sold,
+                price: avg_price,
+                fees,
+            };
+            ledger.fills.push(fill.clone());
+            ledger.save()?;
+            output::print_paper_fill(&fill, ledger.cash());
+        }
+        PaperAction::Status => {
+            output::print_header("Valuing paper portfolio");
+            let positions: Vec<_> = ledger.positions().into_iter().filter(|p| p.is_open()).collect();
+
+            // one gamma call per slug, several positions can share an event
+            // positions in a group that failed to load are valued at cost below
+            let slugs: Vec<String> = positions.iter().map(|p| p.market_slug.clone()).collect::<HashSet<_>>().into_iter().collect();
+            let batch = market_provider.get_market_groups(&slugs).await;
+            output::print_fetch_failures(&batch.failures);
+            let markets: Vec<_> = batch.groups.iter().flat_map(|group| group.markets.iter()).collect();
+
+            let marks: Vec<Option<f64>> = positions
+                .iter()
+                .map(|position| {
+                    let market = markets.iter().find(|m| m.condition_id == position.condition_id)?;
+                    paper::mark_price(market, position.outcome)
+                })
+                .collect();
+
+            // positions without a price count at cost so the equity curve doesn't jump around
+            let positions_value = positions
+                .iter()
+                .zip(&marks)
+                .map(|(position, mark)| mark.map_or(position.cost, |price| price * position.shares))
+                .sum();
+            ledger.snapshots.push(PaperSnapshot {
+                timestamp: clock::now().timestamp(),
+                cash: ledger.cash(),
+                positions_value,
+            });
+            ledger.save()?;
+
+            output::print_paper_portfolio(&ledger, &positions, &marks);
+        }
+        PaperAction::Reset { cash } => {
+            if cash <= 0.0 {
+                bail!("--cash must be positive");
+            }
+            PaperLedger::new(cash).save()?;
+            println!("  Paper account reset with ${:.2}", cash);
+        }
+    }
+
+    Ok(())
+}
+
+// real orders, refused unless the user passed --i-understand-the-risks
+#[cfg(feature = "trading")]
+pub async fn handle_trade<M>(
+    action: crate::cli::commands::TradeAction,
+    i_understand_the_risks: bool,
+    market_provider: &M,
+    http_client: crate::adapters::HttpClient,
+    clob_url: &str,
+    fees: &FeeModel,
+) -> Result<()>
+where
+    M: MarketMetadataProvider + OrderBookProvider,
+{
+    use crate::cli::commands::TradeAction;
+    use crate::trading::{ClobTradingClient, Credentials, OrderSide};
+
+    if !i_understand_the_risks {
+        bail!("trade sends real orders that spend real money, rerun with --i-understand-the-risks");
+    }
+
+    // fail on missing credentials before touching any market
+    let credentials = Credentials::from_env().map_err(crate::error::AppError::from)?;
+    let client = ClobTradingClient::new(http_client, credentials).with_clob_url(clob_url);
+
+    match action {
+        TradeAction::Place { market_slug, side, size, limit_price } => {
+            let request = OrderRequest { outcome: side, shares: size, limit_price };
+            check_order_request(&request)?;
+
+            output::print_header(&format!("Placing order: {}", market_slug));
+            let market_group = market_provider.get_market_group(&market_slug).await?;
+            let Some(market) = market_group.markets.first() else {
+                bail!("no markets found in {}", market_slug);
+            };
+
+            // same numbers plan-order shows, so the user sees what the fill should look like
+            let token_id = request.outcome.token_id(market);
+            let book = market_provider.get_order_book(token_id).await?;
+            let plan = order_plan::plan_buy(&book, request, fees, None);
+            output::print_order_plan(market, &plan, fees);
+
+            let placed = client.place_limit_order(token_id, OrderSide::Buy, limit_price, size).await?;
+            output::print_placed_order(&placed, client.credentials());
+        }
+        TradeAction::Cancel { order_id } => {
+            output::print_header(&format!("Cancelling order: {}", order_id));
+            let result = client.cancel_order(&order_id).await?;
+            output::print_cancel_result(&result);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_compare<M, T, P>(
+    market_slugs: &[String],
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    output::print_header(&format!("Comparing {} market groups", market_slugs.len()));
+    output::print_smart_money(smart_money);
+
+    let (summaries, failures) = summarize_batch(market_slugs, smart_money, market_provider, trader_provider, position_provider).await?;
+    output::print_fetch_failures(&failures);
+    if summaries.is_empty() && !failures.is_empty() {
+        bail!("none of the {} market groups could be loaded", market_slugs.len());
+    }
+
+    output::print_comparison(&summaries);
+
+    Ok(())
+}
+
+// poll every slug until count runs out, one failed poll doesn't stop the monitor
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_monitor<M, T, P>(
+    market_slugs: &[String],
+    interval: Duration,
+    count: Option<u32>,
+    metrics_addr: Option<SocketAddr>,
+    archive_books: Option<u32>,
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider + OrderBookProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    output::print_header(&format!("Monitoring {} market groups every {}s", market_slugs.len(), interval.as_secs()));
+    output::print_smart_money(smart_money);
+
+    let metrics_state: Option<MetricsState> = match metrics_addr {
+        Some(addr) => {
+            let state = Arc::new(RwLock::new(String::new()));
+            metrics::serve_metrics(addr, Arc::clone(&state)).await?;
+            Some(state)
+        }
+        None => None,
+    };
+
+    let mut tracker = AlertTracker::new();
+    let mut polls = 0;
+    loop {
+        let result = summarize_slugs(market_slugs, smart_money, market_provider, trader_provider, position_provider).await;
+
+        let now = chrono::Utc::now();
+        match result {
+            Ok((summaries, failures)) => {
+                output::print_monitor_poll(&summaries, now);
+                output::print_fetch_failures(&failures);
+                output::print_alerts(&poll_alerts(&mut tracker, &summaries, now));
+                record_liquidity(&summaries, now);
+                // the first poll always archives, then every nth
+                if archive_books.is_some_and(|every| polls % every.max(1) == 0) {
+                    archive_order_books(market_slugs, market_provider, now).await;
+                }
+
+                if let Some(state) = &metrics_state
+                    && let Ok(mut body) = state.write()
+                {
+                    *body = metrics::render(&summaries, now.timestamp());
+                }
+            }
+            Err(e) => println!("  {} poll failed: {:#}", format::clock(now), e),
+        }
+
+        polls += 1;
+        if count.is_some_and(|count| polls >= count) {
+            return Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+// polls the wallets' transactions across every market and reports each position opened or closed,
+// with a link to the market and the same summary monitor prints for it
+pub async fn handle_monitor_traders<M, T, P>(
+    addresses: &[String],
+    interval: Duration,
+    count: Option<u32>,
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider + TransactionProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    output::print_header(&format!("Monitoring {} traders every {}s", addresses.len(), interval.as_secs()));
+    output::print_smart_money(smart_money);
+    let book = AddressBook::load()?;
+
+    // what they hold going in, so the first poll doesn't report their whole history
+    let since = clock::now().timestamp() - TRADER_WATCH_LOOKBACK_DAYS * 24 * 60 * 60;
+    let mut watch = TraderWatch::new(since);
+    watch.observe(&market_provider.get_wallet_transactions(addresses, since).await?);
+    println!("  Positions learned from the last {} days, reporting changes from here on\n", TRADER_WATCH_LOOKBACK_DAYS);
+
+    let mut polls = 0;
+    loop {
+        tokio::time::sleep(interval).await;
+        let now = chrono::Utc::now();
+        match market_provider.get_wallet_transactions(addresses, watch.cursor()).await {
+            Ok(transactions) => {
+                let activity = watch.observe(&transactions);
+                if let Err(e) = report_trader_activity(&activity, &book, smart_money, market_provider, trader_provider, position_provider).await {
+                    println!("  {} markets for trader activity failed to load: {:#}", format::clock(now), e);
+                }
+            }
+            Err(e) => println!("  {} poll failed: {:#}", format::clock(now), e),
+        }
+
+        polls += 1;
+        if count.is_some_and(|count| polls >= count) {
+            return Ok(());
+        }
+    }
+}
+
+// each change with its market, the market summarized once however many wallets moved in it
+// a market gamma no longer serves is still reported, by condition id
+async fn report_trader_activity<M, T, P>(
+    activity: &[TraderActivity],
+    book: &AddressBook,
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    if activity.is_empty() {
+        return Ok(());
+    }
+
+    let mut ids: Vec<String> = activity.iter().map(|a| a.transaction.market_id.clone()).collect();
+    ids.sort();
+    ids.dedup();
+    let markets = market_provider.get_markets_by_condition_ids(&ids).await?;
+    let groups = book.wallet_groups();
+
+    let mut summaries: HashMap<&str, MarketSummary> = HashMap::new();
+    for market in &markets {
+        let positions = position_provider.get_positions(&market.condition_id).await?;
+        let holders: Vec<String> = positions.iter().map(|p| p.trader_address.clone()).collect();
+        let traders = market_traders(trader_provider, &holders, &market.tags).await?;
+        let summary = compare::summarize_market(&market.slug, market, &positions, &traders, smart_money, &groups);
+        summaries.insert(market.condition_id.as_str(), summary);
+    }
+
+    for change in activity {
+        let market = markets.iter().find(|m| m.condition_id == change.transaction.market_id);
+        output::print_trader_activity(change, market, summaries.get(change.transaction.market_id.as_str()), book);
+    }
+    Ok(())
+}
+
+// same polling as monitor but every snapshot and alert goes out over grpc
+#[cfg(feature = "grpc")]
+pub async fn handle_grpc<M, T, P>(
+    market_slugs: &[String],
+    addr: SocketAddr,
+    interval: Duration,
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    use crate::cli::grpc;
+
+    output::print_header(&format!("Streaming {} market groups over grpc every {}s", market_slugs.len(), interval.as_secs()));
+    output::print_smart_money(smart_money);
+    let (events, server) = grpc::spawn_server(addr);
+    println!("  Listening on {}", addr);
+
+    let mut tracker = AlertTracker::new();
+    loop {
+        // a bind failure or crash ends the server task early
+        if server.is_finished() {
+            server.await??;
+            bail!("grpc server stopped");
+        }
+
+        let now = chrono::Utc::now();
+        match summarize_slugs(market_slugs, smart_money, market_provider, trader_provider, position_provider).await {
+            Ok((summaries, failures)) => {
+                let alerts = poll_alerts(&mut tracker, &summaries, now);
+                output::print_monitor_poll(&summaries, now);
+                output::print_fetch_failures(&failures);
+                output::print_alerts(&alerts);
+                record_liquidity(&summaries, now);
+
+                // no subscribers is fine, the send error only means nobody is listening
+                for summary in &summaries {
+                    let _ = events.send(grpc::snapshot_event(summary, now.timestamp()));
+                }
+                for alert in &alerts {
+                    let _ = events.send(grpc::alert_event(alert, now.timestamp()));
+                }
+            }
+            Err(e) => println!("  {} poll failed: {:#}", format::clock(now), e),
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+// alerts against the last poll saved in the state db, a restarted or second monitor doesn't repeat the ones already shown
+// without the db it falls back to this run's own polls
+fn poll_alerts(tracker: &mut AlertTracker, summaries: &[MarketSummary], now: chrono::DateTime<chrono::Utc>) -> Vec<Alert> {
+    let db = StateDb::open().and_then(|db| {
+        tracker.restore(db.alert_baselines()?);
+        Ok(db)
+    });
+    let alerts = tracker.update(summaries);
+    if let Err(e) = db.and_then(|db| db.save_alert_baselines(summaries)) {
+        println!("  {} alert state not saved: {}", format::clock(now), e);
+    }
+    alerts
+}
+
+// append each summary's liquidity and spread to the log liquidity-history reads
+// a failed write is reported but never stops the polling
+fn record_liquidity(summaries: &[MarketSummary], now: chrono::DateTime<chrono::Utc>) {
+    let points: Vec<_> = summaries
+        .iter()
+        .map(|summary| (summary.slug.as_str(), LiquidityPoint {
+            timestamp: now.timestamp(),
+            liquidity: summary.liquidity,
+            spread: summary.spread,
+            yes_price: summary.yes_price,
+        }))
+        .collect();
+    let result = StateDb::open().and_then(|db| db.record_liquidity(&points));
+    if let Err(e) = result {
+        println!("  {} liquidity history not saved: {}", format::clock(now), e);
+    }
+}
+
+// each group's primary YES book into the archive book-history reads, the market the poll summarized
+// a failed fetch or write is reported but never stops the polling
+async fn archive_order_books<M>(market_slugs: &[String], market_provider: &M, now: chrono::DateTime<chrono::Utc>)
+where
+    M: MarketMetadataProvider + OrderBookProvider,
+{
+    let archive = match StateDb::open() {
+        Ok(archive) => archive,
+        Err(e) => {
+            println!("  {} books not archived: {}", format::clock(now), e);
+            return;
+        }
+    };
+    let batch = market_provider.get_market_groups(market_slugs).await;
+    for group in &batch.groups {
+        let Some(market) = group.markets.first() else {
+            continue;
+        };
+        let archived = match market_provider.get_order_book(&market.yes_token_id).await {
+            Ok(book) => archive.append_book(&group.slug, &BookSnapshot::new(now.timestamp(), book)),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = archived {
+            println!("  {} {} book not archived: {}", format::clock(now), group.slug, e);
+        }
+    }
+}
+
+// spread and depth near the mid over the books monitor archived
+pub fn handle_book_history(market_slug: &str, limit: usize) -> Result<()> {
+    let snapshots = StateDb::open()?.book_snapshots(market_slug)?;
+    if snapshots.is_empty() {
+        bail!("no archived books for {}, run `monitor {} --archive-books 1` to start archiving", market_slug, market_slug);
+    }
+
+    let points = book_history::book_history(&snapshots, &DEPTH_DISTANCES);
+    output::print_book_history(market_slug, &points, &DEPTH_DISTANCES, limit);
+
+    Ok(())
+}
+
+// when liquidity came into or left a market, from what monitor recorded
+pub fn handle_liquidity_history(market_slug: &str, threshold: f64, limit: usize) -> Result<()> {
+    if threshold <= 0.0 {
+        bail!("--threshold must be above 0, got {}", threshold);
+    }
+
+    let points = StateDb::open()?.liquidity_points(market_slug)?;
+    if points.is_empty() {
+        bail!("no liquidity history for {}, run `monitor {}` to start recording", market_slug, market_slug);
+    }
+
+    let mut shifts = liquidity::liquidity_shifts(&points, threshold);
+    let total = shifts.len();
+    // newest first, the latest moves matter most
+    shifts.reverse();
+    shifts.truncate(limit);
+    output::print_liquidity_history(market_slug, &points, &shifts, total, threshold);
+
+    Ok(())
+}
+
+// summaries for every slug at once, groups without markets are dropped
+// slugs gamma couldn't load come back with their error next to the summaries of the rest
+pub async fn summarize_slugs<M, T, P>(
+    market_slugs: &[String],
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<(Vec<MarketSummary>, Vec<(String, crate::error::AppError)>)>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    let (summaries, failures) = summarize_batch(market_slugs, smart_money, market_provider, trader_provider, position_provider).await?;
+    Ok((summaries.into_iter().flatten().collect(), failures))
+}
+
+// one summary per loaded group, None for groups without markets
+async fn summarize_batch<M, T, P>(
+    market_slugs: &[String],
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<(Vec<Option<MarketSummary>>, Vec<(String, crate::error::AppError)>)>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    let batch = market_provider.get_market_groups(market_slugs).await;
+    let groups = AddressBook::load()?.wallet_groups();
+    let summaries = workers::try_join_all(batch.groups.iter().map(|group| {
+        summarize_group(group, smart_money, &groups, trader_provider, position_provider)
+    }))
+    .await?;
+
+    Ok((summaries, batch.failures))
+}
+
+// monitor --traders only follows the watchlist
+fn watched_traders() -> Result<Vec<String>> {
+    let watched: Vec<String> = Watchlist::load()?.traders.into_iter().collect();
+    if watched.is_empty() {
+        bail!("the watchlist has no traders, add some with `watchlist add --trader <address>`");
+    }
+    Ok(watched)
+}
+
+// explicit slugs win, otherwise everything on the watchlist
+fn slugs_or_watchlist(market_slugs: Vec<String>) -> Result<Vec<String>> {
+    if !market_slugs.is_empty() {
+        return Ok(market_slugs);
+    }
+
+    let watched: Vec<String> = Watchlist::load()?.markets.into_iter().collect();
+    if watched.is_empty() {
+        bail!("no slugs given and the watchlist has no markets, add some with `watchlist add --market <slug>`");
+    }
+    Ok(watched)
+}
+
+// primary market summary for one group, None when the group has no markets
+async fn summarize_group<T, P>(
+    market_group: &MarketGroup,
+    smart_money: &SmartMoney,
+    groups: &WalletGroups,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<Option<MarketSummary>>
+where
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    let market_slug = &market_group.slug;
+    let Some(market) = market_group.markets.first() else {
+        return Ok(None);
+    };
+
+    let positions = position_provider.get_positions(&market.condition_id).await?;
+    let addresses: Vec<String> = positions.iter().map(|p| p.trader_address.clone()).collect();
+    let traders = market_traders(trader_provider, &addresses, &market.tags).await?;
+
+    Ok(Some(compare::summarize_market(market_slug, market, &positions, &traders, smart_money, groups)))
+}
+
+// holders' stats with the accuracy used for weighting taken from their record in the market's tags
+pub(crate) async fn market_traders<T: TraderStatsProvider>(
+    trader_provider: &T,
+    addresses: &[String],
+    tags: &[String],
+) -> crate::error::Result<Vec<Trader>> {
+    let mut traders = trader_provider.get_traders_by_addresses(addresses).await?;
+    if !tags.is_empty() {
+        let stats = trader_provider.get_category_stats(addresses, tags).await?;
+        category::apply_category_skill(&mut traders, &stats);
+    }
+    Ok(traders)
+}
+
+// expose the analysis over http until interrupted
+pub async fn handle_serve<M, D>(addr: SocketAddr, report_version: u32, config: &Config, market_provider: &M, db: &D) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    D: TraderStatsProvider + PositionProvider + TransactionProvider,
+{
+    output::print_header("REST API");
+    output::print_smart_money(&config.smart_money);
+    server::serve(addr, report_version, &config.smart_money, &config.fees, market_provider, db).await?;
+    Ok(())
+}
+
+// local only, never touches a provider
+pub fn handle_watchlist(action: WatchlistAction) -> Result<()> {
+    let mut watchlist = Watchlist::load()?;
+
+    match action {
+        WatchlistAction::Add { markets, traders } => {
+            if markets.is_empty() && traders.is_empty() {
+                bail!("nothing to add, pass --market <slug> or --trader <address>");
+            }
+            for slug in &markets {
+                if watchlist.markets.insert(slug.clone()) {
+                    println!("  Watching market {}", slug);
+                }
+            }
+            for address in &traders {
+                if watchlist.add_trader(address) {
+                    println!("  Watching trader {}", address);
+                }
+            }
+            watchlist.save()?;
+        }
+        WatchlistAction::Remove { markets, traders } => {
+            for slug in &markets {
+                if watchlist.markets.remove(slug) {
+                    println!("  Stopped watching market {}", slug);
+                } else {
+                    println!("  Market {} was not on the watchlist", slug);
+                }
+            }
+            for address in &traders {
+                if watchlist.remove_trader(address) {
+                    println!("  Stopped watching trader {}", address);
+                } else {
+                    println!("  Trader {} was not on the watchlist", address);
+                }
+            }
+            watchlist.save()?;
+        }
+        WatchlistAction::List => output::print_watchlist(&watchlist, &AddressBook::load()?),
+    }
+
+    Ok(())
+}
+
+// slugs are baked in when the script is generated, rerun after changing the watchlist
+pub fn handle_completions(shell: clap_complete::Shell) -> Result<()> {
+    let watchlist = Watchlist::load()?;
+    let slugs: Vec<String> = watchlist.markets.into_iter().collect();
+
+    let mut command = Cli::command();
+    if !slugs.is_empty() {
+        command = with_slug_hints(command, &slugs);
+        command = command.mut_subcommand("watchlist", |watchlist| {
+            watchlist.mut_subcommand("remove", |remove| {
+                remove.mut_arg("markets", |arg| arg.value_parser(PossibleValuesParser::new(slugs.clone())))
+            })
+        });
+    }
+
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+    Ok(())
+}
+
+// draft 7 schemas generated from the types the outputs are serialized from
+pub fn handle_schema(target: SchemaTarget, report_version: u32) -> Result<()> {
+    let schema = |target| match target {
+        SchemaTarget::Report => output::report_schema_as_version(json!(schemars::schema_for!(server::AnalysisReport)), report_version),
+        SchemaTarget::Alert => json!(schemars::schema_for!(Alert)),
+        SchemaTarget::Snapshot => json!(schemars::schema_for!(MarketSummary)),
+        SchemaTarget::Error => json!(schemars::schema_for!(output::ErrorReport)),
+        SchemaTarget::All => Value::Null,
+    };
+
+    let document = match target {
+        SchemaTarget::All => json!({
+            "report": schema(SchemaTarget::Report),
+            "alert": schema(SchemaTarget::Alert),
+            "snapshot": schema(SchemaTarget::Snapshot),
+            "error": schema(SchemaTarget::Error),
+        }),
+        target => schema(target),
+    };
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+// every market slug argument, in every subcommand, suggests the watched slugs
+// only used for generating the script so parsing still takes any slug
+fn with_slug_hints(mut command: clap::Command, slugs: &[String]) -> clap::Command {
+    let slug_args: Vec<String> = command
+        .get_arguments()
+        .map(|arg| arg.get_id().to_string())
+        .filter(|id| id == "market_slug" || id == "market_slugs")
+        .collect();
+    for id in slug_args {
+        command = command.mut_arg(id, |arg| arg.value_parser(PossibleValuesParser::new(slugs.to_vec())));
+    }
+
+    let subcommands: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |sub| with_slug_hints(sub, slugs));
+    }
+    command
+}
+
+pub fn handle_label(action: LabelAction) -> Result<()> {
+    let mut book = AddressBook::load()?;
+
+    match action {
+        LabelAction::Set { address, label } => {
+            if label.trim().is_empty() {
+                bail!("label can't be empty, use `label remove` to drop one");
+            }
+            match book.set(&address, &label) {
+                Some(previous) => println!("  Relabeled {} from \"{}\" to \"{}\"", address, previous, label.trim()),
+                None => println!("  Labeled {} as \"{}\"", address, label.trim()),
+            }
+            book.save()?;
+        }
+        LabelAction::Remove { address } => {
+            match book.remove(&address) {
+                Some(label) => println!("  Removed label \"{}\" from {}", label, address),
+                None => println!("  {} has no label", address),
+            }
+            book.save()?;
+        }
+        LabelAction::Group { name, address } => {
+            if name.trim().is_empty() {
+                bail!("group name can't be empty");
+            }
+            let moved = book.group(&name, &address);
+            println!("  Group \"{}\" now has {} addresses", name.trim(), book.members(&name).len());
+            if moved > 0 {
+                println!("  Moved {} addresses out of other groups", moved);
+            }
+            book.save()?;
+        }
+        LabelAction::Ungroup { address } => {
+            match book.ungroup(&address) {
+                Some(name) => println!("  Took {} out of group \"{}\"", address, name),
+                None => println!("  {} isn't in a group", address),
+            }
+            book.save()?;
+        }
+        LabelAction::List => output::print_address_book(&book),
+    }
+
+    Ok(())
+}
+
+// replay local history with a follow the smart money strategy
+pub async fn handle_backtest<X, R>(
+    config: &BacktestConfig,
+    transaction_provider: &X,
+    resolution_provider: &R,
+) -> Result<()>
+where
+    X: TransactionProvider,
+    R: ResolutionProvider,
+{
+    output::print_header("LOADING HISTORY");
+    let transactions = transaction_provider.get_all_transactions().await?;
+    println!("  Found {} transactions", transactions.len());
+
+    let resolutions = resolution_provider.get_resolutions().await?;
+    println!("  Found {} resolved markets", resolutions.len());
+
+    let report = backtest::run_backtest(config, &transactions, &resolutions);
+    output::print_backtest_report(&report);
+
+    Ok(())
+}
+
+// score what smart money would have predicted before each resolved market closed
+pub async fn handle_calibration<T, X, R>(
+    config: &CalibrationConfig,
+    trader_provider: &T,
+    transaction_provider: &X,
+    resolution_provider: &R,
+) -> Result<()>
+where
+    T: TraderStatsProvider,
+    X: TransactionProvider,
+    R: ResolutionProvider,
+{
+    output::print_header("LOADING HISTORY");
+    let snapshots = trader_provider.get_trader_snapshots().await?;
+    println!("  Found {} trader stats snapshots", snapshots.len());
+
+    let transactions = transaction_provider.get_all_transactions().await?;
+    println!("  Found {} transactions", transactions.len());
+
+    let resolutions = resolution_provider.get_resolutions().await?;
+    println!("  Found {} resolved markets", resolutions.len());
+
+    let report = calibration::run_calibration(config, &snapshots, &transactions, &resolutions);
+    output::print_calibration_report(&report);
+
+    Ok(())
+}
+
+// categorized counts of suspicious rows in the local tables
+pub async fn handle_audit_db<T, P, X>(
+    examples: usize,
+    trader_provider: &T,
+    position_provider: &P,
+    transaction_provider: &X,
+) -> Result<()>
+where
+    T: TraderStatsProvider,
+    P: PositionProvider,
+    X: TransactionProvider,
+{
+    output::print_header("LOADING LOCAL DATA");
+    let positions = position_provider.get_all_positions().await?;
+    let transactions = transaction_provider.get_all_transactions().await?;
+    let traders = trader_provider.get_traders(0).await?;
+
+    let report = audit::audit(&positions, &transactions, &traders, examples);
+    output::print_audit_report(&report);
+
+    Ok(())
+}
+
+// rewrite the local tables after many small ingests have fragmented them
+pub async fn handle_compact<S>(row_group_size: usize, store: &S) -> Result<()>
+where
+    S: DataStore,
+{
+    if row_group_size == 0 {
+        bail!("--row-group-size must be at least 1");
+    }
+
+    output::print_header("COMPACTING LOCAL DB");
+    let compactions = store.compact(row_group_size).await?;
+    output::print_compaction(&compactions);
+
+    Ok(())
+}
+
+// traders ranked on their record, within one tag when a category is given
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_leaderboard<X, R, G, W>(
+    category: Option<&str>,
+    limit: usize,
+    config: &Config,
+    capabilities: &Capabilities,
+    names: Option<&NameResolver>,
+    transaction_provider: &X,
+    resolution_provider: &R,
+    tag_provider: &G,
+    reward_provider: &W,
+) -> Result<()>
+where
+    X: TransactionProvider,
+    R: ResolutionProvider,
+    G: TagProvider,
+    W: RewardProvider,
+{
+    output::print_header("LOADING HISTORY");
+    let transactions = transaction_provider.get_all_transactions().await?;
+    println!("  Found {} transactions", transactions.len());
+
+    let resolutions = resolution_provider.get_resolutions().await?;
+    println!("  Found {} resolved markets", resolutions.len());
+
+    let tags = match category {
+        Some(_) if !capabilities.tags => bail!("no market tags in the local db, run `ingest tags` first"),
+        Some(_) => tag_provider.get_market_tags().await?,
+        None => Vec::new(),
+    };
+    let markets = category.map(|category| category::markets_in_category(&tags, category));
+    if let (Some(category), Some(markets)) = (category, &markets) {
+        println!("  {} markets tagged {}", markets.len(), category);
+    }
+
+    let mix = CollateralMix::of(&transactions, &[]);
+    if mix.is_mixed() {
+        output::print_mixed_collateral(&mix, &config.collateral);
+    }
+
+    let mut traders = category::leaderboard(&transactions, &resolutions, markets.as_ref(), config.smart_money.min_resolved);
+
+    // a category only counts payouts for its own markets, those without a market can't be placed in one
+    let addresses: Vec<String> = traders.iter().map(|t| t.trader_address.clone()).collect();
+    let mut payouts = reward_provider.get_rewards(&addresses, None).await?;
+    if let Some(markets) = &markets {
+        payouts.retain(|reward| reward.market_id.as_deref().is_some_and(|id| markets.contains(id)));
+    }
+    rewards::apply_to_traders(&mut traders, &payouts, config.rewards);
+
+    let mut book = AddressBook::load()?;
+    let top: Vec<String> = traders.iter().take(limit).map(|t| t.trader_address.clone()).collect();
+    resolve_names(&mut book, names, &top).await;
+    output::print_leaderboard(&traders, category, limit, config.smart_money.min_resolved, &book);
+
+    Ok(())
+}
+
+// one wallet's markets and results per category
+pub async fn handle_portfolio<M, X, R, G>(
+    trader_address: &str,
+    capabilities: &Capabilities,
+    market_provider: &M,
+    transaction_provider: &X,
+    resolution_provider: &R,
+    tag_provider: &G,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    X: TransactionProvider,
+    R: ResolutionProvider,
+    G: TagProvider,
+{
+    let book = AddressBook::load()?;
+    output::print_header(&format!("Portfolio: {}", book.display(trader_address)));
+
+    let transactions = transaction_provider.get_all_transactions().await?;
+    let resolutions = resolution_provider.get_resolutions().await?;
+    let tags = if capabilities.tags {
+        tag_provider.get_market_tags().await?
+    } else {
+        println!("  No market tags in the local db, run `ingest tags` to split by category");
+        Vec::new()
+    };
+
+    // a grouped wallet or a group name covers every member, their fills are read as one trader's
+    let members = book.members(trader_address);
+    if members.len() > 1 {
+        println!("  Wallet group: {} addresses", members.len());
+        for member in &members {
+            println!("    {}", member);
+        }
+    }
+    let wallet: Vec<_> = transactions
+        .iter()
+        .filter(|tx| members.iter().any(|m| tx.trader_address.eq_ignore_ascii_case(m)))
+        .map(|tx| Transaction { trader_address: trader_address.to_string(), ..tx.clone() })
+        .collect();
+
+    // the overall record, streaks and drawdown included, over every market the wallet traded
+    let record = ingest::compute_trader_stats(&wallet, &resolutions).into_iter().next();
+    output::print_trader_record(record.as_ref());
+
+    let exposures = category::portfolio(trader_address, &wallet, &resolutions, &tags);
+    output::print_portfolio(&exposures);
+
+    let history = trader_history::market_history(trader_address, &wallet, &resolutions, &tags);
+    // questions read better than condition ids, but the table still works without them
+    let mut questions: HashMap<String, String> = HashMap::new();
+    let ids: Vec<String> = history.iter().map(|r| r.market_id.clone()).collect();
+    for batch in ids.chunks(HISTORY_LOOKUP_BATCH) {
+        match market_provider.get_markets_by_condition_ids(batch).await {
+            Ok(markets) => questions.extend(markets.into_iter().map(|m| (m.condition_id, m.question))),
+            Err(e) => {
+                println!("  Market questions unavailable ({}), showing condition ids", e);
+                break;
+            }
+        }
+    }
+    output::print_market_history(&history, &questions);
+
+    Ok(())
+}
+
+// what analyze runs after its own sections, the wasm plugins from --plugins or the plugins dir, then each --script
+fn load_analyzers(plugins: Option<String>, scripts: &[String]) -> Result<Vec<Box<dyn Analyzer>>> {
+    let mut analyzers: Vec<Box<dyn Analyzer>> = Vec::new();
+    #[cfg(feature = "plugins")]
+    {
+        use crate::adapters::wasm_plugin;
+        let dir = plugins.map_or_else(wasm_plugin::default_plugin_dir, std::path::PathBuf::from);
+        for plugin in wasm_plugin::load_plugins(&dir)? {
+            analyzers.push(Box::new(plugin));
+        }
+    }
+    #[cfg(not(feature = "plugins"))]
+    if let Some(dir) = plugins {
+        bail!("--plugins {} needs a build with the plugins feature", dir);
+    }
+    for script in scripts {
+        analyzers.push(Box::new(ScriptAnalyzer::load(Path::new(script))?));
+    }
+    Ok(analyzers)
+}
+
+// bucket a market's trades by weekday and hour, optionally only smart traders
+// look up names for the addresses about to be printed, only when --resolve-names is on
+// text or json lines on stdout, plus whatever extra sinks the analyze flags ask for
+fn analysis_bus(output_format: OutputFormat, html: Option<String>, webhook: Option<WebhookTarget>, webhooks: &[WebhookTarget], http_client: &HttpClient) -> AnalysisBus {
+    let mut bus = match output_format {
+        OutputFormat::Text => AnalysisBus::new().subscribe(TerminalSink),
+        OutputFormat::Json => AnalysisBus::new().subscribe(JsonSink),
+        OutputFormat::Arrow => AnalysisBus::new().subscribe(ArrowSink::stdout()),
+    };
+    if let Some(path) = html {
+        bus = bus.subscribe(HtmlSink::new(path));
+    }
+    for target in webhook.into_iter().chain(webhooks.iter().cloned()) {
+        bus = bus.subscribe(WebhookSink::new(http_client.clone(), target));
+    }
+    bus
+}
+
+async fn resolve_names(book: &mut AddressBook, names: Option<&NameResolver>, addresses: &[String]) {
+    if let Some(resolver) = names {
+        book.add_resolved(resolver.resolve_many(addresses).await);
+    }
+}
+
+pub async fn handle_heatmap<M, T, X>(
+    market_slug: &str,
+    days: u32,
+    smart_filter: Option<&SmartMoney>,
+    market_provider: &M,
+    trader_provider: &T,
+    transaction_provider: &X,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    X: TransactionProvider,
+{
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    // same primary market choice as analyze
+    let Some(market) = market_group.markets.first() else {
+        println!("  No markets found in this group\n");
+        return Ok(());
+    };
+    println!("  Market: {}", market.question);
+
+    let transactions = transaction_provider.get_recent_transactions(&market.condition_id, days).await?;
+    println!("  Found {} transactions in the last {} days", transactions.len(), days);
+
+    let smart = match smart_filter {
+        Some(smart_money) => {
+            let addresses: Vec<String> = transactions
+                .iter()
+                .map(|tx| tx.trader_address.clone())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+
+            let smart: HashSet<String> = trader_provider
+                .get_traders_by_addresses(&addresses)
+                .await?
+                .into_iter()
+                .filter(|t| smart_money.includes(t))
+                .map(|t| t.trader_address)
+                .collect();
+            println!("  {} of {} traders pass the smart money filter ({})", smart.len(), addresses.len(), smart_money.describe());
+            Some(smart)
+        }
+        None => None,
+    };
+
+    let heatmap = heatmap::build_heatmap(&transactions, smart.as_ref(), format::wall_clock_seconds);
+    output::print_heatmap(&heatmap);
+
+    Ok(())
+}
+
+// a market's largest recent trades, with who made them and whether they opened, added to or cut a position
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_big_trades<M, T, X>(
+    market_slug: &str,
+    min_usdc: f64,
+    days: u32,
+    limit: usize,
+    smart_money: &SmartMoney,
+    names: Option<&NameResolver>,
+    market_provider: &M,
+    trader_provider: &T,
+    transaction_provider: &X,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    X: TransactionProvider,
+{
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    // same primary market choice as analyze
+    let Some(market) = market_group.markets.first() else {
+        println!("  No markets found in this group\n");
+        return Ok(());
+    };
+    println!("  Market: {}", market.question);
+
+    let recent = transaction_provider.get_recent_transactions(&market.condition_id, days).await?;
+    println!("  Found {} transactions in the last {} days", recent.len(), days);
+
+    // the whole history tells a first buy apart from adding to a position taken earlier
+    let history = transaction_provider.get_market_transactions(&market.condition_id).await?;
+    let mut trades = big_trades::big_trades(&history, &recent, min_usdc);
+    let total = trades.len();
+    trades.truncate(limit);
+
+    let addresses: Vec<String> = trades
+        .iter()
+        .map(|t| t.transaction.trader_address.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let traders: HashMap<String, Trader> = trader_provider
+        .get_traders_by_addresses(&addresses)
+        .await?
+        .into_iter()
+        .map(|t| (t.trader_address.clone(), t))
+        .collect();
+
+    let mut book = AddressBook::load()?;
+    resolve_names(&mut book, names, &addresses).await;
+    output::print_big_trades(&trades, total, min_usdc, &traders, smart_money, &book);
+
+    Ok(())
+}
+
+// the primary market's fills printed in the order they happened, the time between them shrunk by speed
+// smart money and large first buys are called out as they come in
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_replay<M, T, X>(
+    market_slug: &str,
+    speed: f64,
+    min_usdc: f64,
+    capabilities: &Capabilities,
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    transaction_provider: &X,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    X: TransactionProvider,
+{
+    if !capabilities.transactions {
+        bail!("no transactions in the local db to replay, run `ingest trades` first");
+    }
+
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    // same primary market choice as analyze
+    let Some(market) = market_group.markets.first() else {
+        println!("  No markets found in this group\n");
+        return Ok(());
+    };
+
+    let transactions = transaction_provider.get_market_transactions(&market.condition_id).await?;
+    let addresses: Vec<String> = transactions
+        .iter()
+        .map(|tx| tx.trader_address.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let traders = trader_provider.get_traders_by_addresses(&addresses).await?;
+    let smart: HashSet<&str> = traders.iter().filter(|t| smart_money.includes(t)).map(|t| t.trader_address.as_str()).collect();
+
+    let tape = tape::tape(&transactions, &smart, min_usdc);
+    if tape.prints.is_empty() {
+        bail!("no timed trades to replay for {}", market_slug);
+    }
+
+    let book = AddressBook::load()?;
+    output::print_replay_start(market, &tape, speed, min_usdc);
+    let mut previous: Option<i64> = None;
+    for print in &tape.prints {
+        let timestamp = print.transaction.timestamp.unwrap_or_default();
+        if let Some(previous) = previous {
+            let pause = ((timestamp - previous) as f64 / speed).min(MAX_REPLAY_PAUSE_SECS);
+            if pause > 0.0 {
+                tokio::time::sleep(Duration::from_secs_f64(pause)).await;
+            }
+        }
+        output::print_tape_print(print, &book);
+        previous = Some(timestamp);
+    }
+    output::print_replay_end(&tape);
+
+    Ok(())
+}
+
+// holders scored on how much they look like someone trading on inside knowledge
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_insiders<M, T, P, X>(
+    market_slug: &str,
+    min_score: f64,
+    limit: usize,
+    names: Option<&NameResolver>,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+    transaction_provider: &X,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+    X: TransactionProvider,
+{
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    // same primary market choice as analyze
+    let Some(market) = market_group.markets.first() else {
+        println!("  No markets found in this group\n");
+        return Ok(());
+    };
+    println!("  Market: {}", market.question);
+
+    let positions = position_provider.get_positions(&market.condition_id).await?;
+    let addresses: Vec<String> = positions
+        .iter()
+        .map(|p| p.trader_address.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let traders = trader_provider.get_traders_by_addresses(&addresses).await?;
+    // buy times tell how close to the end each wallet got in
+    let transactions = transaction_provider.get_market_transactions(&market.condition_id).await?;
+
+    let report = insider::insider_signals(market, &positions, &traders, &transactions);
+    let shown: Vec<String> = report.flagged(min_score).take(limit).map(|s| s.trader_address.clone()).collect();
+    let mut book = AddressBook::load()?;
+    resolve_names(&mut book, names, &shown).await;
+    output::print_insiders(market, &report, min_score, limit, &book);
+
+    Ok(())
+}
+
+// polymarket's prices next to the bookmakers' consensus on the same event, gaps over the threshold flagged
+pub async fn handle_parity<M>(
+    market_slug: &str,
+    sport: &str,
+    threshold: f64,
+    odds: Option<&dyn ExternalOddsProvider>,
+    market_provider: &M,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+{
+    let Some(odds) = odds else {
+        bail!("parity reads bookmaker lines from an odds api, pass a key with --odds-api-key");
+    };
+
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+    let events = odds.get_external_odds(sport).await?;
+    println!("  {} events with odds for {}", events.len(), sport);
+
+    match line_parity::compare(&market_group, &events, threshold) {
+        Some(report) => output::print_parity(&report),
+        None => println!("  No {} event matches the outcomes of {}\n", sport, market_group.title),
+    }
+    Ok(())
+}
+
+// crypto strike markets next to a binary option's value at the current spot, one spot and volatility per asset
+pub async fn handle_crypto<M>(
+    market_slug: &str,
+    vol_days: u32,
+    volatility: Option<f64>,
+    spot_feed: &SpotPriceFeed,
+    market_provider: &M,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+{
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    let questions: Vec<(&Market, crypto::CryptoQuestion)> = market_group.markets
+        .iter()
+        .filter(|market| !market.closed)
+        .filter_map(|market| Some((market, crypto::parse_question(&market.question)?)))
+        .collect();
+    if questions.is_empty() {
+        println!("  No open market in {} asks for a crypto price level\n", market_group.title);
+        return Ok(());
+    }
+
+    let now = clock::now();
+    let mut assets: Vec<&str> = questions.iter().map(|(_, question)| question.asset).collect();
+    assets.sort();
+    assets.dedup();
+
+    let mut values: Vec<CryptoFairValue> = Vec::new();
+    for asset in assets {
+        let spot = spot_feed.spot(asset).await?;
+        let realized = crypto::realized_volatility(&spot_feed.daily_closes(asset, vol_days + 1).await?);
+        let Some(volatility) = volatility.or(realized) else {
+            println!("  Not enough {} price history for a volatility, pass --volatility", asset);
+            continue;
+        };
+        values.extend(questions
+            .iter()
+            .filter(|(_, question)| question.asset == asset)
+            .filter_map(|(market, question)| crypto::fair_value(market, question, spot, volatility, now)));
+    }
+
+    values.sort_by(|a, b| a.asset.cmp(b.asset).then(a.days_left.total_cmp(&b.days_left)).then(a.strike.total_cmp(&b.strike)));
+    output::print_crypto_fair_values(&values, vol_days, volatility.is_some());
+    Ok(())
+}
+
+// where the top holders' usdc came from, holders funded by the same wallet may be one person
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_funding<M, T, P>(
+    market_slug: &str,
+    top: usize,
+    lookback_days: u32,
+    funding: Option<&FundingTracer>,
+    names: Option<&NameResolver>,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+{
+    let Some(tracer) = funding else {
+        bail!("funding reads usdc transfers from the chain, pass a polygon json-rpc url with --polygon-rpc");
+    };
+
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    // same primary market choice as analyze
+    let Some(market) = market_group.markets.first() else {
+        println!("  No markets found in this group\n");
+        return Ok(());
+    };
+    println!("  Market: {}", market.question);
+
+    let positions = position_provider.get_positions(&market.condition_id).await?;
+    let holders = funding::top_holders(&positions, top);
+    let addresses: Vec<String> = holders.iter().map(|(address, _)| address.clone()).collect();
+
+    // deposits are looked for before each wallet's first trade anywhere, not just in this market
+    let first_blocks: HashMap<String, u64> = trader_provider
+        .get_traders_by_addresses(&addresses)
+        .await?
+        .into_iter()
+        .filter_map(|t| Some((t.trader_address, t.first_activity_block?)))
+        .collect();
+    let wallets: Vec<(String, u64)> = first_blocks.iter().map(|(address, block)| (address.clone(), *block)).collect();
+    println!("  Tracing {} holders over --polygon-rpc, cached traces are reused", wallets.len());
+
+    let traces = tracer.trace_many(&wallets, lookback_days as u64 * backtest::BLOCKS_PER_DAY).await;
+    let sources: HashMap<String, FundingSource> = wallets
+        .iter()
+        .filter_map(|(address, _)| {
+            let trace = traces.get(&address.to_lowercase())?;
+            let (funder, amount) = trace.source()?;
+            Some((address.clone(), FundingSource { funder: funder.to_string(), amount, funded_block: trace.funded_block()? }))
+        })
+        .collect();
+    if traces.len() < wallets.len() {
+        println!("  {} holders couldn't be traced, the node refused or failed the lookups", wallets.len() - traces.len());
+    }
+
+    let mut book = AddressBook::load()?;
+    let exchanges: HashSet<String> = book
+        .labels
+        .iter()
+        .filter(|(_, label)| label.to_lowercase().contains("exchange"))
+        .map(|(address, _)| address.clone())
+        .collect();
+    let report = funding::funding_report(&holders, &first_blocks, &sources, &exchanges);
+
+    let mut shown = addresses;
+    shown.extend(sources.values().map(|s| s.funder.clone()));
+    resolve_names(&mut book, names, &shown).await;
+    output::print_funding(&report, lookback_days, &book);
+
+    Ok(())
+}
+
+// who grew, cut or left positions in a market over the window, as plain sentences
+pub async fn handle_position_changes<M, X>(
+    market_slug: &str,
+    days: u32,
+    min_shares: f64,
+    limit: usize,
+    names: Option<&NameResolver>,
+    market_provider: &M,
+    transaction_provider: &X,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    X: TransactionProvider,
+{
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    // same primary market choice as analyze
+    let Some(market) = market_group.markets.first() else {
+        println!("  No markets found in this group\n");
+        return Ok(());
+    };
+    println!("  Market: {}", market.question);
+
+    let recent = transaction_provider.get_recent_transactions(&market.condition_id, days).await?;
+    let history = transaction_provider.get_market_transactions(&market.condition_id).await?;
+    println!("  Found {} transactions, {} in the last {} days", history.len(), recent.len(), days);
+
+    let mut deltas = position_diff::position_changes(&history, &recent, min_shares);
+    let total = deltas.len();
+    deltas.truncate(limit);
+
+    let addresses: Vec<String> = deltas
+        .iter()
+        .map(|d| d.trader_address.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let mut book = AddressBook::load()?;
+    resolve_names(&mut book, names, &addresses).await;
+    output::print_position_changes(&deltas, total, days, &book);
+
+    Ok(())
+}
+
+// look back at a resolved market, who came out ahead and whether smart money saw it coming
+pub async fn handle_postmortem<M, T, X, R>(
+    market_slug: &str,
+    checkpoints: usize,
+    smart_money: &SmartMoney,
+    market_provider: &M,
+    trader_provider: &T,
+    transaction_provider: &X,
+    resolution_provider: &R,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    X: TransactionProvider,
+    R: ResolutionProvider,
+{
+    output::print_header(&format!("Fetching market: {}", market_slug));
+    let market_group = market_provider.get_market_group(market_slug).await?;
+
+    // same primary market choice as analyze
+    let Some(market) = market_group.markets.first() else {
+        println!("  No markets found in this group\n");
+        return Ok(());
+    };
+    println!("  Market: {}", market.question);
+
+    let transactions = transaction_provider.get_market_transactions(&market.condition_id).await?;
+    println!("  Found {} transactions", transactions.len());
+
+    // a closed market paid out at 1 and 0 counts even before `ingest resolutions` has stored it
+    let last_block = transactions.iter().map(|tx| tx.block_number).max().unwrap_or_default();
+    let stored = resolution_provider
+        .get_resolutions()
+        .await?
+        .into_iter()
+        .find(|r| r.condition_id == market.condition_id);
+    let resolution = match stored {
+        Some(r) => Some((r.outcome, r.resolution_block)),
+        None if market.closed => match pnl::outcome_marks(market) {
+            (yes, no) if yes >= 1.0 && no <= 0.0 => Some(("YES".to_string(), last_block)),
+            (yes, no) if no >= 1.0 && yes <= 0.0 => Some(("NO".to_string(), last_block)),
+            _ => None,
+        },
+        None => None,
+    };
+    let Some((outcome, resolution_block)) = resolution else {
+        println!("  Market has not resolved yet\n");
+        return Ok(());
+    };
+
+    let addresses: Vec<String> = transactions
+        .iter()
+        .map(|tx| tx.trader_address.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let smart: HashMap<String, Trader> = trader_provider
+        .get_traders_by_addresses(&addresses)
+        .await?
+        .into_iter()
+        .filter(|t| smart_money.includes(t))
+        .map(|t| (t.trader_address.clone(), t))
+        .collect();
+    println!("  {} of {} traders pass the smart money filter ({})", smart.len(), addresses.len(), smart_money.describe());
+
+    let report = postmortem::postmortem(&transactions, &outcome, resolution_block, &smart, smart_money.model, checkpoints);
+    output::print_postmortem(&report, smart_money);
+
+    Ok(())
+}
+
+// backfill resolutions for locally traded markets and rebuild trader stats from them
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_ingest_resolutions<M, X, R, G, S>(
+    batch_size: usize,
+    from_scratch: bool,
+    capabilities: &Capabilities,
+    collateral: &CollateralRates,
+    market_provider: &M,
+    transaction_provider: &X,
+    resolution_provider: &R,
+    tag_provider: &G,
+    store: &S,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    X: TransactionProvider,
+    R: ResolutionProvider,
+    G: TagProvider,
+    S: DataStore,
+{
+    output::print_header("LOADING LOCAL DATA");
+    let transactions = transaction_provider.get_all_transactions().await?;
+    println!("  Found {} transactions", transactions.len());
+
+    // the first backfill won't have a resolutions table yet
+    let mut known = match resolution_provider.get_resolutions().await {
+        Ok(known) => known,
+        Err(e) => {
+            println!("  No existing resolutions loaded ({}), starting fresh", e);
+            Vec::new()
+        }
+    };
+    println!("  Found {} existing resolutions", known.len());
+
+    output::print_header("QUERYING GAMMA");
+    let pending = resolutions::unresolved_market_ids(&transactions, &known);
+    println!("  {} markets without a resolution", pending.len());
+
+    let mut checkpoints = store.load_checkpoints().await?;
+    if from_scratch {
+        checkpoints.resolutions = None;
+    }
+    let remaining = checkpoint::after_cursor(&pending, checkpoints.resolutions.as_deref());
+    if remaining.len() < pending.len() {
+        println!("  Resuming an interrupted run, {} markets were already queried", pending.len() - remaining.len());
+    }
+
+    // resolutions and the cursor are saved after every batch so an interrupt only loses one batch
+    let mut added = 0;
+    let mut unplaced = 0;
+    for batch in remaining.chunks(batch_size.max(1)) {
+        let markets = market_provider.get_markets_by_condition_ids(batch).await?;
+
+        // a resolution counts from the block gamma closed the market at, not the last trade stored locally,
+        // which can be long before the outcome was decided. markets without a close time or whose time
+        // the chain can't place are left for the next run
+        let resolved: Vec<&Market> = markets.iter().filter(|market| resolutions::resolution_from_market(market, 0).is_some()).collect();
+        let settled: Vec<(&Market, i64)> = resolved
+            .iter()
+            .filter_map(|market| Some((*market, market.closed_time?.timestamp())))
+            .collect();
+        unplaced += resolved.len() - settled.len();
+        let closed_at: Vec<i64> = settled.iter().map(|(_, closed_at)| *closed_at).collect();
+        let blocks = store.blocks_at(&closed_at).await?;
+        for ((market, _), block) in settled.into_iter().zip(blocks) {
+            match block.and_then(|block| resolutions::resolution_from_market(market, block)) {
+                Some(resolution) => {
+                    known.push(resolution);
+                    added += 1;
+                }
+                None => unplaced += 1,
+            }
+        }
+
+        store.save_resolutions(&known).await?;
+        checkpoints.resolutions = batch.last().cloned();
+        store.save_checkpoints(&checkpoints).await?;
+    }
+    println!("  Resolved {} new markets", added);
+    if unplaced > 0 {
+        println!("  Skipped {} resolved markets whose close couldn't be placed on the chain", unplaced);
+    }
+
+    // markets still open get queried again on the next run
+    store.save_resolutions(&known).await?;
+    checkpoints.resolutions = None;
+    store.save_checkpoints(&checkpoints).await?;
+
+    rebuild_trader_stats(from_scratch, capabilities, collateral, &known, transaction_provider, tag_provider, store).await
+}
+
+// recompute traders.parquet from what's already in the local db, nothing is fetched
+pub async fn handle_stats_rebuild<X, R, G, S>(
+    full: bool,
+    capabilities: &Capabilities,
+    collateral: &CollateralRates,
+    transaction_provider: &X,
+    resolution_provider: &R,
+    tag_provider: &G,
+    store: &S,
+) -> Result<()>
+where
+    X: TransactionProvider,
+    R: ResolutionProvider,
+    G: TagProvider,
+    S: DataStore,
+{
+    if !capabilities.transactions {
+        bail!("no transactions in the local db to rebuild trader stats from");
+    }
+    if !capabilities.resolutions {
+        bail!("no market resolutions in the local db, run `ingest resolutions` first");
+    }
+
+    output::print_header("LOADING LOCAL DATA");
+    let resolutions = resolution_provider.get_resolutions().await?;
+    println!("  Found {} resolved markets", resolutions.len());
+
+    rebuild_trader_stats(full, capabilities, collateral, &resolutions, transaction_provider, tag_provider, store).await
+}
+
+// traders.parquet and, with tags, the per category stats, both replaced
+// only transactions past the stats checkpoint are folded into the stored ledgers unless full
+async fn rebuild_trader_stats<X, G, S>(
+    full: bool,
+    capabilities: &Capabilities,
+    collateral: &CollateralRates,
+    resolutions: &[MarketResolution],
+    transaction_provider: &X,
+    tag_provider: &G,
+    store: &S,
+) -> Result<()>
+where
+    X: TransactionProvider,
+    G: TagProvider,
+    S: DataStore,
+{
+    output::print_header("RECOMPUTING TRADER STATS");
+    let mut checkpoints = store.load_checkpoints().await?;
+    let previous = checkpoints.stats.filter(|_| !full);
+
+    let (mut ledgers, new) = match previous {
+        Some(checkpoint) => {
+            let ledgers: ingest::Ledgers = store.load_trader_ledgers().await?
+                .into_iter()
+                .map(|ledger| ((ledger.trader_address.clone(), ledger.market_id.clone()), ledger))
+                .collect();
+            let new = transaction_provider.get_transactions_after(checkpoint.block, checkpoint.timestamp).await?;
+            println!("  {} new transactions since block {}", new.len(), checkpoint.block);
+            (ledgers, new)
+        }
+        None => {
+            let all = transaction_provider.get_all_transactions().await?;
+            println!("  Folding in all {} transactions", all.len());
+            (ingest::Ledgers::new(), all)
+        }
+    };
+    let mix = CollateralMix::of(&new, &[]);
+    if mix.is_mixed() {
+        output::print_mixed_collateral(&mix, collateral);
+    }
+
+    let unchanged = previous.is_some_and(|checkpoint| checkpoint.resolutions == resolutions.len());
+    if new.is_empty() && unchanged && capabilities.traders {
+        println!("  Nothing new since the last rebuild\n");
+        return Ok(());
+    }
+
+    // an interrupt between the writes below would leave ledgers ahead of the checkpoint, the next run starts over instead
+    let mut checkpoint = previous.unwrap_or_default();
+    checkpoints.stats = None;
+    store.save_checkpoints(&checkpoints).await?;
+
+    ingest::fold_transactions(&mut ledgers, &new);
+    checkpoint.advance(&new);
+    checkpoint.resolutions = resolutions.len();
+
+    let traders = ingest::traders_from_ledgers(ledgers.values(), resolutions);
+    store.save_traders(&traders).await?;
+    output::print_rebuilt_traders(&traders);
+
+    // kept for calibration, which judges traders by what these stats said back then
+    let seen = resolutions.iter().map(|r| r.resolution_block).max().unwrap_or_default().max(checkpoint.block);
+    store.save_trader_snapshot(&TraderSnapshot { block: seen, traders }).await?;
+    println!("  Stored a trader stats snapshot at block {}", seen);
+
+    // category stats go stale with every new resolution
+    if capabilities.tags {
+        let tags = tag_provider.get_market_tags().await?;
+        let stats = ingest::category_stats_from_ledgers(&ledgers, resolutions, &tags);
+        store.save_category_stats(&stats).await?;
+        println!("  Wrote {} per category trader stats", stats.len());
+    }
+
+    store.save_trader_ledgers(&ledgers.into_values().collect::<Vec<_>>()).await?;
+    checkpoints.stats = Some(checkpoint);
+    store.save_checkpoints(&checkpoints).await?;
+
+    Ok(())
+}
+
+// tag every market the local transactions touch, the stored tags are replaced
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_ingest_tags<M, X, R, G, S>(
+    batch_size: usize,
+    from_scratch: bool,
+    capabilities: &Capabilities,
+    market_provider: &M,
+    transaction_provider: &X,
+    resolution_provider: &R,
+    tag_provider: &G,
+    store: &S,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    X: TransactionProvider,
+    R: ResolutionProvider,
+    G: TagProvider,
+    S: DataStore,
+{
+    output::print_header("LOADING LOCAL DATA");
+    let transactions = transaction_provider.get_all_transactions().await?;
+    let mut market_ids: Vec<String> = transactions
+        .iter()
+        .map(|tx| tx.market_id.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    market_ids.sort();
+    println!("  Found {} markets in {} transactions", market_ids.len(), transactions.len());
+
+    output::print_header("QUERYING GAMMA");
+    let mut checkpoints = store.load_checkpoints().await?;
+    if from_scratch {
+        checkpoints.tags = None;
+    }
+    let remaining = checkpoint::after_cursor(&market_ids, checkpoints.tags.as_deref());
+
+    // an interrupted run already wrote the tags of the markets before the cursor, keep those
+    let mut tags = Vec::new();
+    if remaining.len() < market_ids.len() {
+        println!("  Resuming an interrupted run, {} markets were already tagged", market_ids.len() - remaining.len());
+        let done = &market_ids[..market_ids.len() - remaining.len()];
+        tags = tag_provider.get_market_tags().await?;
+        tags.retain(|tag| done.binary_search(&tag.condition_id).is_ok());
+    }
+
+    let mut tagged = 0;
+    for batch in remaining.chunks(batch_size.max(1)) {
+        for market in market_provider.get_markets_by_condition_ids(batch).await? {
+            if !market.tags.is_empty() {
+                tagged += 1;
+            }
+            tags.extend(market.tags.into_iter().map(|tag| MarketTag {
+                condition_id: market.condition_id.clone(),
+                tag,
+            }));
+        }
+
+        store.save_market_tags(&tags).await?;
+        checkpoints.tags = batch.last().cloned();
+        store.save_checkpoints(&checkpoints).await?;
+    }
+    println!("  Tagged {} of {} markets", tagged, remaining.len());
+
+    store.save_market_tags(&tags).await?;
+    checkpoints.tags = None;
+    store.save_checkpoints(&checkpoints).await?;
+    println!("  Wrote {} tags", tags.len());
+
+    output::print_header("RECOMPUTING CATEGORY STATS");
+    if !capabilities.resolutions {
+        println!("  No resolutions in the local db yet, run `ingest resolutions` to fill them in");
+        return Ok(());
+    }
+    let resolutions = resolution_provider.get_resolutions().await?;
+    let stats = ingest::compute_category_stats(&transactions, &resolutions, &tags);
+    store.save_category_stats(&stats).await?;
+    println!("  Wrote {} per category trader stats", stats.len());
+
+    Ok(())
+}
+
+// append each market's recent trades that the local db doesn't have yet
+pub async fn handle_ingest_trades<M, R, X, S>(
+    market_slugs: &[String],
+    days: u32,
+    from_scratch: bool,
+    market_provider: &M,
+    remote_provider: &R,
+    local_provider: &X,
+    store: &S,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    R: TransactionProvider,
+    X: TransactionProvider,
+    S: DataStore,
+{
+    output::print_header(&format!("INGESTING {} DAYS OF TRADES", days));
+
+    // a slug that fails to load is reported and skipped, the others still get ingested
+    let batch = market_provider.get_market_groups(market_slugs).await;
+    output::print_fetch_failures(&batch.failures);
+
+    // markets ingested before only need the days since their newest trade
+    let mut checkpoints = store.load_checkpoints().await?;
+    if from_scratch {
+        checkpoints.trades.clear();
+    }
+    let now = chrono::Utc::now().timestamp();
+
+    let mut added = 0;
+    for market_group in &batch.groups {
+        for market in &market_group.markets {
+            let market_days = checkpoints.trade_days(&market.condition_id, days, now);
+            let fetched = remote_provider.get_recent_transactions(&market.condition_id, market_days).await?;
+
+            // saved per market so an interrupted run picks up at the next one, rows already stored are skipped
+            let new = store.append_transactions(&fetched).await?;
+            if !new.is_empty() {
+                checkpoints.positions.insert(market.condition_id.clone());
+            }
+            checkpoints.record_trades(&market.condition_id, &fetched);
+            checkpoints.invalidate_stats(&new);
+            store.save_checkpoints(&checkpoints).await?;
+            println!("  {}: {} new trades ({} days)", market.question, new.len(), market_days);
+            added += new.len();
+        }
+    }
+    println!("  Added {} trades", added);
+
+    refresh_positions(&mut checkpoints, local_provider, store).await
+}
+
+// holdings of every market given new rows replayed from everything stored for it, upserted by wallet and token in one write
+async fn refresh_positions<X, S>(checkpoints: &mut Checkpoints, local_provider: &X, store: &S) -> Result<()>
+where
+    X: TransactionProvider,
+    S: DataStore,
+{
+    if checkpoints.positions.is_empty() {
+        return Ok(());
+    }
+    let mut positions = Vec::new();
+    for condition_id in &checkpoints.positions {
+        let history = local_provider.get_market_transactions(condition_id).await?;
+        positions.extend(ingest::positions_from_transactions(&history));
+    }
+    store.upsert_positions(&positions).await?;
+    println!("  Updated positions in {} markets", checkpoints.positions.len());
+
+    checkpoints.positions.clear();
+    store.save_checkpoints(checkpoints).await?;
+    Ok(())
+}
+
+// liquidity reward payouts per wallet, wallets already pulled only need the payouts since their newest one
+// without addresses it pulls the smart money traders, theirs is the roi the analysis leans on
+pub async fn handle_ingest_rewards<R, T, S>(
+    addresses: &[String],
+    from_scratch: bool,
+    smart_money: &SmartMoney,
+    remote_provider: &R,
+    trader_provider: &T,
+    store: &S,
+) -> Result<()>
+where
+    R: RewardProvider,
+    T: TraderStatsProvider,
+    S: DataStore,
+{
+    output::print_header("INGESTING LIQUIDITY REWARDS");
+
+    let wallets: Vec<String> = match addresses.is_empty() {
+        true => trader_provider.get_traders(smart_money.min_resolved).await?
+            .into_iter()
+            .filter(|trader| smart_money.includes(trader))
+            .map(|trader| trader.trader_address)
+            .collect(),
+        false => addresses.iter().map(|address| address.to_lowercase()).collect(),
+    };
+    if wallets.is_empty() {
+        bail!("no wallets to pull rewards for, pass addresses or ingest trader stats first");
+    }
+
+    let mut checkpoints = store.load_checkpoints().await?;
+    if from_scratch {
+        checkpoints.rewards.clear();
+    }
+
+    let mut paid = 0.0;
+    for wallet in &wallets {
+        // the newest payout is asked for again, the stored copy wins on append
+        let after = checkpoints.rewards.get(wallet).copied();
+        let fetched = remote_provider.get_rewards(std::slice::from_ref(wallet), after).await?;
+
+        // saved per wallet so an interrupted run picks up at the next one
+        store.append_rewards(&fetched).await?;
+        checkpoints.record_rewards(wallet, &fetched);
+        store.save_checkpoints(&checkpoints).await?;
+        let total: f64 = fetched.iter().map(|reward| reward.usdc_amount).sum();
+        println!("  {}: {} payouts, {}", wallet, fetched.len(), format::usd(total));
+        paid += total;
+    }
+    println!("  Pulled {} in rewards for {} wallets", format::usd(paid), wallets.len());
+
+    Ok(())
+}
+
+// splits, merges and redemptions straight from the ctf contract, the data api only has order fills
+// without them a wallet that minted its shares looks like it sold what it never bought
+pub async fn handle_ingest_ctf<M, X, S>(
+    market_slugs: &[String],
+    days: u32,
+    from_scratch: bool,
+    market_provider: &M,
+    ctf: Option<&CtfEventReader>,
+    local_provider: &X,
+    store: &S,
+) -> Result<()>
+where
+    M: MarketMetadataProvider,
+    X: TransactionProvider,
+    S: DataStore,
+{
+    let Some(reader) = ctf else {
+        bail!("ingest ctf reads the ctf contract's logs, pass a polygon json-rpc url with --polygon-rpc");
+    };
+    output::print_header("INGESTING SPLITS, MERGES AND REDEMPTIONS");
+
+    let batch = market_provider.get_market_groups(market_slugs).await;
+    output::print_fetch_failures(&batch.failures);
+
+    let mut checkpoints = store.load_checkpoints().await?;
+    if from_scratch {
+        checkpoints.ctf.clear();
+    }
+    let head = reader.head_block().await?;
+    let default_from = head.saturating_sub(days as u64 * backtest::BLOCKS_PER_DAY);
+
+    let mut added = 0;
+    for market_group in &batch.groups {
+        for market in &market_group.markets {
+            let from = checkpoints.ctf_from(&market.condition_id, default_from);
+            // redemptions only start once the market settles, until then only splits and merges are read
+            let winner = resolutions::resolution_from_market(market, head).map(|resolution| resolution.outcome);
+            let events = reader.market_events(&market.condition_id, from, head, winner.is_some()).await?;
+            let fetched = ingest::ctf_transactions(&events, market, winner.as_deref());
+
+            // rescans find rows already stored, the store skips them
+            let new = store.append_transactions(&fetched).await?;
+            if !new.is_empty() {
+                checkpoints.positions.insert(market.condition_id.clone());
+            }
+            // a closed market without a winner yet is scanned again next time so its redemptions aren't missed
+            if winner.is_some() || !market.closed {
+                checkpoints.ctf.insert(market.condition_id.clone(), head);
+            }
+            checkpoints.invalidate_stats(&new);
+            store.save_checkpoints(&checkpoints).await?;
+            println!("  {}: {} new rows from {} operations", market.question, new.len(), events.len());
+            added += new.len();
+        }
+    }
+    println!("  Added {} rows", added);
+
+    refresh_positions(&mut checkpoints, local_provider, store).await
+}
@@ -0,0 +1,81 @@
+use crate::analysis::MarketSummary;
+use crate::cli::server::{self, RequestError};
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+// latest rendered exposition, swapped in after every monitor poll
+pub type MetricsState = Arc<RwLock<String>>;
+
+// serve GET /metrics in the background until the process exits
+pub async fn serve_metrics(addr: SocketAddr, state: MetricsState) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    println!("  Serving prometheus metrics on http://{}/metrics", listener.local_addr()?);
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let state = Arc::clone(&state);
+
+            tokio::spawn(async move {
+                let request = server::read_request_line(&mut stream).await;
+
+                let response = match request {
+                    Ok((method, path)) if method == "GET" && path == "/metrics" => {
+                        let body = state.read().map(|body| body.clone()).unwrap_or_default();
+                        server::response("200 OK", "text/plain; version=0.0.4", &body)
+                    }
+                    Err(RequestError::TimedOut) => server::response("408 Request Timeout", "text/plain", ""),
+                    _ => server::response("404 Not Found", "text/plain", ""),
+                };
+
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    Ok(())
+}
+
+// prometheus text format, one gauge family per metric with a slug label
+pub fn render(summaries: &[MarketSummary], polled_at: i64) -> String {
+    let mut out = String::new();
+
+    gauge(&mut out, "polymarket_yes_price", "Last YES trade price", summaries, |s| Some(s.yes_price));
+    gauge(&mut out, "polymarket_spread", "Best ask minus best bid", summaries, |s| Some(s.spread));
+    gauge(&mut out, "polymarket_liquidity", "Order book liquidity in USDC", summaries, |s| Some(s.liquidity));
+    gauge(&mut out, "polymarket_smart_money_lean", "Smart money YES probability", summaries, |s| s.smart_lean);
+    gauge(&mut out, "polymarket_whale_count", "Wallets holding at least the whale threshold", summaries, |s| Some(s.whale_count as f64));
+    gauge(&mut out, "polymarket_whale_share", "Capital share of the largest holders", summaries, |s| Some(s.whale_share));
+    gauge(&mut out, "polymarket_volume_24h", "Volume over the last 24 hours in USDC", summaries, |s| Some(s.volume_24h));
+
+    let _ = writeln!(out, "# HELP polymarket_monitor_last_poll_timestamp_seconds Unix time of the last successful poll");
+    let _ = writeln!(out, "# TYPE polymarket_monitor_last_poll_timestamp_seconds gauge");
+    let _ = writeln!(out, "polymarket_monitor_last_poll_timestamp_seconds {}", polled_at);
+
+    out
+}
+
+fn gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    summaries: &[MarketSummary],
+    value: impl Fn(&MarketSummary) -> Option<f64>,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    for summary in summaries {
+        if let Some(value) = value(summary) {
+            let _ = writeln!(out, "{}{{slug=\"{}\"}} {}", name, escape_label(&summary.slug), value);
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
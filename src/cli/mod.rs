@@ -1,6 +1,18 @@
 pub mod commands;
+pub mod events;
+pub mod export;
+pub mod format;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod handlers;
+pub mod metrics;
 pub mod output;
+pub mod server;
 
-pub use commands::CLI;
-pub use handlers::handle_analyze;
+pub use commands::{ApiUrlArgs, Cli, CliCommand, Command, HttpArgs, IngestTarget, LabelAction, LocalCommand, OddsArgs, OutputFormat, PaperAction, SchemaTarget, SmartMoneyArgs, Source, StatsAction, TlsVersion, WatchlistAction};
+#[cfg(feature = "trading")]
+pub use commands::TradeAction;
+pub use events::{AnalysisBus, AnalysisEvent, AnalysisSink, HtmlSink, JsonSink, TerminalSink, WebhookSink, ArrowSink};
+pub use handlers::{dispatch, dispatch_local, handle_analyze, handle_audit_db, handle_backtest, handle_big_trades, handle_book_history, handle_compact, handle_calibration, handle_closing_soon, handle_compare, handle_completions, handle_crypto, handle_funding, handle_heatmap, handle_insiders, handle_ingest_resolutions, handle_ingest_rewards, handle_ingest_tags, handle_ingest_trades, handle_label, handle_leaderboard, handle_liquidity_history, handle_monitor, handle_movers, handle_new_markets, handle_paper, handle_parity, handle_plan_order, handle_portfolio, handle_position_changes, handle_postmortem, handle_replay, handle_schema, handle_serve, handle_stats_rebuild, handle_watchlist};
+#[cfg(feature = "trading")]
+pub use handlers::handle_trade;
@@ -1,4 +1,129 @@
-use crate::standard_data::models::{MarketGroup, Market};
+use crate::standard_data::models::{Collateral, ExternalForecast, MarketGroup, Market, Position, Trader};
+use crate::analysis::{Alert, AuditReport, BacktestReport, BigTrade, BookPoint, CalibrationReport, CategoryExposure, Chatter, CryptoFairValue, ClosingMarket, CollateralMix, CollateralRates, Concentration, CostBasis, FeeModel, FundingReport, GroupCoherence, ImpliedReturns, InsiderReport, LiquidityShift, ParityReport, MarketRecord, MarketSummary, Mover, NewMarket, OpenInterest, OrderFlowReport, OrderPlan, PositionDelta, Postmortem, ProbabilityModel, RewardSettings, Tape, TradeHeatmap, TraderPnl, VwapReport, WalletAgeBreakdown};
+use crate::analysis::big_trades::PositionChange;
+use crate::analysis::expiry;
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::analysis::funding::FRESH_FUNDING_BLOCKS;
+use crate::analysis::insider::InsiderReason;
+use crate::analysis::liquidity::{LiquidityMove, LiquidityPoint, PRICE_LOOKAHEAD_SECS};
+use crate::analysis::chatter::{CHATTER_COMMENT_LIMIT, CHATTER_GAP};
+use crate::analysis::coherence::RICH_CHEAP_THRESHOLD;
+use crate::analysis::compare::WHALE_TOP_N;
+use crate::analysis::concentration::{CONCENTRATED_HHI, CONCENTRATION_TOP_N};
+use crate::analysis::heatmap::WEEKDAYS;
+use crate::analysis::movers::MOVER_VWAP_HOURS;
+use crate::analysis::open_interest::{OpenInterestSignal, SupplySource};
+use crate::analysis::order_flow::FlowSignal;
+use crate::analysis::vwap::{self, SideVwap};
+use crate::analysis::implied_return::SideReturn;
+use crate::analysis::smart_money::SmartMoney;
+use crate::analysis::tape::{NotableEntry, TapePrint, MAX_REPLAY_PAUSE_SECS};
+use crate::analysis::trader_watch::{self, TraderActivity};
+use crate::analysis::wallet_age::{FRESH_MAX_AGE_DAYS, FRESH_MAX_MARKETS};
+use crate::adapters::{Compaction, RequestStatsSnapshot};
+use crate::data_sources::polymarket_api::{ObjectDrift, SchemaDriftReport};
+use crate::cli::OutputFormat;
+use crate::cli::format;
+use crate::clock;
+use crate::error::AppError;
+use crate::watchlist::Watchlist;
+use crate::address_book::AddressBook;
+use crate::paper::{FillSide, PaperFill, PaperLedger, PaperPosition};
+use chrono::{DateTime, TimeDelta, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// json report versioning
+// report_version goes up by one whenever a section is added to, renamed in or removed from the report
+// every version from OLDEST_REPORT_VERSION up can still be asked for and comes out exactly as it was,
+// so a pipeline pinned with --report-version keeps working when new sections show up
+pub const REPORT_VERSION: u32 = 2;
+pub const OLDEST_REPORT_VERSION: u32 = 1;
+// sections added after the first version, their path in the report and the version that added them
+pub const REPORT_SECTIONS: &[(&[&str], u32)] = &[
+    (&["primary", "order_flow"], 2),
+];
+
+pub fn check_report_version(version: u32) -> std::result::Result<u32, String> {
+    if (OLDEST_REPORT_VERSION..=REPORT_VERSION).contains(&version) {
+        Ok(version)
+    } else {
+        Err(format!("report version {} is not supported, use {} to {}", version, OLDEST_REPORT_VERSION, REPORT_VERSION))
+    }
+}
+
+// the report as an older version had it, sections added since are left out
+pub fn report_as_version(mut report: Value, version: u32) -> Value {
+    for (path, _) in REPORT_SECTIONS.iter().filter(|(_, added)| *added > version) {
+        let (last, parents) = path.split_last().expect("report section paths are never empty");
+        if let Some(Value::Object(fields)) = parents.iter().try_fold(&mut report, |node, key| node.get_mut(*key)) {
+            fields.remove(*last);
+        }
+    }
+    report["report_version"] = json!(version);
+    report
+}
+
+// the report schema of an older version, same sections dropped as report_as_version
+pub fn report_schema_as_version(mut schema: Value, version: u32) -> Value {
+    for (path, _) in REPORT_SECTIONS.iter().filter(|(_, added)| *added > version) {
+        let (last, parents) = path.split_last().expect("report section paths are never empty");
+        // walk down through properties, following refs into the definitions
+        let mut definition: Option<String> = None;
+        for key in parents {
+            let node = match &definition {
+                Some(name) => &schema["definitions"][name.as_str()],
+                None => &schema,
+            };
+            let property = &node["properties"][*key];
+            // options come out as anyOf a ref and null
+            let reference = property["$ref"].as_str()
+                .or_else(|| property["anyOf"].as_array()?.iter().find_map(|s| s["$ref"].as_str()));
+            definition = reference.and_then(|r| r.strip_prefix("#/definitions/")).map(str::to_string);
+        }
+
+        let node = match &definition {
+            Some(name) => &mut schema["definitions"][name.as_str()],
+            None => &mut schema,
+        };
+        if let Some(properties) = node["properties"].as_object_mut() {
+            properties.remove(*last);
+        }
+        if let Some(required) = node["required"].as_array_mut() {
+            required.retain(|name| name != *last);
+        }
+    }
+    schema
+}
+
+// what --output json prints for a failed run
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ErrorReport {
+    // http, data, parse, source, trading or internal
+    pub category: String,
+    // stable dotted code like http.timeout
+    pub code: String,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+// print an error as one json object on stderr
+// typed errors carry a stable code, anything else is reported as internal
+pub fn print_error_json(error: &anyhow::Error) {
+    let app_error = error.downcast_ref::<AppError>();
+
+    let body = ErrorReport {
+        category: app_error.map(AppError::category).unwrap_or("internal").to_string(),
+        code: app_error.map(AppError::code).unwrap_or("internal.error").to_string(),
+        message: format!("{:#}", error),
+        hint: app_error.and_then(AppError::hint).map(str::to_string),
+    };
+
+    eprintln!("{}", serde_json::to_string(&body).unwrap_or_default());
+}
 
 // helper to  print section headers
 pub fn print_header(title: &str) {
@@ -8,21 +133,79 @@ pub fn print_header(title: &str) {
     println!("{}", lines);
 }
 
+// the active smart money definition, under the header of every report that uses it
+pub fn print_smart_money(smart_money: &SmartMoney) {
+    println!("  Smart money: {} ({} model)", smart_money.describe(), smart_money.model.as_str());
+}
+
+// section that couldn't run with the data at hand
+pub fn print_unavailable(title: &str, reason: &str) {
+    print_header(title);
+    println!("  Unavailable: {}\n", reason);
+}
+
+// an extra analyzer's output, an object key by key, anything else as json
+pub fn print_analyzer(name: &str, output: &Value) {
+    print_header(&name.to_uppercase());
+    match output {
+        Value::Object(fields) if !fields.is_empty() => {
+            for (key, value) in fields {
+                match value {
+                    Value::String(text) => println!("  {}: {}", key, text),
+                    _ => println!("  {}: {}", key, value),
+                }
+            }
+        }
+        Value::String(text) => println!("  {}", text),
+        _ => println!("  {}", output),
+    }
+    println!();
+}
+
+// section whose data couldn't be fetched, the rest of the report still ran
+pub fn print_failed_section(title: &str, error: &str) {
+    print_header(title);
+    println!("  Failed: {}\n", error);
+}
+
+// rows in both usdcs, any usd total adds them up at the configured rates
+pub fn print_mixed_collateral(mix: &CollateralMix, rates: &CollateralRates) {
+    println!("  ! Mixed collateral: {} rows in {}, {} in {}", mix.usdc_e, Collateral::UsdcE.label(), mix.usdc, Collateral::Usdc.label());
+    println!("    USD figures count {} at ${:.4} and {} at ${:.4}, see [collateral] in config.toml\n",
+        Collateral::UsdcE.label(), rates.usdc_e, Collateral::Usdc.label(), rates.usdc);
+}
+
+// analysis that stopped before its last section
+pub fn print_incomplete(reason: &str) {
+    print_header("INCOMPLETE");
+    println!("  Stopped early: {}", reason);
+    println!("  The sections above are everything that finished\n");
+}
+
+// files an --export run wrote
+pub fn print_export(written: &[PathBuf]) {
+    print_header("EXPORT");
+    for path in written {
+        println!("  Wrote {}", path.display());
+    }
+    println!();
+}
+
 pub fn print_market_group_info(group: &MarketGroup) {
     print_header("MARKET GROUP");
     
     println!("  Title: {}", group.title);
     println!("  Slug: {}", group.slug);
     
-    println!("  Total Volume: ${:.2}", group.volume);
-    println!("  Total Liquidity: ${:.2}", group.liquidity);
+    println!("  Total Volume: {}", format::usd(group.volume));
+    println!("  Total Liquidity: {}", format::usd(group.liquidity));
     println!("  Active: {}", group.active);
     println!("  Closed: {}", group.closed);
     println!("  Number of Sub Markets: {}", group.markets.len());
     println!();
 }
 
-pub fn print_market_info(market: &Market) {
+pub fn print_market_info(market: &Market, open_interest: Option<&OpenInterest>) {
     println!("  Question: {}", market.question);
     println!("  Slug: {}", market.slug);
     println!("  Condition ID: {}", market.condition_id);
@@ -30,19 +213,1612 @@ pub fn print_market_info(market: &Market) {
     println!("  NO Token: {}", market.no_token_id);
     
     if market.outcomes.len() == 2 && market.outcome_prices.len() == 2 {
-        println!("  YES Price: {}", market.outcome_prices[0]);
-        println!("  NO Price: {}", market.outcome_prices[1]);
+        // gamma sends them as strings, shown as is if one doesn't parse
+        let price = |text: &str| text.parse::<f64>().map_or(text.to_string(), |p| format::price(p, 3));
+        println!("  YES Price: {}", price(&market.outcome_prices[0]));
+        println!("  NO Price: {}", price(&market.outcome_prices[1]));
     }
     
-    println!("  Volume: ${:.2}", market.volume);
-    println!("  Volume 24hr: ${:.2}", market.volume_24h);
-    println!("  Volume 1 week: ${:.2}", market.volume_1w);
-    println!("  Volume 1 month: ${:.2}", market.volume_1m);
-    println!("  Volume 1 year: ${:.2}", market.volume_1y);
+    println!("  Volume: {}", format::usd(market.volume));
+    println!("  Volume 24hr: {}", format::usd(market.volume_24h));
+    println!("  Volume 1 week: {}", format::usd(market.volume_1w));
+    println!("  Volume 1 month: {}", format::usd(market.volume_1m));
+    println!("  Volume 1 year: {}", format::usd(market.volume_1y));
     println!("  Competitive: {:.5}", market.competitive);
-    println!("  Last trade price: ${:.5}", market.last_trade_price);
-    println!("  Best Bid Price: ${:.5}", market.bid_price);
-    println!("  Best Ask Price: ${:.5}", market.ask_price);
+    println!("  Last trade price: {}", format::price(market.last_trade_price, 5));
+    println!("  Best Bid Price: {}", format::price(market.bid_price, 5));
+    println!("  Best Ask Price: {}", format::price(market.ask_price, 5));
+
+    let now = clock::now();
+    if let Some(end_date) = market.end_date {
+        println!("  End Date: {}", format::datetime(end_date));
+    }
+    if let Some(left) = expiry::time_to_expiry(market, now) {
+        let flag = if expiry::resolves_soon(market, now) { "  (resolves within 48 hours)" } else { "" };
+        println!("  Time to expiry: {}{}", format_time_left(left), flag);
+    }
+    if let Some(source) = &market.resolution_source {
+        println!("  Resolution Source: {}", source);
+    }
+    if !market.tags.is_empty() {
+        println!("  Tags: {}", market.tags.join(", "));
+    }
+    if let Some(open_interest) = open_interest {
+        print_open_interest(open_interest);
+    }
     
     println!();
 }
+
+// part of the market info, outstanding shares now and their trend
+fn print_open_interest(oi: &OpenInterest) {
+    let source = match oi.source {
+        SupplySource::Positions => "from positions",
+        SupplySource::Transactions => "netted from the trade log",
+    };
+    println!("  Open Interest: {:.0} YES / {:.0} NO shares, {} in sets ({})", oi.yes_shares, oi.no_shares, format::usd(oi.usdc()), source);
+    if oi.history.is_empty() {
+        return;
+    }
+
+    let points: Vec<String> = oi.history.iter().map(|p| format!("{:.0}", p.yes_shares + p.no_shares)).collect();
+    println!("  Shares outstanding, {}h windows: {}", oi.window_hours, points.join(" -> "));
+    let trend = oi.trend.map_or("-".to_string(), |t| format!("{:+.0} shares per window", t));
+    let smart = oi.smart_pressure.map_or("no smart money trades".to_string(), |p| format!("smart money pressure {:+.2}", p));
+    println!("  OI trend: {}, {}", trend, smart);
+    match oi.signal {
+        OpenInterestSignal::BuildingYes => println!("  Signal: open interest rising with smart money buying YES"),
+        OpenInterestSignal::BuildingNo => println!("  Signal: open interest rising with smart money buying NO"),
+        OpenInterestSignal::Neutral => {}
+    }
+    if oi.untimed > 0 {
+        println!("  Skipped in the trend (no timestamp): {}", oi.untimed);
+    }
+}
+
+// hold to resolution returns for every market in the group, on a yearly basis
+pub fn print_expiry_overview(group: &MarketGroup) {
+    print_header("TIME TO EXPIRY");
+
+    let now = clock::now();
+    for market in &group.markets {
+        println!("  {}", market.question);
+
+        let Some(left) = expiry::time_to_expiry(market, now) else {
+            println!("    No end date\n");
+            continue;
+        };
+        let flag = if expiry::resolves_soon(market, now) { "  (resolves within 48 hours)" } else { "" };
+        println!("    Time to expiry: {}{}", format_time_left(left), flag);
+
+        // no is bought at one minus the yes bid
+        let no_ask = 1.0 - market.bid_price;
+        for (side, ask) in [("YES", market.ask_price), ("NO", no_ask)] {
+            if ask <= 0.0 || ask >= 1.0 {
+                continue;
+            }
+            let simple = 1.0 / ask - 1.0;
+            match expiry::annualize(simple, left) {
+                // long shots compound into meaningless numbers
+                Some(annual) if annual > 10.0 => println!("    Buy {} at {}: {:.1}% if it wins, >1000% annualized",
+                    side, format::price(ask, 3), simple * 100.0),
+                Some(annual) => println!("    Buy {} at {}: {:.1}% if it wins, {:.1}% annualized",
+                    side, format::price(ask, 3), simple * 100.0, annual * 100.0),
+                None => println!("    Buy {} at {}: {:.1}% if it wins", side, format::price(ask, 3), simple * 100.0),
+            }
+        }
+        println!();
+    }
+}
+
+pub fn print_implied_returns(implied: Option<&ImpliedReturns>, smart_money: &SmartMoney, fees: &FeeModel) {
+    print_header("IMPLIED RETURNS AT SMART MONEY ODDS");
+    print_smart_money(smart_money);
+    println!("  Returns are after a {:.0} bps taker fee", fees.taker_bps);
+
+    let Some(implied) = implied else {
+        println!("  No smart traders hold this market\n");
+        return;
+    };
+
+    println!("  Smart holders: {}", implied.smart_traders);
+    println!("  Smart money YES probability: {:.1}%", implied.smart_probability * 100.0);
+    for model in ProbabilityModel::ALL {
+        let probability = match implied.models.get(model) {
+            Some(p) => format!("{:.1}%", p * 100.0),
+            None => "n/a".to_string(),
+        };
+        let active = if model == smart_money.model { " (active)" } else { "" };
+        println!("    {:<9} {:>6}{}", model.as_str(), probability, active);
+    }
+
+    for (side, result) in [("YES", implied.yes), ("NO", implied.no)] {
+        match result {
+            Some(SideReturn { ask, expected_return, annualized, kelly }) => {
+                let annualized = match annualized {
+                    Some(annual) if annual > 10.0 => ">1000%".to_string(),
+                    Some(annual) => format!("{:.1}%", annual * 100.0),
+                    None => "n/a".to_string(),
+                };
+                println!("  Buy {} at {}: expected {:.1}%, annualized {}, kelly {:.1}%",
+                    side, format::price(ask, 3), expected_return * 100.0, annualized, kelly * 100.0);
+            }
+            None => println!("  Buy {}: no usable ask", side),
+        }
+    }
+    println!();
+}
+
+pub fn print_forecasts(market_price: Option<f64>, smart_money: Option<f64>, forecasts: &[ExternalForecast]) {
+    print_header("MODEL VS MARKET VS SMART MONEY");
+    if forecasts.is_empty() {
+        println!("  No outside forecast covers this market");
+    }
+
+    // every row against the market price
+    let versus = |probability: f64| market_price.map_or("-".to_string(), |price| format::price_signed(probability - price, 3));
+    println!("  {:<30} {:>8} {:>10}  Updated", "Source", "YES", "vs market");
+    match market_price {
+        Some(price) => println!("  {:<30} {:>7.1}% {:>10}", "Market price", price * 100.0, "-"),
+        None => println!("  {:<30} {:>8}", "Market price", "n/a"),
+    }
+    match smart_money {
+        Some(probability) => println!("  {:<30} {:>7.1}% {:>10}", "Smart money", probability * 100.0, versus(probability)),
+        None => println!("  {:<30} {:>8}", "Smart money", "n/a"),
+    }
+    for forecast in forecasts {
+        println!("  {:<30} {:>7.1}% {:>10}  {}",
+            truncate(&forecast.source, 30),
+            forecast.probability * 100.0,
+            versus(forecast.probability),
+            forecast.updated_at.map_or("-".to_string(), format::datetime),
+        );
+    }
+    println!();
+}
+
+// "3d 4h", "5h 12m", or "expired 2h ago"
+fn format_time_left(left: TimeDelta) -> String {
+    if left < TimeDelta::zero() {
+        return format!("expired {} ago", format_time_left(-left));
+    }
+
+    let days = left.num_days();
+    let hours = left.num_hours() % 24;
+    let minutes = left.num_minutes() % 60;
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else {
+        format!("{}h {}m", hours, minutes)
+    }
+}
+
+pub fn print_group_coherence(coherence: &GroupCoherence) {
+    print_header("OUTCOME CONSISTENCY");
+    println!("  Assumes exactly one open market in the group resolves YES");
+    println!("  Sum of YES prices: {}", format::price(coherence.yes_price_sum, 3));
+    println!("  Sum of YES asks: {}", format::price(coherence.yes_ask_sum, 3));
+    println!("  Sum of YES bids: {}", format::price(coherence.yes_bid_sum, 3));
+
+    match (coherence.buy_all_yes_edge(), coherence.buy_all_no_edge()) {
+        (Some(edge), _) => println!("  Dutch book: buying every YES locks in {} per set", format::price(edge, 3)),
+        (None, Some(edge)) => println!("  Dutch book: buying every NO locks in {} per set", format::price(edge, 3)),
+        (None, None) => println!("  No dutch book at current asks"),
+    }
+
+    println!("\n  {:<50} {:>7} {:>7}", "Market", "YES", "Fair");
+    for market in &coherence.markets {
+        let mispricing = market.mispricing();
+        let label = if mispricing > RICH_CHEAP_THRESHOLD {
+            "rich"
+        } else if mispricing < -RICH_CHEAP_THRESHOLD {
+            "cheap"
+        } else {
+            ""
+        };
+        println!("  {:<50} {:>7} {:>7} {:>8}",
+            truncate(&market.question, 50),
+            format::price(market.yes_price, 3),
+            format::price(market.fair_price, 3),
+            label,
+        );
+    }
+    println!();
+}
+
+pub fn print_chatter(chatter: &Chatter, yes_price: Option<f64>) {
+    print_header("CROWD CHATTER");
+    if chatter.comments == 0 {
+        println!("  No comments on this event\n");
+        return;
+    }
+
+    println!("  Comments read: {} from {} commenters (latest {} at most)", chatter.comments, chatter.commenters, CHATTER_COMMENT_LIMIT);
+    println!("  Last 24h: {}  Last 7d: {}", chatter.last_24h, chatter.last_7d);
+    println!("  Per day: {}", chatter.per_day.map_or("-".to_string(), |rate| format!("{:.1}", rate)));
+    let tone = chatter.tone.map_or("-".to_string(), |tone| format!("{:+.2}", tone));
+    println!("  Tone: {} ({} positive, {} negative)", tone, chatter.positive, chatter.negative);
+    println!("  Backing: {} YES, {} NO", chatter.yes_leaning, chatter.no_leaning);
+
+    // the crowd's share backing YES next to what the money prices YES at
+    match (chatter.crowd_yes, yes_price) {
+        (Some(crowd), Some(price)) => {
+            println!("  Crowd YES: {:.1}% vs YES price {}", crowd * 100.0, format::price(price, 3));
+            let gap = crowd - price;
+            let reading = if gap > CHATTER_GAP {
+                "talk is more bullish on YES than the price"
+            } else if gap < -CHATTER_GAP {
+                "talk is more bearish on YES than the price"
+            } else {
+                "talk and price agree"
+            };
+            println!("  Versus positioning: {}", reading);
+        }
+        (Some(crowd), None) => println!("  Crowd YES: {:.1}%", crowd * 100.0),
+        (None, _) => println!("  No comments back an outcome"),
+    }
+
+    if let Some(comment) = &chatter.most_liked {
+        println!("\n  Most liked ({} likes, {}):", comment.likes, format::timestamp(comment.created_at));
+        println!("    {}", truncate(&comment.body.replace('\n', " "), 120));
+    }
+    println!();
+}
+
+pub fn print_watchlist(watchlist: &Watchlist, book: &AddressBook) {
+    print_header("WATCHLIST");
+
+    println!("  Markets: {}", watchlist.markets.len());
+    for slug in &watchlist.markets {
+        println!("    {}", slug);
+    }
+
+    println!("  Traders: {}", watchlist.traders.len());
+    for address in &watchlist.traders {
+        println!("    {}", book.display(address));
+    }
+    println!();
+}
+
+pub fn print_address_book(book: &AddressBook) {
+    print_header("ADDRESS BOOK");
+
+    println!("  Labeled addresses: {}", book.labels.len());
+    for (address, label) in &book.labels {
+        println!("    {}  {}", address, label);
+    }
+
+    println!("  Wallet groups: {}", book.groups.len());
+    for (name, members) in &book.groups {
+        println!("    {}  {}", name, members.join(", "));
+    }
+    println!();
+}
+
+// the newest `limit` snapshots as rows, depth columns are both sides within each distance of the mid
+pub fn print_book_history(slug: &str, points: &[BookPoint], distances: &[f64], limit: usize) {
+    print_header(&format!("BOOK HISTORY: {}", slug));
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        println!();
+        return;
+    };
+    let spread = |point: &BookPoint| point.spread.map_or_else(|| "-".to_string(), |s| format::price(s, 3));
+    let mut spreads: Vec<f64> = points.iter().filter_map(|p| p.spread).collect();
+    spreads.sort_by(f64::total_cmp);
+
+    println!("  Snapshots: {} from {} to {}", points.len(), format::timestamp(first.timestamp), format::timestamp(last.timestamp));
+    println!("  Spread: {} now, {} at the first snapshot, median {}", spread(last), spread(first),
+        spreads.get(spreads.len() / 2).map_or_else(|| "-".to_string(), |s| format::price(*s, 3)));
+    for (i, distance) in distances.iter().enumerate() {
+        println!("  Depth within {:.0}c: {} now, {} at the first snapshot", distance * 100.0, format::usd(last.depth(i)), format::usd(first.depth(i)));
+    }
+
+    println!();
+    print!("  {:<24} {:>7} {:>7} {:>7}", "Time", "Bid", "Ask", "Spread");
+    for distance in distances {
+        print!(" {:>11}", format!("+/-{:.0}c", distance * 100.0));
+    }
+    println!();
+    let price = |p: Option<f64>| p.map_or_else(|| "-".to_string(), |p| format::price(p, 3));
+    for point in points.iter().rev().take(limit) {
+        print!("  {:<24} {:>7} {:>7} {:>7}", format::timestamp(point.timestamp), price(point.best_bid), price(point.best_ask), spread(point));
+        for i in 0..distances.len() {
+            print!(" {:>11}", format::usd(point.depth(i)));
+        }
+        println!();
+    }
+    if points.len() > limit {
+        println!("\n  Showing the latest {} of {}", limit, points.len());
+    }
+    println!();
+}
+
+pub fn print_liquidity_history(slug: &str, points: &[LiquidityPoint], shifts: &[LiquidityShift], total: usize, threshold: f64) {
+    print_header(&format!("LIQUIDITY HISTORY: {}", slug));
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        println!();
+        return;
+    };
+    let lowest = points.iter().map(|p| p.liquidity).fold(f64::INFINITY, f64::min);
+    let highest = points.iter().map(|p| p.liquidity).fold(0.0, f64::max);
+
+    println!("  Polls: {} from {} to {}", points.len(), format::timestamp(first.timestamp), format::timestamp(last.timestamp));
+    println!("  Liquidity: {} now, {} at the first poll, {} to {}", format::usd(last.liquidity), format::usd(first.liquidity), format::usd(lowest), format::usd(highest));
+    println!("  Spread: {} now, {} at the first poll", format::price(last.spread, 3), format::price(first.spread, 3));
+    println!("  Moves of {:.0}% or more: {}", threshold * 100.0, total);
+    if shifts.len() < total {
+        println!("  Showing the latest {}", shifts.len());
+    }
+    if shifts.is_empty() {
+        println!();
+        return;
+    }
+
+    println!();
+    println!("  {:<24} {:<8} {:>12} {:>12} {:>8} {:>13} {:>7} {:>9}",
+        "Time", "Move", "From", "To", "Change", "Spread", "YES", "YES +1h");
+    for shift in shifts {
+        let kind = match shift.kind {
+            LiquidityMove::Entered => "entered",
+            LiquidityMove::Fled => "fled",
+        };
+        println!("  {:<24} {:<8} {:>12} {:>12} {:>7.0}% {:>13} {:>7} {:>9}",
+            format::timestamp(shift.to.timestamp),
+            kind,
+            format::usd(shift.from.liquidity),
+            format::usd(shift.to.liquidity),
+            shift.change() * 100.0,
+            format!("{} > {}", format::price(shift.from.spread, 3), format::price(shift.to.spread, 3)),
+            format::price(shift.to.yes_price, 3),
+            shift.price_after.map_or_else(|| "-".to_string(), |p| format::price(p, 3)),
+        );
+    }
+    println!("\n  A move is measured from the previous one, YES +1h is the first poll {} minutes later", PRICE_LOOKAHEAD_SECS / 60);
+    println!();
+}
+
+// one line per market, meant to scroll
+pub fn print_monitor_poll(summaries: &[MarketSummary], polled_at: DateTime<Utc>) {
+    let time = format::clock(polled_at);
+    for summary in summaries {
+        let lean = summary.smart_lean
+            .map(|lean| format!("{:.1}%", lean * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+
+        println!("  {}  {:<32} YES {}  spread {}  smart YES {:>6}  whales {}",
+            time,
+            truncate(&summary.slug, 32),
+            format::price(summary.yes_price, 3),
+            format::price(summary.spread, 3),
+            lean,
+            summary.whale_count,
+        );
+    }
+}
+
+// slugs a batch couldn't load, the rest of the batch still ran
+pub fn print_fetch_failures(failures: &[(String, AppError)]) {
+    for (slug, error) in failures {
+        println!("  ! {} failed to load: {}", slug, error);
+    }
+}
+
+// a watched trader's open or close, the market link and monitor's line for the market under it
+pub fn print_trader_activity(activity: &TraderActivity, market: Option<&Market>, summary: Option<&MarketSummary>, book: &AddressBook) {
+    let tx = &activity.transaction;
+    let verb = match activity.change {
+        PositionChange::Open => "opened",
+        _ => "closed",
+    };
+    println!("  {}  {} {} {}: {} {} shares at {}",
+        format::timestamp(tx.timestamp.unwrap_or_default()),
+        book.display(&tx.trader_address),
+        verb,
+        tx.side,
+        tx.action.to_lowercase(),
+        format::current().number(tx.shares, 1),
+        format::price(tx.usdc_amount / tx.shares.max(f64::EPSILON), 3),
+    );
+    match market {
+        Some(market) => {
+            println!("      {}", market.question);
+            println!("      {}", trader_watch::market_url(&market.slug));
+        }
+        None => println!("      market {}", tx.market_id),
+    }
+    if let Some(summary) = summary {
+        let lean = summary.smart_lean
+            .map(|lean| format!("{:.1}%", lean * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        println!("      now YES {}  spread {}  smart YES {}  whales {}  24h volume {}",
+            format::price(summary.yes_price, 3),
+            format::price(summary.spread, 3),
+            lean,
+            summary.whale_count,
+            format::usd(summary.volume_24h),
+        );
+    }
+}
+
+pub fn print_alerts(alerts: &[Alert]) {
+    for alert in alerts {
+        println!("  ! {} {}: {}", alert.slug, alert.kind.as_str(), alert.message);
+    }
+}
+
+pub fn print_movers(movers: &[Mover], min_volume: f64, min_liquidity: f64) {
+    print_header("TOP MOVERS (24HR)");
+    println!("  Volume 24hr >= {}, liquidity >= {}", format::usd(min_volume), format::usd(min_liquidity));
+
+    if movers.is_empty() {
+        println!("  No markets moved\n");
+        return;
+    }
+
+    println!("  vs VWAP is the YES price against the {}h YES vwap, positive means still bid above it",
+        MOVER_VWAP_HOURS);
+
+    println!("\n  {:<48} {:>7} {:>8} {:>14} {:>12} {:>8}",
+        "Market", "YES", "Change", "Volume 24hr", "Liquidity", "vs VWAP");
+    for mover in movers {
+        let deviation = match mover.vwap_deviation {
+            Some(deviation) => format!("{:+.1}%", deviation * 100.0),
+            None => "-".to_string(),
+        };
+        println!("  {:<48} {:>7} {:>8} {:>14} {:>12} {:>8}",
+            truncate(&mover.question, 48),
+            format::price(mover.yes_price, 3),
+            format::price_signed(mover.change_24h, 3),
+            format::usd(mover.volume_24h),
+            format::usd(mover.liquidity),
+            deviation,
+        );
+    }
+    println!();
+}
+
+// usdc bought minus sold per side in each window, oldest first
+pub fn print_order_flow(report: &OrderFlowReport) {
+    print_header("ORDER FLOW IMBALANCE");
+    if report.windows.is_empty() {
+        println!("  No timed trades to bucket\n");
+        return;
+    }
+    if report.untimed > 0 {
+        println!("  Skipped (no timestamp): {}", report.untimed);
+    }
+
+    println!("  {:<24} {:>12} {:>12} {:>12} {:>12} {:>9}",
+        "Window start", "YES bought", "YES sold", "NO bought", "NO sold", "Pressure");
+    for window in &report.windows {
+        let pressure = window.yes_pressure()
+            .map_or("-".to_string(), |p| format!("{:+.2}", p));
+        println!("  {:<24} {:>12} {:>12} {:>12} {:>12} {:>9}",
+            format::timestamp(window.start),
+            format::usd(window.yes.buy),
+            format::usd(window.yes.sell),
+            format::usd(window.no.buy),
+            format::usd(window.no.sell),
+            pressure,
+        );
+    }
+
+    let trend = report.trend.map_or("-".to_string(), |t| format!("{:+.3} per window", t));
+    println!("\n  Trend: {}", trend);
+    let signal = match report.signal {
+        FlowSignal::BuyingYes => "buyers leaning YES",
+        FlowSignal::BuyingNo => "buyers leaning NO",
+        FlowSignal::Neutral => "no clear lean",
+    };
+    println!("  Signal: {}", signal);
+    println!("  Pressure is net YES flow minus net NO flow over all flow, {}h windows ending at the last trade", report.window_hours);
+    println!();
+}
+
+// each window's vwap next to the current price of the same side
+pub fn print_vwap(report: &VwapReport, yes_price: f64, no_price: f64) {
+    print_header("VOLUME WEIGHTED AVERAGE PRICE");
+
+    let Some(as_of) = report.as_of.and_then(|ts| DateTime::from_timestamp(ts, 0)) else {
+        println!("  No timed trades to average\n");
+        return;
+    };
+    println!("  Windows end at the last trade, {}", format::datetime(as_of));
+    println!("  Current YES {} / NO {}", format::price(yes_price, 4), format::price(no_price, 4));
+    if report.untimed > 0 {
+        println!("  Skipped (no timestamp): {}", report.untimed);
+    }
+
+    println!("\n  {:>7} {:>9} {:>9} {:>10} {:>9} {:>9} {:>10}",
+        "Window", "YES VWAP", "vs VWAP", "Shares", "NO VWAP", "vs VWAP", "Shares");
+    for window in &report.windows {
+        let side = |side: Option<SideVwap>, price: f64| match side {
+            Some(side) => (
+                format::price(side.vwap, 4),
+                vwap::deviation(price, side.vwap).map_or("-".to_string(), |d| format!("{:+.1}%", d * 100.0)),
+                format!("{:.0}", side.shares),
+            ),
+            None => ("-".to_string(), "-".to_string(), "-".to_string()),
+        };
+        let (yes_vwap, yes_dev, yes_shares) = side(window.yes, yes_price);
+        let (no_vwap, no_dev, no_shares) = side(window.no, no_price);
+        println!("  {:>6}h {:>9} {:>9} {:>10} {:>9} {:>9} {:>10}",
+            window.hours, yes_vwap, yes_dev, yes_shares, no_vwap, no_dev, no_shares);
+    }
+    println!();
+}
+
+pub fn print_new_markets(markets: &[NewMarket], window: TimeDelta, min_position: f64) {
+    print_header(&format!("NEW MARKETS (LAST {}H)", window.num_hours()));
+
+    if markets.is_empty() {
+        println!("  No markets listed in this window\n");
+        return;
+    }
+
+    let now = clock::now();
+    println!("  {:<48} {:>9} {:>7} {:>12} {:>8}",
+        "Market", "Listed", "YES", "Volume", "Large");
+    for market in markets {
+        let large = match market.large_holders {
+            Some(count) => count.to_string(),
+            None => "-".to_string(),
+        };
+        let flag = if market.accumulating() { "  <- accumulating" } else { "" };
+
+        println!("  {:<48} {:>9} {:>7} {:>12} {:>8}{}",
+            truncate(&market.question, 48),
+            format!("{}h ago", (now - market.created_at).num_hours()),
+            format::price(market.yes_price, 3),
+            format::usd(market.volume),
+            large,
+            flag,
+        );
+    }
+
+    println!();
+    if markets.iter().all(|m| m.large_holders.is_none()) {
+        println!("  Large is unavailable, the local db has no positions");
+    } else {
+        println!("  Large is the number of wallets already holding {}+ in the market", format::usd(min_position));
+    }
+    println!();
+}
+
+pub fn print_closing_soon(markets: &[ClosingMarket], window: TimeDelta, with_smart_money: bool) {
+    print_header(&format!("CLOSING WITHIN {}H", window.num_hours()));
+
+    if markets.is_empty() {
+        println!("  No markets resolve in this window\n");
+        return;
+    }
+
+    let now = clock::now();
+    println!("  {:<48} {:>10} {:>7} {:>10} {:>9}",
+        "Market", "Closes in", "YES", "Smart YES", "Gap");
+    for market in markets {
+        let smart = market.smart_probability
+            .map(|p| format!("{:.1}%", p * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        let gap = market.divergence()
+            .map(|gap| format!("{:+.1}pp", gap * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+
+        println!("  {:<48} {:>10} {:>7} {:>10} {:>9}",
+            truncate(&market.question, 48),
+            format_time_left(market.end_date - now),
+            format::price(market.yes_price, 3),
+            smart,
+            gap,
+        );
+    }
+
+    println!();
+    if with_smart_money {
+        println!("  Gap is smart money YES probability minus the YES price, positive means YES looks cheap");
+    } else {
+        println!("  Smart money is unavailable, the local db has no positions or trader stats");
+    }
+    println!();
+}
+
+pub fn print_order_plan(market: &Market, plan: &OrderPlan, fees: &FeeModel) {
+    let request = &plan.request;
+    print_header("ORDER PLAN (DRY RUN, NOTHING IS PLACED)");
+    println!("  Market: {}", market.question);
+    println!("  Buy {} {:.2} shares, limit {}", request.outcome.as_str(), request.shares, format::price(request.limit_price, 3));
+
+    match plan.best_ask {
+        Some(best) => println!("  Best ask: {}", format::price(best, 3)),
+        None => println!("  Best ask: none, the book has no asks"),
+    }
+
+    println!("\n  Fills now: {:.2} shares over {} levels", plan.filled_shares, plan.levels_used);
+    if let Some(avg) = plan.avg_price {
+        println!("  Avg fill price: {}", format::price(avg, 4));
+    }
+    if let Some(slippage) = plan.slippage {
+        println!("  Slippage vs best ask: {} per share", format::price(slippage, 4));
+    }
+    println!("  Fees ({:.0} bps taker): {}", fees.taker_bps, format::usd(plan.fees));
+    println!("  Total cost: {}", format::usd(plan.total_cost));
+    if plan.unfilled_shares() > 0.0 {
+        println!("  Resting at the limit: {:.2} shares", plan.unfilled_shares());
+    }
+
+    println!();
+    match plan.model_probability {
+        Some(probability) => {
+            println!("  Smart money {} probability: {:.1}%", request.outcome.as_str(), probability * 100.0);
+            if let Some(edge) = plan.edge() {
+                println!("  Edge per share after fees: {:+.1}pp", edge * 100.0);
+            }
+            if let Some(profit) = plan.expected_profit() {
+                println!("  Expected profit on the fill: {}", format::usd(profit));
+            }
+            if let Some(kelly) = plan.kelly {
+                println!("  Kelly stake: {:.1}% of bankroll", kelly * 100.0);
+            }
+        }
+        None => println!("  Edge unavailable, no smart money read for this market"),
+    }
+    println!();
+}
+
+#[cfg(feature = "trading")]
+pub fn print_placed_order(placed: &crate::trading::clob::PlacedOrder, credentials: &crate::trading::Credentials) {
+    print_header("ORDER PLACED");
+    println!("  Order id: {}", placed.order_id);
+    println!("  Status: {}", placed.status);
+    println!("  Maker: {}", credentials.funder);
+    println!();
+}
+
+#[cfg(feature = "trading")]
+pub fn print_cancel_result(result: &crate::trading::clob::CancelResult) {
+    for order_id in &result.canceled {
+        println!("  Cancelled {}", order_id);
+    }
+    for (order_id, reason) in &result.not_canceled {
+        println!("  Not cancelled {}: {}", order_id, reason);
+    }
+    if result.canceled.is_empty() && result.not_canceled.is_empty() {
+        println!("  Nothing was cancelled");
+    }
+    println!();
+}
+
+pub fn print_paper_fill(fill: &PaperFill, cash: f64) {
+    let verb = match fill.side {
+        FillSide::Buy => "Bought",
+        FillSide::Sell => "Sold",
+    };
+    print_header("PAPER FILL");
+    println!("  {} {:.2} {} at {}, fees {}", verb, fill.shares, fill.outcome.as_str(), format::price(fill.price, 4), format::usd(fill.fees));
+    println!("  Cash left: {}", format::usd(cash));
+    println!();
+}
+
+// equity snapshots shown under the paper portfolio
+const PAPER_HISTORY_ROWS: usize = 10;
+
+// marks line up with positions, None when the market couldn't be priced
+pub fn print_paper_portfolio(ledger: &PaperLedger, positions: &[PaperPosition], marks: &[Option<f64>]) {
+    print_header("PAPER PORTFOLIO");
+    println!("  Cash: {}", format::usd(ledger.cash()));
+
+    if positions.is_empty() {
+        println!("  No open positions");
+    } else {
+        println!("\n  {:<44} {:>4} {:>10} {:>10} {:>8} {:>10}",
+            "Market", "Side", "Shares", "Cost", "Mark", "PnL");
+        for (position, mark) in positions.iter().zip(marks) {
+            let (mark, pnl) = match mark {
+                Some(price) => (format::price(*price, 3), format::usd_signed(price * position.shares - position.cost)),
+                None => ("n/a".to_string(), "n/a".to_string()),
+            };
+            println!("  {:<44} {:>4} {:>10.2} {:>10} {:>8} {:>10}",
+                truncate(&position.question, 44),
+                position.outcome.as_str(),
+                position.shares,
+                format::usd(position.cost),
+                mark,
+                pnl,
+            );
+        }
+    }
+
+    let realized: f64 = ledger.positions().iter().map(|p| p.realized_pnl).sum();
+    let fees: f64 = ledger.fills.iter().map(|f| f.fees).sum();
+    println!("\n  Fills: {}", ledger.fills.len());
+    println!("  Realized PnL: {}", format::usd_signed(realized));
+    println!("  Fees paid: {}", format::usd(fees));
+
+    if let Some(latest) = ledger.snapshots.last() {
+        let total = latest.equity() - ledger.starting_cash;
+        println!("  Equity: {} ({}, {:+.1}% on {})",
+            format::usd(latest.equity()),
+            format::usd_signed(total),
+            total / ledger.starting_cash * 100.0,
+            format::usd(ledger.starting_cash),
+        );
+    }
+
+    // the equity curve, most recent last
+    let recent = &ledger.snapshots[ledger.snapshots.len().saturating_sub(PAPER_HISTORY_ROWS)..];
+    if recent.len() > 1 {
+        println!("\n  {:<24} {:>12} {:>12} {:>12}", "Valued at", "Cash", "Positions", "Equity");
+        for snapshot in recent {
+            let time = format::timestamp(snapshot.timestamp);
+            println!("  {:<24} {:>12} {:>12} {:>12}",
+                time,
+                format::usd(snapshot.cash),
+                format::usd(snapshot.positions_value),
+                format::usd(snapshot.equity()),
+            );
+        }
+    }
+    println!();
+}
+
+pub fn print_comparison(summaries: &[Option<MarketSummary>]) {
+    print_header("COMPARISON");
+
+    println!("  {:<32} {:>7} {:>10} {:>8} {:>14} {:>7}",
+        "Slug", "YES", "Smart YES", "Whales", "Volume 24hr", "Trend");
+    for summary in summaries.iter().flatten() {
+        let lean = summary.smart_lean
+            .map(|lean| format!("{:.1}%", lean * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        let trend = summary.volume_trend
+            .map(|trend| format!("{:.2}x", trend))
+            .unwrap_or_else(|| "-".to_string());
+
+        println!("  {:<32} {:>7} {:>10} {:>7.1}% {:>14} {:>7}",
+            truncate(&summary.slug, 32),
+            format::price(summary.yes_price, 3),
+            lean,
+            summary.whale_share * 100.0,
+            format::usd(summary.volume_24h),
+            trend,
+        );
+    }
+
+    let empty = summaries.iter().filter(|s| s.is_none()).count();
+    if empty > 0 {
+        println!("\n  {} groups had no markets and were left out", empty);
+    }
+
+    println!();
+    println!("  Primary market of each group. Smart YES is smart money capital on YES,");
+    println!("  Whales is the capital share of the top {} holders, Trend is 24hr volume vs the weekly daily average", WHALE_TOP_N);
+    println!();
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(width - 3).collect();
+    out.push_str("...");
+    out
+}
+
+pub fn print_backtest_report(report: &BacktestReport) {
+    print_header("BACKTEST");
+
+    let config = &report.config;
+    println!("  Strategy: follow traders with {}", config.smart_money.describe());
+    println!("  Entry window: {} days after the smart trade", config.max_entry_delay_days);
+    println!("  Stake per trade: {}", format::usd(config.stake));
+    println!("  Smart traders: {}", report.smart_traders);
+    println!();
+
+    println!("  Signals: {}", report.signals);
+    println!("  Missed (no fill in window): {}", report.missed_signals);
+    println!("  Open (unresolved): {}", report.open_trades);
+    println!("  Resolved trades: {}", report.trades.len());
+    println!();
+
+    println!("  Total Staked: {}", format::usd(report.total_staked));
+    println!("  Total Returned: {}", format::usd(report.total_returned));
+    println!("  ROI: {:.1}%", report.roi * 100.0);
+    println!("  Hit Rate: {:.1}%", report.hit_rate * 100.0);
+    println!("  Max Drawdown: {}", format::usd(report.max_drawdown));
+
+    println!("\n  Note: traders are judged on the markets resolved before each signal");
+    println!();
+}
+
+pub fn print_compaction(compactions: &[Compaction]) {
+    if compactions.is_empty() {
+        println!("  No local tables to compact");
+        return;
+    }
+
+    let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    println!("  {:<28} {:>10} {:>14} {:>12} {:>20}", "Table", "Partitions", "Files", "Rows", "Size (MB)");
+    for c in compactions {
+        println!(
+            "  {:<28} {:>10} {:>6} -> {:<5} {:>12} {:>8.2} -> {:.2}",
+            c.table,
+            c.partitions,
+            c.files_before,
+            c.files_after,
+            c.rows,
+            mb(c.bytes_before),
+            mb(c.bytes_after),
+        );
+    }
+
+    let stale: usize = compactions.iter().map(|c| c.stale_removed).sum();
+    if stale > 0 {
+        println!("\n  Removed {} temp files left by interrupted writes", stale);
+    }
+    println!();
+}
+
+// what stats rebuild wrote, with the methodology in short
+pub fn print_rebuilt_traders(traders: &[Trader]) {
+    let resolved = traders.iter().filter(|t| t.total_markets_resolved > 0).count();
+    let invested: f64 = traders.iter().map(|t| t.total_invested).sum();
+    let returned: f64 = traders.iter().map(|t| t.total_returned).sum();
+    println!("  Wrote stats for {} traders, {} with a resolved market", traders.len(), resolved);
+    println!("  Invested {} and got back {} over resolved markets", format::usd(invested), format::usd(returned));
+    println!("  A market is a win when sells plus winning shares redeemed at $1 beat the buys, unresolved markets only count as entered");
+    println!();
+}
+
+pub fn print_audit_report(report: &AuditReport) {
+    print_header("DATA QUALITY AUDIT");
+    for (table, rows) in &report.tables {
+        println!("  {:<14} {:>10} rows", table, rows);
+    }
+
+    for (table, _) in &report.tables {
+        println!("\n  {}:", table);
+        for check in report.checks.iter().filter(|c| c.table == *table) {
+            let status = if check.rows == 0 { "ok" } else { "!!" };
+            println!("    {} {:<46} {:>8}", status, check.name, check.rows);
+            for example in &check.examples {
+                println!("         {}", example);
+            }
+        }
+    }
+
+    println!();
+    match report.problems() {
+        0 => println!("  No problems found"),
+        problems => println!("  {} problem rows, a row can fail more than one check", problems),
+    }
+    println!();
+}
+
+pub fn print_calibration_report(report: &CalibrationReport) {
+    print_header("CALIBRATION");
+
+    let config = &report.config;
+    print_smart_money(&config.smart_money);
+    println!("  Predictions rebuilt {} days before resolution", config.days_before);
+    println!("  Trader stats snapshots: {}", report.snapshots);
+    println!("  Smart traders: {}", report.smart_traders);
+    println!("  Scored markets: {} of {} resolved", report.scored_markets, report.resolved_markets);
+    if report.before_first_snapshot > 0 {
+        println!("  Left out: {} closed before the first snapshot, each `stats rebuild` stores one", report.before_first_snapshot);
+    }
+
+    if report.scored_markets == 0 {
+        println!("  No resolved market had a smart holder before the cutoff\n");
+        return;
+    }
+
+    println!();
+    println!("  {:<10} {:>11} {:>8}", "Model", "Predictions", "Brier");
+    for score in &report.scores {
+        println!("  {:<10} {:>11} {:>8.4}", score.name, score.predictions, score.brier);
+    }
+    println!("  Brier is 0 for perfect calls and 0.25 for always saying 50%");
+
+    for score in &report.scores {
+        println!("\n  {} calibration:", score.name);
+        println!("    {:<11} {:>7} {:>10} {:>10}", "Bucket", "Markets", "Predicted", "Resolved");
+        for bucket in &score.buckets {
+            let range = format!("{:.0}-{:.0}%", bucket.low * 100.0, bucket.high * 100.0);
+            println!("    {:<11} {:>7} {:>9.1}% {:>9.1}%",
+                range,
+                bucket.count,
+                bucket.mean_predicted * 100.0,
+                bucket.observed * 100.0,
+            );
+        }
+    }
+
+    println!("\n  Note: traders are judged by the last stats snapshot stored before each cutoff");
+    println!();
+}
+
+pub fn print_leaderboard(traders: &[Trader], category: Option<&str>, limit: usize, min_resolved: u32, book: &AddressBook) {
+    match category {
+        Some(category) => print_header(&format!("LEADERBOARD: {}", category.to_uppercase())),
+        None => print_header("LEADERBOARD"),
+    }
+    println!("  Traders with {}+ resolved markets{}: {}",
+        min_resolved,
+        if category.is_some() { " in the category" } else { "" },
+        traders.len(),
+    );
+    println!("  Ranked by the 95% lower bound on accuracy, stats only count markets in scope");
+
+    if traders.is_empty() {
+        println!();
+        return;
+    }
+
+    // rewards get their own columns once any were ingested, ROI stays what trading alone made
+    let rewarded = traders.iter().take(limit).any(|t| t.rewards.is_some());
+    println!();
+    print!("  {:>4}  {:<44} {:>8} {:>9} {:>11} {:>8} {:>12}", "#", "Trader", "Resolved", "Accuracy", "Lower bound", "ROI", "Invested");
+    println!("{}", if rewarded { format!(" {:>10} {:>11}", "Rewards", "ROI w/ rew") } else { String::new() });
+    for (rank, trader) in traders.iter().take(limit).enumerate() {
+        print!("  {:>4}  {:<44} {:>8} {:>8.1}% {:>10.1}% {:>7.1}% {:>12}",
+            rank + 1,
+            truncate(&book.display(&trader.trader_address), 44),
+            trader.total_markets_resolved,
+            trader.accuracy * 100.0,
+            trader.adjusted_accuracy * 100.0,
+            trader.trading_roi() * 100.0,
+            format::usd(trader.total_invested),
+        );
+        match (rewarded, trader.rewards) {
+            (true, Some(rewards)) => println!(" {:>10} {:>10.1}%", format::usd(rewards), trader.roi_with_rewards() * 100.0),
+            (true, None) => println!(" {:>10} {:>11}", "-", "-"),
+            (false, _) => println!(),
+        }
+    }
+    println!();
+}
+
+// a wallet's overall results and how rough the ride was
+pub fn print_trader_record(trader: Option<&Trader>) {
+    let Some(trader) = trader.filter(|t| t.total_markets_resolved > 0) else {
+        println!("  No resolved markets for this wallet yet\n");
+        return;
+    };
+
+    let count = |value: Option<u32>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+    println!("  Resolved: {} of {} markets, {} won ({:.1}%)",
+        trader.total_markets_resolved,
+        trader.total_markets_entered,
+        trader.total_wins,
+        trader.accuracy * 100.0,
+    );
+    println!("  ROI: {:.1}% on {}", trader.trading_roi() * 100.0, format::usd(trader.total_invested));
+    if let Some(rewards) = trader.rewards {
+        println!("  Liquidity rewards: {}, ROI {:.1}% with them", format::usd(rewards), trader.roi_with_rewards() * 100.0);
+    }
+    println!("  Longest streaks: {} won, {} lost", count(trader.longest_win_streak), count(trader.longest_loss_streak));
+    println!("  Max drawdown: {}", trader.max_drawdown.map_or_else(|| "-".to_string(), format::usd));
+    println!("  ROI volatility per market: {}", trader.return_volatility.map_or_else(|| "-".to_string(), |v| format!("{:.1}%", v * 100.0)));
+    println!();
+}
+
+pub fn print_portfolio(exposures: &[CategoryExposure]) {
+    if exposures.is_empty() {
+        println!("  No trades from this wallet in the local db\n");
+        return;
+    }
+
+    println!("  {:<16} {:>7} {:>8} {:>9} {:>12} {:>8} {:>12}", "Category", "Markets", "Resolved", "Accuracy", "Invested", "ROI", "Open cost");
+    for exposure in exposures {
+        println!("  {:<16} {:>7} {:>8} {:>8.1}% {:>12} {:>7.1}% {:>12}",
+            truncate(&exposure.category, 16),
+            exposure.markets,
+            exposure.resolved,
+            exposure.accuracy() * 100.0,
+            format::usd(exposure.invested),
+            exposure.roi() * 100.0,
+            format::usd(exposure.open_cost),
+        );
+    }
+    println!("\n  Markets with several tags count in each category");
+    println!();
+}
+
+// every market the wallet traded in order, to see where its edge comes from
+pub fn print_market_history(history: &[MarketRecord], questions: &HashMap<String, String>) {
+    print_header("MARKET HISTORY");
+    if history.is_empty() {
+        println!("  No trades from this wallet in the local db\n");
+        return;
+    }
+
+    println!("  {:<17} {:<40} {:<16} {:<4} {:>8} {:>8} {:>12} {:<8} {:>13}", "First trade", "Market", "Category", "Side", "Entry", "Exit", "Invested", "Result", "PnL");
+    for record in history {
+        // older dumps carry no timestamp, the block still orders them
+        let when = record.first_timestamp.map_or_else(
+            || format!("block {}", record.first_block),
+            |t| format::timestamp(t).chars().take(16).collect(),
+        );
+        let market = questions.get(&record.market_id).unwrap_or(&record.market_id);
+        let price = |p: Option<f64>| p.map_or_else(|| "-".to_string(), |p| format::price(p, 3));
+        println!("  {:<17} {:<40} {:<16} {:<4} {:>8} {:>8} {:>12} {:<8} {:>13}",
+            when,
+            truncate(market, 40),
+            truncate(&record.categories.join(","), 16),
+            record.side,
+            price(record.entry_price),
+            price(record.exit_price),
+            format::usd(record.invested),
+            record.outcome.as_deref().unwrap_or("open"),
+            record.pnl.map_or_else(|| "-".to_string(), format::usd_signed),
+        );
+    }
+    println!("\n  Side is where most of the usdc went, entry and exit are average prices on that side");
+    println!();
+}
+
+pub fn print_position_count(count: usize) {
+    print_header("FETCHING POSITION DATA");
+    println!("  Found {} positions for this market", count);
+}
+
+// weighted is how many traders switched to their record in the market's tags
+pub fn print_trader_count(count: usize, weighted: Option<usize>, tags: &[String]) {
+    print_header("TRADER STATS");
+    println!("  Found {} traders", count);
+    if let Some(weighted) = weighted {
+        println!("  {} traders weighted by their record in {}", weighted, tags.join(", "));
+    }
+}
+
+pub fn print_samples(position: Option<&Position>, trader: Option<&Trader>, book: &AddressBook) {
+    println!("Sample data: ");
+    if let Some(position) = position {
+        println!("\n  Sample position:");
+        println!("    Trader: {}", book.display(&position.trader_address));
+        println!("    Side: {}", position.side);
+        println!("    Shares: {}", position.shares_held);
+        println!("    Avg Price: ${:.4}", position.avg_entry_price);
+    }
+
+    if let Some(trader) = trader {
+        println!("\n  Sample trader:");
+        println!("    Address: {}", book.display(&trader.trader_address));
+        println!("    Accuracy: {:.1}% ({:.1}% lower bound)", trader.accuracy * 100.0, trader.adjusted_accuracy * 100.0);
+        println!("    ROI: {:.1}%", trader.roi * 100.0);
+        println!("    Markets: {}", trader.total_markets_resolved);
+    }
+}
+
+pub fn print_wallet_age_breakdown(breakdown: &WalletAgeBreakdown) {
+    print_header("NEW WALLETS VS VETERANS");
+    println!("  Fresh: {} or fewer markets, or first trade within {} days of entering",
+        FRESH_MAX_MARKETS,
+        FRESH_MAX_AGE_DAYS,
+    );
+
+    for (side, split) in [("YES", &breakdown.yes), ("NO", &breakdown.no)] {
+        println!("\n  {}", side);
+        println!("    Fresh wallets: {} holding {}", split.fresh_wallets, format::usd(split.fresh_capital));
+        println!("    Veteran wallets: {} holding {}", split.veteran_wallets, format::usd(split.veteran_capital));
+        println!("    Fresh share of capital: {:.1}%", split.fresh_share() * 100.0);
+    }
+    println!();
+}
+
+// whale dominated or broadly held, per side
+pub fn print_concentration(concentration: &Concentration) {
+    print_header("HOLDER CONCENTRATION");
+    println!("  HHI is the sum of squared capital shares, above {:.2} is flagged as concentrated", CONCENTRATED_HHI);
+
+    for (side, split) in [("YES", &concentration.yes), ("NO", &concentration.no)] {
+        println!("\n  {}", side);
+        if split.holders == 0 {
+            println!("    No holders");
+            continue;
+        }
+
+        let flag = if split.concentrated() { "  (concentrated)" } else { "" };
+        println!("    Holders: {} with {}", split.holders, format::usd(split.capital));
+        println!("    HHI: {:.3}{}", split.hhi, flag);
+        println!("    Top {} share: {:.1}%", CONCENTRATION_TOP_N, split.top_share * 100.0);
+        println!("    Gini: {:.3}", split.gini);
+    }
+    println!();
+}
+
+pub const PNL_TOP_TRADERS: usize = 10;
+
+pub fn print_trader_pnl(pnls: &[TraderPnl], method: CostBasis, rewards: RewardSettings, yes_mark: f64, no_mark: f64, book: &AddressBook) {
+    print_header("TRADER PNL IN THIS MARKET");
+
+    let method = match method {
+        CostBasis::Fifo => "FIFO",
+        CostBasis::Average => "average cost",
+        CostBasis::Lifo => "LIFO",
+    };
+    println!("  Cost basis: {}", method);
+    println!("  Marked at YES {} / NO {}", format::price(yes_mark, 4), format::price(no_mark, 4));
+    println!("  Traders: {}", pnls.len());
+    println!("  Total Realized: {}", format::usd(pnls.iter().map(|p| p.realized).sum::<f64>()));
+    println!("  Total Unrealized: {}", format::usd(pnls.iter().map(|p| p.unrealized).sum::<f64>()));
+    println!("  Fees and redemption gas: {}", format::usd(pnls.iter().map(|p| p.fees).sum::<f64>()));
+    let paid = pnls.iter().map(|p| p.rewards).sum::<f64>();
+    if paid > 0.0 {
+        println!("  Liquidity rewards: {} ({})", format::usd(paid), if rewards.include { "counted in the ranking" } else { "not counted, see [rewards] in config.toml" });
+    }
+
+    let unmatched = pnls.iter().filter(|p| p.unmatched_shares > 0.0).count();
+    if unmatched > 0 {
+        println!("  Note: {} traders sold shares with no matching buy, those sells are left out", unmatched);
+    }
+
+    if !pnls.is_empty() {
+        println!("\n  Top {} by total PnL{}:", PNL_TOP_TRADERS.min(pnls.len()), if rewards.include { " with rewards" } else { "" });
+    }
+    for pnl in pnls.iter().take(PNL_TOP_TRADERS) {
+        let with_rewards = match pnl.rewards > 0.0 {
+            true => format!("  with rewards {}", format::usd_signed(pnl.total_with_rewards())),
+            false => String::new(),
+        };
+        println!("    {}  realized {}  unrealized {}  total {}{}",
+            book.display(&pnl.trader_address),
+            format::usd_signed(pnl.realized),
+            format::usd_signed(pnl.unrealized),
+            format::usd_signed(pnl.total()),
+            with_rewards,
+        );
+    }
+    println!();
+}
+
+// darkest shade is the busiest bucket across both sides
+const SHADES: [char; 5] = [' ', '.', ':', '*', '#'];
+
+pub fn print_heatmap(heatmap: &TradeHeatmap) {
+    print_header(&format!("TRADE HEATMAP ({}, USDC VOLUME)", format::current().tz.label().to_uppercase()));
+
+    println!("  Trades: {}", heatmap.trades);
+    if heatmap.estimated > 0 {
+        println!("  Times estimated from block number: {}", heatmap.estimated);
+    }
+    if heatmap.untimed > 0 {
+        println!("  Skipped (no timestamp): {}", heatmap.untimed);
+    }
+
+    let peak = heatmap.peak();
+    if peak <= 0.0 {
+        println!("\n  Nothing to plot");
+        return;
+    }
+
+    for (side, grid) in [("YES", &heatmap.yes), ("NO", &heatmap.no)] {
+        println!("\n  {}", side);
+        println!("        0     6     12    18   23");
+        for (weekday, hours) in WEEKDAYS.iter().zip(grid.iter()) {
+            let row: String = hours
+                .iter()
+                .map(|volume| {
+                    if *volume <= 0.0 {
+                        SHADES[0]
+                    } else {
+                        // anything traded gets at least the lightest mark
+                        let level = (volume / peak * (SHADES.len() - 1) as f64).ceil() as usize;
+                        SHADES[level.clamp(1, SHADES.len() - 1)]
+                    }
+                })
+                .collect();
+            println!("  {}  |{}|", weekday, row);
+        }
+    }
+
+    println!("\n  Scale: '{}' low to '{}' = {} per bucket", SHADES[1], SHADES[SHADES.len() - 1], format::usd(peak));
+    println!();
+}
+
+pub fn print_replay_start(market: &Market, tape: &Tape, speed: f64, min_usdc: f64) {
+    let (Some(first), Some(last)) = (tape.prints.first(), tape.prints.last()) else {
+        return;
+    };
+    print_header(&format!("REPLAY: {}", market.question));
+    println!("  {} fills from {} to {}", tape.prints.len(),
+        format::timestamp(first.transaction.timestamp.unwrap_or_default()),
+        format::timestamp(last.transaction.timestamp.unwrap_or_default()));
+    if tape.untimed > 0 {
+        println!("  {} fills without a timestamp left out", tape.untimed);
+    }
+    println!("  Playing at {}x, no pause longer than {}s, calling out smart money and first buys of {} or more", speed, MAX_REPLAY_PAUSE_SECS, format::usd(min_usdc));
+    println!();
+    println!("  {:<24} {:<4} {:<4} {:>10} {:>7} {:>7} {:>12} {:>12}  Trader",
+        "Time", "Act", "Side", "Shares", "Price", "YES", "YES bought", "NO bought");
+}
+
+// one line per fill as it comes up
+pub fn print_tape_print(print: &TapePrint, book: &AddressBook) {
+    let tx = &print.transaction;
+    let callout = match print.notable {
+        Some(NotableEntry::Smart) => "  << smart money enters",
+        Some(NotableEntry::Large) => "  << large first buy",
+        None => "",
+    };
+    println!("  {:<24} {:<4} {:<4} {:>10} {:>7} {:>7} {:>12} {:>12}  {}{}",
+        format::timestamp(tx.timestamp.unwrap_or_default()),
+        tx.action,
+        tx.side,
+        format::current().number(tx.shares, 1),
+        format::price(print.price, 3),
+        format::price(print.yes_price, 3),
+        format::usd(print.yes_volume),
+        format::usd(print.no_volume),
+        truncate(&book.display(&tx.trader_address), 42),
+        callout,
+    );
+}
+
+pub fn print_replay_end(tape: &Tape) {
+    let Some(last) = tape.prints.last() else {
+        return;
+    };
+    let smart = tape.prints.iter().filter(|p| p.notable == Some(NotableEntry::Smart)).count();
+    let large = tape.prints.iter().filter(|p| p.notable == Some(NotableEntry::Large)).count();
+    println!();
+    println!("  End of tape, YES last traded at {}", format::price(last.yes_price, 3));
+    println!("  Bought: {} into YES, {} into NO", format::usd(last.yes_volume), format::usd(last.no_volume));
+    println!("  Smart money entries: {}, large first buys: {}\n", smart, large);
+}
+
+pub fn print_big_trades(
+    trades: &[BigTrade],
+    total: usize,
+    min_usdc: f64,
+    traders: &HashMap<String, Trader>,
+    smart_money: &SmartMoney,
+    book: &AddressBook,
+) {
+    print_header("BIG TRADES");
+    println!("  Trades of {} or more: {}", format::usd(min_usdc), total);
+    if trades.len() < total {
+        println!("  Showing the largest {}", trades.len());
+    }
+    if trades.is_empty() {
+        println!();
+        return;
+    }
+
+    println!();
+    println!("  {:<24} {:<42} {:<4} {:<4} {:>12} {:>12} {:>7} {:<6} {:>8} {:>9} {:>7}",
+        "Time", "Trader", "Side", "Act", "Shares", "USDC", "Price", "Change", "Resolved", "Accuracy", "ROI");
+    for trade in trades {
+        let tx = &trade.transaction;
+        // older dumps carry no timestamp, the block still orders them
+        let time = tx.timestamp.map_or_else(|| format!("block {}", tx.block_number), format::timestamp);
+        let price = if tx.shares > 0.0 { format::price(tx.usdc_amount / tx.shares, 3) } else { "-".to_string() };
+        let record = match traders.get(&tx.trader_address) {
+            Some(trader) => format!("{:>8} {:>8.1}% {:>6.1}%{}",
+                trader.total_markets_resolved,
+                trader.accuracy * 100.0,
+                trader.roi * 100.0,
+                if smart_money.includes(trader) { "  smart" } else { "" },
+            ),
+            None => format!("{:>8} {:>9} {:>7}", "-", "-", "-"),
+        };
+        println!("  {:<24} {:<42} {:<4} {:<4} {:>12} {:>12} {:>7} {:<6} {}",
+            time,
+            truncate(&book.display(&tx.trader_address), 42),
+            tx.side,
+            tx.action,
+            format::current().number(tx.shares, 1),
+            format::usd(tx.usdc_amount),
+            price,
+            trade.change.as_str(),
+            record,
+        );
+    }
+    println!("\n  Change is against the trader's holding of that outcome before the trade");
+    println!();
+}
+
+pub fn print_position_changes(deltas: &[PositionDelta], total: usize, days: u32, book: &AddressBook) {
+    print_header(&format!("POSITION CHANGES (LAST {} DAYS)", days));
+    println!("  Traders with a net move: {}", total);
+    if deltas.len() < total {
+        println!("  Showing the largest {}", deltas.len());
+    }
+    if deltas.is_empty() {
+        println!();
+        return;
+    }
+
+    println!();
+    let shares = |value: f64| format::current().number(value, 0);
+    for delta in deltas {
+        let who = book.display(&delta.trader_address);
+        let line = match delta.change() {
+            PositionChange::Open => format!("{} entered {} with {} shares", who, delta.side, shares(delta.after)),
+            PositionChange::Add => format!("{} added {} {} shares (now {})", who, shares(delta.shares()), delta.side, shares(delta.after)),
+            PositionChange::Reduce => format!("{} cut {} by {} shares (now {})", who, delta.side, shares(-delta.shares()), shares(delta.after)),
+            PositionChange::Close => format!("{} exited {} ({} shares)", who, delta.side, shares(delta.before)),
+        };
+        println!("  {}", line);
+    }
+
+    println!();
+    for side in ["YES", "NO"] {
+        let net: f64 = deltas.iter().filter(|d| d.side == side).map(PositionDelta::shares).sum();
+        let sign = if net < 0.0 { "-" } else { "+" };
+        println!("  Net {} shares among those shown: {}{}", side, sign, shares(net.abs()));
+    }
+    println!("\n  Holdings are rebuilt from the trade log, shares sold past what it shows bought count as zero");
+    println!();
+}
+
+pub fn print_insiders(market: &Market, report: &InsiderReport, min_score: f64, limit: usize, book: &AddressBook) {
+    print_header("INSIDER PATTERNS");
+    println!("  Holders: {}", report.holders);
+    println!("  Market volume: {} (obscurity {:.0}%)", format::usd(report.volume), report.obscurity * 100.0);
+    match market.end_date {
+        Some(end) => println!("  Scheduled end: {}", format::datetime(end)),
+        None => println!("  No scheduled end, timing doesn't count towards the score"),
+    }
+    if report.untimed > 0 {
+        println!("  {} holders have no timed buy in the trade log and aren't scored on timing", report.untimed);
+    }
+
+    let flagged: Vec<_> = report.flagged(min_score).collect();
+    println!("  Scoring {:.2} or more: {}", min_score, flagged.len());
+    if flagged.len() > limit {
+        println!("  Showing the top {}", limit);
+    }
+
+    for signal in flagged.iter().take(limit) {
+        println!();
+        println!("  {:.2}  {}  {} {}",
+            signal.score,
+            book.display(&signal.trader_address),
+            format::usd(signal.capital),
+            signal.side,
+        );
+        for reason in &signal.reasons {
+            let line = match reason {
+                InsiderReason::NoHistory => "no trading history on record".to_string(),
+                InsiderReason::FewMarkets(markets) => format!("has traded only {} markets", markets),
+                InsiderReason::YoungWallet(days) => format!("first traded {:.1} days before entering here", days),
+                InsiderReason::LargePosition(share) => format!("larger than {:.0}% of the other holders", share * 100.0),
+                InsiderReason::OneSided(share) => format!("{:.0}% of the capital on {}", share * 100.0, signal.side),
+                InsiderReason::ObscureMarket(volume) => format!("niche market, {} traded in total", format::usd(*volume)),
+                InsiderReason::LateEntry(days) => format!("first bought {:.1} days before the scheduled end", days),
+            };
+            println!("        - {}", line);
+        }
+    }
+
+    println!("\n  A pattern, not proof: the score weighs wallet age, size, one sidedness, market volume and timing");
+    println!();
+}
+
+pub fn print_crypto_fair_values(values: &[CryptoFairValue], vol_days: u32, volatility_given: bool) {
+    print_header("CRYPTO FAIR VALUE");
+    if values.is_empty() {
+        println!("  No market could be priced\n");
+        return;
+    }
+    println!("  Binary option value with a driftless lognormal price, touch markets by the reflection principle");
+
+    let mut asset = "";
+    for value in values {
+        if value.asset != asset {
+            asset = value.asset;
+            let source = match volatility_given {
+                true => "--volatility".to_string(),
+                false => format!("{} day realized", vol_days),
+            };
+            println!("\n  {} spot {}  volatility {:.1}% ({})", asset, format::usd(value.spot), value.volatility * 100.0, source);
+            println!("  {:<8} {:>14} {:>9} {:>7} {:>7} {:>7} {:>7} {:>8} {:>9}",
+                "Kind", "Strike", "Distance", "Sigmas", "Days", "Fair", "Market", "Edge", "Decay/day");
+        }
+        println!("  {:<8} {:>14} {:>8.1}% {:>7} {:>7.1} {:>7} {:>7} {:>8} {:>9}",
+            value.kind.as_str(),
+            format::usd(value.strike),
+            value.distance * 100.0,
+            value.sigmas.map_or("-".to_string(), |sigmas| format!("{:+.2}", sigmas)),
+            value.days_left,
+            format::price(value.fair_yes, 3),
+            format::price(value.market_yes, 3),
+            format::price_signed(value.edge, 3),
+            format::price_signed(value.decay_per_day, 4),
+        );
+    }
+    println!("\n  Edge is fair minus market, positive means YES is cheap; decay is how the fair value moves over one more day at this spot\n");
+}
+
+pub fn print_parity(report: &ParityReport) {
+    print_header("LINE PARITY");
+    println!("  Event: {}", report.event);
+    if let Some(starts_at) = report.starts_at {
+        println!("  Starts: {}", format::datetime(starts_at));
+    }
+    if let Some(overround) = report.overround {
+        println!("  Bookmaker margin: {:.1}% (taken out of the consensus)", overround * 100.0);
+    }
+    if report.lines.is_empty() {
+        println!("  No bookmaker quotes enough of the event's outcomes to take its margin out\n");
+        return;
+    }
+
+    println!("\n  {:<40} {:>10} {:>10} {:>8} {:>6} {:>9}  At",
+        "Outcome", "Polymarket", "Consensus", "Gap", "Books", "Best odds");
+    for line in &report.lines {
+        // rich when polymarket asks more than the books think it's worth
+        let label = match (line.flagged, line.gap() > 0.0) {
+            (false, _) => "",
+            (true, true) => "  rich",
+            (true, false) => "  cheap",
+        };
+        println!("  {:<40} {:>10} {:>10} {:>8} {:>6} {:>9}  {}{}",
+            truncate(&line.outcome, 40),
+            format::price(line.polymarket_price, 3),
+            format::price(line.consensus, 3),
+            format::price_signed(line.gap(), 3),
+            line.bookmakers,
+            format::current().number(line.best_odds, 2),
+            line.best_bookmaker,
+            label,
+        );
+    }
+
+    match report.flagged() {
+        0 => println!("\n  Polymarket is within {} of the books on every outcome", format::price(report.threshold, 2)),
+        flagged => println!("\n  {} outcomes differ from the books by more than {}", flagged, format::price(report.threshold, 2)),
+    }
+    println!();
+}
+
+pub fn print_funding(report: &FundingReport, lookback_days: u32, book: &AddressBook) {
+    print_header("FUNDING SOURCES");
+    let traced = report.holders.iter().filter(|h| h.source.is_some()).count();
+    println!("  Top holders: {}, funding found for {} within {} days before their first trade", report.holders.len(), traced, lookback_days);
+    if report.holders.is_empty() {
+        println!();
+        return;
+    }
+
+    println!();
+    println!("  {:<42} {:>12}  {:<42} {:<8} {:>12} {:>8}", "Holder", "Capital", "Funded by", "Kind", "Amount", "Lead");
+    for holder in &report.holders {
+        let funding = match (&holder.source, holder.kind) {
+            (Some(source), Some(kind)) => format!("{:<42} {:<8} {:>12} {:>8}",
+                truncate(&book.display(&source.funder), 42),
+                kind.as_str(),
+                format::usd(source.amount),
+                holder.lead_blocks().map_or_else(|| "-".to_string(), |blocks| format!("{:.1}d", blocks as f64 / BLOCKS_PER_DAY as f64)),
+            ),
+            _ if holder.first_activity_block.is_none() => "no first trade block, not traced".to_string(),
+            _ => "nothing found".to_string(),
+        };
+        println!("  {:<42} {:>12}  {}{}",
+            truncate(&book.display(&holder.address), 42),
+            format::usd(holder.capital),
+            funding,
+            if holder.fresh() { "  fresh" } else { "" },
+        );
+    }
+
+    println!();
+    if report.clusters.is_empty() {
+        println!("  No funding wallet is shared by two or more top holders");
+    } else {
+        println!("  Shared funding wallets, possibly one person behind several accounts:");
+        for cluster in &report.clusters {
+            println!("    {} funded {} holders with {} in the market",
+                book.display(&cluster.funder),
+                cluster.holders.len(),
+                format::usd(cluster.capital),
+            );
+            for holder in &cluster.holders {
+                println!("      {}", book.display(holder));
+            }
+        }
+    }
+
+    let fresh = report.holders.iter().filter(|h| h.fresh()).count();
+    println!("  Fresh wallets, bridged or withdrawn from an exchange within {:.0} day of trading: {}", FRESH_FUNDING_BLOCKS as f64 / BLOCKS_PER_DAY as f64, fresh);
+    println!("\n  Exchanges are senders labeled with \"exchange\" in the address book, bridge means minted by the polygon bridge");
+    println!();
+}
+
+pub fn print_postmortem(report: &Postmortem, smart_money: &SmartMoney) {
+    print_header(&format!("POSTMORTEM: RESOLVED {}", report.outcome.to_uppercase()));
+    println!("  Resolution block: {}", report.resolution_block);
+    println!("  Trades: {}", report.trades);
+
+    println!();
+    println!("  {:<14} {:>7} {:>12} {:>9} {:>9} {:>12} {:>12} {:>13}", "Cohort", "Traders", "Bought", "Won at", "Lost at", "Payout", "Sold", "Net");
+    for cohort in &report.cohorts {
+        let entry = |price: Option<f64>| price.map_or_else(|| "-".to_string(), |p| format::price(p, 3));
+        println!("  {:<14} {:>7} {:>12} {:>9} {:>9} {:>12} {:>12} {:>13}",
+            cohort.name,
+            cohort.traders,
+            format::usd(cohort.bought),
+            entry(cohort.winning_entry),
+            entry(cohort.losing_entry),
+            format::usd(cohort.payout),
+            format::usd(cohort.proceeds),
+            format::usd_signed(cohort.net()),
+        );
+    }
+    println!("\n  Won at and lost at are average prices paid for the winning and losing outcome");
+    println!("  A winner got back more than they put in, fees aren't counted");
+
+    print_header(&format!("SMART MONEY SIGNAL ({} MODEL)", smart_money.model.as_str().to_uppercase()));
+    if report.signal.is_empty() {
+        println!("  No trades to rebuild it from\n");
+        return;
+    }
+
+    println!("  {:>12} {:>11} {:>10} {:>8} {:>10} {:>6}", "Block", "Days before", "YES price", "Holders", "Smart YES", "Right");
+    let yes_won = report.yes_won();
+    for point in &report.signal {
+        let probability = |p: Option<f64>| p.map_or_else(|| "-".to_string(), |p| format::price(p, 3));
+        let right = match point.right(yes_won) {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "-",
+        };
+        println!("  {:>12} {:>11.1} {:>10} {:>8} {:>10} {:>6}",
+            point.block,
+            point.days_before,
+            probability(point.yes_price),
+            point.smart_holders,
+            probability(point.smart_yes),
+            right,
+        );
+    }
+
+    let called: Vec<bool> = report.signal.iter().filter_map(|p| p.right(yes_won)).collect();
+    println!("\n  Smart money leaned the right way at {} of {} checkpoints", called.iter().filter(|r| **r).count(), called.len());
+    println!("  Note: trader accuracy includes this market's result (look-ahead bias)");
+    println!();
+}
+
+// after the report for people, on stderr when stdout carries json or arrow
+pub fn print_request_stats(stats: &RequestStatsSnapshot, output_format: OutputFormat) {
+    let lines = "=".repeat(67);
+    let text = [
+        format!("\n{}\nAPI CALL STATS\n{}", lines, lines),
+        format!("  Requests: {}", stats.requests),
+        format!("  Failed: {}", stats.failures),
+        format!("  Retries: {}", stats.retries),
+        format!("  Cache Hits: {}", stats.cache_hits),
+        format!("  Response Bodies: {:.1} KB (decompressed)", stats.body_bytes as f64 / 1024.0),
+        format!("  Total Latency: {:.2}s", stats.total_latency.as_secs_f64()),
+        format!("  Avg Latency: {:.0}ms", stats.average_latency().as_secs_f64() * 1000.0),
+    ]
+    .join("\n");
+
+    if output_format.is_machine_readable() {
+        eprintln!("{}\n", text);
+    } else {
+        println!("{}\n", text);
+    }
+}
+
+// on stderr so it doesn't end up in piped output
+pub fn print_schema_drift(drift: &SchemaDriftReport) {
+    eprintln!("Warning: the gamma api response differs from the expected schema");
+    print_object_drift("events", &drift.events);
+    print_object_drift("markets", &drift.markets);
+    eprintln!();
+}
+
+fn print_object_drift(kind: &str, drift: &ObjectDrift) {
+    if drift.is_empty() {
+        return;
+    }
+    eprintln!("  {} checked: {}", kind, drift.checked);
+    for (field, count) in &drift.unknown {
+        eprintln!("    unknown field {}: {}/{}", field, count, drift.checked);
+    }
+    for (field, count) in &drift.missing {
+        eprintln!("    missing field {}: {}/{}", field, count, drift.checked);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::analysis::{coherence, concentration, implied_return, order_flow, pnl, vwap, wallet_age, Concentration, CostBasis, FeeModel, GroupCoherence, ImpliedReturns, OrderFlowReport, SmartMoney, TraderPnl, VwapReport, WalletAgeBreakdown};
+use crate::address_book::AddressBook;
+use crate::cli::handlers::market_traders;
+use crate::cli::output;
+use crate::clock;
+use crate::error::{AppError, HttpError};
+use crate::standard_data::models::{Market, MarketGroup};
+use crate::standard_data::providers::{MarketMetadataProvider, PositionProvider, TraderStatsProvider, TransactionProvider};
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// requests handled at the same time
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+// request line plus headers, bodies are never read
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+// a client gets this long to send its request line, one that never does would otherwise hold a slot for good
+pub const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// why no request line could be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestError {
+    Malformed,
+    TimedOut,
+}
+
+// body of /markets/{slug}/analysis
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AnalysisReport {
+    // see the versioning policy in output, older versions are served with ?report_version=
+    pub report_version: u32,
+    pub group: MarketGroup,
+    pub coherence: Option<GroupCoherence>,
+    // sections for the first market of the group, null when the group has none
+    pub primary: Option<PrimaryAnalysis>,
+}
+
+// same sections as the analyze command
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PrimaryAnalysis {
+    pub market: Market,
+    pub positions: usize,
+    pub traders: usize,
+    pub wallet_age: WalletAgeBreakdown,
+    pub concentration: Concentration,
+    // null when no smart trader holds the market
+    pub implied_returns: Option<ImpliedReturns>,
+    pub smart_money: SmartMoney,
+    pub cost_basis: CostBasis,
+    pub fees: FeeModel,
+    pub pnl: Vec<TraderPnl>,
+    pub vwap: VwapReport,
+    // since version 2
+    pub order_flow: OrderFlowReport,
+}
+
+// raw http/1.1 response, every connection is closed after one request
+pub fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body,
+    )
+}
+
+// method and path from the request line, a client too slow to send it times out
+pub async fn read_request_line(stream: &mut TcpStream) -> Result<(String, String), RequestError> {
+    tokio::time::timeout(REQUEST_READ_TIMEOUT, read_line(stream))
+        .await
+        .map_err(|_| RequestError::TimedOut)?
+        .ok_or(RequestError::Malformed)
+}
+
+// None for anything malformed
+async fn read_line(stream: &mut TcpStream) -> Option<(String, String)> {
+    let mut buf = vec![0u8; MAX_REQUEST_BYTES];
+    let mut filled = 0;
+
+    while filled < buf.len() {
+        let read = stream.read(&mut buf[filled..]).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+        if buf[..filled].windows(2).any(|w| w == b"\r\n") {
+            break;
+        }
+    }
+
+    let request = String::from_utf8_lossy(&buf[..filled]);
+    let mut parts = request.lines().next()?.split_whitespace();
+    Some((parts.next()?.to_string(), parts.next()?.to_string()))
+}
+
+// serve the analysis as json until the process is stopped
+// connections are handled inside this future so the providers can stay borrowed
+// reports come out as report_version unless a request asks for another
+pub async fn serve<M, D>(
+    addr: SocketAddr,
+    report_version: u32,
+    smart_money: &SmartMoney,
+    fees: &FeeModel,
+    market_provider: &M,
+    db: &D,
+) -> std::io::Result<()>
+where
+    M: MarketMetadataProvider,
+    D: TraderStatsProvider + PositionProvider + TransactionProvider,
+{
+    let listener = TcpListener::bind(addr).await?;
+    println!("  Listening on http://{}", listener.local_addr()?);
+    println!("  GET /markets/{{slug}}/analysis[?cost_basis=fifo|avg|lifo][&report_version={}..{}]", output::OLDEST_REPORT_VERSION, output::REPORT_VERSION);
+    println!("  Reports are version {} unless asked otherwise", report_version);
+    println!("  GET /traders/{{address}}");
+
+    let connections = futures::stream::unfold(listener, |listener| async move {
+        let accepted = listener.accept().await;
+        Some((accepted, listener))
+    });
+
+    connections
+        .for_each_concurrent(MAX_CONCURRENT_REQUESTS, |accepted| async move {
+            let Ok((mut stream, _)) = accepted else {
+                return;
+            };
+
+            let reply = match read_request_line(&mut stream).await {
+                Ok((method, target)) if method == "GET" => route(&target, report_version, smart_money, fees, market_provider, db).await,
+                Ok(_) => error_reply("405 Method Not Allowed", "http.method", "only GET is supported"),
+                Err(RequestError::Malformed) => error_reply("400 Bad Request", "http.bad_request", "malformed request"),
+                Err(RequestError::TimedOut) => error_reply("408 Request Timeout", "http.timeout", "no request line received in time"),
+            };
+
+            let _ = stream.write_all(reply.as_bytes()).await;
+        })
+        .await;
+
+    Ok(())
+}
+
+async fn route<M, D>(target: &str, report_version: u32, smart_money: &SmartMoney, fees: &FeeModel, market_provider: &M, db: &D) -> String
+where
+    M: MarketMetadataProvider,
+    D: TraderStatsProvider + PositionProvider + TransactionProvider,
+{
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    let result = match segments.as_slice() {
+        ["markets", slug, "analysis"] => {
+            let cost_basis = match query_param(query, "cost_basis") {
+                None => Ok(CostBasis::Fifo),
+                Some(value) => <CostBasis as clap::ValueEnum>::from_str(value, true)
+                    .map_err(|_| format!("unknown cost_basis {}", value)),
+            };
+
+            let version = match query_param(query, "report_version") {
+                None => Ok(report_version),
+                Some(value) => value.parse().map_err(|_| format!("unknown report_version {}", value)).and_then(output::check_report_version),
+            };
+
+            match (cost_basis, version) {
+                (Ok(cost_basis), Ok(version)) => market_analysis(slug, cost_basis, smart_money, fees, market_provider, db, db, db)
+                    .await
+                    .map(|report| Some(output::report_as_version(json!(report), version))),
+                (Err(message), _) | (_, Err(message)) => return error_reply("400 Bad Request", "http.bad_request", &message),
+            }
+        }
+        ["traders", address] => trader_stats(address, db).await,
+        _ => return error_reply("404 Not Found", "http.not_found", "unknown route"),
+    };
+
+    match result {
+        Ok(Some(body)) => response("200 OK", "application/json", &body.to_string()),
+        Ok(None) => error_reply("404 Not Found", "http.not_found", "nothing found"),
+        Err(e) => {
+            let status = match &e {
+                AppError::Http(HttpError::Status { status, .. }) if status.as_u16() == 404 => "404 Not Found",
+                AppError::Http(_) | AppError::Parse(_) => "502 Bad Gateway",
+                _ => "500 Internal Server Error",
+            };
+            error_reply(status, e.code(), &e.to_string())
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn market_analysis<M, T, P, X>(
+    market_slug: &str,
+    cost_basis: CostBasis,
+    smart_money: &SmartMoney,
+    fees: &FeeModel,
+    market_provider: &M,
+    trader_provider: &T,
+    position_provider: &P,
+    transaction_provider: &X,
+) -> crate::error::Result<AnalysisReport>
+where
+    M: MarketMetadataProvider,
+    T: TraderStatsProvider,
+    P: PositionProvider,
+    X: TransactionProvider,
+{
+    let market_group = market_provider.get_market_group(market_slug).await?;
+    let coherence = coherence::check_group(&market_group);
+
+    let Some(market) = market_group.markets.first() else {
+        return Ok(AnalysisReport { report_version: output::REPORT_VERSION, group: market_group, coherence, primary: None });
+    };
+
+    let positions = position_provider.get_positions(&market.condition_id).await?;
+    let addresses: Vec<String> = positions.iter().map(|p| p.trader_address.clone()).collect();
+    let traders = market_traders(trader_provider, &addresses, &market.tags).await?;
+    let transactions = transaction_provider.get_market_transactions(&market.condition_id).await?;
+    let groups = AddressBook::load()?.wallet_groups();
+
+    let (yes_mark, no_mark) = pnl::outcome_marks(market);
+    let primary = PrimaryAnalysis {
+        market: market.clone(),
+        positions: positions.len(),
+        traders: traders.len(),
+        wallet_age: wallet_age::wallet_age_breakdown(&positions, &traders),
+        concentration: concentration::concentration(&positions),
+        implied_returns: implied_return::implied_returns(market, &positions, &traders, smart_money, &groups, fees, clock::now()),
+        smart_money: *smart_money,
+        cost_basis,
+        fees: *fees,
+        pnl: pnl::reconstruct_pnl(&transactions, cost_basis, yes_mark, no_mark, fees),
+        vwap: vwap::vwap_windows(&transactions, &vwap::DEFAULT_VWAP_WINDOWS),
+        order_flow: order_flow::order_flow(&transactions, order_flow::DEFAULT_OFI_WINDOW_HOURS, order_flow::OFI_WINDOWS),
+    };
+
+    Ok(AnalysisReport { report_version: output::REPORT_VERSION, group: market_group, coherence, primary: Some(primary) })
+}
+
+async fn trader_stats<T: TraderStatsProvider>(address: &str, trader_provider: &T) -> crate::error::Result<Option<Value>> {
+    let traders = trader_provider.get_traders_by_addresses(&[address.to_string()]).await?;
+    Ok(traders.into_iter().next().map(|trader| json!(trader)))
+}
+
+fn error_reply(status: &str, code: &str, message: &str) -> String {
+    let body = json!({ "code": code, "message": message });
+    response(status, "application/json", &body.to_string())
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
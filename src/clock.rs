@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use std::sync::OnceLock;
+
+// "now" for --deterministic runs, 2026-01-01 00:00:00 utc
+pub const DETERMINISTIC_NOW: i64 = 1_767_225_600;
+
+static FIXED: OnceLock<DateTime<Utc>> = OnceLock::new();
+
+// pin the clock for the rest of the run, set once before anything reads it, later calls are ignored
+pub fn fix(now: DateTime<Utc>) {
+    let _ = FIXED.set(now);
+}
+
+pub fn is_fixed() -> bool {
+    FIXED.get().is_some()
+}
+
+// what reports and relative times are computed against, the wall clock unless pinned
+// timing, caching and capture metadata keep using the real clock
+pub fn now() -> DateTime<Utc> {
+    FIXED.get().copied().unwrap_or_else(Utc::now)
+}
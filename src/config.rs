@@ -0,0 +1,45 @@
+use crate::adapters::{ApiUrls, WebhookTarget};
+use crate::analysis::collateral::CollateralRates;
+use crate::analysis::fees::FeeModel;
+use crate::analysis::rewards::RewardSettings;
+use crate::analysis::smart_money::SmartMoney;
+use crate::error::{DataError, Result};
+use crate::paths;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_FILE: &str = "config.toml";
+
+// user settings, every section is optional and falls back to its defaults
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub fees: FeeModel,
+    pub smart_money: SmartMoney,
+    pub rewards: RewardSettings,
+    pub collateral: CollateralRates,
+    pub api: ApiUrls,
+    // every analysis report is also POSTed to each of these
+    pub webhooks: Vec<WebhookTarget>,
+}
+
+impl Config {
+    // config.toml in the platform config dir, see paths
+    pub fn default_path() -> PathBuf {
+        paths::config_file(CONFIG_FILE)
+    }
+
+    // a missing file means all defaults, a broken one is an error so typos don't go unnoticed
+    pub fn load() -> Result<Self> {
+        let path = Self::default_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(&path)?;
+        let config = toml::from_str(&text)
+            .map_err(|e| DataError::Corrupt(format!("{}: {}", path.display(), e)))?;
+        Ok(config)
+    }
+}
@@ -0,0 +1,33 @@
+// which kinds of data a source can actually serve, probed once before a command runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub market_metadata: bool,
+    pub traders: bool,
+    pub positions: bool,
+    pub transactions: bool,
+    pub resolutions: bool,
+    pub tags: bool,
+}
+
+impl Capabilities {
+    pub fn full() -> Self {
+        Self {
+            market_metadata: true,
+            traders: true,
+            positions: true,
+            transactions: true,
+            resolutions: true,
+            tags: true,
+        }
+    }
+
+    // positions are only useful together with the stats of whoever holds them
+    pub fn holders(&self) -> bool {
+        self.positions && self.traders
+    }
+
+    // everything the local db side is needed for
+    pub fn local_db(&self) -> bool {
+        self.holders() && self.transactions
+    }
+}
@@ -0,0 +1,269 @@
+use crate::analysis::collateral::CollateralRates;
+use crate::data_sources::local_db::schema::{ColumnType, MigrationStep, MIGRATIONS};
+use crate::error::Result;
+use crate::standard_data::models::Collateral;
+use duckdb::Connection;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+// same trader methodology as ingest::trader_stats but grouped inside duckdb
+// so millions of transactions never have to be materialized in rust
+const TRADER_STATS_SQL: &str = "
+WITH ledgers AS (
+    SELECT
+        trader_address,
+        market_id,
+        MIN(block_number) AS first_block,
+        SUM(CASE WHEN upper(action) IN ('SELL', 'MERGE', 'REDEEM') THEN 0 ELSE {usd_amount} END) AS invested,
+        SUM(CASE WHEN upper(action) IN ('SELL', 'MERGE', 'REDEEM') THEN {usd_amount} ELSE 0 END) AS proceeds,
+        SUM(CASE WHEN upper(side) = 'YES'
+            THEN (CASE WHEN upper(action) IN ('SELL', 'MERGE', 'REDEEM') THEN -shares ELSE shares END) ELSE 0 END) AS yes_shares,
+        SUM(CASE WHEN upper(side) = 'YES'
+            THEN 0 ELSE (CASE WHEN upper(action) IN ('SELL', 'MERGE', 'REDEEM') THEN -shares ELSE shares END) END) AS no_shares
+    FROM transactions
+    GROUP BY trader_address, market_id
+),
+scored AS (
+    SELECT
+        l.*,
+        r.outcome,
+        r.resolution_block,
+        l.proceeds + GREATEST(
+            CASE WHEN upper(r.outcome) = 'YES' THEN l.yes_shares ELSE l.no_shares END, 0
+        ) AS returned
+    FROM ledgers l
+    LEFT JOIN market_resolutions r ON r.condition_id = l.market_id
+),
+totals AS (
+    SELECT
+        trader_address,
+        COUNT(*) AS entered,
+        COUNT(outcome) AS resolved,
+        COUNT(*) FILTER (WHERE outcome IS NOT NULL AND returned > invested) AS wins,
+        COALESCE(SUM(invested) FILTER (WHERE outcome IS NOT NULL), 0) AS invested,
+        COALESCE(SUM(returned) FILTER (WHERE outcome IS NOT NULL), 0) AS returned,
+        MIN(first_block) AS first_block
+    FROM scored
+    GROUP BY trader_address
+),
+-- resolved markets in resolution order, ties by condition id like the rust path
+results AS (
+    SELECT
+        trader_address,
+        resolution_block,
+        market_id,
+        returned > invested AS won,
+        CASE WHEN invested > 0 THEN (returned - invested) / invested END AS market_roi,
+        SUM(returned - invested) OVER ordered AS cumulative,
+        ROW_NUMBER() OVER ordered
+            - ROW_NUMBER() OVER (PARTITION BY trader_address, returned > invested ORDER BY resolution_block, market_id) AS run
+    FROM scored
+    WHERE outcome IS NOT NULL
+    WINDOW ordered AS (PARTITION BY trader_address ORDER BY resolution_block, market_id ROWS UNBOUNDED PRECEDING)
+),
+drawdowns AS (
+    SELECT
+        *,
+        GREATEST(MAX(cumulative) OVER (PARTITION BY trader_address ORDER BY resolution_block, market_id ROWS UNBOUNDED PRECEDING), 0)
+            - cumulative AS drawdown
+    FROM results
+),
+streaks AS (
+    SELECT trader_address, won, COUNT(*) AS length
+    FROM results
+    GROUP BY trader_address, won, run
+),
+risk AS (
+    SELECT
+        trader_address,
+        MAX(drawdown) AS max_drawdown,
+        STDDEV_POP(market_roi) AS volatility
+    FROM drawdowns
+    GROUP BY trader_address
+),
+longest AS (
+    SELECT
+        trader_address,
+        MAX(length) FILTER (WHERE won) AS win_streak,
+        MAX(length) FILTER (WHERE NOT won) AS loss_streak
+    FROM streaks
+    GROUP BY trader_address
+)
+SELECT
+    trader_address,
+    CAST(entered AS UINTEGER),
+    CAST(resolved AS UINTEGER),
+    CAST(wins AS UINTEGER),
+    CASE WHEN resolved > 0 THEN wins / resolved ELSE 0 END,
+    CAST(invested AS DOUBLE),
+    CAST(returned AS DOUBLE),
+    CASE WHEN invested > 0 THEN (returned - invested) / invested ELSE 0 END,
+    CAST(first_block AS UBIGINT),
+    CAST(COALESCE(win_streak, 0) AS UINTEGER),
+    CAST(COALESCE(loss_streak, 0) AS UINTEGER),
+    CAST(COALESCE(max_drawdown, 0) AS DOUBLE),
+    CAST(COALESCE(volatility, 0) AS DOUBLE)
+FROM totals
+LEFT JOIN risk USING (trader_address)
+LEFT JOIN longest USING (trader_address)
+ORDER BY trader_address
+";
+
+pub struct DuckDbHandler {
+    data_dir: PathBuf,
+}
+
+impl DuckDbHandler {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+        }
+    }
+
+    // aggregate trader stats from transactions and resolutions in sql
+    // amounts are converted to usd at the collateral rates the way the polars path reads them
+    pub fn aggregate_trader_stats(&self, rates: CollateralRates) -> Result<DataFrame> {
+        let conn = Connection::open_in_memory()?;
+        self.create_view(&conn, "transactions", "transactions.parquet")?;
+        self.create_view(&conn, "market_resolutions", "market_resolutions.parquet")?;
+        // rows stored before collateral was tracked are all usdc.e
+        let usd_amount = if columns(&conn, "transactions")?.iter().any(|(name, _)| name == "collateral") {
+            format!(
+                "usdc_amount * CASE collateral WHEN '{}' THEN {:?} ELSE {:?} END",
+                Collateral::Usdc.as_str(), rates.usdc, rates.usdc_e,
+            )
+        } else {
+            format!("usdc_amount * {:?}", rates.usdc_e)
+        };
+
+        let sql = TRADER_STATS_SQL.replace("{usd_amount}", &usd_amount);
+        let mut stmt = conn.prepare(&sql)?;
+
+        let mut addresses = Vec::new();
+        let mut entered = Vec::new();
+        let mut resolved = Vec::new();
+        let mut wins = Vec::new();
+        let mut accuracy = Vec::new();
+        let mut invested = Vec::new();
+        let mut returned = Vec::new();
+        let mut roi = Vec::new();
+        let mut first_blocks = Vec::new();
+        let mut win_streaks = Vec::new();
+        let mut loss_streaks = Vec::new();
+        let mut drawdowns = Vec::new();
+        let mut volatilities = Vec::new();
+
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            addresses.push(row.get::<_, String>(0)?);
+            entered.push(row.get::<_, u32>(1)?);
+            resolved.push(row.get::<_, u32>(2)?);
+            wins.push(row.get::<_, u32>(3)?);
+            accuracy.push(row.get::<_, f64>(4)?);
+            invested.push(row.get::<_, f64>(5)?);
+            returned.push(row.get::<_, f64>(6)?);
+            roi.push(row.get::<_, f64>(7)?);
+            first_blocks.push(row.get::<_, Option<u64>>(8)?);
+            win_streaks.push(row.get::<_, u32>(9)?);
+            loss_streaks.push(row.get::<_, u32>(10)?);
+            drawdowns.push(row.get::<_, f64>(11)?);
+            volatilities.push(row.get::<_, f64>(12)?);
+        }
+
+        // same layout as traders.parquet so the standardizer can be reused
+        let df = df!(
+            "trader_address" => addresses,
+            "total_markets_entered" => entered,
+            "total_markets_resolved" => resolved,
+            "total_wins" => wins,
+            "accuracy" => accuracy,
+            "total_invested" => invested,
+            "total_returned" => returned,
+            "roi" => roi,
+            "first_activity_block" => first_blocks,
+            "longest_win_streak" => win_streaks,
+            "longest_loss_streak" => loss_streaks,
+            "max_drawdown" => drawdowns,
+            "return_volatility" => volatilities,
+        )?;
+
+        Ok(df)
+    }
+
+    // a temp view over a table with the dump migrations applied, so old layouts read the way the polars path reads them
+    fn create_view(&self, conn: &Connection, name: &str, filename: &str) -> Result<()> {
+        let scan = self.scan(filename);
+        // (expression, column name, sql type) per column of the file
+        let mut select: Vec<(String, String, String)> = columns(conn, &scan)?
+            .into_iter()
+            .map(|(column, sql_type)| (quote(&column), column, sql_type))
+            .collect();
+
+        for migration in MIGRATIONS.iter().filter(|m| m.table == filename) {
+            match migration.step {
+                MigrationStep::Rename { from, to } => {
+                    if select.iter().any(|(_, column, _)| column == to) {
+                        continue;
+                    }
+                    if let Some((_, column, _)) = select.iter_mut().find(|(_, column, _)| column == from) {
+                        *column = to.to_string();
+                    }
+                }
+                MigrationStep::Cast { column, to } => {
+                    if let Some((expression, _, sql_type)) = select.iter_mut().find(|(_, name, _)| name == column)
+                        && is_numeric(sql_type)
+                    {
+                        *expression = format!("CAST({} AS {})", expression, sql_type_of(to));
+                    }
+                }
+            }
+        }
+
+        let projection: Vec<String> = select
+            .iter()
+            .map(|(expression, column, _)| format!("{} AS {}", expression, quote(column)))
+            .collect();
+        conn.execute_batch(&format!("CREATE TEMP VIEW {} AS SELECT {} FROM {}", name, projection.join(", "), scan))?;
+        Ok(())
+    }
+
+    // read_parquet call for a table in the data dir, single file or hive partitioned dir
+    fn scan(&self, filename: &str) -> String {
+        let path = self.data_dir.join(filename);
+        if path.exists() {
+            return format!("read_parquet('{}')", escape(&path));
+        }
+
+        let dir = self.data_dir.join(filename.trim_end_matches(".parquet"));
+        format!("read_parquet('{}', hive_partitioning = true)", escape(&dir.join("**/*.parquet")))
+    }
+}
+
+// (name, sql type) of each column a table or scan returns
+fn columns(conn: &Connection, table: &str) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(&format!("SELECT column_name, column_type FROM (DESCRIBE SELECT * FROM {})", table))?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<std::result::Result<_, _>>()?)
+}
+
+fn is_numeric(sql_type: &str) -> bool {
+    sql_type.contains("INT") || matches!(sql_type, "FLOAT" | "DOUBLE") || sql_type.starts_with("DECIMAL")
+}
+
+fn sql_type_of(column_type: ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::Str => "VARCHAR",
+        ColumnType::U32 => "UINTEGER",
+        ColumnType::U64 => "UBIGINT",
+        ColumnType::I64 => "BIGINT",
+        ColumnType::F64 => "DOUBLE",
+    }
+}
+
+fn quote(column: &str) -> String {
+    format!("\"{}\"", column.replace('"', "\"\""))
+}
+
+fn escape(path: &Path) -> String {
+    path.to_string_lossy().replace('\'', "''")
+}
@@ -1,30 +1,163 @@
-use crate::adapters::ParquetReader;
-use anyhow::Result;
+use crate::adapters::{Compaction, ParquetReader, ParquetWriter};
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::cancel::Cancellation;
+use crate::data_sources::local_db::query_cache::{QUERY_CACHE_ENTRIES, QueryCache};
+use crate::data_sources::local_db::schema;
+use crate::data_sources::local_db::standardizer::LocalDbStandardizer;
+use crate::error::{DataError, OrMissing, Result};
+use crate::ingest::checkpoint::{CHECKPOINTS_FILE, Checkpoints};
 use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::Mutex;
 
 pub struct LocalDbHandler {
     reader: ParquetReader,
+    writer: ParquetWriter,
+    cache: Mutex<QueryCache>,
+    // checked before every scan, a scan polars already started runs to the end
+    cancellation: Cancellation,
 }
 
 impl LocalDbHandler {
-    pub fn new(reader: ParquetReader) -> Self {
-        Self {reader}
+    pub fn new(reader: ParquetReader, writer: ParquetWriter) -> Self {
+        Self {
+            reader,
+            writer,
+            cache: Mutex::new(QueryCache::new(QUERY_CACHE_ENTRIES)),
+            cancellation: Cancellation::new(),
+        }
+    }
+
+    pub fn set_cancellation(&mut self, cancellation: Cancellation) {
+        self.cancellation = cancellation;
+    }
+
+    // fail with the full list of missing tables instead of stopping at the first one
+    pub fn check_required_tables(&self) -> Result<()> {
+        let missing: Vec<String> = schema::REQUIRED_TABLES
+            .iter()
+            .filter(|filename| !self.reader.exists(filename))
+            .map(|filename| filename.to_string())
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(DataError::MissingTables {
+                dir: self.reader.data_dir().to_path_buf(),
+                missing,
+            }.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn has_table(&self, filename: &str) -> bool {
+        self.reader.exists(filename)
+    }
+
+    // check every table that exists against the expected schema, missing tables are skipped
+    pub fn validate_tables(&self) -> Result<()> {
+        let mut issues = Vec::new();
+
+        for (filename, _) in schema::TABLES {
+            if !self.reader.exists(filename) {
+                continue;
+            }
+
+            let mut frame = schema::migrate(filename, self.reader.read_lazy(filename)?)?;
+            let current = frame.collect_schema()?;
+            issues.extend(schema::validate(filename, &current));
+        }
+
+        if !issues.is_empty() {
+            return Err(DataError::Schema(issues).into());
+        }
+
+        Ok(())
+    }
+
+    // lazily scan a table upgraded to the current layout
+    fn scan(&self, filename: &str) -> Result<LazyFrame> {
+        self.checked(filename, self.reader.read_lazy(filename)?)
+    }
+
+    // scan narrowed to the columns the standardizer reads, so wide files only load what gets used
+    fn scan_columns(&self, filename: &str) -> Result<LazyFrame> {
+        self.projected(filename, self.scan(filename)?)
+    }
+
+    // scan only one market's rows, using the partition dir when there is one
+    fn scan_market(&self, filename: &str, condition_id: &str) -> Result<LazyFrame> {
+        let frame = match self.reader.read_lazy_partition(filename, "market_id", condition_id)? {
+            Some(frame) => self.checked(filename, frame)?,
+            None => self.scan(filename)?.filter(col("market_id").eq(lit(condition_id))),
+        };
+        self.projected(filename, frame)
+    }
+
+    // optional columns an older file doesn't have are left out rather than added as nulls
+    fn projected(&self, filename: &str, mut frame: LazyFrame) -> Result<LazyFrame> {
+        let Some(columns) = LocalDbStandardizer::columns(filename) else {
+            return Ok(frame);
+        };
+
+        let current = frame.collect_schema()?;
+        let present: Vec<Expr> = columns.iter().filter(|name| current.contains(name)).map(|name| col(*name)).collect();
+        Ok(frame.select(present))
+    }
+
+    // run a read once per table and filter, later calls get the collected frame until the table is written
+    fn cached(&self, filename: &str, filter: String, query: impl FnOnce() -> Result<DataFrame>) -> Result<DataFrame> {
+        if let Some(df) = self.cache.lock().ok().and_then(|mut cache| cache.get(filename, &filter)) {
+            return Ok(df);
+        }
+
+        let df = query()?;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(filename, filter, df.clone());
+        }
+        Ok(df)
+    }
+
+    fn invalidate(&self, filename: &str) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.invalidate(filename);
+        }
+    }
+
+    // migrate then fail with a readable message if columns are still off
+    fn checked(&self, filename: &str, frame: LazyFrame) -> Result<LazyFrame> {
+        self.cancellation.check()?;
+        let mut frame = schema::migrate(filename, frame)?;
+
+        let current = frame.collect_schema()?;
+        let issues = schema::validate(filename, &current);
+        if !issues.is_empty() {
+            return Err(DataError::Schema(issues).into());
+        }
+
+        Ok(frame)
     }
     
     // fetch all traders with min resolved markets
     pub fn fetch_traders(&self, mine_resolved_markets: u32) -> Result<DataFrame> {
-        let df = self.reader.read_lazy("traders.parquet")?
-            .filter(col("total_markets_resolved").gt_eq(lit(mine_resolved_markets)))
-            .collect()?;
-
-        Ok(df)
+        self.cached("traders.parquet", format!("min_resolved={}", mine_resolved_markets), || {
+            let df = self.scan_columns("traders.parquet")?
+                .filter(col("total_markets_resolved").gt_eq(lit(mine_resolved_markets)))
+                .collect()?;
+            Ok(df)
+        })
     }
 
     // fetch specific traders by adresses
     pub fn fetch_traders_by_addresses(&self, addresses: &[String]) -> Result<DataFrame> {
+        self.cached("traders.parquet", format!("addresses={}", addresses.join(",")), || self.scan_traders_by_addresses(addresses))
+    }
+
+    fn scan_traders_by_addresses(&self, addresses: &[String]) -> Result<DataFrame> {
         if addresses.is_empty() {
             // Return empty dataframe with correct schema
-            let df = self.reader.read_lazy("traders.parquet")?
+            let df = self.scan_columns("traders.parquet")?
                 .filter(lit(false))
                 .collect()?;
             return Ok(df);
@@ -36,35 +169,375 @@ impl LocalDbHandler {
             filter_expr = filter_expr.or(col("trader_address").eq(lit(addr.as_str())));
         }
         
-        let df = self.reader.read_lazy("traders.parquet")?
+        let df = self.scan_columns("traders.parquet")?
             .filter(filter_expr)
             .collect()?;
         Ok(df)
     }
 
-    // fetch poitions for a conditoin id
-    pub fn fetch_positions(&self, condition_id: &str) -> Result<DataFrame> {
-        let df = self.reader.read_lazy("positions.parquet")?
-            .filter(col("market_id").eq(lit(condition_id)))
+    // fetch per category stats for traders in any of the categories
+    pub fn fetch_category_stats(&self, addresses: &[String], categories: &[String]) -> Result<DataFrame> {
+        let filter = format!("addresses={} categories={}", addresses.join(","), categories.join(","));
+        self.cached("trader_categories.parquet", filter, || self.scan_category_stats(addresses, categories))
+    }
+
+    fn scan_category_stats(&self, addresses: &[String], categories: &[String]) -> Result<DataFrame> {
+        let frame = self.scan_columns("trader_categories.parquet")?;
+        if addresses.is_empty() || categories.is_empty() {
+            return Ok(frame.filter(lit(false)).collect()?);
+        }
+
+        let mut by_address = col("trader_address").eq(lit(addresses[0].as_str()));
+        for addr in &addresses[1..] {
+            by_address = by_address.or(col("trader_address").eq(lit(addr.as_str())));
+        }
+        let mut by_category = col("category").eq(lit(categories[0].as_str()));
+        for category in &categories[1..] {
+            by_category = by_category.or(col("category").eq(lit(category.as_str())));
+        }
+
+        let df = frame
+            .filter(by_address.and(by_category))
             .collect()?;
         Ok(df)
     }
 
-    // fetch recent transactions for a condition ID
+    // fetch poitions for a conditoin id
+    // ingest keeps closed holdings at 0 shares so re-ingesting overwrites them, they aren't positions anymore
+    pub fn fetch_positions(&self, condition_id: &str) -> Result<DataFrame> {
+        self.cached("positions.parquet", format!("market={}", condition_id), || {
+            Ok(self.scan_market("positions.parquet", condition_id)?.filter(col("shares_held").gt(lit(0.0))).collect()?)
+        })
+    }
+
+    pub fn fetch_all_positions(&self) -> Result<DataFrame> {
+        self.cached("positions.parquet", String::new(), || {
+            Ok(self.scan_columns("positions.parquet")?.filter(col("shares_held").gt(lit(0.0))).collect()?)
+        })
+    }
+
+    // fetch a market's transactions from the last days_back days
+    // timed rows are cut at the timestamp, untimed ones at min_block, or without one by counting
+    // back from the market's last trade at the nominal block time
     pub fn fetch_recent_transactions(
         &self,
         condition_id: &str,
-        _days_back: u32,
+        days_back: u32,
+        cutoff_timestamp: i64,
+        min_block: Option<u64>,
     ) -> Result<DataFrame> {
-        // Calculate block threshold (approximate - need block timestamps for precision)
-        // For now, just get all transactions for the condition_id
-        let df = self.reader.read_lazy("transactions.parquet")?
-            .filter(col("market_id").eq(lit(condition_id)))
-            .collect()?;
-        
-        // TODO: Filter by time once we have timestamp data
+        let filter = format!("market={} days={} cutoff={} min_block={:?}", condition_id, days_back, cutoff_timestamp, min_block);
+        self.cached("transactions.parquet", filter, || {
+            self.scan_recent_transactions(condition_id, days_back, cutoff_timestamp, min_block)
+        })
+    }
+
+    fn scan_recent_transactions(
+        &self,
+        condition_id: &str,
+        days_back: u32,
+        cutoff_timestamp: i64,
+        min_block: Option<u64>,
+    ) -> Result<DataFrame> {
+        let mut frame = self.scan_market("transactions.parquet", condition_id)?;
+
+        let min_block = match min_block {
+            Some(block) => block,
+            None => {
+                let latest = frame.clone()
+                    .select([col("block_number").max()])
+                    .collect()?;
+                let latest = latest.column("block_number")?.u64()?.get(0).unwrap_or_default();
+                latest.saturating_sub(days_back as u64 * BLOCKS_PER_DAY)
+            }
+        };
+
+        let by_block = col("block_number").gt_eq(lit(min_block));
+        let recent = if frame.collect_schema()?.contains("timestamp") {
+            when(col("timestamp").is_not_null())
+                .then(col("timestamp").gt_eq(lit(cutoff_timestamp)))
+                .otherwise(by_block)
+        } else {
+            by_block
+        };
+
+        let df = replay_order(frame.filter(recent))?.collect()?;
         Ok(df)
     }
+
+    // fetch one market's full history in replay order
+    pub fn fetch_market_transactions(&self, condition_id: &str) -> Result<DataFrame> {
+        self.cached("transactions.parquet", format!("market={}", condition_id), || {
+            let df = replay_order(self.scan_market("transactions.parquet", condition_id)?)?.collect()?;
+            Ok(df)
+        })
+    }
+
+    // fetch every transaction in replay order so history can be replayed
+    pub fn fetch_all_transactions(&self) -> Result<DataFrame> {
+        self.cached("transactions.parquet", String::new(), || {
+            let df = replay_order(self.scan_columns("transactions.parquet")?)?.collect()?;
+            Ok(df)
+        })
+    }
+
+    // transactions past a stats checkpoint, rows without a block go by timestamp
+    pub fn fetch_transactions_after(&self, block: u64, timestamp: i64) -> Result<DataFrame> {
+        self.cached("transactions.parquet", format!("after={} ts={}", block, timestamp), || {
+            let mut frame = self.scan_columns("transactions.parquet")?;
+            let mut after = col("block_number").gt(lit(block));
+            // older dumps have no timestamps and every row has a block
+            if frame.collect_schema()?.contains("timestamp") {
+                after = after.or(col("block_number").eq(lit(0u64)).and(col("timestamp").gt(lit(timestamp))));
+            }
+            let df = replay_order(frame.filter(after))?.collect()?;
+            Ok(df)
+        })
+    }
+
+    // every transaction of these wallets in replay order
+    pub fn fetch_wallet_transactions(&self, addresses: &[String]) -> Result<DataFrame> {
+        self.cached("transactions.parquet", format!("wallets={}", addresses.join(",")), || {
+            let wallet = addresses.iter()
+                .map(|address| col("trader_address").eq(lit(address.as_str())))
+                .reduce(|a, b| a.or(b))
+                .unwrap_or(lit(false));
+            let df = replay_order(self.scan_columns("transactions.parquet")?.filter(wallet))?.collect()?;
+            Ok(df)
+        })
+    }
+
+    // the per trader and market running totals stats rebuild keeps, written by it
+    pub fn fetch_trader_ledgers(&self) -> Result<DataFrame> {
+        self.cached("trader_ledgers.parquet", String::new(), || Ok(self.scan_columns("trader_ledgers.parquet")?.collect()?))
+    }
+
+    // the trader stats each stats rebuild left behind, written by it
+    pub fn fetch_trader_snapshots(&self) -> Result<DataFrame> {
+        self.cached("trader_snapshots.parquet", String::new(), || Ok(self.scan_columns("trader_snapshots.parquet")?.collect()?))
+    }
+
+    // fetch all resolved markets
+    pub fn fetch_resolutions(&self) -> Result<DataFrame> {
+        self.cached("market_resolutions.parquet", String::new(), || Ok(self.scan_columns("market_resolutions.parquet")?.collect()?))
+    }
+
+    // fetch every market tag row, created by `ingest tags`
+    pub fn fetch_market_tags(&self) -> Result<DataFrame> {
+        self.cached("market_tags.parquet", String::new(), || Ok(self.scan_columns("market_tags.parquet")?.collect()?))
+    }
+
+    pub fn fetch_rewards(&self) -> Result<DataFrame> {
+        self.cached("rewards.parquet", String::new(), || Ok(self.scan_columns("rewards.parquet")?.collect()?))
+    }
+
+    // overwrite a table in the data dir
+    pub fn write_table(&self, filename: &str, df: &mut DataFrame) -> Result<()> {
+        self.invalidate(filename);
+        self.writer.write(filename, df)
+    }
+
+    // rewrite every stored table sorted the way the market scans read it
+    pub fn compact_tables(&self, row_group_size: usize) -> Result<Vec<Compaction>> {
+        schema::TABLES
+            .iter()
+            .filter(|(filename, _)| self.reader.exists(filename))
+            .map(|(filename, _)| {
+                self.invalidate(filename);
+                self.writer.compact(filename, schema::SORT_KEYS, row_group_size)
+            })
+            .collect()
+    }
+
+    // a missing file means no ingest has run against this dir yet
+    pub fn read_checkpoints(&self) -> Result<Checkpoints> {
+        let path = self.reader.path(CHECKPOINTS_FILE);
+        if !path.is_file() {
+            return Ok(Checkpoints::default());
+        }
+
+        let text = fs::read_to_string(&path)?;
+        let checkpoints = serde_json::from_str(&text)
+            .map_err(|e| DataError::Corrupt(format!("{}: {}", path.display(), e)))?;
+        Ok(checkpoints)
+    }
+
+    // written next to the target then renamed so an interrupt never leaves half a file
+    pub fn write_checkpoints(&self, checkpoints: &Checkpoints) -> Result<()> {
+        let path = self.reader.path(CHECKPOINTS_FILE);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let text = serde_json::to_string_pretty(checkpoints)
+            .map_err(|e| DataError::Corrupt(e.to_string()))?;
+        let tmp = path.with_extension(format!("json.tmp-{}", std::process::id()));
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    // partitioned tables get new part files, a single file table is rewritten with the rows added
+    // rows whose primary key is already stored are dropped, so running an ingest twice adds nothing
+    // returns which of df's rows were added
+    pub fn append_table(&self, filename: &str, partition_column: &str, df: &DataFrame) -> Result<Vec<bool>> {
+        let added = match schema::primary_key(filename) {
+            Some(keys) => self.unstored_rows(filename, partition_column, keys, df)?,
+            None => vec![true; df.height()],
+        };
+        let df = df.filter(&added.iter().copied().collect::<BooleanChunked>())?;
+        if df.height() == 0 {
+            return Ok(added);
+        }
+        self.invalidate(filename);
+        if !self.reader.path(filename).is_file() {
+            self.writer.append_partitioned(filename, partition_column, &df)?;
+            return Ok(added);
+        }
+
+        let mut existing = self.scan(filename)?;
+        let stored = existing.collect_schema()?;
+        let schema = union_schema(&stored, df.schema());
+        let mut combined = self.aligned(existing, &schema)?.collect()?;
+        combined.vstack_mut(&self.aligned(df.lazy(), &schema)?.collect()?)?;
+        self.writer.write(filename, &mut combined)?;
+        Ok(added)
+    }
+
+    // replace stored rows that share a primary key with the new ones, keep the rest
+    // a partitioned table only has the partitions the new rows fall in rewritten, a single file table the whole file
+    pub fn upsert_table(&self, filename: &str, partition_column: &str, df: &DataFrame) -> Result<()> {
+        let keys = schema::primary_key(filename).or_missing(&format!("primary key for {}", filename))?;
+        self.invalidate(filename);
+
+        if !self.reader.is_partitioned(filename) {
+            let existing = match self.reader.exists(filename) {
+                true => Some(self.scan(filename)?),
+                false => None,
+            };
+            let mut combined = self.upserted(existing, keys, df)?;
+            return self.writer.write(filename, &mut combined);
+        }
+
+        for part in df.partition_by([partition_column], true)? {
+            let values = part.column(partition_column)?.cast(&DataType::String)?;
+            let Some(value) = values.str()?.get(0).map(str::to_string) else {
+                continue;
+            };
+            let existing = match self.reader.read_lazy_partition(filename, partition_column, &value)? {
+                Some(frame) => Some(self.checked(filename, frame)?),
+                None => None,
+            };
+            let combined = self.upserted(existing, keys, &part)?;
+            self.writer.replace_partition(filename, partition_column, &value, &combined)?;
+        }
+        Ok(())
+    }
+
+    // existing rows minus the ones the new rows replace, then the new rows, first of each key in df wins
+    fn upserted(&self, existing: Option<LazyFrame>, keys: &[&str], df: &DataFrame) -> Result<DataFrame> {
+        let mut new_keys = HashSet::new();
+        let mask: BooleanChunked = row_keys(df, keys)?.into_iter().map(|key| new_keys.insert(key)).collect();
+        let combined = df.filter(&mask)?;
+        let Some(mut existing) = existing else {
+            return Ok(combined);
+        };
+
+        let stored = existing.collect_schema()?;
+        let schema = union_schema(&stored, combined.schema());
+        let existing = self.aligned(existing, &schema)?.collect()?;
+        let combined = self.aligned(combined.lazy(), &schema)?.collect()?;
+        let keep: BooleanChunked = row_keys(&existing, keys)?.iter().map(|key| !new_keys.contains(key)).collect();
+        Ok(existing.filter(&keep)?.vstack(&combined)?)
+    }
+
+    // which new rows aren't stored yet, only the partitions being written are read back
+    // keys are counted rather than matched, a key the frame has n times more than the store adds n rows,
+    // so two fills the source can't tell apart are both kept and pulling them again adds neither
+    fn unstored_rows(&self, filename: &str, partition_column: &str, keys: &[&str], df: &DataFrame) -> Result<Vec<bool>> {
+        let mut stored: HashMap<Vec<Option<String>>, usize> = HashMap::new();
+
+        if self.reader.exists(filename) && df.height() > 0 {
+            let partitions = df.column(partition_column)?.cast(&DataType::String)?;
+            let in_partitions = partitions
+                .str()?
+                .into_iter()
+                .flatten()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .map(|value| col(partition_column).cast(DataType::String).eq(lit(value.to_string())))
+                .reduce(|a, b| a.or(b))
+                .unwrap_or(lit(false));
+
+            let key_schema: Schema = df.schema().iter()
+                .filter(|(name, _)| keys.contains(&name.as_str()))
+                .map(|(name, dtype)| Field::new(name.clone(), dtype.clone()))
+                .collect();
+            let rows = self.aligned(self.scan(filename)?.filter(in_partitions), &key_schema)?.collect()?;
+            for key in row_keys(&rows, keys)? {
+                *stored.entry(key).or_default() += 1;
+            }
+        }
+
+        Ok(row_keys(df, keys)?
+            .into_iter()
+            .map(|key| match stored.get_mut(&key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect())
+    }
+
+    // rows in the columns and types of schema, columns the frame lacks come back null
+    fn aligned(&self, mut existing: LazyFrame, schema: &Schema) -> Result<LazyFrame> {
+        let current = existing.collect_schema()?;
+        let columns: Vec<Expr> = schema
+            .iter()
+            .map(|(name, dtype)| match current.get(name) {
+                Some(_) => col(name.clone()).cast(dtype.clone()),
+                None => lit(NULL).cast(dtype.clone()).alias(name.clone()),
+            })
+            .collect();
+        Ok(existing.select(columns))
+    }
+}
+
+// the stored columns in their stored types, then the ones only the new rows have
+// rewriting a table with this keeps columns the writer doesn't know about, like vendor extras
+fn union_schema(stored: &Schema, new: &Schema) -> Schema {
+    let mut schema = stored.clone();
+    for (name, dtype) in new.iter() {
+        if !schema.contains(name) {
+            schema.with_column(name.clone(), dtype.clone());
+        }
+    }
+    schema
+}
+
+// block, then timestamp and log index for rows in the same block, whichever of them the table has
+// older dumps have no timestamps and api rows no log index, rows missing one go last among their ties
+fn replay_order(mut frame: LazyFrame) -> Result<LazyFrame> {
+    let schema = frame.collect_schema()?;
+    let by: Vec<&str> = ["block_number", "timestamp", "log_index"]
+        .into_iter()
+        .filter(|column| schema.contains(column))
+        .collect();
+    Ok(frame.sort(by, SortMultipleOptions::default().with_nulls_last(true)))
 }
 
+// each row's key columns as strings, nulls kept so a missing log index still compares
+fn row_keys(df: &DataFrame, keys: &[&str]) -> Result<Vec<Vec<Option<String>>>> {
+    let columns = keys
+        .iter()
+        .filter(|key| df.schema().contains(key))
+        .map(|key| df.column(key)?.cast(&DataType::String))
+        .collect::<PolarsResult<Vec<_>>>()?;
+    let columns = columns.iter().map(|column| column.str()).collect::<PolarsResult<Vec<_>>>()?;
 
+    Ok((0..df.height())
+        .map(|i| columns.iter().map(|column| column.get(i).map(str::to_string)).collect())
+        .collect())
+}
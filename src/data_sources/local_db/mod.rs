@@ -1,25 +1,128 @@
 mod handler;
+mod query_cache;
 mod standardizer;
+pub mod schema;
+#[cfg(feature = "duckdb")]
+mod duckdb_handler;
 
-use crate::adapters::ParquetReader;
-use crate::standard_data::models::{Trader, Position, Transaction};
-use crate::standard_data::providers::{TraderStatsProvider, PositionProvider, TransactionProvider};
-use anyhow::Result;
+use crate::adapters::{BlockIndex, Compaction, ParquetReader, ParquetWriter};
+use crate::analysis::collateral::{self, CollateralRates};
+use crate::analysis::rewards::{self, RewardSettings};
+use crate::ingest::{self, Checkpoints};
+use crate::standard_data::models::{Trader, TraderCategoryStats, TraderLedger, TraderSnapshot, Position, Transaction, MarketResolution, MarketTag, RewardPayout};
+use crate::standard_data::providers::{TraderStatsProvider, PositionProvider, TransactionProvider, ResolutionProvider, TagProvider, RewardProvider, DataStore};
+use crate::data_sources::Capabilities;
+use crate::cancel::Cancellation;
+use crate::clock;
+use crate::error::{AppError, Result};
 use async_trait::async_trait;
+use std::collections::HashSet;
 
 use handler::LocalDbHandler;
 use standardizer::LocalDbStandardizer;
+#[cfg(feature = "duckdb")]
+use duckdb_handler::DuckDbHandler;
+
+// engine used for heavy aggregation queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum QueryBackend {
+    #[default]
+    Polars,
+    #[cfg(feature = "duckdb")]
+    Duckdb,
+}
 
 pub struct LocalDbSource {
     handler: LocalDbHandler,
+    backend: QueryBackend,
+    // dates untimed transactions, without it they keep only their block number
+    block_index: Option<BlockIndex>,
+    // whether trader roi counts the stored liquidity rewards
+    rewards: RewardSettings,
+    // usd per unit of each collateral, amounts read come out in usd
+    collateral: CollateralRates,
+    #[cfg(feature = "duckdb")]
+    duckdb: DuckDbHandler,
 }
 
 impl LocalDbSource {
     pub fn new(data_dir: &str) -> Self {
+        Self::with_backend(data_dir, QueryBackend::default())
+    }
+
+    pub fn with_backend(data_dir: &str, backend: QueryBackend) -> Self {
         let reader = ParquetReader::new(data_dir);
+        let writer = ParquetWriter::new(data_dir);
 
         Self {
-            handler: LocalDbHandler::new(reader),
+            handler: LocalDbHandler::new(reader, writer),
+            backend,
+            block_index: None,
+            rewards: RewardSettings::default(),
+            collateral: CollateralRates::default(),
+            #[cfg(feature = "duckdb")]
+            duckdb: DuckDbHandler::new(std::path::Path::new(data_dir)),
+        }
+    }
+
+    pub fn with_block_index(mut self, block_index: BlockIndex) -> Self {
+        self.block_index = Some(block_index);
+        self
+    }
+
+    pub fn with_rewards(mut self, settings: RewardSettings) -> Self {
+        self.rewards = settings;
+        self
+    }
+
+    pub fn with_collateral(mut self, rates: CollateralRates) -> Self {
+        self.collateral = rates;
+        self
+    }
+
+    // stored rewards attached to the traders read, nothing to do before ingest rewards ran
+    fn with_stored_rewards(&self, mut traders: Vec<Trader>) -> Result<Vec<Trader>> {
+        if self.handler.has_table("rewards.parquet") {
+            let stored = LocalDbStandardizer::standardize_rewards(self.handler.fetch_rewards()?)?;
+            rewards::apply_to_traders(&mut traders, &stored, self.rewards);
+        }
+        Ok(traders)
+    }
+
+    // no new parquet scans once the run is cancelled
+    pub fn with_cancellation(mut self, cancellation: Cancellation) -> Self {
+        self.handler.set_cancellation(cancellation);
+        self
+    }
+
+    // interpolated timestamps for rows the dump didn't date
+    async fn fill_timestamps(&self, transactions: &mut [Transaction]) -> Result<()> {
+        match &self.block_index {
+            Some(index) => index.fill_timestamps(transactions).await,
+            None => Ok(()),
+        }
+    }
+
+    // validate local parquet files at startup so bad dumps fail with a clear message
+    pub fn validate_schema(&self) -> Result<()> {
+        self.handler.check_required_tables()?;
+        self.handler.validate_tables()
+    }
+
+    // same checks minus the required tables, for commands that can run without some of them
+    pub fn validate_present_tables(&self) -> Result<()> {
+        self.handler.validate_tables()
+    }
+
+    // market metadata always comes from the api, everything else needs its parquet table
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            market_metadata: true,
+            traders: self.handler.has_table("traders.parquet"),
+            positions: self.handler.has_table("positions.parquet"),
+            transactions: self.handler.has_table("transactions.parquet"),
+            resolutions: self.handler.has_table("market_resolutions.parquet"),
+            tags: self.handler.has_table("market_tags.parquet"),
         }
     }
 }
@@ -28,12 +131,45 @@ impl LocalDbSource {
 impl TraderStatsProvider for LocalDbSource {
     async fn get_traders(&self, min_resolved_markets: u32) -> Result<Vec<Trader>> {
         let df = self.handler.fetch_traders(min_resolved_markets)?;
-        LocalDbStandardizer::standardize_traders(df)
+        self.with_stored_rewards(LocalDbStandardizer::standardize_traders(df)?)
     }
 
     async fn get_traders_by_addresses(&self, addresses: &[String]) -> Result<Vec<Trader>> {
         let df = self.handler.fetch_traders_by_addresses(addresses)?;
-        LocalDbStandardizer::standardize_traders(df)
+        self.with_stored_rewards(LocalDbStandardizer::standardize_traders(df)?)
+    }
+
+    async fn compute_traders(&self) -> Result<Vec<Trader>> {
+        match self.backend {
+            QueryBackend::Polars => {
+                let mut transactions = LocalDbStandardizer::standardize_transactions(self.handler.fetch_all_transactions()?)?;
+                collateral::transactions_to_usd(&mut transactions, self.collateral);
+                let resolutions = LocalDbStandardizer::standardize_resolutions(self.handler.fetch_resolutions()?)?;
+                Ok(ingest::compute_trader_stats(&transactions, &resolutions))
+            }
+            #[cfg(feature = "duckdb")]
+            QueryBackend::Duckdb => {
+                let df = self.duckdb.aggregate_trader_stats(self.collateral)?;
+                LocalDbStandardizer::standardize_traders(df)
+            }
+        }
+    }
+
+    async fn get_category_stats(&self, addresses: &[String], categories: &[String]) -> Result<Vec<TraderCategoryStats>> {
+        // written by `ingest tags`, older data dirs don't have it
+        if !self.handler.has_table("trader_categories.parquet") {
+            return Ok(Vec::new());
+        }
+        let df = self.handler.fetch_category_stats(addresses, categories)?;
+        LocalDbStandardizer::standardize_category_stats(df)
+    }
+
+    async fn get_trader_snapshots(&self) -> Result<Vec<TraderSnapshot>> {
+        if !self.handler.has_table("trader_snapshots.parquet") {
+            return Ok(Vec::new());
+        }
+        let df = self.handler.fetch_trader_snapshots()?;
+        LocalDbStandardizer::standardize_trader_snapshots(df)
     }
 }
 
@@ -41,14 +177,183 @@ impl TraderStatsProvider for LocalDbSource {
 impl PositionProvider for LocalDbSource {
     async fn get_positions(&self, condition_id: &str) -> Result<Vec<Position>> {
         let df = self.handler.fetch_positions(condition_id)?;
-        LocalDbStandardizer::standardize_positions(df)
+        let mut positions = LocalDbStandardizer::standardize_positions(df)?;
+        collateral::positions_to_usd(&mut positions, self.collateral);
+        Ok(positions)
+    }
+
+    async fn get_all_positions(&self) -> Result<Vec<Position>> {
+        let df = self.handler.fetch_all_positions()?;
+        let mut positions = LocalDbStandardizer::standardize_positions(df)?;
+        collateral::positions_to_usd(&mut positions, self.collateral);
+        Ok(positions)
     }
 }
 
 #[async_trait]
 impl TransactionProvider for LocalDbSource {
     async fn get_recent_transactions( &self, condition_id: &str, days_back: u32) -> Result<Vec<Transaction>> {
-        let df = self.handler.fetch_recent_transactions(condition_id, days_back)?;
-        LocalDbStandardizer::standardize_transactions(df)
+        let cutoff = clock::now().timestamp() - days_back as i64 * 24 * 60 * 60;
+        let min_block = match &self.block_index {
+            Some(index) => index.block_at(cutoff).await?,
+            None => None,
+        };
+
+        let df = self.handler.fetch_recent_transactions(condition_id, days_back, cutoff, min_block)?;
+        let mut transactions = LocalDbStandardizer::standardize_transactions(df)?;
+        collateral::transactions_to_usd(&mut transactions, self.collateral);
+        self.fill_timestamps(&mut transactions).await?;
+        Ok(transactions)
+    }
+
+    async fn get_market_transactions(&self, condition_id: &str) -> Result<Vec<Transaction>> {
+        let df = self.handler.fetch_market_transactions(condition_id)?;
+        let mut transactions = LocalDbStandardizer::standardize_transactions(df)?;
+        collateral::transactions_to_usd(&mut transactions, self.collateral);
+        self.fill_timestamps(&mut transactions).await?;
+        Ok(transactions)
+    }
+
+    async fn get_all_transactions(&self) -> Result<Vec<Transaction>> {
+        let df = self.handler.fetch_all_transactions()?;
+        let mut transactions = LocalDbStandardizer::standardize_transactions(df)?;
+        collateral::transactions_to_usd(&mut transactions, self.collateral);
+        self.fill_timestamps(&mut transactions).await?;
+        Ok(transactions)
+    }
+
+    async fn get_transactions_after(&self, block: u64, timestamp: i64) -> Result<Vec<Transaction>> {
+        let df = self.handler.fetch_transactions_after(block, timestamp)?;
+        let mut transactions = LocalDbStandardizer::standardize_transactions(df)?;
+        collateral::transactions_to_usd(&mut transactions, self.collateral);
+        self.fill_timestamps(&mut transactions).await?;
+        Ok(transactions)
+    }
+
+    // timestamps are filled in before the cutoff applies, dump rows only carry a block
+    async fn get_wallet_transactions(&self, addresses: &[String], timestamp: i64) -> Result<Vec<Transaction>> {
+        let df = self.handler.fetch_wallet_transactions(addresses)?;
+        let mut transactions = LocalDbStandardizer::standardize_transactions(df)?;
+        collateral::transactions_to_usd(&mut transactions, self.collateral);
+        self.fill_timestamps(&mut transactions).await?;
+        transactions.retain(|tx| tx.timestamp.is_some_and(|ts| ts >= timestamp));
+        Ok(transactions)
+    }
+}
+
+#[async_trait]
+impl ResolutionProvider for LocalDbSource {
+    async fn get_resolutions(&self) -> Result<Vec<MarketResolution>> {
+        let df = self.handler.fetch_resolutions()?;
+        LocalDbStandardizer::standardize_resolutions(df)
+    }
+}
+
+#[async_trait]
+impl TagProvider for LocalDbSource {
+    async fn get_market_tags(&self) -> Result<Vec<MarketTag>> {
+        let df = self.handler.fetch_market_tags()?;
+        LocalDbStandardizer::standardize_market_tags(df)
+    }
+}
+
+#[async_trait]
+impl RewardProvider for LocalDbSource {
+    async fn get_rewards(&self, addresses: &[String], after: Option<i64>) -> Result<Vec<RewardPayout>> {
+        if !self.handler.has_table("rewards.parquet") {
+            return Ok(Vec::new());
+        }
+        let wanted: HashSet<String> = addresses.iter().map(|address| address.to_lowercase()).collect();
+        let mut rewards = LocalDbStandardizer::standardize_rewards(self.handler.fetch_rewards()?)?;
+        rewards.retain(|reward| wanted.contains(&reward.trader_address.to_lowercase()) && after.is_none_or(|after| reward.timestamp >= after));
+        rewards.sort_by_key(|reward| reward.timestamp);
+        Ok(rewards)
+    }
+}
+
+#[async_trait]
+impl DataStore for LocalDbSource {
+    async fn save_resolutions(&self, resolutions: &[MarketResolution]) -> Result<()> {
+        let mut df = LocalDbStandardizer::resolutions_to_frame(resolutions)?;
+        self.handler.write_table("market_resolutions.parquet", &mut df)
+    }
+
+    async fn save_traders(&self, traders: &[Trader]) -> Result<()> {
+        let mut df = LocalDbStandardizer::traders_to_frame(traders)?;
+        self.handler.write_table("traders.parquet", &mut df)
+    }
+
+    async fn save_trader_snapshot(&self, snapshot: &TraderSnapshot) -> Result<()> {
+        let df = LocalDbStandardizer::trader_snapshot_to_frame(snapshot)?;
+        self.handler.upsert_table("trader_snapshots.parquet", "snapshot_block", &df)
+    }
+
+    // gamma's close times come without a block, resolutions are placed through the index like api rows
+    async fn blocks_at(&self, timestamps: &[i64]) -> Result<Vec<Option<u64>>> {
+        let Some(index) = &self.block_index else {
+            return Err(AppError::Unsupported("market close times have no block number, pass --polygon-rpc so resolutions can be placed on the chain".to_string()));
+        };
+        index.blocks_at(timestamps).await
+    }
+
+    // data api rows come without a block, they're placed from their timestamp before they're stored
+    // so replays and block cutoffs see them where they happened instead of before everything
+    async fn append_transactions(&self, transactions: &[Transaction]) -> Result<Vec<Transaction>> {
+        let mut transactions = transactions.to_vec();
+        if transactions.iter().any(|tx| tx.block_number == 0) {
+            let Some(index) = &self.block_index else {
+                return Err(AppError::Unsupported("data api trades have no block number, pass --polygon-rpc so they can be placed on the chain".to_string()));
+            };
+            index.fill_blocks(&mut transactions).await?;
+        }
+        let df = LocalDbStandardizer::transactions_to_frame(&transactions)?;
+        let added = self.handler.append_table("transactions.parquet", "market_id", &df)?;
+        Ok(transactions.into_iter().zip(added).filter_map(|(tx, added)| added.then_some(tx)).collect())
+    }
+
+    async fn append_rewards(&self, rewards: &[RewardPayout]) -> Result<()> {
+        let df = LocalDbStandardizer::rewards_to_frame(rewards)?;
+        self.handler.append_table("rewards.parquet", "trader_address", &df)?;
+        Ok(())
+    }
+
+    async fn upsert_positions(&self, positions: &[Position]) -> Result<()> {
+        let df = LocalDbStandardizer::positions_to_frame(positions)?;
+        self.handler.upsert_table("positions.parquet", "market_id", &df)
+    }
+
+    async fn save_market_tags(&self, tags: &[MarketTag]) -> Result<()> {
+        let mut df = LocalDbStandardizer::market_tags_to_frame(tags)?;
+        self.handler.write_table("market_tags.parquet", &mut df)
+    }
+
+    async fn save_category_stats(&self, stats: &[TraderCategoryStats]) -> Result<()> {
+        let mut df = LocalDbStandardizer::category_stats_to_frame(stats)?;
+        self.handler.write_table("trader_categories.parquet", &mut df)
+    }
+
+    async fn load_trader_ledgers(&self) -> Result<Vec<TraderLedger>> {
+        if !self.handler.has_table("trader_ledgers.parquet") {
+            return Ok(Vec::new());
+        }
+        let df = self.handler.fetch_trader_ledgers()?;
+        LocalDbStandardizer::standardize_trader_ledgers(df)
+    }
+
+    async fn save_trader_ledgers(&self, ledgers: &[TraderLedger]) -> Result<()> {
+        let mut df = LocalDbStandardizer::trader_ledgers_to_frame(ledgers)?;
+        self.handler.write_table("trader_ledgers.parquet", &mut df)
+    }
+
+    async fn load_checkpoints(&self) -> Result<Checkpoints> {
+        self.handler.read_checkpoints()
+    }
+
+    async fn save_checkpoints(&self, checkpoints: &Checkpoints) -> Result<()> {
+        self.handler.write_checkpoints(checkpoints)
+    }
+
+    async fn compact(&self, row_group_size: usize) -> Result<Vec<Compaction>> {
+        self.handler.compact_tables(row_group_size)
     }
 }
@@ -0,0 +1,52 @@
+use polars::prelude::DataFrame;
+use std::collections::HashMap;
+
+// results kept per run, enough for a multi market analyze without holding every market's history
+pub const QUERY_CACHE_ENTRIES: usize = 64;
+
+// collected query results keyed by (table, filter), least recently used goes first when full
+// frames share their column buffers so a hit is only a clone of the handles
+pub struct QueryCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<(String, String), (u64, DataFrame)>,
+}
+
+impl QueryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, filename: &str, filter: &str) -> Option<DataFrame> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (used, df) = self.entries.get_mut(&(filename.to_string(), filter.to_string()))?;
+        *used = tick;
+        Some(df.clone())
+    }
+
+    pub fn insert(&mut self, filename: &str, filter: String, df: DataFrame) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.tick += 1;
+        let key = (filename.to_string(), filter);
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self.entries.iter().min_by_key(|(_, (used, _))| *used).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (self.tick, df));
+    }
+
+    // drop everything read from a table once it has been written
+    pub fn invalidate(&mut self, filename: &str) {
+        self.entries.retain(|(table, _), _| table != filename);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::error::Result;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+// column types the standardizers read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Str,
+    U32,
+    U64,
+    I64,
+    F64,
+}
+
+impl ColumnType {
+    pub fn dtype(&self) -> DataType {
+        match self {
+            ColumnType::Str => DataType::String,
+            ColumnType::U32 => DataType::UInt32,
+            ColumnType::U64 => DataType::UInt64,
+            ColumnType::I64 => DataType::Int64,
+            ColumnType::F64 => DataType::Float64,
+        }
+    }
+}
+
+// tables the commands can't work without, market_resolutions, market_tags and trader_categories are created by `ingest`
+pub const REQUIRED_TABLES: &[&str] = &["traders.parquet", "positions.parquet", "transactions.parquet"];
+
+pub struct ExpectedColumn {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+    pub required: bool,
+}
+
+const fn required(name: &'static str, column_type: ColumnType) -> ExpectedColumn {
+    ExpectedColumn { name, column_type, required: true }
+}
+
+const fn optional(name: &'static str, column_type: ColumnType) -> ExpectedColumn {
+    ExpectedColumn { name, column_type, required: false }
+}
+
+// every table in the local db and the columns we rely on
+pub const TABLES: &[(&str, &[ExpectedColumn])] = &[
+    ("traders.parquet", &[
+        required("trader_address", ColumnType::Str),
+        required("total_markets_entered", ColumnType::U32),
+        required("total_markets_resolved", ColumnType::U32),
+        required("total_wins", ColumnType::U32),
+        required("accuracy", ColumnType::F64),
+        required("total_invested", ColumnType::F64),
+        required("total_returned", ColumnType::F64),
+        required("roi", ColumnType::F64),
+        optional("first_activity_block", ColumnType::U64),
+        optional("longest_win_streak", ColumnType::U32),
+        optional("longest_loss_streak", ColumnType::U32),
+        optional("max_drawdown", ColumnType::F64),
+        optional("return_volatility", ColumnType::F64),
+    ]),
+    ("positions.parquet", &[
+        required("trader_address", ColumnType::Str),
+        required("token_id", ColumnType::Str),
+        required("market_id", ColumnType::Str),
+        required("side", ColumnType::Str),
+        required("shares_held", ColumnType::F64),
+        required("avg_entry_price", ColumnType::F64),
+        optional("first_entry_block", ColumnType::U64),
+        optional("collateral", ColumnType::Str),
+    ]),
+    ("transactions.parquet", &[
+        required("block_number", ColumnType::U64),
+        required("transaction_hash", ColumnType::Str),
+        optional("log_index", ColumnType::U32),
+        required("trader_address", ColumnType::Str),
+        required("token_id", ColumnType::Str),
+        required("side", ColumnType::Str),
+        required("action", ColumnType::Str),
+        required("shares", ColumnType::F64),
+        required("usdc_amount", ColumnType::F64),
+        required("market_id", ColumnType::Str),
+        optional("timestamp", ColumnType::I64),
+        optional("collateral", ColumnType::Str),
+    ]),
+    ("market_resolutions.parquet", &[
+        required("condition_id", ColumnType::Str),
+        required("outcome", ColumnType::Str),
+        required("resolution_block", ColumnType::U64),
+        required("yes_token_id", ColumnType::Str),
+        required("no_token_id", ColumnType::Str),
+    ]),
+    ("trader_categories.parquet", &[
+        required("trader_address", ColumnType::Str),
+        required("category", ColumnType::Str),
+        required("total_markets_resolved", ColumnType::U32),
+        required("total_wins", ColumnType::U32),
+        required("accuracy", ColumnType::F64),
+        required("total_invested", ColumnType::F64),
+        required("total_returned", ColumnType::F64),
+        required("roi", ColumnType::F64),
+    ]),
+    ("market_tags.parquet", &[
+        required("condition_id", ColumnType::Str),
+        required("tag", ColumnType::Str),
+    ]),
+    ("trader_ledgers.parquet", &[
+        required("trader_address", ColumnType::Str),
+        required("market_id", ColumnType::Str),
+        required("invested", ColumnType::F64),
+        required("proceeds", ColumnType::F64),
+        required("yes_shares", ColumnType::F64),
+        required("no_shares", ColumnType::F64),
+        required("first_block", ColumnType::U64),
+    ]),
+    // traders.parquet as each stats rebuild wrote it, snapshot_block tells them apart
+    ("trader_snapshots.parquet", &[
+        required("snapshot_block", ColumnType::U64),
+        required("trader_address", ColumnType::Str),
+        required("total_markets_entered", ColumnType::U32),
+        required("total_markets_resolved", ColumnType::U32),
+        required("total_wins", ColumnType::U32),
+        required("accuracy", ColumnType::F64),
+        required("total_invested", ColumnType::F64),
+        required("total_returned", ColumnType::F64),
+        required("roi", ColumnType::F64),
+        optional("first_activity_block", ColumnType::U64),
+        optional("longest_win_streak", ColumnType::U32),
+        optional("longest_loss_streak", ColumnType::U32),
+        optional("max_drawdown", ColumnType::F64),
+        optional("return_volatility", ColumnType::F64),
+    ]),
+    ("rewards.parquet", &[
+        required("trader_address", ColumnType::Str),
+        optional("market_id", ColumnType::Str),
+        required("usdc_amount", ColumnType::F64),
+        required("timestamp", ColumnType::I64),
+        required("transaction_hash", ColumnType::Str),
+    ]),
+];
+
+// columns that identify a row, writes never store two rows with the same key
+// one fill gives the maker and the taker a row each so the log index alone isn't enough
+pub const PRIMARY_KEYS: &[(&str, &[&str])] = &[
+    // data api fills have no log index or trade id, two fills of one wallet in the same transaction share a key
+    // and are counted apart when appended
+    ("transactions.parquet", &["transaction_hash", "log_index", "trader_address", "token_id", "action"]),
+    ("positions.parquet", &["trader_address", "token_id"]),
+    ("trader_snapshots.parquet", &["snapshot_block", "trader_address"]),
+    // one payout transaction can pay a wallet for several markets
+    ("rewards.parquet", &["transaction_hash", "trader_address", "market_id"]),
+];
+
+pub fn primary_key(filename: &str) -> Option<&'static [&'static str]> {
+    PRIMARY_KEYS.iter().find(|(table, _)| *table == filename).map(|(_, keys)| *keys)
+}
+
+// compacted tables are sorted by whichever of these they have, so row group stats can skip most of a market scan
+pub const SORT_KEYS: &[&str] = &["market_id", "block_number"];
+
+pub enum MigrationStep {
+    Rename { from: &'static str, to: &'static str },
+    Cast { column: &'static str, to: ColumnType },
+}
+
+pub struct Migration {
+    pub table: &'static str,
+    pub step: MigrationStep,
+}
+
+// changes between dump versions, applied in order whenever an old layout is detected
+pub const MIGRATIONS: &[Migration] = &[
+    // v1 dumps keyed positions and transactions by condition_id and used tx_hash
+    Migration { table: "positions.parquet", step: MigrationStep::Rename { from: "condition_id", to: "market_id" } },
+    Migration { table: "transactions.parquet", step: MigrationStep::Rename { from: "condition_id", to: "market_id" } },
+    Migration { table: "transactions.parquet", step: MigrationStep::Rename { from: "tx_hash", to: "transaction_hash" } },
+    Migration { table: "positions.parquet", step: MigrationStep::Rename { from: "avg_price", to: "avg_entry_price" } },
+    // v2 dumps came out of pandas with signed 64 bit ints everywhere
+    Migration { table: "traders.parquet", step: MigrationStep::Cast { column: "total_markets_entered", to: ColumnType::U32 } },
+    Migration { table: "traders.parquet", step: MigrationStep::Cast { column: "total_markets_resolved", to: ColumnType::U32 } },
+    Migration { table: "traders.parquet", step: MigrationStep::Cast { column: "total_wins", to: ColumnType::U32 } },
+    Migration { table: "traders.parquet", step: MigrationStep::Cast { column: "first_activity_block", to: ColumnType::U64 } },
+    Migration { table: "positions.parquet", step: MigrationStep::Cast { column: "first_entry_block", to: ColumnType::U64 } },
+    Migration { table: "transactions.parquet", step: MigrationStep::Cast { column: "block_number", to: ColumnType::U64 } },
+    Migration { table: "transactions.parquet", step: MigrationStep::Cast { column: "timestamp", to: ColumnType::I64 } },
+    Migration { table: "market_resolutions.parquet", step: MigrationStep::Cast { column: "resolution_block", to: ColumnType::U64 } },
+];
+
+// bring an older table layout up to date
+pub fn migrate(filename: &str, mut frame: LazyFrame) -> Result<LazyFrame> {
+    let schema = frame.collect_schema()?;
+    let mut columns: HashMap<String, DataType> = schema
+        .iter()
+        .map(|(name, dtype)| (name.to_string(), dtype.clone()))
+        .collect();
+
+    for migration in MIGRATIONS.iter().filter(|m| m.table == filename) {
+        match migration.step {
+            MigrationStep::Rename { from, to } => {
+                if columns.contains_key(to) {
+                    continue;
+                }
+                if let Some(dtype) = columns.remove(from) {
+                    frame = frame.rename([from], [to], true);
+                    columns.insert(to.to_string(), dtype);
+                }
+            }
+            MigrationStep::Cast { column, to } => {
+                if let Some(dtype) = columns.get_mut(column)
+                    && *dtype != to.dtype()
+                    && dtype.is_primitive_numeric()
+                {
+                    frame = frame.with_column(col(column).cast(to.dtype()));
+                    *dtype = to.dtype();
+                }
+            }
+        }
+    }
+
+    Ok(frame)
+}
+
+// list what's wrong with a table, empty if it matches what the standardizers expect
+pub fn validate(filename: &str, schema: &Schema) -> Vec<String> {
+    let Some((_, expected)) = TABLES.iter().find(|(table, _)| *table == filename) else {
+        return Vec::new();
+    };
+
+    let mut issues = Vec::new();
+    for column in expected.iter() {
+        match schema.get(column.name) {
+            None if column.required => {
+                issues.push(format!("{} missing {}", filename, column.name));
+            }
+            Some(dtype) if *dtype != column.column_type.dtype() => {
+                issues.push(format!(
+                    "{} column {} has type {}, expected {}",
+                    filename,
+                    column.name,
+                    dtype,
+                    column.column_type.dtype()
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    issues
+}
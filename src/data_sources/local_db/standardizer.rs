@@ -1,10 +1,57 @@
-use crate::standard_data::models::{Trader, Position, Transaction};
-use anyhow::{Context, Result};
+use crate::ingest::wilson_lower_bound;
+use crate::standard_data::models::{Collateral, Trader, TraderCategoryStats, TraderLedger, TraderSnapshot, Position, Transaction, MarketResolution, MarketTag, RewardPayout};
+use crate::error::{DataError, OrMissing, Result};
 use polars::prelude::*;
+use std::collections::BTreeMap;
 
 pub struct LocalDbStandardizer;
 
 impl LocalDbStandardizer {
+    // columns each standardize_* reads, the handler selects only these when scanning
+    pub const TRADER_COLUMNS: &[&str] = &[
+        "trader_address", "total_markets_entered", "total_markets_resolved", "total_wins",
+        "accuracy", "total_invested", "total_returned", "roi", "first_activity_block",
+        "longest_win_streak", "longest_loss_streak", "max_drawdown", "return_volatility",
+    ];
+    pub const SNAPSHOT_COLUMNS: &[&str] = &[
+        "snapshot_block", "trader_address", "total_markets_entered", "total_markets_resolved", "total_wins",
+        "accuracy", "total_invested", "total_returned", "roi", "first_activity_block",
+        "longest_win_streak", "longest_loss_streak", "max_drawdown", "return_volatility",
+    ];
+    pub const CATEGORY_STATS_COLUMNS: &[&str] = &[
+        "trader_address", "category", "total_markets_resolved", "total_wins",
+        "accuracy", "total_invested", "total_returned", "roi",
+    ];
+    pub const LEDGER_COLUMNS: &[&str] = &[
+        "trader_address", "market_id", "invested", "proceeds", "yes_shares", "no_shares", "first_block",
+    ];
+    pub const POSITION_COLUMNS: &[&str] = &[
+        "trader_address", "token_id", "market_id", "side", "shares_held", "avg_entry_price", "first_entry_block", "collateral",
+    ];
+    pub const TRANSACTION_COLUMNS: &[&str] = &[
+        "block_number", "transaction_hash", "log_index", "trader_address", "token_id",
+        "side", "action", "shares", "usdc_amount", "market_id", "timestamp", "collateral",
+    ];
+    pub const RESOLUTION_COLUMNS: &[&str] = &["condition_id", "outcome", "resolution_block", "yes_token_id", "no_token_id"];
+    pub const MARKET_TAG_COLUMNS: &[&str] = &["condition_id", "tag"];
+    pub const REWARD_COLUMNS: &[&str] = &["trader_address", "market_id", "usdc_amount", "timestamp", "transaction_hash"];
+
+    // needed columns of a table, None for one no standardizer reads
+    pub fn columns(filename: &str) -> Option<&'static [&'static str]> {
+        match filename {
+            "traders.parquet" => Some(Self::TRADER_COLUMNS),
+            "trader_snapshots.parquet" => Some(Self::SNAPSHOT_COLUMNS),
+            "trader_categories.parquet" => Some(Self::CATEGORY_STATS_COLUMNS),
+            "trader_ledgers.parquet" => Some(Self::LEDGER_COLUMNS),
+            "positions.parquet" => Some(Self::POSITION_COLUMNS),
+            "transactions.parquet" => Some(Self::TRANSACTION_COLUMNS),
+            "market_resolutions.parquet" => Some(Self::RESOLUTION_COLUMNS),
+            "market_tags.parquet" => Some(Self::MARKET_TAG_COLUMNS),
+            "rewards.parquet" => Some(Self::REWARD_COLUMNS),
+            _ => None,
+        }
+    }
+
     // convert data frame to Vec(traders)
     pub fn standardize_traders(df: DataFrame) -> Result<Vec<Trader>> {
         if df.height() == 0 {
@@ -22,33 +69,57 @@ impl LocalDbStandardizer {
         let total_returned = df.column("total_returned")?.f64()?;
         let roi = df.column("roi")?.f64()?;
 
+        // first_activity_block is optional
+        let first_blocks = df.column("first_activity_block").ok()
+            .and_then(|col| col.u64().ok());
+        // so are the risk stats, dumps written before them don't have the columns
+        let win_streaks = df.column("longest_win_streak").ok()
+            .and_then(|col| col.u32().ok());
+        let loss_streaks = df.column("longest_loss_streak").ok()
+            .and_then(|col| col.u32().ok());
+        let drawdowns = df.column("max_drawdown").ok()
+            .and_then(|col| col.f64().ok());
+        let volatilities = df.column("return_volatility").ok()
+            .and_then(|col| col.f64().ok());
+
         for i in 0..df.height() {
+            let wins = total_wins.get(i).or_missing("total_wins")?;
+            let resolved = total_resolved.get(i).or_missing("total_markets_resolved")?;
             traders.push(Trader {
                 trader_address: addresses
                     .get(i)
-                    .context("Missing trader_address")?
+                    .or_missing("trader_address")?
                     .to_string(),
                 total_markets_entered: total_entered
                     .get(i)
-                    .context("Missing total_markets_entered")?,
-                total_markets_resolved: total_resolved
-                    .get(i)
-                    .context("Missing total_markets_resolved")?,
-                total_wins: total_wins
-                    .get(i)
-                    .context("Missing total_wins")?,
+                    .or_missing("total_markets_entered")?,
+                total_markets_resolved: resolved,
+                total_wins: wins,
                 accuracy: accuracy
                     .get(i)
-                    .context("Missing accuracy")?,
+                    .or_missing("accuracy")?,
+                // not stored, always recomputed so older dumps get it too
+                adjusted_accuracy: wilson_lower_bound(wins, resolved),
                 total_invested: total_invested
                     .get(i)
-                    .context("Missing total_invested")?,
+                    .or_missing("total_invested")?,
                 total_returned: total_returned
                     .get(i)
-                    .context("Missing total_returned")?,
+                    .or_missing("total_returned")?,
                 roi: roi
                     .get(i)
-                    .context("Missing roi")?,
+                    .or_missing("roi")?,
+                first_activity_block: first_blocks
+                    .and_then(|col| col.get(i)),
+                longest_win_streak: win_streaks
+                    .and_then(|col| col.get(i)),
+                longest_loss_streak: loss_streaks
+                    .and_then(|col| col.get(i)),
+                max_drawdown: drawdowns
+                    .and_then(|col| col.get(i)),
+                return_volatility: volatilities
+                    .and_then(|col| col.get(i)),
+                rewards: None,
             });
         }
 
@@ -56,6 +127,99 @@ impl LocalDbStandardizer {
     }
     
 
+    // convert data frame to vec(trader category stats)
+    pub fn standardize_category_stats(df: DataFrame) -> Result<Vec<TraderCategoryStats>> {
+        if df.height() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut stats = Vec::new();
+
+        let addresses = df.column("trader_address")?.str()?;
+        let categories = df.column("category")?.str()?;
+        let total_resolved = df.column("total_markets_resolved")?.u32()?;
+        let total_wins = df.column("total_wins")?.u32()?;
+        let accuracy = df.column("accuracy")?.f64()?;
+        let total_invested = df.column("total_invested")?.f64()?;
+        let total_returned = df.column("total_returned")?.f64()?;
+        let roi = df.column("roi")?.f64()?;
+
+        for i in 0..df.height() {
+            let wins = total_wins.get(i).or_missing("total_wins")?;
+            let resolved = total_resolved.get(i).or_missing("total_markets_resolved")?;
+            stats.push(TraderCategoryStats {
+                trader_address: addresses
+                    .get(i)
+                    .or_missing("trader_address")?
+                    .to_string(),
+                category: categories
+                    .get(i)
+                    .or_missing("category")?
+                    .to_string(),
+                total_markets_resolved: resolved,
+                total_wins: wins,
+                accuracy: accuracy
+                    .get(i)
+                    .or_missing("accuracy")?,
+                adjusted_accuracy: wilson_lower_bound(wins, resolved),
+                total_invested: total_invested
+                    .get(i)
+                    .or_missing("total_invested")?,
+                total_returned: total_returned
+                    .get(i)
+                    .or_missing("total_returned")?,
+                roi: roi
+                    .get(i)
+                    .or_missing("roi")?,
+            });
+        }
+
+        Ok(stats)
+    }
+
+    // convert data frame to vec(trader snapshot), oldest first
+    pub fn standardize_trader_snapshots(df: DataFrame) -> Result<Vec<TraderSnapshot>> {
+        let blocks: Vec<Option<u64>> = df.column("snapshot_block")?.u64()?.into_iter().collect();
+        let traders = Self::standardize_traders(df)?;
+
+        let mut snapshots: BTreeMap<u64, Vec<Trader>> = BTreeMap::new();
+        for (block, trader) in blocks.into_iter().zip(traders) {
+            snapshots.entry(block.or_missing("snapshot_block")?).or_default().push(trader);
+        }
+        Ok(snapshots.into_iter().map(|(block, traders)| TraderSnapshot { block, traders }).collect())
+    }
+
+    // convert data frame to vec(trader ledger)
+    pub fn standardize_trader_ledgers(df: DataFrame) -> Result<Vec<TraderLedger>> {
+        if df.height() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut ledgers = Vec::new();
+
+        let addresses = df.column("trader_address")?.str()?;
+        let markets = df.column("market_id")?.str()?;
+        let invested = df.column("invested")?.f64()?;
+        let proceeds = df.column("proceeds")?.f64()?;
+        let yes_shares = df.column("yes_shares")?.f64()?;
+        let no_shares = df.column("no_shares")?.f64()?;
+        let first_blocks = df.column("first_block")?.u64()?;
+
+        for i in 0..df.height() {
+            ledgers.push(TraderLedger {
+                trader_address: addresses.get(i).or_missing("trader_address")?.to_string(),
+                market_id: markets.get(i).or_missing("market_id")?.to_string(),
+                invested: invested.get(i).or_missing("invested")?,
+                proceeds: proceeds.get(i).or_missing("proceeds")?,
+                yes_shares: yes_shares.get(i).or_missing("yes_shares")?,
+                no_shares: no_shares.get(i).or_missing("no_shares")?,
+                first_block: first_blocks.get(i),
+            });
+        }
+
+        Ok(ledgers)
+    }
+
     // convert data frame to vec(positons)    
     pub fn standardize_positions(df: DataFrame) -> Result<Vec<Position>> {
         if df.height() == 0 {
@@ -71,38 +235,42 @@ impl LocalDbStandardizer {
         let shares = df.column("shares_held")?.f64()?;
         let avg_prices = df.column("avg_entry_price")?.f64()?;
         
-        // first_entry_block is optional
+        // first_entry_block and collateral are optional
         let first_blocks = df.column("first_entry_block").ok()
             .and_then(|col| col.u64().ok());
+        let collaterals = df.column("collateral").ok()
+            .and_then(|col| col.str().ok());
 
         for i in 0..df.height() {
             let first_entry_block = first_blocks
                 .and_then(|col| col.get(i));
+            let collateral = collateral_at(collaterals, i)?;
 
             positions.push(Position {
                 trader_address: addresses
                     .get(i)
-                    .context("Missing trader_address")?
+                    .or_missing("trader_address")?
                     .to_string(),
                 token_id: token_ids
                     .get(i)
-                    .context("Missing token_id")?
+                    .or_missing("token_id")?
                     .to_string(),
                 market_id: market_ids
                     .get(i)
-                    .context("Missing market_id")?
+                    .or_missing("market_id")?
                     .to_string(),
                 side: sides
                     .get(i)
-                    .context("Missing side")?
+                    .or_missing("side")?
                     .to_string(),
                 shares_held: shares
                     .get(i)
-                    .context("Missing shares_held")?,
+                    .or_missing("shares_held")?,
                 avg_entry_price: avg_prices
                     .get(i)
-                    .context("Missing avg_entry_price")?,
+                    .or_missing("avg_entry_price")?,
                 first_entry_block,
+                collateral,
             });
         }
 
@@ -127,45 +295,306 @@ impl LocalDbStandardizer {
         let usdc_amounts = df.column("usdc_amount")?.f64()?;
         let market_ids = df.column("market_id")?.str()?;
 
+        // timestamp, log_index and collateral are optional
+        let timestamps = df.column("timestamp").ok()
+            .and_then(|col| col.i64().ok());
+        let log_indexes = df.column("log_index").ok()
+            .and_then(|col| col.u32().ok());
+        let collaterals = df.column("collateral").ok()
+            .and_then(|col| col.str().ok());
+
         for i in 0..df.height() {
+            let timestamp = timestamps
+                .and_then(|col| col.get(i));
+            let log_index = log_indexes
+                .and_then(|col| col.get(i));
+            let collateral = collateral_at(collaterals, i)?;
+
             transactions.push(Transaction {
                 block_number: block_numbers
                     .get(i)
-                    .context("Missing block_number")?,
+                    .or_missing("block_number")?,
                 transaction_hash: tx_hashes
                     .get(i)
-                    .context("Missing transaction_hash")?
+                    .or_missing("transaction_hash")?
                     .to_string(),
+                log_index,
                 trader_address: trader_addresses
                     .get(i)
-                    .context("Missing trader_address")?
+                    .or_missing("trader_address")?
                     .to_string(),
                 token_id: token_ids
                     .get(i)
-                    .context("Missing token_id")?
+                    .or_missing("token_id")?
                     .to_string(),
                 side: sides
                     .get(i)
-                    .context("Missing side")?
+                    .or_missing("side")?
                     .to_string(),
                 action: actions
                     .get(i)
-                    .context("Missing action")?
+                    .or_missing("action")?
                     .to_string(),
                 shares: shares
                     .get(i)
-                    .context("Missing shares")?,
+                    .or_missing("shares")?,
                 usdc_amount: usdc_amounts
                     .get(i)
-                    .context("Missing usdc_amount")?,
+                    .or_missing("usdc_amount")?,
                 market_id: market_ids
                     .get(i)
-                    .context("Missing market_id")?
+                    .or_missing("market_id")?
                     .to_string(),
+                timestamp,
+                collateral,
             });
         }
 
         Ok(transactions)
     }
+
+    // convert data frame to vec(market resolution)
+    pub fn standardize_resolutions(df: DataFrame) -> Result<Vec<MarketResolution>> {
+        if df.height() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut resolutions = Vec::new();
+
+        let condition_ids = df.column("condition_id")?.str()?;
+        let outcomes = df.column("outcome")?.str()?;
+        let resolution_blocks = df.column("resolution_block")?.u64()?;
+        let yes_token_ids = df.column("yes_token_id")?.str()?;
+        let no_token_ids = df.column("no_token_id")?.str()?;
+
+        for i in 0..df.height() {
+            resolutions.push(MarketResolution {
+                condition_id: condition_ids
+                    .get(i)
+                    .or_missing("condition_id")?
+                    .to_string(),
+                outcome: outcomes
+                    .get(i)
+                    .or_missing("outcome")?
+                    .to_string(),
+                resolution_block: resolution_blocks
+                    .get(i)
+                    .or_missing("resolution_block")?,
+                yes_token_id: yes_token_ids
+                    .get(i)
+                    .or_missing("yes_token_id")?
+                    .to_string(),
+                no_token_id: no_token_ids
+                    .get(i)
+                    .or_missing("no_token_id")?
+                    .to_string(),
+            });
+        }
+
+        Ok(resolutions)
+    }
+
+    // convert data frame to vec(market tag)
+    pub fn standardize_market_tags(df: DataFrame) -> Result<Vec<MarketTag>> {
+        if df.height() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut tags = Vec::new();
+
+        let condition_ids = df.column("condition_id")?.str()?;
+        let names = df.column("tag")?.str()?;
+
+        for i in 0..df.height() {
+            tags.push(MarketTag {
+                condition_id: condition_ids
+                    .get(i)
+                    .or_missing("condition_id")?
+                    .to_string(),
+                tag: names
+                    .get(i)
+                    .or_missing("tag")?
+                    .to_string(),
+            });
+        }
+
+        Ok(tags)
+    }
+
+    // convert data frame to vec(reward payout)
+    pub fn standardize_rewards(df: DataFrame) -> Result<Vec<RewardPayout>> {
+        if df.height() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut rewards = Vec::new();
+
+        let addresses = df.column("trader_address")?.str()?;
+        let market_ids = df.column("market_id")?.str()?;
+        let amounts = df.column("usdc_amount")?.f64()?;
+        let timestamps = df.column("timestamp")?.i64()?;
+        let hashes = df.column("transaction_hash")?.str()?;
+
+        for i in 0..df.height() {
+            rewards.push(RewardPayout {
+                trader_address: addresses
+                    .get(i)
+                    .or_missing("trader_address")?
+                    .to_string(),
+                market_id: market_ids
+                    .get(i)
+                    .map(str::to_string),
+                usdc_amount: amounts
+                    .get(i)
+                    .or_missing("usdc_amount")?,
+                timestamp: timestamps
+                    .get(i)
+                    .or_missing("timestamp")?,
+                transaction_hash: hashes
+                    .get(i)
+                    .or_missing("transaction_hash")?
+                    .to_string(),
+            });
+        }
+
+        Ok(rewards)
+    }
+
+    // convert vec(traders) back to a data frame for writing
+    pub fn traders_to_frame(traders: &[Trader]) -> Result<DataFrame> {
+        let df = df!(
+            "trader_address" => traders.iter().map(|t| t.trader_address.as_str()).collect::<Vec<_>>(),
+            "total_markets_entered" => traders.iter().map(|t| t.total_markets_entered).collect::<Vec<_>>(),
+            "total_markets_resolved" => traders.iter().map(|t| t.total_markets_resolved).collect::<Vec<_>>(),
+            "total_wins" => traders.iter().map(|t| t.total_wins).collect::<Vec<_>>(),
+            "accuracy" => traders.iter().map(|t| t.accuracy).collect::<Vec<_>>(),
+            "total_invested" => traders.iter().map(|t| t.total_invested).collect::<Vec<_>>(),
+            "total_returned" => traders.iter().map(|t| t.total_returned).collect::<Vec<_>>(),
+            "roi" => traders.iter().map(|t| t.roi).collect::<Vec<_>>(),
+            "first_activity_block" => traders.iter().map(|t| t.first_activity_block).collect::<Vec<_>>(),
+            "longest_win_streak" => traders.iter().map(|t| t.longest_win_streak).collect::<Vec<_>>(),
+            "longest_loss_streak" => traders.iter().map(|t| t.longest_loss_streak).collect::<Vec<_>>(),
+            "max_drawdown" => traders.iter().map(|t| t.max_drawdown).collect::<Vec<_>>(),
+            "return_volatility" => traders.iter().map(|t| t.return_volatility).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
+
+    // convert vec(trader category stats) back to a data frame for writing
+    pub fn category_stats_to_frame(stats: &[TraderCategoryStats]) -> Result<DataFrame> {
+        let df = df!(
+            "trader_address" => stats.iter().map(|s| s.trader_address.as_str()).collect::<Vec<_>>(),
+            "category" => stats.iter().map(|s| s.category.as_str()).collect::<Vec<_>>(),
+            "total_markets_resolved" => stats.iter().map(|s| s.total_markets_resolved).collect::<Vec<_>>(),
+            "total_wins" => stats.iter().map(|s| s.total_wins).collect::<Vec<_>>(),
+            "accuracy" => stats.iter().map(|s| s.accuracy).collect::<Vec<_>>(),
+            "total_invested" => stats.iter().map(|s| s.total_invested).collect::<Vec<_>>(),
+            "total_returned" => stats.iter().map(|s| s.total_returned).collect::<Vec<_>>(),
+            "roi" => stats.iter().map(|s| s.roi).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
+
+    // the traders.parquet columns with the snapshot's block on every row
+    pub fn trader_snapshot_to_frame(snapshot: &TraderSnapshot) -> Result<DataFrame> {
+        let mut df = Self::traders_to_frame(&snapshot.traders)?;
+        df.insert_column(0, Column::new("snapshot_block".into(), vec![snapshot.block; snapshot.traders.len()]))?;
+        Ok(df)
+    }
+
+    // convert vec(trader ledger) back to a data frame for writing
+    pub fn trader_ledgers_to_frame(ledgers: &[TraderLedger]) -> Result<DataFrame> {
+        let df = df!(
+            "trader_address" => ledgers.iter().map(|l| l.trader_address.as_str()).collect::<Vec<_>>(),
+            "market_id" => ledgers.iter().map(|l| l.market_id.as_str()).collect::<Vec<_>>(),
+            "invested" => ledgers.iter().map(|l| l.invested).collect::<Vec<_>>(),
+            "proceeds" => ledgers.iter().map(|l| l.proceeds).collect::<Vec<_>>(),
+            "yes_shares" => ledgers.iter().map(|l| l.yes_shares).collect::<Vec<_>>(),
+            "no_shares" => ledgers.iter().map(|l| l.no_shares).collect::<Vec<_>>(),
+            "first_block" => ledgers.iter().map(|l| l.first_block).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
+
+    // convert vec(position) back to a data frame for writing
+    pub fn positions_to_frame(positions: &[Position]) -> Result<DataFrame> {
+        let df = df!(
+            "trader_address" => positions.iter().map(|p| p.trader_address.as_str()).collect::<Vec<_>>(),
+            "token_id" => positions.iter().map(|p| p.token_id.as_str()).collect::<Vec<_>>(),
+            "market_id" => positions.iter().map(|p| p.market_id.as_str()).collect::<Vec<_>>(),
+            "side" => positions.iter().map(|p| p.side.as_str()).collect::<Vec<_>>(),
+            "shares_held" => positions.iter().map(|p| p.shares_held).collect::<Vec<_>>(),
+            "avg_entry_price" => positions.iter().map(|p| p.avg_entry_price).collect::<Vec<_>>(),
+            "first_entry_block" => positions.iter().map(|p| p.first_entry_block).collect::<Vec<_>>(),
+            "collateral" => positions.iter().map(|p| p.collateral.as_str()).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
+
+    // convert vec(transaction) back to a data frame for writing
+    pub fn transactions_to_frame(transactions: &[Transaction]) -> Result<DataFrame> {
+        let df = df!(
+            "block_number" => transactions.iter().map(|t| t.block_number).collect::<Vec<_>>(),
+            "transaction_hash" => transactions.iter().map(|t| t.transaction_hash.as_str()).collect::<Vec<_>>(),
+            "log_index" => transactions.iter().map(|t| t.log_index).collect::<Vec<_>>(),
+            "trader_address" => transactions.iter().map(|t| t.trader_address.as_str()).collect::<Vec<_>>(),
+            "token_id" => transactions.iter().map(|t| t.token_id.as_str()).collect::<Vec<_>>(),
+            "side" => transactions.iter().map(|t| t.side.as_str()).collect::<Vec<_>>(),
+            "action" => transactions.iter().map(|t| t.action.as_str()).collect::<Vec<_>>(),
+            "shares" => transactions.iter().map(|t| t.shares).collect::<Vec<_>>(),
+            "usdc_amount" => transactions.iter().map(|t| t.usdc_amount).collect::<Vec<_>>(),
+            "market_id" => transactions.iter().map(|t| t.market_id.as_str()).collect::<Vec<_>>(),
+            "timestamp" => transactions.iter().map(|t| t.timestamp).collect::<Vec<_>>(),
+            "collateral" => transactions.iter().map(|t| t.collateral.as_str()).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
+
+    // convert vec(market resolution) back to a data frame for writing
+    pub fn resolutions_to_frame(resolutions: &[MarketResolution]) -> Result<DataFrame> {
+        let df = df!(
+            "condition_id" => resolutions.iter().map(|r| r.condition_id.as_str()).collect::<Vec<_>>(),
+            "outcome" => resolutions.iter().map(|r| r.outcome.as_str()).collect::<Vec<_>>(),
+            "resolution_block" => resolutions.iter().map(|r| r.resolution_block).collect::<Vec<_>>(),
+            "yes_token_id" => resolutions.iter().map(|r| r.yes_token_id.as_str()).collect::<Vec<_>>(),
+            "no_token_id" => resolutions.iter().map(|r| r.no_token_id.as_str()).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
+
+    // convert vec(market tag) back to a data frame for writing
+    pub fn market_tags_to_frame(tags: &[MarketTag]) -> Result<DataFrame> {
+        let df = df!(
+            "condition_id" => tags.iter().map(|t| t.condition_id.as_str()).collect::<Vec<_>>(),
+            "tag" => tags.iter().map(|t| t.tag.as_str()).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
+
+    pub fn rewards_to_frame(rewards: &[RewardPayout]) -> Result<DataFrame> {
+        let df = df!(
+            "trader_address" => rewards.iter().map(|r| r.trader_address.as_str()).collect::<Vec<_>>(),
+            "market_id" => rewards.iter().map(|r| r.market_id.as_deref()).collect::<Vec<_>>(),
+            "usdc_amount" => rewards.iter().map(|r| r.usdc_amount).collect::<Vec<_>>(),
+            "timestamp" => rewards.iter().map(|r| r.timestamp).collect::<Vec<_>>(),
+            "transaction_hash" => rewards.iter().map(|r| r.transaction_hash.as_str()).collect::<Vec<_>>(),
+        )?;
+
+        Ok(df)
+    }
 }
 
+// rows from before collateral was tracked settled in usdc.e, anything else unrecognized is a bad file
+fn collateral_at(column: Option<&StringChunked>, i: usize) -> Result<Collateral> {
+    match column.and_then(|col| col.get(i)) {
+        Some(value) => Collateral::parse(value).ok_or_else(|| DataError::Corrupt(format!("unknown collateral {}", value)).into()),
+        None => Ok(Collateral::UsdcE),
+    }
+}
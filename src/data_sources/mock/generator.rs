@@ -0,0 +1,404 @@
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::analysis::rewards::{self, RewardSettings};
+use crate::clock;
+use crate::ingest;
+use crate::standard_data::models::{Collateral, Comment, Market, MarketGroup, MarketResolution, MarketTag, Position, RewardPayout, Trader, TraderCategoryStats, Transaction};
+use chrono::{DateTime, Days, TimeDelta};
+use std::collections::HashMap;
+
+// used unless --seed gives another
+pub const SEED: u64 = 0x5eed_cafe_f00d_beef;
+const TRADER_COUNT: usize = 40;
+const HISTORICAL_MARKETS: usize = 12;
+// every this many traders also quote the live markets and earn liquidity rewards
+const REWARDED_EVERY: usize = 4;
+const START_BLOCK: u64 = 50_000_000;
+// 2023-11-14 22:13:20 utc, blocks tick every 2 seconds after it
+const START_TIMESTAMP: i64 = 1_700_000_000;
+// comments on the live event, spread over the last couple of weeks
+const MOCK_COMMENTS: usize = 60;
+const COMMENT_SPREAD_HOURS: f64 = 14.0 * 24.0;
+
+// historical markets cycle through these so category views have something to split
+const HISTORICAL_TAGS: &[&str] = &["politics", "sports", "crypto"];
+
+// question and days from today until it resolves
+const LIVE_QUESTIONS: &[(&str, u64)] = &[
+    ("Will the mock candidate win the primary?", 1),
+    ("Will the mock candidate win the general election?", 120),
+    ("Will turnout exceed 60%?", 30),
+];
+
+// comment bodies the mock crowd picks from, a mix of tones and sides
+const COMMENT_BODIES: &[&str] = &[
+    "Easy yes, the polls are not close",
+    "Bought yes at these prices, great value",
+    "No way this happens, the market is crazy",
+    "Buying no, this is overvalued",
+    "Anyone know when the results come in?",
+    "Rigged either way lol",
+    "Long yes, confident on this one",
+    "Not happening, the candidate is weak",
+    "Interesting spread between the markets",
+    "Thanks for the analysis above, solid points",
+];
+
+// small xorshift so mock data is identical every run without pulling in rand
+pub struct MockRng(u64);
+
+impl MockRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    pub fn hex(&mut self, digits: usize) -> String {
+        let mut out = String::from("0x");
+        while out.len() < digits + 2 {
+            out.push_str(&format!("{:016x}", self.next_u64()));
+        }
+        out.truncate(digits + 2);
+        out
+    }
+}
+
+// everything the mock source serves
+pub struct MockData {
+    pub group: MarketGroup,
+    pub markets: Vec<Market>,
+    pub traders: Vec<Trader>,
+    pub category_stats: Vec<TraderCategoryStats>,
+    pub positions: Vec<Position>,
+    pub transactions: Vec<Transaction>,
+    pub resolutions: Vec<MarketResolution>,
+    pub rewards: Vec<RewardPayout>,
+    pub comments: Vec<Comment>,
+}
+
+pub fn generate(seed: u64) -> MockData {
+    let mut rng = MockRng::new(seed);
+
+    // each trader has a hidden skill: the chance they pick the winning side
+    let traders: Vec<(String, f64)> = (0..TRADER_COUNT)
+        .map(|_| (rng.hex(40), rng.range(0.35, 0.85)))
+        .collect();
+
+    let mut markets = Vec::new();
+    let mut transactions = Vec::new();
+    let mut resolutions = Vec::new();
+
+    // resolved history so backtests and trader stats have something to chew on
+    for i in 0..HISTORICAL_MARKETS {
+        let start = START_BLOCK + i as u64 * 3 * BLOCKS_PER_DAY;
+        let yes_wins = rng.chance(0.5);
+        let mut market = mock_market(&mut rng, &format!("Mock historical market #{}?", i + 1), true);
+        market.tags = vec![HISTORICAL_TAGS[i % HISTORICAL_TAGS.len()].to_string()];
+        market.outcome_prices = if yes_wins {
+            vec!["1".to_string(), "0".to_string()]
+        } else {
+            vec!["0".to_string(), "1".to_string()]
+        };
+
+        let market_txs = trade_market(&mut rng, &traders, &market, start, Some(yes_wins));
+        let last_block = market_txs.iter().map(|tx| tx.block_number).max().unwrap_or(start);
+        market.end_date = DateTime::from_timestamp(block_timestamp(last_block + 100), 0);
+        market.closed_time = market.end_date;
+        market.volume = market_txs.iter().map(|tx| tx.usdc_amount).sum();
+        transactions.extend(market_txs);
+
+        resolutions.push(MarketResolution {
+            condition_id: market.condition_id.clone(),
+            outcome: if yes_wins { "YES" } else { "NO" }.to_string(),
+            resolution_block: last_block + 100,
+            yes_token_id: market.yes_token_id.clone(),
+            no_token_id: market.no_token_id.clone(),
+        });
+        markets.push(market);
+    }
+
+    // the live event every slug maps to
+    let live_start = START_BLOCK + HISTORICAL_MARKETS as u64 * 3 * BLOCKS_PER_DAY;
+    // live end dates move with the calendar day so they stay in the future
+    let today = clock::now().date_naive().and_hms_opt(0, 0, 0).map(|d| d.and_utc());
+    let mut live_markets = Vec::new();
+    for (i, (question, days_left)) in LIVE_QUESTIONS.iter().enumerate() {
+        let mut market = mock_market(&mut rng, question, false);
+        market.tags = vec!["politics".to_string(), "elections".to_string()];
+        market.end_date = today.and_then(|d| d.checked_add_days(Days::new(*days_left)));
+        // listed a few hours apart so the newest ones fall inside a one day window
+        market.created_at = Some(clock::now() - TimeDelta::hours(8 + 20 * i as i64));
+        let market_txs = trade_market(&mut rng, &traders, &market, live_start, None);
+        market.volume = market_txs.iter().map(|tx| tx.usdc_amount).sum();
+        market.volume_24h = market.volume * 0.1;
+        market.volume_1w = market.volume * 0.4;
+        market.volume_1m = market.volume * 0.8;
+        market.volume_1y = market.volume;
+        market.price_change_24h = Some((rng.range(-0.12, 0.12) * 1000.0).round() / 1000.0);
+        transactions.extend(market_txs);
+        live_markets.push(market);
+    }
+    markets.extend(live_markets.iter().cloned());
+
+    let positions = positions_from(&transactions, &live_markets);
+    let mut traders = ingest::compute_trader_stats(&transactions, &resolutions);
+    // drawn last so the rest of the data stays what it was for a seed
+    let rewards = liquidity_rewards(&mut rng, &traders, &live_markets, live_start);
+    rewards::apply_to_traders(&mut traders, &rewards, RewardSettings::default());
+    let comments = mock_comments(&mut rng, &traders);
+    let category_stats = ingest::compute_category_stats(&transactions, &resolutions, &market_tags(&markets));
+
+    let group = MarketGroup {
+        slug: "mock-event".to_string(),
+        title: "Mock Event".to_string(),
+        active: true,
+        closed: false,
+        volume: live_markets.iter().map(|m| m.volume).sum(),
+        liquidity: live_markets.iter().map(|m| m.liquidity).sum(),
+        markets: live_markets,
+    };
+
+    MockData {
+        group,
+        markets,
+        traders,
+        category_stats,
+        positions,
+        transactions,
+        resolutions,
+        rewards,
+        comments,
+    }
+}
+
+// comments on the live event from random traders, newest first like gamma returns them
+fn mock_comments(rng: &mut MockRng, traders: &[Trader]) -> Vec<Comment> {
+    let now = clock::now().timestamp();
+    let mut comments: Vec<Comment> = (0..MOCK_COMMENTS)
+        .map(|_| {
+            let author = &traders[(rng.next_u64() % traders.len() as u64) as usize];
+            let body = COMMENT_BODIES[(rng.next_u64() % COMMENT_BODIES.len() as u64) as usize];
+            Comment {
+                id: rng.next_u64().to_string(),
+                body: body.to_string(),
+                author_address: author.trader_address.clone(),
+                created_at: now - (rng.range(0.0, COMMENT_SPREAD_HOURS) * 3600.0) as i64,
+                likes: rng.range(0.0, 12.0) as u32,
+            }
+        })
+        .collect();
+    comments.sort_by_key(|comment| std::cmp::Reverse(comment.created_at));
+    comments
+}
+
+// a daily payout per rewarded trader in about half the live markets
+fn liquidity_rewards(rng: &mut MockRng, traders: &[Trader], live_markets: &[Market], live_start: u64) -> Vec<RewardPayout> {
+    let mut payouts = Vec::new();
+    for trader in traders.iter().step_by(REWARDED_EVERY) {
+        for market in live_markets {
+            if !rng.chance(0.5) {
+                continue;
+            }
+            payouts.push(RewardPayout {
+                trader_address: trader.trader_address.clone(),
+                market_id: Some(market.condition_id.clone()),
+                usdc_amount: (rng.range(1.0, 40.0) * 100.0).round() / 100.0,
+                timestamp: block_timestamp(live_start + BLOCKS_PER_DAY),
+                transaction_hash: rng.hex(64),
+            });
+        }
+    }
+    payouts
+}
+
+// one row per market and tag, the same rows `ingest tags` would write
+pub fn market_tags(markets: &[Market]) -> Vec<MarketTag> {
+    markets
+        .iter()
+        .flat_map(|m| m.tags.iter().map(|tag| MarketTag {
+            condition_id: m.condition_id.clone(),
+            tag: tag.clone(),
+        }))
+        .collect()
+}
+
+fn mock_market(rng: &mut MockRng, question: &str, closed: bool) -> Market {
+    let yes_price = (rng.range(0.05, 0.95) * 100.0).round() / 100.0;
+    let no_price = ((1.0 - yes_price) * 100.0).round() / 100.0;
+    let slug = question
+        .trim_end_matches('?')
+        .to_lowercase()
+        .replace(['#', '%'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-");
+
+    Market {
+        question: question.to_string(),
+        condition_id: rng.hex(64),
+        slug,
+        outcomes: vec!["Yes".to_string(), "No".to_string()],
+        outcome_prices: vec![yes_price.to_string(), no_price.to_string()],
+        yes_token_id: rng.next_u64().to_string(),
+        no_token_id: rng.next_u64().to_string(),
+        active: !closed,
+        closed,
+        archived: false,
+        volume: 0.0,
+        volume_24h: 0.0,
+        volume_1w: 0.0,
+        volume_1m: 0.0,
+        volume_1y: 0.0,
+        liquidity: rng.range(5_000.0, 50_000.0),
+        competitive: rng.range(0.5, 1.0),
+        last_trade_price: yes_price,
+        bid_price: (yes_price - 0.01).max(0.0),
+        ask_price: (yes_price + 0.01).min(1.0),
+        price_change_24h: None,
+        created_at: None,
+        end_date: None,
+        closed_time: None,
+        resolution_source: Some("Mock resolution committee".to_string()),
+        tags: Vec::new(),
+    }
+}
+
+// have roughly half the traders take a side, skilled ones lean toward the winner when known
+fn trade_market(
+    rng: &mut MockRng,
+    traders: &[(String, f64)],
+    market: &Market,
+    start_block: u64,
+    yes_wins: Option<bool>,
+) -> Vec<Transaction> {
+    let yes_price: f64 = market.outcome_prices[0].parse().unwrap_or(0.5);
+    let yes_price = if yes_price == 0.0 || yes_price == 1.0 { rng.range(0.2, 0.8) } else { yes_price };
+
+    let mut txs = Vec::new();
+    for (address, skill) in traders {
+        if !rng.chance(0.5) {
+            continue;
+        }
+
+        let picks_yes = match yes_wins {
+            Some(yes_wins) => rng.chance(*skill) == yes_wins,
+            None => rng.chance(0.5 + (skill - 0.6) / 2.0),
+        };
+
+        let (side, token_id, price) = if picks_yes {
+            ("YES", &market.yes_token_id, yes_price)
+        } else {
+            ("NO", &market.no_token_id, 1.0 - yes_price)
+        };
+        let price = (price + rng.range(-0.05, 0.05)).clamp(0.01, 0.99);
+        let shares = (rng.range(50.0, 5_000.0)).round();
+        let block = start_block + (rng.next_f64() * 2.0 * BLOCKS_PER_DAY as f64) as u64;
+
+        txs.push(mock_tx(rng, address, market, token_id, side, "BUY", block, shares, price));
+
+        // some take partial profit later
+        if rng.chance(0.2) {
+            let sold = (shares * rng.range(0.2, 0.6)).round();
+            let exit = (price + rng.range(-0.1, 0.1)).clamp(0.01, 0.99);
+            txs.push(mock_tx(rng, address, market, token_id, side, "SELL", block + 1_000, sold, exit));
+        }
+    }
+
+    txs.sort_by_key(|tx| tx.block_number);
+    txs
+}
+
+#[allow(clippy::too_many_arguments)]
+fn mock_tx(
+    rng: &mut MockRng,
+    address: &str,
+    market: &Market,
+    token_id: &str,
+    side: &str,
+    action: &str,
+    block_number: u64,
+    shares: f64,
+    price: f64,
+) -> Transaction {
+    Transaction {
+        block_number,
+        transaction_hash: rng.hex(64),
+        log_index: None,
+        trader_address: address.to_string(),
+        token_id: token_id.to_string(),
+        side: side.to_string(),
+        action: action.to_string(),
+        shares,
+        usdc_amount: shares * price,
+        market_id: market.condition_id.clone(),
+        timestamp: Some(block_timestamp(block_number)),
+        collateral: Collateral::UsdcE,
+    }
+}
+
+fn block_timestamp(block_number: u64) -> i64 {
+    START_TIMESTAMP + (block_number - START_BLOCK) as i64 * 2
+}
+
+// first block at or after a timestamp, nothing before the first block
+pub fn block_at(timestamp: i64) -> Option<u64> {
+    let seconds = u64::try_from(timestamp - START_TIMESTAMP).ok()?;
+    Some(START_BLOCK + seconds.div_ceil(2))
+}
+
+// net open positions on the live markets
+fn positions_from(transactions: &[Transaction], live_markets: &[Market]) -> Vec<Position> {
+    let mut open: HashMap<(&str, &str), Position> = HashMap::new();
+
+    for market in live_markets {
+        for tx in transactions.iter().filter(|tx| tx.market_id == market.condition_id) {
+            let position = open
+                .entry((tx.trader_address.as_str(), tx.token_id.as_str()))
+                .or_insert_with(|| Position {
+                    trader_address: tx.trader_address.clone(),
+                    token_id: tx.token_id.clone(),
+                    market_id: tx.market_id.clone(),
+                    side: tx.side.clone(),
+                    shares_held: 0.0,
+                    avg_entry_price: 0.0,
+                    first_entry_block: Some(tx.block_number),
+                    collateral: tx.collateral,
+                });
+
+            if tx.action == "BUY" {
+                let cost = position.shares_held * position.avg_entry_price + tx.usdc_amount;
+                position.shares_held += tx.shares;
+                position.avg_entry_price = cost / position.shares_held;
+            } else {
+                position.shares_held -= tx.shares;
+            }
+        }
+    }
+
+    let mut positions: Vec<Position> = open
+        .into_values()
+        .filter(|p| p.shares_held > 0.0)
+        .collect();
+    positions.sort_by(|a, b| (&a.market_id, &a.trader_address).cmp(&(&b.market_id, &b.trader_address)));
+    positions
+}
@@ -0,0 +1,305 @@
+mod generator;
+
+use crate::analysis::backtest::BLOCKS_PER_DAY;
+use crate::standard_data::models::{BookLevel, Comment, Market, MarketGroup, MarketResolution, MarketTag, OrderBook, Position, RewardPayout, Trader, TraderCategoryStats, TraderLedger, TraderSnapshot, Transaction};
+use crate::standard_data::providers::{
+    CommentProvider, DataStore, MarketFilter, MarketMetadataProvider, MarketOrder, OrderBookProvider, PositionProvider, ResolutionProvider, RewardProvider, TagProvider, TraderStatsProvider, TransactionProvider,
+};
+use crate::data_sources::Capabilities;
+use crate::adapters::Compaction;
+use crate::ingest::Checkpoints;
+use crate::error::{OrMissing, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use generator::MockData;
+
+const MOCK_BOOK_LEVELS: usize = 5;
+
+// deterministic offline source, every provider is backed by the same generated data
+pub struct MockSource {
+    data: MockData,
+}
+
+impl MockSource {
+    pub fn new() -> Self {
+        Self::with_seed(generator::SEED)
+    }
+
+    // different traders and trades, same shape
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            data: generator::generate(seed),
+        }
+    }
+
+    // generated data covers every table
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::full()
+    }
+}
+
+impl Default for MockSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MarketMetadataProvider for MockSource {
+    async fn get_market_group(&self, slug: &str) -> Result<MarketGroup> {
+        // any slug resolves to the mock event
+        let mut group = self.data.group.clone();
+        group.slug = slug.to_string();
+        group.title = format!("Mock Event ({})", slug);
+        Ok(group)
+    }
+
+    async fn get_markets_by_condition_ids(&self, condition_ids: &[String]) -> Result<Vec<Market>> {
+        Ok(self.data.markets
+            .iter()
+            .filter(|m| condition_ids.contains(&m.condition_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_active_markets(&self, filter: &MarketFilter) -> Result<Vec<Market>> {
+        let mut markets: Vec<Market> = self.data.markets
+            .iter()
+            .filter(|m| m.active && !m.closed)
+            .filter(|m| filter.include_archived || !m.archived)
+            .filter(|m| filter.min_liquidity.is_none_or(|min| m.liquidity >= min))
+            .filter(|m| filter.ends_before.is_none_or(|before| m.end_date.is_some_and(|end| end <= before)))
+            .cloned()
+            .collect();
+
+        match filter.order {
+            MarketOrder::Volume24h => markets.sort_by(|a, b| b.volume_24h.total_cmp(&a.volume_24h)),
+            MarketOrder::Newest => markets.sort_by_key(|m| std::cmp::Reverse(m.created_at)),
+            MarketOrder::EndingSoonest => markets.sort_by_key(|m| m.end_date.unwrap_or(DateTime::<Utc>::MAX_UTC)),
+        }
+        markets.truncate(filter.limit);
+        Ok(markets)
+    }
+}
+
+// a few levels either side of the quoted bid and ask, deeper levels hold more size
+#[async_trait]
+impl OrderBookProvider for MockSource {
+    async fn get_order_book(&self, token_id: &str) -> Result<OrderBook> {
+        let market = self.data.markets
+            .iter()
+            .find(|m| m.yes_token_id == token_id || m.no_token_id == token_id)
+            .or_missing(&format!("order book for token {}", token_id))?;
+
+        let (bid, ask) = if market.yes_token_id == token_id {
+            (market.bid_price, market.ask_price)
+        } else {
+            (1.0 - market.ask_price, 1.0 - market.bid_price)
+        };
+
+        let ladder = |start: f64, step: f64| -> Vec<BookLevel> {
+            (0..MOCK_BOOK_LEVELS)
+                .map(|i| BookLevel {
+                    price: ((start + step * i as f64) * 100.0).round() / 100.0,
+                    size: 250.0 * (i + 1) as f64,
+                })
+                .filter(|level| level.price > 0.0 && level.price < 1.0)
+                .collect()
+        };
+
+        Ok(OrderBook {
+            token_id: token_id.to_string(),
+            bids: ladder(bid, -0.01),
+            asks: ladder(ask, 0.01),
+        })
+    }
+}
+
+#[async_trait]
+impl TraderStatsProvider for MockSource {
+    async fn get_traders(&self, min_resolved_markets: u32) -> Result<Vec<Trader>> {
+        Ok(self.data.traders
+            .iter()
+            .filter(|t| t.total_markets_resolved >= min_resolved_markets)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_traders_by_addresses(&self, addresses: &[String]) -> Result<Vec<Trader>> {
+        Ok(self.data.traders
+            .iter()
+            .filter(|t| addresses.contains(&t.trader_address))
+            .cloned()
+            .collect())
+    }
+
+    async fn compute_traders(&self) -> Result<Vec<Trader>> {
+        Ok(self.data.traders.clone())
+    }
+
+    async fn get_category_stats(&self, addresses: &[String], categories: &[String]) -> Result<Vec<TraderCategoryStats>> {
+        Ok(self.data.category_stats
+            .iter()
+            .filter(|s| addresses.contains(&s.trader_address) && categories.contains(&s.category))
+            .cloned()
+            .collect())
+    }
+
+    // the generated stats as if a rebuild had stored them before the first block
+    async fn get_trader_snapshots(&self) -> Result<Vec<TraderSnapshot>> {
+        Ok(vec![TraderSnapshot { block: 0, traders: self.data.traders.clone() }])
+    }
+}
+
+#[async_trait]
+impl PositionProvider for MockSource {
+    async fn get_positions(&self, condition_id: &str) -> Result<Vec<Position>> {
+        Ok(self.data.positions
+            .iter()
+            .filter(|p| p.market_id == condition_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_all_positions(&self) -> Result<Vec<Position>> {
+        Ok(self.data.positions.clone())
+    }
+}
+
+#[async_trait]
+impl TransactionProvider for MockSource {
+    async fn get_recent_transactions(&self, condition_id: &str, days_back: u32) -> Result<Vec<Transaction>> {
+        let latest = self.data.transactions.iter().map(|tx| tx.block_number).max().unwrap_or_default();
+        let cutoff = latest.saturating_sub(days_back as u64 * BLOCKS_PER_DAY);
+
+        Ok(self.data.transactions
+            .iter()
+            .filter(|tx| tx.market_id == condition_id && tx.block_number >= cutoff)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_market_transactions(&self, condition_id: &str) -> Result<Vec<Transaction>> {
+        Ok(self.data.transactions
+            .iter()
+            .filter(|tx| tx.market_id == condition_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_all_transactions(&self) -> Result<Vec<Transaction>> {
+        Ok(self.data.transactions.clone())
+    }
+
+    async fn get_transactions_after(&self, block: u64, timestamp: i64) -> Result<Vec<Transaction>> {
+        Ok(self.data.transactions
+            .iter()
+            .filter(|tx| tx.block_number > block || (tx.block_number == 0 && tx.timestamp.is_some_and(|ts| ts > timestamp)))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_wallet_transactions(&self, addresses: &[String], timestamp: i64) -> Result<Vec<Transaction>> {
+        Ok(self.data.transactions
+            .iter()
+            .filter(|tx| addresses.contains(&tx.trader_address) && tx.timestamp.is_some_and(|ts| ts >= timestamp))
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ResolutionProvider for MockSource {
+    async fn get_resolutions(&self) -> Result<Vec<MarketResolution>> {
+        Ok(self.data.resolutions.clone())
+    }
+}
+
+#[async_trait]
+impl TagProvider for MockSource {
+    async fn get_market_tags(&self) -> Result<Vec<MarketTag>> {
+        Ok(generator::market_tags(&self.data.markets))
+    }
+}
+
+// writes are dropped so mock runs never touch disk
+#[async_trait]
+impl RewardProvider for MockSource {
+    async fn get_rewards(&self, addresses: &[String], after: Option<i64>) -> Result<Vec<RewardPayout>> {
+        Ok(self.data.rewards
+            .iter()
+            .filter(|r| addresses.contains(&r.trader_address) && after.is_none_or(|after| r.timestamp >= after))
+            .cloned()
+            .collect())
+    }
+}
+
+// every slug is the one live event, so every slug has its comments
+#[async_trait]
+impl CommentProvider for MockSource {
+    async fn get_event_comments(&self, _slug: &str, limit: usize) -> Result<Vec<Comment>> {
+        Ok(self.data.comments.iter().take(limit).cloned().collect())
+    }
+}
+
+#[async_trait]
+impl DataStore for MockSource {
+    async fn save_resolutions(&self, _resolutions: &[MarketResolution]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_traders(&self, _traders: &[Trader]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_trader_snapshot(&self, _snapshot: &TraderSnapshot) -> Result<()> {
+        Ok(())
+    }
+
+    // the generator's own clock, two second blocks from its first one
+    async fn blocks_at(&self, timestamps: &[i64]) -> Result<Vec<Option<u64>>> {
+        Ok(timestamps.iter().map(|timestamp| generator::block_at(*timestamp)).collect())
+    }
+
+    async fn append_transactions(&self, transactions: &[Transaction]) -> Result<Vec<Transaction>> {
+        Ok(transactions.to_vec())
+    }
+
+    async fn upsert_positions(&self, _positions: &[Position]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn append_rewards(&self, _rewards: &[RewardPayout]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_market_tags(&self, _tags: &[MarketTag]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_category_stats(&self, _stats: &[TraderCategoryStats]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_trader_ledgers(&self) -> Result<Vec<TraderLedger>> {
+        Ok(Vec::new())
+    }
+
+    async fn save_trader_ledgers(&self, _ledgers: &[TraderLedger]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn load_checkpoints(&self) -> Result<Checkpoints> {
+        Ok(Checkpoints::default())
+    }
+
+    async fn save_checkpoints(&self, _checkpoints: &Checkpoints) -> Result<()> {
+        Ok(())
+    }
+
+    // nothing on disk to compact
+    async fn compact(&self, _row_group_size: usize) -> Result<Vec<Compaction>> {
+        Ok(Vec::new())
+    }
+}
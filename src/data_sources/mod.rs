@@ -1,5 +1,9 @@
 pub mod polymarket_api;
 pub mod local_db;
+pub mod mock;
+pub mod capabilities;
 
 pub use polymarket_api::PolymarketApiSource;
-pub use local_db::LocalDbSource;
+pub use local_db::{LocalDbSource, QueryBackend};
+pub use mock::MockSource;
+pub use capabilities::Capabilities;
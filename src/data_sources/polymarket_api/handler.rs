@@ -1,22 +1,236 @@
-use crate::adapters::HttpClient;
-use crate::data_sources::polymarket_api::types::GammaMarketGroupResponse;
-use anyhow::Result;
+use crate::adapters::{ApiUrls, HttpClient, Revalidated, Validators};
+use crate::data_sources::polymarket_api::schema::{SchemaDrift, SchemaDriftReport};
+use crate::data_sources::polymarket_api::types::{ClobBookResponse, DataApiActivity, DataApiTrade, GammaComment, GammaMarketGroupResponse, GammaMarketResponse};
+use crate::clock;
+use crate::error::{AppError, HttpError, Result};
+use crate::standard_data::providers::{MarketFilter, MarketOrder};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
 
-const GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
+// trades per /trades page
+const TRADES_PAGE_SIZE: usize = 500;
+// the data api stops serving pages this deep, older history has to come from a dump
+const TRADES_MAX_OFFSET: usize = 10_000;
+// comments per /comments page
+const COMMENTS_PAGE_SIZE: usize = 100;
 
 pub struct PolymarketApiHandler {
     http_client: HttpClient,
+    urls: ApiUrls,
+    // last copy of each event by slug, revalidated instead of downloaded again on repeat fetches
+    group_cache: Mutex<HashMap<String, (Validators, GammaMarketGroupResponse)>>,
+    // gamma responses go through this before they're decoded
+    drift: SchemaDrift,
 }
 
 impl PolymarketApiHandler {
     // constructor
     pub fn new(http_client: HttpClient) -> Self {
-        Self { http_client }
+        Self { http_client, urls: ApiUrls::default(), group_cache: Mutex::new(HashMap::new()), drift: SchemaDrift::new() }
     }
 
-    // get market data from gamma api
+    pub fn with_urls(mut self, urls: ApiUrls) -> Self {
+        self.urls = urls;
+        self
+    }
+
+    // get market data from gamma api, monitor and watch fetch the same slug every interval so send
+    // the etag of the cached copy and reuse it on a 304
     pub async fn fetch_market_group(&self, slug: &str) -> Result<GammaMarketGroupResponse> {
-        let url = format!("{}/events/slug/{}", GAMMA_API_URL, slug);
+        let url = format!("{}/events/slug/{}", self.urls.gamma, slug);
+        let cached = self.group_cache.lock().ok().and_then(|cache| cache.get(slug).cloned());
+
+        match self.http_client.get_revalidated::<Value>(&url, cached.as_ref().map(|(validators, _)| validators)).await? {
+            Revalidated::Modified(raw, validators) => {
+                self.drift.check_event(&raw);
+                let group: GammaMarketGroupResponse = decode(raw)?;
+                if !validators.is_empty()
+                    && let Ok(mut cache) = self.group_cache.lock()
+                {
+                    cache.insert(slug.to_string(), (validators, group.clone()));
+                }
+                Ok(group)
+            }
+            // only sent validators when there was a cached copy
+            Revalidated::NotModified => match cached {
+                Some((_, group)) => Ok(group),
+                None => {
+                    let raw = self.http_client.get(&url).await?;
+                    self.drift.check_event(&raw);
+                    decode(raw)
+                }
+            },
+        }
+    }
+
+    // unknown and missing gamma fields seen so far
+    pub fn schema_drift(&self) -> SchemaDriftReport {
+        self.drift.report()
+    }
+
+    async fn fetch_gamma_markets(&self, url: &str) -> Result<Vec<GammaMarketResponse>> {
+        let raw = self.http_client.get(url).await?;
+        self.drift.check_markets(&raw);
+        decode(raw)
+    }
+
+    // get individual markets by condition id, closed ones included
+    pub async fn fetch_markets_by_condition_ids(&self, condition_ids: &[String]) -> Result<Vec<GammaMarketResponse>> {
+        let mut url = format!("{}/markets?closed=true&include_tag=true&limit={}", self.urls.gamma, condition_ids.len());
+        for condition_id in condition_ids {
+            url.push_str(&format!("&condition_ids={}", condition_id));
+        }
+        self.fetch_gamma_markets(&url).await
+    }
+
+    // open markets in the filter's order, gamma caps one page at 500
+    pub async fn fetch_active_markets(&self, filter: &MarketFilter) -> Result<Vec<GammaMarketResponse>> {
+        let (order, ascending) = match filter.order {
+            MarketOrder::Volume24h => ("volume24hr", false),
+            MarketOrder::Newest => ("createdAt", false),
+            MarketOrder::EndingSoonest => ("endDate", true),
+        };
+        let mut url = format!(
+            "{}/markets?active=true&closed=false&include_tag=true&order={}&ascending={}&limit={}",
+            self.urls.gamma,
+            order,
+            ascending,
+            filter.limit.min(500),
+        );
+        if !filter.include_archived {
+            url.push_str("&archived=false");
+        }
+        if let Some(min_liquidity) = filter.min_liquidity {
+            url.push_str(&format!("&liquidity_num_min={}", min_liquidity));
+        }
+        if let Some(ends_before) = filter.ends_before {
+            // markets past their end date but not resolved yet are still open, keep the lower bound at now
+            url.push_str(&format!(
+                "&end_date_min={}&end_date_max={}",
+                clock::now().format("%Y-%m-%dT%H:%M:%SZ"),
+                ends_before.format("%Y-%m-%dT%H:%M:%SZ"),
+            ));
+        }
+        self.fetch_gamma_markets(&url).await
+    }
+
+    // live order book of one outcome token
+    pub async fn fetch_order_book(&self, token_id: &str) -> Result<ClobBookResponse> {
+        let url = format!("{}/book?token_id={}", self.urls.clob, token_id);
         self.http_client.get(&url).await
     }
+
+    // every fill of a market between after and before (unix seconds), newest first
+    pub async fn fetch_trades(&self, condition_id: &str, after: Option<i64>, before: Option<i64>) -> Result<Vec<DataApiTrade>> {
+        self.fetch_trade_pages(&format!("market={}", condition_id), after, before).await
+    }
+
+    // a wallet's fills across every market at or after `after`, newest first
+    pub async fn fetch_wallet_trades(&self, address: &str, after: i64) -> Result<Vec<DataApiTrade>> {
+        self.fetch_trade_pages(&format!("user={}", address), Some(after), None).await
+    }
+
+    // pages are walked with the offset as cursor until one comes back short or goes past after
+    // rows carry no id, trades landing mid pull push the last rows of a page onto the next one and those are dropped
+    async fn fetch_trade_pages(&self, filter: &str, after: Option<i64>, before: Option<i64>) -> Result<Vec<DataApiTrade>> {
+        let mut trades = Vec::new();
+        let mut previous: Vec<DataApiTrade> = Vec::new();
+        let mut cursor = 0;
+
+        while cursor < TRADES_MAX_OFFSET {
+            // maker fills too, every wallet in the fill gets its own row
+            let url = format!(
+                "{}/trades?{}&takerOnly=false&limit={}&offset={}",
+                self.urls.data, filter, TRADES_PAGE_SIZE, cursor,
+            );
+            let page: Vec<DataApiTrade> = self.http_client.get(&url).await?;
+            let page_len = page.len();
+            let reached_after = page.last().is_some_and(|trade| after.is_some_and(|after| trade.timestamp < after));
+
+            let repeated = page_overlap(&previous, &page);
+            trades.extend(page[repeated..].iter().filter(|trade| {
+                after.is_none_or(|after| trade.timestamp >= after) && before.is_none_or(|before| trade.timestamp < before)
+            }).cloned());
+            previous = page;
+
+            if page_len < TRADES_PAGE_SIZE || reached_after {
+                break;
+            }
+            cursor += page_len;
+        }
+
+        Ok(trades)
+    }
+
+    // an event's latest comments newest first, comments hang off the event id so the event is looked up first,
+    // usually a revalidation of the copy analyze already fetched
+    pub async fn fetch_event_comments(&self, slug: &str, limit: usize) -> Result<Vec<GammaComment>> {
+        let event_id = self.fetch_market_group(slug).await?.id
+            .ok_or_else(|| AppError::Parse(format!("event {} has no id to fetch comments by", slug)))?;
+
+        let mut comments = Vec::new();
+        while comments.len() < limit {
+            let url = format!(
+                "{}/comments?parent_entity_type=Event&parent_entity_id={}&order=createdAt&ascending=false&limit={}&offset={}",
+                self.urls.gamma, event_id, COMMENTS_PAGE_SIZE, comments.len(),
+            );
+            let page: Vec<GammaComment> = self.http_client.get(&url).await?;
+            let page_len = page.len();
+            comments.extend(page);
+            if page_len < COMMENTS_PAGE_SIZE {
+                break;
+            }
+        }
+
+        comments.truncate(limit);
+        Ok(comments)
+    }
+
+    // a wallet's liquidity reward payouts at or after `after`, newest first, paged like the trades
+    pub async fn fetch_rewards(&self, address: &str, after: Option<i64>) -> Result<Vec<DataApiActivity>> {
+        let mut rewards = Vec::new();
+        let mut cursor = 0;
+
+        while cursor < TRADES_MAX_OFFSET {
+            let mut url = format!(
+                "{}/activity?user={}&type=REWARD&limit={}&offset={}",
+                self.urls.data, address, TRADES_PAGE_SIZE, cursor,
+            );
+            if let Some(after) = after {
+                url.push_str(&format!("&start={}", after));
+            }
+            let page: Vec<DataApiActivity> = self.http_client.get(&url).await?;
+            let page_len = page.len();
+            rewards.extend(page.into_iter().filter(|reward| after.is_none_or(|after| reward.timestamp >= after)));
+
+            if page_len < TRADES_PAGE_SIZE {
+                break;
+            }
+            cursor += page_len;
+        }
+
+        Ok(rewards)
+    }
+}
+
+// typed decode of json that was already checked for drift, errors read like the http client's
+fn decode<T: DeserializeOwned>(raw: Value) -> Result<T> {
+    T::deserialize(&raw).map_err(|error| {
+        HttpError::Deserialize {
+            error,
+            expected: std::any::type_name::<T>(),
+            raw: raw.to_string(),
+        }
+        .into()
+    })
+}
+
+// how many rows at the start of page repeat the end of the page before it
+fn page_overlap(previous: &[DataApiTrade], page: &[DataApiTrade]) -> usize {
+    (1..=previous.len().min(page.len()))
+        .rev()
+        .find(|n| previous[previous.len() - n..] == page[..*n])
+        .unwrap_or(0)
 }
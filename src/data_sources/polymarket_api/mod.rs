@@ -1,14 +1,17 @@
 mod handler;
+mod schema;
 mod standardizer;
 mod types;
 
-use crate::adapters::HttpClient;
-use crate::standard_data::models::MarketGroup;
-use crate::standard_data::providers::MarketMetadataProvider;
-use anyhow::Result;
+use crate::adapters::{ApiUrls, HttpClient};
+use crate::standard_data::models::{Comment, Market, MarketGroup, OrderBook, RewardPayout, Transaction};
+use crate::standard_data::providers::{CommentProvider, MarketFilter, MarketMetadataProvider, OrderBookProvider, RewardProvider, TransactionProvider};
+use crate::clock;
+use crate::error::{AppError, Result};
 use async_trait::async_trait;
 
 use handler::PolymarketApiHandler;
+pub use schema::{ObjectDrift, SchemaDriftReport};
 use standardizer::PolymarketApiStandardizer;
 
 pub struct PolymarketApiSource {
@@ -21,6 +24,17 @@ impl PolymarketApiSource {
             handler: PolymarketApiHandler::new(http_client),
         }
     }
+
+    // polymarket's own apis unless given others, see ApiUrls
+    pub fn with_urls(mut self, urls: ApiUrls) -> Self {
+        self.handler = self.handler.with_urls(urls);
+        self
+    }
+
+    // how far gamma's json has drifted from the typed schema during this run
+    pub fn schema_drift(&self) -> SchemaDriftReport {
+        self.handler.schema_drift()
+    }
 }
 
 #[async_trait]
@@ -29,9 +43,87 @@ impl MarketMetadataProvider for PolymarketApiSource {
         // get raw data from handler
         let raw = self.handler.fetch_market_group(slug).await?;
         // standardize the data from source
-        println!("{:#?}", raw);
         let market_group = PolymarketApiStandardizer::standardize_market_group(raw)?;
 
         Ok(market_group)
     }
+
+    async fn get_markets_by_condition_ids(&self, condition_ids: &[String]) -> Result<Vec<Market>> {
+        if condition_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let raw = self.handler.fetch_markets_by_condition_ids(condition_ids).await?;
+        raw.into_iter()
+            .map(PolymarketApiStandardizer::standardize_market)
+            .collect()
+    }
+
+    async fn get_active_markets(&self, filter: &MarketFilter) -> Result<Vec<Market>> {
+        let raw = self.handler.fetch_active_markets(filter).await?;
+        let markets = raw.into_iter()
+            .map(PolymarketApiStandardizer::standardize_market)
+            .collect::<Result<Vec<_>>>()?;
+        // the archived query param isn't honored everywhere, drop them here as well
+        Ok(markets.into_iter().filter(|m| filter.include_archived || !m.archived).collect())
+    }
+}
+
+#[async_trait]
+impl OrderBookProvider for PolymarketApiSource {
+    async fn get_order_book(&self, token_id: &str) -> Result<OrderBook> {
+        let raw = self.handler.fetch_order_book(token_id).await?;
+        PolymarketApiStandardizer::standardize_order_book(raw)
+    }
+}
+
+// trades straight from the data api, for markets the local dump doesn't cover
+#[async_trait]
+impl TransactionProvider for PolymarketApiSource {
+    async fn get_recent_transactions(&self, condition_id: &str, days_back: u32) -> Result<Vec<Transaction>> {
+        let after = clock::now().timestamp() - days_back as i64 * 24 * 60 * 60;
+        let raw = self.handler.fetch_trades(condition_id, Some(after), None).await?;
+        PolymarketApiStandardizer::standardize_trades(raw)
+    }
+
+    async fn get_market_transactions(&self, condition_id: &str) -> Result<Vec<Transaction>> {
+        let raw = self.handler.fetch_trades(condition_id, None, None).await?;
+        PolymarketApiStandardizer::standardize_trades(raw)
+    }
+
+    async fn get_all_transactions(&self) -> Result<Vec<Transaction>> {
+        Err(AppError::Unsupported("the data api only serves trades per market, replays need the local db".to_string()))
+    }
+
+    async fn get_transactions_after(&self, _block: u64, _timestamp: i64) -> Result<Vec<Transaction>> {
+        Err(AppError::Unsupported("the data api only serves trades per market, stats rebuilds need the local db".to_string()))
+    }
+
+    async fn get_wallet_transactions(&self, addresses: &[String], timestamp: i64) -> Result<Vec<Transaction>> {
+        let mut raw = Vec::new();
+        for address in addresses {
+            raw.extend(self.handler.fetch_wallet_trades(address, timestamp).await?);
+        }
+        PolymarketApiStandardizer::standardize_trades(raw)
+    }
+}
+
+#[async_trait]
+impl RewardProvider for PolymarketApiSource {
+    async fn get_rewards(&self, addresses: &[String], after: Option<i64>) -> Result<Vec<RewardPayout>> {
+        let mut rewards = Vec::new();
+        for address in addresses {
+            rewards.extend(PolymarketApiStandardizer::standardize_rewards(self.handler.fetch_rewards(address, after).await?));
+        }
+        rewards.sort_by_key(|reward| reward.timestamp);
+        Ok(rewards)
+    }
+}
+
+#[async_trait]
+impl CommentProvider for PolymarketApiSource {
+    async fn get_event_comments(&self, slug: &str, limit: usize) -> Result<Vec<Comment>> {
+        let raw = self.handler.fetch_event_comments(slug, limit).await?;
+        PolymarketApiStandardizer::standardize_comments(raw)
+    }
 }
@@ -0,0 +1,149 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+// event fields the typed response reads and gamma always sends
+const EVENT_EXPECTED: &[&str] = &["slug", "title", "active", "closed", "volume", "liquidity", "markets"];
+// read when present, gamma leaves them out for some events
+const EVENT_OPTIONAL: &[&str] = &["id", "archived", "tags"];
+// sent by gamma and not used here, anything outside the three lists is new
+const EVENT_IGNORED: &[&str] = &[
+    "ticker", "description", "resolutionSource", "startDate", "creationDate", "endDate", "image", "icon",
+    "new", "featured", "restricted", "openInterest", "sortBy", "category", "published_at", "createdAt",
+    "updatedAt", "competitive", "volume24hr", "volume1wk", "volume1mo", "volume1yr", "enableOrderBook",
+    "liquidityAmm", "liquidityClob", "negRisk", "negRiskMarketID", "negRiskFeeBips", "commentCount", "series",
+    "cyom", "showAllOutcomes", "showMarketImages", "enableNegRisk", "automaticallyActive", "gmpChartMode",
+    "negRiskAugmented", "pendingDeployment", "deploying", "startTime", "seriesSlug", "closedTime",
+    "countryName", "electionType", "subcategory", "eventDate", "eventStartTime", "tweetCount", "featuredOrder",
+    "estimateValue", "cantEstimate", "estimatedValue", "templates", "spreadsMainLine", "totalsMainLine",
+    "collections", "categories", "subtitle",
+];
+
+const MARKET_EXPECTED: &[&str] = &[
+    "question", "conditionId", "slug", "outcomes", "outcomePrices", "clobTokenIds", "active", "closed",
+    "volumeNum", "liquidityNum", "lastTradePrice", "bestBid", "bestAsk", "endDate",
+];
+const MARKET_OPTIONAL: &[&str] = &[
+    "archived", "volume24hr", "volume1wk", "volume1mo", "volume1yr", "competitive", "oneDayPriceChange",
+    "createdAt", "resolutionSource", "tags", "category",
+];
+const MARKET_IGNORED: &[&str] = &[
+    "id", "resolvedBy", "restricted", "startDate", "image", "icon", "description", "volume", "liquidity",
+    "marketMakerAddress", "updatedAt", "closedTime", "new", "featured", "submitted_by", "groupItemTitle",
+    "groupItemThreshold", "questionID", "enableOrderBook", "orderPriceMinTickSize", "orderMinSize",
+    "endDateIso", "startDateIso", "hasReviewedDates", "umaEndDate", "umaBond", "umaReward", "volume24hrAmm",
+    "volume1wkAmm", "volume1moAmm", "volume1yrAmm", "volume24hrClob", "volume1wkClob", "volume1moClob",
+    "volume1yrClob", "volumeAmm", "volumeClob", "liquidityAmm", "liquidityClob", "acceptingOrders", "negRisk",
+    "negRiskMarketID", "negRiskRequestID", "negRiskOther", "ready", "funded", "acceptingOrdersTimestamp",
+    "cyom", "pagerDutyNotificationEnabled", "approved", "clobRewards", "rewardsMinSize", "rewardsMaxSpread",
+    "spread", "oneHourPriceChange", "oneWeekPriceChange", "oneMonthPriceChange", "oneYearPriceChange",
+    "automaticallyActive", "clearBookOnStart", "seriesColor", "showGmpSeries", "showGmpOutcome",
+    "manualActivation", "umaResolutionStatus", "umaResolutionStatuses", "pendingDeployment", "deploying",
+    "deployingTimestamp", "rfqEnabled", "holdingRewardsEnabled", "feesEnabled", "twitterCardImage",
+    "mailchimpTag", "sportsMarketType", "line", "gameStartTime", "secondsDelay", "fpmmLive", "events",
+    "creator", "wideFormat", "customLiveness", "ammType", "marketType", "denominationToken", "fee",
+    "lowerBound", "upperBound", "lowerBoundDate", "upperBoundDate", "readyForCron", "curationOrder",
+    "scheduledDeploymentTimestamp", "notificationsEnabled", "competitiveScore", "teamAID", "teamBID",
+];
+
+// how far a kind of gamma object has moved from the typed schema over a run
+#[derive(Debug, Clone, Default)]
+pub struct ObjectDrift {
+    pub checked: u64,
+    // field name to the number of objects carrying it
+    pub unknown: BTreeMap<String, u64>,
+    // field name to the number of objects without it, null counts as missing
+    pub missing: BTreeMap<String, u64>,
+}
+
+impl ObjectDrift {
+    pub fn is_empty(&self) -> bool {
+        self.unknown.is_empty() && self.missing.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SchemaDriftReport {
+    pub events: ObjectDrift,
+    pub markets: ObjectDrift,
+}
+
+impl SchemaDriftReport {
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.markets.is_empty()
+    }
+}
+
+// compares raw gamma json against the fields the typed responses read before it's decoded
+#[derive(Default)]
+pub struct SchemaDrift {
+    report: Mutex<SchemaDriftReport>,
+    // unknown fields already warned about, each is logged the first time it shows up
+    logged: Mutex<HashSet<String>>,
+}
+
+impl SchemaDrift {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // an /events/slug response, its markets are checked too
+    pub fn check_event(&self, event: &Value) {
+        self.check("event", event, EVENT_EXPECTED, &[EVENT_OPTIONAL, EVENT_IGNORED], |report| &mut report.events);
+        if let Some(markets) = event.get("markets").and_then(Value::as_array) {
+            markets.iter().for_each(|market| self.check_market(market));
+        }
+    }
+
+    pub fn check_market(&self, market: &Value) {
+        self.check("market", market, MARKET_EXPECTED, &[MARKET_OPTIONAL, MARKET_IGNORED], |report| &mut report.markets);
+    }
+
+    // a /markets response
+    pub fn check_markets(&self, markets: &Value) {
+        if let Some(markets) = markets.as_array() {
+            markets.iter().for_each(|market| self.check_market(market));
+        }
+    }
+
+    pub fn report(&self) -> SchemaDriftReport {
+        self.report.lock().map(|report| report.clone()).unwrap_or_default()
+    }
+
+    fn check(
+        &self,
+        kind: &str,
+        object: &Value,
+        expected: &[&str],
+        known: &[&[&str]],
+        drift: impl Fn(&mut SchemaDriftReport) -> &mut ObjectDrift,
+    ) {
+        // not an object at all is a decode error, serde reports that one
+        let Some(fields) = object.as_object() else {
+            return;
+        };
+        let Ok(mut report) = self.report.lock() else {
+            return;
+        };
+        let drift = drift(&mut report);
+        drift.checked += 1;
+
+        for name in fields.keys() {
+            if expected.contains(&name.as_str()) || known.iter().any(|list| list.contains(&name.as_str())) {
+                continue;
+            }
+            *drift.unknown.entry(name.clone()).or_default() += 1;
+            if let Ok(mut logged) = self.logged.lock()
+                && logged.insert(format!("{}.{}", kind, name))
+            {
+                eprintln!("Warning: gamma {} has an unknown field '{}'", kind, name);
+            }
+        }
+
+        for name in expected {
+            if fields.get(*name).is_none_or(Value::is_null) {
+                *drift.missing.entry(name.to_string()).or_default() += 1;
+            }
+        }
+    }
+}
@@ -1,69 +1,230 @@
-use crate::standard_data::models::{Market, MarketGroup};
-use crate::data_sources::polymarket_api::types::{GammaMarketGroupResponse, GammaMarketResponse};
-use anyhow::{Context, Result};
+use crate::standard_data::models::{BookLevel, Collateral, Comment, Market, MarketGroup, OrderBook, RewardPayout, Transaction};
+use crate::data_sources::polymarket_api::types::{ClobBookLevel, ClobBookResponse, DataApiActivity, DataApiTrade, GammaComment, GammaMarketGroupResponse, GammaMarketResponse, GammaTag};
+use crate::error::{AppError, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 
 // struct to standardize from X sourcse for analytic engine
 pub struct PolymarketApiStandardizer;
 
 impl PolymarketApiStandardizer {
     pub fn standardize_market_group(raw: GammaMarketGroupResponse) -> Result<MarketGroup> {
-        let markets = raw.markets
+        let group_tags = Self::standardize_tags(raw.tags.unwrap_or_default(), None);
+        let mut markets = raw.markets
             .into_iter()
             .map(Self::standardize_market)
             .collect::<Result<Vec<_>>>()?;
 
+        // event endpoints tag the event, not each market
+        for market in markets.iter_mut().filter(|m| m.tags.is_empty()) {
+            market.tags = group_tags.clone();
+        }
+
         Ok(MarketGroup {
             slug: raw.slug,
             title: raw.title,
             active: raw.active,
             closed: raw.closed,
-            volume: raw.volume,
-            liquidity: raw.liquidity,
+            volume: raw.volume.unwrap_or_default(),
+            liquidity: raw.liquidity.unwrap_or_default(),
             markets,
         })
     }
 
     // convert the gamma api data to standard data model
-    fn standardize_market(raw: GammaMarketResponse) -> Result<Market> {
-        // Parse JSON strings
-        let outcomes: Vec<String> = serde_json::from_str(&raw.outcomes)
-            .context("Failed to parse outcomes")?;
-        
-        let outcome_prices: Vec<String> = serde_json::from_str(&raw.outcome_prices)
-            .context("Failed to parse outcome prices")?;
-        
-        let token_ids: Vec<String> = serde_json::from_str(&raw.clob_token_ids)
-            .context("Failed to parse token IDs")?;
-
-        // get token Id for YES, NO from vec
-        let yes_token_id = token_ids.get(0)
-            .context("Missing YES token ID")?
-            .clone();
-        
-        let no_token_id = token_ids.get(1)
-            .context("Missing NO token ID")?
-            .clone();
+    pub fn standardize_market(raw: GammaMarketResponse) -> Result<Market> {
+        // Parse JSON strings, a null one is an empty list
+        let parse_list = |raw: Option<&str>, what: &str| -> Result<Vec<String>> {
+            match raw {
+                Some(raw) => serde_json::from_str(raw).map_err(|e| AppError::Parse(format!("{}: {}", what, e))),
+                None => Ok(Vec::new()),
+            }
+        };
+        let outcomes = parse_list(raw.outcomes.as_deref(), "outcomes")?;
+        let outcome_prices = parse_list(raw.outcome_prices.as_deref(), "outcome prices")?;
+        let token_ids = parse_list(raw.clob_token_ids.as_deref(), "token IDs")?;
+
+        // get token Id for YES, NO from vec, archived markets may never have had tokens
+        let archived = raw.archived.unwrap_or(false);
+        let (yes_token_id, no_token_id) = match (token_ids.first(), token_ids.get(1)) {
+            (Some(yes), Some(no)) => (yes.clone(), no.clone()),
+            _ if archived || raw.clob_token_ids.is_none() => (String::new(), String::new()),
+            (None, _) => return Err(AppError::Parse("Missing YES token ID".to_string())),
+            (Some(_), None) => return Err(AppError::Parse("Missing NO token ID".to_string())),
+        };
+
+        let created_at = raw.created_at
+            .as_deref()
+            .map(Self::parse_date)
+            .transpose()?;
+
+        let end_date = raw.end_date
+            .as_deref()
+            .map(Self::parse_date)
+            .transpose()?;
+
+        let closed_time = raw.closed_time
+            .as_deref()
+            .map(Self::parse_date)
+            .transpose()?;
+
+        // gamma sends an empty string when there is no source
+        let resolution_source = raw.resolution_source
+            .filter(|s| !s.trim().is_empty());
+
+        let tags = Self::standardize_tags(raw.tags.unwrap_or_default(), raw.category);
+
+        // without a last trade the quoted YES outcome price is the best guess, then even odds
+        let last_trade_price = raw.last_trade_price
+            .or_else(|| outcome_prices.first().and_then(|p| p.parse().ok()))
+            .unwrap_or(0.5);
 
         Ok(Market {
             question: raw.question,
             condition_id: raw.condition_id,
             slug: raw.slug,
-            outcomes: outcomes,
-            outcome_prices: outcome_prices,
-            yes_token_id: yes_token_id,
-            no_token_id: no_token_id,
+            outcomes,
+            outcome_prices,
+            yes_token_id,
+            no_token_id,
             active: raw.active,
             closed: raw.closed,
-            volume: raw.volume_num,
-            volume_24h: raw.volume_24hr,
-            volume_1w: raw.volume_1wk,
-            volume_1m: raw.volume_1mo,
-            volume_1y: raw.volume_1yr,
-            liquidity: raw.liquidity_num,
-            competitive: raw.competitive,
-            last_trade_price: raw.last_trade_price,
-            bid_price: raw.best_bid,
-            ask_price: raw.best_ask,
+            archived,
+            volume: raw.volume_num.unwrap_or_default(),
+            volume_24h: raw.volume_24hr.unwrap_or_default(),
+            volume_1w: raw.volume_1wk.unwrap_or_default(),
+            volume_1m: raw.volume_1mo.unwrap_or_default(),
+            volume_1y: raw.volume_1yr.unwrap_or_default(),
+            liquidity: raw.liquidity_num.unwrap_or_default(),
+            competitive: raw.competitive.unwrap_or_default(),
+            last_trade_price,
+            // no quotes reads as an empty book, nothing bid and nothing offered below 1
+            bid_price: raw.best_bid.unwrap_or(0.0),
+            ask_price: raw.best_ask.unwrap_or(1.0),
+            price_change_24h: raw.one_day_price_change,
+            created_at,
+            end_date,
+            closed_time,
+            resolution_source,
+            tags,
         })
     }
+
+    // the clob doesn't promise any level order, sort both sides best first
+    pub fn standardize_order_book(raw: ClobBookResponse) -> Result<OrderBook> {
+        let levels = |raw: Vec<ClobBookLevel>| -> Result<Vec<BookLevel>> {
+            raw.into_iter()
+                .map(|level| {
+                    let price = level.price.parse::<f64>()
+                        .map_err(|e| AppError::Parse(format!("book price {}: {}", level.price, e)))?;
+                    let size = level.size.parse::<f64>()
+                        .map_err(|e| AppError::Parse(format!("book size {}: {}", level.size, e)))?;
+                    Ok(BookLevel { price, size })
+                })
+                .collect()
+        };
+
+        let mut bids = levels(raw.bids)?;
+        let mut asks = levels(raw.asks)?;
+        bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+        asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+
+        Ok(OrderBook { token_id: raw.asset_id, bids, asks })
+    }
+
+    // the data api has no block numbers, trades carry their timestamp instead and block_number is 0
+// until the local db places them on the chain when they're stored
+    pub fn standardize_trade(raw: DataApiTrade) -> Result<Transaction> {
+        let side = match raw.outcome_index {
+            0 => "YES",
+            1 => "NO",
+            other => return Err(AppError::Parse(format!("trade outcome index {}", other))),
+        };
+
+        Ok(Transaction {
+            block_number: 0,
+            transaction_hash: raw.transaction_hash,
+            log_index: None,
+            trader_address: raw.proxy_wallet.to_lowercase(),
+            token_id: raw.asset,
+            side: side.to_string(),
+            action: raw.side.to_uppercase(),
+            shares: raw.size,
+            usdc_amount: raw.size * raw.price,
+            market_id: raw.condition_id,
+            timestamp: Some(raw.timestamp),
+            // the data api doesn't say which token a trade settled in, the exchange has only ever used usdc.e
+            collateral: Collateral::UsdcE,
+        })
+    }
+
+    // oldest first, rows without a condition id get no market
+    pub fn standardize_rewards(raw: Vec<DataApiActivity>) -> Vec<RewardPayout> {
+        let mut rewards: Vec<RewardPayout> = raw.into_iter()
+            .map(|raw| RewardPayout {
+                trader_address: raw.proxy_wallet.to_lowercase(),
+                market_id: Some(raw.condition_id).filter(|id| !id.is_empty()),
+                usdc_amount: raw.usdc_size,
+                timestamp: raw.timestamp,
+                transaction_hash: raw.transaction_hash,
+            })
+            .collect();
+        rewards.sort_by_key(|reward| reward.timestamp);
+        rewards
+    }
+
+    // newest first as gamma sent them, deleted comments are dropped
+    pub fn standardize_comments(raw: Vec<GammaComment>) -> Result<Vec<Comment>> {
+        raw.into_iter()
+            .filter(|raw| raw.body.as_deref().is_some_and(|body| !body.trim().is_empty()))
+            .map(|raw| Ok(Comment {
+                created_at: Self::parse_date(&raw.created_at)?.timestamp(),
+                id: raw.id,
+                body: raw.body.unwrap_or_default(),
+                author_address: raw.user_address.unwrap_or_default().to_lowercase(),
+                likes: raw.reaction_count.unwrap_or_default(),
+            }))
+            .collect()
+    }
+
+    // no block numbers to order by, the timestamp stands in
+    pub fn standardize_trades(raw: Vec<DataApiTrade>) -> Result<Vec<Transaction>> {
+        let mut transactions = raw.into_iter()
+            .map(Self::standardize_trade)
+            .collect::<Result<Vec<_>>>()?;
+        transactions.sort_by_key(|tx| tx.timestamp);
+        Ok(transactions)
+    }
+
+    // tag slugs, falling back to the label, plus the legacy category, deduplicated
+    fn standardize_tags(raw: Vec<GammaTag>, category: Option<String>) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        let names = raw.into_iter()
+            .map(|tag| tag.slug.filter(|s| !s.trim().is_empty()).unwrap_or(tag.label))
+            .chain(category);
+
+        for name in names {
+            let slug = name.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("-");
+            if !slug.is_empty() && !tags.contains(&slug) {
+                tags.push(slug);
+            }
+        }
+        tags
+    }
+
+    // full timestamps, with a fallback for bare dates
+    fn parse_date(raw: &str) -> Result<DateTime<Utc>> {
+        if let Ok(date) = DateTime::parse_from_rfc3339(raw) {
+            return Ok(date.with_timezone(&Utc));
+        }
+        // closedTime has a space and an hour only offset
+        if let Ok(date) = DateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%#z") {
+            return Ok(date.with_timezone(&Utc));
+        }
+
+        NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc())
+            .ok_or_else(|| AppError::Parse(format!("date: {}", raw)))
+    }
 }
@@ -1,38 +1,154 @@
 use serde::{Deserialize, Serialize};
 
 // raw from Gamma API
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GammaMarketGroupResponse {
+    // what comments hang off, sent as a string
+    #[serde(default)]
+    pub id: Option<String>,
     pub slug: String,
     pub title: String,
     pub active: bool,
     pub closed: bool,
-    pub volume: f64,
-    pub liquidity: f64,
+    // archived and restricted events come back with nulls where the numbers should be
+    #[serde(default)]
+    pub archived: Option<bool>,
+    #[serde(default)]
+    pub volume: Option<f64>,
+    #[serde(default)]
+    pub liquidity: Option<f64>,
     pub markets: Vec<GammaMarketResponse>,
+    #[serde(default)]
+    pub tags: Option<Vec<GammaTag>>,
 }
 
 // individual market events
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GammaMarketResponse {
     pub question: String,
     pub condition_id: String,
     pub slug: String,
-    pub outcomes: String,
-    pub outcome_prices: String,
-    pub clob_token_ids: String,
+    // json arrays sent as strings, null on archived markets that never got tokens
+    #[serde(default)]
+    pub outcomes: Option<String>,
+    #[serde(default)]
+    pub outcome_prices: Option<String>,
+    #[serde(default)]
+    pub clob_token_ids: Option<String>,
     pub active: bool,
     pub closed: bool,
-    pub volume_num: f64,
-    pub volume_24hr: f64,
-    pub volume_1wk: f64,
-    pub volume_1mo: f64,
-    pub volume_1yr: f64,
-    pub liquidity_num: f64,
-    pub competitive: f64,
-    pub last_trade_price: f64,
-    pub best_bid: f64,
-    pub best_ask: f64,
+    #[serde(default)]
+    pub archived: Option<bool>,
+    // archived and restricted markets send null or leave these out, the standardizer fills in defaults
+    #[serde(default)]
+    pub volume_num: Option<f64>,
+    #[serde(default)]
+    pub volume_24hr: Option<f64>,
+    #[serde(default)]
+    pub volume_1wk: Option<f64>,
+    #[serde(default)]
+    pub volume_1mo: Option<f64>,
+    #[serde(default)]
+    pub volume_1yr: Option<f64>,
+    #[serde(default)]
+    pub liquidity_num: Option<f64>,
+    #[serde(default)]
+    pub competitive: Option<f64>,
+    #[serde(default)]
+    pub last_trade_price: Option<f64>,
+    #[serde(default)]
+    pub best_bid: Option<f64>,
+    #[serde(default)]
+    pub best_ask: Option<f64>,
+    #[serde(default)]
+    pub one_day_price_change: Option<f64>,
+    // iso 8601, missing on some older markets
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub end_date: Option<String>,
+    // "2024-11-06 12:00:00+00", not iso 8601
+    #[serde(default)]
+    pub closed_time: Option<String>,
+    #[serde(default)]
+    pub resolution_source: Option<String>,
+    // only sent with include_tag=true
+    #[serde(default)]
+    pub tags: Option<Vec<GammaTag>>,
+    // older markets carry a single free text category instead
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GammaTag {
+    pub label: String,
+    #[serde(default)]
+    pub slug: Option<String>,
+}
+
+// raw book from the CLOB api, prices and sizes come as strings
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ClobBookResponse {
+    pub asset_id: String,
+    #[serde(default)]
+    pub bids: Vec<ClobBookLevel>,
+    #[serde(default)]
+    pub asks: Vec<ClobBookLevel>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ClobBookLevel {
+    pub price: String,
+    pub size: String,
+}
+
+// one fill from the data api /trades endpoint
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataApiTrade {
+    pub proxy_wallet: String,
+    // BUY or SELL
+    pub side: String,
+    // clob token id
+    pub asset: String,
+    pub condition_id: String,
+    pub size: f64,
+    pub price: f64,
+    // unix seconds
+    pub timestamp: i64,
+    // 0 is YES, 1 is NO
+    pub outcome_index: u32,
+    pub transaction_hash: String,
+}
+
+// one row of a wallet's data api /activity, only reward rows are asked for
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataApiActivity {
+    pub proxy_wallet: String,
+    // unix seconds
+    pub timestamp: i64,
+    // empty when the row isn't tied to a market
+    #[serde(default)]
+    pub condition_id: String,
+    pub usdc_size: f64,
+    pub transaction_hash: String,
+}
+
+// one comment from gamma's /comments
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaComment {
+    pub id: String,
+    // deleted comments come back without a body
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub user_address: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub reaction_count: Option<u32>,
 }
@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, AppError>;
+
+// every error the sources and adapters can hand back to the cli
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Http(#[from] HttpError),
+
+    #[error(transparent)]
+    Data(#[from] DataError),
+
+    #[error("Failed to parse API data: {0}")]
+    Parse(String),
+
+    #[error("Not supported by this source: {0}")]
+    Unsupported(String),
+
+    #[error(transparent)]
+    Cancelled(#[from] CancelError),
+
+    #[cfg(feature = "trading")]
+    #[error(transparent)]
+    Trading(#[from] crate::trading::TradingError),
+}
+
+// failures talking to remote apis
+#[derive(Debug, Error)]
+pub enum HttpError {
+    #[error("HTTP Request failed: {0}")]
+    Request(#[source] reqwest::Error),
+
+    #[error("Request timed out after {seconds} seconds: {url}")]
+    Timeout { url: String, seconds: u64 },
+
+    #[error("HTTP Request failed: {status} - {body}")]
+    Status { status: reqwest::StatusCode, body: String },
+
+    #[error("Deserialization Error: {error}\nExpected Type: {expected}\nRaw JSON: {raw}")]
+    Deserialize { error: serde_json::Error, expected: &'static str, raw: String },
+
+    #[error("Invalid HTTP client settings: {0}")]
+    InvalidConfig(String),
+
+    #[error("RPC call {method} failed: {message}")]
+    Rpc { method: String, message: String },
+
+    #[error("No captured response for {method} {url}")]
+    NotCaptured { method: String, url: String },
+
+    #[error("Response from {url} is larger than the {limit} byte limit")]
+    TooLarge { url: String, limit: u64 },
+}
+
+// why a run stopped before it finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum CancelError {
+    #[error("Interrupted")]
+    Interrupted,
+
+    #[error("Stopped after the --timeout of {seconds} seconds")]
+    TimedOut { seconds: u64 },
+}
+
+// failures reading or writing the local db
+#[derive(Debug, Error)]
+pub enum DataError {
+    #[error("Parquet file not found {0:?}")]
+    TableNotFound(PathBuf),
+
+    #[error("Local data dir {} is missing: {}", dir.display(), missing.join(", "))]
+    MissingTables { dir: PathBuf, missing: Vec<String> },
+
+    #[error("local db schema problems:\n  {}", .0.join("\n  "))]
+    Schema(Vec<String>),
+
+    #[error("Missing {0}")]
+    MissingValue(String),
+
+    #[error("Unreadable local file: {0}")]
+    Corrupt(String),
+
+    #[error("Polars error: {0}")]
+    Polars(#[from] polars::error::PolarsError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[cfg(feature = "duckdb")]
+    #[error("DuckDB error: {0}")]
+    DuckDb(#[from] duckdb::Error),
+}
+
+impl From<polars::error::PolarsError> for AppError {
+    fn from(e: polars::error::PolarsError) -> Self {
+        AppError::Data(DataError::Polars(e))
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Data(DataError::Io(e))
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Data(DataError::Sqlite(e))
+    }
+}
+
+#[cfg(feature = "duckdb")]
+impl From<duckdb::Error> for AppError {
+    fn from(e: duckdb::Error) -> Self {
+        AppError::Data(DataError::DuckDb(e))
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        AppError::Http(HttpError::Request(e))
+    }
+}
+
+impl AppError {
+    // top level group, stable for scripts
+    pub fn category(&self) -> &'static str {
+        match self {
+            AppError::Http(_) => "http",
+            AppError::Data(_) => "data",
+            AppError::Parse(_) => "parse",
+            AppError::Unsupported(_) => "source",
+            AppError::Cancelled(_) => "cancelled",
+            #[cfg(feature = "trading")]
+            AppError::Trading(_) => "trading",
+        }
+    }
+
+    // stable machine readable code, never reuse or rename these
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Http(HttpError::Request(_)) => "http.request",
+            AppError::Http(HttpError::Timeout { .. }) => "http.timeout",
+            AppError::Http(HttpError::Status { .. }) => "http.status",
+            AppError::Http(HttpError::Deserialize { .. }) => "http.deserialize",
+            AppError::Http(HttpError::InvalidConfig(_)) => "http.config",
+            AppError::Http(HttpError::Rpc { .. }) => "http.rpc",
+            AppError::Http(HttpError::NotCaptured { .. }) => "http.not_captured",
+            AppError::Http(HttpError::TooLarge { .. }) => "http.too_large",
+            AppError::Data(DataError::TableNotFound(_)) => "data.table_not_found",
+            AppError::Data(DataError::MissingTables { .. }) => "data.missing_tables",
+            AppError::Data(DataError::Schema(_)) => "data.schema",
+            AppError::Data(DataError::MissingValue(_)) => "data.missing_value",
+            AppError::Data(DataError::Corrupt(_)) => "data.corrupt",
+            AppError::Data(DataError::Polars(_)) => "data.polars",
+            AppError::Data(DataError::Io(_)) => "data.io",
+            AppError::Data(DataError::Sqlite(_)) => "data.sqlite",
+            #[cfg(feature = "duckdb")]
+            AppError::Data(DataError::DuckDb(_)) => "data.duckdb",
+            AppError::Parse(_) => "parse.api",
+            AppError::Unsupported(_) => "source.unsupported",
+            AppError::Cancelled(CancelError::Interrupted) => "cancelled.interrupted",
+            AppError::Cancelled(CancelError::TimedOut { .. }) => "cancelled.timeout",
+            #[cfg(feature = "trading")]
+            AppError::Trading(crate::trading::TradingError::Credentials(_)) => "trading.credentials",
+            #[cfg(feature = "trading")]
+            AppError::Trading(crate::trading::TradingError::InvalidOrder(_)) => "trading.invalid_order",
+            #[cfg(feature = "trading")]
+            AppError::Trading(crate::trading::TradingError::Rejected(_)) => "trading.rejected",
+        }
+    }
+
+    // what the user can do about it, printed under the error by the cli
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AppError::Http(HttpError::Timeout { .. }) => Some("Check your connection or raise --request-timeout"),
+            AppError::Http(HttpError::InvalidConfig(_)) => Some("Check --proxy, the --*-url api flags and the other HTTP flags"),
+            AppError::Http(HttpError::Rpc { .. }) => Some("Check that --ens-rpc points at an ethereum mainnet and --polygon-rpc at a polygon json-rpc endpoint"),
+            AppError::Http(HttpError::NotCaptured { .. }) => {
+                Some("The --replay-raw dir doesn't have this request, capture it with --dump-raw running the same command")
+            }
+            AppError::Http(HttpError::TooLarge { .. }) => {
+                Some("Raise --max-response-mb if the api really sends this much, otherwise it's sending something unexpected")
+            }
+            AppError::Http(HttpError::Request(_)) => Some("Check your internet connection"),
+            AppError::Http(HttpError::Status { status, .. }) => match status.as_u16() {
+                404 => Some("Check the market slug, it's the last part of the polymarket event url"),
+                429 => Some("Rate limited by the API, wait a bit before retrying"),
+                _ => None,
+            },
+            AppError::Http(HttpError::Deserialize { .. }) | AppError::Parse(_) => {
+                Some("The API response format may have changed")
+            }
+            AppError::Data(DataError::TableNotFound(_)) | AppError::Data(DataError::MissingTables { .. }) => {
+                Some("Point --data-dir (or POLYMARKET_EXPLORER_DATA) at the processed parquet dump, or try --source mock")
+            }
+            AppError::Data(DataError::Schema(_)) => {
+                Some("Regenerate the dump or add a migration in data_sources/local_db/schema.rs")
+            }
+            AppError::Data(DataError::MissingValue(_)) => Some("The local parquet files contain null values"),
+            AppError::Cancelled(CancelError::TimedOut { .. }) => Some("Raise --timeout or ask for fewer markets"),
+            AppError::Data(DataError::Corrupt(_)) => Some("Fix or delete the file, it will be recreated"),
+            AppError::Data(DataError::Sqlite(_)) => Some("Another run may be holding state.sqlite in the data dir, try again once it's done"),
+            #[cfg(feature = "trading")]
+            AppError::Trading(crate::trading::TradingError::Credentials(_)) => {
+                Some("Set POLYMARKET_PRIVATE_KEY, POLYMARKET_API_KEY, POLYMARKET_API_SECRET and POLYMARKET_API_PASSPHRASE")
+            }
+            _ => None,
+        }
+    }
+}
+
+// turn a missing value into a typed error
+pub trait OrMissing<T> {
+    fn or_missing(self, what: &str) -> Result<T>;
+}
+
+impl<T> OrMissing<T> for Option<T> {
+    fn or_missing(self, what: &str) -> Result<T> {
+        self.ok_or_else(|| DataError::MissingValue(what.to_string()).into())
+    }
+}
@@ -0,0 +1,112 @@
+use crate::standard_data::models::{RewardPayout, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const CHECKPOINTS_FILE: &str = "ingest_checkpoints.json";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// how far each ingest dataset got, stored next to the tables so another data dir starts fresh
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoints {
+    // newest trade timestamp ingested per market
+    #[serde(default)]
+    pub trades: BTreeMap<String, i64>,
+    // last condition id queried by a run that was interrupted, cleared when a run finishes
+    #[serde(default)]
+    pub resolutions: Option<String>,
+    #[serde(default)]
+    pub tags: Option<String>,
+    // newest reward payout ingested per wallet
+    #[serde(default)]
+    pub rewards: BTreeMap<String, i64>,
+    // last block scanned for splits, merges and redemptions per market
+    #[serde(default)]
+    pub ctf: BTreeMap<String, u64>,
+    // how far the stored trader ledgers got, None means the next stats rebuild starts over
+    #[serde(default)]
+    pub stats: Option<StatsCheckpoint>,
+    // markets given new rows whose positions haven't been upserted yet, an interrupted run leaves them for the next
+    #[serde(default)]
+    pub positions: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsCheckpoint {
+    // newest transaction block folded into the ledgers
+    pub block: u64,
+    // data api trades have no block, newest timestamp folded among those
+    pub timestamp: i64,
+    // resolutions the stored stats were derived with, resolutions only get added
+    pub resolutions: usize,
+}
+
+impl StatsCheckpoint {
+    // whether the ledgers already hold this transaction
+    pub fn covers(&self, tx: &Transaction) -> bool {
+        match tx.block_number {
+            0 => tx.timestamp.is_none_or(|ts| ts <= self.timestamp),
+            block => block <= self.block,
+        }
+    }
+
+    // moved up past transactions just folded in
+    pub fn advance(&mut self, folded: &[Transaction]) {
+        for tx in folded {
+            match tx.block_number {
+                0 => self.timestamp = self.timestamp.max(tx.timestamp.unwrap_or_default()),
+                block => self.block = self.block.max(block),
+            }
+        }
+    }
+}
+
+impl Checkpoints {
+    // days of trades to pull for a market, back to its checkpoint when it has one
+    // whole days so the first day overlaps, the overlap is deduped against the local trades
+    pub fn trade_days(&self, condition_id: &str, default_days: u32, now: i64) -> u32 {
+        match self.trades.get(condition_id) {
+            Some(&last) => ((now - last).max(0) as u64).div_ceil(SECONDS_PER_DAY as u64).max(1) as u32,
+            None => default_days,
+        }
+    }
+
+    // move the market's checkpoint up to the newest timed trade
+    pub fn record_trades(&mut self, condition_id: &str, transactions: &[Transaction]) {
+        let Some(newest) = transactions.iter().filter_map(|tx| tx.timestamp).max() else {
+            return;
+        };
+        let last = self.trades.entry(condition_id.to_string()).or_insert(newest);
+        *last = (*last).max(newest);
+    }
+
+    // move the wallet's checkpoint up to its newest payout
+    pub fn record_rewards(&mut self, address: &str, rewards: &[RewardPayout]) {
+        let Some(newest) = rewards.iter().map(|reward| reward.timestamp).max() else {
+            return;
+        };
+        let last = self.rewards.entry(address.to_lowercase()).or_insert(newest);
+        *last = (*last).max(newest);
+    }
+
+    // first block to scan a market's ctf logs from, the one after its checkpoint when it has one
+    pub fn ctf_from(&self, condition_id: &str, default_from: u64) -> u64 {
+        self.ctf.get(condition_id).map_or(default_from, |last| last + 1)
+    }
+
+    // trades landing behind the stats checkpoint would never be folded in, start the ledgers over instead
+    pub fn invalidate_stats(&mut self, appended: &[Transaction]) {
+        if self.stats.is_some_and(|stats| appended.iter().any(|tx| stats.covers(tx))) {
+            self.stats = None;
+        }
+    }
+}
+
+// ids left after the cursor of an interrupted run, every id without one
+// ids must be sorted, runs walk them in that order
+pub fn after_cursor<'a>(ids: &'a [String], cursor: Option<&str>) -> &'a [String] {
+    match cursor {
+        Some(cursor) => &ids[ids.partition_point(|id| id.as_str() <= cursor)..],
+        None => ids,
+    }
+}
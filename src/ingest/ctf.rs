@@ -0,0 +1,39 @@
+use crate::adapters::ctf_events::{CtfEvent, CtfOperation};
+use crate::standard_data::models::{Market, Transaction};
+
+// transaction rows for a market's splits, merges and redemptions
+// a split of n usdc mints n shares of each side, each booked at half the cost so both enter at $0.50, merges the reverse
+// a redemption sells the winning side at $1, the losing shares it burns were worth nothing and are left out
+// without a winner there's no telling which side a redemption paid for, those are skipped
+pub fn ctf_transactions(events: &[CtfEvent], market: &Market, winner: Option<&str>) -> Vec<Transaction> {
+    let row = |event: &CtfEvent, side: &str, shares: f64, usdc_amount: f64| Transaction {
+        block_number: event.block,
+        transaction_hash: event.transaction_hash.clone(),
+        log_index: Some(event.log_index),
+        trader_address: event.account.clone(),
+        token_id: if side == "YES" { market.yes_token_id.clone() } else { market.no_token_id.clone() },
+        side: side.to_string(),
+        action: event.operation.action().to_string(),
+        shares,
+        usdc_amount,
+        market_id: market.condition_id.clone(),
+        timestamp: None,
+        collateral: event.collateral,
+    };
+
+    let mut transactions = Vec::new();
+    for event in events.iter().filter(|event| event.condition_id.eq_ignore_ascii_case(&market.condition_id)) {
+        match event.operation {
+            CtfOperation::Split | CtfOperation::Merge => {
+                transactions.push(row(event, "YES", event.amount, event.amount / 2.0));
+                transactions.push(row(event, "NO", event.amount, event.amount / 2.0));
+            }
+            CtfOperation::Redeem => {
+                if let Some(winner) = winner {
+                    transactions.push(row(event, &winner.to_uppercase(), event.amount, event.amount));
+                }
+            }
+        }
+    }
+    transactions
+}
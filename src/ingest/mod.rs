@@ -0,0 +1,10 @@
+pub mod checkpoint;
+pub mod ctf;
+pub mod positions;
+pub mod resolutions;
+pub mod trader_stats;
+
+pub use checkpoint::{Checkpoints, StatsCheckpoint};
+pub use ctf::ctf_transactions;
+pub use positions::positions_from_transactions;
+pub use trader_stats::{category_stats_from_ledgers, combine_traders, compute_category_stats, compute_trader_stats, fold_transactions, traders_from_ledgers, traders_from_outcomes, wilson_lower_bound, Ledgers, Outcomes};
@@ -0,0 +1,36 @@
+use crate::standard_data::models::{Position, Transaction};
+use std::collections::HashMap;
+
+// every wallet's holding of every token it traded, replayed from its transactions in order
+// buys and splits add shares at their cost, sells, merges and redemptions take shares off at the average
+// closed holdings stay in at 0 shares so an upsert overwrites what was stored for them
+pub fn positions_from_transactions<'a>(transactions: impl IntoIterator<Item = &'a Transaction>) -> Vec<Position> {
+    let mut held: HashMap<(&str, &str), Position> = HashMap::new();
+
+    for tx in transactions {
+        let position = held
+            .entry((tx.trader_address.as_str(), tx.token_id.as_str()))
+            .or_insert_with(|| Position {
+                trader_address: tx.trader_address.clone(),
+                token_id: tx.token_id.clone(),
+                market_id: tx.market_id.clone(),
+                side: tx.side.clone(),
+                shares_held: 0.0,
+                avg_entry_price: 0.0,
+                first_entry_block: Some(tx.block_number).filter(|block| *block > 0),
+                collateral: tx.collateral,
+            });
+
+        if tx.removes_shares() {
+            position.shares_held = (position.shares_held - tx.shares).max(0.0);
+        } else if tx.shares > 0.0 {
+            let cost = position.shares_held * position.avg_entry_price + tx.usdc_amount;
+            position.shares_held += tx.shares;
+            position.avg_entry_price = cost / position.shares_held;
+        }
+    }
+
+    let mut positions: Vec<Position> = held.into_values().collect();
+    positions.sort_by(|a, b| (&a.market_id, &a.trader_address, &a.token_id).cmp(&(&b.market_id, &b.trader_address, &b.token_id)));
+    positions
+}
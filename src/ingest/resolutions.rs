@@ -0,0 +1,47 @@
+use crate::standard_data::models::{Market, MarketResolution, Transaction};
+use std::collections::HashSet;
+
+// work out how a closed market resolved from its final prices
+// gamma settles the winning outcome at 1 and the loser at 0
+pub fn resolution_from_market(market: &Market, resolution_block: u64) -> Option<MarketResolution> {
+    if !market.closed || market.outcome_prices.len() != 2 {
+        return None;
+    }
+
+    let yes_price: f64 = market.outcome_prices[0].parse().ok()?;
+    let no_price: f64 = market.outcome_prices[1].parse().ok()?;
+
+    let outcome = if yes_price == 1.0 && no_price == 0.0 {
+        "YES"
+    } else if yes_price == 0.0 && no_price == 1.0 {
+        "NO"
+    } else {
+        // closed but not settled yet, or voided 50/50
+        return None;
+    };
+
+    Some(MarketResolution {
+        condition_id: market.condition_id.clone(),
+        outcome: outcome.to_string(),
+        resolution_block,
+        yes_token_id: market.yes_token_id.clone(),
+        no_token_id: market.no_token_id.clone(),
+    })
+}
+
+// markets traded locally that don't have a resolution yet
+pub fn unresolved_market_ids(transactions: &[Transaction], resolutions: &[MarketResolution]) -> Vec<String> {
+    let resolved: HashSet<&str> = resolutions.iter().map(|r| r.condition_id.as_str()).collect();
+
+    let mut market_ids: Vec<String> = transactions
+        .iter()
+        .map(|tx| tx.market_id.as_str())
+        .filter(|id| !resolved.contains(id))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    market_ids.sort();
+    market_ids
+}
@@ -0,0 +1,287 @@
+use crate::standard_data::models::{MarketResolution, MarketTag, Trader, TraderCategoryStats, TraderLedger, Transaction};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// z score for a 95% interval
+pub const ACCURACY_Z: f64 = 1.96;
+
+// every ledger by (trader, market), sorted so one trader's markets sit together
+pub type Ledgers = BTreeMap<(String, String), TraderLedger>;
+
+impl TraderLedger {
+    fn apply(&mut self, tx: &Transaction) {
+        self.first_block = Some(self.first_block.map_or(tx.block_number, |b| b.min(tx.block_number)));
+
+        let signed_shares = if tx.removes_shares() {
+            self.proceeds += tx.usdc_amount;
+            -tx.shares
+        } else {
+            self.invested += tx.usdc_amount;
+            tx.shares
+        };
+
+        if tx.side.eq_ignore_ascii_case("YES") {
+            self.yes_shares += signed_shares;
+        } else {
+            self.no_shares += signed_shares;
+        }
+    }
+
+    // sell proceeds plus winning shares redeemed at $1
+    fn returned(&self, outcome: &str) -> f64 {
+        let winning_shares = if outcome.eq_ignore_ascii_case("YES") {
+            self.yes_shares
+        } else {
+            self.no_shares
+        };
+
+        self.proceeds + winning_shares.max(0.0)
+    }
+}
+
+// add transactions to the ledgers, each one only once or it's counted twice
+pub fn fold_transactions(ledgers: &mut Ledgers, transactions: &[Transaction]) {
+    for tx in transactions {
+        ledgers
+            .entry((tx.trader_address.clone(), tx.market_id.clone()))
+            .or_insert_with(|| TraderLedger {
+                trader_address: tx.trader_address.clone(),
+                market_id: tx.market_id.clone(),
+                ..Default::default()
+            })
+            .apply(tx);
+    }
+}
+
+// lower bound of the wilson score interval on wins / resolved
+// a 6 for 6 trader lands around 61% instead of 100%, no markets gives 0
+pub fn wilson_lower_bound(wins: u32, resolved: u32) -> f64 {
+    if resolved == 0 {
+        return 0.0;
+    }
+    let n = resolved as f64;
+    let p = wins as f64 / n;
+    let z2 = ACCURACY_Z * ACCURACY_Z;
+
+    let center = p + z2 / (2.0 * n);
+    let margin = ACCURACY_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - margin) / (1.0 + z2 / n)).max(0.0)
+}
+
+// streaks, drawdown and volatility over one trader's resolved markets
+#[derive(Debug, Default)]
+struct RiskStats {
+    win_streak: u32,
+    loss_streak: u32,
+    max_drawdown: f64,
+    volatility: f64,
+}
+
+impl RiskStats {
+    // results are (resolution block, market, invested, returned), put in resolution order here
+    // markets resolving in the same block go by condition id so the duckdb backend agrees
+    fn from_results(results: &mut [(u64, &str, f64, f64)]) -> Self {
+        results.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+        let mut stats = RiskStats::default();
+        let (mut wins, mut losses) = (0, 0);
+        let (mut cumulative, mut peak) = (0.0, 0.0_f64);
+        for (_, _, invested, returned) in results.iter() {
+            if returned > invested {
+                wins += 1;
+                losses = 0;
+            } else {
+                losses += 1;
+                wins = 0;
+            }
+            stats.win_streak = stats.win_streak.max(wins);
+            stats.loss_streak = stats.loss_streak.max(losses);
+
+            cumulative += returned - invested;
+            peak = peak.max(cumulative);
+            stats.max_drawdown = stats.max_drawdown.max(peak - cumulative);
+        }
+
+        // markets with nothing bought have no roi to speak of
+        let rois: Vec<f64> = results
+            .iter()
+            .filter(|(_, _, invested, _)| *invested > 0.0)
+            .map(|(_, _, invested, returned)| (returned - invested) / invested)
+            .collect();
+        if !rois.is_empty() {
+            let mean = rois.iter().sum::<f64>() / rois.len() as f64;
+            stats.volatility = (rois.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / rois.len() as f64).sqrt();
+        }
+
+        stats
+    }
+}
+
+// rebuild trader stats from the raw transaction log and resolutions, the traders.parquet columns are
+// - total_markets_entered: markets with at least one transaction, resolved or not
+// - total_markets_resolved: entered markets with a resolution, everything below is over these only
+// - total_invested: usdc spent on buys, either outcome
+// - total_returned: usdc from sells plus net winning shares held at resolution, redeemed at $1
+// - total_wins: markets where returned beats invested, so a hedged or sold early market can win too
+// - accuracy: wins / resolved, adjusted_accuracy its wilson lower bound at ACCURACY_Z
+// - roi: (returned - invested) / invested, 0 with nothing invested
+// - first_activity_block: earliest transaction block in any market
+// - streaks and max_drawdown over the per market results in resolution block order, return_volatility
+//   the standard deviation of per market roi
+// the duckdb backend computes the same thing in sql
+pub fn compute_trader_stats(transactions: &[Transaction], resolutions: &[MarketResolution]) -> Vec<Trader> {
+    let mut ledgers = Ledgers::new();
+    fold_transactions(&mut ledgers, transactions);
+    traders_from_ledgers(ledgers.values(), resolutions)
+}
+
+// the same stats from ledgers already folded, a pass over the ledgers instead of every transaction
+pub fn traders_from_ledgers<'a>(ledgers: impl IntoIterator<Item = &'a TraderLedger>, resolutions: &[MarketResolution]) -> Vec<Trader> {
+    let outcomes: Outcomes = resolutions
+        .iter()
+        .map(|r| (r.condition_id.as_str(), (r.outcome.as_str(), r.resolution_block)))
+        .collect();
+    traders_from_outcomes(ledgers, &outcomes)
+}
+
+// winning outcome and resolution block by market, kept by callers that learn resolutions one at a time
+pub type Outcomes<'a> = HashMap<&'a str, (&'a str, u64)>;
+
+// same as traders_from_ledgers with the resolution lookup already built
+pub fn traders_from_outcomes<'a>(ledgers: impl IntoIterator<Item = &'a TraderLedger>, outcomes: &Outcomes) -> Vec<Trader> {
+    // sorted by address so rebuilt tables are stable between runs
+    let mut by_trader: BTreeMap<&str, Vec<&TraderLedger>> = BTreeMap::new();
+    for ledger in ledgers {
+        by_trader.entry(ledger.trader_address.as_str()).or_default().push(ledger);
+    }
+
+    by_trader
+        .into_iter()
+        .map(|(address, markets)| {
+            let mut resolved = 0;
+            let mut wins = 0;
+            let mut invested = 0.0;
+            let mut returned = 0.0;
+            // (resolution block, market, invested, returned) for the risk stats
+            let mut results = Vec::new();
+
+            for ledger in &markets {
+                let market_id = ledger.market_id.as_str();
+                let Some((outcome, resolution_block)) = outcomes.get(market_id) else {
+                    continue;
+                };
+
+                let market_returned = ledger.returned(outcome);
+                resolved += 1;
+                if market_returned > ledger.invested {
+                    wins += 1;
+                }
+                invested += ledger.invested;
+                returned += market_returned;
+                results.push((*resolution_block, market_id, ledger.invested, market_returned));
+            }
+            let risk = RiskStats::from_results(&mut results);
+
+            Trader {
+                trader_address: address.to_string(),
+                total_markets_entered: markets.len() as u32,
+                total_markets_resolved: resolved,
+                total_wins: wins,
+                accuracy: if resolved > 0 { wins as f64 / resolved as f64 } else { 0.0 },
+                adjusted_accuracy: wilson_lower_bound(wins, resolved),
+                total_invested: invested,
+                total_returned: returned,
+                roi: if invested > 0.0 { (returned - invested) / invested } else { 0.0 },
+                first_activity_block: markets.iter().filter_map(|l| l.first_block).min(),
+                longest_win_streak: Some(risk.win_streak),
+                longest_loss_streak: Some(risk.loss_streak),
+                max_drawdown: Some(risk.max_drawdown),
+                return_volatility: Some(risk.volatility),
+                rewards: None,
+            }
+        })
+        .collect()
+}
+
+// one trader standing for several wallets of the same person
+// a market two wallets both traded counts twice, the weighting accuracy is the members' averaged by
+// resolved markets so category weighting done on them carries over, streaks and risk take the largest member's
+pub fn combine_traders(address: &str, traders: &[&Trader]) -> Option<Trader> {
+    let (first, rest) = traders.split_first()?;
+    if rest.is_empty() {
+        return Some(Trader { trader_address: address.to_string(), ..(*first).clone() });
+    }
+
+    let resolved: u32 = traders.iter().map(|t| t.total_markets_resolved).sum();
+    let wins: u32 = traders.iter().map(|t| t.total_wins).sum();
+    let invested: f64 = traders.iter().map(|t| t.total_invested).sum();
+    let returned: f64 = traders.iter().map(|t| t.total_returned).sum();
+    let weighted: f64 = traders.iter().map(|t| t.adjusted_accuracy * t.total_markets_resolved as f64).sum();
+    // missing on any member means unknown for the group
+    let largest = |stat: fn(&Trader) -> Option<f64>| {
+        traders.iter().map(|t| stat(t)).collect::<Option<Vec<f64>>>().map(|v| v.into_iter().fold(0.0, f64::max))
+    };
+
+    Some(Trader {
+        trader_address: address.to_string(),
+        total_markets_entered: traders.iter().map(|t| t.total_markets_entered).sum(),
+        total_markets_resolved: resolved,
+        total_wins: wins,
+        accuracy: if resolved > 0 { wins as f64 / resolved as f64 } else { 0.0 },
+        adjusted_accuracy: if resolved > 0 { weighted / resolved as f64 } else { 0.0 },
+        total_invested: invested,
+        total_returned: returned,
+        // members' roi weighted by what they put in, so rewards carry over when roi counts them
+        roi: if invested > 0.0 { traders.iter().map(|t| t.roi * t.total_invested).sum::<f64>() / invested } else { 0.0 },
+        first_activity_block: traders.iter().filter_map(|t| t.first_activity_block).min(),
+        longest_win_streak: largest(|t| t.longest_win_streak.map(f64::from)).map(|v| v as u32),
+        longest_loss_streak: largest(|t| t.longest_loss_streak.map(f64::from)).map(|v| v as u32),
+        max_drawdown: largest(|t| t.max_drawdown),
+        return_volatility: largest(|t| t.return_volatility),
+        rewards: traders.iter().filter_map(|t| t.rewards).reduce(|a, b| a + b),
+    })
+}
+
+// the same stats split by market tag, traders with nothing resolved in a tag are left out
+// a market with several tags counts toward each of them
+pub fn compute_category_stats(
+    transactions: &[Transaction],
+    resolutions: &[MarketResolution],
+    tags: &[MarketTag],
+) -> Vec<TraderCategoryStats> {
+    let mut ledgers = Ledgers::new();
+    fold_transactions(&mut ledgers, transactions);
+    category_stats_from_ledgers(&ledgers, resolutions, tags)
+}
+
+pub fn category_stats_from_ledgers(ledgers: &Ledgers, resolutions: &[MarketResolution], tags: &[MarketTag]) -> Vec<TraderCategoryStats> {
+    let mut markets_by_tag: BTreeMap<&str, HashSet<&str>> = BTreeMap::new();
+    for tag in tags {
+        markets_by_tag.entry(tag.tag.as_str()).or_default().insert(tag.condition_id.as_str());
+    }
+
+    let mut stats = Vec::new();
+    for (category, markets) in markets_by_tag {
+        let category_resolutions: Vec<MarketResolution> = resolutions.iter().filter(|r| markets.contains(r.condition_id.as_str())).cloned().collect();
+        if category_resolutions.is_empty() {
+            continue;
+        }
+
+        let category_ledgers = ledgers.values().filter(|l| markets.contains(l.market_id.as_str()));
+        stats.extend(traders_from_ledgers(category_ledgers, &category_resolutions)
+            .into_iter()
+            .filter(|t| t.total_markets_resolved > 0)
+            .map(|t| TraderCategoryStats {
+                trader_address: t.trader_address,
+                category: category.to_string(),
+                total_markets_resolved: t.total_markets_resolved,
+                total_wins: t.total_wins,
+                accuracy: t.accuracy,
+                adjusted_accuracy: t.adjusted_accuracy,
+                total_invested: t.total_invested,
+                total_returned: t.total_returned,
+                roi: t.roi,
+            }));
+    }
+
+    stats
+}
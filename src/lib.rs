@@ -0,0 +1,20 @@
+pub mod cli;
+pub mod standard_data;
+pub mod adapters;
+pub mod data_sources;
+pub mod analysis;
+pub mod ingest;
+pub mod error;
+pub mod cancel;
+pub mod clock;
+pub mod workers;
+pub mod config;
+pub mod paths;
+pub mod watchlist;
+pub mod address_book;
+pub mod paper;
+pub mod state_db;
+#[cfg(feature = "trading")]
+pub mod trading;
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -1,17 +1,64 @@
-mod cli;
-mod standard_data;
-mod adapters;
-mod data_sources;
-
 use clap::Parser;
-use cli::{CLI, handle_analyze};
-use adapters::HttpClient;
-use data_sources::{PolymarketApiSource, LocalDbSource};
+use polymarket_explorer::cli::{Cli, CliCommand, HttpArgs, OutputFormat, Source, TlsVersion, dispatch, dispatch_local, output};
+use polymarket_explorer::cli::format::{self, DisplayFormat, DisplayTz, NumberLocale};
+use polymarket_explorer::{clock, workers};
+use chrono::DateTime;
+use std::time::Duration;
+use polymarket_explorer::adapters::{api_urls, BlockIndex, CtfEventReader, FundingTracer, HttpClient, NameResolver, OddsApiClient, RawCapture};
+use polymarket_explorer::standard_data::providers::ExternalOddsProvider;
+use polymarket_explorer::config::Config;
+use polymarket_explorer::cancel::Cancellation;
+use polymarket_explorer::error::{AppError, CancelError};
+use polymarket_explorer::data_sources::{PolymarketApiSource, LocalDbSource, MockSource};
 
 #[tokio::main]
 async fn main() {
+    // parse
+    let cli = Cli::parse();
+    let output_format = cli.output;
+    let deterministic = cli.deterministic || cli.seed.is_some();
+    if deterministic {
+        clock::fix(DateTime::from_timestamp(clock::DETERMINISTIC_NOW, 0).unwrap_or_default());
+    }
+    workers::set_concurrency(cli.concurrency);
+    format::init(DisplayFormat {
+        compact: cli.compact_numbers,
+        price: cli.price_format,
+        // the machine's zone would leak into the output
+        tz: match cli.tz {
+            DisplayTz::Local if deterministic => DisplayTz::Named(chrono_tz::UTC),
+            tz => tz,
+        },
+        locale: match cli.locale.as_deref() {
+            Some(locale) => NumberLocale::parse(locale),
+            None if deterministic => NumberLocale::default(),
+            None => NumberLocale::from_env(),
+        },
+    });
+
+    // ctrl-c and --timeout stop the run, providers give up on what they have in flight
+    // commands with nothing in flight are dropped once the grace period is over
+    let cancellation = Cancellation::new();
+    cancellation.watch(cli.timeout.map(Duration::from_secs));
+    let result = tokio::select! {
+        result = run(cli, &cancellation) => result,
+        reason = cancellation.abandoned() => Err(AppError::from(reason).into()),
+    };
+
     // run and parse slug or error
-    if let Err(e) = run().await {
+    if let Err(e) = result {
+        // 130 like a shell after ctrl-c, 124 like timeout(1)
+        let code = match e.downcast_ref::<AppError>() {
+            Some(AppError::Cancelled(CancelError::Interrupted)) => 130,
+            Some(AppError::Cancelled(CancelError::TimedOut { .. })) => 124,
+            _ => 1,
+        };
+
+        if output_format == OutputFormat::Json {
+            output::print_error_json(&e);
+            std::process::exit(code);
+        }
+
         eprintln!("Error: {}", e);
         
         // Print error chain
@@ -21,30 +68,143 @@ async fn main() {
                 eprintln!("  {}: {}", i, err);
             }
         }
+
+        // typed errors know what the user can do about them
+        if let Some(hint) = e.downcast_ref::<AppError>().and_then(AppError::hint) {
+            eprintln!("\nTip: {}", hint);
+        }
         
-        std::process::exit(1);
+        std::process::exit(code);
     }
 }
 
-async fn run() -> anyhow::Result<()> {
-    // parse
-    let cli = CLI::parse();
+async fn run(cli: Cli, cancellation: &Cancellation) -> anyhow::Result<()> {
+    // refused up front instead of printing terminal text where a program expects arrow
+    if cli.output == OutputFormat::Arrow && !cli.command.streams_arrow() {
+        anyhow::bail!("--output arrow only works with analyze, use --output json for other commands");
+    }
+
+    // local bookkeeping, no need to open any source
+    let command = match cli.command {
+        CliCommand::Local(command) => return dispatch_local(command),
+        CliCommand::Source(command) => command,
+    };
+
+    // config.toml read once for every handler, smart money definition with the --smart-* flags on top
+    let mut config = Config::load()?;
+    config.smart_money = cli.smart_money.apply(config.smart_money);
+    // api base urls the same way, a bad one fails here before any request
+    config.api = cli.api.apply(config.api).validated()?;
+
+    // one client for the whole run, every source and sink shares its connection pool
+    let http_client = build_http_client(&cli.http, cancellation)?;
+
+    match cli.source {
+        Source::Live => {
+            let request_stats = http_client.stats();
+
+            // usernames are looked up on the same client so they show up in --stats
+            let name_resolver = (cli.resolve_names || cli.ens_rpc.is_some()).then(|| {
+                let resolver = NameResolver::new(http_client.clone()).with_gamma_url(&config.api.gamma);
+                match &cli.ens_rpc {
+                    Some(rpc) => resolver.with_ens_rpc(rpc),
+                    None => resolver,
+                }
+            });
+
+            // make polymarket api source
+            let market_provider = PolymarketApiSource::new(http_client.clone()).with_urls(config.api.clone());
 
-    // create http cleint
-    let http_client = HttpClient::new();
+            // orders only need gamma and the clob, not the local db
+            #[cfg(feature = "trading")]
+            if let polymarket_explorer::cli::Command::Trade { action, i_understand_the_risks } = command {
+                return polymarket_explorer::cli::handle_trade(action, i_understand_the_risks, &market_provider, http_client, &config.api.clob, &config.fees).await;
+            }
+
+            // local db source
+            let mut local_db = LocalDbSource::with_backend(&cli.data_dir, cli.backend)
+                .with_cancellation(cancellation.clone())
+                .with_rewards(config.rewards)
+                .with_collateral(config.collateral);
+            if let Some(rpc) = &cli.polygon_rpc {
+                local_db = local_db.with_block_index(BlockIndex::new(http_client.clone(), rpc));
+            }
+            // the same node traces where top holders got their usdc
+            let funding_tracer = cli.polygon_rpc.as_ref().map(|rpc| FundingTracer::new(http_client.clone(), rpc));
+            // and reads splits, merges and redemptions for ingest ctf
+            let ctf_reader = cli.polygon_rpc.as_ref().map(|rpc| CtfEventReader::new(http_client.clone(), rpc));
+            // bookmaker lines for parity, only with a key
+            let odds_client = match &cli.odds.odds_api_key {
+                Some(key) => Some(
+                    OddsApiClient::new(http_client.clone(), key)
+                        .with_url(api_urls::validate("odds", &cli.odds.odds_api_url)?)
+                        .with_regions(&cli.odds.odds_regions),
+                ),
+                None => None,
+            };
+            let capabilities = local_db.capabilities();
+            if capabilities.local_db() || !command.runs_without_local_db() {
+                local_db.validate_schema()?;
+            } else {
+                // run what gamma alone can answer instead of failing the whole command
+                local_db.validate_present_tables()?;
+                eprintln!("Warning: local data missing in {}, trader and position sections are unavailable", cli.data_dir);
+            }
+
+            // run
+            let result = dispatch(command, cli.output, &market_provider, &local_db, &capabilities, name_resolver.as_ref(), funding_tracer.as_ref(), ctf_reader.as_ref(), odds_client.as_ref().map(|client| client as &dyn ExternalOddsProvider), &http_client, &config).await;
+
+            // print even when the run failed, that's when rate limits matter most
+            if cli.stats {
+                output::print_request_stats(&request_stats.snapshot(), cli.output);
+            }
+            // api drift is worth a look before it turns into decode errors
+            let drift = market_provider.schema_drift();
+            if !drift.is_empty() {
+                output::print_schema_drift(&drift);
+            }
+
+            result
+        }
+        Source::Mock => {
+            // offline data for demos, serves both market metadata and the db side
+            let mock = cli.seed.map_or_else(MockSource::new, MockSource::with_seed);
+            // mock addresses have no profiles to look up
+            dispatch(command, cli.output, &mock, &mock, &mock.capabilities(), None, None, None, None, &http_client, &config).await
+        }
+    }
+}
 
-    // make polymarket api source
-    let market_provider = PolymarketApiSource::new(http_client);
+// http client from the cli flags
+fn build_http_client(args: &HttpArgs, cancellation: &Cancellation) -> anyhow::Result<HttpClient> {
+    let mut builder = HttpClient::builder()
+        .cancellation(cancellation.clone())
+        .connect_timeout(Duration::from_secs(args.connect_timeout))
+        .timeout(Duration::from_secs(args.request_timeout))
+        .accept_invalid_certs(args.insecure)
+        .pool_idle_timeout(Duration::from_secs(args.pool_idle_timeout))
+        .http1_only(args.http1_only)
+        .max_response_bytes(args.max_response_mb.saturating_mul(1024 * 1024))
+        .retries(args.retries);
 
-    // local db source
-    let local_db = LocalDbSource::new("/Users/hosungkim/data/poly/processed_data");
+    if let Some(proxy) = &args.proxy {
+        builder = builder.proxy(proxy);
+    }
+    if let Some(user_agent) = &args.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(version) = args.min_tls {
+        builder = builder.min_tls_version(match version {
+            TlsVersion::Tls12 => reqwest::tls::Version::TLS_1_2,
+            TlsVersion::Tls13 => reqwest::tls::Version::TLS_1_3,
+        });
+    }
+    if let Some(dir) = &args.dump_raw {
+        builder = builder.raw_capture(RawCapture::record(dir)?);
+    }
+    if let Some(dir) = &args.replay_raw {
+        builder = builder.raw_capture(RawCapture::replay(dir)?);
+    }
 
-    // run
-    handle_analyze(
-            &cli.market_slug,
-            &market_provider,
-            &local_db, // trader stats provider
-            &local_db, // position provider
-    ).await
-    
+    Ok(builder.build()?)
 }
@@ -0,0 +1,193 @@
+use crate::analysis::fees::FeeModel;
+use crate::analysis::order_plan::Outcome;
+use crate::error::Result;
+use crate::standard_data::models::{Market, OrderBook};
+use crate::state_db::StateDb;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// usdc a fresh paper account starts with
+pub const DEFAULT_PAPER_CASH: f64 = 1000.0;
+// anything below this is dust left over from float math, not a position
+const MIN_SHARES: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillSide {
+    Buy,
+    Sell,
+}
+
+// one simulated fill, taken from the live book at the time it was recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperFill {
+    pub timestamp: i64,
+    pub market_slug: String,
+    pub condition_id: String,
+    pub question: String,
+    pub outcome: Outcome,
+    pub side: FillSide,
+    pub shares: f64,
+    // average book price per share, before fees
+    pub price: f64,
+    pub fees: f64,
+}
+
+impl PaperFill {
+    // usdc into the account, negative for buys
+    pub fn cash_flow(&self) -> f64 {
+        match self.side {
+            FillSide::Buy => -(self.shares * self.price + self.fees),
+            FillSide::Sell => self.shares * self.price - self.fees,
+        }
+    }
+}
+
+// portfolio value at one point in time, one is taken every time the portfolio is valued
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PaperSnapshot {
+    pub timestamp: i64,
+    pub cash: f64,
+    pub positions_value: f64,
+}
+
+impl PaperSnapshot {
+    pub fn equity(&self) -> f64 {
+        self.cash + self.positions_value
+    }
+}
+
+// shares held in one outcome, cost basis is the average cost of what's still held
+#[derive(Debug, Clone, Serialize)]
+pub struct PaperPosition {
+    pub market_slug: String,
+    pub condition_id: String,
+    pub question: String,
+    pub outcome: Outcome,
+    pub shares: f64,
+    pub cost: f64,
+    pub realized_pnl: f64,
+}
+
+impl PaperPosition {
+    pub fn is_open(&self) -> bool {
+        self.shares > MIN_SHARES
+    }
+}
+
+// simulated account kept in the state db
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperLedger {
+    pub starting_cash: f64,
+    #[serde(default)]
+    pub fills: Vec<PaperFill>,
+    #[serde(default)]
+    pub snapshots: Vec<PaperSnapshot>,
+}
+
+impl Default for PaperLedger {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAPER_CASH)
+    }
+}
+
+impl PaperLedger {
+    pub fn new(starting_cash: f64) -> Self {
+        Self {
+            starting_cash,
+            fills: Vec::new(),
+            snapshots: Vec::new(),
+        }
+    }
+
+    // from the state db, a fresh account until the first fill or reset
+    pub fn load() -> Result<Self> {
+        StateDb::open()?.paper_ledger()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        StateDb::open()?.save_paper_ledger(self)
+    }
+
+    pub fn cash(&self) -> f64 {
+        self.starting_cash + self.fills.iter().map(PaperFill::cash_flow).sum::<f64>()
+    }
+
+    // replay the fills, sells take cost out at the average cost so far
+    pub fn positions(&self) -> Vec<PaperPosition> {
+        let mut positions: BTreeMap<(String, &'static str), PaperPosition> = BTreeMap::new();
+
+        for fill in &self.fills {
+            let position = positions
+                .entry((fill.condition_id.clone(), fill.outcome.as_str()))
+                .or_insert_with(|| PaperPosition {
+                    market_slug: fill.market_slug.clone(),
+                    condition_id: fill.condition_id.clone(),
+                    question: fill.question.clone(),
+                    outcome: fill.outcome,
+                    shares: 0.0,
+                    cost: 0.0,
+                    realized_pnl: 0.0,
+                });
+
+            match fill.side {
+                FillSide::Buy => {
+                    position.shares += fill.shares;
+                    position.cost += fill.shares * fill.price + fill.fees;
+                }
+                FillSide::Sell => {
+                    let avg_cost = if position.shares > 0.0 { position.cost / position.shares } else { 0.0 };
+                    position.realized_pnl += fill.cash_flow() - avg_cost * fill.shares;
+                    position.cost -= avg_cost * fill.shares;
+                    position.shares -= fill.shares;
+                }
+            }
+        }
+
+        positions.into_values().collect()
+    }
+
+    pub fn shares_held(&self, condition_id: &str, outcome: Outcome) -> f64 {
+        self.positions()
+            .iter()
+            .find(|p| p.condition_id == condition_id && p.outcome == outcome)
+            .map_or(0.0, |p| p.shares)
+    }
+}
+
+// what one share of the outcome is worth right now, settled price once resolved, else what the bid pays
+// gamma only quotes the yes book, a no share sells at one minus the yes ask
+pub fn mark_price(market: &Market, outcome: Outcome) -> Option<f64> {
+    if market.closed {
+        let index = match outcome {
+            Outcome::Yes => 0,
+            Outcome::No => 1,
+        };
+        return market.outcome_prices.get(index)?.parse().ok();
+    }
+
+    match outcome {
+        Outcome::Yes => Some(market.bid_price),
+        Outcome::No => Some(1.0 - market.ask_price),
+    }
+}
+
+// walk the bids down to the min price, returns shares sold, average price and fees
+pub fn simulate_sell(book: &OrderBook, shares: f64, min_price: f64, fees: &FeeModel) -> (f64, Option<f64>, f64) {
+    let mut remaining = shares;
+    let mut sold = 0.0;
+    let mut notional = 0.0;
+
+    for level in book.bids.iter().take_while(|level| level.price >= min_price) {
+        if remaining <= 0.0 {
+            break;
+        }
+        let take = level.size.min(remaining);
+        sold += take;
+        notional += take * level.price;
+        remaining -= take;
+    }
+
+    let avg_price = (sold > 0.0).then(|| notional / sold);
+    (sold, avg_price, fees.taker_fee(notional))
+}
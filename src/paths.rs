@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+const APP_DIR: &str = "polymarket-explorer";
+
+// where files live, following each platform's conventions
+// linux: $XDG_CONFIG_HOME, $XDG_CACHE_HOME and $XDG_DATA_HOME, falling back to ~/.config, ~/.cache and ~/.local/share
+// macos: ~/Library/Application Support for config and data, ~/Library/Caches for the cache
+// windows: %APPDATA% for config and data, %LOCALAPPDATA% for the cache
+
+// files the user edits by hand, config.toml
+pub fn config_file(name: &str) -> PathBuf {
+    locate(dirs::config_dir(), name)
+}
+
+// lookups that can be rebuilt from the network, block times and ens names
+pub fn cache_file(name: &str) -> PathBuf {
+    locate(dirs::cache_dir(), name)
+}
+
+// state the tool keeps between runs, the state db
+pub fn data_file(name: &str) -> PathBuf {
+    locate(dirs::data_dir(), name)
+}
+
+// default for --data-dir, the processed parquet tables
+pub fn default_data_dir() -> String {
+    app_dir(dirs::data_dir()).join("processed_data").to_string_lossy().to_string()
+}
+
+// everything used to go in ~/.polymarket-explorer, a file still there keeps being used until one exists in the new place
+fn locate(base: Option<PathBuf>, name: &str) -> PathBuf {
+    let path = app_dir(base).join(name);
+    let legacy = legacy_dir().join(name);
+    if !path.exists() && legacy.exists() {
+        return legacy;
+    }
+    path
+}
+
+// the legacy dir when the platform has no such directory
+fn app_dir(base: Option<PathBuf>) -> PathBuf {
+    base.map_or_else(legacy_dir, |base| base.join(APP_DIR))
+}
+
+fn legacy_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_default().join(".polymarket-explorer")
+}
@@ -1,4 +1,2 @@
 pub mod models;
 pub mod providers;
-
-pub use models::*;
@@ -1,11 +1,13 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 
 /**
 * GAMMA API MODELS
 */
 // market group responce from slug that will give all related market events
 // voume and liquidity are total I think from all of its sub markets
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MarketGroup {
     pub slug: String,
     pub title: String,
@@ -17,7 +19,7 @@ pub struct MarketGroup {
 }
 
 // individual market from the group
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Market {
     pub question: String,
     pub condition_id: String,
@@ -28,6 +30,9 @@ pub struct Market {
     pub no_token_id: String,
     pub active: bool,
     pub closed: bool,
+    // hidden by gamma, still served by slug and condition id
+    #[serde(default)]
+    pub archived: bool,
     pub volume: f64,
     pub volume_24h: f64,
     pub volume_1w: f64,
@@ -39,6 +44,36 @@ pub struct Market {
     pub last_trade_price: f64,
     pub bid_price: f64,
     pub ask_price: f64,
+    // yes price change over the last day, gamma leaves it out for some markets
+    pub price_change_24h: Option<f64>,
+    // when the market was listed
+    pub created_at: Option<DateTime<Utc>>,
+    // when the market is scheduled to resolve
+    pub end_date: Option<DateTime<Utc>>,
+    // when gamma closed the market, None while it's open and on some older markets
+    #[serde(default)]
+    pub closed_time: Option<DateTime<Utc>>,
+    pub resolution_source: Option<String>,
+    // lowercase tag slugs like "politics", markets without their own take the event's
+    pub tags: Vec<String>,
+}
+
+/*
+* CLOB MODELS
+*/
+// one price level of an order book
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+// resting orders for one outcome token, bids best (highest) first, asks best (lowest) first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub token_id: String,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
 }
 
 /*
@@ -46,16 +81,105 @@ pub struct Market {
 */
 
 // trader performace stats
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Trader {
     pub trader_address: String,
     pub total_markets_entered: u32,
     pub total_markets_resolved: u32,
     pub total_wins: u32,
     pub accuracy: f64,
+    // 95% wilson lower bound on accuracy, derived from wins and resolved markets
+    pub adjusted_accuracy: f64,
     pub total_invested: f64,
     pub total_returned: f64,
     pub roi: f64,
+    // earliest block this wallet traded in, older dumps don't have it
+    pub first_activity_block: Option<u64>,
+    // longest runs of won and lost markets in resolution order, older dumps don't have these either
+    pub longest_win_streak: Option<u32>,
+    pub longest_loss_streak: Option<u32>,
+    // biggest peak to trough drop of cumulative pnl over resolved markets, in usdc
+    pub max_drawdown: Option<f64>,
+    // standard deviation of per market roi over resolved markets
+    pub return_volatility: Option<f64>,
+    // liquidity rewards paid to the wallet, None when none were ingested
+    // never part of total_returned, roi counts them only with [rewards] include = true
+    #[serde(default)]
+    pub rewards: Option<f64>,
+}
+
+impl Trader {
+    // what trading alone returned on what was put in
+    pub fn trading_roi(&self) -> f64 {
+        if self.total_invested > 0.0 { (self.total_returned - self.total_invested) / self.total_invested } else { 0.0 }
+    }
+
+    // same with the rewards counted as returns
+    pub fn roi_with_rewards(&self) -> f64 {
+        let returned = self.total_returned + self.rewards.unwrap_or_default();
+        if self.total_invested > 0.0 { (returned - self.total_invested) / self.total_invested } else { 0.0 }
+    }
+}
+
+// one trader's record in markets carrying a single tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraderCategoryStats {
+    pub trader_address: String,
+    pub category: String,
+    pub total_markets_resolved: u32,
+    pub total_wins: u32,
+    pub accuracy: f64,
+    // 95% wilson lower bound on accuracy, derived from wins and resolved markets
+    pub adjusted_accuracy: f64,
+    pub total_invested: f64,
+    pub total_returned: f64,
+    pub roi: f64,
+}
+
+// one trader's running totals in one market, stored so stats rebuilds only fold in new transactions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraderLedger {
+    pub trader_address: String,
+    pub market_id: String,
+    // usdc spent on buys and got from sells
+    pub invested: f64,
+    pub proceeds: f64,
+    // net shares of each outcome, sells take them off
+    pub yes_shares: f64,
+    pub no_shares: f64,
+    pub first_block: Option<u64>,
+}
+
+// every trader's stats as a stats rebuild left them, kept so later runs can ask what was known back then
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TraderSnapshot {
+    // the last block the stats had seen, transactions and resolutions alike
+    pub block: u64,
+    pub traders: Vec<Trader>,
+}
+
+// a liquidity reward paid out to a wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardPayout {
+    pub trader_address: String,
+    // the market the reward was earned in, None when the payout doesn't say
+    pub market_id: Option<String>,
+    pub usdc_amount: f64,
+    // unix seconds
+    pub timestamp: i64,
+    pub transaction_hash: String,
+}
+
+// a comment under an event, replies included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: String,
+    pub body: String,
+    // lowercase, empty when the commenter's wallet isn't shown
+    pub author_address: String,
+    // unix seconds
+    pub created_at: i64,
+    pub likes: u32,
 }
 
 // positions held by trader
@@ -68,6 +192,53 @@ pub struct Position {
     pub shares_held: f64,
     pub avg_entry_price: f64,
     pub first_entry_block: Option<u64>,
+    // avg_entry_price is in this token, reads from the local db convert it to usd
+    #[serde(default)]
+    pub collateral: Collateral,
+}
+
+// the token a trade settled in, polymarket has run on bridged usdc.e and is moving to circle's native usdc
+// rows stored before collateral was tracked are all usdc.e
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Collateral {
+    #[default]
+    UsdcE,
+    Usdc,
+}
+
+impl Collateral {
+    pub const ALL: [Collateral; 2] = [Collateral::UsdcE, Collateral::Usdc];
+
+    pub fn address(&self) -> &'static str {
+        match self {
+            Collateral::UsdcE => "0x2791bca1f2de4661ed88a30c99a7a9449aa84174",
+            Collateral::Usdc => "0x3c499c542cef5e3811e1192ce70d8cc03d5c3359",
+        }
+    }
+
+    pub fn from_address(address: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.address().eq_ignore_ascii_case(address))
+    }
+
+    // the value stored in the collateral column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Collateral::UsdcE => "usdc_e",
+            Collateral::Usdc => "usdc",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str().eq_ignore_ascii_case(value))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Collateral::UsdcE => "USDC.e",
+            Collateral::Usdc => "USDC",
+        }
+    }
 }
 
 // transaction/trade
@@ -75,13 +246,33 @@ pub struct Position {
 pub struct Transaction {
     pub block_number: u64,
     pub transaction_hash: String,
+    // index of the fill event in the transaction, only on-chain dumps carry it
+    pub log_index: Option<u32>,
     pub trader_address: String,
     pub token_id: String,
     pub side: String,  // "YES" or "NO"
-    pub action: String,  // "BUY" or "SELL"
+    // "BUY" or "SELL" for fills, "SPLIT", "MERGE" or "REDEEM" for ctf operations
+    pub action: String,
     pub shares: f64,
     pub usdc_amount: f64,
     pub market_id: String,
+    // unix seconds, only some dumps carry it
+    pub timestamp: Option<i64>,
+    // usdc_amount is in this token, reads from the local db convert it to usd
+    #[serde(default)]
+    pub collateral: Collateral,
+}
+
+impl Transaction {
+    // an order book fill, the ctf operations trade at fixed prices against the contract
+    pub fn is_fill(&self) -> bool {
+        self.action.eq_ignore_ascii_case("BUY") || self.action.eq_ignore_ascii_case("SELL")
+    }
+
+    // sells, merges and redemptions give shares up, buys and splits take them on
+    pub fn removes_shares(&self) -> bool {
+        ["SELL", "MERGE", "REDEEM"].iter().any(|action| self.action.eq_ignore_ascii_case(action))
+    }
 }
 
 // resolved market
@@ -93,3 +284,47 @@ pub struct MarketResolution {
     pub yes_token_id: String,
     pub no_token_id: String,
 }
+
+// one tag of a market, markets with several tags get one row each
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketTag {
+    pub condition_id: String,
+    pub tag: String,
+}
+
+/*
+* EXTERNAL ODDS MODELS
+*/
+// an event as a sportsbook or other betting site lists it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalEvent {
+    pub id: String,
+    // e.g. "Boston Celtics vs Los Angeles Lakers"
+    pub title: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub lines: Vec<ExternalLine>,
+}
+
+// one bookmaker's price on one outcome of an event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalLine {
+    pub bookmaker: String,
+    pub outcome: String,
+    // decimal odds, 2.0 pays back twice the stake
+    pub decimal_odds: f64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/*
+* EXTERNAL FORECAST MODELS
+*/
+// an outside model's YES probability for one market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalForecast {
+    // the model's name, e.g. "538"
+    pub source: String,
+    // condition id or market slug the forecast is for
+    pub market: String,
+    pub probability: f64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
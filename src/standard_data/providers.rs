@@ -1,11 +1,74 @@
-use crate::standard_data::models::{MarketGroup, Trader, Position, Transaction};
-use anyhow::Result;
+use crate::standard_data::models::{Comment, ExternalEvent, ExternalForecast, Market, MarketGroup, MarketTag, OrderBook, RewardPayout, Trader, TraderCategoryStats, TraderLedger, TraderSnapshot, Position, Transaction, MarketResolution};
+use crate::adapters::Compaction;
+use crate::error::{AppError, Result};
+use crate::ingest::Checkpoints;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use crate::workers;
+
+// sort order of a market listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketOrder {
+    // highest 24h volume first
+    Volume24h,
+    // most recently created first
+    Newest,
+    // closest scheduled resolution first
+    EndingSoonest,
+}
+
+// which open markets a listing returns
+#[derive(Debug, Clone)]
+pub struct MarketFilter {
+    pub order: MarketOrder,
+    pub min_liquidity: Option<f64>,
+    // only markets scheduled to resolve before this
+    pub ends_before: Option<DateTime<Utc>>,
+    // archived markets are left out unless asked for
+    pub include_archived: bool,
+    pub limit: usize,
+}
+
+// market groups fetched together, a slug that failed keeps its error instead of failing the batch
+#[derive(Debug, Default)]
+pub struct MarketGroupBatch {
+    // in the order the slugs were given
+    pub groups: Vec<MarketGroup>,
+    pub failures: Vec<(String, AppError)>,
+}
 
 // interface for market data getter
 #[async_trait]
 pub trait MarketMetadataProvider: Send + Sync {
     async fn get_market_group(&self, slug: &str) -> Result<MarketGroup>;
+
+    // several groups at once, at most --concurrency requests in flight
+    async fn get_market_groups(&self, slugs: &[String]) -> MarketGroupBatch {
+        let requests: Vec<_> = slugs.iter().map(|slug| async move { (slug, self.get_market_group(slug).await) }).collect();
+        let results: Vec<(&String, Result<MarketGroup>)> = workers::join_all(requests).await;
+
+        let mut batch = MarketGroupBatch::default();
+        for (slug, result) in results {
+            match result {
+                Ok(group) => batch.groups.push(group),
+                Err(e) => batch.failures.push((slug.clone(), e)),
+            }
+        }
+        batch
+    }
+
+    // get single markets by condition id, including closed ones
+    async fn get_markets_by_condition_ids(&self, condition_ids: &[String]) -> Result<Vec<Market>>;
+
+    // list open markets across every event
+    async fn get_active_markets(&self, filter: &MarketFilter) -> Result<Vec<Market>>;
+}
+
+// interface for live order books
+#[async_trait]
+pub trait OrderBookProvider: Send + Sync {
+    // current book of one outcome token
+    async fn get_order_book(&self, token_id: &str) -> Result<OrderBook>;
 }
 
 // interface for trader stats
@@ -16,6 +79,15 @@ pub trait TraderStatsProvider: Send + Sync {
 
     // Get position data for traders by address
     async fn get_traders_by_addresses(&self, addresses: &[String]) -> Result<Vec<Trader>>;
+
+    // recompute stats for every trader from raw transactions and resolutions
+    async fn compute_traders(&self) -> Result<Vec<Trader>>;
+
+    // per category stats of these traders in any of the categories, empty when none are stored
+    async fn get_category_stats(&self, addresses: &[String], categories: &[String]) -> Result<Vec<TraderCategoryStats>>;
+
+    // the stats every past stats rebuild stored, oldest first, empty before the first rebuild
+    async fn get_trader_snapshots(&self) -> Result<Vec<TraderSnapshot>>;
 }
 
 // interface for position data
@@ -23,6 +95,9 @@ pub trait TraderStatsProvider: Send + Sync {
 pub trait PositionProvider: Send + Sync {
     // get all positions from a condition ID
     async fn get_positions(&self, condition_id: &str) -> Result<Vec<Position>>;
+
+    // every stored position across all markets
+    async fn get_all_positions(&self) -> Result<Vec<Position>>;
 }
 
 // interface for transactions or trades in time window
@@ -34,4 +109,103 @@ pub trait TransactionProvider: Send + Sync {
         condition_id: &str,
         days_back: u32,
     ) -> Result<Vec<Transaction>>;
+
+    // get a market's whole trade history ordered by block
+    async fn get_market_transactions(&self, condition_id: &str) -> Result<Vec<Transaction>>;
+
+    // get every transaction across all markets ordered by block, used for replays
+    async fn get_all_transactions(&self) -> Result<Vec<Transaction>>;
+
+    // transactions after a block ordered by block, plus rows without one (data api trades) timestamped after timestamp
+    async fn get_transactions_after(&self, block: u64, timestamp: i64) -> Result<Vec<Transaction>>;
+
+    // these wallets' transactions across every market at or after timestamp (unix seconds), oldest first, untimed rows left out
+    async fn get_wallet_transactions(&self, addresses: &[String], timestamp: i64) -> Result<Vec<Transaction>>;
+}
+
+// interface for resolved market outcomes
+#[async_trait]
+pub trait ResolutionProvider: Send + Sync {
+    // get all markets that have resolved
+    async fn get_resolutions(&self) -> Result<Vec<MarketResolution>>;
+}
+
+// interface for stored market tags
+#[async_trait]
+pub trait TagProvider: Send + Sync {
+    // every tagged market, one row per tag
+    async fn get_market_tags(&self) -> Result<Vec<MarketTag>>;
+}
+
+// interface for liquidity reward payouts
+#[async_trait]
+pub trait RewardProvider: Send + Sync {
+    // payouts to these wallets oldest first, only those at or after `after` (unix seconds) when given
+    async fn get_rewards(&self, addresses: &[String], after: Option<i64>) -> Result<Vec<RewardPayout>>;
+}
+
+// interface for the discussion under events
+#[async_trait]
+pub trait CommentProvider: Send + Sync {
+    // an event's latest comments newest first, at most limit of them
+    async fn get_event_comments(&self, slug: &str, limit: usize) -> Result<Vec<Comment>>;
+}
+
+// interface for odds quoted outside polymarket, sportsbooks and other betting apis
+#[async_trait]
+pub trait ExternalOddsProvider: Send + Sync {
+    // upcoming and live events of a sport or competition with every bookmaker's line on each outcome
+    async fn get_external_odds(&self, sport: &str) -> Result<Vec<ExternalEvent>>;
+}
+
+// interface for outside probability forecasts, election models and the like
+#[async_trait]
+pub trait ExternalForecastProvider: Send + Sync {
+    // every forecast for the market, matched on condition id or slug, empty when no model covers it
+    async fn get_forecasts(&self, market: &Market) -> Result<Vec<ExternalForecast>>;
+}
+
+// interface for persisting standardized data back to storage
+#[async_trait]
+pub trait DataStore: Send + Sync {
+    // replace stored market resolutions
+    async fn save_resolutions(&self, resolutions: &[MarketResolution]) -> Result<()>;
+
+    // replace stored trader stats
+    async fn save_traders(&self, traders: &[Trader]) -> Result<()>;
+
+    // keep these stats alongside the earlier snapshots, one already stored at the same block is replaced
+    async fn save_trader_snapshot(&self, snapshot: &TraderSnapshot) -> Result<()>;
+
+    // first block at or after each unix timestamp, None where the chain can't place it
+    async fn blocks_at(&self, timestamps: &[i64]) -> Result<Vec<Option<u64>>>;
+
+    // add transactions to the stored ones, fills already stored are skipped, the ones added come back
+    async fn append_transactions(&self, transactions: &[Transaction]) -> Result<Vec<Transaction>>;
+
+    // add reward payouts to the stored ones, payouts already stored are skipped
+    async fn append_rewards(&self, rewards: &[RewardPayout]) -> Result<()>;
+
+    // store positions, one per trader and token, replacing what was stored for the same pair
+    async fn upsert_positions(&self, positions: &[Position]) -> Result<()>;
+
+    // replace stored market tags
+    async fn save_market_tags(&self, tags: &[MarketTag]) -> Result<()>;
+
+    // replace stored per category trader stats
+    async fn save_category_stats(&self, stats: &[TraderCategoryStats]) -> Result<()>;
+
+    // running totals per trader and market behind the trader stats, empty before the first stats rebuild
+    async fn load_trader_ledgers(&self) -> Result<Vec<TraderLedger>>;
+
+    // replace stored trader ledgers
+    async fn save_trader_ledgers(&self, ledgers: &[TraderLedger]) -> Result<()>;
+
+    // ingest progress, empty when nothing was ingested yet
+    async fn load_checkpoints(&self) -> Result<Checkpoints>;
+
+    async fn save_checkpoints(&self, checkpoints: &Checkpoints) -> Result<()>;
+
+    // rewrite stored tables into sorted files with row groups of about row_group_size rows
+    async fn compact(&self, row_group_size: usize) -> Result<Vec<Compaction>>;
 }
@@ -0,0 +1,414 @@
+use crate::address_book::AddressBook;
+use crate::analysis::book_history::BookSnapshot;
+use crate::analysis::liquidity::LiquidityPoint;
+use crate::analysis::MarketSummary;
+use crate::error::{DataError, Result};
+use crate::paper::{PaperLedger, PaperSnapshot};
+use crate::paths;
+use crate::watchlist::Watchlist;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const STATE_FILE: &str = "state.sqlite";
+// how long a write waits on another run holding the lock, monitor and a watch command can overlap
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// schema changes in order, user_version counts how many have run, only ever append to this
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE watched_markets (slug TEXT PRIMARY KEY);
+     CREATE TABLE watched_traders (address TEXT PRIMARY KEY);
+     CREATE TABLE labels (address TEXT PRIMARY KEY, label TEXT NOT NULL);
+     CREATE TABLE wallet_groups (address TEXT PRIMARY KEY, name TEXT NOT NULL);
+     CREATE TABLE book_snapshots (slug TEXT NOT NULL, timestamp INTEGER NOT NULL, book TEXT NOT NULL);
+     CREATE INDEX book_snapshots_by_slug ON book_snapshots (slug, timestamp);
+     CREATE TABLE alert_baselines (slug TEXT PRIMARY KEY, summary TEXT NOT NULL);",
+    "CREATE TABLE liquidity_points (slug TEXT NOT NULL, timestamp INTEGER NOT NULL, liquidity REAL NOT NULL, spread REAL NOT NULL, yes_price REAL NOT NULL);
+     CREATE INDEX liquidity_points_by_slug ON liquidity_points (slug, timestamp);
+     CREATE TABLE paper_account (id INTEGER PRIMARY KEY CHECK (id = 1), starting_cash REAL NOT NULL);
+     CREATE TABLE paper_fills (timestamp INTEGER NOT NULL, fill TEXT NOT NULL);
+     CREATE TABLE paper_snapshots (timestamp INTEGER NOT NULL, cash REAL NOT NULL, positions_value REAL NOT NULL);",
+];
+// polls kept per market, the oldest go first, a week of one minute polls
+pub const MAX_LIQUIDITY_POINTS: usize = 10_080;
+
+// the json files and books dir the state db replaced, each read once by the migration that made its tables
+pub struct LegacyFiles {
+    pub watchlist: PathBuf,
+    pub labels: PathBuf,
+    pub books: PathBuf,
+    pub liquidity: PathBuf,
+    pub paper: PathBuf,
+}
+
+impl LegacyFiles {
+    pub fn default_locations() -> Self {
+        Self {
+            watchlist: paths::config_file("watchlist.json"),
+            labels: paths::config_file("labels.json"),
+            books: paths::data_file("books"),
+            liquidity: paths::data_file("liquidity.json"),
+            paper: paths::data_file("paper.json"),
+        }
+    }
+}
+
+// liquidity.json as monitor wrote it, per market slug oldest first
+#[derive(Deserialize)]
+struct LegacyLiquidityLog {
+    #[serde(default)]
+    markets: BTreeMap<String, Vec<LiquidityPoint>>,
+}
+
+// everything the tool keeps between runs that isn't a cache, one sqlite file
+// watchlist, labels and wallet groups, archived order books, the last poll monitor alerted against,
+// the liquidity monitor recorded and the paper account
+pub struct StateDb {
+    conn: Connection,
+}
+
+impl StateDb {
+    // state.sqlite in the platform data dir
+    pub fn default_path() -> PathBuf {
+        paths::data_file(STATE_FILE)
+    }
+
+    // the default db, created from the legacy files on first use
+    pub fn open() -> Result<Self> {
+        Self::connect(&Self::default_path(), Some(&LegacyFiles::default_locations()))
+    }
+
+    pub fn at(path: impl AsRef<Path>) -> Result<Self> {
+        Self::connect(path.as_ref(), None)
+    }
+
+    pub fn at_importing(path: impl AsRef<Path>, legacy: &LegacyFiles) -> Result<Self> {
+        Self::connect(path.as_ref(), Some(legacy))
+    }
+
+    fn connect(path: &Path, legacy: Option<&LegacyFiles>) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // readers don't block the monitor's writes
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        migrate(&mut conn, path, legacy)?;
+        Ok(Self { conn })
+    }
+
+    // migrations run so far
+    pub fn schema_version(&self) -> Result<usize> {
+        Ok(self.conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
+    pub fn watchlist(&self) -> Result<Watchlist> {
+        Ok(Watchlist {
+            markets: self.column("SELECT slug FROM watched_markets")?.into_iter().collect(),
+            traders: self.column("SELECT address FROM watched_traders")?.into_iter().collect(),
+        })
+    }
+
+    // replaces the stored watchlist
+    pub fn save_watchlist(&self, watchlist: &Watchlist) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        write_watchlist(&tx, watchlist)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn address_book(&self) -> Result<AddressBook> {
+        let mut book = AddressBook::default();
+        let mut labels = self.conn.prepare("SELECT address, label FROM labels")?;
+        for row in labels.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (address, label): (String, String) = row?;
+            book.labels.insert(address, label);
+        }
+        // rowid keeps each group's members in the order they were added
+        let mut groups = self.conn.prepare("SELECT name, address FROM wallet_groups ORDER BY rowid")?;
+        for row in groups.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (name, address): (String, String) = row?;
+            book.groups.entry(name).or_default().push(address);
+        }
+        Ok(book)
+    }
+
+    // replaces the stored labels and groups, resolved names are never stored
+    pub fn save_address_book(&self, book: &AddressBook) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        write_address_book(&tx, book)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn append_book(&self, slug: &str, snapshot: &BookSnapshot) -> Result<()> {
+        let book = serde_json::to_string(&snapshot.book).map_err(|e| DataError::Corrupt(e.to_string()))?;
+        self.conn.execute(
+            "INSERT INTO book_snapshots (slug, timestamp, book) VALUES (?1, ?2, ?3)",
+            params![slug, snapshot.timestamp, book],
+        )?;
+        Ok(())
+    }
+
+    // oldest first, a slug never archived has none
+    pub fn book_snapshots(&self, slug: &str) -> Result<Vec<BookSnapshot>> {
+        let mut statement = self.conn.prepare("SELECT timestamp, book FROM book_snapshots WHERE slug = ?1 ORDER BY timestamp, rowid")?;
+        let rows = statement.query_map([slug], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        rows.map(|row| {
+            let (timestamp, book) = row?;
+            let book = serde_json::from_str(&book)
+                .map_err(|e| DataError::Corrupt(format!("{} book at {}: {}", slug, timestamp, e)))?;
+            Ok(BookSnapshot { timestamp, book })
+        })
+        .collect()
+    }
+
+    // the last poll of every market, what the next poll's alerts are measured from
+    pub fn alert_baselines(&self) -> Result<HashMap<String, MarketSummary>> {
+        let mut statement = self.conn.prepare("SELECT slug, summary FROM alert_baselines")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        rows.map(|row| {
+            let (slug, summary) = row?;
+            let summary = serde_json::from_str(&summary)
+                .map_err(|e| DataError::Corrupt(format!("{} alert baseline: {}", slug, e)))?;
+            Ok((slug, summary))
+        })
+        .collect()
+    }
+
+    pub fn save_alert_baselines(&self, summaries: &[MarketSummary]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for summary in summaries {
+            let json = serde_json::to_string(summary).map_err(|e| DataError::Corrupt(e.to_string()))?;
+            tx.execute(
+                "INSERT INTO alert_baselines (slug, summary) VALUES (?1, ?2) ON CONFLICT (slug) DO UPDATE SET summary = excluded.summary",
+                params![summary.slug, json],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // one point per market for a monitor poll, each market keeps its newest MAX_LIQUIDITY_POINTS
+    pub fn record_liquidity(&self, points: &[(&str, LiquidityPoint)]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for (slug, point) in points {
+            insert_liquidity(&tx, slug, point)?;
+            tx.execute(
+                "DELETE FROM liquidity_points WHERE slug = ?1 AND rowid NOT IN
+                     (SELECT rowid FROM liquidity_points WHERE slug = ?1 ORDER BY timestamp DESC, rowid DESC LIMIT ?2)",
+                params![slug, MAX_LIQUIDITY_POINTS as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // oldest first, a slug monitor never polled has none
+    pub fn liquidity_points(&self, slug: &str) -> Result<Vec<LiquidityPoint>> {
+        let mut statement = self.conn.prepare(
+            "SELECT timestamp, liquidity, spread, yes_price FROM liquidity_points WHERE slug = ?1 ORDER BY timestamp, rowid",
+        )?;
+        let points = statement
+            .query_map([slug], |row| Ok(LiquidityPoint {
+                timestamp: row.get(0)?,
+                liquidity: row.get(1)?,
+                spread: row.get(2)?,
+                yes_price: row.get(3)?,
+            }))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(points)
+    }
+
+    // a fresh account until the first fill or reset
+    pub fn paper_ledger(&self) -> Result<PaperLedger> {
+        let starting_cash: Option<f64> = self.conn
+            .query_row("SELECT starting_cash FROM paper_account", [], |row| row.get(0))
+            .optional()?;
+        let mut ledger = starting_cash.map_or_else(PaperLedger::default, PaperLedger::new);
+
+        let mut fills = self.conn.prepare("SELECT fill FROM paper_fills ORDER BY rowid")?;
+        for fill in fills.query_map([], |row| row.get::<_, String>(0))? {
+            let fill = fill?;
+            ledger.fills.push(serde_json::from_str(&fill).map_err(|e| DataError::Corrupt(format!("paper fill: {}", e)))?);
+        }
+        let mut snapshots = self.conn.prepare("SELECT timestamp, cash, positions_value FROM paper_snapshots ORDER BY rowid")?;
+        ledger.snapshots = snapshots
+            .query_map([], |row| Ok(PaperSnapshot { timestamp: row.get(0)?, cash: row.get(1)?, positions_value: row.get(2)? }))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(ledger)
+    }
+
+    // replaces the stored account
+    pub fn save_paper_ledger(&self, ledger: &PaperLedger) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        write_paper_ledger(&tx, ledger)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn column(&self, sql: &str) -> Result<Vec<String>> {
+        let mut statement = self.conn.prepare(sql)?;
+        let values = statement.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        Ok(values)
+    }
+}
+
+// every pending migration and the user_version bump commit together, a failed one leaves the db as it was
+// the version is read again under the write lock, two runs creating the db at once only migrate it once
+// a db from a newer build is refused rather than written with a schema this one doesn't know
+fn migrate(conn: &mut Connection, path: &Path, legacy: Option<&LegacyFiles>) -> Result<()> {
+    let version = |conn: &Connection| -> Result<usize> { Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?) };
+    let newer = |version: usize| DataError::Corrupt(format!(
+        "{} is at schema version {}, this build knows {}",
+        path.display(), version, MIGRATIONS.len(),
+    ));
+    if version(conn)? == MIGRATIONS.len() {
+        return Ok(());
+    }
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let current = version(&tx)?;
+    if current > MIGRATIONS.len() {
+        return Err(newer(current).into());
+    }
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+        tx.execute_batch(sql)?;
+        // fill the tables a migration created from the files they replace
+        if let Some(legacy) = legacy {
+            import_legacy(&tx, legacy, i)?;
+        }
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn write_watchlist(conn: &Connection, watchlist: &Watchlist) -> Result<()> {
+    conn.execute("DELETE FROM watched_markets", [])?;
+    conn.execute("DELETE FROM watched_traders", [])?;
+    for slug in &watchlist.markets {
+        conn.execute("INSERT INTO watched_markets (slug) VALUES (?1)", [slug])?;
+    }
+    for address in &watchlist.traders {
+        conn.execute("INSERT INTO watched_traders (address) VALUES (?1)", [address])?;
+    }
+    Ok(())
+}
+
+fn write_address_book(conn: &Connection, book: &AddressBook) -> Result<()> {
+    conn.execute("DELETE FROM labels", [])?;
+    conn.execute("DELETE FROM wallet_groups", [])?;
+    for (address, label) in &book.labels {
+        conn.execute("INSERT INTO labels (address, label) VALUES (?1, ?2)", [address, label])?;
+    }
+    for (name, members) in &book.groups {
+        for address in members {
+            conn.execute("INSERT INTO wallet_groups (address, name) VALUES (?1, ?2)", [address, name])?;
+        }
+    }
+    Ok(())
+}
+
+fn insert_liquidity(conn: &Connection, slug: &str, point: &LiquidityPoint) -> Result<()> {
+    conn.execute(
+        "INSERT INTO liquidity_points (slug, timestamp, liquidity, spread, yes_price) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![slug, point.timestamp, point.liquidity, point.spread, point.yes_price],
+    )?;
+    Ok(())
+}
+
+fn write_paper_ledger(conn: &Connection, ledger: &PaperLedger) -> Result<()> {
+    conn.execute("DELETE FROM paper_fills", [])?;
+    conn.execute("DELETE FROM paper_snapshots", [])?;
+    conn.execute(
+        "INSERT INTO paper_account (id, starting_cash) VALUES (1, ?1) ON CONFLICT (id) DO UPDATE SET starting_cash = excluded.starting_cash",
+        [ledger.starting_cash],
+    )?;
+    for fill in &ledger.fills {
+        let json = serde_json::to_string(fill).map_err(|e| DataError::Corrupt(e.to_string()))?;
+        conn.execute("INSERT INTO paper_fills (timestamp, fill) VALUES (?1, ?2)", params![fill.timestamp, json])?;
+    }
+    for snapshot in &ledger.snapshots {
+        conn.execute(
+            "INSERT INTO paper_snapshots (timestamp, cash, positions_value) VALUES (?1, ?2, ?3)",
+            params![snapshot.timestamp, snapshot.cash, snapshot.positions_value],
+        )?;
+    }
+    Ok(())
+}
+
+// a broken legacy file fails the migration, the db is migrated again next run once it's fixed or deleted
+fn import_legacy(conn: &Connection, legacy: &LegacyFiles, migration: usize) -> Result<()> {
+    match migration {
+        0 => import_lists_and_books(conn, legacy),
+        1 => {
+            if let Some(log) = read_json::<LegacyLiquidityLog>(&legacy.liquidity)? {
+                for (slug, points) in &log.markets {
+                    for point in points.iter().skip(points.len().saturating_sub(MAX_LIQUIDITY_POINTS)) {
+                        insert_liquidity(conn, slug, point)?;
+                    }
+                }
+            }
+            if let Some(ledger) = read_json::<PaperLedger>(&legacy.paper)? {
+                write_paper_ledger(conn, &ledger)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn import_lists_and_books(conn: &Connection, legacy: &LegacyFiles) -> Result<()> {
+    if let Some(watchlist) = read_json::<Watchlist>(&legacy.watchlist)? {
+        write_watchlist(conn, &watchlist)?;
+    }
+    if let Some(book) = read_json::<AddressBook>(&legacy.labels)? {
+        write_address_book(conn, &book)?;
+    }
+    if legacy.books.is_dir() {
+        for entry in fs::read_dir(&legacy.books)? {
+            let path = entry?.path();
+            let Some(slug) = path.file_stem().filter(|_| path.extension().is_some_and(|e| e == "jsonl")) else {
+                continue;
+            };
+            let slug = slug.to_string_lossy();
+            for snapshot in read_book_lines(&path)? {
+                let book = serde_json::to_string(&snapshot.book).map_err(|e| DataError::Corrupt(e.to_string()))?;
+                conn.execute(
+                    "INSERT INTO book_snapshots (slug, timestamp, book) VALUES (?1, ?2, ?3)",
+                    params![slug, snapshot.timestamp, book],
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(path)?;
+    let value = serde_json::from_str(&text).map_err(|e| DataError::Corrupt(format!("{}: {}", path.display(), e)))?;
+    Ok(Some(value))
+}
+
+// one snapshot a line, a half written last line is a poll interrupted mid write and is skipped
+fn read_book_lines(path: &Path) -> Result<Vec<BookSnapshot>> {
+    let text = fs::read_to_string(path)?;
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+    let mut snapshots = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(_) if i + 1 == lines.len() && !text.ends_with('\n') => break,
+            Err(e) => return Err(DataError::Corrupt(format!("{} line {}: {}", path.display(), i + 1, e)).into()),
+        }
+    }
+    Ok(snapshots)
+}
@@ -0,0 +1,257 @@
+use crate::clock;
+use crate::error::{HttpError, Result};
+use crate::standard_data::models::{Comment, Market, MarketGroup, OrderBook, Position, RewardPayout, Trader, TraderCategoryStats, TraderSnapshot, Transaction};
+use crate::standard_data::providers::{
+    CommentProvider, MarketFilter, MarketMetadataProvider, OrderBookProvider, PositionProvider, RewardProvider, TraderStatsProvider, TransactionProvider,
+};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+// provider fake serving exactly the data it's given, unknown slugs and tokens come back as a 404
+// every call is logged so tests can check what was asked for
+#[derive(Debug, Clone, Default)]
+pub struct FakeSource {
+    groups: BTreeMap<String, MarketGroup>,
+    traders: Vec<Trader>,
+    category_stats: Vec<TraderCategoryStats>,
+    snapshots: Vec<TraderSnapshot>,
+    positions: Vec<Position>,
+    transactions: Vec<Transaction>,
+    rewards: Vec<RewardPayout>,
+    comments: BTreeMap<String, Vec<Comment>>,
+    books: HashMap<String, OrderBook>,
+    // provider methods that fail every call, by method name
+    failing: HashSet<String>,
+    // shared between clones
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // its markets are served by condition id and in active market listings too
+    pub fn with_group(mut self, group: MarketGroup) -> Self {
+        self.groups.insert(group.slug.clone(), group);
+        self
+    }
+
+    pub fn with_traders(mut self, traders: Vec<Trader>) -> Self {
+        self.traders = traders;
+        self
+    }
+
+    pub fn with_category_stats(mut self, stats: Vec<TraderCategoryStats>) -> Self {
+        self.category_stats = stats;
+        self
+    }
+
+    pub fn with_trader_snapshots(mut self, snapshots: Vec<TraderSnapshot>) -> Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    pub fn with_positions(mut self, positions: Vec<Position>) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    pub fn with_transactions(mut self, transactions: Vec<Transaction>) -> Self {
+        self.transactions = transactions;
+        self
+    }
+
+    pub fn with_rewards(mut self, rewards: Vec<RewardPayout>) -> Self {
+        self.rewards = rewards;
+        self
+    }
+
+    // comments on the event with this slug, a group without any has none
+    pub fn with_comments(mut self, slug: &str, comments: Vec<Comment>) -> Self {
+        self.comments.insert(slug.to_string(), comments);
+        self
+    }
+
+    pub fn with_order_book(mut self, book: OrderBook) -> Self {
+        self.books.insert(book.token_id.clone(), book);
+        self
+    }
+
+    // every call to the provider method with this name fails with a 500, e.g. "get_traders_by_addresses"
+    pub fn with_failure(mut self, method: &str) -> Self {
+        self.failing.insert(method.to_string());
+        self
+    }
+
+    // method name and arguments of every provider call so far, oldest first
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().map(|calls| calls.clone()).unwrap_or_default()
+    }
+
+    // logs the call, then the 500 when the method was set to fail
+    fn record(&self, call: String) -> std::result::Result<(), HttpError> {
+        let method = call.split(' ').next().unwrap_or_default().to_string();
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(call);
+        }
+        match self.failing.contains(&method) {
+            true => Err(HttpError::Status { status: reqwest::StatusCode::INTERNAL_SERVER_ERROR, body: format!("{} failed", method) }),
+            false => Ok(()),
+        }
+    }
+
+    fn markets(&self) -> impl Iterator<Item = &Market> {
+        self.groups.values().flat_map(|group| group.markets.iter())
+    }
+}
+
+fn not_found(what: &str) -> HttpError {
+    HttpError::Status { status: reqwest::StatusCode::NOT_FOUND, body: format!("{} not found", what) }
+}
+
+#[async_trait]
+impl MarketMetadataProvider for FakeSource {
+    async fn get_market_group(&self, slug: &str) -> Result<MarketGroup> {
+        self.record(format!("get_market_group {}", slug))?;
+        Ok(self.groups.get(slug).cloned().ok_or_else(|| not_found(slug))?)
+    }
+
+    async fn get_markets_by_condition_ids(&self, condition_ids: &[String]) -> Result<Vec<Market>> {
+        self.record(format!("get_markets_by_condition_ids {}", condition_ids.join(",")))?;
+        Ok(self.markets().filter(|m| condition_ids.contains(&m.condition_id)).cloned().collect())
+    }
+
+    // no ordering, listings come back in slug order
+    async fn get_active_markets(&self, filter: &MarketFilter) -> Result<Vec<Market>> {
+        self.record("get_active_markets".to_string())?;
+        Ok(self.markets()
+            .filter(|m| m.active && !m.closed)
+            .filter(|m| filter.include_archived || !m.archived)
+            .filter(|m| filter.min_liquidity.is_none_or(|min| m.liquidity >= min))
+            .take(filter.limit)
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl OrderBookProvider for FakeSource {
+    async fn get_order_book(&self, token_id: &str) -> Result<OrderBook> {
+        self.record(format!("get_order_book {}", token_id))?;
+        Ok(self.books.get(token_id).cloned().ok_or_else(|| not_found(token_id))?)
+    }
+}
+
+#[async_trait]
+impl TraderStatsProvider for FakeSource {
+    async fn get_traders(&self, min_resolved_markets: u32) -> Result<Vec<Trader>> {
+        self.record(format!("get_traders {}", min_resolved_markets))?;
+        Ok(self.traders.iter().filter(|t| t.total_markets_resolved >= min_resolved_markets).cloned().collect())
+    }
+
+    async fn get_traders_by_addresses(&self, addresses: &[String]) -> Result<Vec<Trader>> {
+        self.record(format!("get_traders_by_addresses {}", addresses.len()))?;
+        Ok(self.traders.iter().filter(|t| addresses.contains(&t.trader_address)).cloned().collect())
+    }
+
+    async fn compute_traders(&self) -> Result<Vec<Trader>> {
+        self.record("compute_traders".to_string())?;
+        Ok(self.traders.clone())
+    }
+
+    async fn get_category_stats(&self, addresses: &[String], categories: &[String]) -> Result<Vec<TraderCategoryStats>> {
+        self.record(format!("get_category_stats {} {}", addresses.len(), categories.join(",")))?;
+        Ok(self.category_stats
+            .iter()
+            .filter(|s| addresses.contains(&s.trader_address) && categories.contains(&s.category))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_trader_snapshots(&self) -> Result<Vec<TraderSnapshot>> {
+        self.record("get_trader_snapshots".to_string())?;
+        Ok(self.snapshots.clone())
+    }
+}
+
+#[async_trait]
+impl PositionProvider for FakeSource {
+    async fn get_positions(&self, condition_id: &str) -> Result<Vec<Position>> {
+        self.record(format!("get_positions {}", condition_id))?;
+        Ok(self.positions.iter().filter(|p| p.market_id == condition_id).cloned().collect())
+    }
+
+    async fn get_all_positions(&self) -> Result<Vec<Position>> {
+        self.record("get_all_positions".to_string())?;
+        Ok(self.positions.clone())
+    }
+}
+
+#[async_trait]
+impl TransactionProvider for FakeSource {
+    // untimed transactions always count as recent
+    async fn get_recent_transactions(&self, condition_id: &str, days_back: u32) -> Result<Vec<Transaction>> {
+        self.record(format!("get_recent_transactions {} {}", condition_id, days_back))?;
+        let after = clock::now().timestamp() - days_back as i64 * 24 * 60 * 60;
+        Ok(self.transactions
+            .iter()
+            .filter(|tx| tx.market_id == condition_id && tx.timestamp.is_none_or(|t| t >= after))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_market_transactions(&self, condition_id: &str) -> Result<Vec<Transaction>> {
+        self.record(format!("get_market_transactions {}", condition_id))?;
+        Ok(self.transactions.iter().filter(|tx| tx.market_id == condition_id).cloned().collect())
+    }
+
+    async fn get_all_transactions(&self) -> Result<Vec<Transaction>> {
+        self.record("get_all_transactions".to_string())?;
+        Ok(self.transactions.clone())
+    }
+
+    async fn get_transactions_after(&self, block: u64, timestamp: i64) -> Result<Vec<Transaction>> {
+        self.record(format!("get_transactions_after {} {}", block, timestamp))?;
+        Ok(self.transactions
+            .iter()
+            .filter(|tx| tx.block_number > block || (tx.block_number == 0 && tx.timestamp.is_some_and(|t| t > timestamp)))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_wallet_transactions(&self, addresses: &[String], timestamp: i64) -> Result<Vec<Transaction>> {
+        self.record(format!("get_wallet_transactions {} {}", addresses.len(), timestamp))?;
+        let mut transactions: Vec<Transaction> = self.transactions
+            .iter()
+            .filter(|tx| addresses.contains(&tx.trader_address) && tx.timestamp.is_some_and(|t| t >= timestamp))
+            .cloned()
+            .collect();
+        transactions.sort_by_key(|tx| tx.timestamp);
+        Ok(transactions)
+    }
+}
+
+#[async_trait]
+impl CommentProvider for FakeSource {
+    async fn get_event_comments(&self, slug: &str, limit: usize) -> Result<Vec<Comment>> {
+        self.record(format!("get_event_comments {} {}", slug, limit))?;
+        if !self.groups.contains_key(slug) && !self.comments.contains_key(slug) {
+            return Err(not_found(slug).into());
+        }
+        Ok(self.comments.get(slug).into_iter().flatten().take(limit).cloned().collect())
+    }
+}
+
+#[async_trait]
+impl RewardProvider for FakeSource {
+    async fn get_rewards(&self, addresses: &[String], after: Option<i64>) -> Result<Vec<RewardPayout>> {
+        self.record(format!("get_rewards {}", addresses.len()))?;
+        Ok(self.rewards
+            .iter()
+            .filter(|r| addresses.contains(&r.trader_address) && after.is_none_or(|after| r.timestamp >= after))
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,123 @@
+use crate::adapters::{CapturedResponse, HttpClient, RawCapture};
+use crate::adapters::api_urls::GAMMA_API_URL;
+use crate::data_sources::{LocalDbSource, MockSource, PolymarketApiSource};
+use crate::error::{DataError, Result};
+use crate::standard_data::models::{Market, MarketGroup, Position, Trader, Transaction};
+use crate::standard_data::providers::{DataStore, MarketMetadataProvider, PositionProvider, TraderStatsProvider, TransactionProvider};
+use serde_json::{json, Value};
+use std::path::Path;
+
+// an event the way gamma's /events/slug sends it, json arrays as strings and a few fields nothing reads
+pub fn gamma_event(group: &MarketGroup) -> Value {
+    json!({
+        "id": "900001",
+        "ticker": group.slug,
+        "slug": group.slug,
+        "title": group.title,
+        "description": "",
+        "active": group.active,
+        "closed": group.closed,
+        "archived": false,
+        "restricted": false,
+        "volume": group.volume,
+        "liquidity": group.liquidity,
+        "markets": group.markets.iter().map(gamma_market).collect::<Vec<_>>(),
+        "tags": [],
+    })
+}
+
+fn gamma_market(market: &Market) -> Value {
+    // gamma double encodes the lists
+    let encoded = |list: &[String]| serde_json::to_string(list).unwrap_or_default();
+    json!({
+        "id": "500001",
+        "question": market.question,
+        "conditionId": market.condition_id,
+        "slug": market.slug,
+        "outcomes": encoded(&market.outcomes),
+        "outcomePrices": encoded(&market.outcome_prices),
+        "clobTokenIds": encoded(&[market.yes_token_id.clone(), market.no_token_id.clone()]),
+        "active": market.active,
+        "closed": market.closed,
+        "archived": market.archived,
+        "volume": market.volume.to_string(),
+        "volumeNum": market.volume,
+        "volume24hr": market.volume_24h,
+        "volume1wk": market.volume_1w,
+        "volume1mo": market.volume_1m,
+        "volume1yr": market.volume_1y,
+        "liquidity": market.liquidity.to_string(),
+        "liquidityNum": market.liquidity,
+        "competitive": market.competitive,
+        "lastTradePrice": market.last_trade_price,
+        "bestBid": market.bid_price,
+        "bestAsk": market.ask_price,
+        "oneDayPriceChange": market.price_change_24h,
+        "createdAt": market.created_at.map(|date| date.to_rfc3339()),
+        "endDate": market.end_date.map(|date| date.to_rfc3339()),
+        "closedTime": market.closed_time.map(|date| date.format("%Y-%m-%d %H:%M:%S+00").to_string()),
+        "resolutionSource": market.resolution_source.clone().unwrap_or_default(),
+        "tags": market.tags.iter().map(|tag| json!({ "label": tag, "slug": tag })).collect::<Vec<_>>(),
+        "enableOrderBook": true,
+        "negRisk": false,
+    })
+}
+
+// a --replay-raw capture answering /events/slug/{slug} with the event
+pub fn write_gamma_event(dir: &Path, group: &MarketGroup) -> Result<()> {
+    let body = serde_json::to_string(&gamma_event(group)).map_err(|e| DataError::Corrupt(e.to_string()))?;
+    RawCapture::record(dir)?.save(&CapturedResponse {
+        method: "GET".to_string(),
+        url: format!("{}/events/slug/{}", GAMMA_API_URL, group.slug),
+        request_body: None,
+        status: 200,
+        captured_at: 0,
+        elapsed_ms: 0,
+        body,
+    })
+}
+
+// the real gamma source, answering from the captures in dir instead of the network
+pub fn replay_source(dir: &Path) -> Result<PolymarketApiSource> {
+    let client = HttpClient::builder().raw_capture(RawCapture::replay(dir)?).build()?;
+    Ok(PolymarketApiSource::new(client))
+}
+
+// the required parquet tables written through the local db's own writers
+pub async fn write_parquet_fixtures(
+    dir: &Path,
+    traders: &[Trader],
+    positions: &[Position],
+    transactions: &[Transaction],
+) -> Result<LocalDbSource> {
+    let db = LocalDbSource::new(&dir.to_string_lossy());
+    db.save_traders(traders).await?;
+    db.upsert_positions(positions).await?;
+    db.append_transactions(transactions).await?;
+    Ok(db)
+}
+
+// gamma source and local db over the same generated data as --source mock
+pub struct Fixtures {
+    pub group: MarketGroup,
+    pub markets: PolymarketApiSource,
+    pub db: LocalDbSource,
+}
+
+// gamma captures go in dir/gamma and the parquet tables in dir/processed_data
+pub async fn mock_fixtures(dir: &Path, slug: &str) -> Result<Fixtures> {
+    let mock = MockSource::new();
+    let group = mock.get_market_group(slug).await?;
+
+    let gamma_dir = dir.join("gamma");
+    write_gamma_event(&gamma_dir, &group)?;
+    let db = write_parquet_fixtures(
+        &dir.join("processed_data"),
+        &mock.get_traders(0).await?,
+        &mock.get_all_positions().await?,
+        &mock.get_all_transactions().await?,
+    )
+    .await?;
+
+    Ok(Fixtures { group, markets: replay_source(&gamma_dir)?, db })
+}
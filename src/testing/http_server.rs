@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// one request the server was sent, header names lowercased
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+}
+
+// what the server answers, content-length is added when it's written
+#[derive(Debug, Clone)]
+pub struct CannedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl CannedResponse {
+    // empty body
+    pub fn status(status: u16) -> Self {
+        Self { status, headers: Vec::new(), body: Vec::new() }
+    }
+
+    // 200 with a json body
+    pub fn json(body: &str) -> Self {
+        Self::status(200).with_header("content-type", "application/json").with_body(body.as_bytes().to_vec())
+    }
+
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+// raw http/1.1 server on a loopback port for testing clients against
+// each request gets the next response, the last one repeating, and is kept for the test to look at
+pub struct TestServer {
+    url: String,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl TestServer {
+    // one request per connection, closed after the answer
+    pub async fn start(responses: Vec<CannedResponse>) -> std::io::Result<Self> {
+        Self::listen(responses, false).await
+    }
+
+    // connections stay open for more requests, like a pooled client expects
+    pub async fn keep_alive(responses: Vec<CannedResponse>) -> std::io::Result<Self> {
+        Self::listen(responses, true).await
+    }
+
+    async fn listen(responses: Vec<CannedResponse>, keep_alive: bool) -> std::io::Result<Self> {
+        assert!(!responses.is_empty(), "a test server needs something to answer");
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let server = Self {
+            url: format!("http://{}", listener.local_addr()?),
+            requests: Arc::new(Mutex::new(Vec::new())),
+            connections: Arc::new(AtomicUsize::new(0)),
+        };
+
+        let responses = Arc::new(responses);
+        let requests = Arc::clone(&server.requests);
+        let connections = Arc::clone(&server.connections);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(serve(socket, Arc::clone(&responses), Arc::clone(&requests), keep_alive));
+            }
+        });
+        Ok(server)
+    }
+
+    // base url without a trailing slash
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+async fn serve(mut socket: TcpStream, responses: Arc<Vec<CannedResponse>>, requests: Arc<Mutex<Vec<ReceivedRequest>>>, keep_alive: bool) {
+    let mut pending = Vec::new();
+    while let Some(request) = read_request(&mut socket, &mut pending).await {
+        let response = {
+            let mut requests = requests.lock().unwrap();
+            requests.push(request);
+            responses[(requests.len() - 1).min(responses.len() - 1)].clone()
+        };
+
+        let mut head = format!("HTTP/1.1 {} Status\r\ncontent-length: {}\r\n", response.status, response.body.len());
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        if !keep_alive {
+            head.push_str("connection: close\r\n");
+        }
+        head.push_str("\r\n");
+        if socket.write_all(head.as_bytes()).await.is_err() || socket.write_all(&response.body).await.is_err() {
+            return;
+        }
+        if !keep_alive {
+            return;
+        }
+    }
+}
+
+// the next request off the connection, bytes past it stay in pending for the one after
+async fn read_request(socket: &mut TcpStream, pending: &mut Vec<u8>) -> Option<ReceivedRequest> {
+    let mut buf = vec![0; 4096];
+    let head_end = loop {
+        if let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(read) => pending.extend_from_slice(&buf[..read]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&pending[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let length: usize = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+
+    let end = head_end + 4 + length;
+    while pending.len() < end {
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => return None,
+            Ok(read) => pending.extend_from_slice(&buf[..read]),
+        }
+    }
+    let body = pending[head_end + 4..end].to_vec();
+    pending.drain(..end);
+
+    Some(ReceivedRequest { method, path, headers, body })
+}
@@ -0,0 +1,28 @@
+// fixtures and fakes for testing code built on the library, behind the testing feature
+mod fakes;
+mod fixtures;
+mod http_server;
+
+pub use fakes::FakeSource;
+pub use fixtures::{gamma_event, mock_fixtures, replay_source, write_gamma_event, write_parquet_fixtures, Fixtures};
+pub use http_server::{CannedResponse, ReceivedRequest, TestServer};
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static SCRATCH_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+// fresh empty dir under the system temp dir, unique per process and call, left behind for inspection
+pub fn scratch_dir(name: &str) -> std::io::Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!(
+        "polymarket-explorer-{}-{}-{}",
+        name,
+        std::process::id(),
+        SCRATCH_DIRS.fetch_add(1, Ordering::SeqCst),
+    ));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
@@ -0,0 +1,117 @@
+use crate::adapters::HttpClient;
+use crate::adapters::api_urls::CLOB_API_URL;
+use crate::error::Result;
+use crate::trading::order::{self, OrderArgs};
+use crate::trading::{Credentials, OrderSide, TradingError};
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+struct NegRiskResponse {
+    neg_risk: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeeRateResponse {
+    base_fee: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TickSizeResponse {
+    minimum_tick_size: f64,
+}
+
+// what the clob says after accepting an order
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlacedOrder {
+    #[serde(default)]
+    pub success: bool,
+    #[serde(default)]
+    pub error_msg: String,
+    #[serde(rename = "orderID", default)]
+    pub order_id: String,
+    // live, matched or delayed
+    #[serde(default)]
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelResult {
+    #[serde(default)]
+    pub canceled: Vec<String>,
+    // order id -> reason
+    #[serde(default)]
+    pub not_canceled: HashMap<String, String>,
+}
+
+// authenticated side of the clob api, every call here moves real money
+pub struct ClobTradingClient {
+    http_client: HttpClient,
+    clob_url: String,
+    credentials: Credentials,
+}
+
+impl ClobTradingClient {
+    pub fn new(http_client: HttpClient, credentials: Credentials) -> Self {
+        Self { http_client, clob_url: CLOB_API_URL.to_string(), credentials }
+    }
+
+    pub fn with_clob_url(mut self, url: impl Into<String>) -> Self {
+        self.clob_url = url.into();
+        self
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    // good till cancelled limit order, tick size, fee rate and exchange are looked up so the signature matches what the clob expects
+    pub async fn place_limit_order(&self, token_id: &str, side: OrderSide, price: f64, shares: f64) -> Result<PlacedOrder> {
+        let neg_risk: NegRiskResponse = self.http_client
+            .get(&format!("{}/neg-risk?token_id={}", self.clob_url, token_id))
+            .await?;
+        let fee_rate: FeeRateResponse = self.http_client
+            .get(&format!("{}/fee-rate?token_id={}", self.clob_url, token_id))
+            .await?;
+        let tick_size: TickSizeResponse = self.http_client
+            .get(&format!("{}/tick-size?token_id={}", self.clob_url, token_id))
+            .await?;
+
+        let signed = order::build_order(&self.credentials, &OrderArgs {
+            token_id: token_id.to_string(),
+            side,
+            price,
+            shares,
+            tick_size: tick_size.minimum_tick_size,
+            fee_rate_bps: fee_rate.base_fee,
+            neg_risk: neg_risk.neg_risk,
+        })?;
+
+        let body = json!({
+            "order": signed,
+            "owner": self.credentials.api_key,
+            "orderType": "GTC",
+        })
+        .to_string();
+
+        let placed: PlacedOrder = self.authenticated(Method::POST, "/order", body).await?;
+        if !placed.success {
+            return Err(TradingError::Rejected(placed.error_msg).into());
+        }
+        Ok(placed)
+    }
+
+    pub async fn cancel_order(&self, order_id: &str) -> Result<CancelResult> {
+        let body = json!({ "orderID": order_id }).to_string();
+        self.authenticated(Method::DELETE, "/order", body).await
+    }
+
+    async fn authenticated<T: serde::de::DeserializeOwned>(&self, method: Method, path: &str, body: String) -> Result<T> {
+        let headers = self.credentials.l2_headers(method.as_str(), path, &body)?;
+        let url = format!("{}{}", self.clob_url, path);
+        self.http_client.request_with_headers(method, &url, &headers, Some(body)).await
+    }
+}
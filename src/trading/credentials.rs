@@ -0,0 +1,142 @@
+use crate::adapters::abi::{from_hex, keccak256, to_hex};
+use crate::trading::TradingError;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE;
+use hmac::{Hmac, Mac};
+use k256::ecdsa::SigningKey;
+use sha2::Sha256;
+use std::fmt;
+
+// how the exchange checks the order signature against the maker
+pub const SIGNATURE_EOA: u8 = 0;
+pub const SIGNATURE_POLY_PROXY: u8 = 1;
+pub const SIGNATURE_GNOSIS_SAFE: u8 = 2;
+
+// wallet key for signing orders plus the api key for authenticating requests
+// only ever read from the environment, never written anywhere
+pub struct Credentials {
+    signing_key: SigningKey,
+    // address of the signing key, 0x prefixed lowercase
+    pub address: String,
+    // wallet holding the funds, differs from the signer for polymarket proxy wallets
+    pub funder: String,
+    pub signature_type: u8,
+    pub api_key: String,
+    api_secret: String,
+    api_passphrase: String,
+}
+
+// keep secrets out of debug output and logs
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("address", &self.address)
+            .field("funder", &self.funder)
+            .field("signature_type", &self.signature_type)
+            .field("api_key", &self.api_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Credentials {
+    // POLYMARKET_PRIVATE_KEY, POLYMARKET_API_KEY, POLYMARKET_API_SECRET, POLYMARKET_API_PASSPHRASE
+    // plus optional POLYMARKET_FUNDER and POLYMARKET_SIGNATURE_TYPE for proxy wallets
+    pub fn from_env() -> Result<Self, TradingError> {
+        let signing_key = signing_key(&required_env("POLYMARKET_PRIVATE_KEY")?)?;
+        let address = address_of(&signing_key);
+
+        let funder = std::env::var("POLYMARKET_FUNDER").ok().map(|f| f.trim().to_lowercase());
+        let signature_type = match std::env::var("POLYMARKET_SIGNATURE_TYPE").ok() {
+            Some(value) => match value.trim() {
+                "0" => SIGNATURE_EOA,
+                "1" => SIGNATURE_POLY_PROXY,
+                "2" => SIGNATURE_GNOSIS_SAFE,
+                other => {
+                    return Err(TradingError::Credentials(format!("POLYMARKET_SIGNATURE_TYPE must be 0, 1 or 2, got {}", other)));
+                }
+            },
+            // browser wallet accounts trade through a safe
+            None if funder.is_some() => SIGNATURE_GNOSIS_SAFE,
+            None => SIGNATURE_EOA,
+        };
+
+        Ok(Self {
+            signing_key,
+            funder: funder.unwrap_or_else(|| address.clone()),
+            address,
+            signature_type,
+            api_key: required_env("POLYMARKET_API_KEY")?,
+            api_secret: required_env("POLYMARKET_API_SECRET")?,
+            api_passphrase: required_env("POLYMARKET_API_PASSPHRASE")?,
+        })
+    }
+
+    // an eoa signing for itself with no api key, for checking signatures against fixed vectors
+    #[cfg(test)]
+    pub(crate) fn from_private_key(private_key: &str) -> Result<Self, TradingError> {
+        let signing_key = signing_key(private_key)?;
+        let address = address_of(&signing_key);
+        Ok(Self {
+            signing_key,
+            funder: address.clone(),
+            address,
+            signature_type: SIGNATURE_EOA,
+            api_key: String::new(),
+            api_secret: String::new(),
+            api_passphrase: String::new(),
+        })
+    }
+
+    // 65 byte r || s || v signature over a 32 byte digest
+    pub fn sign_digest(&self, digest: &[u8; 32]) -> Result<String, TradingError> {
+        let (signature, recovery_id) = self.signing_key
+            .sign_prehash_recoverable(digest)
+            .map_err(|e| TradingError::InvalidOrder(format!("signing failed: {}", e)))?;
+
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        Ok(format!("0x{}", to_hex(&bytes)))
+    }
+
+    // POLY_* headers for an authenticated request, the signature covers the exact body sent
+    pub fn l2_headers(&self, method: &str, path: &str, body: &str) -> Result<Vec<(&'static str, String)>, TradingError> {
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+
+        let secret = URL_SAFE
+            .decode(&self.api_secret)
+            .map_err(|_| TradingError::Credentials("POLYMARKET_API_SECRET is not base64".to_string()))?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(&secret)
+            .map_err(|_| TradingError::Credentials("POLYMARKET_API_SECRET is empty".to_string()))?;
+        mac.update(format!("{}{}{}{}", timestamp, method, path, body).as_bytes());
+        let signature = URL_SAFE.encode(mac.finalize().into_bytes());
+
+        Ok(vec![
+            ("POLY_ADDRESS", self.address.clone()),
+            ("POLY_SIGNATURE", signature),
+            ("POLY_TIMESTAMP", timestamp),
+            ("POLY_API_KEY", self.api_key.clone()),
+            ("POLY_PASSPHRASE", self.api_passphrase.clone()),
+        ])
+    }
+}
+
+fn required_env(name: &str) -> Result<String, TradingError> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| TradingError::Credentials(format!("{} is not set", name)))
+}
+
+fn signing_key(private_key: &str) -> Result<SigningKey, TradingError> {
+    let key_bytes = from_hex(private_key.trim())
+        .ok_or_else(|| TradingError::Credentials("POLYMARKET_PRIVATE_KEY is not hex".to_string()))?;
+    SigningKey::from_slice(&key_bytes)
+        .map_err(|_| TradingError::Credentials("POLYMARKET_PRIVATE_KEY is not a valid secp256k1 key".to_string()))
+}
+
+// last 20 bytes of the keccak of the uncompressed public key
+fn address_of(key: &SigningKey) -> String {
+    let point = key.verifying_key().to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    format!("0x{}", to_hex(&hash[12..]))
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TradingError {
+    #[error("Missing or invalid trading credentials: {0}")]
+    Credentials(String),
+
+    #[error("Can't build order: {0}")]
+    InvalidOrder(String),
+
+    #[error("Order rejected by the CLOB: {0}")]
+    Rejected(String),
+}
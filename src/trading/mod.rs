@@ -0,0 +1,9 @@
+pub mod clob;
+pub mod credentials;
+pub mod error;
+pub mod order;
+
+pub use clob::ClobTradingClient;
+pub use credentials::Credentials;
+pub use error::TradingError;
+pub use order::{OrderSide, SignedOrder};
@@ -0,0 +1,267 @@
+use crate::adapters::abi::{address_word, decimal_word, keccak256, u64_word};
+use crate::trading::{Credentials, TradingError};
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// polygon mainnet
+const CHAIN_ID: u64 = 137;
+const EXCHANGE: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+// multi outcome events settle through a separate exchange
+const NEG_RISK_EXCHANGE: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+// anyone can fill the order
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+// usdc and outcome shares both use 6 decimals on chain
+const UNITS: f64 = 1_000_000.0;
+
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const ORDER_TYPE: &str = "Order(uint256 salt,address maker,address signer,address taker,uint256 tokenId,uint256 makerAmount,uint256 takerAmount,uint256 expiration,uint256 nonce,uint256 feeRateBps,uint8 side,uint8 signatureType)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        }
+    }
+
+    fn code(&self) -> u64 {
+        match self {
+            OrderSide::Buy => 0,
+            OrderSide::Sell => 1,
+        }
+    }
+}
+
+// what the user asked for, prices and sizes in normal units
+#[derive(Debug, Clone)]
+pub struct OrderArgs {
+    pub token_id: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub shares: f64,
+    // the market's price increment, 0.01 on most markets and finer near 0 and 1
+    pub tick_size: f64,
+    pub fee_rate_bps: u64,
+    pub neg_risk: bool,
+}
+
+// the order as the clob wants it in the POST /order body
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedOrder {
+    pub salt: u64,
+    pub maker: String,
+    pub signer: String,
+    pub taker: String,
+    pub token_id: String,
+    pub maker_amount: String,
+    pub taker_amount: String,
+    pub expiration: String,
+    pub nonce: String,
+    pub fee_rate_bps: String,
+    pub side: String,
+    pub signature_type: u8,
+    pub signature: String,
+}
+
+// build and sign a good till cancelled limit order
+pub fn build_order(credentials: &Credentials, args: &OrderArgs) -> Result<SignedOrder, TradingError> {
+    sign_order(credentials, args, salt())
+}
+
+fn sign_order(credentials: &Credentials, args: &OrderArgs, salt: u64) -> Result<SignedOrder, TradingError> {
+    let (maker_amount, taker_amount) = order_amounts(args)?;
+    let digest = order_digest(credentials, args, salt, maker_amount, taker_amount)?;
+    let signature = credentials.sign_digest(&digest)?;
+
+    Ok(SignedOrder {
+        salt,
+        maker: credentials.funder.clone(),
+        signer: credentials.address.clone(),
+        taker: ZERO_ADDRESS.to_string(),
+        token_id: args.token_id.clone(),
+        maker_amount: maker_amount.to_string(),
+        taker_amount: taker_amount.to_string(),
+        expiration: "0".to_string(),
+        nonce: "0".to_string(),
+        fee_rate_bps: args.fee_rate_bps.to_string(),
+        side: args.side.as_str().to_string(),
+        signature_type: credentials.signature_type,
+        signature,
+    })
+}
+
+// (maker amount, taker amount) in 6 decimal units
+// prices go to the market's tick toward the user, down for a buy and up for a sell, so the order never
+// trades past the limit asked for, sizes go down to cents of a share
+fn order_amounts(args: &OrderArgs) -> Result<(u64, u64), TradingError> {
+    // the clob's ticks run from 0.1 down to 0.0001, finer would leave the usdc side more than 6 decimals
+    let tick_units = to_step(args.tick_size, 1, f64::round);
+    if tick_units < 100 || !(UNITS as u64).is_multiple_of(tick_units) {
+        return Err(TradingError::InvalidOrder(format!("tick size {} is not one the clob uses", args.tick_size)));
+    }
+    let toward_user = match args.side {
+        OrderSide::Buy => f64::floor,
+        OrderSide::Sell => f64::ceil,
+    };
+    let price_units = to_step(args.price, tick_units, toward_user);
+    if price_units == 0 || price_units >= UNITS as u64 {
+        return Err(TradingError::InvalidOrder(format!(
+            "price {} must be between {} and {} at a {} tick",
+            args.price, args.tick_size, 1.0 - args.tick_size, args.tick_size,
+        )));
+    }
+    let share_units = to_step(args.shares, 10_000, f64::floor);
+    if share_units == 0 {
+        return Err(TradingError::InvalidOrder(format!("size {} rounds down to zero shares", args.shares)));
+    }
+    // cents of a share times a tick of at least 0.0001 comes out in whole units
+    let usdc_units = (share_units as u128 * price_units as u128 / UNITS as u128) as u64;
+
+    Ok(match args.side {
+        OrderSide::Buy => (usdc_units, share_units),
+        OrderSide::Sell => (share_units, usdc_units),
+    })
+}
+
+// 6 decimal units of a value taken to a multiple of step units, counted in whole units first
+// so float error can't turn 0.29 into 28.999... cents and floor it away
+fn to_step(value: f64, step: u64, round: fn(f64) -> f64) -> u64 {
+    let units = (value * UNITS).round();
+    if units.is_nan() || units <= 0.0 {
+        return 0;
+    }
+    (round(units / step as f64) * step as f64) as u64
+}
+
+// the EIP-712 hash of the order the exchange recovers the signer from
+fn order_digest(credentials: &Credentials, args: &OrderArgs, salt: u64, maker_amount: u64, taker_amount: u64) -> Result<[u8; 32], TradingError> {
+    let token_word = decimal_word(&args.token_id)
+        .ok_or_else(|| TradingError::InvalidOrder(format!("token id {} is not a uint256", args.token_id)))?;
+    let maker_word = address_word(&credentials.funder)
+        .ok_or_else(|| TradingError::Credentials(format!("funder {} is not an address", credentials.funder)))?;
+    let signer_word = address_word(&credentials.address)
+        .ok_or_else(|| TradingError::Credentials(format!("signer {} is not an address", credentials.address)))?;
+    let taker_word = address_word(ZERO_ADDRESS).unwrap_or_default();
+
+    let mut encoded = keccak256(ORDER_TYPE.as_bytes()).to_vec();
+    for word in [
+        u64_word(salt),
+        maker_word,
+        signer_word,
+        taker_word,
+        token_word,
+        u64_word(maker_amount),
+        u64_word(taker_amount),
+        // no expiration
+        u64_word(0),
+        u64_word(0),
+        u64_word(args.fee_rate_bps),
+        u64_word(args.side.code()),
+        u64_word(credentials.signature_type as u64),
+    ] {
+        encoded.extend_from_slice(&word);
+    }
+    let struct_hash = keccak256(&encoded);
+
+    let exchange = if args.neg_risk { NEG_RISK_EXCHANGE } else { EXCHANGE };
+    let mut digest_input = vec![0x19, 0x01];
+    digest_input.extend_from_slice(&domain_separator(exchange)?);
+    digest_input.extend_from_slice(&struct_hash);
+    Ok(keccak256(&digest_input))
+}
+
+fn domain_separator(exchange: &str) -> Result<[u8; 32], TradingError> {
+    let contract = address_word(exchange)
+        .ok_or_else(|| TradingError::InvalidOrder(format!("exchange {} is not an address", exchange)))?;
+
+    let mut encoded = keccak256(DOMAIN_TYPE.as_bytes()).to_vec();
+    encoded.extend_from_slice(&keccak256(b"Polymarket CTF Exchange"));
+    encoded.extend_from_slice(&keccak256(b"1"));
+    encoded.extend_from_slice(&u64_word(CHAIN_ID));
+    encoded.extend_from_slice(&contract);
+    Ok(keccak256(&encoded))
+}
+
+// only has to be unique per maker, kept under 2^53 so json number parsers don't round it
+fn salt() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    (nanos % (1u128 << 53)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::abi::to_hex;
+
+    // first hardhat dev account, address 0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266
+    const KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const TOKEN: &str = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+
+    fn args(side: OrderSide, price: f64, shares: f64) -> OrderArgs {
+        OrderArgs { token_id: TOKEN.to_string(), side, price, shares, tick_size: 0.01, fee_rate_bps: 0, neg_risk: false }
+    }
+
+    // hashes and signature from a separate keccak, secp256k1 and rfc6979 implementation of the
+    // same typed data as py-clob-client signs, any drift here means the clob rejects our orders
+    #[test]
+    fn orders_sign_to_the_reference_vector() {
+        let credentials = Credentials::from_private_key(KEY).unwrap();
+        assert_eq!(credentials.address, "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+        assert_eq!(to_hex(&domain_separator(EXCHANGE).unwrap()), "1a573e3617c78403b5b4b892827992f027b03d4eaf570048b8ee8cdd84d151be");
+        assert_eq!(to_hex(&domain_separator(NEG_RISK_EXCHANGE).unwrap()), "82cb6aa85babb812f4b521a12b10f0cbc68d2b44be7bc02c047004f544adb49f");
+
+        let order = args(OrderSide::Buy, 0.57, 10.999);
+        let (maker_amount, taker_amount) = order_amounts(&order).unwrap();
+        let digest = order_digest(&credentials, &order, 479249096354, maker_amount, taker_amount).unwrap();
+        assert_eq!(to_hex(&digest), "d1dacc1fbcae022dfcd4d4ce02dd1c2ea9bd003be9af8a321cab7341f58ea6a1");
+
+        let signed = sign_order(&credentials, &order, 479249096354).unwrap();
+        assert_eq!(signed.maker_amount, "6264300");
+        assert_eq!(signed.taker_amount, "10990000");
+        assert_eq!(
+            signed.signature,
+            "0x19c87d478b3cbfeb8e1b0ced2c30388921ac73391780d028b951979bc84c2e2b20c1666eb5d84882d4b52ac1b88d78a7c9675870e4312e555c2216300b2ad6251b"
+        );
+    }
+
+    #[test]
+    fn prices_round_toward_the_user_and_sizes_down_to_cents() {
+        // 0.29 * 100 is 28.999... in floats, flooring that would drop a cent
+        assert_eq!(order_amounts(&args(OrderSide::Buy, 0.5, 0.29)).unwrap(), (145_000, 290_000));
+        // a sell gives shares and takes usdc
+        assert_eq!(order_amounts(&args(OrderSide::Sell, 0.33, 3.339)).unwrap(), (3_330_000, 1_098_900));
+
+        // off tick limits never get signed past what was asked, a buy pays at most and a sell takes at least the limit
+        for limit in [0.575, 0.571, 0.579, 0.57, 0.985, 0.015] {
+            let (usdc, shares) = order_amounts(&args(OrderSide::Buy, limit, 10.0)).unwrap();
+            assert!(usdc as f64 / shares as f64 <= limit + 1e-12, "buy at {} signed at {}", limit, usdc as f64 / shares as f64);
+            let (shares, usdc) = order_amounts(&args(OrderSide::Sell, limit, 10.0)).unwrap();
+            assert!(usdc as f64 / shares as f64 >= limit - 1e-12, "sell at {} signed at {}", limit, usdc as f64 / shares as f64);
+        }
+        assert_eq!(order_amounts(&args(OrderSide::Buy, 0.575, 10.0)).unwrap(), (5_700_000, 10_000_000));
+        assert_eq!(order_amounts(&args(OrderSide::Sell, 0.575, 10.0)).unwrap(), (10_000_000, 5_800_000));
+
+        // a finer tick keeps more of the price
+        let fine = OrderArgs { tick_size: 0.001, ..args(OrderSide::Buy, 0.5755, 10.0) };
+        assert_eq!(order_amounts(&fine).unwrap(), (5_750_000, 10_000_000));
+
+        for (price, shares) in [(0.004, 10.0), (0.0, 10.0), (-0.5, 10.0), (f64::NAN, 10.0), (0.5, 0.009), (0.5, -1.0)] {
+            assert!(matches!(order_amounts(&args(OrderSide::Buy, price, shares)), Err(TradingError::InvalidOrder(_))), "{} x {}", price, shares);
+        }
+        // a sell above the last tick rounds up to 1
+        assert!(order_amounts(&args(OrderSide::Sell, 0.996, 10.0)).is_err());
+        for tick_size in [0.0, 0.00001, 0.03, f64::NAN] {
+            assert!(order_amounts(&OrderArgs { tick_size, ..args(OrderSide::Buy, 0.5, 10.0) }).is_err(), "tick {}", tick_size);
+        }
+    }
+}
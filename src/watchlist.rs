@@ -0,0 +1,33 @@
+use crate::error::Result;
+use crate::state_db::StateDb;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// market slugs and trader addresses the user keeps an eye on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Watchlist {
+    #[serde(default)]
+    pub markets: BTreeSet<String>,
+    #[serde(default)]
+    pub traders: BTreeSet<String>,
+}
+
+impl Watchlist {
+    // from the state db, empty until something is added
+    pub fn load() -> Result<Self> {
+        StateDb::open()?.watchlist()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        StateDb::open()?.save_watchlist(self)
+    }
+
+    // addresses are case insensitive on chain, keep one spelling
+    pub fn add_trader(&mut self, address: &str) -> bool {
+        self.traders.insert(address.to_lowercase())
+    }
+
+    pub fn remove_trader(&mut self, address: &str) -> bool {
+        self.traders.remove(&address.to_lowercase())
+    }
+}
@@ -0,0 +1,45 @@
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use std::future::Future;
+use std::sync::OnceLock;
+
+// markets analyzed or fetched at once unless --concurrency says otherwise
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+static CONCURRENCY: OnceLock<usize> = OnceLock::new();
+
+// set once from --concurrency before any work starts, later calls are ignored
+pub fn set_concurrency(limit: usize) {
+    let _ = CONCURRENCY.set(limit.max(1));
+}
+
+pub fn concurrency() -> usize {
+    CONCURRENCY.get().copied().unwrap_or(DEFAULT_CONCURRENCY)
+}
+
+// results one at a time in the order given as soon as each is ready, at most concurrency() in flight
+pub fn ordered<I>(work: I) -> impl Stream<Item = <I::Item as Future>::Output>
+where
+    I: IntoIterator,
+    I::Item: Future,
+{
+    stream::iter(work).buffered(concurrency())
+}
+
+// futures::future::join_all with at most concurrency() in flight, results come back in the order given
+// the futures borrow the caller's providers, so they all go through the same http client, limits and caches
+pub async fn join_all<I>(work: I) -> Vec<<I::Item as Future>::Output>
+where
+    I: IntoIterator,
+    I::Item: Future,
+{
+    ordered(work).collect().await
+}
+
+// same, stops at the first error and drops whatever is still in flight
+pub async fn try_join_all<I, T, E>(work: I) -> Result<Vec<T>, E>
+where
+    I: IntoIterator,
+    I::Item: Future<Output = Result<T, E>>,
+{
+    ordered(work).try_collect().await
+}
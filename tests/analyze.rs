@@ -0,0 +1,265 @@
+use polymarket_explorer::analysis::CostBasis;
+use polymarket_explorer::analysis::order_flow::DEFAULT_OFI_WINDOW_HOURS;
+use polymarket_explorer::analysis::vwap::DEFAULT_VWAP_WINDOWS;
+use polymarket_explorer::cli::{handle_analyze, AnalysisBus, AnalysisEvent, AnalysisSink, TerminalSink};
+use polymarket_explorer::config::Config;
+use polymarket_explorer::data_sources::{Capabilities, MockSource};
+use polymarket_explorer::standard_data::providers::{MarketMetadataProvider, PositionProvider};
+use polymarket_explorer::testing::{self, FakeSource};
+use std::sync::{Arc, Mutex};
+
+// gamma json through the real parser and parquet through the real reader, nothing over the network
+#[tokio::test]
+async fn analyze_runs_on_gamma_and_parquet_fixtures() {
+    let dir = testing::scratch_dir("analyze").unwrap();
+    let fixtures = testing::mock_fixtures(&dir, "fixture-event").await.unwrap();
+
+    let parsed = fixtures.markets.get_market_group("fixture-event").await.unwrap();
+    assert_eq!(parsed.markets.len(), fixtures.group.markets.len());
+    assert_eq!(parsed.markets[0].condition_id, fixtures.group.markets[0].condition_id);
+    assert_eq!(parsed.markets[0].yes_token_id, fixtures.group.markets[0].yes_token_id);
+
+    fixtures.db.validate_schema().unwrap();
+    let capabilities = fixtures.db.capabilities();
+    assert!(capabilities.local_db());
+
+    handle_analyze(
+        "fixture-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &capabilities,
+        None,
+        &Config::default(),
+        &fixtures.markets,
+        &fixtures.db,
+        &fixtures.db,
+        &fixtures.db,
+        &[],
+        AnalysisBus::new().subscribe(TerminalSink),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn analyze_fails_for_a_slug_gamma_never_sent() {
+    let dir = testing::scratch_dir("analyze-missing").unwrap();
+    let fixtures = testing::mock_fixtures(&dir, "fixture-event").await.unwrap();
+
+    let error = fixtures.markets.get_market_group("other-event").await.unwrap_err();
+    assert_eq!(error.code(), "http.not_captured");
+}
+
+#[tokio::test]
+async fn analyze_reads_holders_and_trades_of_the_primary_market() {
+    let mock = MockSource::new();
+    let group = mock.get_market_group("fake-event").await.unwrap();
+    let fake = FakeSource::new().with_group(group.clone());
+
+    handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &Capabilities::full(),
+        None,
+        &Config::default(),
+        &fake,
+        &fake,
+        &fake,
+        &fake,
+        &[],
+        AnalysisBus::new().subscribe(TerminalSink),
+    )
+    .await
+    .unwrap();
+
+    let calls = fake.calls();
+    assert_eq!(calls[0], "get_market_group fake-event");
+    let primary = &group.markets[0].condition_id;
+    assert!(calls.contains(&format!("get_positions {}", primary)));
+    assert!(calls.contains(&format!("get_market_transactions {}", primary)));
+}
+
+// keeps the json of every event it sees, shared so the test can read it after the bus is gone
+#[derive(Clone, Default)]
+struct RecordingSink {
+    events: Arc<Mutex<Vec<serde_json::Value>>>,
+}
+
+#[async_trait::async_trait]
+impl AnalysisSink for RecordingSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        self.events.lock().unwrap().push(serde_json::json!(event));
+    }
+}
+
+#[tokio::test]
+async fn analyze_emits_every_section_without_a_terminal() {
+    let mock = MockSource::new();
+    let recorder = RecordingSink::default();
+
+    handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &mock.capabilities(),
+        None,
+        &Config::default(),
+        &mock,
+        &mock,
+        &mock,
+        &mock,
+        &[],
+        AnalysisBus::new().subscribe(recorder.clone()),
+    )
+    .await
+    .unwrap();
+
+    let events = recorder.events.lock().unwrap();
+    let names: Vec<&str> = events.iter().filter_map(|event| event["event"].as_str()).collect();
+    assert_eq!(names.first(), Some(&"started"));
+    for section in ["group", "market", "positions", "traders", "wallet_age", "concentration", "implied_returns", "pnl", "vwap", "order_flow"] {
+        assert!(names.contains(&section), "no {} event in {:?}", section, names);
+    }
+    assert!(!names.contains(&"unavailable"));
+}
+
+#[tokio::test]
+async fn analyze_all_markets_emits_every_market_in_group_order() {
+    let mock = MockSource::new();
+    let group = mock.get_market_group("fake-event").await.unwrap();
+    let recorder = RecordingSink::default();
+
+    handle_analyze(
+        "fake-event",
+        true,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &mock.capabilities(),
+        None,
+        &Config::default(),
+        &mock,
+        &mock,
+        &mock,
+        &mock,
+        &[],
+        AnalysisBus::new().subscribe(recorder.clone()),
+    )
+    .await
+    .unwrap();
+
+    let events = recorder.events.lock().unwrap();
+    let markets: Vec<&str> = events
+        .iter()
+        .filter(|event| event["event"] == "market")
+        .filter_map(|event| event["market"]["condition_id"].as_str())
+        .collect();
+    let expected: Vec<&str> = group.markets.iter().map(|m| m.condition_id.as_str()).collect();
+    assert!(expected.len() > 1);
+    assert_eq!(markets, expected);
+}
+
+#[tokio::test]
+async fn analyze_marks_sections_whose_fetch_failed_and_renders_the_rest() {
+    let mock = MockSource::new();
+    let group = mock.get_market_group("fake-event").await.unwrap();
+    let condition_id = group.markets[0].condition_id.clone();
+    let fake = FakeSource::new()
+        .with_group(group)
+        .with_positions(mock.get_positions(&condition_id).await.unwrap())
+        .with_failure("get_traders_by_addresses");
+    let recorder = RecordingSink::default();
+
+    handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &Capabilities::full(),
+        None,
+        &Config::default(),
+        &fake,
+        &fake,
+        &fake,
+        &fake,
+        &[],
+        AnalysisBus::new().subscribe(recorder.clone()),
+    )
+    .await
+    .unwrap();
+
+    let events = recorder.events.lock().unwrap();
+    let failed: Vec<&str> = events
+        .iter()
+        .filter(|event| event["event"] == "failed")
+        .filter_map(|event| event["section"].as_str())
+        .collect();
+    assert_eq!(failed, ["TRADER STATS", "WALLET AGE", "IMPLIED RETURNS"]);
+    let names: Vec<&str> = events.iter().filter_map(|event| event["event"].as_str()).collect();
+    for section in ["positions", "concentration", "pnl", "vwap", "order_flow"] {
+        assert!(names.contains(&section), "no {} event in {:?}", section, names);
+    }
+}
+
+#[tokio::test]
+async fn analyze_fail_fast_stops_at_the_failed_fetch() {
+    let mock = MockSource::new();
+    let group = mock.get_market_group("fake-event").await.unwrap();
+    let condition_id = group.markets[0].condition_id.clone();
+    let fake = FakeSource::new()
+        .with_group(group)
+        .with_positions(mock.get_positions(&condition_id).await.unwrap())
+        .with_failure("get_traders_by_addresses");
+
+    let error = handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        true,
+        false,
+        None,
+        None,
+        &Capabilities::full(),
+        None,
+        &Config::default(),
+        &fake,
+        &fake,
+        &fake,
+        &fake,
+        &[],
+        AnalysisBus::new(),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.to_string().contains("500"), "{}", error);
+}
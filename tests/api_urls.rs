@@ -0,0 +1,39 @@
+use polymarket_explorer::adapters::{ApiUrls, CapturedResponse, HttpClient, RawCapture};
+use polymarket_explorer::data_sources::{MockSource, PolymarketApiSource};
+use polymarket_explorer::standard_data::providers::MarketMetadataProvider;
+use polymarket_explorer::testing;
+
+// base urls lose a trailing slash, anything that can't take a path appended is refused
+#[test]
+fn api_urls_are_validated_at_startup() {
+    let urls = ApiUrls { gamma: "http://127.0.0.1:8080/gamma/".to_string(), ..ApiUrls::default() }.validated().unwrap();
+    assert_eq!(urls.gamma, "http://127.0.0.1:8080/gamma");
+    assert_eq!(urls.clob, ApiUrls::default().clob);
+
+    for bad in ["gamma-api.polymarket.com", "ftp://mirror.example", "http://", "https://mirror.example/?key=1"] {
+        let urls = ApiUrls { clob: bad.to_string(), ..ApiUrls::default() };
+        assert!(urls.validated().is_err(), "{} should be refused", bad);
+    }
+}
+
+// a source pointed at a mirror asks the mirror, not gamma
+#[tokio::test]
+async fn gamma_requests_go_to_the_configured_url() {
+    let group = MockSource::new().get_market_group("mock-event").await.unwrap();
+    let dir = testing::scratch_dir("api-urls").unwrap();
+    RawCapture::record(&dir).unwrap().save(&CapturedResponse {
+        method: "GET".to_string(),
+        url: "http://127.0.0.1:8080/gamma/events/slug/mock-event".to_string(),
+        request_body: None,
+        status: 200,
+        captured_at: 0,
+        elapsed_ms: 0,
+        body: testing::gamma_event(&group).to_string(),
+    }).unwrap();
+
+    let client = || HttpClient::builder().raw_capture(RawCapture::replay(&dir).unwrap()).build().unwrap();
+    let urls = ApiUrls { gamma: "http://127.0.0.1:8080/gamma/".to_string(), ..ApiUrls::default() }.validated().unwrap();
+    let mirrored = PolymarketApiSource::new(client()).with_urls(urls);
+    assert_eq!(mirrored.get_market_group("mock-event").await.unwrap().markets.len(), group.markets.len());
+    assert!(PolymarketApiSource::new(client()).get_market_group("mock-event").await.is_err());
+}
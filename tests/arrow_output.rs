@@ -0,0 +1,146 @@
+use polars::prelude::*;
+use polymarket_explorer::analysis::CostBasis;
+use polymarket_explorer::analysis::order_flow::DEFAULT_OFI_WINDOW_HOURS;
+use polymarket_explorer::analysis::vwap::DEFAULT_VWAP_WINDOWS;
+use polymarket_explorer::cli::{handle_analyze, AnalysisBus, ArrowSink};
+use polymarket_explorer::config::Config;
+use polymarket_explorer::data_sources::{Capabilities, MockSource};
+use polymarket_explorer::standard_data::providers::MarketMetadataProvider;
+use polymarket_explorer::testing::{self, FakeSource};
+use std::io::{Cursor, Write};
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+
+// stands in for stdout, the test reads back what the sink wrote
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// arrow ipc streams back to back, each with its table name, market and rows
+fn read_streams(bytes: Vec<u8>) -> Vec<(String, String, DataFrame)> {
+    let mut input = Cursor::new(bytes);
+    let mut tables = Vec::new();
+    while (input.position() as usize) < input.get_ref().len() {
+        let mut reader = IpcStreamReader::new(&mut input);
+        let metadata = reader.custom_metadata().unwrap().unwrap();
+        let table = metadata.get("table").unwrap().to_string();
+        let condition_id = metadata.get("condition_id").unwrap().to_string();
+        tables.push((table, condition_id, reader.finish().unwrap()));
+    }
+    tables
+}
+
+// the built binary with its config, data and cache dirs inside dir so nothing of the machine's leaks in
+fn explorer(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_polymarket-explorer"));
+    command
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env("XDG_CACHE_HOME", dir.join("cache"));
+    command
+}
+
+// the live source with gamma answered from a replayed capture and the local db from parquet fixtures
+fn run_live(dir: &Path, args: &[&str]) -> Output {
+    let output = explorer(dir)
+        .arg("--replay-raw")
+        .arg(dir.join("gamma"))
+        .arg("--data-dir")
+        .arg(dir.join("processed_data"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+// analyze's tables come out as arrow ipc streams back to back, each naming its table and market,
+// the same tables and rows --export writes as files
+#[tokio::test]
+async fn analyze_streams_its_tables_as_arrow_ipc() {
+    let mock = MockSource::new();
+    let group = mock.get_market_group("fake-event").await.unwrap();
+    let fake = FakeSource::new().with_group(group.clone());
+
+    let out = SharedBuffer::default();
+    handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &Capabilities::full(),
+        None,
+        &Config::default(),
+        &fake,
+        &fake,
+        &fake,
+        &fake,
+        &[],
+        AnalysisBus::new().subscribe(ArrowSink::new(out.clone())),
+    )
+    .await
+    .unwrap();
+
+    let tables = read_streams(out.0.lock().unwrap().clone());
+    assert!(tables.iter().all(|(_, condition_id, _)| *condition_id == group.markets[0].condition_id));
+    let names: Vec<&str> = tables.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(names, ["markets", "positions", "traders", "trader_pnl", "vwap", "metrics"]);
+    let markets = &tables[0].2;
+    assert_eq!(markets.height(), group.markets.len());
+    assert_eq!(markets.column("slug").unwrap().str().unwrap().get(0), Some(group.markets[0].slug.as_str()));
+    assert_eq!(tables[5].2.height(), 1);
+}
+
+// through the real gamma source stdout carries the streams and nothing else, a stray print would break the first read
+#[tokio::test]
+async fn analyze_output_arrow_on_the_live_source_is_only_the_streams() {
+    let dir = testing::scratch_dir("arrow-live").unwrap();
+    let fixtures = testing::mock_fixtures(&dir, "fixture-event").await.unwrap();
+
+    let output = run_live(&dir, &["--output", "arrow", "analyze", "--market-slug", "fixture-event"]);
+    let tables = read_streams(output.stdout);
+    let names: Vec<&str> = tables.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(names.first(), Some(&"markets"));
+    assert!(names.contains(&"positions"));
+    assert_eq!(tables[0].1, fixtures.group.markets[0].condition_id);
+    assert_eq!(tables[0].2.height(), fixtures.group.markets.len());
+}
+
+// --stats goes to stderr when stdout is carrying the streams
+#[tokio::test]
+async fn analyze_output_arrow_sends_request_stats_to_stderr() {
+    let dir = testing::scratch_dir("arrow-stats").unwrap();
+    testing::mock_fixtures(&dir, "fixture-event").await.unwrap();
+
+    let output = run_live(&dir, &["--output", "arrow", "--stats", "analyze", "--market-slug", "fixture-event"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("API CALL STATS"));
+    let tables = read_streams(output.stdout);
+    assert_eq!(tables[0].0, "markets");
+}
+
+// only analyze has tables to stream, other commands refuse arrow instead of printing text into it
+#[test]
+fn output_arrow_is_refused_outside_analyze() {
+    let dir = testing::scratch_dir("arrow-refused").unwrap();
+    let output = explorer(&dir).args(["--source", "mock", "--output", "arrow", "movers"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--output arrow only works with analyze"));
+}
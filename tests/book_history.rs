@@ -0,0 +1,36 @@
+use polymarket_explorer::analysis::book_history::{self, BookSnapshot, DEPTH_DISTANCES};
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::standard_data::providers::{MarketMetadataProvider, OrderBookProvider};
+use polymarket_explorer::state_db::StateDb;
+use polymarket_explorer::testing;
+
+// archived books read back oldest first whatever order they were written in
+// and depth only counts the levels within each distance of the mid
+#[tokio::test]
+async fn archived_books_read_back_as_spread_and_depth() {
+    let mock = MockSource::new();
+    let market = mock.get_market_group("mock-event").await.unwrap().markets.remove(0);
+    let book = mock.get_order_book(&market.yes_token_id).await.unwrap();
+
+    let dir = testing::scratch_dir("book-archive").unwrap();
+    let archive = StateDb::at(dir.join("state.sqlite")).unwrap();
+    archive.append_book("mock-event", &BookSnapshot::new(160, book.clone())).unwrap();
+    archive.append_book("mock-event", &BookSnapshot::new(100, book.clone())).unwrap();
+    archive.append_book("other-event", &BookSnapshot::new(130, book.clone())).unwrap();
+
+    let snapshots = archive.book_snapshots("mock-event").unwrap();
+    assert_eq!(snapshots.iter().map(|s| s.timestamp).collect::<Vec<_>>(), [100, 160]);
+    assert!(archive.book_snapshots("never-watched").unwrap().is_empty());
+
+    let points = book_history::book_history(&snapshots, &DEPTH_DISTANCES);
+    let best_bid = book.bids.iter().map(|l| l.price).fold(0.0, f64::max);
+    let best_ask = book.asks.iter().map(|l| l.price).fold(1.0, f64::min);
+    let mid = (best_bid + best_ask) / 2.0;
+    let expected: f64 = book.bids.iter().chain(&book.asks)
+        .filter(|l| (l.price - mid).abs() <= 0.02 + 1e-9)
+        .map(|l| l.price * l.size)
+        .sum();
+    assert!((points[1].spread.unwrap() - (best_ask - best_bid)).abs() < 1e-9);
+    assert!((points[1].depth(1) - expected).abs() < 1e-9);
+    assert!(points[1].depth(0) <= points[1].depth(3));
+}
@@ -0,0 +1,103 @@
+use polymarket_explorer::analysis::chatter;
+use polymarket_explorer::analysis::order_flow::DEFAULT_OFI_WINDOW_HOURS;
+use polymarket_explorer::analysis::vwap::DEFAULT_VWAP_WINDOWS;
+use polymarket_explorer::analysis::CostBasis;
+use polymarket_explorer::cli::{handle_analyze, AnalysisBus, AnalysisEvent, AnalysisSink};
+use polymarket_explorer::config::Config;
+use polymarket_explorer::data_sources::{Capabilities, MockSource};
+use polymarket_explorer::standard_data::models::Comment;
+use polymarket_explorer::standard_data::providers::MarketMetadataProvider;
+use polymarket_explorer::testing::FakeSource;
+use std::sync::{Arc, Mutex};
+
+const NOW: i64 = 1_750_000_000;
+const DAY: i64 = 24 * 60 * 60;
+
+fn comment(author: &str, body: &str, age: i64, likes: u32) -> Comment {
+    Comment { id: format!("{}-{}", author, age), body: body.to_string(), author_address: author.to_string(), created_at: NOW - age, likes }
+}
+
+// volume counts every comment, tone and backing only the ones that say something
+#[test]
+fn chatter_counts_tone_and_backing() {
+    let comments = vec![
+        comment("0xa", "Easy yes, great value", 60, 3),
+        comment("0xa", "Bought yes this morning", 2 * 60 * 60, 0),
+        comment("0xb", "No way, this market is rigged", 2 * DAY, 9),
+        comment("0xc", "When does it resolve?", 4 * DAY, 1),
+    ];
+    let chatter = chatter::chatter(&comments, NOW);
+
+    assert_eq!(chatter.comments, 4);
+    assert_eq!(chatter.commenters, 3);
+    assert_eq!(chatter.last_24h, 2);
+    assert_eq!(chatter.last_7d, 4);
+    assert_eq!(chatter.per_day, Some(1.0));
+    assert_eq!((chatter.positive, chatter.negative), (1, 1));
+    assert_eq!(chatter.tone, Some(0.0));
+    assert_eq!((chatter.yes_leaning, chatter.no_leaning), (2, 1));
+    assert!((chatter.crowd_yes.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(chatter.most_liked.unwrap().author_address, "0xb");
+
+    assert_eq!(chatter::chatter(&[], NOW).tone, None);
+}
+
+#[derive(Clone, Default)]
+struct RecordingSink {
+    events: Arc<Mutex<Vec<serde_json::Value>>>,
+}
+
+#[async_trait::async_trait]
+impl AnalysisSink for RecordingSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        self.events.lock().unwrap().push(serde_json::json!(event));
+    }
+}
+
+async fn analyze_with_chatter(fake: &FakeSource) -> Vec<serde_json::Value> {
+    let recorder = RecordingSink::default();
+    handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        true,
+        None,
+        None,
+        &Capabilities::full(),
+        None,
+        &Config::default(),
+        fake,
+        fake,
+        fake,
+        fake,
+        &[],
+        AnalysisBus::new().subscribe(recorder.clone()),
+    )
+    .await
+    .unwrap();
+    recorder.events.lock().unwrap().clone()
+}
+
+// --chatter adds the section next to the group, a failed comments fetch only marks that section
+#[tokio::test]
+async fn analyze_reports_crowd_chatter_against_the_yes_price() {
+    let group = MockSource::new().get_market_group("fake-event").await.unwrap();
+    let comments = vec![comment("0xa", "buying yes", 60, 1), comment("0xb", "buy yes", 120, 0)];
+    let fake = FakeSource::new().with_group(group.clone()).with_comments("fake-event", comments);
+
+    let events = analyze_with_chatter(&fake).await;
+    let chatter = events.iter().find(|event| event["event"] == "chatter").unwrap();
+    assert_eq!(chatter["chatter"]["comments"], 2);
+    assert_eq!(chatter["chatter"]["crowd_yes"], 1.0);
+    let yes_price: f64 = group.markets[0].outcome_prices[0].parse().unwrap();
+    assert_eq!(chatter["yes_price"], yes_price);
+    assert!(fake.calls().contains(&format!("get_event_comments fake-event {}", chatter::CHATTER_COMMENT_LIMIT)));
+
+    let failing = FakeSource::new().with_group(group).with_failure("get_event_comments");
+    let events = analyze_with_chatter(&failing).await;
+    assert!(events.iter().any(|event| event["event"] == "failed" && event["section"] == "CROWD CHATTER"));
+    assert!(events.iter().any(|event| event["event"] == "market"));
+}
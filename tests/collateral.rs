@@ -0,0 +1,115 @@
+use polymarket_explorer::analysis::{CollateralMix, CollateralRates};
+use polymarket_explorer::data_sources::{LocalDbSource, MockSource};
+use polymarket_explorer::standard_data::models::Collateral;
+use polymarket_explorer::standard_data::providers::{PositionProvider, TraderStatsProvider, TransactionProvider};
+use polymarket_explorer::testing;
+
+// native usdc rows keep their collateral through the local db and come out converted at its rate, usdc.e rows untouched
+#[tokio::test]
+async fn amounts_come_out_in_usd_per_collateral() {
+    let mock = MockSource::new();
+    let mut transactions = mock.get_all_transactions().await.unwrap();
+    let mut positions = mock.get_all_positions().await.unwrap();
+    for tx in transactions.iter_mut().step_by(2) {
+        tx.collateral = Collateral::Usdc;
+    }
+    positions[0].collateral = Collateral::Usdc;
+
+    let dir = testing::scratch_dir("collateral").unwrap();
+    testing::write_parquet_fixtures(&dir, &mock.get_traders(0).await.unwrap(), &positions, &transactions).await.unwrap();
+    let rates = CollateralRates { usdc_e: 1.0, usdc: 0.5 };
+    let db = LocalDbSource::new(&dir.to_string_lossy()).with_collateral(rates);
+
+    let read = db.get_all_transactions().await.unwrap();
+    let mix = CollateralMix::of(&read, &[]);
+    assert!(mix.is_mixed());
+    assert_eq!(mix.usdc, transactions.len().div_ceil(2));
+    for tx in &read {
+        let stored = transactions.iter().find(|t| t.transaction_hash == tx.transaction_hash && t.token_id == tx.token_id).unwrap();
+        assert_eq!(tx.collateral, stored.collateral);
+        assert!((tx.usdc_amount - stored.usdc_amount * rates.usd(stored.collateral)).abs() < 1e-9);
+    }
+
+    let held = db.get_all_positions().await.unwrap();
+    let converted = held.iter().find(|p| p.trader_address == positions[0].trader_address && p.token_id == positions[0].token_id).unwrap();
+    assert_eq!(converted.collateral, Collateral::Usdc);
+    assert!((converted.avg_entry_price - positions[0].avg_entry_price * 0.5).abs() < 1e-9);
+}
+
+// stats computed in duckdb convert amounts at the same rates as the polars path, both agree on native usdc rows
+#[cfg(feature = "duckdb")]
+#[tokio::test]
+async fn both_backends_compute_traders_in_usd() {
+    use polymarket_explorer::data_sources::QueryBackend;
+    use polymarket_explorer::standard_data::providers::{DataStore, ResolutionProvider};
+
+    let mock = MockSource::new();
+    let mut transactions = mock.get_all_transactions().await.unwrap();
+    for tx in transactions.iter_mut() {
+        tx.collateral = Collateral::Usdc;
+    }
+    let dir = testing::scratch_dir("collateral-backends").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &[], &mock.get_all_positions().await.unwrap(), &transactions).await.unwrap();
+    db.save_resolutions(&mock.get_resolutions().await.unwrap()).await.unwrap();
+
+    let rates = CollateralRates { usdc_e: 1.0, usdc: 0.5 };
+    let path = dir.to_string_lossy();
+    let polars = LocalDbSource::with_backend(&path, QueryBackend::Polars).with_collateral(rates).compute_traders().await.unwrap();
+    let duckdb = LocalDbSource::with_backend(&path, QueryBackend::Duckdb).with_collateral(rates).compute_traders().await.unwrap();
+    let unconverted = LocalDbSource::with_backend(&path, QueryBackend::Polars).compute_traders().await.unwrap();
+
+    assert_eq!(polars.len(), duckdb.len());
+    assert!(polars.iter().zip(&unconverted).any(|(a, b)| (a.total_invested - b.total_invested).abs() > 1e-9));
+    for (a, b) in polars.iter().zip(&duckdb) {
+        assert_eq!(a.trader_address, b.trader_address);
+        assert_eq!(a.total_wins, b.total_wins);
+        assert!((a.total_invested - b.total_invested).abs() < 1e-6, "{} invested", a.trader_address);
+        assert!((a.total_returned - b.total_returned).abs() < 1e-6, "{} returned", a.trader_address);
+    }
+}
+
+// a v1 dump with pandas ints, condition_id and tx_hash reads through the same migrations in duckdb as in polars
+#[cfg(feature = "duckdb")]
+#[tokio::test]
+async fn duckdb_reads_old_dumps_like_polars() {
+    use polars::prelude::*;
+    use polymarket_explorer::data_sources::QueryBackend;
+    use polymarket_explorer::standard_data::models::MarketResolution;
+    use polymarket_explorer::standard_data::providers::DataStore;
+
+    let dir = testing::scratch_dir("duckdb-old-dump").unwrap();
+    let mut old = df!(
+        "block_number" => [10i64, 20, 30],
+        "tx_hash" => ["0xa", "0xb", "0xc"],
+        "trader_address" => ["0x1", "0x1", "0x2"],
+        "token_id" => ["m1-yes", "m2-yes", "m1-no"],
+        "side" => ["YES", "YES", "NO"],
+        "action" => ["BUY", "BUY", "BUY"],
+        "shares" => [10.0, 5.0, 8.0],
+        "usdc_amount" => [4.0, 2.0, 5.0],
+        "condition_id" => ["m1", "m2", "m1"],
+    )
+    .unwrap();
+    ParquetWriter::new(std::fs::File::create(dir.join("transactions.parquet")).unwrap()).finish(&mut old).unwrap();
+
+    let path = dir.to_string_lossy();
+    let resolution = MarketResolution {
+        condition_id: "m1".to_string(),
+        outcome: "YES".to_string(),
+        resolution_block: 100,
+        yes_token_id: "m1-yes".to_string(),
+        no_token_id: "m1-no".to_string(),
+    };
+    LocalDbSource::new(&path).save_resolutions(&[resolution]).await.unwrap();
+
+    let polars = LocalDbSource::with_backend(&path, QueryBackend::Polars).compute_traders().await.unwrap();
+    let duckdb = LocalDbSource::with_backend(&path, QueryBackend::Duckdb).compute_traders().await.unwrap();
+    assert_eq!(polars.len(), 2);
+    assert_eq!(polars.len(), duckdb.len());
+    for (a, b) in polars.iter().zip(&duckdb) {
+        assert_eq!(a.trader_address, b.trader_address);
+        assert_eq!(a.total_markets_entered, b.total_markets_entered);
+        assert_eq!(a.total_wins, b.total_wins);
+        assert!((a.total_returned - b.total_returned).abs() < 1e-9, "{} returned", a.trader_address);
+    }
+}
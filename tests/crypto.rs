@@ -0,0 +1,101 @@
+use chrono::TimeDelta;
+use polymarket_explorer::adapters::{CapturedResponse, HttpClient, RawCapture, SpotPriceFeed};
+use polymarket_explorer::analysis::crypto::{self, StrikeKind};
+use polymarket_explorer::cli::handle_crypto;
+use polymarket_explorer::clock;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::standard_data::providers::MarketMetadataProvider;
+use polymarket_explorer::testing::{self, FakeSource};
+use serde_json::json;
+
+// asset, side and strike come out of the usual question shapes, anything else is left alone
+#[test]
+fn crypto_questions_are_parsed() {
+    let parsed = crypto::parse_question("Will the price of Bitcoin be above $120,000 on October 31?").unwrap();
+    assert_eq!((parsed.asset, parsed.kind, parsed.strike), ("BTC", StrikeKind::Above, 120_000.0));
+    let parsed = crypto::parse_question("Will ETH dip to $3.5k in October?").unwrap();
+    assert_eq!((parsed.asset, parsed.kind, parsed.strike), ("ETH", StrikeKind::Dip, 3_500.0));
+    let parsed = crypto::parse_question("Will Solana reach $300 by December 31?").unwrap();
+    assert_eq!((parsed.asset, parsed.kind, parsed.strike), ("SOL", StrikeKind::Reach, 300.0));
+
+    assert!(crypto::parse_question("Bitcoin Up or Down on October 20?").is_none());
+    assert!(crypto::parse_question("Will turnout exceed 60%?").is_none());
+}
+
+// at the money is about a coin flip, touching is likelier than closing past the strike,
+// and at the end date only the side of the strike counts
+#[test]
+fn fair_probability_behaves_like_a_binary_option() {
+    let above = crypto::fair_probability(StrikeKind::Above, 100.0, 100.0, 0.6, 30.0 / 365.0);
+    assert!(above > 0.45 && above < 0.5);
+    let far = crypto::fair_probability(StrikeKind::Above, 100.0, 200.0, 0.6, 30.0 / 365.0);
+    assert!(far < 0.01);
+    let touch = crypto::fair_probability(StrikeKind::Reach, 100.0, 120.0, 0.6, 30.0 / 365.0);
+    assert!(touch > crypto::fair_probability(StrikeKind::Above, 100.0, 120.0, 0.6, 30.0 / 365.0));
+    let below = crypto::fair_probability(StrikeKind::Below, 100.0, 120.0, 0.6, 30.0 / 365.0);
+    assert!((below + crypto::fair_probability(StrikeKind::Above, 100.0, 120.0, 0.6, 30.0 / 365.0) - 1.0).abs() < 1e-9);
+    assert_eq!(crypto::fair_probability(StrikeKind::Above, 101.0, 100.0, 0.6, 0.0), 1.0);
+    assert_eq!(crypto::fair_probability(StrikeKind::Dip, 101.0, 100.0, 0.6, 0.0), 0.0);
+
+    // closes moving 1% up then 1% down have one daily deviation of about 1.15%
+    let closes = [100.0, 101.0, 99.99, 100.9899, 99.980001];
+    let volatility = crypto::realized_volatility(&closes).unwrap();
+    assert!((volatility / 365f64.sqrt() - 0.01155).abs() < 1e-4);
+    assert!(crypto::realized_volatility(&closes[..2]).is_none());
+}
+
+fn capture(url: &str, body: serde_json::Value) -> CapturedResponse {
+    CapturedResponse {
+        method: "GET".to_string(),
+        url: url.to_string(),
+        request_body: None,
+        status: 200,
+        captured_at: 0,
+        elapsed_ms: 0,
+        body: body.to_string(),
+    }
+}
+
+// spot and closes come from the feed, an out of the money strike is worth less than at the money and loses value by the day
+#[tokio::test]
+async fn crypto_markets_are_priced_off_the_spot_feed() {
+    let dir = testing::scratch_dir("crypto").unwrap();
+    let capture_dir = RawCapture::record(&dir).unwrap();
+    capture_dir.save(&capture("http://127.0.0.1:8080/products/BTC-USD/ticker", json!({"price": "100000.00"}))).unwrap();
+    // newest first, alternating 2% moves
+    let candles: Vec<serde_json::Value> = (0..31)
+        .map(|i| {
+            let close = if i % 2 == 0 { 100_000.0 } else { 102_000.0 };
+            json!([1_760_000_000 - i * 86_400, close, close, close, close, 10.0])
+        })
+        .collect();
+    capture_dir.save(&capture("http://127.0.0.1:8080/products/BTC-USD/candles?granularity=86400", json!(candles))).unwrap();
+
+    let client = HttpClient::builder().raw_capture(RawCapture::replay(&dir).unwrap()).build().unwrap();
+    let feed = SpotPriceFeed::new(client).with_url("http://127.0.0.1:8080");
+    assert_eq!(feed.spot("BTC").await.unwrap(), 100_000.0);
+    let closes = feed.daily_closes("BTC", 30).await.unwrap();
+    assert_eq!(closes.len(), 30);
+    let volatility = crypto::realized_volatility(&closes).unwrap();
+
+    let mut group = MockSource::new().get_market_group("btc-above").await.unwrap();
+    group.slug = "btc-above".to_string();
+    for (market, strike) in group.markets.iter_mut().zip(["100,000", "110,000", "120,000"]) {
+        market.question = format!("Will the price of Bitcoin be above ${} on October 31?", strike);
+        market.end_date = Some(clock::now() + TimeDelta::days(14));
+        market.outcome_prices = vec!["0.3".to_string(), "0.7".to_string()];
+    }
+
+    let at_the_money = crypto::parse_question(&group.markets[0].question).unwrap();
+    let out_of_the_money = crypto::parse_question(&group.markets[1].question).unwrap();
+    let atm = crypto::fair_value(&group.markets[0], &at_the_money, 100_000.0, volatility, clock::now()).unwrap();
+    let otm = crypto::fair_value(&group.markets[1], &out_of_the_money, 100_000.0, volatility, clock::now()).unwrap();
+    assert!(otm.fair_yes < atm.fair_yes);
+    assert!((otm.distance + 1.0 / 11.0).abs() < 1e-9);
+    assert!(otm.sigmas.unwrap() < 0.0);
+    assert!(otm.decay_per_day < 0.0);
+    assert!((atm.edge - (atm.fair_yes - 0.3)).abs() < 1e-12);
+
+    let fake = FakeSource::new().with_group(group);
+    handle_crypto("btc-above", 30, None, &feed, &fake).await.unwrap();
+}
@@ -0,0 +1,53 @@
+use polymarket_explorer::adapters::{CtfEvent, CtfOperation};
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::ingest;
+use polymarket_explorer::standard_data::models::{Collateral, Transaction};
+use polymarket_explorer::standard_data::providers::{MarketMetadataProvider, ResolutionProvider};
+
+// a wallet that splits, sells the side it doesn't want and redeems the other has a cost basis and a payout
+// with fills alone it sold shares it never bought and the redemption never shows up
+#[tokio::test]
+async fn split_sell_and_redeem_book_the_whole_round_trip() {
+    let mock = MockSource::new();
+    let resolution = mock.get_resolutions().await.unwrap().into_iter().next().unwrap();
+    let market = mock.get_markets_by_condition_ids(std::slice::from_ref(&resolution.condition_id)).await.unwrap().remove(0);
+    let (won, lost) = if resolution.outcome == "YES" { ("YES", "NO") } else { ("NO", "YES") };
+
+    let wallet = "0x00000000000000000000000000000000000000aa";
+    let event = |operation, amount, block| CtfEvent {
+        operation,
+        account: wallet.to_string(),
+        condition_id: market.condition_id.clone(),
+        amount,
+        collateral: Collateral::UsdcE,
+        block,
+        transaction_hash: format!("0x{:064x}", block),
+        log_index: 0,
+    };
+    let events = [event(CtfOperation::Split, 100.0, 1), event(CtfOperation::Redeem, 100.0, 3)];
+    let mut transactions = ingest::ctf_transactions(&events, &market, Some(&resolution.outcome));
+    assert_eq!(transactions.len(), 3);
+    assert!(transactions.iter().all(|tx| !tx.is_fill()));
+
+    let sell = Transaction {
+        block_number: 2,
+        transaction_hash: format!("0x{:064x}", 2),
+        log_index: Some(0),
+        trader_address: wallet.to_string(),
+        token_id: if lost == "YES" { market.yes_token_id.clone() } else { market.no_token_id.clone() },
+        side: lost.to_string(),
+        action: "SELL".to_string(),
+        shares: 100.0,
+        usdc_amount: 40.0,
+        market_id: market.condition_id.clone(),
+        timestamp: None,
+        collateral: Collateral::UsdcE,
+    };
+    transactions.push(sell);
+    assert!(transactions.iter().any(|tx| tx.action == "REDEEM" && tx.side == won));
+
+    let trader = ingest::compute_trader_stats(&transactions, std::slice::from_ref(&resolution)).remove(0);
+    assert!((trader.total_invested - 100.0).abs() < 1e-9);
+    assert!((trader.total_returned - 140.0).abs() < 1e-9);
+    assert_eq!(trader.total_wins, 1);
+}
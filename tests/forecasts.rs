@@ -0,0 +1,85 @@
+use polymarket_explorer::adapters::{forecast_file, ForecastFile, HttpClient};
+use polymarket_explorer::analysis::order_flow::DEFAULT_OFI_WINDOW_HOURS;
+use polymarket_explorer::analysis::vwap::DEFAULT_VWAP_WINDOWS;
+use polymarket_explorer::analysis::CostBasis;
+use polymarket_explorer::cli::{handle_analyze, AnalysisBus, AnalysisEvent, AnalysisSink};
+use polymarket_explorer::config::Config;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::standard_data::providers::MarketMetadataProvider;
+use polymarket_explorer::testing;
+use std::sync::{Arc, Mutex};
+
+// quoted fields, percentages and missing optional cells all read, a bad row names its line
+#[test]
+fn forecast_csv_rows_are_parsed() {
+    let text = "Market,Probability,Source,Updated_At\n\
+        0xabc,0.62,\"Model, v2\",2026-10-01\n\
+        \n\
+        some-market-slug,41%,,\n";
+    let forecasts = forecast_file::parse_forecasts(text, "forecasts.csv").unwrap();
+    assert_eq!(forecasts.len(), 2);
+    assert_eq!((forecasts[0].market.as_str(), forecasts[0].source.as_str()), ("0xabc", "Model, v2"));
+    assert_eq!(forecasts[0].updated_at.unwrap().to_rfc3339(), "2026-10-01T00:00:00+00:00");
+    assert!((forecasts[1].probability - 0.41).abs() < 1e-12);
+    assert_eq!(forecasts[1].source, "model");
+    assert!(forecasts[1].updated_at.is_none());
+
+    let error = forecast_file::parse_forecasts("market,probability\nx,1.4\n", "forecasts.csv").unwrap_err();
+    assert!(error.to_string().contains("line 2"), "{}", error);
+    assert!(forecast_file::parse_forecasts("slug,price\nx,0.5\n", "forecasts.csv").is_err());
+}
+
+#[derive(Clone, Default)]
+struct RecordingSink {
+    events: Arc<Mutex<Vec<serde_json::Value>>>,
+}
+
+#[async_trait::async_trait]
+impl AnalysisSink for RecordingSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        self.events.lock().unwrap().push(serde_json::json!(event));
+    }
+}
+
+// forecasts are matched to the analyzed market by slug and shown with its price and the smart money probability
+#[tokio::test]
+async fn analyze_sets_forecasts_against_market_and_smart_money() {
+    let mock = MockSource::new();
+    let primary = mock.get_market_group("mock-event").await.unwrap().markets[0].clone();
+    let dir = testing::scratch_dir("forecasts").unwrap();
+    let path = dir.join("forecasts.csv");
+    std::fs::write(&path, format!("market,probability,source\n{},0.7,polls\nother-market,0.2,polls\n", primary.slug)).unwrap();
+    let forecasts = ForecastFile::load(path.to_str().unwrap(), &HttpClient::new()).await.unwrap();
+
+    let recorder = RecordingSink::default();
+    handle_analyze(
+        "mock-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        Some(&forecasts),
+        None,
+        &mock.capabilities(),
+        None,
+        &Config::default(),
+        &mock,
+        &mock,
+        &mock,
+        &mock,
+        &[],
+        AnalysisBus::new().subscribe(recorder.clone()),
+    )
+    .await
+    .unwrap();
+
+    let events = recorder.events.lock().unwrap();
+    let table = events.iter().find(|event| event["event"] == "forecasts").unwrap();
+    assert_eq!(table["forecasts"].as_array().unwrap().len(), 1);
+    assert_eq!(table["forecasts"][0]["source"], "polls");
+    assert_eq!(table["market_price"], primary.outcome_prices[0].parse::<f64>().unwrap());
+    let implied = events.iter().find(|event| event["event"] == "implied_returns").unwrap();
+    assert_eq!(table["smart_money"], implied["implied"]["smart_probability"]);
+}
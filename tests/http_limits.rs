@@ -0,0 +1,64 @@
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use polymarket_explorer::adapters::HttpClient;
+use polymarket_explorer::error::{AppError, HttpError};
+use polymarket_explorer::testing::{CannedResponse, TestServer};
+use serde_json::Value;
+use std::io::Write;
+
+// one gzipped json body for every request
+async fn gzip_server(json: &str) -> TestServer {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(json.as_bytes()).unwrap();
+    let response = CannedResponse::status(200)
+        .with_header("content-type", "application/json")
+        .with_header("content-encoding", "gzip")
+        .with_body(encoder.finish().unwrap());
+    TestServer::start(vec![response]).await.unwrap()
+}
+
+// gzip bodies are decoded before parsing, and the size limit counts the decoded bytes,
+// so a small compressed payload that inflates past it still fails
+#[tokio::test]
+async fn gzip_bodies_are_decoded_and_capped_after_decoding() {
+    let server = gzip_server(r#"{"ok":true}"#).await;
+    let value: Value = HttpClient::new().get(server.url()).await.unwrap();
+    assert_eq!(value["ok"], true);
+
+    let padding = "x".repeat(64 * 1024);
+    let server = gzip_server(&format!(r#"{{"pad":"{}"}}"#, padding)).await;
+    let client = HttpClient::builder().max_response_bytes(16 * 1024).build().unwrap();
+    let error = client.get::<Value>(server.url()).await.unwrap_err();
+    assert!(matches!(error, AppError::Http(HttpError::TooLarge { limit: 16384, .. })));
+    assert_eq!(error.code(), "http.too_large");
+}
+
+// {} with each status in turn, the last one repeating
+async fn status_server(statuses: &[u16]) -> TestServer {
+    TestServer::start(statuses.iter().map(|status| CannedResponse::json("{}").with_status(*status)).collect()).await.unwrap()
+}
+
+// a GET that hits a 503 or a 429 is tried again and each retry shows up in the stats
+#[tokio::test]
+async fn transient_get_failures_are_retried_and_counted() {
+    let server = status_server(&[503, 429, 200]).await;
+    let client = HttpClient::new();
+    let _: Value = client.get(server.url()).await.unwrap();
+    assert_eq!(server.request_count(), 3);
+    let stats = client.stats().snapshot();
+    assert_eq!((stats.requests, stats.failures, stats.retries), (1, 0, 2));
+}
+
+// posts aren't sent twice and a client without retries gives up on the first error
+#[tokio::test]
+async fn posts_and_clients_without_retries_get_one_try() {
+    let server = status_server(&[503]).await;
+    assert!(HttpClient::new().post_json::<_, Value>(server.url(), &serde_json::json!({})).await.is_err());
+    assert_eq!(server.request_count(), 1);
+
+    let server = status_server(&[503]).await;
+    let client = HttpClient::builder().retries(0).build().unwrap();
+    assert!(client.get::<Value>(server.url()).await.is_err());
+    assert_eq!(server.request_count(), 1);
+    assert_eq!(client.stats().snapshot().retries, 0);
+}
@@ -0,0 +1,23 @@
+use polymarket_explorer::adapters::HttpClient;
+use polymarket_explorer::testing::{CannedResponse, TestServer};
+use serde_json::Value;
+use std::time::Duration;
+
+// clones of one client share its pool, so a run's requests go over one warm connection
+// unless the pool is switched off
+#[tokio::test]
+async fn requests_reuse_pooled_connections() {
+    let server = TestServer::keep_alive(vec![CannedResponse::json("{}")]).await.unwrap();
+    let client = HttpClient::builder().build().unwrap();
+    for i in 0..5 {
+        let _: Value = client.clone().get(&format!("{}/events/{}", server.url(), i)).await.unwrap();
+    }
+    assert_eq!(server.connections(), 1);
+
+    let server = TestServer::keep_alive(vec![CannedResponse::json("{}")]).await.unwrap();
+    let client = HttpClient::builder().pool_idle_timeout(Duration::ZERO).build().unwrap();
+    for i in 0..5 {
+        let _: Value = client.get(&format!("{}/events/{}", server.url(), i)).await.unwrap();
+    }
+    assert_eq!(server.connections(), 5);
+}
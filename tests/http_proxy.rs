@@ -0,0 +1,73 @@
+use polymarket_explorer::adapters::HttpClient;
+use polymarket_explorer::testing::{CannedResponse, TestServer};
+use serde_json::Value;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// minimal no-auth socks5 relay on a loopback port, counts the connections it tunnels
+async fn socks5_relay() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("socks5://{}", listener.local_addr().unwrap());
+    let tunnels = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&tunnels);
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let counted = Arc::clone(&counted);
+            tokio::spawn(async move {
+                if let Ok((mut client, mut upstream)) = handshake(socket).await {
+                    counted.fetch_add(1, Ordering::SeqCst);
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+                }
+            });
+        }
+    });
+    (url, tunnels)
+}
+
+// greeting, then a connect request to an ipv4 or domain target, answered with success once it's dialed
+async fn handshake(mut client: TcpStream) -> std::io::Result<(TcpStream, TcpStream)> {
+    let mut head = [0u8; 2];
+    client.read_exact(&mut head).await?;
+    let mut methods = vec![0u8; head[1] as usize];
+    client.read_exact(&mut methods).await?;
+    client.write_all(&[5, 0]).await?;
+
+    let mut request = [0u8; 4];
+    client.read_exact(&mut request).await?;
+    let host = match request[3] {
+        1 => {
+            let mut ip = [0u8; 4];
+            client.read_exact(&mut ip).await?;
+            std::net::Ipv4Addr::from(ip).to_string()
+        }
+        3 => {
+            let mut len = [0u8; 1];
+            client.read_exact(&mut len).await?;
+            let mut name = vec![0u8; len[0] as usize];
+            client.read_exact(&mut name).await?;
+            String::from_utf8_lossy(&name).to_string()
+        }
+        _ => return Err(std::io::Error::other("unsupported address type")),
+    };
+    let mut port = [0u8; 2];
+    client.read_exact(&mut port).await?;
+
+    let upstream = TcpStream::connect((host.as_str(), u16::from_be_bytes(port))).await?;
+    client.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await?;
+    Ok((client, upstream))
+}
+
+// a socks5 proxy url is accepted and requests go through the tunnel to the server
+#[tokio::test]
+async fn requests_go_through_a_socks5_proxy() {
+    let server = TestServer::start(vec![CannedResponse::json(r#"{"ok":true}"#)]).await.unwrap();
+    let (proxy, tunnels) = socks5_relay().await;
+    let client = HttpClient::builder().proxy(proxy).build().unwrap();
+
+    let body: Value = client.get(&format!("{}/events", server.url())).await.unwrap();
+    assert_eq!(body["ok"], true);
+    assert_eq!(tunnels.load(Ordering::SeqCst), 1);
+    assert_eq!(server.requests()[0].path, "/events");
+}
@@ -0,0 +1,121 @@
+use polars::prelude::*;
+use polymarket_explorer::cli::handle_ingest_trades;
+use polymarket_explorer::adapters::{ParquetReader, ParquetWriter};
+use polymarket_explorer::data_sources::{LocalDbSource, MockSource};
+use polymarket_explorer::ingest;
+use polymarket_explorer::standard_data::providers::{DataStore, MarketMetadataProvider, PositionProvider, ResolutionProvider, TransactionProvider};
+use polymarket_explorer::testing::{self, FakeSource};
+use std::collections::HashSet;
+
+// a second pull that overlaps the first adds only the trades it hadn't seen and leaves one position per wallet and token
+#[tokio::test]
+async fn reingesting_an_overlapping_batch_keeps_one_position_per_holding() {
+    let mock = MockSource::new();
+    let group = mock.get_market_group("fake-event").await.unwrap();
+    let market = &group.markets[0];
+    let trades = mock.get_market_transactions(&market.condition_id).await.unwrap();
+    let slugs = [group.slug.clone()];
+
+    let dir = testing::scratch_dir("ingest-overlap").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &[], &[], &[]).await.unwrap();
+    let first = FakeSource::new().with_group(group.clone()).with_transactions(trades[..trades.len() * 2 / 3].to_vec());
+    handle_ingest_trades(&slugs, 100_000, false, &first, &first, &db, &db).await.unwrap();
+    let second = FakeSource::new().with_group(group.clone()).with_transactions(trades[trades.len() / 3..].to_vec());
+    handle_ingest_trades(&slugs, 100_000, true, &second, &second, &db, &db).await.unwrap();
+
+    assert_eq!(db.get_market_transactions(&market.condition_id).await.unwrap().len(), trades.len());
+    let positions = db.get_positions(&market.condition_id).await.unwrap();
+    let holdings: HashSet<(&str, &str)> = positions.iter().map(|p| (p.trader_address.as_str(), p.token_id.as_str())).collect();
+    assert_eq!(holdings.len(), positions.len());
+
+    let expected: Vec<_> = ingest::positions_from_transactions(&trades).into_iter().filter(|p| p.shares_held > 0.0).collect();
+    assert_eq!(positions.len(), expected.len());
+    for want in &expected {
+        let got = positions.iter().find(|p| p.trader_address == want.trader_address && p.token_id == want.token_id).unwrap();
+        assert!((got.shares_held - want.shares_held).abs() < 1e-9, "{} shares", want.trader_address);
+    }
+}
+
+// a resolution is placed at the block gamma closed the market at, which comes after the last stored trade
+// without a block index the local db refuses to place it rather than guess from its trades
+#[tokio::test]
+async fn resolutions_are_placed_at_the_close_not_the_last_trade() {
+    let mock = MockSource::new();
+    let resolution = mock.get_resolutions().await.unwrap().remove(0);
+    let market = mock.get_markets_by_condition_ids(std::slice::from_ref(&resolution.condition_id)).await.unwrap().remove(0);
+    let mut group = mock.get_market_group("mock-event").await.unwrap();
+    group.markets = vec![market.clone()];
+
+    let dir = testing::scratch_dir("ingest-resolution-close").unwrap();
+    testing::write_gamma_event(&dir.join("gamma"), &group).unwrap();
+    let gamma = testing::replay_source(&dir.join("gamma")).unwrap();
+    let closed_at = gamma.get_market_group(&group.slug).await.unwrap().markets[0].closed_time.unwrap();
+    assert_eq!(Some(closed_at), market.closed_time);
+
+    let placed = mock.blocks_at(&[closed_at.timestamp()]).await.unwrap();
+    assert_eq!(placed, [Some(resolution.resolution_block)]);
+    let last_trade = mock.get_market_transactions(&market.condition_id).await.unwrap().iter().map(|tx| tx.block_number).max().unwrap();
+    assert!(last_trade < resolution.resolution_block);
+
+    let db = testing::write_parquet_fixtures(&dir.join("processed_data"), &[], &[], &[]).await.unwrap();
+    assert_eq!(db.blocks_at(&[closed_at.timestamp()]).await.unwrap_err().code(), "source.unsupported");
+}
+
+// a hive partitioned positions table is upserted in place, the market scan sees the new rows and other markets are untouched
+#[tokio::test]
+async fn upserts_keep_a_partitioned_positions_table_partitioned() {
+    let mock = MockSource::new();
+    let positions = mock.get_all_positions().await.unwrap();
+    let market_id = positions[0].market_id.clone();
+
+    let dir = testing::scratch_dir("ingest-partitioned-positions").unwrap();
+    let single = dir.join("single");
+    LocalDbSource::new(&single.to_string_lossy()).upsert_positions(&positions).await.unwrap();
+    let df = ParquetReader::new(&single.to_string_lossy()).read("positions.parquet").unwrap();
+    let partitioned = dir.join("partitioned");
+    ParquetWriter::new(&partitioned.to_string_lossy()).append_partitioned("positions.parquet", "market_id", &df).unwrap();
+
+    let db = LocalDbSource::new(&partitioned.to_string_lossy());
+    let before = db.get_all_positions().await.unwrap().len();
+    let mut changed = db.get_positions(&market_id).await.unwrap()[0].clone();
+    changed.shares_held += 5.0;
+    db.upsert_positions(std::slice::from_ref(&changed)).await.unwrap();
+
+    assert!(!partitioned.join("positions.parquet").exists());
+    let market_dir = partitioned.join("positions").join(format!("market_id={}", market_id));
+    assert_eq!(std::fs::read_dir(&market_dir).unwrap().count(), 1);
+    let stored = db.get_positions(&market_id).await.unwrap();
+    let got = stored.iter().find(|p| p.trader_address == changed.trader_address && p.token_id == changed.token_id).unwrap();
+    assert!((got.shares_held - changed.shares_held).abs() < 1e-9);
+    assert_eq!(db.get_all_positions().await.unwrap().len(), before);
+}
+
+// ingesting into a single file dump with columns this tool doesn't write leaves them in place for the rows they came with
+#[tokio::test]
+async fn ingesting_keeps_extra_columns_of_a_dump() {
+    let mock = MockSource::new();
+    let group = mock.get_market_group("fake-event").await.unwrap();
+    let trades = mock.get_market_transactions(&group.markets[0].condition_id).await.unwrap();
+    let (old, new) = trades.split_at(trades.len() / 2);
+    let slugs = [group.slug.clone()];
+
+    let dir = testing::scratch_dir("ingest-extra-columns").unwrap();
+    let partitioned = dir.join("partitioned");
+    testing::write_parquet_fixtures(&partitioned, &[], &ingest::positions_from_transactions(old), old).await.unwrap();
+    let single = dir.join("single");
+    for table in ["transactions.parquet", "positions.parquet"] {
+        let mut df = ParquetReader::new(&partitioned.to_string_lossy()).read(table).unwrap();
+        df.with_column(Column::new("vendor_id".into(), (0..df.height() as i64).collect::<Vec<_>>())).unwrap();
+        ParquetWriter::new(&single.to_string_lossy()).write(table, &mut df).unwrap();
+    }
+
+    let db = LocalDbSource::new(&single.to_string_lossy());
+    let source = FakeSource::new().with_group(group.clone()).with_transactions(new.to_vec());
+    handle_ingest_trades(&slugs, 100_000, false, &source, &source, &db, &db).await.unwrap();
+
+    let stored = ParquetReader::new(&single.to_string_lossy()).read("transactions.parquet").unwrap();
+    assert_eq!(stored.height(), trades.len());
+    assert_eq!(stored.column("vendor_id").unwrap().null_count(), new.len());
+    let positions = ParquetReader::new(&single.to_string_lossy()).read("positions.parquet").unwrap();
+    assert!(positions.column("vendor_id").is_ok());
+}
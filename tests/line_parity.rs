@@ -0,0 +1,90 @@
+use polymarket_explorer::adapters::{CapturedResponse, HttpClient, OddsApiClient, RawCapture};
+use polymarket_explorer::analysis::line_parity::{self, DEFAULT_PARITY_THRESHOLD};
+use polymarket_explorer::cli::handle_parity;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::standard_data::models::MarketGroup;
+use polymarket_explorer::standard_data::providers::{ExternalOddsProvider, MarketMetadataProvider};
+use polymarket_explorer::testing::{self, FakeSource};
+use serde_json::json;
+
+// a single moneyline market, the way polymarket lists most games
+async fn moneyline() -> MarketGroup {
+    let mut group = MockSource::new().get_market_group("celtics-lakers").await.unwrap();
+    group.slug = "celtics-lakers".to_string();
+    group.title = "Celtics vs. Lakers".to_string();
+    group.markets.truncate(1);
+    group.markets[0].question = "Celtics vs. Lakers".to_string();
+    group.markets[0].outcomes = vec!["Celtics".to_string(), "Lakers".to_string()];
+    group.markets[0].outcome_prices = vec!["0.62".to_string(), "0.38".to_string()];
+    group
+}
+
+fn bookmaker(title: &str, celtics: f64, lakers: f64) -> serde_json::Value {
+    json!({
+        "key": title.to_lowercase(),
+        "title": title,
+        "last_update": "2026-10-17T12:00:00Z",
+        "markets": [{"key": "h2h", "outcomes": [
+            {"name": "Boston Celtics", "price": celtics},
+            {"name": "Los Angeles Lakers", "price": lakers},
+        ]}],
+    })
+}
+
+// the bookmakers' lines come back through the odds api adapter, the game polymarket lists is picked out of the sport
+// and each side is held against the bookmakers' margin free consensus
+#[tokio::test]
+async fn parity_flags_prices_far_from_the_books() {
+    let dir = testing::scratch_dir("line-parity").unwrap();
+    let body = json!([
+        {
+            "id": "other", "sport_title": "NBA", "commence_time": "2026-10-18T00:00:00Z",
+            "home_team": "New York Knicks", "away_team": "Miami Heat",
+            "bookmakers": [{"key": "dk", "title": "DraftKings", "markets": [{"key": "h2h", "outcomes": [
+                {"name": "New York Knicks", "price": 1.5}, {"name": "Miami Heat", "price": 2.6},
+            ]}]}],
+        },
+        {
+            "id": "game", "sport_title": "NBA", "commence_time": "2026-10-19T00:00:00Z",
+            "home_team": "Boston Celtics", "away_team": "Los Angeles Lakers",
+            "bookmakers": [bookmaker("DraftKings", 1.80, 2.10), bookmaker("FanDuel", 1.75, 2.20)],
+        },
+    ]);
+    RawCapture::record(&dir).unwrap().save(&CapturedResponse {
+        method: "GET".to_string(),
+        url: "http://127.0.0.1:8080/v4/sports/basketball_nba/odds?apiKey=key&regions=us&markets=h2h&oddsFormat=decimal".to_string(),
+        request_body: None,
+        status: 200,
+        captured_at: 0,
+        elapsed_ms: 0,
+        body: body.to_string(),
+    }).unwrap();
+
+    let client = HttpClient::builder().raw_capture(RawCapture::replay(&dir).unwrap()).build().unwrap();
+    let odds = OddsApiClient::new(client, "key").with_url("http://127.0.0.1:8080/v4").with_regions("us");
+    let events = odds.get_external_odds("basketball_nba").await.unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].title, "Boston Celtics vs Los Angeles Lakers");
+    assert_eq!(events[1].lines.len(), 4);
+
+    let group = moneyline().await;
+    let report = line_parity::compare(&group, &events, DEFAULT_PARITY_THRESHOLD).unwrap();
+    assert_eq!(report.event, "Boston Celtics vs Los Angeles Lakers");
+    assert_eq!(report.lines.len(), 2);
+
+    let celtics = &report.lines[0];
+    assert_eq!((celtics.outcome.as_str(), celtics.external_outcome.as_str()), ("Celtics", "Boston Celtics"));
+    // 1/1.80 and 1/1.75 scaled by each book's total, then the median of the two
+    let draftkings = (1.0 / 1.80) / (1.0 / 1.80 + 1.0 / 2.10);
+    let fanduel = (1.0 / 1.75) / (1.0 / 1.75 + 1.0 / 2.20);
+    assert!((celtics.consensus - (draftkings + fanduel) / 2.0).abs() < 1e-9);
+    assert_eq!(celtics.bookmakers, 2);
+    assert_eq!((celtics.best_odds, celtics.best_bookmaker.as_str()), (1.80, "DraftKings"));
+    assert!(celtics.flagged && celtics.gap() > 0.0);
+    assert!(report.lines[1].flagged && report.lines[1].gap() < 0.0);
+    assert!(line_parity::compare(&group, &events, 0.2).unwrap().lines.iter().all(|line| !line.flagged));
+
+    let fake = FakeSource::new().with_group(group);
+    handle_parity("celtics-lakers", "basketball_nba", DEFAULT_PARITY_THRESHOLD, Some(&odds), &fake).await.unwrap();
+    assert!(handle_parity("celtics-lakers", "basketball_nba", DEFAULT_PARITY_THRESHOLD, None, &fake).await.is_err());
+}
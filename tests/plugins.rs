@@ -0,0 +1,136 @@
+#![cfg(feature = "plugins")]
+
+use polymarket_explorer::adapters::wasm_plugin::{self, WasmAnalyzer};
+use polymarket_explorer::analysis::order_flow::DEFAULT_OFI_WINDOW_HOURS;
+use polymarket_explorer::analysis::vwap::DEFAULT_VWAP_WINDOWS;
+use polymarket_explorer::analysis::{Analyzer, CostBasis};
+use polymarket_explorer::cli::{handle_analyze, AnalysisBus, AnalysisEvent, AnalysisSink};
+use polymarket_explorer::config::Config;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::testing;
+use serde_json::{json, Value};
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+// alloc grows memory past what's there and hands out the new pages
+const ALLOC: &str = r#"
+    (memory (export "memory") 1)
+    (func (export "alloc") (param $len i32) (result i32)
+        (local $base i32)
+        (local.set $base (i32.mul (memory.size) (i32.const 65536)))
+        (drop (memory.grow (i32.add (i32.div_u (local.get $len) (i32.const 65536)) (i32.const 1))))
+        (local.get $base))
+"#;
+
+// hands its input straight back, the output is the AnalyzerInput json
+fn echo() -> String {
+    format!(r#"(module {}
+        (func (export "analyze") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32)) (i64.extend_i32_u (local.get $len)))))"#, ALLOC)
+}
+
+// the same answer whatever it's given
+fn constant(output: &str) -> String {
+    format!(r#"(module {}
+        (data (i32.const 16) "{}")
+        (func (export "analyze") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const {}))))"#, ALLOC, output.replace('"', "\\\""), output.len())
+}
+
+fn spin() -> String {
+    format!(r#"(module {}
+        (func (export "analyze") (param i32 i32) (result i64)
+            (loop $forever (br $forever))
+            (i64.const 0)))"#, ALLOC)
+}
+
+#[derive(Clone, Default)]
+struct RecordingSink {
+    events: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait::async_trait]
+impl AnalysisSink for RecordingSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        self.events.lock().unwrap().push(json!(event));
+    }
+}
+
+async fn analyze_with(analyzers: Vec<Box<dyn Analyzer>>) -> Vec<Value> {
+    let mock = MockSource::new();
+    let recorder = RecordingSink::default();
+    handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &mock.capabilities(),
+        None,
+        &Config::default(),
+        &mock,
+        &mock,
+        &mock,
+        &mock,
+        &analyzers,
+        AnalysisBus::new().subscribe(recorder.clone()),
+    )
+    .await
+    .unwrap();
+    recorder.events.lock().unwrap().clone()
+}
+
+// a plugin gets the market and everything fetched for it, its output is a section of the report
+#[tokio::test]
+async fn plugins_see_the_analyzed_market_and_add_a_section() {
+    let echo = WasmAnalyzer::from_bytes("echo", echo().as_bytes()).unwrap();
+    let edge = WasmAnalyzer::from_bytes("edge", constant(r#"{"edge": 0.12}"#).as_bytes()).unwrap();
+    let events = analyze_with(vec![Box::new(echo), Box::new(edge)]).await;
+
+    let sections: Vec<&Value> = events.iter().filter(|event| event["event"] == "analyzer").collect();
+    assert_eq!(sections.len(), 2);
+    assert_eq!(sections[0]["name"], "echo");
+    let input = &sections[0]["output"];
+    let primary = events.iter().find(|event| event["event"] == "market").unwrap();
+    assert_eq!(input["market"]["condition_id"], primary["market"]["condition_id"]);
+    assert!(!input["positions"].as_array().unwrap().is_empty());
+    assert!(!input["transactions"].as_array().unwrap().is_empty());
+    assert_eq!(sections[1]["output"], json!({"edge": 0.12}));
+}
+
+// a plugin that never returns runs out of fuel, its section is marked unavailable and the run goes on
+#[tokio::test]
+async fn runaway_plugins_are_stopped() {
+    let spin = WasmAnalyzer::from_bytes("spin", spin().as_bytes()).unwrap();
+    let events = analyze_with(vec![Box::new(spin)]).await;
+    let unavailable = events.iter().find(|event| event["event"] == "unavailable").unwrap();
+    assert_eq!(unavailable["section"], "spin");
+    assert!(events.iter().any(|event| event["event"] == "vwap"));
+}
+
+// the sandbox hands out nothing, a module asking for a host function isn't loaded
+#[test]
+fn plugins_with_imports_are_refused() {
+    let wat = format!(r#"(module (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32))) {}
+        (func (export "analyze") (param i32 i32) (result i64) (i64.const 0)))"#, ALLOC);
+    let error = WasmAnalyzer::from_bytes("sneaky", wat.as_bytes()).err().unwrap();
+    assert!(error.to_string().contains("fd_write"), "{}", error);
+}
+
+// every .wasm in the dir by name, other files are left alone and a missing dir has no plugins
+#[test]
+fn plugins_load_from_a_dir_in_name_order() {
+    let dir = testing::scratch_dir("plugins").unwrap();
+    fs::write(dir.join("b_second.wasm"), constant("2")).unwrap();
+    fs::write(dir.join("a_first.wasm"), constant("1")).unwrap();
+    fs::write(dir.join("notes.txt"), "not a plugin").unwrap();
+    let plugins = wasm_plugin::load_plugins(&dir).unwrap();
+    let names: Vec<&str> = plugins.iter().map(|plugin| plugin.name()).collect();
+    assert_eq!(names, ["a_first", "b_second"]);
+
+    assert!(wasm_plugin::load_plugins(&dir.join("missing")).unwrap().is_empty());
+}
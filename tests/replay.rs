@@ -0,0 +1,29 @@
+use polymarket_explorer::analysis::tape::{self, NotableEntry};
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::standard_data::providers::{MarketMetadataProvider, TransactionProvider};
+use std::collections::HashSet;
+
+// the tape runs in time order with running buy totals, and each wallet is called out at most once, on its first buy
+#[tokio::test]
+async fn tape_calls_out_first_buys_in_time_order() {
+    let mock = MockSource::new();
+    let market = mock.get_market_group("mock-event").await.unwrap().markets.remove(0);
+    let mut transactions = mock.get_market_transactions(&market.condition_id).await.unwrap();
+    transactions.reverse();
+    let smart_wallet = transactions.iter().find(|tx| tx.action == "BUY").unwrap().trader_address.clone();
+    let smart = HashSet::from([smart_wallet.as_str()]);
+
+    let tape = tape::tape(&transactions, &smart, f64::INFINITY);
+    assert_eq!(tape.prints.len(), transactions.len());
+    assert!(tape.prints.windows(2).all(|w| w[0].transaction.timestamp <= w[1].transaction.timestamp));
+    assert!(tape.prints.windows(2).all(|w| w[0].yes_volume <= w[1].yes_volume && w[0].no_volume <= w[1].no_volume));
+
+    let bought: f64 = transactions.iter().filter(|tx| tx.action == "BUY").map(|tx| tx.usdc_amount).sum();
+    let last = tape.prints.last().unwrap();
+    assert!((last.yes_volume + last.no_volume - bought).abs() < 1e-6);
+
+    let called: Vec<_> = tape.prints.iter().filter(|p| p.notable.is_some()).collect();
+    assert_eq!(called.len(), 1);
+    assert_eq!(called[0].notable, Some(NotableEntry::Smart));
+    assert_eq!(called[0].transaction.trader_address, smart_wallet);
+}
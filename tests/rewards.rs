@@ -0,0 +1,41 @@
+use polymarket_explorer::analysis::{RewardSettings, SmartMoney};
+use polymarket_explorer::cli::handle_ingest_rewards;
+use polymarket_explorer::data_sources::{LocalDbSource, MockSource};
+use polymarket_explorer::standard_data::models::{RewardPayout, Trader};
+use polymarket_explorer::standard_data::providers::{DataStore, RewardProvider, TraderStatsProvider};
+use polymarket_explorer::testing::{self, FakeSource};
+
+// pulling twice stores every payout once, roi counts them only when the config says so and trading roi never does
+#[tokio::test]
+async fn ingested_rewards_reach_roi_only_when_included() {
+    let mock = MockSource::new();
+    let traders = mock.get_traders(0).await.unwrap();
+    let wallet = traders[0].trader_address.clone();
+    let payout = |timestamp: i64, usdc_amount: f64| RewardPayout {
+        trader_address: wallet.clone(),
+        market_id: None,
+        usdc_amount,
+        timestamp,
+        transaction_hash: format!("0x{:064x}", timestamp),
+    };
+    let remote = FakeSource::new().with_rewards(vec![payout(100, 25.0), payout(200, 15.0)]);
+
+    let dir = testing::scratch_dir("rewards-ingest").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &traders, &[], &[]).await.unwrap();
+    let wallets = std::slice::from_ref(&wallet);
+    handle_ingest_rewards(wallets, false, &SmartMoney::default(), &remote, &db, &db).await.unwrap();
+    handle_ingest_rewards(wallets, false, &SmartMoney::default(), &remote, &db, &db).await.unwrap();
+    assert_eq!(db.load_checkpoints().await.unwrap().rewards.get(&wallet), Some(&200));
+    assert_eq!(db.get_rewards(wallets, None).await.unwrap().len(), 2);
+
+    let find = |traders: Vec<Trader>| traders.into_iter().find(|trader| trader.trader_address == wallet).unwrap();
+    let excluded = find(db.get_traders(0).await.unwrap());
+    assert_eq!(excluded.rewards, Some(40.0));
+    assert!((excluded.roi - excluded.trading_roi()).abs() < 1e-9);
+
+    let included = LocalDbSource::new(&dir.to_string_lossy()).with_rewards(RewardSettings { include: true });
+    let included = find(included.get_traders(0).await.unwrap());
+    assert!((included.roi - included.roi_with_rewards()).abs() < 1e-9);
+    assert!(included.roi > included.trading_roi());
+    assert!((included.trading_roi() - excluded.trading_roi()).abs() < 1e-9);
+}
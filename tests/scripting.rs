@@ -0,0 +1,98 @@
+use polymarket_explorer::adapters::ScriptAnalyzer;
+use polymarket_explorer::analysis::order_flow::DEFAULT_OFI_WINDOW_HOURS;
+use polymarket_explorer::analysis::vwap::DEFAULT_VWAP_WINDOWS;
+use polymarket_explorer::analysis::{Analyzer, CostBasis};
+use polymarket_explorer::cli::{handle_analyze, AnalysisBus, AnalysisEvent, AnalysisSink};
+use polymarket_explorer::config::Config;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::standard_data::providers::{MarketMetadataProvider, PositionProvider, TransactionProvider};
+use polymarket_explorer::testing;
+use serde_json::{json, Value};
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct RecordingSink {
+    events: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait::async_trait]
+impl AnalysisSink for RecordingSink {
+    fn emit(&mut self, event: &AnalysisEvent<'_>) {
+        self.events.lock().unwrap().push(json!(event));
+    }
+}
+
+async fn analyze_with(analyzers: Vec<Box<dyn Analyzer>>) -> Vec<Value> {
+    let mock = MockSource::new();
+    let recorder = RecordingSink::default();
+    handle_analyze(
+        "fake-event",
+        false,
+        CostBasis::default(),
+        &DEFAULT_VWAP_WINDOWS,
+        DEFAULT_OFI_WINDOW_HOURS,
+        false,
+        false,
+        None,
+        None,
+        &mock.capabilities(),
+        None,
+        &Config::default(),
+        &mock,
+        &mock,
+        &mock,
+        &mock,
+        &analyzers,
+        AnalysisBus::new().subscribe(recorder.clone()),
+    )
+    .await
+    .unwrap();
+    recorder.events.lock().unwrap().clone()
+}
+
+// the script reads the market's data by field name and whatever map it ends on is its section
+#[tokio::test]
+async fn scripts_add_a_section_from_the_market_data() {
+    let dir = testing::scratch_dir("scripting").unwrap();
+    let path = dir.join("yes_holders.rhai");
+    fs::write(&path, r#"
+        let yes = positions.filter(|p| p.side == "YES");
+        #{ slug: market.slug, yes_holders: yes.len(), trades: transactions.len() }
+    "#).unwrap();
+    let events = analyze_with(vec![Box::new(ScriptAnalyzer::load(&path).unwrap())]).await;
+
+    let mock = MockSource::new();
+    let market = mock.get_market_group("fake-event").await.unwrap().markets.remove(0);
+    let positions = mock.get_positions(&market.condition_id).await.unwrap();
+    let trades = mock.get_market_transactions(&market.condition_id).await.unwrap();
+    let section = events.iter().find(|event| event["event"] == "analyzer").unwrap();
+    assert_eq!(section["name"], "yes_holders");
+    assert_eq!(section["output"], json!({
+        "slug": market.slug,
+        "yes_holders": positions.iter().filter(|p| p.side == "YES").count(),
+        "trades": trades.len(),
+    }));
+}
+
+// a script that throws or never stops only loses its own section
+#[tokio::test]
+async fn failing_scripts_leave_the_rest_of_the_report() {
+    let throws = ScriptAnalyzer::from_source("throws", r#"throw "no edge here""#).unwrap();
+    let spins = ScriptAnalyzer::from_source("spins", "loop { }").unwrap();
+    let events = analyze_with(vec![Box::new(throws), Box::new(spins)]).await;
+
+    let unavailable: Vec<&Value> = events.iter().filter(|event| event["event"] == "unavailable").collect();
+    assert_eq!(unavailable.len(), 2);
+    assert_eq!(unavailable[0]["section"], "throws");
+    assert!(unavailable[0]["reason"].as_str().unwrap().contains("no edge here"));
+    assert_eq!(unavailable[1]["section"], "spins");
+    assert!(events.iter().any(|event| event["event"] == "order_flow"));
+}
+
+// a typo is reported before anything is fetched
+#[test]
+fn scripts_that_dont_compile_are_refused() {
+    let error = ScriptAnalyzer::from_source("typo", "let x = ;").err().unwrap();
+    assert!(!error.to_string().is_empty());
+}
@@ -0,0 +1,42 @@
+use polymarket_explorer::analysis::{FeeModel, SmartMoney};
+use polymarket_explorer::cli::{output, server};
+use polymarket_explorer::data_sources::MockSource;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// clients that connect and never send a request line get a 408 once the read times out,
+// so a full set of them can't hold every slot and a real request behind them is still answered
+#[tokio::test]
+async fn idle_clients_time_out_instead_of_stalling_the_server() {
+    let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+    let mock = MockSource::new();
+    let smart_money = SmartMoney::default();
+    let fees = FeeModel::default();
+    let serving = server::serve(addr, output::REPORT_VERSION, &smart_money, &fees, &mock, &mock);
+
+    let clients = async {
+        // give the listener a moment to bind the port again
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut idle = Vec::new();
+        for _ in 0..16 {
+            idle.push(TcpStream::connect(addr).await.unwrap());
+        }
+        let mut real = TcpStream::connect(addr).await.unwrap();
+        real.write_all(b"GET /traders/0x0 HTTP/1.1\r\n\r\n").await.unwrap();
+
+        let mut reply = String::new();
+        let wait = server::REQUEST_READ_TIMEOUT * 2;
+        tokio::time::timeout(wait, real.read_to_string(&mut reply)).await.unwrap().unwrap();
+        assert!(reply.starts_with("HTTP/1.1 "), "{}", reply);
+
+        let mut timed_out = String::new();
+        idle[0].read_to_string(&mut timed_out).await.unwrap();
+        assert!(timed_out.starts_with("HTTP/1.1 408"), "{}", timed_out);
+    };
+
+    tokio::select! {
+        result = serving => panic!("server stopped: {:?}", result),
+        _ = clients => {}
+    }
+}
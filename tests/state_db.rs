@@ -0,0 +1,214 @@
+use polymarket_explorer::address_book::AddressBook;
+use polymarket_explorer::analysis::book_history::BookSnapshot;
+use polymarket_explorer::analysis::liquidity::LiquidityPoint;
+use polymarket_explorer::analysis::compare::MarketSummary;
+use polymarket_explorer::analysis::AlertTracker;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::paper::{PaperLedger, PaperSnapshot};
+use polymarket_explorer::standard_data::providers::{MarketMetadataProvider, OrderBookProvider};
+use polymarket_explorer::state_db::{LegacyFiles, StateDb, MAX_LIQUIDITY_POINTS};
+use polymarket_explorer::testing;
+use polymarket_explorer::watchlist::Watchlist;
+use std::fs;
+
+fn point(timestamp: i64, liquidity: f64) -> LiquidityPoint {
+    LiquidityPoint { timestamp, liquidity, spread: 0.02, yes_price: 0.5 }
+}
+
+fn summary(slug: &str, yes_price: f64) -> MarketSummary {
+    MarketSummary {
+        slug: slug.to_string(),
+        question: format!("{}?", slug),
+        yes_price,
+        spread: 0.01,
+        liquidity: 5_000.0,
+        smart_lean: Some(0.6),
+        whale_share: 0.3,
+        whale_count: 2,
+        volume_24h: 1_000.0,
+        volume_trend: None,
+    }
+}
+
+// the watchlist and address book round trip through the db, groups keep the order members were added in
+#[test]
+fn watchlist_and_labels_round_trip() {
+    let dir = testing::scratch_dir("state-db").unwrap();
+    let db = StateDb::at(dir.join("state.sqlite")).unwrap();
+    assert!(db.watchlist().unwrap().markets.is_empty());
+
+    let mut watchlist = Watchlist::default();
+    watchlist.markets.insert("mock-event".to_string());
+    watchlist.add_trader("0xABC");
+    db.save_watchlist(&watchlist).unwrap();
+    watchlist.remove_trader("0xabc");
+    watchlist.add_trader("0xdef");
+    db.save_watchlist(&watchlist).unwrap();
+    let stored = db.watchlist().unwrap();
+    assert_eq!(stored.markets.into_iter().collect::<Vec<_>>(), ["mock-event"]);
+    assert_eq!(stored.traders.into_iter().collect::<Vec<_>>(), ["0xdef"]);
+
+    let mut book = AddressBook::default();
+    book.set("0xAAA", "known sharp");
+    book.group("whale", &["0xccc".to_string(), "0xbbb".to_string()]);
+    db.save_address_book(&book).unwrap();
+    let stored = db.address_book().unwrap();
+    assert_eq!(stored.label("0xaaa"), Some("known sharp"));
+    assert_eq!(stored.members("whale"), ["0xccc", "0xbbb"]);
+    assert_eq!(stored.group_of("0xBBB"), Some("whale"));
+}
+
+// a new db takes in the json files and books dir it replaces once, later opens leave them alone
+// a half written last book line is a poll interrupted mid write and is skipped
+#[tokio::test]
+async fn legacy_files_are_imported_once() {
+    let dir = testing::scratch_dir("state-db-legacy").unwrap();
+    let legacy = LegacyFiles {
+        watchlist: dir.join("watchlist.json"),
+        labels: dir.join("labels.json"),
+        books: dir.join("books"),
+        liquidity: dir.join("liquidity.json"),
+        paper: dir.join("paper.json"),
+    };
+    fs::write(&legacy.watchlist, r#"{"markets": ["mock-event"], "traders": ["0xabc"]}"#).unwrap();
+    fs::write(&legacy.labels, r#"{"labels": {"0xabc": "insider-suspect"}, "groups": {"pair": ["0x1", "0x2"]}}"#).unwrap();
+
+    let mock = MockSource::new();
+    let market = mock.get_market_group("mock-event").await.unwrap().markets.remove(0);
+    let book = mock.get_order_book(&market.yes_token_id).await.unwrap();
+    fs::create_dir_all(&legacy.books).unwrap();
+    let lines: Vec<String> = [100, 160].iter().map(|t| serde_json::to_string(&BookSnapshot::new(*t, book.clone())).unwrap()).collect();
+    fs::write(legacy.books.join("mock-event.jsonl"), format!("{}\n{}\n{{\"timestamp\": 220, \"bo", lines[0], lines[1])).unwrap();
+
+    let path = dir.join("state.sqlite");
+    let db = StateDb::at_importing(&path, &legacy).unwrap();
+    assert_eq!(db.schema_version().unwrap(), 2);
+    assert!(db.watchlist().unwrap().markets.contains("mock-event"));
+    assert_eq!(db.address_book().unwrap().label("0xABC"), Some("insider-suspect"));
+    assert_eq!(db.address_book().unwrap().members("0x2"), ["0x1", "0x2"]);
+    let snapshots = db.book_snapshots("mock-event").unwrap();
+    assert_eq!(snapshots.iter().map(|s| s.timestamp).collect::<Vec<_>>(), [100, 160]);
+    drop(db);
+
+    fs::write(&legacy.watchlist, r#"{"markets": ["changed-later"]}"#).unwrap();
+    let db = StateDb::at_importing(&path, &legacy).unwrap();
+    assert!(db.watchlist().unwrap().markets.contains("mock-event"));
+    assert_eq!(db.book_snapshots("mock-event").unwrap().len(), 2);
+
+    // a broken legacy file leaves no half migrated db behind
+    fs::write(&legacy.labels, "{not json").unwrap();
+    let broken = dir.join("broken.sqlite");
+    assert!(StateDb::at_importing(&broken, &legacy).is_err());
+    fs::remove_file(&legacy.labels).unwrap();
+    let db = StateDb::at_importing(&broken, &legacy).unwrap();
+    assert!(db.watchlist().unwrap().markets.contains("changed-later"));
+}
+
+// a db made before liquidity and the paper account moved in takes in their files on its next open
+#[test]
+fn later_migrations_import_their_own_files() {
+    let dir = testing::scratch_dir("state-db-later").unwrap();
+    let legacy = LegacyFiles {
+        watchlist: dir.join("watchlist.json"),
+        labels: dir.join("labels.json"),
+        books: dir.join("books"),
+        liquidity: dir.join("liquidity.json"),
+        paper: dir.join("paper.json"),
+    };
+    let path = dir.join("state.sqlite");
+    drop(StateDb::at(&path).unwrap());
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    conn.execute_batch("DROP TABLE liquidity_points; DROP TABLE paper_account; DROP TABLE paper_fills; DROP TABLE paper_snapshots;").unwrap();
+    conn.pragma_update(None, "user_version", 1).unwrap();
+    drop(conn);
+
+    fs::write(&legacy.watchlist, r#"{"markets": ["not-imported-again"]}"#).unwrap();
+    fs::write(&legacy.liquidity, r#"{"markets": {"mock-event": [
+        {"timestamp": 100, "liquidity": 5000.0, "spread": 0.02, "yes_price": 0.5},
+        {"timestamp": 160, "liquidity": 3000.0, "spread": 0.04, "yes_price": 0.45}]}}"#).unwrap();
+    fs::write(&legacy.paper, r#"{"starting_cash": 250.0, "snapshots": [{"timestamp": 100, "cash": 250.0, "positions_value": 0.0}]}"#).unwrap();
+
+    let db = StateDb::at_importing(&path, &legacy).unwrap();
+    assert_eq!(db.schema_version().unwrap(), 2);
+    assert!(db.watchlist().unwrap().markets.is_empty());
+    let points = db.liquidity_points("mock-event").unwrap();
+    assert_eq!(points.iter().map(|p| p.liquidity).collect::<Vec<_>>(), [5000.0, 3000.0]);
+    let ledger = db.paper_ledger().unwrap();
+    assert_eq!(ledger.starting_cash, 250.0);
+    assert_eq!(ledger.snapshots.len(), 1);
+}
+
+// each market keeps its newest polls, oldest first, without touching the others
+#[test]
+fn liquidity_keeps_the_newest_points() {
+    let dir = testing::scratch_dir("state-db-liquidity").unwrap();
+    let db = StateDb::at(dir.join("state.sqlite")).unwrap();
+    assert!(db.liquidity_points("mock-event").unwrap().is_empty());
+
+    let polls: Vec<_> = (0..MAX_LIQUIDITY_POINTS as i64 + 2).map(|t| ("mock-event", point(t, t as f64))).collect();
+    db.record_liquidity(&polls).unwrap();
+    db.record_liquidity(&[("other-event", point(5, 1.0))]).unwrap();
+
+    let points = db.liquidity_points("mock-event").unwrap();
+    assert_eq!(points.len(), MAX_LIQUIDITY_POINTS);
+    assert_eq!(points.first().unwrap().timestamp, 2);
+    assert_eq!(points.last().unwrap().timestamp, MAX_LIQUIDITY_POINTS as i64 + 1);
+    assert_eq!(db.liquidity_points("other-event").unwrap().len(), 1);
+}
+
+// an account never saved is a fresh one, a saved one comes back with its snapshots in order
+#[test]
+fn paper_ledger_round_trips() {
+    let dir = testing::scratch_dir("state-db-paper").unwrap();
+    let db = StateDb::at(dir.join("state.sqlite")).unwrap();
+    assert_eq!(db.paper_ledger().unwrap().starting_cash, PaperLedger::default().starting_cash);
+
+    let mut ledger = PaperLedger::new(500.0);
+    ledger.snapshots.push(PaperSnapshot { timestamp: 100, cash: 500.0, positions_value: 0.0 });
+    ledger.snapshots.push(PaperSnapshot { timestamp: 200, cash: 450.0, positions_value: 60.0 });
+    db.save_paper_ledger(&ledger).unwrap();
+    let stored = db.paper_ledger().unwrap();
+    assert_eq!(stored.starting_cash, 500.0);
+    assert!(stored.fills.is_empty());
+    assert_eq!(stored.snapshots.iter().map(|s| s.timestamp).collect::<Vec<_>>(), [100, 200]);
+
+    // a reset replaces the account instead of adding to it
+    db.save_paper_ledger(&PaperLedger::new(1_000.0)).unwrap();
+    let stored = db.paper_ledger().unwrap();
+    assert_eq!(stored.starting_cash, 1_000.0);
+    assert!(stored.snapshots.is_empty());
+}
+
+// a db written by a newer build is refused instead of used with a schema this one doesn't know
+#[test]
+fn newer_schemas_are_refused() {
+    let dir = testing::scratch_dir("state-db-newer").unwrap();
+    let path = dir.join("state.sqlite");
+    drop(StateDb::at(&path).unwrap());
+    rusqlite::Connection::open(&path).unwrap().pragma_update(None, "user_version", 99).unwrap();
+    let error = StateDb::at(&path).err().unwrap();
+    assert_eq!(error.code(), "data.corrupt");
+}
+
+// a monitor restarted, or a second one, measures from the last saved poll, so a move is alerted once
+#[test]
+fn alert_baselines_keep_alerts_from_repeating() {
+    let dir = testing::scratch_dir("state-db-alerts").unwrap();
+    let db = StateDb::at(dir.join("state.sqlite")).unwrap();
+
+    let mut first = AlertTracker::new();
+    first.restore(db.alert_baselines().unwrap());
+    assert!(first.update(&[summary("mock-event", 0.40)]).is_empty());
+    db.save_alert_baselines(&[summary("mock-event", 0.40)]).unwrap();
+
+    let moved = [summary("mock-event", 0.50)];
+    first.restore(db.alert_baselines().unwrap());
+    assert_eq!(first.update(&moved).len(), 1);
+    db.save_alert_baselines(&moved).unwrap();
+
+    // a fresh tracker would only set its baseline, one restored from the db sees the move already alerted
+    let mut second = AlertTracker::new();
+    second.restore(db.alert_baselines().unwrap());
+    assert!(second.update(&moved).is_empty());
+    assert_eq!(db.alert_baselines().unwrap()["mock-event"].yes_price, 0.50);
+}
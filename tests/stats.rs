@@ -0,0 +1,75 @@
+use polymarket_explorer::analysis::CollateralRates;
+use polymarket_explorer::cli::handle_stats_rebuild;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::ingest;
+use polymarket_explorer::standard_data::models::Transaction;
+use polymarket_explorer::standard_data::providers::{DataStore, PositionProvider, ResolutionProvider, TraderStatsProvider, TransactionProvider};
+use polymarket_explorer::testing;
+
+// a rebuild after more trades landed only folds in those, and ends up where a full recompute does
+#[tokio::test]
+async fn incremental_rebuild_matches_a_full_recompute() {
+    let mock = MockSource::new();
+    let transactions = mock.get_all_transactions().await.unwrap();
+    let resolutions = mock.get_resolutions().await.unwrap();
+    let split = transactions[transactions.len() / 2].block_number;
+    let (early, late): (Vec<Transaction>, Vec<Transaction>) = transactions.iter().cloned().partition(|tx| tx.block_number <= split);
+    assert!(!late.is_empty());
+
+    let dir = testing::scratch_dir("stats-rebuild").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &[], &mock.get_all_positions().await.unwrap(), &early).await.unwrap();
+    db.save_resolutions(&resolutions).await.unwrap();
+
+    handle_stats_rebuild(false, &db.capabilities(), &CollateralRates::default(), &db, &db, &db, &db).await.unwrap();
+    assert_eq!(db.load_checkpoints().await.unwrap().stats.map(|stats| stats.block), Some(split));
+
+    db.append_transactions(&late).await.unwrap();
+    handle_stats_rebuild(false, &db.capabilities(), &CollateralRates::default(), &db, &db, &db, &db).await.unwrap();
+    let newest = late.iter().map(|tx| tx.block_number).max();
+    assert_eq!(db.load_checkpoints().await.unwrap().stats.map(|stats| stats.block), newest);
+
+    let rebuilt = db.get_traders(0).await.unwrap();
+    let expected = ingest::compute_trader_stats(&transactions, &resolutions);
+    assert_eq!(rebuilt.len(), expected.len());
+    for (got, want) in rebuilt.iter().zip(&expected) {
+        assert_eq!(got.trader_address, want.trader_address);
+        assert_eq!(got.total_markets_entered, want.total_markets_entered);
+        assert_eq!(got.total_markets_resolved, want.total_markets_resolved);
+        assert_eq!(got.total_wins, want.total_wins);
+        assert!((got.total_invested - want.total_invested).abs() < 1e-6, "{} invested", got.trader_address);
+        assert!((got.total_returned - want.total_returned).abs() < 1e-6, "{} returned", got.trader_address);
+    }
+}
+
+// every rebuild keeps its stats as a snapshot at the newest block it had seen, calibration reads them back in order
+#[tokio::test]
+async fn rebuilds_store_a_snapshot_of_the_stats_they_wrote() {
+    let mock = MockSource::new();
+    let transactions = mock.get_all_transactions().await.unwrap();
+    let resolutions = mock.get_resolutions().await.unwrap();
+    let split = transactions[transactions.len() / 2].block_number;
+    let (early, late): (Vec<Transaction>, Vec<Transaction>) = transactions.iter().cloned().partition(|tx| tx.block_number <= split);
+    let known: Vec<_> = resolutions.iter().filter(|r| r.resolution_block <= split).cloned().collect();
+
+    let dir = testing::scratch_dir("stats-snapshots").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &[], &mock.get_all_positions().await.unwrap(), &early).await.unwrap();
+    db.save_resolutions(&known).await.unwrap();
+    handle_stats_rebuild(false, &db.capabilities(), &CollateralRates::default(), &db, &db, &db, &db).await.unwrap();
+    let first = db.get_traders(0).await.unwrap();
+
+    db.append_transactions(&late).await.unwrap();
+    db.save_resolutions(&resolutions).await.unwrap();
+    handle_stats_rebuild(false, &db.capabilities(), &CollateralRates::default(), &db, &db, &db, &db).await.unwrap();
+    let second = db.get_traders(0).await.unwrap();
+
+    let snapshots = db.get_trader_snapshots().await.unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[0].block, split);
+    let newest = transactions.iter().map(|tx| tx.block_number).chain(resolutions.iter().map(|r| r.resolution_block)).max();
+    assert_eq!(Some(snapshots[1].block), newest);
+    for (snapshot, written) in snapshots.iter().zip([&first, &second]) {
+        let stored: Vec<(&str, u32)> = snapshot.traders.iter().map(|t| (t.trader_address.as_str(), t.total_wins)).collect();
+        let expected: Vec<(&str, u32)> = written.iter().map(|t| (t.trader_address.as_str(), t.total_wins)).collect();
+        assert_eq!(stored, expected);
+    }
+}
@@ -0,0 +1,48 @@
+use polymarket_explorer::analysis::big_trades::PositionChange;
+use polymarket_explorer::analysis::trader_watch::TraderWatch;
+use polymarket_explorer::data_sources::MockSource;
+use polymarket_explorer::standard_data::models::Transaction;
+use polymarket_explorer::standard_data::providers::TransactionProvider;
+
+// the history before the first poll is only learned from, later polls report opens and closes once each
+// even when the inclusive cutoff hands the same fills back again
+#[tokio::test]
+async fn watched_wallets_report_opens_and_closes_once() {
+    let mock = MockSource::new();
+    let all = mock.get_all_transactions().await.unwrap();
+    let wallet = all[0].trader_address.clone();
+    let history = mock.get_wallet_transactions(std::slice::from_ref(&wallet), 0).await.unwrap();
+    assert!(!history.is_empty());
+    assert!(history.iter().all(|tx| tx.trader_address == wallet));
+
+    let start = history.iter().filter_map(|tx| tx.timestamp).max().unwrap();
+    let mut watch = TraderWatch::new(0);
+    watch.observe(&history);
+    assert_eq!(watch.cursor(), start);
+
+    // a token the wallet never touched, bought into twice then sold out of
+    let fill = |hash: &str, action: &str, shares: f64, at: i64| Transaction {
+        transaction_hash: hash.to_string(),
+        log_index: None,
+        token_id: "watched-token".to_string(),
+        action: action.to_string(),
+        shares,
+        usdc_amount: shares * 0.5,
+        timestamp: Some(at),
+        ..history[0].clone()
+    };
+    let first = vec![fill("0x1", "BUY", 10.0, start + 60), fill("0x2", "BUY", 5.0, start + 120)];
+    let activity = watch.observe(&first);
+    assert_eq!(activity.len(), 1);
+    assert_eq!(activity[0].change, PositionChange::Open);
+    assert_eq!(activity[0].transaction.transaction_hash, "0x1");
+
+    let mut second = first.clone();
+    second.push(fill("0x3", "SELL", 15.0, start + 180));
+    let activity = watch.observe(&second);
+    assert_eq!(activity.len(), 1);
+    assert_eq!(activity[0].change, PositionChange::Close);
+    assert_eq!(activity[0].shares_after, 0.0);
+    assert_eq!(watch.cursor(), start + 180);
+    assert!(watch.observe(&second).is_empty());
+}
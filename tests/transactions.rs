@@ -0,0 +1,96 @@
+use polars::prelude::*;
+use polymarket_explorer::adapters::{ParquetReader, ParquetWriter};
+use polymarket_explorer::data_sources::{LocalDbSource, MockSource};
+use polymarket_explorer::standard_data::models::Transaction;
+use polymarket_explorer::standard_data::providers::{DataStore, PositionProvider, TransactionProvider};
+use polymarket_explorer::testing;
+
+fn fill(base: &Transaction, block_number: u64, log_index: Option<u32>, timestamp: Option<i64>, shares: f64) -> Transaction {
+    Transaction {
+        block_number,
+        transaction_hash: format!("0x{:064x}", block_number),
+        log_index,
+        timestamp,
+        shares,
+        usdc_amount: shares / 2.0,
+        ..base.clone()
+    }
+}
+
+// two fills of one wallet in one transaction without a log index are both kept, even of the same size,
+// appending them again adds nothing and a third one that shows up later is added on its own
+#[tokio::test]
+async fn fills_without_a_log_index_are_counted_apart() {
+    let mock = MockSource::new();
+    let base = mock.get_all_transactions().await.unwrap().remove(0);
+    let dir = testing::scratch_dir("transactions-fills").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &[], &mock.get_all_positions().await.unwrap(), &[]).await.unwrap();
+
+    let fills = [fill(&base, 7, None, Some(1_700_000_000), 10.0), fill(&base, 7, None, Some(1_700_000_000), 10.0)];
+    assert_eq!(db.append_transactions(&fills).await.unwrap().len(), 2);
+    assert!(db.append_transactions(&fills).await.unwrap().is_empty());
+    let more = [fills[0].clone(), fills[1].clone(), fill(&base, 7, None, Some(1_700_000_000), 25.0)];
+    assert_eq!(db.append_transactions(&more).await.unwrap().len(), 1);
+
+    let stored = db.get_market_transactions(&base.market_id).await.unwrap();
+    let mut shares: Vec<f64> = stored.iter().map(|tx| tx.shares).collect();
+    shares.sort_by(f64::total_cmp);
+    assert_eq!(shares, [10.0, 10.0, 25.0]);
+}
+
+// rows in the same block come back by timestamp and then log index, whatever order they were written in
+#[tokio::test]
+async fn transactions_in_one_block_replay_in_log_order() {
+    let mock = MockSource::new();
+    let base = mock.get_all_transactions().await.unwrap().remove(0);
+    let written = [
+        fill(&base, 9, Some(4), Some(1_700_000_100), 1.0),
+        fill(&base, 9, Some(2), Some(1_700_000_100), 2.0),
+        fill(&base, 8, Some(7), Some(1_700_000_050), 3.0),
+        fill(&base, 9, Some(3), Some(1_700_000_100), 4.0),
+    ];
+    let dir = testing::scratch_dir("transactions-order").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &[], &mock.get_all_positions().await.unwrap(), &written).await.unwrap();
+
+    let replayed: Vec<(u64, Option<u32>)> = db.get_all_transactions().await.unwrap()
+        .iter()
+        .map(|tx| (tx.block_number, tx.log_index))
+        .collect();
+    assert_eq!(replayed, [(8, Some(7)), (9, Some(2)), (9, Some(3)), (9, Some(4))]);
+}
+
+// data api rows have no block, without a node to place them they aren't stored at block 0
+#[tokio::test]
+async fn api_rows_need_a_node_to_be_stored() {
+    let mock = MockSource::new();
+    let base = mock.get_all_transactions().await.unwrap().remove(0);
+    let dir = testing::scratch_dir("transactions-unplaced").unwrap();
+    let db = testing::write_parquet_fixtures(&dir, &[], &mock.get_all_positions().await.unwrap(), &[]).await.unwrap();
+
+    let error = db.append_transactions(&[fill(&base, 0, None, Some(1_700_000_000), 5.0)]).await.err().unwrap();
+    assert!(error.to_string().contains("--polygon-rpc"), "{}", error);
+}
+
+// appending to a single file dump rewrites it, columns the new rows don't have are kept and null for them
+#[tokio::test]
+async fn appends_keep_columns_the_new_rows_lack() {
+    let mock = MockSource::new();
+    let transactions = mock.get_all_transactions().await.unwrap();
+    let dir = testing::scratch_dir("transactions-extra-columns").unwrap();
+    let partitioned = dir.join("partitioned");
+    testing::write_parquet_fixtures(&partitioned, &[], &[], &transactions).await.unwrap();
+    let mut df = ParquetReader::new(&partitioned.to_string_lossy()).read("transactions.parquet").unwrap();
+    df.with_column(Column::new("venue".into(), vec!["clob"; df.height()])).unwrap();
+    let single = dir.join("single");
+    ParquetWriter::new(&single.to_string_lossy()).write("transactions.parquet", &mut df).unwrap();
+
+    let db = LocalDbSource::new(&single.to_string_lossy());
+    let added = db.append_transactions(&[fill(&transactions[0], 99_999, Some(0), Some(1_700_000_000), 3.0)]).await.unwrap();
+    assert_eq!(added.len(), 1);
+
+    let stored = ParquetReader::new(&single.to_string_lossy()).read("transactions.parquet").unwrap();
+    assert_eq!(stored.height(), transactions.len() + 1);
+    let venue = stored.column("venue").unwrap();
+    assert_eq!(venue.null_count(), 1);
+    assert_eq!(venue.str().unwrap().into_iter().flatten().filter(|v| *v == "clob").count(), transactions.len());
+}
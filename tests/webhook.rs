@@ -0,0 +1,79 @@
+use hmac::{Hmac, Mac};
+use polymarket_explorer::adapters::webhook::{self, DEFAULT_WEBHOOK_RETRIES, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use polymarket_explorer::adapters::{HttpClient, WebhookTarget};
+use polymarket_explorer::config::Config;
+use polymarket_explorer::testing::{CannedResponse, TestServer};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+// a webhook receiver answering with each status in turn, the last one repeating
+async fn receiver(statuses: &[u16]) -> (TestServer, String) {
+    let server = TestServer::start(statuses.iter().map(|status| CannedResponse::status(*status)).collect()).await.unwrap();
+    let url = format!("{}/hook", server.url());
+    (server, url)
+}
+
+// a server error is retried, and each try carries a signature over its own timestamp and the exact body bytes
+#[tokio::test]
+async fn deliveries_are_retried_and_signed() {
+    let (server, url) = receiver(&[503, 200]).await;
+    let target = WebhookTarget::new(&url).with_secret(Some("shh".to_string())).with_retries(2);
+    let report = json!({"slug": "mock-event", "events": [{"event": "started"}]});
+    let client = HttpClient::new();
+    webhook::deliver(&client, &target, &report).await.unwrap();
+    assert_eq!(client.stats().snapshot().retries, 1);
+
+    let received = server.requests();
+    assert_eq!(received.len(), 2);
+    for request in &received {
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/hook"));
+        let body = request.body_text();
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), report);
+        assert_eq!(request.header("content-type"), Some("application/json"));
+
+        let timestamp = request.header(TIMESTAMP_HEADER).unwrap();
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"shh").unwrap();
+        mac.update(format!("{}.{}", timestamp, body).as_bytes());
+        let expected: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(request.header(SIGNATURE_HEADER), Some(format!("sha256={}", expected).as_str()));
+        assert_eq!(webhook::sign("shh", timestamp.parse().unwrap(), &body), format!("sha256={}", expected));
+    }
+}
+
+// a receiver turning the body down isn't asked again, and without a secret nothing is signed
+#[tokio::test]
+async fn rejected_deliveries_are_not_retried() {
+    let (server, url) = receiver(&[400]).await;
+    assert!(webhook::deliver(&HttpClient::new(), &WebhookTarget::new(&url), &json!({})).await.is_err());
+    let received = server.requests();
+    assert_eq!(received.len(), 1);
+    assert!(received[0].header(TIMESTAMP_HEADER).is_some());
+    assert!(received[0].header(SIGNATURE_HEADER).is_none());
+
+    let (server, url) = receiver(&[500]).await;
+    assert!(webhook::deliver(&HttpClient::new(), &WebhookTarget::new(&url).with_retries(0), &json!({})).await.is_err());
+    assert_eq!(server.request_count(), 1);
+}
+
+// [[webhooks]] entries of config.toml, retries default when left out
+#[test]
+fn webhooks_are_read_from_config() {
+    let config: Config = toml::from_str(
+        r#"
+        [[webhooks]]
+        url = "https://example.com/a"
+        secret = "shh"
+
+        [[webhooks]]
+        url = "https://example.com/b"
+        retries = 0
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.webhooks, vec![
+        WebhookTarget::new("https://example.com/a").with_secret(Some("shh".to_string())),
+        WebhookTarget::new("https://example.com/b").with_retries(0),
+    ]);
+    assert_eq!(config.webhooks[0].retries, DEFAULT_WEBHOOK_RETRIES);
+    assert!(toml::from_str::<Config>("[[webhooks]]\nurl = \"x\"\nsecrt = \"typo\"\n").is_err());
+}